@@ -0,0 +1,152 @@
+//! Compares query throughput over components kept together with `#[component_group]` against
+//! the same components left in the order they were added, while objects are spawned and removed
+//! between passes
+//! Run with `cargo bench --bench component_groups`
+
+use std::{hint::black_box, time::Instant};
+
+use apostasy_core::{
+    anyhow,
+    objects::{Object, scene::ObjectId, world::World},
+    serde_yaml,
+};
+use apostasy_macros::Component;
+
+const OBJECTS: usize = 50_000;
+const ROUNDS: usize = 20;
+const PASSES: usize = 10;
+/// how many objects are removed and spawned again each round
+const CHURN: usize = OBJECTS / 10;
+
+/// Declares a component that isn't saved, optionally in a group
+macro_rules! component {
+    ($name:ident($data:ty) $(, $group:literal)?) => {
+        #[derive(Component, Clone, Debug, Default)]
+        $(#[component_group($group)])?
+        #[allow(dead_code)]
+        struct $name($data);
+
+        impl $name {
+            fn deserialize(&mut self, _value: &serde_yaml::Value) -> anyhow::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+component!(GroupedPosition([f32; 3]), "motion");
+component!(GroupedSpeed([f32; 3]), "motion");
+component!(Position([f32; 3]));
+component!(Speed([f32; 3]));
+
+// the components a real object carries that the benchmark's query doesn't want
+component!(Health(u64));
+component!(Name(u64));
+component!(Inventory(u64));
+component!(Team(u64));
+component!(Sound(u64));
+component!(Light(u64));
+component!(Script(u64));
+component!(Network(u64));
+
+/// Adds the padding before the queried components, the order objects are usually built in
+fn add_padding(object: &mut Object) {
+    object.add_component(Health::default());
+    object.add_component(Name::default());
+    object.add_component(Inventory::default());
+    object.add_component(Team::default());
+    object.add_component(Sound::default());
+    object.add_component(Light::default());
+    object.add_component(Script::default());
+    object.add_component(Network::default());
+}
+
+/// A small generator so every run churns the same objects
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, below: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % below
+    }
+}
+
+/// Spawns an object with the padding first, so without groups the queried components are last
+fn spawn(world: &mut World, grouped: bool) -> ObjectId {
+    let mut object = Object::new();
+    add_padding(&mut object);
+    if grouped {
+        object.add_component(GroupedPosition::default());
+        object.add_component(GroupedSpeed([1.0, 0.5, 0.25]));
+    } else {
+        object.add_component(Position::default());
+        object.add_component(Speed([1.0, 0.5, 0.25]));
+    }
+    world.add_object(object)
+}
+
+/// Moves every object, returns how many were visited
+fn pass(world: &mut World, grouped: bool) -> usize {
+    let mut visited = 0;
+    if grouped {
+        for (position, speed) in world.query::<(&mut GroupedPosition, &GroupedSpeed)>() {
+            for axis in 0..3 {
+                position.0[axis] += speed.0[axis];
+            }
+            visited += 1;
+        }
+    } else {
+        for (position, speed) in world.query::<(&mut Position, &Speed)>() {
+            for axis in 0..3 {
+                position.0[axis] += speed.0[axis];
+            }
+            visited += 1;
+        }
+    }
+    visited
+}
+
+/// Churns and iterates a world, returns the nanoseconds spent per visited object
+fn run(grouped: bool) -> f64 {
+    let mut world = World::default();
+    let mut ids: Vec<ObjectId> = (0..OBJECTS).map(|_| spawn(&mut world, grouped)).collect();
+    let mut rng = Lcg(7);
+
+    let mut visited = 0;
+    let mut elapsed = 0.0;
+    for _ in 0..ROUNDS {
+        for _ in 0..CHURN {
+            let index = rng.next(ids.len());
+            world.remove_object(ids.swap_remove(index));
+        }
+        for _ in 0..CHURN {
+            ids.push(spawn(&mut world, grouped));
+        }
+
+        let start = Instant::now();
+        for _ in 0..PASSES {
+            visited += black_box(pass(&mut world, grouped));
+        }
+        elapsed += start.elapsed().as_secs_f64();
+    }
+    elapsed * 1e9 / visited as f64
+}
+
+fn main() {
+    // warm up both paths before measuring
+    run(false);
+    run(true);
+
+    let ungrouped = run(false);
+    let grouped = run(true);
+    println!(
+        "{} objects, {} of them respawned per round, {} rounds of {} passes",
+        OBJECTS, CHURN, ROUNDS, PASSES
+    );
+    println!("ungrouped: {:.2} ns per object", ungrouped);
+    println!("grouped:   {:.2} ns per object", grouped);
+    println!("speedup:   {:.2}x", ungrouped / grouped);
+}
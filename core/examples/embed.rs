@@ -0,0 +1,60 @@
+//! Runs the engine inside a host application's own winit event loop
+//! The host keeps its own state and decides when frames are drawn, the engine only needs its
+//! events forwarded
+
+use apostasy_core::{
+    Engine,
+    packages::Packages,
+    rendering::RenderingBackend,
+    winit::{
+        application::ApplicationHandler,
+        event::{DeviceEvent, DeviceId, WindowEvent},
+        event_loop::{ActiveEventLoop, EventLoop},
+        window::WindowId,
+    },
+};
+
+struct Host {
+    engine: Engine,
+    frames: u64,
+}
+
+impl ApplicationHandler for Host {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        self.engine.resume(event_loop);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
+        if event == WindowEvent::RedrawRequested {
+            self.frames += 1;
+        }
+        // the engine draws a frame on RedrawRequested and shuts down and exits on CloseRequested
+        self.engine.window_event(event_loop, id, event);
+    }
+
+    fn device_event(&mut self, event_loop: &ActiveEventLoop, id: DeviceId, event: DeviceEvent) {
+        self.engine.device_event(event_loop, id, event);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.engine.should_exit() {
+            // runs the #[shutdown] systems so they can save before the loop ends
+            self.engine.shutdown();
+            event_loop.exit();
+            return;
+        }
+        self.engine.request_redraw();
+    }
+}
+
+fn main() -> apostasy_core::anyhow::Result<()> {
+    let mut host = Host {
+        engine: Engine::new(RenderingBackend::Vulkan, vec![Packages::Voxel]),
+        frames: 0,
+    };
+
+    let event_loop = EventLoop::new()?;
+    event_loop.run_app(&mut host)?;
+    println!("The engine drew {} frames", host.frames);
+    Ok(())
+}
@@ -0,0 +1,18 @@
+#version 450
+layout(location = 0) in vec2 fragUV;
+layout(location = 0) out vec4 outColor;
+
+layout(set = 0, binding = 0) uniform sampler2D aoTexture;
+
+void main() {
+  // a small blur hides the noise of the random samples
+  vec2 texel = 1.0 / vec2(textureSize(aoTexture, 0));
+  float ao = 0.0;
+  for (int x = -2; x < 2; x++) {
+    for (int y = -2; y < 2; y++) {
+      ao += texture(aoTexture, fragUV + (vec2(x, y) + 0.5) * texel).r;
+    }
+  }
+  // multiplied over the frame by the blend state
+  outColor = vec4(vec3(ao / 16.0), 1.0);
+}
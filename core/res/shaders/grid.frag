@@ -0,0 +1,55 @@
+#version 450
+layout(location = 0) in vec3 fragWorld;
+layout(location = 0) out vec4 outColor;
+
+layout(push_constant) uniform Push {
+  mat4 proj_view;
+  mat4 model;
+  vec3 camera_pos;
+  float cell_size;
+  float height;
+  float fade_distance;
+  float major_every;
+  float snap_size;
+  vec4 color;
+} pc;
+
+// 1 on a line every `size` units, antialiased to a pixel wide
+float grid_line(vec2 coord, float size) {
+  vec2 scaled = coord / size;
+  vec2 width = fwidth(scaled);
+  vec2 distance = abs(fract(scaled - 0.5) - 0.5) / width;
+  return 1.0 - min(min(distance.x, distance.y), 1.0);
+}
+
+// 1 on the line where `value` is 0
+float axis_line(float value) {
+  return 1.0 - min(abs(value) / fwidth(value), 1.0);
+}
+
+void main() {
+  vec2 coord = fragWorld.xz;
+  float minor = grid_line(coord, pc.cell_size);
+  float major = grid_line(coord, pc.cell_size * pc.major_every);
+  float snap = pc.snap_size > 0.0 ? grid_line(coord, pc.snap_size) : 0.0;
+
+  float alpha = max(max(minor * 0.4, major), snap * 0.2) * pc.color.a;
+  vec3 color = pc.color.rgb;
+
+  // the x axis runs along z = 0 and the z axis along x = 0
+  float x_axis = axis_line(fragWorld.z);
+  float z_axis = axis_line(fragWorld.x);
+  if (x_axis > 0.0) {
+    color = mix(color, vec3(0.9, 0.2, 0.2), x_axis);
+    alpha = max(alpha, x_axis);
+  }
+  if (z_axis > 0.0) {
+    color = mix(color, vec3(0.2, 0.4, 0.9), z_axis);
+    alpha = max(alpha, z_axis);
+  }
+
+  float distance = length(fragWorld - pc.camera_pos);
+  alpha *= 1.0 - smoothstep(pc.fade_distance * 0.5, pc.fade_distance, distance);
+  if (alpha <= 0.001) discard;
+  outColor = vec4(color, alpha);
+}
@@ -0,0 +1,29 @@
+#version 450
+layout(location = 0) out vec3 fragWorld;
+
+layout(push_constant) uniform Push {
+  mat4 proj_view;
+  mat4 model;
+  vec3 camera_pos;
+  float cell_size;
+  float height;
+  float fade_distance;
+  float major_every;
+  float snap_size;
+  vec4 color;
+} pc;
+
+void main() {
+  // one quad on the grid plane, centred under the camera and as wide as the fade
+  uint corner = uint(gl_VertexIndex);
+  float u = (corner == 1u || corner == 2u || corner == 4u) ? 1.0 : -1.0;
+  float v = (corner == 2u || corner == 4u || corner == 5u) ? 1.0 : -1.0;
+  vec3 world = vec3(
+    pc.camera_pos.x + u * pc.fade_distance,
+    pc.height,
+    pc.camera_pos.z + v * pc.fade_distance
+  );
+
+  fragWorld = world;
+  gl_Position = pc.proj_view * vec4(world, 1.0);
+}
@@ -0,0 +1,16 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 mvp;
+    mat4 model;
+    vec3 pos;
+    vec3 scale;
+    vec4 rotation;
+    uint id;
+} pc;
+
+layout(location = 0) out uint outId;
+
+void main() {
+    outId = pc.id;
+}
@@ -0,0 +1,36 @@
+#version 450
+layout(location = 0) in vec2 fragUV;
+layout(location = 0) out vec4 outColor;
+
+// the ids of the outlined meshes, 0 where none was drawn
+layout(set = 0, binding = 0) uniform usampler2D mask;
+
+layout(push_constant) uniform PushConstants {
+  vec4 color;
+  float thickness;
+} pc;
+
+void main() {
+  ivec2 size = textureSize(mask, 0);
+  ivec2 pixel = ivec2(fragUV * vec2(size));
+  if (texelFetch(mask, pixel, 0).r != 0u) {
+    discard;
+  }
+
+  // an edge is any empty pixel within the thickness of a covered one
+  int radius = int(ceil(pc.thickness));
+  float limit = pc.thickness * pc.thickness;
+  for (int x = -radius; x <= radius; x++) {
+    for (int y = -radius; y <= radius; y++) {
+      if (float(x * x + y * y) > limit) {
+        continue;
+      }
+      ivec2 neighbour = clamp(pixel + ivec2(x, y), ivec2(0), size - 1);
+      if (texelFetch(mask, neighbour, 0).r != 0u) {
+        outColor = pc.color;
+        return;
+      }
+    }
+  }
+  discard;
+}
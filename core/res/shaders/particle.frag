@@ -0,0 +1,23 @@
+#version 450
+layout(location = 0) in vec2 fragUV;
+layout(location = 0) out vec4 outColor;
+
+layout(push_constant) uniform Push {
+  mat4 proj_view;
+  mat4 model;
+  vec3 camera_pos;
+  float time;
+  vec3 area;
+  float size;
+  vec3 velocity;
+  float streak;
+  vec4 color;
+} pc;
+
+void main() {
+  // soft edges so particles don't look like flat quads
+  vec2 centered = fragUV * 2.0 - 1.0;
+  float falloff = 1.0 - clamp(dot(centered, centered), 0.0, 1.0);
+  if (falloff <= 0.0) discard;
+  outColor = vec4(pc.color.rgb, pc.color.a * falloff);
+}
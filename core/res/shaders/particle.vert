@@ -0,0 +1,44 @@
+#version 450
+layout(location = 0) out vec2 fragUV;
+
+layout(push_constant) uniform Push {
+  mat4 proj_view;
+  mat4 model;
+  vec3 camera_pos;
+  float time;
+  vec3 area;
+  float size;
+  vec3 velocity;
+  float streak;
+  vec4 color;
+} pc;
+
+float hash(uint n) {
+  n = (n << 13u) ^ n;
+  n = n * (n * n * 15731u + 789221u) + 1376312589u;
+  return float(n & 0x7fffffffu) / 2147483647.0;
+}
+
+void main() {
+  uint id = uint(gl_InstanceIndex);
+  uint corner = uint(gl_VertexIndex);
+
+  // every particle starts at a random point and wraps around the box centred on the camera
+  vec3 seed = vec3(hash(id * 3u), hash(id * 3u + 1u), hash(id * 3u + 2u)) * pc.area;
+  vec3 box_min = pc.camera_pos - pc.area * 0.5;
+  vec3 moved = seed + pc.velocity * pc.time - box_min;
+  vec3 position = moved - pc.area * floor(moved / pc.area) + box_min;
+
+  // rain is stretched along its velocity, snow faces the camera
+  vec3 along = pc.velocity * pc.streak;
+  vec3 axis = length(along) > 0.0001 ? along : vec3(0.0, pc.size, 0.0);
+  vec3 to_camera = normalize(pc.camera_pos - position);
+  vec3 side = normalize(cross(axis, to_camera)) * pc.size * 0.5;
+
+  float u = (corner == 1u || corner == 2u || corner == 4u) ? 1.0 : 0.0;
+  float v = (corner == 2u || corner == 4u || corner == 5u) ? 1.0 : 0.0;
+  vec3 world = position + side * (u * 2.0 - 1.0) + axis * (v - 0.5);
+
+  fragUV = vec2(u, v);
+  gl_Position = pc.proj_view * vec4(world, 1.0);
+}
@@ -1,13 +1,113 @@
 #version 450
 
+// glTF metallic-roughness shading with a single directional light
+
 layout(location = 0) in vec3 fragNormal;
 layout(location = 1) in vec2 fragTexCoord;
+layout(location = 2) in vec3 fragWorldPos;
+layout(location = 3) in vec4 fragTangent;
+// baked by World::bake_ambient_occlusion, 1 for models that were not baked
+layout(location = 4) in float fragAo;
+
+layout(set = 0, binding = 0) uniform sampler2D baseColorMap;
+layout(set = 0, binding = 1) uniform sampler2D normalMap;
+layout(set = 0, binding = 2) uniform sampler2D metallicRoughnessMap;
+layout(set = 0, binding = 3) uniform sampler2D emissiveMap;
+layout(set = 0, binding = 4) uniform sampler2D occlusionMap;
+
+// the material follows the 176 bytes of model push constants
+layout(push_constant) uniform PushConstants {
+    layout(offset = 176) vec4 baseColorFactor;
+    vec3 emissiveFactor;
+    float metallicFactor;
+    float roughnessFactor;
+    float normalScale;
+    float occlusionStrength;
+    vec3 cameraPosition;
+} pc;
 
 layout(location = 0) out vec4 outColor;
 
+const float PI = 3.14159265359;
+const vec3 LIGHT_DIR = normalize(vec3(1.0, 1.0, 1.0));
+// pi * 0.7 keeps the lighting of untextured models as it was before pbr
+const vec3 LIGHT_COLOR = vec3(PI * 0.7);
+const vec3 AMBIENT_COLOR = vec3(0.3);
+
+// builds the tangent frame from the vertex tangent, or from screen space derivatives for
+// vertices without one
+vec3 perturbNormal(vec3 normal) {
+    vec3 mapped = texture(normalMap, fragTexCoord).xyz * 2.0 - 1.0;
+    mapped.xy *= pc.normalScale;
+
+    if (dot(fragTangent.xyz, fragTangent.xyz) > 1e-6) {
+        // interpolation leaves the tangent slightly off perpendicular
+        vec3 tangent = normalize(fragTangent.xyz - normal * dot(normal, fragTangent.xyz));
+        vec3 bitangent = cross(normal, tangent) * (fragTangent.w < 0.0 ? -1.0 : 1.0);
+        return normalize(mat3(tangent, bitangent, normal) * mapped);
+    }
+
+    vec3 dp1 = dFdx(fragWorldPos);
+    vec3 dp2 = dFdy(fragWorldPos);
+    vec2 duv1 = dFdx(fragTexCoord);
+    vec2 duv2 = dFdy(fragTexCoord);
+
+    vec3 dp2perp = cross(dp2, normal);
+    vec3 dp1perp = cross(normal, dp1);
+    vec3 tangent = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 bitangent = dp2perp * duv1.y + dp1perp * duv2.y;
+
+    float len = max(dot(tangent, tangent), dot(bitangent, bitangent));
+    if (len < 1e-12) {
+        return normal;
+    }
+    float scale = inversesqrt(len);
+    return normalize(mat3(tangent * scale, bitangent * scale, normal) * mapped);
+}
+
+float distributionGGX(float nDotH, float roughness) {
+    float a2 = roughness * roughness * roughness * roughness;
+    float denom = nDotH * nDotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denom * denom);
+}
+
+float geometrySmith(float nDotV, float nDotL, float roughness) {
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    float gv = nDotV / (nDotV * (1.0 - k) + k);
+    float gl = nDotL / (nDotL * (1.0 - k) + k);
+    return gv * gl;
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
 void main() {
-    vec3 lightDir = normalize(vec3(1.0, 1.0, 1.0));
-    float diff = max(dot(normalize(fragNormal), lightDir), 0.0);
-    vec3 color = vec3(0.8, 0.8, 0.8) * (0.3 + 0.7 * diff);
-    outColor = vec4(color, 1.0);
+    vec4 baseColor = texture(baseColorMap, fragTexCoord) * pc.baseColorFactor;
+    vec4 metallicRoughness = texture(metallicRoughnessMap, fragTexCoord);
+    float metallic = clamp(metallicRoughness.b * pc.metallicFactor, 0.0, 1.0);
+    float roughness = clamp(metallicRoughness.g * pc.roughnessFactor, 0.04, 1.0);
+    float occlusion = mix(1.0, texture(occlusionMap, fragTexCoord).r, pc.occlusionStrength);
+    vec3 emissive = texture(emissiveMap, fragTexCoord).rgb * pc.emissiveFactor;
+
+    vec3 n = perturbNormal(normalize(fragNormal));
+    vec3 v = normalize(pc.cameraPosition - fragWorldPos);
+    vec3 h = normalize(v + LIGHT_DIR);
+    float nDotL = max(dot(n, LIGHT_DIR), 0.0);
+    float nDotV = max(dot(n, v), 1e-4);
+    float nDotH = max(dot(n, h), 0.0);
+
+    vec3 f0 = mix(vec3(0.04), baseColor.rgb, metallic);
+    vec3 f = fresnelSchlick(max(dot(h, v), 0.0), f0);
+    float d = distributionGGX(nDotH, roughness);
+    float g = geometrySmith(nDotV, nDotL, roughness);
+
+    vec3 specular = d * g * f / (4.0 * nDotV * max(nDotL, 1e-4));
+    vec3 diffuse = (1.0 - f) * (1.0 - metallic) * baseColor.rgb / PI;
+    vec3 direct = (diffuse + specular) * LIGHT_COLOR * nDotL;
+    vec3 ambient = AMBIENT_COLOR * baseColor.rgb * occlusion * fragAo;
+
+    // linear, the swapchain converts to srgb
+    vec3 color = direct + ambient + emissive;
+    outColor = vec4(color, baseColor.a);
 }
@@ -2,6 +2,8 @@
 layout(location = 0) in vec3 inPosition;
 layout(location = 1) in vec3 inNormal;
 layout(location = 2) in vec2 inTexCoord;
+layout(location = 3) in vec4 inTangent;
+layout(location = 4) in float inAo;
 
 layout(push_constant) uniform PushConstants {
     mat4 mvp;
@@ -13,6 +15,9 @@ layout(push_constant) uniform PushConstants {
 
 layout(location = 0) out vec3 fragNormal;
 layout(location = 1) out vec2 fragTexCoord;
+layout(location = 2) out vec3 fragWorldPos;
+layout(location = 3) out vec4 fragTangent;
+layout(location = 4) out float fragAo;
 
 
 vec3 applyQuaternion(vec4 q, vec3 v) {
@@ -28,8 +33,13 @@ void main() {
     vec3 worldPos = rotatedPosition + offset;
 
     gl_Position = pc.mvp * vec4(worldPos, 1.0);
-    fragNormal = normalize(mat3(transpose(inverse(pc.model))) * inNormal);
+    // normals and tangents follow the object, the inverse scale keeps normals perpendicular
+    fragNormal = normalize(applyQuaternion(pc.rotation, inNormal / scale));
+    vec3 tangent = applyQuaternion(pc.rotation, inTangent.xyz * scale);
+    fragTangent = vec4(dot(tangent, tangent) > 0.0 ? normalize(tangent) : vec3(0.0), inTangent.w);
     fragTexCoord = inTexCoord;
+    fragWorldPos = worldPos;
+    fragAo = inAo;
 }
 
 
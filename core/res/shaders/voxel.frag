@@ -5,8 +5,23 @@ layout(location = 2) flat in uint fragAtlasTiles;
 layout(location = 3) flat in uint fragFace;
 layout(location = 4) in float fragAO;
 layout(location = 5) in vec3 fragTint;
+layout(location = 6) in float fragDepth;
 layout(binding = 0) uniform sampler2D atlas;
 layout(location = 0) out vec4 outColor;
+
+layout(push_constant) uniform Push {
+  mat4 proj_view;
+  mat4 model;
+  uint atlas_tiles;
+  uint _pad0;
+  uint _pad1;
+  uint _pad2;
+  ivec3 world_pos;
+  float time;
+  vec3 fog_color;
+  float fog_density;
+} pc;
+
 void main() {
   float tile_size = 1.0 / float(fragAtlasTiles);
   uint tx = fragTexId % fragAtlasTiles;
@@ -42,5 +57,6 @@ void main() {
   }
 
   float ao = mix(0.1, 1.0, pow(fragAO, 5.0));
-  outColor = vec4(color.rgb * shade * ao, color.a);
+  float fog = 1.0 - exp(-pow(pc.fog_density * fragDepth, 2.0));
+  outColor = vec4(mix(color.rgb * shade * ao, pc.fog_color, fog), color.a);
 }
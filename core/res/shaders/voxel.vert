@@ -9,6 +9,7 @@ layout(location = 2) out flat uint fragAtlasTiles;
 layout(location = 3) out flat uint fragFace;
 layout(location = 4) out float fragAO;
 layout(location = 5) out vec3 fragTint;
+layout(location = 6) out float fragDepth;
 
 layout(push_constant) uniform Push {
   mat4 proj_view;
@@ -51,4 +52,5 @@ void main() {
   gl_Position = pc.proj_view * vec4(float(x) + world_offset.x,
       float(y) + world_offset.y,
       float(z) + world_offset.z, 1.0);
+  fragDepth = gl_Position.w;
 }
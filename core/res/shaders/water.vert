@@ -10,6 +10,7 @@ layout(location = 2) flat out uint fragAtlasTiles;
 layout(location = 3) flat out uint fragFace;
 layout(location = 4) out float fragAO;
 layout(location = 5) out vec3 fragTint;
+layout(location = 6) out float fragDepth;
 
 layout(push_constant) uniform Push {
   mat4 proj_view;
@@ -64,4 +65,5 @@ void main() {
       yf               + world_offset.y,
       float(z) + world_offset.z,
       1.0);
+  fragDepth = gl_Position.w;
 }
@@ -1,21 +1,296 @@
 use std::{path::Path, sync::Arc};
 
-use anyhow::Result;
-use ash::vk::CommandPool;
+use anyhow::{Result, anyhow};
+use ash::vk::{self, CommandPool};
+use cgmath::{InnerSpace, Vector3};
+use hashbrown::HashMap;
 
-use crate::rendering::{
-    shared::{
-        model::{GpuModel, Mesh},
-        vertex::Vertex,
+use crate::{
+    assets::vox::{self, is_vox},
+    log_warn,
+    rendering::{
+        shared::{
+            material::{
+                BASE_COLOR_BINDING, EMISSIVE_BINDING, MATERIAL_TEXTURE_COUNT,
+                METALLIC_ROUGHNESS_BINDING, Material, NORMAL_BINDING, OCCLUSION_BINDING,
+            },
+            model::{GpuModel, Mesh},
+            vertex::Vertex,
+        },
+        vulkan::rendering_context::VulkanRenderingContext,
     },
-    vulkan::rendering_context::VulkanRenderingContext,
 };
 
+/// Bound in place of a missing texture, multiplying by one leaves the factor unchanged
+pub(crate) const WHITE: [u8; 4] = [255, 255, 255, 255];
+/// Bound in place of a missing normal map, points straight out of the surface
+pub(crate) const FLAT_NORMAL: [u8; 4] = [128, 128, 255, 255];
+
+/// Converts a glTF image to rgba8, None for 16 and 32 bit images
+fn to_rgba8(image: &gltf::image::Data) -> Option<Vec<u8>> {
+    use gltf::image::Format;
+
+    let pixels = &image.pixels;
+    Some(match image.format {
+        Format::R8G8B8A8 => pixels.clone(),
+        Format::R8G8B8 => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        Format::R8G8 => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[1], 0, 255])
+            .collect(),
+        Format::R8 => pixels.iter().flat_map(|p| [*p, *p, *p, 255]).collect(),
+        _ => return None,
+    })
+}
+
+/// Uploads the textures of a model once, however many materials use them
+struct ModelTextures<'a> {
+    context: &'a VulkanRenderingContext,
+    command_pool: CommandPool,
+    images: &'a [gltf::image::Data],
+    /// the uploaded (image, is srgb)
+    uploaded: HashMap<(usize, bool), vk::ImageView>,
+    fallbacks: HashMap<[u8; 4], vk::ImageView>,
+}
+
+impl ModelTextures<'_> {
+    fn upload(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        is_srgb: bool,
+    ) -> Result<vk::ImageView> {
+        let format = if is_srgb {
+            vk::Format::R8G8B8A8_SRGB
+        } else {
+            vk::Format::R8G8B8A8_UNORM
+        };
+        let (_, _, view) =
+            self.context
+                .upload_texture(self.command_pool, width, height, pixels, format)?;
+        Ok(view)
+    }
+
+    fn fallback(&mut self, color: [u8; 4]) -> Result<vk::ImageView> {
+        if let Some(view) = self.fallbacks.get(&color) {
+            return Ok(*view);
+        }
+        let view = self.upload(&color, 1, 1, false)?;
+        self.fallbacks.insert(color, view);
+        Ok(view)
+    }
+
+    /// Gets the view of a texture, or of a 1x1 fallback texture when it is missing or unsupported
+    /// Note: colour textures are srgb, data textures like normals are linear
+    fn get(
+        &mut self,
+        texture: Option<gltf::Texture>,
+        is_srgb: bool,
+        fallback: [u8; 4],
+    ) -> Result<vk::ImageView> {
+        let Some(texture) = texture else {
+            return self.fallback(fallback);
+        };
+        let index = texture.source().index();
+        if let Some(view) = self.uploaded.get(&(index, is_srgb)) {
+            return Ok(*view);
+        }
+
+        let image = &self.images[index];
+        let Some(pixels) = to_rgba8(image) else {
+            log_warn!(
+                "Unsupported texture format {:?}, using a fallback texture",
+                image.format
+            );
+            return self.fallback(fallback);
+        };
+        let view = self.upload(&pixels, image.width, image.height, is_srgb)?;
+        self.uploaded.insert((index, is_srgb), view);
+        Ok(view)
+    }
+}
+
+/// Generates a tangent for every vertex from the triangles using it, for models exported without
+/// tangents
+/// Note: averages the tangents of each triangle like mikktspace but does not split vertices, so
+/// mirrored uv seams can show
+fn generate_tangents(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    tex_coords: &[[f32; 2]],
+    indices: &[u32],
+) -> Vec<[f32; 4]> {
+    let mut tangents = vec![Vector3::new(0.0, 0.0, 0.0); positions.len()];
+    let mut bitangents = vec![Vector3::new(0.0, 0.0, 0.0); positions.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [
+            triangle[0] as usize,
+            triangle[1] as usize,
+            triangle[2] as usize,
+        ];
+        let p0 = Vector3::from(positions[a]);
+        let edge1 = Vector3::from(positions[b]) - p0;
+        let edge2 = Vector3::from(positions[c]) - p0;
+        let du1 = tex_coords[b][0] - tex_coords[a][0];
+        let dv1 = tex_coords[b][1] - tex_coords[a][1];
+        let du2 = tex_coords[c][0] - tex_coords[a][0];
+        let dv2 = tex_coords[c][1] - tex_coords[a][1];
+
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+        let r = 1.0 / det;
+        let tangent = (edge1 * dv2 - edge2 * dv1) * r;
+        let bitangent = (edge2 * du1 - edge1 * du2) * r;
+        for index in [a, b, c] {
+            tangents[index] += tangent;
+            bitangents[index] += bitangent;
+        }
+    }
+
+    tangents
+        .iter()
+        .zip(&bitangents)
+        .zip(normals)
+        .map(|((tangent, bitangent), normal)| {
+            let normal = Vector3::from(*normal);
+            // make the tangent perpendicular to the normal
+            let tangent = tangent - normal * normal.dot(*tangent);
+            if tangent.magnitude2() < f32::EPSILON {
+                // no uvs to follow, the shader falls back to screen space derivatives
+                return [0.0; 4];
+            }
+            let tangent = tangent.normalize();
+            let handedness = if normal.cross(tangent).dot(*bitangent) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            [tangent.x, tangent.y, tangent.z, handedness]
+        })
+        .collect()
+}
+
+/// Reads the factors and uploads the textures of a glTF material
+fn load_material(
+    material: gltf::Material,
+    textures: &mut ModelTextures,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    sampler: vk::Sampler,
+) -> Result<Material> {
+    let pbr = material.pbr_metallic_roughness();
+    let normal = material.normal_texture();
+    let occlusion = material.occlusion_texture();
+
+    let mut views = [vk::ImageView::null(); MATERIAL_TEXTURE_COUNT];
+    views[BASE_COLOR_BINDING] = textures.get(
+        pbr.base_color_texture().map(|info| info.texture()),
+        true,
+        WHITE,
+    )?;
+    views[NORMAL_BINDING] =
+        textures.get(normal.as_ref().map(|n| n.texture()), false, FLAT_NORMAL)?;
+    views[METALLIC_ROUGHNESS_BINDING] = textures.get(
+        pbr.metallic_roughness_texture().map(|info| info.texture()),
+        false,
+        WHITE,
+    )?;
+    views[EMISSIVE_BINDING] = textures.get(
+        material.emissive_texture().map(|info| info.texture()),
+        true,
+        WHITE,
+    )?;
+    views[OCCLUSION_BINDING] =
+        textures.get(occlusion.as_ref().map(|o| o.texture()), false, WHITE)?;
+
+    let descriptor_set = textures.context.create_textures_descriptor_set(
+        descriptor_pool,
+        descriptor_set_layout,
+        &views,
+        sampler,
+    )?;
+
+    Ok(Material {
+        name: material.name().unwrap_or("material").to_string(),
+        base_color_factor: pbr.base_color_factor(),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        emissive_factor: material.emissive_factor(),
+        normal_scale: normal.as_ref().map(|n| n.scale()).unwrap_or(1.0),
+        occlusion_strength: occlusion.as_ref().map(|o| o.strength()).unwrap_or(1.0),
+        descriptor_set,
+    })
+}
+
+/// The shape of one primitive of a model, in the order `load_model` makes its meshes
+#[derive(Clone, Debug, Default)]
+pub struct MeshGeometry {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+}
+
+/// Reads the shape of a model without uploading it, used by the ambient occlusion bake
+pub fn load_geometry(path: &Path) -> Result<Vec<MeshGeometry>> {
+    if is_vox(path) {
+        return vox::load_geometry(path);
+    }
+    let (gltf, buffers, _) = gltf::import(path)?;
+
+    let mut meshes = Vec::new();
+    for mesh in gltf.meshes() {
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            meshes.push(MeshGeometry {
+                positions: reader
+                    .read_positions()
+                    .ok_or_else(|| anyhow!("A mesh of {} has no positions", path.display()))?
+                    .collect(),
+                normals: reader
+                    .read_normals()
+                    .ok_or_else(|| anyhow!("A mesh of {} has no normals", path.display()))?
+                    .collect(),
+                indices: reader
+                    .read_indices()
+                    .ok_or_else(|| anyhow!("A mesh of {} has no indices", path.display()))?
+                    .into_u32()
+                    .collect(),
+            });
+        }
+    }
+
+    Ok(meshes)
+}
+
+/// Loads a model and uploads it, `.vox` files are meshed by `vox::load_model`
+/// `ambient_occlusion` is the baked occlusion of each vertex of each mesh, meshes it doesn't
+/// match are drawn unoccluded
 pub fn load_model(
     path: &Path,
     context: Arc<VulkanRenderingContext>,
     command_pool: CommandPool,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    ambient_occlusion: Option<&[Vec<f32>]>,
 ) -> Result<GpuModel> {
+    if is_vox(path) {
+        return vox::load_model(
+            path,
+            context,
+            command_pool,
+            descriptor_pool,
+            descriptor_set_layout,
+            ambient_occlusion,
+        );
+    }
+
     let path_str = path.to_str();
 
     let name = path
@@ -24,7 +299,33 @@ pub fn load_model(
         .unwrap_or("model")
         .to_string();
 
-    let (gltf, buffers, _images) = gltf::import(path_str.unwrap())?;
+    let (gltf, buffers, images) = gltf::import(path_str.unwrap())?;
+
+    let sampler = unsafe {
+        context.device.create_sampler(
+            &vk::SamplerCreateInfo::default()
+                .mag_filter(vk::Filter::LINEAR)
+                .min_filter(vk::Filter::LINEAR)
+                .address_mode_u(vk::SamplerAddressMode::REPEAT)
+                .address_mode_v(vk::SamplerAddressMode::REPEAT)
+                .address_mode_w(vk::SamplerAddressMode::REPEAT)
+                .anisotropy_enable(false)
+                .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
+                .unnormalized_coordinates(false)
+                .compare_enable(false)
+                .mipmap_mode(vk::SamplerMipmapMode::LINEAR),
+            None,
+        )?
+    };
+    let mut textures = ModelTextures {
+        context: &context,
+        command_pool,
+        images: &images,
+        uploaded: HashMap::new(),
+        fallbacks: HashMap::new(),
+    };
+    // primitives sharing a material share its descriptor set, None is the default material
+    let mut materials: HashMap<Option<usize>, Material> = HashMap::new();
 
     let mut meshes = Vec::new();
 
@@ -42,32 +343,57 @@ pub fn load_model(
                 .into_f32()
                 .collect::<Vec<_>>();
 
+            let indices = reader
+                .read_indices()
+                .unwrap()
+                .into_u32()
+                .collect::<Vec<_>>();
+
+            let tangents = match reader.read_tangents() {
+                Some(tangents) => tangents.collect::<Vec<_>>(),
+                None => generate_tangents(&positions, &normals, &tex_coords, &indices),
+            };
+
+            // a bake of an older version of the model is ignored
+            let ao = ambient_occlusion
+                .and_then(|baked| baked.get(meshes.len()))
+                .filter(|ao| ao.len() == positions.len());
+
             let vertices: Vec<Vertex> = positions
                 .iter()
                 .zip(normals.iter())
                 .zip(tex_coords.iter())
-                .map(|((pos, norm), tex)| Vertex {
+                .zip(tangents.iter())
+                .enumerate()
+                .map(|(i, (((pos, norm), tex), tangent))| Vertex {
                     position: *pos,
                     normal: *norm,
                     tex_coord: *tex,
+                    tangent: *tangent,
+                    ao: ao.map_or(1.0, |ao| ao[i]),
                 })
                 .collect();
 
-            let indices = reader
-                .read_indices()
-                .unwrap()
-                .into_u32()
-                .collect::<Vec<_>>();
-
             let vertex_buffer = context.create_vertex_buffer(vertices.as_slice(), command_pool)?;
 
             let index_buffer = context.create_index_buffer(&indices, command_pool)?;
 
-            let material_name = primitive
-                .material()
-                .name()
-                .unwrap_or("material")
-                .to_string();
+            let gltf_material = primitive.material();
+            let material = match materials.get(&gltf_material.index()) {
+                Some(material) => material.clone(),
+                None => {
+                    let material = load_material(
+                        gltf_material.clone(),
+                        &mut textures,
+                        descriptor_pool,
+                        descriptor_set_layout,
+                        sampler,
+                    )
+                    .map_err(|e| anyhow!("Failed to load a material of {}: {}", name, e))?;
+                    materials.insert(gltf_material.index(), material.clone());
+                    material
+                }
+            };
 
             meshes.push(Mesh {
                 vertex_buffer: vertex_buffer.0,
@@ -75,7 +401,7 @@ pub fn load_model(
                 index_buffer: index_buffer.0,
                 index_buffer_memory: index_buffer.1,
                 index_count: indices.len() as u32,
-                material_name: material_name,
+                material,
             });
         }
     }
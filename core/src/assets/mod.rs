@@ -1,5 +1,8 @@
 pub mod asset_manager;
 pub mod shader_loader;
+pub mod texture_import;
+pub mod vox;
 pub mod gltf;
 pub mod loader;
 pub mod loaders;
+pub mod pack;
@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::log;
+
+/// Where an exported game keeps its packed assets, unpacked into `res/` on startup
+pub const ASSET_PACK_PATH: &str = "res.pak";
+/// The hash of the last pack unpacked, a pack with another hash overwrites the unpacked files
+pub const ASSET_PACK_STAMP_PATH: &str = "res.pak.stamp";
+
+const MAGIC: &[u8; 4] = b"APAK";
+const VERSION: u32 = 1;
+/// The smallest a packed file can be, its path and data lengths
+const MIN_FILE_SIZE: usize = 4 + 8;
+
+/// Files packed into one blob, paths are relative to the packed directory with `/` separators
+/// Layout: `APAK`, version u32, count u32, then per file the path length u32, the path, the data
+/// length u64 and the data, all little endian
+#[derive(Clone, Debug, Default)]
+pub struct AssetPack {
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+impl AssetPack {
+    /// Packs every file under a directory that `include` accepts, given the relative path
+    pub fn from_directory(directory: &Path, include: impl Fn(&str) -> bool) -> Result<Self> {
+        let mut files = Vec::new();
+        let mut pending = vec![directory.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            let entries = fs::read_dir(&current)
+                .map_err(|e| anyhow!("Failed to read {}: {}", current.display(), e))?;
+            for entry in entries {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let relative = relative_name(directory, &path)?;
+                if include(&relative) {
+                    let data = fs::read(&path)
+                        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+                    files.push((relative, data));
+                }
+            }
+        }
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(Self { files })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.files.len() as u32).to_le_bytes());
+        for (path, data) in &self.files {
+            bytes.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(path.as_bytes());
+            bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(anyhow!("Not an asset pack"));
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(anyhow!("Asset pack version {} is not supported", version));
+        }
+        let count = reader.u32()?;
+        // the count isn't trusted, a truncated pack can't claim more files than it has bytes for
+        let mut files =
+            Vec::with_capacity((count as usize).min(reader.remaining() / MIN_FILE_SIZE));
+        for _ in 0..count {
+            let length = reader.u32()? as usize;
+            let path = String::from_utf8(reader.take(length)?.to_vec())?;
+            let length = reader.u64()? as usize;
+            files.push((path, reader.take(length)?.to_vec()));
+        }
+        Ok(Self { files })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_bytes())
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Writes every file into a directory, files that already exist are only replaced if
+    /// `overwrite` is set, so edits to unpacked assets survive until a new pack ships
+    /// returns how many files were written
+    pub fn unpack(&self, directory: &Path, overwrite: bool) -> Result<usize> {
+        let mut written = 0;
+        for (name, data) in &self.files {
+            let path = safe_join(directory, name)?;
+            if !overwrite && path.exists() {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, data)
+                .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+/// Unpacks `res.pak` into `res/` if the game was exported with one
+/// Note: a pack that differs from the one in `res.pak.stamp` replaces the unpacked files,
+/// otherwise only missing files are written
+pub fn unpack_assets() -> Result<()> {
+    let pack_path = Path::new(ASSET_PACK_PATH);
+    if !pack_path.exists() {
+        return Ok(());
+    }
+    let bytes = fs::read(pack_path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", pack_path.display(), e))?;
+    let stamp = format!("{:016x}", pack_hash(&bytes));
+    let is_new = fs::read_to_string(ASSET_PACK_STAMP_PATH)
+        .ok()
+        .is_none_or(|previous| previous.trim() != stamp);
+
+    let written = AssetPack::from_bytes(&bytes)?.unpack(Path::new("res"), is_new)?;
+    if written > 0 {
+        log!("Unpacked {} assets from {}", written, ASSET_PACK_PATH);
+    }
+    if is_new {
+        fs::write(ASSET_PACK_STAMP_PATH, stamp)
+            .map_err(|e| anyhow!("Failed to write {}: {}", ASSET_PACK_STAMP_PATH, e))?;
+    }
+    Ok(())
+}
+
+/// Hashes a pack, fnv-1a so the stamp never changes between builds
+fn pack_hash(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// Gets the path of a file relative to a directory with `/` separators
+pub fn relative_name(directory: &Path, path: &Path) -> Result<String> {
+    let relative = path
+        .strip_prefix(directory)
+        .map_err(|_| anyhow!("{} is not in {}", path.display(), directory.display()))?;
+    Ok(relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Joins a packed path to a directory, refusing paths that would leave it
+fn safe_join(directory: &Path, name: &str) -> Result<PathBuf> {
+    let mut path = directory.to_path_buf();
+    for part in name.split('/') {
+        if part.is_empty() || part == "." || part == ".." || part.contains(['\\', ':']) {
+            return Err(anyhow!("Asset pack has an invalid path: {}", name));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("Asset pack is truncated"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
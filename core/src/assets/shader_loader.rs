@@ -83,6 +83,7 @@ fn compile_shader(path: &Path) -> Result<Vec<u8>> {
     let stage_arg = match stage {
         ShaderKind::Vertex => "vert",
         ShaderKind::Fragment => "frag",
+        ShaderKind::Compute => "comp",
     };
 
     let output_path = std::env::temp_dir().join(format!(
@@ -128,14 +129,16 @@ fn compile_shader(path: &Path) -> Result<Vec<u8>> {
 enum ShaderKind {
     Vertex,
     Fragment,
+    Compute,
 }
 
 fn shader_kind_from_path(path: &Path) -> Result<ShaderKind> {
     match path.extension().and_then(|e| e.to_str()) {
         Some("vert") => Ok(ShaderKind::Vertex),
         Some("frag") => Ok(ShaderKind::Fragment),
+        Some("comp") => Ok(ShaderKind::Compute),
         Some(ext) => anyhow::bail!(
-            "Unsupported shader extension '{}', expected .vert or .frag",
+            "Unsupported shader extension '{}', expected .vert, .frag or .comp",
             ext
         ),
         None => anyhow::bail!("Shader path {} has no extension", path.display()),
@@ -0,0 +1,225 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Result, anyhow};
+use image::{
+    DynamicImage, ImageEncoder,
+    codecs::png::{CompressionType, FilterType, PngEncoder},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    log_warn,
+    objects::world::World,
+    utils::atomic_write::write_atomic,
+    voxels::texture_atlas::{AtlasChanged, PendingAtlas},
+};
+
+/// Where images are picked up from, relative to the working directory like the rest of `res/`
+pub const TEXTURE_DIR: &str = "res/textures";
+/// The extensions `find_new_textures` picks up
+pub const IMPORTABLE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// How hard the converted png is compressed, smaller files take longer to write
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextureCompression {
+    Fast,
+    #[default]
+    Default,
+    Best,
+}
+
+impl TextureCompression {
+    pub const ALL: [TextureCompression; 3] = [
+        TextureCompression::Fast,
+        TextureCompression::Default,
+        TextureCompression::Best,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextureCompression::Fast => "Fast",
+            TextureCompression::Default => "Default",
+            TextureCompression::Best => "Best",
+        }
+    }
+
+    fn png_compression(&self) -> CompressionType {
+        match self {
+            TextureCompression::Fast => CompressionType::Fast,
+            TextureCompression::Default => CompressionType::Default,
+            TextureCompression::Best => CompressionType::Best,
+        }
+    }
+}
+
+/// How an image is converted, saved next to it as `<image>.import`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextureImportSettings {
+    /// colour images are srgb, data like masks is linear and gets encoded so the srgb atlas
+    /// samples it back unchanged
+    pub srgb: bool,
+    /// resizes the image to power of two sides so a full mip chain can be made from it
+    pub mipmaps: bool,
+    pub compression: TextureCompression,
+}
+
+impl Default for TextureImportSettings {
+    fn default() -> Self {
+        Self {
+            srgb: true,
+            mipmaps: false,
+            compression: TextureCompression::default(),
+        }
+    }
+}
+
+impl TextureImportSettings {
+    /// Gets the settings an image was last imported with, none if it was never imported
+    pub fn load(image_path: &Path) -> Option<Self> {
+        let path = settings_path(image_path);
+        let contents = std::fs::read_to_string(&path).ok()?;
+        match serde_yaml::from_str(&contents) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                log_warn!("Failed to read {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, image_path: &Path) -> Result<()> {
+        write_atomic(&settings_path(image_path), serde_yaml::to_string(self)?)
+    }
+}
+
+/// The `.import` file kept next to an imported image
+pub fn settings_path(image_path: &Path) -> PathBuf {
+    let mut name: OsString = image_path.as_os_str().to_owned();
+    name.push(".import");
+    PathBuf::from(name)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn is_importable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMPORTABLE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Gets the images in a directory that were never imported or changed since their last import
+/// Note: an image counts as imported while its `.import` file is newer than it
+pub fn find_new_textures(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return found;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            found.extend(find_new_textures(&path));
+            continue;
+        }
+        if !is_importable(&path) {
+            continue;
+        }
+        let is_current = match (modified(&path), modified(&settings_path(&path))) {
+            (Some(image), Some(settings)) => settings >= image,
+            _ => false,
+        };
+        if !is_current {
+            found.push(path);
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Encodes linear values as srgb, so sampling the srgb atlas gives the linear values back
+fn encode_srgb(value: u8) -> u8 {
+    let linear = value as f32 / 255.0;
+    let encoded = if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Converts an image to the runtime format, an rgba8 png next to the source, and records the
+/// settings, returns the path of the png
+/// Note: a png source is replaced by its converted copy
+pub fn import_texture(path: &Path, settings: &TextureImportSettings) -> Result<PathBuf> {
+    let mut source =
+        image::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+
+    if settings.mipmaps {
+        let width = source.width().max(1).next_power_of_two();
+        let height = source.height().max(1).next_power_of_two();
+        if width != source.width() || height != source.height() {
+            source = source.resize_exact(width, height, image::imageops::FilterType::Nearest);
+        }
+    }
+
+    let mut pixels = source.to_rgba8();
+    if !settings.srgb {
+        for pixel in pixels.pixels_mut() {
+            for channel in &mut pixel.0[..3] {
+                *channel = encode_srgb(*channel);
+            }
+        }
+    }
+
+    let mut bytes = Vec::new();
+    PngEncoder::new_with_quality(
+        &mut bytes,
+        settings.compression.png_compression(),
+        FilterType::Adaptive,
+    )
+    .write_image(
+        pixels.as_raw(),
+        pixels.width(),
+        pixels.height(),
+        image::ExtendedColorType::Rgba8,
+    )
+    .map_err(|e| anyhow!("Failed to encode {}: {}", path.display(), e))?;
+
+    let output = path.with_extension("png");
+    write_atomic(&output, bytes)?;
+
+    // written after the images so they count as imported
+    settings.save(path)?;
+    if output != path {
+        settings.save(&output)?;
+    }
+    Ok(output)
+}
+
+impl World {
+    /// Reloads a texture the voxel atlas uses, e.g. `textures/stone.png`, and uploads the atlas
+    /// again at the start of the next frame
+    /// returns false if no voxel uses the texture
+    /// Note: new textures are only added to the atlas on the next start, when voxels use them
+    pub fn refresh_texture(&mut self, texture: &str) -> Result<bool> {
+        let Ok(atlas) = self.get_resource_mut::<PendingAtlas>() else {
+            return Ok(false);
+        };
+        if !atlas.paths.iter().any(|path| path == texture) {
+            return Ok(false);
+        }
+
+        let path = Path::new("res/").join(texture);
+        let image: DynamicImage =
+            image::open(&path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+        atlas.replace_tile(texture, &image);
+        self.insert_resource(AtlasChanged);
+        Ok(true)
+    }
+}
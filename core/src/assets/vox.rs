@@ -0,0 +1,421 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use ash::vk::{self, CommandPool};
+use cgmath::{InnerSpace, Vector3};
+
+use crate::{
+    assets::gltf::{FLAT_NORMAL, MeshGeometry, WHITE},
+    log_warn,
+    rendering::{
+        shared::{
+            material::{BASE_COLOR_BINDING, MATERIAL_TEXTURE_COUNT, Material, NORMAL_BINDING},
+            model::{GpuModel, Mesh},
+            vertex::Vertex,
+        },
+        vulkan::rendering_context::VulkanRenderingContext,
+    },
+};
+
+/// The size of one voxel of a `.vox` model in world units, 16 voxels make a metre
+pub const VOX_SCALE: f32 = 1.0 / 16.0;
+
+/// One model of a `.vox` file
+#[derive(Clone, Debug)]
+pub struct VoxModel {
+    pub size: [u32; 3],
+    /// the palette index of every voxel, 0 is empty, x changes fastest then y then z
+    pub voxels: Vec<u8>,
+}
+
+impl VoxModel {
+    /// Gets the palette index at a position, 0 outside the model
+    pub fn get(&self, position: [i32; 3]) -> u8 {
+        let [x, y, z] = position;
+        let [sx, sy, sz] = self.size.map(|s| s as i32);
+        if x < 0 || y < 0 || z < 0 || x >= sx || y >= sy || z >= sz {
+            return 0;
+        }
+        self.voxels[(x + sx * (y + sy * z)) as usize]
+    }
+}
+
+/// A MagicaVoxel file, its models and the colours of its palette
+#[derive(Clone, Debug)]
+pub struct VoxFile {
+    pub models: Vec<VoxModel>,
+    /// the rgba colour of each palette index, index 0 is empty
+    pub palette: [[u8; 4]; 256],
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("The .vox file is truncated"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn is_done(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+}
+
+impl VoxFile {
+    /// Reads the models and palette of a `.vox` file
+    /// Note: the scene graph (nTRN, nGRP and nSHP) is ignored, every model sits at the origin
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != b"VOX " {
+            return Err(anyhow!("Not a .vox file"));
+        }
+        let _version = reader.u32()?;
+        if reader.take(4)? != b"MAIN" {
+            return Err(anyhow!("The .vox file has no MAIN chunk"));
+        }
+        let content = reader.u32()? as usize;
+        reader.take(content)?;
+        let _children = reader.u32()?;
+
+        let mut models = Vec::new();
+        let mut size = None;
+        let mut palette = None;
+        while !reader.is_done() {
+            let id = reader.take(4)?;
+            let content = reader.u32()? as usize;
+            let children = reader.u32()? as usize;
+            let mut chunk = Reader {
+                bytes: reader.take(content)?,
+                offset: 0,
+            };
+            reader.take(children)?;
+
+            match id {
+                b"SIZE" => size = Some([chunk.u32()?, chunk.u32()?, chunk.u32()?]),
+                b"XYZI" => {
+                    let size = size
+                        .take()
+                        .ok_or_else(|| anyhow!("The .vox file has voxels without a SIZE"))?;
+                    let mut voxels = vec![0; size.iter().map(|s| *s as usize).product()];
+                    let count = chunk.u32()?;
+                    for _ in 0..count {
+                        let voxel = chunk.take(4)?;
+                        let [x, y, z] = [voxel[0], voxel[1], voxel[2]].map(u32::from);
+                        if x < size[0] && y < size[1] && z < size[2] {
+                            voxels[(x + size[0] * (y + size[1] * z)) as usize] = voxel[3];
+                        }
+                    }
+                    models.push(VoxModel { size, voxels });
+                }
+                b"RGBA" => {
+                    // the colour of index i is stored at i - 1
+                    let mut colors = [[0; 4]; 256];
+                    for color in colors.iter_mut().skip(1) {
+                        *color = chunk.take(4)?.try_into()?;
+                    }
+                    palette = Some(colors);
+                }
+                _ => {}
+            }
+        }
+
+        if models.is_empty() {
+            return Err(anyhow!("The .vox file has no models"));
+        }
+        let palette = palette.unwrap_or_else(|| {
+            log_warn!("The .vox file has no palette, drawing it grey");
+            let mut colors = [[200, 200, 200, 255]; 256];
+            colors[0] = [0; 4];
+            colors
+        });
+
+        Ok(Self { models, palette })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_bytes(&bytes).map_err(|e| anyhow!("Failed to load {}: {}", path.display(), e))
+    }
+}
+
+/// Checks if a model path is a MagicaVoxel file
+pub fn is_vox(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("vox"))
+}
+
+/// The triangles of one `.vox` model, faces of the same colour merged into larger quads
+#[derive(Clone, Debug, Default)]
+pub struct VoxMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// points at the texel of the palette texture with the colour of the face
+    pub tex_coords: Vec<[f32; 2]>,
+    pub tangents: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+impl VoxMesh {
+    /// Meshes a model, only the faces between a voxel and empty space are kept and neighbouring
+    /// faces of the same colour are merged
+    /// Note: .vox models are z up, the mesh is y up and centred on x and z with its base at 0
+    pub fn new(model: &VoxModel) -> Self {
+        let mut mesh = Self::default();
+        let dims = model.size.map(|s| s as i32);
+
+        for d in 0..3 {
+            let u = (d + 1) % 3;
+            let v = (d + 2) % 3;
+            let index = |i: i32, j: i32| (i + j * dims[u]) as usize;
+            let mut mask = vec![0i16; (dims[u] * dims[v]) as usize];
+
+            // the plane between slice and slice + 1
+            for slice in -1..dims[d] {
+                for j in 0..dims[v] {
+                    for i in 0..dims[u] {
+                        let mut position = [0; 3];
+                        position[d] = slice;
+                        position[u] = i;
+                        position[v] = j;
+                        let behind = model.get(position);
+                        position[d] += 1;
+                        let ahead = model.get(position);
+                        // positive faces towards +d, negative towards -d
+                        mask[index(i, j)] = match (behind != 0, ahead != 0) {
+                            (true, false) => behind as i16,
+                            (false, true) => -(ahead as i16),
+                            _ => 0,
+                        };
+                    }
+                }
+
+                for j in 0..dims[v] {
+                    let mut i = 0;
+                    while i < dims[u] {
+                        let face = mask[index(i, j)];
+                        if face == 0 {
+                            i += 1;
+                            continue;
+                        }
+                        let mut width = 1;
+                        while i + width < dims[u] && mask[index(i + width, j)] == face {
+                            width += 1;
+                        }
+                        let mut height = 1;
+                        'grow: while j + height < dims[v] {
+                            for k in 0..width {
+                                if mask[index(i + k, j + height)] != face {
+                                    break 'grow;
+                                }
+                            }
+                            height += 1;
+                        }
+                        for l in 0..height {
+                            for k in 0..width {
+                                mask[index(i + k, j + l)] = 0;
+                            }
+                        }
+
+                        let mut origin = [0; 3];
+                        origin[d] = slice + 1;
+                        origin[u] = i;
+                        origin[v] = j;
+                        let mut along_u = [0; 3];
+                        along_u[u] = width;
+                        let mut along_v = [0; 3];
+                        along_v[v] = height;
+                        let mut normal = [0; 3];
+                        normal[d] = face.signum() as i32;
+                        mesh.push_quad(
+                            model,
+                            origin,
+                            along_u,
+                            along_v,
+                            normal,
+                            face.unsigned_abs() as u8,
+                        );
+                        i += width;
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+
+    /// Converts a .vox position to a y up, centred position in world units
+    fn to_model_space(model: &VoxModel, position: [i32; 3]) -> Vector3<f32> {
+        let [x, y, z] = position.map(|p| p as f32);
+        let half_x = model.size[0] as f32 / 2.0;
+        let half_y = model.size[1] as f32 / 2.0;
+        Vector3::new(x - half_x, z, -(y - half_y)) * VOX_SCALE
+    }
+
+    fn push_quad(
+        &mut self,
+        model: &VoxModel,
+        origin: [i32; 3],
+        along_u: [i32; 3],
+        along_v: [i32; 3],
+        normal: [i32; 3],
+        color: u8,
+    ) {
+        let add = |a: [i32; 3], b: [i32; 3]| [a[0] + b[0], a[1] + b[1], a[2] + b[2]];
+        let corners = [
+            origin,
+            add(origin, along_u),
+            add(add(origin, along_u), along_v),
+            add(origin, along_v),
+        ]
+        .map(|corner| Self::to_model_space(model, corner));
+        let normal = Vector3::new(normal[0] as f32, normal[2] as f32, -normal[1] as f32);
+        let tangent = (corners[1] - corners[0]).normalize();
+        let tex_coord = [(color as f32 + 0.5) / 256.0, 0.5];
+
+        // counter clockwise seen from the side the normal points to
+        let first = self.positions.len() as u32;
+        let is_ccw = (corners[1] - corners[0])
+            .cross(corners[2] - corners[0])
+            .dot(normal)
+            > 0.0;
+        let order = if is_ccw {
+            [0, 1, 2, 0, 2, 3]
+        } else {
+            [0, 2, 1, 0, 3, 2]
+        };
+        self.indices.extend(order.map(|i| first + i));
+
+        for corner in corners {
+            self.positions.push(corner.into());
+            self.normals.push(normal.into());
+            self.tex_coords.push(tex_coord);
+            self.tangents.push([tangent.x, tangent.y, tangent.z, 1.0]);
+        }
+    }
+}
+
+/// Reads the shape of a `.vox` file without uploading it, one mesh per model
+pub fn load_geometry(path: &Path) -> Result<Vec<MeshGeometry>> {
+    Ok(VoxFile::load(path)?
+        .models
+        .iter()
+        .map(VoxMesh::new)
+        .filter(|mesh| !mesh.indices.is_empty())
+        .map(|mesh| MeshGeometry {
+            positions: mesh.positions,
+            normals: mesh.normals,
+            indices: mesh.indices,
+        })
+        .collect())
+}
+
+/// Loads a `.vox` file and uploads it, one mesh per model coloured by a palette texture, so it
+/// can be drawn by a `ModelRenderer` like a glTF model
+/// `ambient_occlusion` is the baked occlusion of each vertex of each mesh, see `gltf::load_model`
+pub fn load_model(
+    path: &Path,
+    context: Arc<VulkanRenderingContext>,
+    command_pool: CommandPool,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    ambient_occlusion: Option<&[Vec<f32>]>,
+) -> Result<GpuModel> {
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("model")
+        .to_string();
+    let vox = VoxFile::load(path)?;
+
+    // nearest so each face gets exactly its palette colour
+    let sampler = unsafe {
+        context.device.create_sampler(
+            &vk::SamplerCreateInfo::default()
+                .mag_filter(vk::Filter::NEAREST)
+                .min_filter(vk::Filter::NEAREST)
+                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .anisotropy_enable(false)
+                .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
+                .unnormalized_coordinates(false)
+                .compare_enable(false)
+                .mipmap_mode(vk::SamplerMipmapMode::NEAREST),
+            None,
+        )?
+    };
+    let upload = |pixels: &[u8], width: u32, format: vk::Format| -> Result<vk::ImageView> {
+        let (_, _, view) = context.upload_texture(command_pool, width, 1, pixels, format)?;
+        Ok(view)
+    };
+    let palette = vox.palette.concat();
+    let white = upload(&WHITE, 1, vk::Format::R8G8B8A8_UNORM)?;
+    let mut views = [white; MATERIAL_TEXTURE_COUNT];
+    views[BASE_COLOR_BINDING] = upload(&palette, 256, vk::Format::R8G8B8A8_SRGB)?;
+    views[NORMAL_BINDING] = upload(&FLAT_NORMAL, 1, vk::Format::R8G8B8A8_UNORM)?;
+    let descriptor_set = context.create_textures_descriptor_set(
+        descriptor_pool,
+        descriptor_set_layout,
+        &views,
+        sampler,
+    )?;
+    let material = Material {
+        name: format!("{} palette", name),
+        base_color_factor: [1.0; 4],
+        metallic_factor: 0.0,
+        roughness_factor: 1.0,
+        emissive_factor: [0.0; 3],
+        normal_scale: 1.0,
+        occlusion_strength: 1.0,
+        descriptor_set,
+    };
+
+    let mut meshes = Vec::new();
+    for model in &vox.models {
+        let mesh = VoxMesh::new(model);
+        if mesh.indices.is_empty() {
+            continue;
+        }
+
+        // a bake of an older version of the model is ignored
+        let ao = ambient_occlusion
+            .and_then(|baked| baked.get(meshes.len()))
+            .filter(|ao| ao.len() == mesh.positions.len());
+        let vertices: Vec<Vertex> = (0..mesh.positions.len())
+            .map(|i| Vertex {
+                position: mesh.positions[i],
+                normal: mesh.normals[i],
+                tex_coord: mesh.tex_coords[i],
+                tangent: mesh.tangents[i],
+                ao: ao.map_or(1.0, |ao| ao[i]),
+            })
+            .collect();
+
+        let vertex_buffer = context.create_vertex_buffer(vertices.as_slice(), command_pool)?;
+        let index_buffer = context.create_index_buffer(&mesh.indices, command_pool)?;
+        meshes.push(Mesh {
+            vertex_buffer: vertex_buffer.0,
+            vertex_buffer_memory: vertex_buffer.1,
+            index_buffer: index_buffer.0,
+            index_buffer_memory: index_buffer.1,
+            index_count: mesh.indices.len() as u32,
+            material: material.clone(),
+        });
+    }
+
+    Ok(GpuModel { name, meshes })
+}
@@ -0,0 +1,118 @@
+use anyhow::{Result, anyhow};
+
+use crate::objects::world::World;
+
+/// A value a typed `#[console_command]` takes as an argument, parsed from one word
+pub trait CommandArg: Sized {
+    /// the type shown in the usage text, e.g. `<x: f32>`
+    const TYPE_NAME: &'static str;
+    /// if the argument can be left out
+    const OPTIONAL: bool = false;
+
+    fn parse(word: &str) -> Result<Self>;
+
+    /// The words the console offers when tab is pressed on this argument
+    fn completions(_world: &World) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Parses the argument at `index`, errors if it is missing
+    /// Note: `Option` overrides this to give `None` instead
+    fn from_args(args: &[&str], index: usize) -> Result<Self> {
+        match args.get(index) {
+            Some(word) => Self::parse(word),
+            None => Err(anyhow!("missing a {}", Self::TYPE_NAME)),
+        }
+    }
+}
+
+macro_rules! number_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl CommandArg for $ty {
+                const TYPE_NAME: &'static str = stringify!($ty);
+
+                fn parse(word: &str) -> Result<Self> {
+                    word.parse()
+                        .map_err(|_| anyhow!("expected a {}, got {}", Self::TYPE_NAME, word))
+                }
+            }
+        )*
+    };
+}
+
+number_arg!(f32, f64, i32, i64, u8, u32, u64, usize);
+
+impl CommandArg for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn parse(word: &str) -> Result<Self> {
+        match word.to_lowercase().as_str() {
+            "true" | "on" | "yes" | "1" => Ok(true),
+            "false" | "off" | "no" | "0" => Ok(false),
+            _ => Err(anyhow!("expected true or false, got {}", word)),
+        }
+    }
+
+    fn completions(_world: &World) -> Vec<String> {
+        vec!["true".to_string(), "false".to_string()]
+    }
+}
+
+impl CommandArg for String {
+    const TYPE_NAME: &'static str = "text";
+
+    fn parse(word: &str) -> Result<Self> {
+        Ok(word.to_string())
+    }
+}
+
+impl<T: CommandArg> CommandArg for Option<T> {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+    const OPTIONAL: bool = true;
+
+    fn parse(word: &str) -> Result<Self> {
+        T::parse(word).map(Some)
+    }
+
+    fn completions(world: &World) -> Vec<String> {
+        T::completions(world)
+    }
+
+    fn from_args(args: &[&str], index: usize) -> Result<Self> {
+        args.get(index).map(|word| T::parse(word)).transpose()
+    }
+}
+
+/// An argument of a typed `#[console_command]`, used for the usage text and tab completion
+pub struct CommandArgInfo {
+    pub name: &'static str,
+    pub type_name: &'static str,
+    pub optional: bool,
+    pub completions: fn(&World) -> Vec<String>,
+}
+
+/// The arguments a command takes, `inputs` if it was given or else built from the typed
+/// arguments, e.g. `<x: f32> [radius: f32]`
+pub fn signature(inputs: &str, args: &[CommandArgInfo]) -> String {
+    if !inputs.is_empty() {
+        return inputs.to_string();
+    }
+    args.iter()
+        .map(|arg| match arg.optional {
+            true => format!("[{}: {}]", arg.name, arg.type_name),
+            false => format!("<{}: {}>", arg.name, arg.type_name),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The error a typed command gives when its arguments do not parse
+pub fn usage_error(
+    name: &str,
+    inputs: &str,
+    args: &[CommandArgInfo],
+    problem: String,
+) -> anyhow::Error {
+    anyhow!("{}\nUsage: {} {}", problem, name, signature(inputs, args))
+}
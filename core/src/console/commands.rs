@@ -0,0 +1,534 @@
+use std::path::Path;
+
+use anyhow::Result;
+use apostasy_macros::console_command;
+use cgmath::Vector3;
+
+use crate::{
+    console::{Console, ConsoleCommand, RuntimeCommands},
+    export::{ExportSettings, ExportTarget, export},
+    log, log_error,
+    net::{Net, voxel_sync::VoxelSync},
+    objects::{
+        registry::registered,
+        systems::{Time, stats::SystemProfiler},
+        world::World,
+    },
+    profiler,
+    rendering::shared::baked_ao::AoBakeSettings,
+};
+
+/// Lists every console command
+#[console_command]
+pub fn help(world: &mut World, _args: &[&str]) -> Result<()> {
+    let mut commands: Vec<&ConsoleCommand> = registered::<ConsoleCommand>().collect();
+    commands.sort_by_key(|command| command.name);
+
+    let mut lines: Vec<String> = commands
+        .into_iter()
+        .map(|command| {
+            format!(
+                "{} {} - {} [{}]",
+                command.name,
+                command.signature(),
+                command.description,
+                command.package
+            )
+        })
+        .collect();
+    if let Ok(runtime) = world.get_resource::<RuntimeCommands>() {
+        let mut runtime: Vec<_> = runtime.iter().collect();
+        runtime.sort_by(|a, b| a.name.cmp(&b.name));
+        lines.extend(runtime.into_iter().map(|command| {
+            format!(
+                "{} {} - {} [{}]",
+                command.name, command.inputs, command.description, command.source
+            )
+        }));
+    }
+
+    for line in lines {
+        world.console_print(line);
+    }
+    Ok(())
+}
+
+/// Clears the console
+#[console_command]
+pub fn clear(world: &mut World, _args: &[&str]) -> Result<()> {
+    world.get_resource_mut::<Console>()?.clear();
+    Ok(())
+}
+
+/// Saves and closes the engine, the same as closing the window
+#[console_command]
+pub fn quit(world: &mut World, _args: &[&str]) -> Result<()> {
+    world.exit();
+    Ok(())
+}
+
+/// Lists registered systems or enables and disables them by name
+#[console_command(
+    name = "system",
+    inputs = "list | enable <name> | disable <name> | enable_all"
+)]
+pub fn system_command(world: &mut World, args: &[&str]) -> Result<()> {
+    match args {
+        ["list"] | [] => {
+            for system in world.systems() {
+                let state = if system.is_enabled { "" } else { " (disabled)" };
+                let set = system
+                    .order
+                    .set
+                    .map(|set| format!(" set {}", set))
+                    .unwrap_or_default();
+                world.console_print(format!(
+                    "[{}] {}::{} priority {}{}{}",
+                    system.kind.label(),
+                    system.package,
+                    system.name,
+                    system.priority,
+                    set,
+                    state
+                ));
+            }
+        }
+        ["enable", name] | ["disable", name] => {
+            let is_enabled = args[0] == "enable";
+            let count = world.set_system_enabled(name, is_enabled)?;
+            world.console_print(format!("{} {} systems", args[0], count));
+        }
+        ["enable_all"] => {
+            world.enable_all_systems();
+            world.console_print("Enabled all systems");
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Usage: system list | enable <name> | disable <name> | enable_all"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Prints the constraints that order the systems, or writes the whole schedule to a dot or json
+/// file
+#[console_command(name = "schedule", inputs = "edges | dump <file.dot | file.json>")]
+pub fn schedule_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let graph = world.schedule_graph();
+    match args {
+        ["edges"] | [] => {
+            for edge in &graph.edges {
+                let from = &graph.nodes[edge.from];
+                let to = &graph.nodes[edge.to];
+                world.console_print(format!(
+                    "[{}] {}::{} -> {}::{} ({})",
+                    from.stage, from.package, from.name, to.package, to.name, edge.constraint
+                ));
+            }
+            for constraint in &graph.unknown {
+                world.console_print(format!("Matches nothing: {}", constraint));
+            }
+            for error in &graph.errors {
+                world.console_print(error.clone());
+            }
+        }
+        ["dump", path] => {
+            graph.write(Path::new(path))?;
+            world.console_print(format!(
+                "Wrote {} systems and {} constraints to {}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                path
+            ));
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Usage: schedule edges | dump <file.dot | file.json>"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Saves every object that is not a voxel chunk to a yaml file
+#[console_command(name = "save_objects", inputs = "<path>")]
+pub fn save_objects_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let [path] = args else {
+        return Err(anyhow::anyhow!("Usage: save_objects <path>"));
+    };
+    world.save_objects(path)?;
+    world.console_print(format!("Saved objects to {}", path));
+    Ok(())
+}
+
+/// Replaces every object that is not a voxel chunk with the objects in a yaml file
+#[console_command(name = "load_objects", inputs = "<path>")]
+pub fn load_objects_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let [path] = args else {
+        return Err(anyhow::anyhow!("Usage: load_objects <path>"));
+    };
+    let count = world.load_objects(path)?.len();
+    world.console_print(format!("Loaded {} objects from {}", count, path));
+    Ok(())
+}
+
+/// Logs every use of a removed object
+#[console_command(name = "stale_objects", inputs = "on | off")]
+pub fn stale_objects_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let enabled = match args {
+        ["on"] => true,
+        ["off"] => false,
+        _ => return Err(anyhow::anyhow!("Usage: stale_objects on | off")),
+    };
+    world.set_stale_object_logging(enabled);
+    world.console_print(format!(
+        "Stale object logging {}",
+        if enabled { "enabled" } else { "disabled" }
+    ));
+    Ok(())
+}
+
+/// Frees spare object storage, or reserves room for more objects
+#[console_command(name = "compact", inputs = "[reserve count]")]
+pub fn compact_command(world: &mut World, args: &[&str]) -> Result<()> {
+    match args {
+        [] => world.compact(),
+        ["reserve", count] => world.reserve_objects(count.parse()?),
+        _ => return Err(anyhow::anyhow!("Usage: compact [reserve count]")),
+    }
+    world.console_print(format!(
+        "{} objects, room for {}",
+        world.object_count(),
+        world.object_capacity()
+    ));
+    Ok(())
+}
+
+/// Bakes the ambient occlusion of static models, then save the scene to keep it
+#[console_command(name = "bake_ao")]
+pub fn bake_ao_command(
+    world: &mut World,
+    samples: Option<u32>,
+    distance: Option<f32>,
+) -> Result<()> {
+    let mut settings = AoBakeSettings::default();
+    if let Some(samples) = samples {
+        settings.samples = samples;
+    }
+    if let Some(distance) = distance {
+        settings.max_distance = distance;
+    }
+    let baked = world.bake_ambient_occlusion(&settings)?;
+    world.console_print(format!(
+        "Baked ambient occlusion of {} static objects",
+        baked
+    ));
+    Ok(())
+}
+
+/// Clears a sphere of voxels, remeshing is spread over the next frames
+#[console_command(name = "carve")]
+pub fn carve_command(world: &mut World, x: i32, y: i32, z: i32, radius: f32) {
+    let removed = world.carve_sphere(Vector3::new(x, y, z), radius);
+    world.console_print(format!("Removed {} voxels", removed));
+}
+
+/// Sets how fast game time runs, e.g. 0.25 for slow motion or 0 to stop everything but UI
+#[console_command(name = "timescale")]
+pub fn timescale_command(world: &mut World, scale: Option<f32>) -> Result<()> {
+    let time = world.get_resource_mut::<Time>()?;
+    if let Some(scale) = scale {
+        time.set_scale(scale);
+    }
+    let scale = time.scale();
+    world.console_print(format!("Time scale is {}", scale));
+    Ok(())
+}
+
+/// Lists the systems with the highest average time, or resets or toggles the system profiler
+#[console_command(name = "profile", inputs = "top [count] | reset | on | off")]
+pub fn profile_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let profiler = world.get_resource_mut::<SystemProfiler>()?;
+    match args {
+        ["top"] | ["top", _] | [] => {
+            let count = match args.get(1) {
+                Some(count) => count.parse()?,
+                None => 10,
+            };
+            let top = profiler.top(count);
+            if top.is_empty() {
+                world.console_print("No systems have run yet");
+            }
+            for stats in top {
+                world.console_print(format!(
+                    "{:>8.3} ms avg {:>8.3} ms max {:>8} calls  {}::{} ({})",
+                    stats.avg_ms,
+                    stats.max_ms,
+                    stats.calls,
+                    stats.package,
+                    stats.name,
+                    stats.kind.label()
+                ));
+            }
+        }
+        ["reset"] => {
+            profiler.reset();
+            world.console_print("Reset the system stats");
+        }
+        ["on"] => profiler.enabled = true,
+        ["off"] => profiler.enabled = false,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Usage: profile top [count] | reset | on | off"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Turns the profiler on or off, or writes the kept frames to a yaml file
+#[console_command(name = "profiler", inputs = "on | off | export <path>")]
+pub fn profiler_command(world: &mut World, args: &[&str]) -> Result<()> {
+    match args {
+        ["on"] => profiler::set_enabled(true),
+        ["off"] => profiler::set_enabled(false),
+        ["export", path] => {
+            profiler::export(Path::new(path))?;
+            world.console_print(format!("Exported profiler frames to {}", path));
+        }
+        _ => return Err(anyhow::anyhow!("Usage: profiler on | off | export <path>")),
+    }
+    Ok(())
+}
+
+/// Prints how many objects, components, tags and layouts the world has
+#[console_command(name = "world_stats")]
+pub fn world_stats_command(world: &mut World, count: Option<usize>) {
+    let count = count.unwrap_or(10);
+    let stats = world.stats();
+    let mut lines = vec![
+        format!(
+            "{} objects (room for {}), {} chunks, {} resources, {} queued commands",
+            stats.object_count,
+            stats.object_capacity,
+            stats.chunk_count,
+            stats.resource_count,
+            stats.queued_commands
+        ),
+        format!(
+            "{} layouts, {} component types using {} bytes",
+            stats.layout_count(),
+            stats.components.len(),
+            stats.component_bytes()
+        ),
+    ];
+    for component in stats.components.iter().take(count) {
+        lines.push(format!(
+            "  {} x{} ({} bytes each, {} total)",
+            component.name, component.count, component.size, component.bytes
+        ));
+    }
+    for tag in stats.tags.iter().take(count) {
+        lines.push(format!("  #{} x{}", tag.name, tag.count));
+    }
+    for line in lines {
+        world.console_print(line);
+    }
+}
+
+/// Prints the components and tags of every object with a name
+#[console_command(name = "find", inputs = "<name>")]
+pub fn find_command(world: &mut World, args: &[&str]) -> Result<()> {
+    if args.is_empty() {
+        return Err(anyhow::anyhow!("Usage: find <name>"));
+    }
+    let name = args.join(" ");
+    let ids = world.find_all_by_name(&name);
+    if ids.is_empty() {
+        world.console_print(format!("No object called {}", name));
+        return Ok(());
+    }
+    let lines: Vec<String> = ids
+        .into_iter()
+        .filter_map(|id| world.object_to_string(id))
+        .collect();
+    for line in lines {
+        world.console_print(line);
+    }
+    Ok(())
+}
+
+/// Spawns a prefab, or saves an object and its children as one
+#[console_command(name = "prefab", inputs = "spawn <path> | save <object name> <path>")]
+pub fn prefab_command(world: &mut World, args: &[&str]) -> Result<()> {
+    match args {
+        ["spawn", path] => {
+            let id = world.spawn_prefab(path)?;
+            let description = world.object_to_string(id).unwrap_or_default();
+            world.console_print(format!("Spawned {}", description));
+        }
+        ["save", name, path] => {
+            let id = world
+                .find_by_name(name)
+                .ok_or_else(|| anyhow::anyhow!("No object called {}", name))?;
+            world.save_prefab(id, path)?;
+            world.console_print(format!("Saved {} to {}", name, path));
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Usage: prefab spawn <path> | save <object name> <path>"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Copies an object and its children, the copy gets the same parent
+#[console_command(name = "duplicate", inputs = "<object name>")]
+pub fn duplicate_command(world: &mut World, args: &[&str]) -> Result<()> {
+    if args.is_empty() {
+        return Err(anyhow::anyhow!("Usage: duplicate <object name>"));
+    }
+    let name = args.join(" ");
+    let id = world
+        .find_by_name(&name)
+        .ok_or_else(|| anyhow::anyhow!("No object called {}", name))?;
+    let copy = world.clone_object(id)?;
+    let description = world.object_to_string(copy).unwrap_or_default();
+    world.console_print(format!("Duplicated {}", description));
+    Ok(())
+}
+
+/// Opens the network socket, talks to a peer, lists the peers or starts syncing voxels
+#[console_command(
+    name = "net",
+    inputs = "host <port> | connect <address> | peers | voxels server | voxels client <address>"
+)]
+pub fn net_command(world: &mut World, args: &[&str]) -> Result<()> {
+    match args {
+        ["host", port] => {
+            let port: u16 = port.parse()?;
+            let net = Net::bind(("0.0.0.0", port))?;
+            world.insert_resource(net);
+            world.console_print(format!("Listening on port {}", port));
+        }
+        ["connect", address] => {
+            if !world.has_resource::<Net>() {
+                world.insert_resource(Net::bind("0.0.0.0:0")?);
+            }
+            let peer = world.get_resource_mut::<Net>()?.connect(*address)?;
+            world.console_print(format!("Connecting to {}", peer));
+        }
+        ["voxels", "server"] => {
+            world.get_resource::<Net>()?;
+            world.insert_resource(VoxelSync::server());
+            world.console_print("Syncing voxels as the server");
+        }
+        ["voxels", "client", address] => {
+            if !world.has_resource::<Net>() {
+                world.insert_resource(Net::bind("0.0.0.0:0")?);
+            }
+            let server = world.get_resource_mut::<Net>()?.connect(*address)?;
+            world.insert_resource(VoxelSync::client(server));
+            world.console_print(format!("Syncing voxels from {}", server));
+        }
+        ["peers"] | [] => {
+            let peers = world.get_resource::<Net>()?.peers();
+            world.console_print(format!("{} peers", peers.len()));
+            for peer in peers {
+                world.console_print(peer.to_string());
+            }
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Usage: net host <port> | connect <address> | peers | voxels server | voxels client <address>"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Builds the game in release into a folder that runs on its own, in the background
+#[console_command(name = "export", inputs = "[linux | windows | macos] [output]")]
+pub fn export_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let target = match args.first() {
+        Some(target) => target.parse()?,
+        None => ExportTarget::host(),
+    };
+    let mut settings = ExportSettings::new(target);
+    if let Some(output) = args.get(1) {
+        settings.output = output.into();
+    }
+    world.console_print(format!(
+        "Exporting for {} to {}, see the log for progress",
+        target,
+        settings.output.display()
+    ));
+    std::thread::spawn(move || match export(&settings) {
+        Ok(output) => log!("Export finished: {}", output.display()),
+        Err(e) => {
+            log_error!("Export failed: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Splits `Component.field` into its parts
+fn component_field(path: &str) -> Result<(&str, &str)> {
+    path.split_once('.')
+        .ok_or_else(|| anyhow::anyhow!("Expected <Component>.<field>, got {}", path))
+}
+
+/// Prints a field of a component of an object, or every field of the component
+#[console_command(name = "get", inputs = "<object> <Component>[.<field>]")]
+pub fn get_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let [object, path] = args else {
+        return Err(anyhow::anyhow!("Usage: get <object> <Component>[.<field>]"));
+    };
+    let id = world
+        .find_by_name(object)
+        .ok_or_else(|| anyhow::anyhow!("No object called {}", object))?;
+    let fields: Vec<(String, String)> = match path.split_once('.') {
+        Some((component, field)) => vec![(component.to_string(), field.to_string())],
+        None => {
+            let registration = world
+                .component_registrations(id)
+                .into_iter()
+                .find(|r| r.type_name.eq_ignore_ascii_case(path))
+                .ok_or_else(|| anyhow::anyhow!("{} has no {}", object, path))?;
+            registration
+                .fields
+                .iter()
+                .map(|field| (path.to_string(), field.name.to_string()))
+                .collect()
+        }
+    };
+    for (component, field) in fields {
+        let value = world.get_component_field(id, &component, &field)?;
+        let text = serde_yaml::to_string(&value)?;
+        world.console_print(format!("{}.{}: {}", component, field, text.trim_end()));
+    }
+    Ok(())
+}
+
+/// Sets a field of a component of an object, the value is yaml, e.g.
+/// `set Player Velocity.linear [0, 5, 0]`
+#[console_command(name = "set", inputs = "<object> <Component>.<field> <value>")]
+pub fn set_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let usage = || anyhow::anyhow!("Usage: set <object> <Component>.<field> <value>");
+    let [object, path, value @ ..] = args else {
+        return Err(usage());
+    };
+    if value.is_empty() {
+        return Err(usage());
+    }
+    let (component, field) = component_field(path)?;
+    let id = world
+        .find_by_name(object)
+        .ok_or_else(|| anyhow::anyhow!("No object called {}", object))?;
+    let value: serde_yaml::Value = serde_yaml::from_str(&value.join(" "))?;
+    world.set_component_field(id, component, field, &value)?;
+    world.console_print(format!("Set {}.{} of {}", component, field, object));
+    Ok(())
+}
@@ -0,0 +1,398 @@
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Resource, update};
+use hashbrown::HashMap;
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+use crate::{
+    console::args::CommandArgInfo,
+    objects::{registry::registered, resources::input_manager::InputManager, world::World},
+    ui::ui_context::EguiContext,
+};
+
+pub mod args;
+pub mod commands;
+
+/// The most lines the console keeps before dropping the oldest
+const MAX_LINES: usize = 500;
+
+/// A command run from the console, registered with `#[console_command]`
+pub struct ConsoleCommand {
+    pub name: &'static str,
+    /// the arguments the command takes, shown by `help`
+    pub inputs: &'static str,
+    pub description: &'static str,
+    /// gets the words typed after the name
+    pub func: fn(&mut World, &[&str]) -> Result<()>,
+    /// the typed arguments of the function, empty if it takes the words itself
+    pub args: &'static [CommandArgInfo],
+    /// the cargo package the command was registered in
+    pub package: &'static str,
+}
+#[cfg(not(target_family = "wasm"))]
+inventory::collect!(ConsoleCommand);
+
+impl ConsoleCommand {
+    /// The arguments shown by `help`, built from the typed arguments if `inputs` was not given
+    pub fn signature(&self) -> String {
+        args::signature(self.inputs, self.args)
+    }
+}
+
+pub type CommandFunc = Arc<dyn Fn(&mut World, &[&str]) -> Result<()> + Send + Sync>;
+
+/// A command registered while the game runs with `World::register_command`, e.g. by a script
+#[derive(Clone)]
+pub struct RuntimeCommand {
+    pub name: String,
+    /// the arguments the command takes, shown by `help`
+    pub inputs: String,
+    pub description: String,
+    /// what registered the command, e.g. a script path, shown by `help`
+    pub source: String,
+    pub func: CommandFunc,
+}
+
+impl RuntimeCommand {
+    pub fn new(
+        name: impl Into<String>,
+        source: impl Into<String>,
+        func: impl Fn(&mut World, &[&str]) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            inputs: String::new(),
+            description: String::new(),
+            source: source.into(),
+            func: Arc::new(func),
+        }
+    }
+}
+
+/// The commands registered at runtime, by lowercase name
+#[derive(Resource, Clone, Default)]
+pub struct RuntimeCommands {
+    commands: HashMap<String, RuntimeCommand>,
+}
+
+impl RuntimeCommands {
+    pub fn get(&self, name: &str) -> Option<&RuntimeCommand> {
+        self.commands.get(&name.to_lowercase())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RuntimeCommand> {
+        self.commands.values()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsoleLineKind {
+    /// a line the user typed
+    Input,
+    Output,
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConsoleLine {
+    pub text: String,
+    pub kind: ConsoleLineKind,
+}
+
+/// The in game console, toggled with the ` key
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Console {
+    pub is_open: bool,
+    pub input: String,
+    pub lines: Vec<ConsoleLine>,
+    /// the previously run lines, oldest first
+    pub history: Vec<String>,
+    /// the history entry being browsed with the arrow keys
+    pub history_index: Option<usize>,
+}
+
+impl Console {
+    pub fn print(&mut self, text: impl Into<String>) {
+        self.push(text.into(), ConsoleLineKind::Output);
+    }
+
+    pub fn print_error(&mut self, text: impl Into<String>) {
+        self.push(text.into(), ConsoleLineKind::Error);
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    fn push(&mut self, text: String, kind: ConsoleLineKind) {
+        for line in text.lines() {
+            self.lines.push(ConsoleLine {
+                text: line.to_string(),
+                kind,
+            });
+        }
+        if self.lines.len() > MAX_LINES {
+            self.lines.drain(..self.lines.len() - MAX_LINES);
+        }
+    }
+}
+
+/// Finds a console command registered with `#[console_command]` by name
+pub fn get_console_command(name: &str) -> Option<&'static ConsoleCommand> {
+    registered::<ConsoleCommand>().find(|command| command.name.eq_ignore_ascii_case(name))
+}
+
+/// The words that can finish the last word of a console line, the command name for the first
+/// word and the completions of the typed argument after that
+pub fn command_completions(world: &World, line: &str) -> Vec<String> {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    // a trailing space starts the next word
+    if line.is_empty() || line.ends_with(char::is_whitespace) {
+        words.push("");
+    }
+    let Some((last, before)) = words.split_last() else {
+        return Vec::new();
+    };
+
+    let mut candidates = match before.split_first() {
+        None => {
+            let mut names: Vec<String> = registered::<ConsoleCommand>()
+                .map(|command| command.name.to_string())
+                .collect();
+            if let Ok(runtime) = world.get_resource::<RuntimeCommands>() {
+                names.extend(runtime.iter().map(|command| command.name.clone()));
+            }
+            names
+        }
+        Some((name, args)) => get_console_command(name)
+            .and_then(|command| command.args.get(args.len()))
+            .map(|arg| (arg.completions)(world))
+            .unwrap_or_default(),
+    };
+    let last = last.to_lowercase();
+    candidates.retain(|candidate| candidate.to_lowercase().starts_with(&last));
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Completes the last word of a console line, to the whole word if only one fits or else as far
+/// as every fitting word agrees, returns the words that fit
+fn complete_line(world: &World, line: &mut String) -> Vec<String> {
+    let candidates = command_completions(world, line);
+    let Some(first) = candidates.first() else {
+        return candidates;
+    };
+    let mut common = first.len();
+    for candidate in &candidates[1..] {
+        common = first
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| !a.eq_ignore_ascii_case(b))
+            .map_or(common.min(candidate.len()), |((i, _), _)| common.min(i));
+    }
+    let start = line
+        .rfind(char::is_whitespace)
+        .map_or(0, |i| i + line[i..].chars().next().unwrap().len_utf8());
+    line.truncate(start);
+    line.push_str(&first[..common]);
+    if candidates.len() == 1 {
+        line.push(' ');
+    }
+    candidates
+}
+
+impl World {
+    /// Prints to the console if there is one
+    pub fn console_print(&mut self, text: impl Into<String>) {
+        if let Ok(console) = self.get_resource_mut::<Console>() {
+            console.print(text);
+        }
+    }
+
+    /// Runs a line of console input, e.g. `stress spawn 100 cube`
+    pub fn run_command(&mut self, line: &str) -> Result<()> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            return Ok(());
+        };
+        if let Some(command) = get_console_command(name) {
+            return (command.func)(self, args);
+        }
+        let func = self
+            .get_resource::<RuntimeCommands>()
+            .ok()
+            .and_then(|commands| commands.get(name))
+            .map(|command| command.func.clone())
+            .ok_or_else(|| anyhow!("Unknown command {}, type help for a list", name))?;
+        func(self, args)
+    }
+
+    /// Adds a console command while the game runs, replacing one with the same name and source
+    /// Note: errors if a `#[console_command]` or another source already has the name
+    pub fn register_command(&mut self, command: RuntimeCommand) -> Result<()> {
+        if get_console_command(&command.name).is_some() {
+            return Err(anyhow!(
+                "Command {} is already registered by the engine",
+                command.name
+            ));
+        }
+        let commands = &mut self.init_resource::<RuntimeCommands>()?.commands;
+        let key = command.name.to_lowercase();
+        if let Some(existing) = commands.get(&key)
+            && existing.source != command.source
+        {
+            return Err(anyhow!(
+                "Command {} is already registered by {}",
+                command.name,
+                existing.source
+            ));
+        }
+        commands.insert(key, command);
+        Ok(())
+    }
+
+    /// Removes a command added with `register_command`, returns false if there was none
+    pub fn unregister_command(&mut self, name: &str) -> bool {
+        self.get_resource_mut::<RuntimeCommands>()
+            .is_ok_and(|commands| commands.commands.remove(&name.to_lowercase()).is_some())
+    }
+
+    /// Removes every command a source registered, e.g. before a script is reloaded
+    /// returns how many were removed
+    pub fn unregister_commands_from(&mut self, source: &str) -> usize {
+        let Ok(commands) = self.get_resource_mut::<RuntimeCommands>() else {
+            return 0;
+        };
+        let before = commands.commands.len();
+        commands
+            .commands
+            .retain(|_, command| command.source != source);
+        before - commands.commands.len()
+    }
+}
+
+#[update(while_paused = true)]
+pub fn console_system(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+    let toggle = inputs
+        .keys_pressed
+        .contains(&PhysicalKey::Code(KeyCode::Backquote));
+    let Ok(console) = world.get_resource_mut::<Console>() else {
+        return Ok(());
+    };
+    if toggle {
+        console.is_open = !console.is_open;
+    }
+    if !console.is_open {
+        return Ok(());
+    }
+
+    let mut console = console.clone();
+    // the key that opened the console is also typed into it
+    console.input.retain(|c| c != '`');
+
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let mut submitted = None;
+    let mut completions = Vec::new();
+    let mut is_open = true;
+    egui::Window::new("Console")
+        .open(&mut is_open)
+        .default_width(560.0)
+        .default_height(280.0)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+        .show(&ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(240.0)
+                .stick_to_bottom(true)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    for line in &console.lines {
+                        let text = egui::RichText::new(&line.text).monospace();
+                        let text = match line.kind {
+                            ConsoleLineKind::Input => text.color(egui::Color32::GRAY),
+                            ConsoleLineKind::Output => text,
+                            ConsoleLineKind::Error => {
+                                text.color(egui::Color32::from_rgb(230, 90, 90))
+                            }
+                        };
+                        ui.label(text);
+                    }
+                });
+            ui.separator();
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut console.input)
+                    .desired_width(f32::INFINITY)
+                    .lock_focus(true)
+                    .hint_text("type help for a list of commands, tab completes"),
+            );
+            if toggle {
+                response.request_focus();
+            }
+
+            let (enter, up, down, tab) = ui.input(|i| {
+                (
+                    i.key_pressed(egui::Key::Enter),
+                    i.key_pressed(egui::Key::ArrowUp),
+                    i.key_pressed(egui::Key::ArrowDown),
+                    i.key_pressed(egui::Key::Tab),
+                )
+            });
+            if response.has_focus() && tab {
+                console.input.retain(|c| c != '\t');
+                completions = complete_line(world, &mut console.input);
+                // move the cursor to the end of the completed word
+                if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), response.id) {
+                    let end = egui::text::CCursor::new(console.input.chars().count());
+                    state
+                        .cursor
+                        .set_char_range(Some(egui::text::CCursorRange::one(end)));
+                    state.store(ui.ctx(), response.id);
+                }
+            }
+            if response.lost_focus() && enter {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    submitted = Some(line);
+                }
+                response.request_focus();
+            }
+            if response.has_focus() && !console.history.is_empty() && (up || down) {
+                let last = console.history.len() - 1;
+                let index = match (console.history_index, up) {
+                    (None, true) => Some(last),
+                    (None, false) => None,
+                    (Some(i), true) => Some(i.saturating_sub(1)),
+                    (Some(i), false) => (i < last).then_some(i + 1),
+                };
+                console.history_index = index;
+                console.input = index
+                    .map(|i| console.history[i].clone())
+                    .unwrap_or_default();
+            }
+        });
+    console.is_open &= is_open;
+
+    // several commands or values fit, list them like a shell does
+    if completions.len() > 1 {
+        console.print(completions.join("  "));
+    }
+    if let Some(line) = &submitted {
+        console.push(format!("> {}", line), ConsoleLineKind::Input);
+        console.history.push(line.clone());
+        console.history_index = None;
+    }
+    *world.get_resource_mut::<Console>()? = console;
+
+    if let Some(line) = submitted
+        && let Err(e) = world.run_command(&line)
+    {
+        world
+            .get_resource_mut::<Console>()?
+            .print_error(e.to_string());
+    }
+
+    Ok(())
+}
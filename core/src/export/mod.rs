@@ -0,0 +1,249 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+    assets::pack::{ASSET_PACK_PATH, AssetPack, relative_name},
+    log,
+};
+
+/// A platform a game can be exported for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportTarget {
+    Linux,
+    Windows,
+    MacOs,
+}
+
+impl ExportTarget {
+    /// The platform the engine is running on
+    pub fn host() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else {
+            Self::Linux
+        }
+    }
+
+    /// The rust target cargo builds for
+    /// Note: other targets than the host need their toolchain installed with `rustup target add`
+    pub fn triple(self) -> &'static str {
+        match self {
+            Self::Linux => "x86_64-unknown-linux-gnu",
+            Self::Windows => "x86_64-pc-windows-msvc",
+            Self::MacOs => "aarch64-apple-darwin",
+        }
+    }
+
+    pub fn executable_name(self, binary: &str) -> String {
+        match self {
+            Self::Windows => format!("{}.exe", binary),
+            _ => binary.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ExportTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Linux => "linux",
+            Self::Windows => "windows",
+            Self::MacOs => "macos",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ExportTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "linux" => Ok(Self::Linux),
+            "windows" | "win" => Ok(Self::Windows),
+            "macos" | "mac" => Ok(Self::MacOs),
+            _ => Err(anyhow!(
+                "Unknown target '{}', use linux, windows or macos",
+                s
+            )),
+        }
+    }
+}
+
+/// What `export` builds and where it puts it
+#[derive(Clone, Debug)]
+pub struct ExportSettings {
+    pub target: ExportTarget,
+    /// the game crate, built on its own so nothing from the editor crate is in the export
+    pub game_dir: PathBuf,
+    /// the folder the game is written to, emptied first
+    pub output: PathBuf,
+    /// the binary the game crate builds
+    pub binary: String,
+}
+
+impl ExportSettings {
+    /// Exports the game crate next to the engine to `export/<target>`
+    pub fn new(target: ExportTarget) -> Self {
+        let game_dir = ["../game", "game", "."]
+            .into_iter()
+            .map(PathBuf::from)
+            .find(|dir| dir.join("Cargo.toml").exists() && dir.join("res").exists())
+            .unwrap_or_else(|| PathBuf::from("../game"));
+        Self {
+            target,
+            output: game_dir.join("export").join(target.to_string()),
+            game_dir,
+            binary: "apostasy".to_string(),
+        }
+    }
+}
+
+/// Files of `res/` that are copied as they are instead of packed, so players can change them
+fn is_loose(name: &str) -> bool {
+    name.starts_with("shaders/") || (!name.contains('/') && name.ends_with(".yaml"))
+}
+
+/// Files of `res/` that only the editor or a running instance uses
+fn is_excluded(name: &str) -> bool {
+    name.starts_with("editor/")
+        || name == ".startup"
+        || [".bak", ".tmp", ".lock"]
+            .iter()
+            .any(|extension| name.ends_with(extension))
+}
+
+/// Copies every file under a directory that `include` accepts, given the relative path
+fn copy_directory(from: &Path, to: &Path, include: impl Fn(&str) -> bool) -> Result<usize> {
+    let mut copied = 0;
+    let mut pending = vec![from.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let name = relative_name(from, &path)?;
+            if !include(&name) {
+                continue;
+            }
+            let destination = to.join(&name);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&path, &destination)
+                .map_err(|e| anyhow!("Failed to copy {}: {}", path.display(), e))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Builds the game in release and writes a folder that runs on its own: the binary, `res.pak`
+/// with the assets, and `res/` with the shaders and config files
+/// returns the folder
+/// Note: blocks until cargo is done, which can take minutes
+pub fn export(settings: &ExportSettings) -> Result<PathBuf> {
+    let manifest = settings.game_dir.join("Cargo.toml");
+    if !manifest.exists() {
+        return Err(anyhow!("No game crate at {}", settings.game_dir.display()));
+    }
+
+    log!(
+        "Building {} for {}",
+        settings.game_dir.display(),
+        settings.target
+    );
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .args(["build", "--release", "--target", settings.target.triple()])
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .status()
+        .map_err(|e| anyhow!("Failed to run cargo: {}", e))?;
+    if !status.success() {
+        return Err(anyhow!("cargo build failed with {}", status));
+    }
+
+    let target_dir = std::env::var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| settings.game_dir.join("target"));
+    let executable = settings.target.executable_name(&settings.binary);
+    let built = target_dir
+        .join(settings.target.triple())
+        .join("release")
+        .join(&executable);
+    if !built.exists() {
+        return Err(anyhow!("cargo built no {}", built.display()));
+    }
+
+    if settings.output.exists() {
+        fs::remove_dir_all(&settings.output)
+            .map_err(|e| anyhow!("Failed to clear {}: {}", settings.output.display(), e))?;
+    }
+    let res = settings.output.join("res");
+    fs::create_dir_all(&res).map_err(|e| anyhow!("Failed to create {}: {}", res.display(), e))?;
+    fs::copy(&built, settings.output.join(&executable))
+        .map_err(|e| anyhow!("Failed to copy {}: {}", built.display(), e))?;
+
+    // the engine shaders first, so the game's own shaders replace them
+    let engine_shaders = settings.game_dir.join("../core/res/shaders");
+    if engine_shaders.exists() {
+        copy_directory(&engine_shaders, &res.join("shaders"), |_| true)?;
+    }
+    let game_res = settings.game_dir.join("res");
+    let loose = copy_directory(&game_res, &res, |name| is_loose(name) && !is_excluded(name))?;
+    let pack = AssetPack::from_directory(&game_res, |name| !is_loose(name) && !is_excluded(name))?;
+    pack.save(&settings.output.join(ASSET_PACK_PATH))?;
+
+    log!(
+        "Exported to {}: {} packed assets, {} loose files",
+        settings.output.display(),
+        pack.files.len(),
+        loose
+    );
+    Ok(settings.output.clone())
+}
+
+/// Runs `<binary> export --target <platform> [--output <folder>] [--game <crate>]`
+/// returns None if the arguments are not an export, so the binary starts as usual
+pub fn export_from_args(args: &[String]) -> Option<Result<PathBuf>> {
+    if args.get(1).map(String::as_str) != Some("export") {
+        return None;
+    }
+    let parse = || -> Result<ExportSettings> {
+        let mut target = ExportTarget::host();
+        let mut output = None;
+        let mut game_dir = None;
+        let mut rest = args[2..].iter();
+        while let Some(flag) = rest.next() {
+            let value = rest
+                .next()
+                .ok_or_else(|| anyhow!("{} needs a value", flag))?;
+            match flag.as_str() {
+                "--target" => target = value.parse()?,
+                "--output" => output = Some(PathBuf::from(value)),
+                "--game" => game_dir = Some(PathBuf::from(value)),
+                _ => return Err(anyhow!("Unknown export option {}", flag)),
+            }
+        }
+        let mut settings = ExportSettings::new(target);
+        if let Some(game_dir) = game_dir {
+            settings.output = game_dir.join("export").join(target.to_string());
+            settings.game_dir = game_dir;
+        }
+        if let Some(output) = output {
+            settings.output = output;
+        }
+        Ok(settings)
+    };
+    Some(parse().and_then(|settings| export(&settings)))
+}
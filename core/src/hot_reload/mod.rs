@@ -0,0 +1,492 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Resource, console_command, update};
+
+use crate::{
+    log, log_error, log_warn,
+    objects::{
+        component::{ComponentRegistration, short_type_name},
+        registry::{Registered, Registry, add_dynamic_registry, remove_dynamic_registry},
+        resource::ResourceRegistration,
+        scene::ObjectId,
+        snapshot::{SerializedComponent, SerializedObject},
+        systems::Time,
+        tag::TagRegistration,
+        world::World,
+    },
+    packages::{AddedPlugins, Plugin},
+};
+
+/// The function `export_dynamic_plugin!` exports that makes the plugin of a library
+pub const PLUGIN_SYMBOL: &[u8] = b"apostasy_dynamic_plugin";
+/// The function `export_dynamic_plugin!` exports that registers the systems, components, tags,
+/// resources and console commands of a library
+pub const REGISTER_SYMBOL: &[u8] = b"apostasy_dynamic_register";
+
+type PluginFn = fn() -> Box<dyn Plugin>;
+type RegisterFn = fn(&mut Registry);
+
+/// Exports a plugin and everything its crate registers, so the crate can be built as a `cdylib`
+/// and loaded with `world.load_dynamic_plugin(path)`, e.g. `export_dynamic_plugin!(GamePlugin);`
+/// Note: call it once in the crate root, the crate needs `apostasy_macros` as a dependency
+#[macro_export]
+macro_rules! export_dynamic_plugin {
+    ($plugin:expr) => {
+        apostasy_macros::register_all!();
+
+        #[unsafe(no_mangle)]
+        pub fn apostasy_dynamic_plugin() -> Box<dyn $crate::packages::Plugin> {
+            Box::new($plugin)
+        }
+
+        #[unsafe(no_mangle)]
+        pub fn apostasy_dynamic_register(registry: &mut $crate::objects::registry::Registry) {
+            register_all(registry);
+        }
+    };
+}
+
+/// A copy of a library and what it registered, the library is closed when the last world
+/// holding it drops it
+struct LoadedLibrary {
+    registry: &'static Registry,
+    /// kept open while the registry and the systems in it are used
+    _library: libloading::Library,
+}
+
+/// A game library loaded with `World::load_dynamic_plugin`
+#[derive(Clone)]
+pub struct DynamicPlugin {
+    /// the library as cargo builds it, it is copied before loading so it can be rebuilt
+    pub path: PathBuf,
+    /// the name of its `Plugin`
+    pub name: String,
+    pub reloads: u32,
+    /// when the loaded build was written
+    modified: Option<SystemTime>,
+    /// the write time the watcher saw last, it reloads once this stops changing
+    seen: Option<SystemTime>,
+    loaded: Arc<LoadedLibrary>,
+}
+
+/// A change to the loaded libraries, applied at the end of the frame when no system is running
+#[derive(Clone, Debug)]
+enum PluginSwap {
+    Load(PathBuf),
+    Reload(String),
+    Unload(String),
+}
+
+/// Game logic loaded from `cdylib` libraries, reloaded when they are rebuilt so gameplay code
+/// can change without restarting the editor
+/// Note: the library must be built by the same compiler from the same engine build as the
+/// executable, types are passed between them as they are
+/// Note: before a library is swapped the `#[component_serde]` components and tags it registered
+/// are saved off every object and loaded back into the new build, other components of it and
+/// the resources it registered are dropped, observers it added must not be used after a swap
+#[derive(Resource, Clone)]
+pub struct DynamicPlugins {
+    /// seconds between checks for rebuilt libraries, 0 stops watching
+    pub watch_interval: f32,
+    pub plugins: Vec<DynamicPlugin>,
+    since_check: f32,
+    pending: Vec<PluginSwap>,
+}
+
+impl Default for DynamicPlugins {
+    fn default() -> Self {
+        Self {
+            watch_interval: 1.0,
+            plugins: Vec::new(),
+            since_check: 0.0,
+            pending: Vec::new(),
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Where a build of a library is copied before loading, so cargo can write the next build
+fn copy_path(path: &Path, reloads: u32) -> PathBuf {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    std::env::temp_dir()
+        .join("apostasy-hot-reload")
+        .join(format!(
+            "{}-{}-{}{}",
+            name,
+            std::process::id(),
+            reloads,
+            extension
+        ))
+}
+
+/// Copies and opens a library, registers what it registered and makes its plugin
+fn open_library(path: &Path, reloads: u32) -> Result<(Arc<LoadedLibrary>, Box<dyn Plugin>)> {
+    let copy = copy_path(path, reloads);
+    if let Some(parent) = copy.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(path, &copy).map_err(|e| anyhow!("Failed to copy {}: {}", path.display(), e))?;
+
+    // Safety: the library is built with `export_dynamic_plugin!` against this engine build, so
+    // the exported functions have these signatures
+    let (library, plugin, registry) = unsafe {
+        let library = libloading::Library::new(&copy)
+            .map_err(|e| anyhow!("Failed to load {}: {}", path.display(), e))?;
+        let make_plugin = *library
+            .get::<PluginFn>(PLUGIN_SYMBOL)
+            .map_err(|e| anyhow!("{} has no export_dynamic_plugin!: {}", path.display(), e))?;
+        let register = *library
+            .get::<RegisterFn>(REGISTER_SYMBOL)
+            .map_err(|e| anyhow!("{} has no export_dynamic_plugin!: {}", path.display(), e))?;
+        let mut registry = Registry::default();
+        register(&mut registry);
+        (library, make_plugin(), registry)
+    };
+
+    // leaked, as systems keep `&'static` references to it, it is small and only made on a load
+    let registry: &'static Registry = Box::leak(Box::new(registry));
+    add_dynamic_registry(registry);
+    Ok((
+        Arc::new(LoadedLibrary {
+            registry,
+            _library: library,
+        }),
+        plugin,
+    ))
+}
+
+impl World {
+    /// Loads a game library built as a `cdylib` with `export_dynamic_plugin!` at the end of the
+    /// frame, its plugin is built and its systems run from the next frame
+    /// Note: start systems of a library don't run as the game has already started, it sets up in
+    /// `Plugin::build` instead
+    pub fn load_dynamic_plugin(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Err(anyhow!("{} does not exist", path.display()));
+        }
+        self.queue_plugin_swap(PluginSwap::Load(path))
+    }
+
+    /// Swaps a loaded library for its current build at the end of the frame
+    pub fn reload_dynamic_plugin(&mut self, name: &str) -> Result<()> {
+        self.queue_plugin_swap(PluginSwap::Reload(name.to_string()))
+    }
+
+    /// Unloads a library at the end of the frame, dropping its components and resources
+    pub fn unload_dynamic_plugin(&mut self, name: &str) -> Result<()> {
+        self.queue_plugin_swap(PluginSwap::Unload(name.to_string()))
+    }
+
+    fn queue_plugin_swap(&mut self, swap: PluginSwap) -> Result<()> {
+        self.init_resource::<DynamicPlugins>()?.pending.push(swap);
+        Ok(())
+    }
+
+    /// Loads, reloads and unloads the libraries asked for this frame
+    /// Note: called at the end of `late_update`, swapping while a stage runs would leave it with
+    /// systems of a closed library
+    pub(crate) fn apply_dynamic_plugin_swaps(&mut self) {
+        let swaps = match self.get_resource_mut::<DynamicPlugins>() {
+            Ok(plugins) if !plugins.pending.is_empty() => std::mem::take(&mut plugins.pending),
+            _ => return,
+        };
+        if self.is_in_play_mode() {
+            for swap in swaps {
+                log_warn!(
+                    "Stop play mode before swapping libraries, skipped {:?}",
+                    swap
+                );
+            }
+            return;
+        }
+
+        self.apply_queued_commands();
+        for swap in swaps {
+            let result = match &swap {
+                PluginSwap::Load(path) => self.load_library(path),
+                PluginSwap::Reload(name) => self.reload_library(name),
+                PluginSwap::Unload(name) => self.unload_library(name).map(|_| ()),
+            };
+            if let Err(e) = result {
+                log_error!("Failed to apply {:?}: {}", swap, e);
+            }
+        }
+        self.collect_systems();
+    }
+
+    fn load_library(&mut self, path: &Path) -> Result<()> {
+        let is_loaded = self
+            .get_resource::<DynamicPlugins>()
+            .is_ok_and(|plugins| plugins.plugins.iter().any(|plugin| plugin.path == path));
+        if is_loaded {
+            return Err(anyhow!("{} is already loaded, reload it", path.display()));
+        }
+
+        let (loaded, plugin) = open_library(path, 0)?;
+        let name = plugin.name().to_string();
+        self.add_boxed_plugin(plugin.as_ref());
+        drop(plugin);
+
+        let modified = modified_time(path);
+        self.init_resource::<DynamicPlugins>()?
+            .plugins
+            .push(DynamicPlugin {
+                path: path.to_path_buf(),
+                name: name.clone(),
+                reloads: 0,
+                modified,
+                seen: modified,
+                loaded,
+            });
+        log!("Loaded {} from {}", name, path.display());
+        Ok(())
+    }
+
+    fn reload_library(&mut self, name: &str) -> Result<()> {
+        let (old, stashed) = self.unload_library(name)?;
+        let reloads = old.reloads + 1;
+        let path = old.path.clone();
+        drop(old);
+
+        let (loaded, plugin) = match open_library(&path, reloads) {
+            Ok(opened) => opened,
+            Err(e) => {
+                log_warn!(
+                    "{} is unloaded, the components of {} objects were lost",
+                    name,
+                    stashed.len()
+                );
+                return Err(e);
+            }
+        };
+        let name = plugin.name().to_string();
+        self.add_boxed_plugin(plugin.as_ref());
+        drop(plugin);
+        self.restore_library_objects(stashed);
+
+        let modified = modified_time(&path);
+        self.init_resource::<DynamicPlugins>()?
+            .plugins
+            .push(DynamicPlugin {
+                path,
+                name: name.clone(),
+                reloads,
+                modified,
+                seen: modified,
+                loaded,
+            });
+        log!("Reloaded {}, build {}", name, reloads);
+        Ok(())
+    }
+
+    /// Takes what a library put in the world out of it and stops using its registrations
+    /// returns the plugin and its saved components, the library closes once the plugin is dropped
+    fn unload_library(
+        &mut self,
+        name: &str,
+    ) -> Result<(DynamicPlugin, Vec<(ObjectId, SerializedObject)>)> {
+        let plugins = &mut self.init_resource::<DynamicPlugins>()?.plugins;
+        let index = plugins
+            .iter()
+            .position(|plugin| plugin.name == name)
+            .ok_or_else(|| anyhow!("No library named {} is loaded", name))?;
+        let plugin = plugins.remove(index);
+        let registry = plugin.loaded.registry;
+
+        let stashed = self.stash_library_objects(registry);
+        let resources: Vec<&str> = ResourceRegistration::list(registry)
+            .iter()
+            .map(|r| r.type_name)
+            .collect();
+        self.resources
+            .map
+            .retain(|_, resource| !resources.contains(&short_type_name(resource.type_name())));
+        if let Ok(added) = self.get_resource_mut::<AddedPlugins>() {
+            added.0.retain(|added| *added != name);
+        }
+
+        remove_dynamic_registry(registry);
+        self.collect_systems();
+        Ok((plugin, stashed))
+    }
+
+    /// Takes the components and tags a library registered off every object, saving the
+    /// `#[component_serde]` ones
+    fn stash_library_objects(&mut self, registry: &Registry) -> Vec<(ObjectId, SerializedObject)> {
+        let components = ComponentRegistration::list(registry);
+        let tags: Vec<&str> = TagRegistration::list(registry)
+            .iter()
+            .map(|r| r.type_name)
+            .collect();
+
+        let mut stashed = Vec::new();
+        for (id, object) in self.scene.objects.iter_mut() {
+            let mut saved = SerializedObject {
+                name: object.name.clone(),
+                ..Default::default()
+            };
+            for component in std::mem::take(&mut object.components) {
+                let name = short_type_name(component.type_name());
+                let Some(registration) = components.iter().find(|r| r.type_name == name) else {
+                    object.components.push(component);
+                    continue;
+                };
+                let value = match registration
+                    .serialize
+                    .map(|serialize| serialize(&component))
+                {
+                    Some(Ok(value)) => value,
+                    Some(Err(e)) => {
+                        log_warn!("Dropped {} of {} for the swap: {}", name, object.name, e);
+                        continue;
+                    }
+                    None => {
+                        log_warn!(
+                            "Dropped {} of {} for the swap, it is not #[component_serde]",
+                            name,
+                            object.name
+                        );
+                        continue;
+                    }
+                };
+                saved.components.push(SerializedComponent {
+                    name: name.to_string(),
+                    value,
+                    version: registration.version,
+                });
+            }
+            object.tags.retain(|tag| {
+                let name = short_type_name(tag.type_name());
+                if tags.contains(&name) {
+                    saved.tags.push(name.to_string());
+                }
+                !tags.contains(&name)
+            });
+
+            if !saved.components.is_empty() || !saved.tags.is_empty() {
+                stashed.push((id, saved));
+            }
+        }
+        stashed
+    }
+
+    /// Puts the components and tags saved by `stash_library_objects` back with the new build
+    fn restore_library_objects(&mut self, stashed: Vec<(ObjectId, SerializedObject)>) {
+        for (id, saved) in stashed {
+            let restored = match saved.to_object() {
+                Ok(restored) => restored,
+                Err(e) => {
+                    log_error!("Failed to restore the components of {}: {}", saved.name, e);
+                    continue;
+                }
+            };
+            let Some(object) = self.scene.objects.get_mut(id) else {
+                continue;
+            };
+            for component in restored.components {
+                object.push_component(component);
+            }
+            object.tags.extend(restored.tags);
+        }
+    }
+}
+
+/// Reloads libraries that were rebuilt, once their file has stopped changing for a check
+#[update(while_paused = true)]
+pub fn watch_dynamic_plugins(world: &mut World) -> Result<()> {
+    let delta = world.get_resource::<Time>()?.unscaled_delta;
+    let Ok(plugins) = world.get_resource_mut::<DynamicPlugins>() else {
+        return Ok(());
+    };
+    if plugins.watch_interval <= 0.0 {
+        return Ok(());
+    }
+    plugins.since_check += delta;
+    if plugins.since_check < plugins.watch_interval {
+        return Ok(());
+    }
+    plugins.since_check = 0.0;
+
+    let mut rebuilt = Vec::new();
+    for plugin in &mut plugins.plugins {
+        let modified = modified_time(&plugin.path);
+        if modified.is_some() && modified != plugin.modified && modified == plugin.seen {
+            rebuilt.push(plugin.name.clone());
+        }
+        plugin.seen = modified;
+    }
+    for name in rebuilt {
+        log!("{} was rebuilt, reloading it", name);
+        plugins.pending.push(PluginSwap::Reload(name));
+    }
+    Ok(())
+}
+
+/// Loads, reloads and unloads game libraries built with `export_dynamic_plugin!`
+#[console_command(
+    name = "plugin",
+    inputs = "list | load <path> | reload <name> | unload <name>"
+)]
+pub fn plugin_command(world: &mut World, args: &[&str]) -> Result<()> {
+    match args {
+        ["list"] | [] => {
+            let lines: Vec<String> = world
+                .get_resource::<DynamicPlugins>()
+                .map(|plugins| {
+                    plugins
+                        .plugins
+                        .iter()
+                        .map(|plugin| {
+                            format!(
+                                "{} from {}, reloaded {} times",
+                                plugin.name,
+                                plugin.path.display(),
+                                plugin.reloads
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if lines.is_empty() {
+                world.console_print("No libraries are loaded");
+            }
+            for line in lines {
+                world.console_print(line);
+            }
+        }
+        ["load", path] => {
+            world.load_dynamic_plugin(path)?;
+            world.console_print(format!("Loading {} at the end of the frame", path));
+        }
+        ["reload", name] => {
+            world.reload_dynamic_plugin(name)?;
+            world.console_print(format!("Reloading {} at the end of the frame", name));
+        }
+        ["unload", name] => {
+            world.unload_dynamic_plugin(name)?;
+            world.console_print(format!("Unloading {} at the end of the frame", name));
+        }
+        _ => {
+            return Err(anyhow!(
+                "Usage: plugin list | load <path> | reload <name> | unload <name>"
+            ));
+        }
+    }
+    Ok(())
+}
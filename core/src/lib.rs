@@ -1,13 +1,19 @@
 extern crate self as apostasy_core;
+pub use apostasy_macros::Bundle;
 pub use apostasy_macros::Component;
+pub use apostasy_macros::console_command;
 pub use apostasy_macros::fixed_update;
+pub use apostasy_macros::game_ui;
 pub use apostasy_macros::late_update;
+pub use apostasy_macros::shutdown;
 pub use apostasy_macros::start;
+pub use apostasy_macros::system;
 pub use apostasy_macros::update;
 
 use winit::event::DeviceEvent;
 use winit::event::DeviceId;
 
+use hashbrown::HashMap;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
@@ -20,31 +26,61 @@ use winit::{
 
 use crate::assets::asset_manager::AssetManager;
 use crate::assets::gltf::load_model;
+use crate::assets::pack::unpack_assets;
+use crate::console::Console;
+use crate::minimap::Minimap;
+use crate::objects::components::interpolation::InterpolatedTransform;
 use crate::objects::components::transform::Transform;
+use crate::objects::resources::action_map::ActionMap;
 use crate::objects::resources::cursor_manager::CursorManager;
+use crate::objects::resources::gamepad::init_gamepads;
 use crate::objects::resources::input_manager::InputManager;
-use crate::objects::resources::window_manager::WindowManager;
-use crate::objects::systems::EngineTimer;
+use crate::objects::resources::window_manager::{
+    PRIMARY_WINDOW, ScaleFactorChanged, WindowManager,
+};
+use crate::objects::resources::window_settings::WindowSettings;
+use crate::objects::snapshot::SceneLoaded;
+use crate::objects::systems::stats::SystemProfiler;
+use crate::objects::systems::{EngineTimer, FixedTime, FrameStage, SystemKind};
 use crate::packages::Packages;
-use crate::packages::add_package;
+use crate::packages::{Plugin, add_package};
+use crate::physics::contacts::{CollisionEvent, Contacts};
+use crate::physics::physics_settings::{PhysicsSettings, ScenePhysicsSettings};
 use crate::rendering::components::camera::ActiveCamera;
 use crate::rendering::components::camera::Camera;
-use crate::rendering::components::camera::get_perspective_projection;
+use crate::rendering::components::camera::get_projection;
 use crate::rendering::components::camera::get_view_matrix;
 use crate::rendering::components::model_renderer::ModelRenderer;
-use crate::rendering::shared::frustrum::Frustum;
+use crate::rendering::shared::baked_ao::BakedAmbientOcclusion;
 use crate::rendering::shared::frustrum::ObjectsDrawing;
-use crate::rendering::shared::push_constants::{PushConstants, VoxelPushConstants};
-use crate::states::ShouldExit;
+use crate::rendering::shared::gpu::gpu_from_args;
+use crate::rendering::shared::grid::EditorGrid;
+use crate::rendering::shared::model::GpuModel;
+use crate::rendering::shared::outline::SelectionOutline;
+use crate::rendering::shared::picking::ObjectPicking;
+use crate::rendering::shared::push_constants::{
+    GridPushConstants, OutlinePushConstants, ParticlePushConstants,
+};
+use crate::rendering::shared::released_buffers::ReleasedBuffers;
+use crate::rendering::shared::rendering_config::{PresentMode, RenderingConfig, RenderingStats};
+use crate::rendering::shared::windows::{
+    draw_map, draw_voxels, draw_window, interpolated_camera, window_camera,
+};
+use crate::safe_mode::SafeMode;
+use crate::sequencer::SequenceCues;
+use crate::states::AppExit;
+use crate::states::GameState;
+use crate::states::pause::PauseSettings;
 use crate::ui::ui_context::EguiContext;
-use crate::voxels::VoxelTransform;
+use crate::utils::rng::WorldRng;
 use crate::voxels::meshes::NeedsRemeshing;
-use crate::voxels::meshes::VoxelChunkMesh;
-use crate::voxels::meshes::WaterMesh;
 use crate::voxels::meshes::{dispatch_remesh_jobs, receive_meshes};
+use crate::voxels::texture_atlas::AtlasChanged;
 use crate::voxels::texture_atlas::PendingAtlas;
 use crate::voxels::texture_atlas::VoxelTextureAtlas;
+use crate::voxels::texture_atlas::reupload_atlas;
 use crate::voxels::texture_atlas::upload_atlas;
+use crate::weather::Weather;
 use crate::{
     objects::world::World,
     rendering::{RenderingBackend, RenderingInfo},
@@ -52,22 +88,39 @@ use crate::{
 use winit::application::ApplicationHandler;
 
 pub mod assets;
+pub mod console;
+pub mod export;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
 pub mod items;
+pub mod math;
+pub mod minimap;
+pub mod net;
 pub mod objects;
+pub mod origin;
 pub mod packages;
 pub mod physics;
+pub mod prelude;
+pub mod profiler;
 pub mod rendering;
+pub mod safe_mode;
+pub mod scripting;
+pub mod sequencer;
 pub mod states;
+pub mod streaming;
+pub mod stress;
+pub mod telemetry;
 pub mod ui;
 pub mod utils;
 pub mod voxels;
+pub mod weather;
 
 pub use anyhow;
 pub use cgmath;
-use cgmath::{InnerSpace, Vector3};
 pub use crossbeam_channel;
 pub use egui;
 pub use epaint;
+pub use gilrs;
 pub use lru;
 pub use noise;
 pub use num_cpus;
@@ -77,284 +130,734 @@ pub use serde;
 pub use serde_yaml;
 pub use winit;
 
+// `register_all`, everything core registers, for `objects::registry::install`
+#[cfg(feature = "explicit-registration")]
+apostasy_macros::register_all!();
+
+/// The engine, `init_core` runs it on its own event loop
+/// Note: to run it inside an existing winit app, forward `resumed`, `window_event`,
+/// `device_event` and `about_to_wait` to `resume`, `window_event`, `device_event` and
+/// `request_redraw`, see `examples/embed.rs`
 pub struct Core {
     pub rendering_api: RenderingBackend,
     pub rendering_info: Option<Arc<Mutex<RenderingInfo>>>,
     pub world: Arc<Mutex<World>>,
+    /// gets the components the world extracts every frame, see `World::extract_component`
+    pub render_world: World,
     pub asset_loader: AssetManager,
+    /// set once the shutdown systems ran, see `shutdown`
+    shut_down: bool,
 }
 
+/// The name the embedding API is documented under
+pub type Engine = Core;
+
 impl Core {
+    /// Registers the engine resources, packages and systems, the window opens in `resume`
     pub fn new(rendering_api: RenderingBackend, packages: Vec<Packages>) -> Self {
+        if let Err(e) = unpack_assets() {
+            log_error!("Failed to unpack assets: {}", e);
+        }
         let mut world = World::default();
         world.insert_resource(InputManager::default());
+        init_gamepads(&mut world);
+        world.insert_resource(ActionMap::load());
         world.insert_resource(CursorManager::default());
         world.insert_resource(WindowManager::default());
+        world.insert_resource(WindowSettings::load());
         world.insert_resource(ObjectsDrawing(0));
         world.insert_resource(EngineTimer(0.0));
+        world.insert_resource(RenderingConfig::load());
+        world.insert_resource(PhysicsSettings::load());
+        world.insert_resource(ScenePhysicsSettings::default());
+        world.insert_resource(RenderingStats::default());
+        world.insert_resource(ObjectPicking::default());
+        world.insert_resource(SequenceCues::default());
+        world.insert_resource(Console::default());
+        world.insert_resource(WorldRng::default());
+        world.insert_resource(Contacts::default());
+        world.insert_resource(SystemProfiler::default());
+        world
+            .add_event::<CollisionEvent>()
+            .add_event::<SceneLoaded>()
+            .add_event::<AppExit>()
+            .add_event::<ScaleFactorChanged>();
+        world.add_state(GameState::Playing);
+        world.insert_resource(PauseSettings::default());
+        world.init_registered_resources(None);
 
         for package in packages {
             add_package(&mut world, package);
         }
 
         world.build_systems();
+        world.report_registrations();
+        if let Some(safe_mode) = SafeMode::detect() {
+            world.enter_safe_mode(safe_mode.reason);
+        }
         Self {
             rendering_api,
             rendering_info: None,
             world: Arc::new(Mutex::new(world)),
+            render_world: World::default(),
             asset_loader: AssetManager::new(),
+            shut_down: false,
         }
     }
 
-    pub fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        if let Some(rendering_info) = &mut self.rendering_info {
-            let mut rendering_info = rendering_info.lock().unwrap();
+    /// Opens the engine window, creates the renderer and runs the start systems
+    /// Note: call it once the event loop is active, e.g. from `ApplicationHandler::resumed`
+    pub fn resume(&mut self, event_loop: &ActiveEventLoop) {
+        let preferred_gpu = gpu_from_args().or_else(|| {
+            let world = self.world.lock().unwrap();
+            world
+                .get_resource::<RenderingConfig>()
+                .ok()
+                .and_then(|config| config.preferred_gpu.clone())
+        });
+        let attributes = {
+            let world = self.world.lock().unwrap();
+            world
+                .get_resource::<WindowSettings>()
+                .map(|settings| settings.attributes())
+                .unwrap_or_default()
+        };
+        let rendering_info = Some(RenderingInfo::new(
+            &event_loop,
+            self.rendering_api,
+            preferred_gpu,
+            attributes,
+        ));
+        let mut world = self.world.lock().unwrap();
+        {
+            let ri = rendering_info.as_ref().unwrap();
+            let locked = ri.lock().unwrap();
 
-            if let Some(renderer) = &mut rendering_info.renderer {
-                let _ = renderer.handle_ui_event(&event.clone());
-            }
+            let window_id = locked.window.id();
+            let window = locked.window.clone();
 
-            match event {
-                WindowEvent::CloseRequested => {
-                    event_loop.exit();
-                }
-                WindowEvent::Resized(_) => {
-                    if let Some(renderer) = &mut rendering_info.renderer
-                        && let Err(e) = renderer.resize()
-                    {
-                        log_error!("Failed to resize renderer: {}", e);
-                    }
-                }
-                WindowEvent::ScaleFactorChanged { .. } => {
-                    if let Some(renderer) = &mut rendering_info.renderer
-                        && let Err(e) = renderer.resize()
-                    {
-                        log_error!("Failed to resize renderer: {}", e);
-                    }
-                }
-                WindowEvent::RedrawRequested => {
-                    let mut objects_dawn = 0;
-                    let mut world = self.world.lock().unwrap();
+            let window_manager = world.get_resource_mut::<WindowManager>().unwrap();
+            window_manager.windows.insert(window_id, window);
+            window_manager.primary_window_id = window_id;
+            window_manager
+                .labels
+                .insert(PRIMARY_WINDOW.to_string(), window_id);
+        }
 
-                    if world.get_resource::<ShouldExit>().is_ok() {
-                        log!("Recieved ShouldExit resource, closing");
-                        event_loop.exit();
-                    }
+        self.rendering_info = rendering_info;
+        let context = self
+            .rendering_info
+            .clone()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .context
+            .clone();
+        world.insert_resource(context.adapters.clone());
 
-                    let context = Arc::new(rendering_info.context.clone());
-                    let push_constants = rendering_info.push_constants.clone();
-                    let mut voxel_push_constants = rendering_info.voxel_push_constants.clone();
-                    let model_push = rendering_info.model_push_constants.clone();
+        let pending = world.get_resource::<PendingAtlas>().unwrap().clone();
 
-                    if let Ok(atlas) = world.get_resource::<VoxelTextureAtlas>() {
-                        voxel_push_constants.set_atlas_tiles(atlas.atlas_size);
-                    }
+        let (command_pool, descriptor_pool, descriptor_set_layout, egui_context) = {
+            let ri = self.rendering_info.as_ref().unwrap().lock().unwrap();
+            let renderer = ri.renderer.as_ref().unwrap();
+            (
+                renderer.get_command_pool().unwrap(),
+                renderer.get_descriptor_pool(),
+                renderer.get_voxel_descriptor_set_layout(),
+                renderer.get_egui_context(),
+            )
+        };
+
+        let atlas = upload_atlas(
+            &context,
+            command_pool,
+            descriptor_pool,
+            descriptor_set_layout,
+            &pending.image,
+            pending.tiles,
+        )
+        .expect("Failed to upload voxel atlas");
 
-                    let Some(renderer) = &mut rendering_info.renderer else {
-                        log_error!("No renderer found!");
-                        return;
-                    };
+        world.insert_resource(EguiContext(egui_context));
+        world.insert_resource(context);
+        world.insert_resource(atlas);
 
-                    let camera = world.get_object_with_tag::<ActiveCamera>().unwrap();
-                    let camera_transform = camera.get_component::<Transform>().unwrap().clone();
-                    let camera_pos = camera_transform.global_position;
-                    let view = get_view_matrix(&camera_transform);
+        safe_mode::begin_startup();
+        world.start();
+        safe_mode::end_startup();
+    }
 
-                    let aspect = renderer.get_aspect();
-                    let proj = get_perspective_projection(
-                        camera.get_component::<Camera>().unwrap(),
-                        aspect,
-                    );
+    /// Asks the engine window to redraw, which makes `window_event` run a frame
+    pub fn request_redraw(&self) {
+        if let Some(render_info) = &self.rendering_info {
+            render_info.lock().unwrap().window.request_redraw();
+        }
+    }
 
-                    let view_proj = proj * view;
+    /// The stages `tick` runs every frame in order, listed by `World::frame_graph`
+    /// Note: keep in step with `tick`, start and shutdown systems run once so they aren't stages
+    pub const FRAME_STAGES: &'static [FrameStage] = &[
+        FrameStage::Flush("Remesh chunks and upload meshes"),
+        FrameStage::Flush("Begin frames and resolve picking"),
+        FrameStage::Systems(SystemKind::Update),
+        FrameStage::Systems(SystemKind::FixedUpdate),
+        FrameStage::Flush("Draw models into the frame and id pass"),
+        FrameStage::Flush("Draw chunks, water, ambient occlusion, grid, particles and outline"),
+        FrameStage::Flush("Draw the minimap"),
+        FrameStage::Flush("End UI and frame"),
+        FrameStage::Flush("Draw the other windows"),
+        FrameStage::Systems(SystemKind::LateUpdate),
+        FrameStage::Flush("Extract the render world"),
+    ];
+
+    /// Runs one frame: remeshing, update, fixed update, drawing and late update
+    /// Note: `window_event` calls it on `RedrawRequested`, call it yourself only when embedding the
+    /// engine without forwarding that event
+    pub fn tick(&mut self) {
+        let Some(rendering_info) = self.rendering_info.clone() else {
+            return;
+        };
+        let mut rendering_info = rendering_info.lock().unwrap();
+        let rendering_info = &mut *rendering_info;
+        let mut objects_dawn = 0;
+        let mut world = self.world.lock().unwrap();
 
-                    let mut push_constants = push_constants;
-                    push_constants.set_camera_constants(camera.to_owned(), aspect);
+        let context = Arc::new(rendering_info.context.clone());
+        let push_constants = rendering_info.push_constants.clone();
+        let mut voxel_push_constants = rendering_info.voxel_push_constants.clone();
+        let model_push = rendering_info.model_push_constants.clone();
 
-                    if !world
-                        .get_objects_with_tag_with_ids::<NeedsRemeshing>()
-                        .is_empty()
-                    {
-                        dispatch_remesh_jobs(&mut world).expect("Failed to dispatch remesh jobs");
-                    }
+        if let Ok(atlas) = world.get_resource::<VoxelTextureAtlas>() {
+            voxel_push_constants.set_atlas_tiles(atlas.atlas_size);
+        }
+
+        let Some(renderer) = &mut rendering_info.renderer else {
+            log_error!("No renderer found!");
+            return;
+        };
+
+        let vsync = world
+            .get_resource::<WindowSettings>()
+            .is_ok_and(|settings| settings.vsync);
+        if let Ok(config) = world.get_resource_mut::<RenderingConfig>()
+            && config.is_dirty
+        {
+            config.is_dirty = false;
+            let mut config = config.clone();
+            if vsync {
+                config.present_mode = PresentMode::Vsync;
+            }
+            renderer.apply_config(&config);
+            for window_renderer in rendering_info.window_renderers.values_mut() {
+                window_renderer.apply_config(&config);
+            }
+        }
+
+        // the fixed update of this frame hasn't run yet, so the camera is moved between the
+        // steps of the last frame
+        let last_overstep_fraction = world
+            .get_resource::<FixedTime>()
+            .map(|time| time.overstep_fraction)
+            .unwrap_or(1.0);
+        let camera = interpolated_camera(
+            world.get_object_with_tag::<ActiveCamera>().unwrap(),
+            last_overstep_fraction,
+        );
+        let camera_transform = camera.get_component::<Transform>().unwrap().clone();
+        let camera_pos = camera_transform.global_position;
+        let view = get_view_matrix(&camera_transform);
+
+        let aspect = renderer.get_aspect();
+        let proj = get_projection(camera.get_component::<Camera>().unwrap(), aspect);
+
+        let view_proj = proj * view;
+
+        let mut push_constants = push_constants;
+        push_constants.set_camera_constants(camera.to_owned(), aspect);
+
+        if !world
+            .get_objects_with_tag_with_ids::<NeedsRemeshing>()
+            .is_empty()
+        {
+            let _scope = profiler::scope("Remesh");
+            dispatch_remesh_jobs(&mut world).expect("Failed to dispatch remesh jobs");
+        }
+
+        if let Ok(command_pool) = renderer.get_command_pool() {
+            let _scope = profiler::scope("UploadMeshes");
+            receive_meshes(
+                &mut world,
+                &context,
+                command_pool,
+                renderer.get_buffer_graveyard(),
+            )
+            .expect("Failed to receive meshes");
+        }
+
+        if let Ok(released) = world.get_resource_mut::<ReleasedBuffers>() {
+            renderer.get_buffer_graveyard().extend(released.0.drain(..));
+        }
+
+        if world.has_resource::<AtlasChanged>() {
+            world.remove_resource::<AtlasChanged>();
+            let pending = world.get_resource::<PendingAtlas>().ok().cloned();
+            if let (Some(pending), Ok(command_pool), Ok(atlas)) = (
+                pending,
+                renderer.get_command_pool(),
+                world.get_resource_mut::<VoxelTextureAtlas>(),
+            ) && let Err(e) = reupload_atlas(&context, command_pool, atlas, &pending.image)
+            {
+                log_error!("Failed to upload the voxel atlas again: {}", e);
+            }
+        }
+
+        if let Err(e) = renderer.begin_frame(push_constants.clone()) {
+            log_error!("Failed to begin frame: {}", e);
+            return;
+        }
+
+        renderer.begin_ui();
+
+        // the other windows begin with the primary one, so systems can draw into their egui
+        // contexts during the update
+        let mut window_frames = Vec::new();
+        for (window_id, window_renderer) in rendering_info.window_renderers.iter_mut() {
+            if let Err(e) = window_renderer.begin_frame(push_constants.clone()) {
+                log_error!("Failed to begin frame: {}", e);
+                continue;
+            }
+            window_renderer.begin_ui();
+            window_frames.push(*window_id);
+        }
+
+        // resolve the pick of the frame that just finished and queue the next one
+        let mut pick_ids = None;
+        if let Ok(picking) = world.get_resource_mut::<ObjectPicking>() {
+            if let Some((frame, raw_id)) = renderer.take_pick_result() {
+                picking.resolve(frame, raw_id);
+            }
+            if let Some((x, y)) = picking.request.take() {
+                let frame = renderer.request_pick(x, y);
+                pick_ids = Some((frame, (x, y), Vec::new()));
+            }
+        }
 
-                    if let Ok(command_pool) = renderer.get_command_pool() {
-                        receive_meshes(
-                            &mut world,
-                            &context,
+        world.update();
+
+        world.fixed_update();
+        let overstep_fraction = world
+            .get_resource::<FixedTime>()
+            .map(|time| time.overstep_fraction)
+            .unwrap_or(1.0);
+
+        let object_ids: Vec<_> = world
+            .get_objects_with_component_with_ids::<ModelRenderer>()
+            .iter()
+            .map(|o| o.0)
+            .collect();
+
+        // the outlined object and its children
+        let outlined: Vec<_> = world
+            .get_resource::<SelectionOutline>()
+            .ok()
+            .filter(|outline| outline.is_visible)
+            .and_then(|outline| outline.object)
+            .map(|root| {
+                let mut ids = world.scene.get_descendants(root);
+                ids.push(root);
+                ids
+            })
+            .unwrap_or_default();
+
+        let mut loaded_models: HashMap<String, GpuModel> = HashMap::new();
+        for id in object_ids {
+            let object = world.get_object_mut(id).unwrap();
+
+            if object
+                .get_component::<ModelRenderer>()
+                .unwrap()
+                .model
+                .is_none()
+            {
+                let model_path = object
+                    .get_component::<ModelRenderer>()
+                    .unwrap()
+                    .model_path
+                    .clone();
+
+                let Some(command_pool) = renderer.get_command_pool().ok() else {
+                    continue;
+                };
+
+                // objects spawned together share one upload of their model, a
+                // baked model has its own occlusion so it gets its own upload
+                let baked = object
+                    .get_component::<BakedAmbientOcclusion>()
+                    .ok()
+                    .map(|baked| baked.meshes.clone());
+                let model = match (&baked, loaded_models.get(&model_path)) {
+                    (None, Some(model)) => model.clone(),
+                    _ => {
+                        let model = load_model(
+                            Path::new(&model_path),
+                            context.clone(),
                             command_pool,
-                            renderer.get_buffer_graveyard(),
+                            renderer.get_material_descriptor_pool(),
+                            renderer.get_material_descriptor_set_layout(),
+                            baked.as_deref(),
                         )
-                        .expect("Failed to receive meshes");
+                        .unwrap();
+                        if baked.is_none() {
+                            loaded_models.insert(model_path.clone(), model.clone());
+                        }
+                        model
                     }
+                };
+
+                object.get_component_mut::<ModelRenderer>().unwrap().model = Some(Box::new(model));
+            }
 
-                    if let Err(e) = renderer.begin_frame(push_constants.clone()) {
-                        log_error!("Failed to begin frame: {}", e);
-                        return;
+            let model_renderer = object.get_component::<ModelRenderer>().unwrap();
+            let model = object
+                .get_component::<ModelRenderer>()
+                .unwrap()
+                .model
+                .clone()
+                .unwrap();
+
+            let transform = object.get_component::<Transform>().unwrap();
+            let (position, rotation) = match object.get_component::<InterpolatedTransform>() {
+                Ok(interpolated) => interpolated.lerp(overstep_fraction),
+                Err(_) => (transform.global_position, transform.global_rotation),
+            };
+
+            let mut frame_model_push = model_push.clone();
+            frame_model_push.world_position = position;
+            frame_model_push.world_scale = transform.global_scale;
+            frame_model_push.world_rotation = rotation;
+
+            if let Some((_, _, ids)) = &mut pick_ids {
+                ids.push(id);
+                for mesh in &model.meshes {
+                    renderer.id_render(
+                        Box::new(mesh.clone()),
+                        &push_constants,
+                        &frame_model_push,
+                        ids.len() as u32,
+                    );
+                }
+            }
+
+            if outlined.contains(&id) {
+                for mesh in &model.meshes {
+                    renderer.outline_mesh(
+                        Box::new(mesh.clone()),
+                        &push_constants,
+                        &frame_model_push,
+                    );
+                }
+            }
+
+            for mesh in &model.meshes {
+                if model_renderer.is_wireframe {
+                    if let Err(e) = renderer.wireframe_render(
+                        Box::new(mesh.clone()),
+                        push_constants.clone(),
+                        &frame_model_push,
+                    ) {
+                        log_error!("Failed to render wireframe: {}", e);
                     }
+                } else {
+                    if let Err(e) = renderer.render(
+                        Box::new(mesh.clone()),
+                        push_constants.clone(),
+                        &frame_model_push,
+                    ) {
+                        log_error!("Failed to render model: {}", e);
+                    }
+                }
+            }
+        }
+
+        // fog is disabled and the sky is the default blue without weather
+        let mut particles = None;
+        if let Ok(weather) = world.get_resource::<Weather>() {
+            voxel_push_constants.set_fog(&weather.fog);
+            let [r, g, b] = weather.sky_color;
+            renderer.set_clear_color([r, g, b, 1.0]);
+            particles = weather.particles;
+        } else {
+            renderer.set_clear_color([0.0, 0.2, 0.8, 1.0]);
+        }
+
+        objects_dawn += draw_voxels(
+            &world,
+            renderer.as_mut(),
+            &push_constants,
+            &voxel_push_constants,
+            view_proj,
+            camera_pos,
+        );
+        if let Ok(grid) = world.get_resource::<EditorGrid>()
+            && grid.is_visible
+            && let Err(e) =
+                renderer.grid_render(&push_constants, &GridPushConstants::new(grid, camera_pos))
+        {
+            log_error!("Failed to render grid: {}", e);
+        }
+        if let Some(emitter) = particles {
+            let time = world.get_resource::<EngineTimer>().unwrap().0;
+            if let Err(e) = renderer.particle_render(
+                &push_constants,
+                &ParticlePushConstants::new(&emitter, camera_pos, time),
+            ) {
+                log_error!("Failed to render particles: {}", e);
+            }
+        }
+        if let Ok(outline) = world.get_resource::<SelectionOutline>()
+            && let Err(e) = renderer.outline_render(&OutlinePushConstants::new(outline))
+        {
+            log_error!("Failed to render the selection outline: {}", e);
+        }
+        // the map drawn this frame is shown by the ui from the next one
+        if let Ok(minimap) = world.get_resource::<Minimap>()
+            && minimap.is_visible
+        {
+            renderer.begin_map(minimap.resolution, minimap.background);
+            draw_map(
+                &world,
+                renderer.as_mut(),
+                &minimap.push_constants(camera_pos),
+                &voxel_push_constants,
+            );
+        }
+        let map_texture = renderer.map_texture();
+        if let Ok(minimap) = world.get_resource_mut::<Minimap>() {
+            minimap.texture = map_texture;
+        }
+        world.get_resource_mut::<ObjectsDrawing>().unwrap().0 = objects_dawn;
+        if let Some((frame, pixel, ids)) = pick_ids
+            && let Ok(picking) = world.get_resource_mut::<ObjectPicking>()
+        {
+            picking.queue(frame, pixel, ids);
+        }
+        if let Err(e) = renderer.end_ui() {
+            log_error!("Failed to end UI: {}", e);
+        }
+        if let Err(e) = renderer.end_frame() {
+            log_error!("Failed to end frame: {}", e);
+        }
+        for window_id in window_frames {
+            let Some(window_renderer) = rendering_info.window_renderers.get_mut(&window_id) else {
+                continue;
+            };
+            if let Some(label) = world
+                .get_resource::<WindowManager>()
+                .ok()
+                .and_then(|window_manager| window_manager.label(window_id))
+                && let Some(camera) = window_camera(&world, label)
+            {
+                draw_window(
+                    &world,
+                    window_renderer.as_mut(),
+                    camera,
+                    &push_constants,
+                    &voxel_push_constants,
+                    &model_push,
+                    overstep_fraction,
+                );
+            }
+            if let Err(e) = window_renderer.end_ui() {
+                log_error!("Failed to end UI: {}", e);
+            }
+            if let Err(e) = window_renderer.end_frame() {
+                log_error!("Failed to end frame: {}", e);
+            }
+        }
+        world.insert_resource(renderer.get_stats());
+        world.late_update();
+        world.extract_into(&mut self.render_world);
+        profiler::end_frame();
+    }
 
-                    renderer.begin_ui();
+    /// Builds a plugin into the world, e.g. one a third party crate provides
+    pub fn add_plugin(&mut self, plugin: impl Plugin) -> &mut Self {
+        self.world.lock().unwrap().add_plugin(plugin);
+        self
+    }
 
-                    world.update();
+    /// Returns true once an `AppExit` event was sent, e.g. by `world.exit()`
+    pub fn should_exit(&self) -> bool {
+        self.world.lock().unwrap().is_exiting()
+    }
 
-                    world.fixed_update();
+    /// Runs the `#[shutdown]` systems, then the shutdown of every resource
+    /// Note: only runs once, call it before exiting the event loop when embedding the engine
+    pub fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+        log!("Shutting down");
+        let mut world = self.world.lock().unwrap();
+        world.shutdown();
+        world.shutdown_resources();
+    }
 
-                    let object_ids: Vec<_> = world
-                        .get_objects_with_component_with_ids::<ModelRenderer>()
-                        .iter()
-                        .map(|o| o.0)
-                        .collect();
+    /// Opens and closes the windows requested through the `WindowManager`
+    /// Note: `about_to_wait` calls it, call it yourself when embedding the engine
+    pub fn update_windows(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(rendering_info) = self.rendering_info.clone() else {
+            return;
+        };
+        let mut world = self.world.lock().unwrap();
+        let Ok(window_manager) = world.get_resource_mut::<WindowManager>() else {
+            return;
+        };
+        if window_manager.open_requests.is_empty() && window_manager.close_requests.is_empty() {
+            return;
+        }
+        let mut rendering_info = rendering_info.lock().unwrap();
+
+        for label in std::mem::take(&mut window_manager.close_requests) {
+            let Some(window_id) = window_manager.labels.remove(&label) else {
+                continue;
+            };
+            rendering_info.remove_window(window_id);
+            window_manager.windows.remove(&window_id);
+            window_manager.egui_contexts.remove(&label);
+            log!("Closed window {}", label);
+        }
 
-                    for id in object_ids {
-                        let object = world.get_object_mut(id).unwrap();
+        for (label, attributes) in std::mem::take(&mut window_manager.open_requests) {
+            if window_manager.labels.contains_key(&label) {
+                log_warn!("A window is already open as {}", label);
+                continue;
+            }
+            let window = match event_loop.create_window(attributes) {
+                Ok(window) => Arc::new(window),
+                Err(e) => {
+                    log_error!("Failed to open window {}: {}", label, e);
+                    continue;
+                }
+            };
+            if let Err(e) = rendering_info.add_window(window.clone()) {
+                log_error!("Failed to create the renderer of window {}: {}", label, e);
+                continue;
+            }
+            if let Some(renderer) = rendering_info.window_renderers.get(&window.id()) {
+                window_manager
+                    .egui_contexts
+                    .insert(label.clone(), renderer.get_egui_context());
+            }
+            window_manager.labels.insert(label.clone(), window.id());
+            window_manager.windows.insert(window.id(), window);
+            log!("Opened window {}", label);
+        }
+    }
+
+    /// Handles an event of a window opened through the `WindowManager`, its input only goes to
+    /// its egui context
+    /// Note: closing it only closes that window, see `WindowManager::close_window`
+    fn secondary_window_event(&mut self, window_id: WindowId, event: WindowEvent) {
+        if event == WindowEvent::CloseRequested {
+            let mut world = self.world.lock().unwrap();
+            if let Ok(window_manager) = world.get_resource_mut::<WindowManager>()
+                && let Some(label) = window_manager.label(window_id).map(str::to_string)
+            {
+                window_manager.close_window(&label);
+            }
+            return;
+        }
 
-                        if object
-                            .get_component::<ModelRenderer>()
-                            .unwrap()
-                            .model
-                            .is_none()
-                        {
-                            let model_path = object
-                                .get_component::<ModelRenderer>()
-                                .unwrap()
-                                .model_path
-                                .clone();
+        let Some(rendering_info) = &self.rendering_info else {
+            return;
+        };
+        let mut rendering_info = rendering_info.lock().unwrap();
+        let Some(renderer) = rendering_info.window_renderers.get_mut(&window_id) else {
+            return;
+        };
+        let _ = renderer.handle_ui_event(&event);
+        if matches!(
+            event,
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. }
+        ) && let Err(e) = renderer.resize()
+        {
+            log_error!("Failed to resize renderer: {}", e);
+        }
+    }
 
-                            let Some(command_pool) = renderer.get_command_pool().ok() else {
-                                continue;
-                            };
+    /// Handles an event of an engine window, drawing a frame on `RedrawRequested` of the primary
+    /// one, which also draws the others
+    /// Note: closing the primary window sends `AppExit::WindowClosed` and shuts down like
+    /// `world.exit()`
+    pub fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let is_primary = {
+            let mut world = self.world.lock().unwrap();
+            let Ok(window_manager) = world.get_resource::<WindowManager>() else {
+                return;
+            };
+            let is_primary = window_manager.is_primary(window_id);
+            let label = window_manager.label(window_id).map(str::to_string);
+            if let WindowEvent::ScaleFactorChanged { scale_factor, .. } = &event
+                && let Some(window) = label
+            {
+                let _ = world.send_event(ScaleFactorChanged {
+                    window,
+                    scale_factor: *scale_factor,
+                });
+            }
+            is_primary
+        };
+        if !is_primary {
+            self.secondary_window_event(window_id, event);
+            return;
+        }
 
-                            let model =
-                                load_model(Path::new(&model_path), context.clone(), command_pool)
-                                    .unwrap();
+        if event == WindowEvent::CloseRequested {
+            let _ = self.world.lock().unwrap().send_event(AppExit::WindowClosed);
+            self.shutdown();
+            event_loop.exit();
+            return;
+        }
 
-                            object.get_component_mut::<ModelRenderer>().unwrap().model =
-                                Some(Box::new(model));
-                        }
+        if event == WindowEvent::RedrawRequested {
+            if self.should_exit() {
+                log!("Received AppExit, closing");
+                self.shutdown();
+                event_loop.exit();
+                return;
+            }
+            self.tick();
+        }
 
-                        let model_renderer = object.get_component::<ModelRenderer>().unwrap();
-                        let model = object
-                            .get_component::<ModelRenderer>()
-                            .unwrap()
-                            .model
-                            .clone()
-                            .unwrap();
-
-                        let transform = object.get_component::<Transform>().unwrap();
-
-                        let mut frame_model_push = model_push.clone();
-                        frame_model_push.world_position = transform.global_position;
-                        frame_model_push.world_scale = transform.global_scale;
-                        frame_model_push.world_rotation = transform.global_rotation;
-
-                        for mesh in &model.meshes {
-                            if model_renderer.is_wireframe {
-                                if let Err(e) = renderer.wireframe_render(
-                                    Box::new(mesh.clone()),
-                                    push_constants.clone(),
-                                    &frame_model_push,
-                                ) {
-                                    log_error!("Failed to render wireframe: {}", e);
-                                }
-                            } else {
-                                if let Err(e) = renderer.render(
-                                    Box::new(mesh.clone()),
-                                    push_constants.clone(),
-                                    &frame_model_push,
-                                ) {
-                                    log_error!("Failed to render model: {}", e);
-                                }
-                            }
-                        }
-                    }
+        if let Some(rendering_info) = &mut self.rendering_info {
+            let mut rendering_info = rendering_info.lock().unwrap();
 
-                    if let Ok(texture_atlas) = world.get_resource::<VoxelTextureAtlas>() {
-                        let frustum = Frustum::from_view_proj(&view_proj);
-                        let mut water_draws: Vec<(
-                            f32,
-                            Box<dyn crate::rendering::shared::model::GpuMesh>,
-                            PushConstants,
-                            VoxelPushConstants,
-                        )> = Vec::new();
-                        for object in world.get_objects_with_component::<VoxelChunkMesh>() {
-                            let transform = object.get_component::<VoxelTransform>().unwrap();
-                            let world_pos = Vector3::new(
-                                transform.position.x as f32 * 32.0,
-                                transform.position.y as f32 * 32.0,
-                                transform.position.z as f32 * 32.0,
-                            );
-
-                            if !frustum.contains_aabb(
-                                world_pos,
-                                world_pos + Vector3::new(32.0, 32.0, 32.0),
-                            ) {
-                                continue;
-                            }
-                            objects_dawn += 1;
-                            let voxel_mesh = object.get_component::<VoxelChunkMesh>().unwrap();
-
-                            let delta = world.get_resource::<EngineTimer>().unwrap();
-
-                            let chunk_push = push_constants.clone();
-                            let mut voxel_chunk_push = voxel_push_constants.clone();
-
-                            voxel_chunk_push.time = delta.0;
-                            voxel_chunk_push.set_position(Vector3::new(
-                                transform.position.x * 32,
-                                transform.position.y * 32,
-                                transform.position.z * 32,
-                            ));
-
-                            if let Err(e) = renderer.voxel_render(
-                                Box::new(voxel_mesh.clone()),
-                                texture_atlas,
-                                &chunk_push,
-                                &voxel_chunk_push,
-                            ) {
-                                log_error!("Failed to render voxel: {}", e);
-                            }
-
-                            if let Ok(water_mesh) = object.get_component::<WaterMesh>() {
-                                let chunk_center = world_pos + Vector3::new(16.0, 16.0, 16.0);
-                                let distance = (chunk_center - camera_pos).magnitude2();
-                                water_draws.push((
-                                    distance,
-                                    Box::new(water_mesh.clone()),
-                                    chunk_push.clone(),
-                                    voxel_chunk_push.clone(),
-                                ));
-                            }
-                        }
+            if let Some(renderer) = &mut rendering_info.renderer {
+                let _ = renderer.handle_ui_event(&event.clone());
+            }
 
-                        water_draws.sort_by(|a, b| {
-                            b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal)
-                        });
-                        for (_, mesh, chunk_push, voxel_chunk_push) in water_draws {
-                            if let Err(e) = renderer.water_render(
-                                mesh,
-                                texture_atlas,
-                                &chunk_push,
-                                &voxel_chunk_push,
-                            ) {
-                                log_error!("Failed to render water: {}", e);
-                            }
-                        }
-                    }
-                    world.get_resource_mut::<ObjectsDrawing>().unwrap().0 = objects_dawn;
-                    if let Err(e) = renderer.end_ui() {
-                        log_error!("Failed to end UI: {}", e);
+            match event {
+                WindowEvent::Resized(_) => {
+                    if let Some(renderer) = &mut rendering_info.renderer
+                        && let Err(e) = renderer.resize()
+                    {
+                        log_error!("Failed to resize renderer: {}", e);
                     }
-                    if let Err(e) = renderer.end_frame() {
-                        log_error!("Failed to end frame: {}", e);
+                }
+                WindowEvent::ScaleFactorChanged { .. } => {
+                    if let Some(renderer) = &mut rendering_info.renderer
+                        && let Err(e) = renderer.resize()
+                    {
+                        log_error!("Failed to resize renderer: {}", e);
                     }
-                    world.late_update();
                 }
-
                 _ => {}
             }
             let mut world = self.world.lock().unwrap();
@@ -363,7 +866,8 @@ impl Core {
         }
     }
 
-    fn device_event(
+    /// Handles a device event, e.g. raw mouse motion
+    pub fn device_event(
         &mut self,
         _event_loop: &ActiveEventLoop,
         _device_id: DeviceId,
@@ -377,58 +881,7 @@ impl Core {
 
 impl ApplicationHandler for Core {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let rendering_info = Some(RenderingInfo::new(&event_loop, self.rendering_api));
-        let mut world = self.world.lock().unwrap();
-        {
-            let ri = rendering_info.as_ref().unwrap();
-            let locked = ri.lock().unwrap();
-
-            let window_id = locked.window.id();
-            let window = locked.window.clone();
-
-            let window_manager = world.get_resource_mut::<WindowManager>().unwrap();
-            window_manager.windows.insert(window_id, window);
-            window_manager.primary_window_id = window_id;
-        }
-
-        self.rendering_info = rendering_info;
-        let context = self
-            .rendering_info
-            .clone()
-            .unwrap()
-            .lock()
-            .unwrap()
-            .context
-            .clone();
-
-        let pending = world.get_resource::<PendingAtlas>().unwrap().clone();
-
-        let (command_pool, descriptor_pool, descriptor_set_layout, egui_context) = {
-            let ri = self.rendering_info.as_ref().unwrap().lock().unwrap();
-            let renderer = ri.renderer.as_ref().unwrap();
-            (
-                renderer.get_command_pool().unwrap(),
-                renderer.get_descriptor_pool(),
-                renderer.get_voxel_descriptor_set_layout(),
-                renderer.get_egui_context(),
-            )
-        };
-
-        let atlas = upload_atlas(
-            &context,
-            command_pool,
-            descriptor_pool,
-            descriptor_set_layout,
-            &pending.image,
-            pending.tiles,
-        )
-        .expect("Failed to upload voxel atlas");
-
-        world.insert_resource(EguiContext(egui_context));
-        world.insert_resource(context);
-        world.insert_resource(atlas);
-
-        world.start();
+        self.resume(event_loop);
     }
 
     fn suspended(&mut self, _event_loop: &ActiveEventLoop) {}
@@ -451,10 +904,13 @@ impl ApplicationHandler for Core {
         self.device_event(event_loop, device_id, event);
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(render_info) = &self.rendering_info {
-            render_info.lock().unwrap().window.request_redraw();
-        }
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.update_windows(event_loop);
+        self.request_redraw();
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        self.shutdown();
     }
 }
 
@@ -462,7 +918,23 @@ impl ApplicationHandler for Core {
 /// Note: nothing can run in main after this
 /// Note: automatically runs all start systems
 pub fn init_core(rendering_api: RenderingBackend, packages: Vec<Packages>) -> Result<()> {
+    init_core_with_plugins(rendering_api, packages, Vec::new())
+}
+
+/// Initializes the core of the application like `init_core`, building `plugins` after the
+/// packages
+pub fn init_core_with_plugins(
+    rendering_api: RenderingBackend,
+    packages: Vec<Packages>,
+    plugins: Vec<Box<dyn Plugin>>,
+) -> Result<()> {
     let mut core = Core::new(rendering_api, packages);
+    {
+        let mut world = core.world.lock().unwrap();
+        for plugin in &plugins {
+            world.add_boxed_plugin(plugin.as_ref());
+        }
+    }
 
     // run all start systems
     {
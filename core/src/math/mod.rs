@@ -0,0 +1,247 @@
+pub use cgmath::{
+    Deg, EuclideanSpace, Euler, InnerSpace, Matrix, Matrix3, Matrix4, MetricSpace, Point3,
+    Quaternion, Rad, Rotation, Rotation3, SquareMatrix, Vector2, Vector3, Vector4, VectorSpace,
+    Zero,
+};
+
+pub const UP: Vector3<f32> = Vector3::new(0.0, 1.0, 0.0);
+pub const RIGHT: Vector3<f32> = Vector3::new(1.0, 0.0, 0.0);
+pub const FORWARD: Vector3<f32> = Vector3::new(0.0, 0.0, -1.0);
+
+// ========== ========== Helpers ========== ==========
+
+pub fn deg_to_rad(degrees: f32) -> f32 {
+    degrees.to_radians()
+}
+
+pub fn rad_to_deg(radians: f32) -> f32 {
+    radians.to_degrees()
+}
+
+/// Linearly interpolates between a and b, t is not clamped
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Gets how far value is between a and b, returns 0 if a and b are equal
+pub fn inverse_lerp(a: f32, b: f32, value: f32) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+    (value - a) / (b - a)
+}
+
+/// Moves current towards target by at most max_delta without overshooting
+pub fn move_towards_scalar(current: f32, target: f32, max_delta: f32) -> f32 {
+    if (target - current).abs() <= max_delta {
+        target
+    } else {
+        current + (target - current).signum() * max_delta
+    }
+}
+
+/// Moves current towards target by at most max_delta without overshooting
+pub fn move_towards(current: Vector3<f32>, target: Vector3<f32>, max_delta: f32) -> Vector3<f32> {
+    let to_target = target - current;
+    let distance = to_target.magnitude();
+    if distance <= max_delta || distance == 0.0 {
+        target
+    } else {
+        current + to_target / distance * max_delta
+    }
+}
+
+/// Spherically interpolates between two rotations, t is clamped to 0..1
+pub fn slerp(a: Quaternion<f32>, b: Quaternion<f32>, t: f32) -> Quaternion<f32> {
+    a.slerp(b, t.clamp(0.0, 1.0))
+}
+
+/// Gets the rotation that points `FORWARD` along forward with `UP` as close to up as possible
+/// Note: forward and up must not be parallel
+pub fn look_rotation(forward: Vector3<f32>, up: Vector3<f32>) -> Quaternion<f32> {
+    let forward = forward.normalize();
+    let right = forward.cross(up).normalize();
+    let up = right.cross(forward);
+    Quaternion::from(Matrix3::from_cols(right, up, -forward))
+}
+
+/// Gets the euler angles (in degrees) that point `FORWARD` along forward
+/// Note: matches the yaw then pitch order Transform builds its rotation with, roll is always 0
+pub fn look_euler_angles(forward: Vector3<f32>) -> Vector3<f32> {
+    let forward = forward.normalize();
+    Vector3::new(
+        forward.y.clamp(-1.0, 1.0).asin().to_degrees(),
+        (-forward.x).atan2(-forward.z).to_degrees(),
+        0.0,
+    )
+}
+
+/// Gets the direction `FORWARD` points with these euler angles (in degrees), the opposite of
+/// `look_euler_angles`
+pub fn euler_forward(euler_angles: Vector3<f32>) -> Vector3<f32> {
+    let pitch = euler_angles.x.to_radians();
+    let yaw = euler_angles.y.to_radians();
+    Vector3::new(
+        -yaw.sin() * pitch.cos(),
+        pitch.sin(),
+        -yaw.cos() * pitch.cos(),
+    )
+}
+
+/// Gets the euler angles (in degrees) of a rotation matrix
+/// Note: matches the yaw, pitch then roll order Transform builds its rotation with
+pub fn euler_angles_from_matrix(rotation: Matrix3<f32>) -> Vector3<f32> {
+    Vector3::new(
+        (-rotation.z.y).clamp(-1.0, 1.0).asin().to_degrees(),
+        rotation.z.x.atan2(rotation.z.z).to_degrees(),
+        rotation.x.y.atan2(rotation.y.y).to_degrees(),
+    )
+}
+
+// ========== ========== Shapes ========== ==========
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    /// always normalized
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Gets the point distance units along the ray
+    pub fn at(&self, distance: f32) -> Vector3<f32> {
+        self.origin + self.direction * distance
+    }
+}
+
+/// An axis aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_half_extents(center: Vector3<f32>, half_extents: Vector3<f32>) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn contains_point(&self, point: Vector3<f32>) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+            && point.z >= self.min.z
+            && point.z <= self.max.z
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        let overlap = self.overlap(other);
+        overlap.x > 0.0 && overlap.y > 0.0 && overlap.z > 0.0
+    }
+
+    /// Gets how far the boxes overlap on each axis, 0 on axes that don't overlap
+    pub fn overlap(&self, other: &Aabb) -> Vector3<f32> {
+        Vector3::new(
+            (self.max.x.min(other.max.x) - self.min.x.max(other.min.x)).max(0.0),
+            (self.max.y.min(other.max.y) - self.min.y.max(other.min.y)).max(0.0),
+            (self.max.z.min(other.max.z) - self.min.z.max(other.min.z)).max(0.0),
+        )
+    }
+
+    /// Gets the distance along the ray to the box, 0 if the ray starts inside the box
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        let mut t_min = 0.0_f32;
+        let mut t_max = f32::MAX;
+
+        for axis in 0..3 {
+            let origin = ray.origin[axis];
+            let direction = ray.direction[axis];
+            if direction.abs() < f32::EPSILON {
+                if origin < self.min[axis] || origin > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let inv = 1.0 / direction;
+            let mut t0 = (self.min[axis] - origin) * inv;
+            let mut t1 = (self.max[axis] - origin) * inv;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_min > t_max {
+                return None;
+            }
+        }
+
+        Some(t_min)
+    }
+}
+
+/// A plane where every point p satisfies `normal.dot(p) + distance == 0`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// always normalized
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn from_point_normal(point: Vector3<f32>, normal: Vector3<f32>) -> Self {
+        let normal = normal.normalize();
+        Self {
+            normal,
+            distance: -normal.dot(point),
+        }
+    }
+
+    /// Creates a plane from the (a, b, c, d) coefficients of `ax + by + cz + d = 0`
+    pub fn from_coefficients(coefficients: Vector4<f32>) -> Self {
+        let normal = coefficients.truncate();
+        let length = normal.magnitude();
+        Self {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        }
+    }
+
+    /// Gets the distance from the plane, positive on the side the normal points to
+    pub fn signed_distance(&self, point: Vector3<f32>) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+
+    /// Gets the distance along the ray to the plane if the ray hits it
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        let denominator = self.normal.dot(ray.direction);
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = -self.signed_distance(ray.origin) / denominator;
+        (t >= 0.0).then_some(t)
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::Result;
+use apostasy_macros::{Component, Resource, update};
+use cgmath::{Matrix4, Point3, SquareMatrix, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    objects::{components::transform::Transform, world::World},
+    rendering::{
+        components::camera::{ActiveCamera, Camera, get_projection},
+        shared::push_constants::PushConstants,
+    },
+    ui::ui_context::EguiContext,
+};
+
+/// Shows an object on the minimap
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
+pub struct MapMarker {
+    pub label: String,
+    #[inspect(color)]
+    pub color: [u8; 3],
+    /// the radius of the marker in points
+    #[inspect(range = 1.0..=16.0)]
+    pub radius: f32,
+}
+
+impl Default for MapMarker {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            color: [255, 60, 60],
+            radius: 4.0,
+        }
+    }
+}
+
+/// A resource that shows a top down map of the voxels around the active camera
+/// The renderer draws the map from an orthographic camera looking straight down over the active
+/// camera into an offscreen target, which the ui shows with the `MapMarker`s over it
+/// Note: the map only renders while this resource exists and is visible
+#[derive(Resource, Clone)]
+pub struct Minimap {
+    /// the width and height of the map in pixels
+    pub resolution: u32,
+    /// the world units covered by a pixel
+    pub scale: f32,
+    /// how far above and below the camera voxels are drawn
+    pub vertical_range: f32,
+    /// the size of the map on screen in points
+    pub display_size: f32,
+    /// the colour of the map where no voxel is drawn
+    pub background: [f32; 4],
+    pub is_visible: bool,
+    /// the texture the renderer draws the map into, none until it has been drawn
+    pub texture: Option<egui::TextureId>,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            resolution: 128,
+            scale: 1.0,
+            vertical_range: 48.0,
+            display_size: 192.0,
+            background: [0.0, 0.0, 0.0, 0.6],
+            is_visible: true,
+            texture: None,
+        }
+    }
+}
+
+impl Minimap {
+    /// Gets the point on a map of `size` points for a position relative to the map's centre
+    pub fn project(&self, offset: Vector3<f32>, size: f32) -> egui::Vec2 {
+        let pixels_per_unit = size / (self.resolution as f32 * self.scale);
+        egui::vec2(
+            size * 0.5 + offset.x * pixels_per_unit,
+            size * 0.5 + offset.z * pixels_per_unit,
+        )
+    }
+
+    /// The orthographic camera the map is drawn from, it covers `resolution * scale` units
+    pub fn camera(&self) -> Camera {
+        Camera {
+            is_orthographic: true,
+            ortho_size: self.resolution as f32 * self.scale * 0.5,
+            // the camera is `vertical_range` above the centre and sees as far below it
+            far: self.vertical_range * 2.0,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the camera constants of the map around a position, with -z at the top of the map
+    pub fn push_constants(&self, center: Vector3<f32>) -> PushConstants {
+        let eye = Point3::new(center.x, center.y + self.vertical_range, center.z);
+        PushConstants {
+            view_matrix: Matrix4::look_at_rh(eye, eye - Vector3::unit_y(), -Vector3::unit_z()),
+            projection_matrix: get_projection(&self.camera(), 1.0),
+            model_matrix: Matrix4::identity(),
+        }
+    }
+}
+
+#[update]
+pub fn minimap_system(world: &mut World) -> Result<()> {
+    let Ok(minimap) = world.get_resource::<Minimap>() else {
+        return Ok(());
+    };
+    if !minimap.is_visible {
+        return Ok(());
+    }
+
+    let Ok(camera) = world.get_object_with_tag::<ActiveCamera>() else {
+        return Ok(());
+    };
+    let camera_transform = camera.get_component::<Transform>()?.clone();
+    let Some(texture) = minimap.texture else {
+        return Ok(());
+    };
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let size = minimap.display_size;
+    let markers: Vec<(egui::Vec2, MapMarker)> = world
+        .get_objects_with_component::<MapMarker>()
+        .iter()
+        .filter_map(|object| {
+            let marker = object.get_component::<MapMarker>().ok()?;
+            let position = object.get_component::<Transform>().ok()?.global_position;
+            let offset = position - camera_transform.global_position;
+            Some((minimap.project(offset, size), marker.clone()))
+        })
+        .filter(|(point, _)| point.x >= 0.0 && point.y >= 0.0 && point.x <= size && point.y <= size)
+        .collect();
+    let forward = camera_transform.calculate_global_forward();
+
+    egui::Area::new(egui::Id::new("minimap"))
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+        .show(&ctx, |ui| {
+            let response = ui.image((texture, egui::vec2(size, size)));
+            let rect = response.rect;
+            let painter = ui.painter_at(rect);
+
+            for (point, marker) in &markers {
+                let [r, g, b] = marker.color;
+                let position = rect.min + *point;
+                painter.circle_filled(position, marker.radius, egui::Color32::from_rgb(r, g, b));
+                if !marker.label.is_empty() {
+                    painter.text(
+                        position + egui::vec2(0.0, -marker.radius - 2.0),
+                        egui::Align2::CENTER_BOTTOM,
+                        &marker.label,
+                        egui::FontId::proportional(11.0),
+                        egui::Color32::WHITE,
+                    );
+                }
+            }
+
+            // the camera is always at the centre, pointing where it looks
+            let center = rect.center();
+            let direction = egui::vec2(forward.x, forward.z);
+            if direction.length() > 0.01 {
+                let direction = direction.normalized() * 8.0;
+                painter.arrow(
+                    center - direction * 0.5,
+                    direction,
+                    egui::Stroke::new(2.0, egui::Color32::WHITE),
+                );
+            } else {
+                painter.circle_filled(center, 3.0, egui::Color32::WHITE);
+            }
+        });
+
+    Ok(())
+}
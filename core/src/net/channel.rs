@@ -0,0 +1,56 @@
+use std::{collections::BTreeMap, time::Instant};
+
+/// The sending side of one channel to one peer
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OutgoingChannel {
+    /// the sequence the next message gets
+    pub next_sequence: u32,
+    /// reliable messages waiting for an ack, with when they were last sent
+    pub unacked: BTreeMap<u32, (Vec<u8>, Instant)>,
+}
+
+impl OutgoingChannel {
+    pub fn next_sequence(&mut self) -> u32 {
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        sequence
+    }
+}
+
+/// The receiving side of one channel from one peer
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IncomingChannel {
+    /// the sequence of the next reliable message to deliver
+    pub next_expected: u32,
+    /// reliable messages that arrived before the ones sent ahead of them
+    pub held: BTreeMap<u32, String>,
+    /// the newest unreliable message delivered, older ones are dropped
+    pub newest: Option<u32>,
+}
+
+impl IncomingChannel {
+    /// Takes a reliable message, returns the messages that can now be delivered in send order
+    /// Note: duplicates of delivered messages return nothing
+    pub fn receive_reliable(&mut self, sequence: u32, payload: String) -> Vec<String> {
+        if sequence < self.next_expected {
+            return Vec::new();
+        }
+        self.held.insert(sequence, payload);
+
+        let mut ready = Vec::new();
+        while let Some(payload) = self.held.remove(&self.next_expected) {
+            ready.push(payload);
+            self.next_expected = self.next_expected.wrapping_add(1);
+        }
+        ready
+    }
+
+    /// Takes an unreliable message, returns false if a newer one was already delivered
+    pub fn receive_unreliable(&mut self, sequence: u32) -> bool {
+        if self.newest.is_some_and(|newest| sequence <= newest) {
+            return false;
+        }
+        self.newest = Some(sequence);
+        true
+    }
+}
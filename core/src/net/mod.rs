@@ -0,0 +1,341 @@
+use std::{
+    any::type_name,
+    fmt,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Resource, update};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    log, log_warn,
+    net::channel::{IncomingChannel, OutgoingChannel},
+    objects::{component::short_type_name, world::World},
+};
+
+pub mod channel;
+pub mod voxel_sync;
+
+/// Reliable messages without an ack are sent again after this long
+pub const RESEND_INTERVAL: Duration = Duration::from_millis(200);
+/// A packet is sent at least this often so the peer knows we are still there
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Peers that sent nothing for this long are dropped with their unsent messages
+pub const PEER_TIMEOUT: Duration = Duration::from_secs(10);
+/// The largest packet that is sent, a message must fit in one
+pub const MAX_PACKET_SIZE: usize = 65_507;
+
+/// How the messages of a channel are delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// sent again until the peer acks it, and handed over in the order it was sent
+    Reliable,
+    /// sent once and may be lost, one older than the newest received is dropped
+    Unreliable,
+}
+
+/// A message type that can be sent with `Net::send`, each type is its own channel
+/// e.g. `impl NetMessage for ChatMessage { const DELIVERY: Delivery = Delivery::Reliable; }`
+pub trait NetMessage: Serialize + DeserializeOwned + 'static {
+    const DELIVERY: Delivery;
+
+    /// The name the channel is matched by on both ends, the short type name by default
+    fn channel() -> &'static str {
+        short_type_name(type_name::<Self>())
+    }
+}
+
+/// A machine messages are exchanged with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PeerId(pub SocketAddr);
+
+impl fmt::Display for PeerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What goes over the wire, one per datagram
+#[derive(Serialize, Deserialize)]
+enum Packet {
+    Message {
+        channel: String,
+        reliable: bool,
+        sequence: u32,
+        payload: String,
+    },
+    Ack {
+        channel: String,
+        sequence: u32,
+    },
+    Heartbeat,
+}
+
+#[derive(Clone, Debug)]
+struct Peer {
+    outgoing: HashMap<String, OutgoingChannel>,
+    incoming: HashMap<String, IncomingChannel>,
+    last_heard: Instant,
+    last_sent: Instant,
+}
+
+impl Peer {
+    fn new() -> Self {
+        Self {
+            outgoing: HashMap::new(),
+            incoming: HashMap::new(),
+            last_heard: Instant::now(),
+            last_sent: Instant::now(),
+        }
+    }
+}
+
+/// Sends and receives typed messages over UDP, polled every frame by `net_update`
+/// Note: insert it with `world.insert_resource(Net::bind("0.0.0.0:7777")?)`
+#[derive(Resource, Clone)]
+pub struct Net {
+    socket: Arc<UdpSocket>,
+    peers: HashMap<PeerId, Peer>,
+    /// messages received but not taken by `receive` yet, by channel
+    inbox: HashMap<String, Vec<(PeerId, String)>>,
+}
+
+impl Net {
+    /// Listens on an address, use port 0 to let the system pick one
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self> {
+        let socket =
+            UdpSocket::bind(address).map_err(|e| anyhow!("Failed to bind socket: {}", e))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket: Arc::new(socket),
+            peers: HashMap::new(),
+            inbox: HashMap::new(),
+        })
+    }
+
+    pub fn local_address(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Starts talking to a peer, peers that send to us are added on their own
+    pub fn connect(&mut self, address: impl ToSocketAddrs) -> Result<PeerId> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("Address resolved to nothing"))?;
+        let peer = PeerId(address);
+        self.peers.entry(peer).or_insert_with(Peer::new);
+        self.send_packet(peer, &Packet::Heartbeat)?;
+        Ok(peer)
+    }
+
+    /// Forgets a peer and the messages still waiting to reach it
+    pub fn disconnect(&mut self, peer: PeerId) {
+        self.peers.remove(&peer);
+    }
+
+    pub fn peers(&self) -> Vec<PeerId> {
+        self.peers.keys().copied().collect()
+    }
+
+    /// Queues a message to a peer on the channel of its type
+    pub fn send<T: NetMessage>(&mut self, peer: PeerId, message: &T) -> Result<()> {
+        let payload = serde_yaml::to_string(message)?;
+        let channel = T::channel();
+        let state = self
+            .peers
+            .entry(peer)
+            .or_insert_with(Peer::new)
+            .outgoing
+            .entry(channel.to_string())
+            .or_default();
+        let sequence = state.next_sequence();
+        let packet = encode(&Packet::Message {
+            channel: channel.to_string(),
+            reliable: T::DELIVERY == Delivery::Reliable,
+            sequence,
+            payload,
+        })?;
+        if T::DELIVERY == Delivery::Reliable {
+            state
+                .unacked
+                .insert(sequence, (packet.clone(), Instant::now()));
+        }
+        self.send_bytes(peer, &packet)
+    }
+
+    /// Sends a message to every peer
+    pub fn broadcast<T: NetMessage>(&mut self, message: &T) -> Result<()> {
+        for peer in self.peers() {
+            self.send(peer, message)?;
+        }
+        Ok(())
+    }
+
+    /// Takes the messages of a type received since the last call, in the order of their channel
+    /// Note: messages that fail to deserialize are logged and dropped
+    pub fn receive<T: NetMessage>(&mut self) -> Vec<(PeerId, T)> {
+        let Some(messages) = self.inbox.remove(T::channel()) else {
+            return Vec::new();
+        };
+        messages
+            .into_iter()
+            .filter_map(|(peer, payload)| match serde_yaml::from_str(&payload) {
+                Ok(message) => Some((peer, message)),
+                Err(e) => {
+                    log_warn!("Dropped a {} from {}: {}", T::channel(), peer, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Reads every waiting packet, resends unacked messages and drops silent peers
+    pub fn poll(&mut self) {
+        let mut buffer = vec![0; MAX_PACKET_SIZE];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((length, address)) => self.handle_packet(PeerId(address), &buffer[..length]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                // a peer that went away makes the next read fail on some platforms
+                Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                Err(e) => {
+                    log_warn!("Failed to read from socket: {}", e);
+                    break;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let timed_out: Vec<PeerId> = self
+            .peers
+            .iter()
+            .filter(|(_, peer)| now.duration_since(peer.last_heard) > PEER_TIMEOUT)
+            .map(|(id, _)| *id)
+            .collect();
+        for peer in timed_out {
+            log!("Peer {} timed out", peer);
+            self.peers.remove(&peer);
+        }
+
+        let mut resends = Vec::new();
+        let mut heartbeats = Vec::new();
+        for (id, peer) in self.peers.iter_mut() {
+            for channel in peer.outgoing.values_mut() {
+                for (packet, sent) in channel.unacked.values_mut() {
+                    if now.duration_since(*sent) >= RESEND_INTERVAL {
+                        *sent = now;
+                        resends.push((*id, packet.clone()));
+                    }
+                }
+            }
+            if now.duration_since(peer.last_sent) >= HEARTBEAT_INTERVAL {
+                heartbeats.push(*id);
+            }
+        }
+        for (peer, packet) in resends {
+            if let Err(e) = self.send_bytes(peer, &packet) {
+                log_warn!("Failed to resend to {}: {}", peer, e);
+            }
+        }
+        for peer in heartbeats {
+            if let Err(e) = self.send_packet(peer, &Packet::Heartbeat) {
+                log_warn!("Failed to send a heartbeat to {}: {}", peer, e);
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, from: PeerId, bytes: &[u8]) {
+        let packet: Packet = match std::str::from_utf8(bytes)
+            .map_err(|e| anyhow!("{}", e))
+            .and_then(|text| serde_yaml::from_str(text).map_err(|e| anyhow!("{}", e)))
+        {
+            Ok(packet) => packet,
+            Err(e) => {
+                log_warn!("Dropped a malformed packet from {}: {}", from, e);
+                return;
+            }
+        };
+        let peer = self.peers.entry(from).or_insert_with(|| {
+            log!("Peer {} connected", from);
+            Peer::new()
+        });
+        peer.last_heard = Instant::now();
+
+        match packet {
+            Packet::Message {
+                channel,
+                reliable,
+                sequence,
+                payload,
+            } => {
+                let incoming = peer.incoming.entry(channel.clone()).or_default();
+                let ready = if reliable {
+                    incoming.receive_reliable(sequence, payload)
+                } else if incoming.receive_unreliable(sequence) {
+                    vec![payload]
+                } else {
+                    Vec::new()
+                };
+                self.inbox
+                    .entry(channel.clone())
+                    .or_default()
+                    .extend(ready.into_iter().map(|payload| (from, payload)));
+                if reliable
+                    && let Err(e) = self.send_packet(from, &Packet::Ack { channel, sequence })
+                {
+                    log_warn!("Failed to ack {}: {}", from, e);
+                }
+            }
+            Packet::Ack { channel, sequence } => {
+                if let Some(outgoing) = peer.outgoing.get_mut(&channel) {
+                    outgoing.unacked.remove(&sequence);
+                }
+            }
+            Packet::Heartbeat => {}
+        }
+    }
+
+    fn send_packet(&mut self, peer: PeerId, packet: &Packet) -> Result<()> {
+        let bytes = encode(packet)?;
+        self.send_bytes(peer, &bytes)
+    }
+
+    fn send_bytes(&mut self, peer: PeerId, bytes: &[u8]) -> Result<()> {
+        if let Some(state) = self.peers.get_mut(&peer) {
+            state.last_sent = Instant::now();
+        }
+        match self.socket.send_to(bytes, peer.0) {
+            Ok(_) => Ok(()),
+            // the socket buffer is full, reliable messages are resent later
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(anyhow!("Failed to send to {}: {}", peer, e)),
+        }
+    }
+}
+
+fn encode(packet: &Packet) -> Result<Vec<u8>> {
+    let bytes = serde_yaml::to_string(packet)?.into_bytes();
+    if bytes.len() > MAX_PACKET_SIZE {
+        return Err(anyhow!(
+            "Message is {} bytes, more than the {} that fit in a packet",
+            bytes.len(),
+            MAX_PACKET_SIZE
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Reads packets and resends unacked messages before the systems that read messages run
+#[update(priority = 90, while_paused = true)]
+pub fn net_update(world: &mut World) -> Result<()> {
+    if let Ok(net) = world.get_resource_mut::<Net>() {
+        net.poll();
+    }
+    Ok(())
+}
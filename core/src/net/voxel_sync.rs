@@ -0,0 +1,380 @@
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Resource, update};
+use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    log_warn,
+    net::{Delivery, Net, NetMessage, PeerId},
+    objects::world::World,
+    utils::flatten::flatten,
+    voxels::{
+        chunk::Chunk,
+        meshes::NeedsRemeshing,
+        voxel::{VoxelId, VoxelRegistry},
+    },
+};
+
+const CHUNK_VOLUME: usize = 32 * 32 * 32;
+
+/// Asks the server to change a voxel, sent by `World::edit_voxel` on clients
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VoxelEditRequest {
+    pub position: [i32; 3],
+    pub voxel: VoxelId,
+}
+
+impl NetMessage for VoxelEditRequest {
+    const DELIVERY: Delivery = Delivery::Reliable;
+}
+
+/// The voxels of one chunk the server changed in a frame, as (index in the chunk, voxel) pairs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VoxelDelta {
+    pub chunk: [i32; 3],
+    pub edits: Vec<(u16, VoxelId)>,
+}
+
+impl NetMessage for VoxelDelta {
+    const DELIVERY: Delivery = Delivery::Reliable;
+}
+
+/// Every voxel of a chunk, sent to peers that joined after it was loaded
+/// Note: run length encoded as `count*voxel` or `voxel` items split by commas
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChunkSnapshot {
+    pub chunk: [i32; 3],
+    pub runs: String,
+}
+
+impl NetMessage for ChunkSnapshot {
+    const DELIVERY: Delivery = Delivery::Reliable;
+}
+
+/// Which side of the voxel sync this world is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoxelSyncRole {
+    /// applies and checks every edit and sends the changes to the clients
+    Server,
+    /// sends edits to the server and applies what it sends back
+    Client { server: PeerId },
+}
+
+/// Keeps the voxels of every peer the same as the server's, on top of the `Net` resource
+/// Note: only loaded chunks are sent to joining peers, and a chunk must fit in one packet
+#[derive(Resource, Clone)]
+pub struct VoxelSync {
+    pub role: VoxelSyncRole,
+    /// extra checks the server runs on client edits, e.g. reach or permissions
+    pub validate: Option<fn(&World, PeerId, [i32; 3], VoxelId) -> bool>,
+    /// server edits of this frame by chunk, sent by `voxel_sync_system`
+    pending: HashMap<(i32, i32, i32), Vec<(u16, VoxelId)>>,
+    /// peers that were sent the loaded chunks
+    synced_peers: HashSet<PeerId>,
+    /// client snapshots of chunks that are not loaded here yet
+    held_snapshots: HashMap<(i32, i32, i32), Vec<VoxelId>>,
+    /// client edits to chunks that are not loaded here yet
+    held_edits: HashMap<(i32, i32, i32), Vec<(u16, VoxelId)>>,
+}
+
+impl VoxelSync {
+    pub fn server() -> Self {
+        Self::new(VoxelSyncRole::Server)
+    }
+
+    pub fn client(server: PeerId) -> Self {
+        Self::new(VoxelSyncRole::Client { server })
+    }
+
+    fn new(role: VoxelSyncRole) -> Self {
+        Self {
+            role,
+            validate: None,
+            pending: HashMap::new(),
+            synced_peers: HashSet::new(),
+            held_snapshots: HashMap::new(),
+            held_edits: HashMap::new(),
+        }
+    }
+}
+
+/// Splits a world position into its chunk and the index in the chunk
+fn chunk_index(position: [i32; 3]) -> ((i32, i32, i32), u16) {
+    let [x, y, z] = position;
+    let chunk = (x.div_euclid(32), y.div_euclid(32), z.div_euclid(32));
+    let index = flatten(
+        x.rem_euclid(32) as u32,
+        y.rem_euclid(32) as u32,
+        z.rem_euclid(32) as u32,
+        32,
+    );
+    (chunk, index as u16)
+}
+
+/// Run length encodes the voxels of a chunk
+pub fn encode_runs(voxels: &[VoxelId]) -> String {
+    let mut runs = Vec::new();
+    let mut iter = voxels.iter().peekable();
+    while let Some(&voxel) = iter.next() {
+        let mut count = 1;
+        while iter.next_if_eq(&&voxel).is_some() {
+            count += 1;
+        }
+        if count == 1 {
+            runs.push(voxel.to_string());
+        } else {
+            runs.push(format!("{}*{}", count, voxel));
+        }
+    }
+    runs.join(",")
+}
+
+/// Reads voxels written by `encode_runs`, errors unless they fill exactly one chunk
+pub fn decode_runs(runs: &str) -> Result<Vec<VoxelId>> {
+    let mut voxels = Vec::with_capacity(CHUNK_VOLUME);
+    for run in runs.split(',').filter(|run| !run.is_empty()) {
+        let (count, voxel) = match run.split_once('*') {
+            Some((count, voxel)) => (count.parse::<usize>()?, voxel.parse::<VoxelId>()?),
+            None => (1, run.parse::<VoxelId>()?),
+        };
+        if voxels.len() + count > CHUNK_VOLUME {
+            return Err(anyhow!(
+                "Chunk snapshot has more than {} voxels",
+                CHUNK_VOLUME
+            ));
+        }
+        voxels.extend(std::iter::repeat_n(voxel, count));
+    }
+    if voxels.len() != CHUNK_VOLUME {
+        return Err(anyhow!(
+            "Chunk snapshot has {} voxels, expected {}",
+            voxels.len(),
+            CHUNK_VOLUME
+        ));
+    }
+    Ok(voxels)
+}
+
+impl World {
+    /// Changes a voxel through the voxel sync, the server applies it and tells the clients, a
+    /// client asks the server and waits for the change to come back
+    /// Note: without a `VoxelSync` this is `set_voxel`
+    pub fn edit_voxel(&mut self, x: i32, y: i32, z: i32, voxel: VoxelId) -> Result<()> {
+        let role = self.get_resource::<VoxelSync>().map(|sync| sync.role).ok();
+        match role {
+            None => {
+                self.set_voxel(x, y, z, voxel);
+            }
+            Some(VoxelSyncRole::Server) => {
+                self.apply_server_edit([x, y, z], voxel);
+            }
+            Some(VoxelSyncRole::Client { server }) => {
+                self.get_resource_mut::<Net>()?.send(
+                    server,
+                    &VoxelEditRequest {
+                        position: [x, y, z],
+                        voxel,
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies an edit on the server and queues it for the clients
+    fn apply_server_edit(&mut self, position: [i32; 3], voxel: VoxelId) -> bool {
+        let [x, y, z] = position;
+        if self.get_voxel(x, y, z) == Some(voxel) || !self.set_voxel(x, y, z, voxel) {
+            return false;
+        }
+        let (chunk, index) = chunk_index(position);
+        if let Ok(sync) = self.get_resource_mut::<VoxelSync>() {
+            sync.pending.entry(chunk).or_default().push((index, voxel));
+        }
+        true
+    }
+
+    /// Runs the server checks on an edit from a client
+    fn is_valid_edit(&self, peer: PeerId, position: [i32; 3], voxel: VoxelId) -> bool {
+        let [x, y, z] = position;
+        if self.get_voxel(x, y, z).is_none() {
+            return false;
+        }
+        let is_known = self
+            .get_resource::<VoxelRegistry>()
+            .map(|registry| (voxel as usize) < registry.defs.len())
+            .unwrap_or(true);
+        if !is_known {
+            return false;
+        }
+        match self
+            .get_resource::<VoxelSync>()
+            .ok()
+            .and_then(|sync| sync.validate)
+        {
+            Some(validate) => validate(self, peer, position, voxel),
+            None => true,
+        }
+    }
+
+    /// Gets the voxels of a loaded chunk
+    fn chunk_voxels_mut(&mut self, chunk: (i32, i32, i32)) -> Option<&mut [VoxelId; CHUNK_VOLUME]> {
+        let id = *self.chunk_position_index.get(&chunk)?;
+        let object = self.scene.objects.get_mut(id)?;
+        object.add_tag(NeedsRemeshing);
+        object
+            .get_component_mut::<Chunk>()
+            .ok()
+            .map(|chunk| &mut *chunk.voxels)
+    }
+}
+
+fn run_server(world: &mut World) -> Result<()> {
+    let requests = world
+        .get_resource_mut::<Net>()?
+        .receive::<VoxelEditRequest>();
+    for (peer, request) in requests {
+        if world.is_valid_edit(peer, request.position, request.voxel) {
+            world.apply_server_edit(request.position, request.voxel);
+        } else {
+            log_warn!(
+                "Rejected a voxel edit at {:?} from {}",
+                request.position,
+                peer
+            );
+        }
+    }
+
+    // peers that joined get every loaded chunk, later changes reach them as deltas
+    let peers = world.get_resource::<Net>()?.peers();
+    let sync = world.get_resource_mut::<VoxelSync>()?;
+    sync.synced_peers.retain(|peer| peers.contains(peer));
+    let joined: Vec<PeerId> = peers
+        .into_iter()
+        .filter(|peer| !sync.synced_peers.contains(peer))
+        .collect();
+    sync.synced_peers.extend(joined.iter().copied());
+    let pending = std::mem::take(&mut sync.pending);
+
+    if !joined.is_empty() {
+        let snapshots: Vec<ChunkSnapshot> = world
+            .chunk_position_index
+            .iter()
+            .filter_map(|(&(x, y, z), &id)| {
+                let chunk = world.scene.objects.get(id)?.get_component::<Chunk>().ok()?;
+                Some(ChunkSnapshot {
+                    chunk: [x, y, z],
+                    runs: encode_runs(&chunk.voxels[..]),
+                })
+            })
+            .collect();
+        let net = world.get_resource_mut::<Net>()?;
+        for peer in joined {
+            for snapshot in &snapshots {
+                if let Err(e) = net.send(peer, snapshot) {
+                    log_warn!(
+                        "Failed to send chunk {:?} to {}: {}",
+                        snapshot.chunk,
+                        peer,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    let net = world.get_resource_mut::<Net>()?;
+    for ((x, y, z), edits) in pending {
+        net.broadcast(&VoxelDelta {
+            chunk: [x, y, z],
+            edits,
+        })?;
+    }
+    Ok(())
+}
+
+fn run_client(world: &mut World, server: PeerId) -> Result<()> {
+    let net = world.get_resource_mut::<Net>()?;
+    let snapshots = net.receive::<ChunkSnapshot>();
+    let deltas = net.receive::<VoxelDelta>();
+
+    let sync = world.get_resource_mut::<VoxelSync>()?;
+    for (peer, snapshot) in snapshots {
+        if peer != server {
+            continue;
+        }
+        let [x, y, z] = snapshot.chunk;
+        match decode_runs(&snapshot.runs) {
+            Ok(voxels) => {
+                sync.held_edits.remove(&(x, y, z));
+                sync.held_snapshots.insert((x, y, z), voxels);
+            }
+            Err(e) => {
+                log_warn!("Dropped chunk {:?}: {}", snapshot.chunk, e);
+            }
+        }
+    }
+    for (peer, delta) in deltas {
+        if peer != server {
+            continue;
+        }
+        let [x, y, z] = delta.chunk;
+        match sync.held_snapshots.get_mut(&(x, y, z)) {
+            Some(voxels) => {
+                for (index, voxel) in delta.edits {
+                    if let Some(slot) = voxels.get_mut(index as usize) {
+                        *slot = voxel;
+                    }
+                }
+            }
+            None => sync
+                .held_edits
+                .entry((x, y, z))
+                .or_default()
+                .extend(delta.edits),
+        }
+    }
+
+    // everything is held until its chunk is loaded here
+    let snapshots = std::mem::take(&mut sync.held_snapshots);
+    let edits = std::mem::take(&mut sync.held_edits);
+    let mut still_held = (HashMap::new(), HashMap::new());
+    for (chunk, voxels) in snapshots {
+        match world.chunk_voxels_mut(chunk) {
+            Some(target) => target.copy_from_slice(&voxels),
+            None => {
+                still_held.0.insert(chunk, voxels);
+            }
+        }
+    }
+    for (chunk, chunk_edits) in edits {
+        match world.chunk_voxels_mut(chunk) {
+            Some(target) => {
+                for (index, voxel) in chunk_edits {
+                    if let Some(slot) = target.get_mut(index as usize) {
+                        *slot = voxel;
+                    }
+                }
+            }
+            None => {
+                still_held.1.insert(chunk, chunk_edits);
+            }
+        }
+    }
+    let sync = world.get_resource_mut::<VoxelSync>()?;
+    sync.held_snapshots = still_held.0;
+    sync.held_edits = still_held.1;
+    Ok(())
+}
+
+/// Applies and sends voxel edits, after `net_update` read the packets of the frame
+#[update(priority = 80, while_paused = true)]
+pub fn voxel_sync_system(world: &mut World) -> Result<()> {
+    let Ok(sync) = world.get_resource::<VoxelSync>() else {
+        return Ok(());
+    };
+    match sync.role {
+        VoxelSyncRole::Server => run_server(world),
+        VoxelSyncRole::Client { server } => run_client(world, server),
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    log_warn,
+    objects::{
+        Object,
+        commands::Commands,
+        component::{BoxedComponent, Component},
+        observers::ComponentChange,
+        scene::ObjectId,
+        world::World,
+    },
+};
+
+/// A set of components added to an object together, a single component, a tuple of up to 12
+/// bundles or a struct with `#[derive(Bundle)]`
+pub trait Bundle: Send + 'static {
+    /// How many components the bundle adds, used to reserve space up front
+    fn component_count() -> usize
+    where
+        Self: Sized;
+
+    /// Moves the components of the bundle onto the end of `components`
+    fn push_components(self, components: &mut Vec<BoxedComponent>);
+}
+
+impl<T: Component + Send + 'static> Bundle for T {
+    fn component_count() -> usize {
+        1
+    }
+
+    fn push_components(self, components: &mut Vec<BoxedComponent>) {
+        components.push(Box::new(self));
+    }
+}
+
+macro_rules! impl_bundle_for_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: Bundle),*> Bundle for ($($name,)*) {
+            fn component_count() -> usize {
+                0 $(+ $name::component_count())*
+            }
+
+            #[allow(non_snake_case)]
+            fn push_components(self, components: &mut Vec<BoxedComponent>) {
+                let ($($name,)*) = self;
+                $($name.push_components(components);)*
+            }
+        }
+    };
+}
+
+impl_bundle_for_tuple!(A);
+impl_bundle_for_tuple!(A, B);
+impl_bundle_for_tuple!(A, B, C);
+impl_bundle_for_tuple!(A, B, C, D);
+impl_bundle_for_tuple!(A, B, C, D, E);
+impl_bundle_for_tuple!(A, B, C, D, E, F);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G, H);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+impl Object {
+    /// Creates an object with the components of a bundle
+    pub fn from_bundle<B: Bundle>(bundle: B) -> Self {
+        let mut object = Object::new();
+        object.components.reserve(B::component_count());
+        object.insert_bundle(bundle);
+        object
+    }
+
+    /// Adds the components of a bundle, components the object already has are skipped
+    pub fn add_bundle<B: Bundle>(&mut self, bundle: B) -> Self {
+        self.insert_bundle(bundle);
+        self.clone()
+    }
+
+    fn insert_bundle<B: Bundle>(&mut self, bundle: B) {
+        let start = self.components.len();
+        bundle.push_components(&mut self.components);
+
+        let mut i = start;
+        while i < self.components.len() {
+            let type_id = self.components[i].as_any().type_id();
+            if self.components[..i]
+                .iter()
+                .any(|c| c.as_any().type_id() == type_id)
+            {
+                log_warn!("You can only have one of any component on an entity");
+                self.components.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        self.sort_components();
+    }
+}
+
+impl World {
+    /// Adds a root object with the components of a bundle
+    pub fn spawn<B: Bundle>(&mut self, bundle: B) -> ObjectId {
+        self.add_object(Object::from_bundle(bundle))
+    }
+
+    /// Adds the components of a bundle to an object, replacing its components of the same types,
+    /// and runs the `OnInsert` observers
+    /// returns false if the object doesn't exist
+    pub fn insert_bundle<B: Bundle>(&mut self, id: ObjectId, bundle: B) -> bool {
+        let Some(object) = self.scene.objects.get_mut(id) else {
+            return false;
+        };
+        let mut components = Vec::with_capacity(B::component_count());
+        bundle.push_components(&mut components);
+
+        let mut inserted = Vec::with_capacity(components.len());
+        for component in components {
+            let type_id = component.as_any().type_id();
+            match object
+                .components
+                .iter_mut()
+                .find(|c| c.as_any().type_id() == type_id)
+            {
+                Some(existing) => *existing = component,
+                None => object.push_component(component),
+            }
+            inserted.push(type_id);
+        }
+        for type_id in inserted {
+            self.trigger(id, type_id, ComponentChange::Inserted);
+        }
+        true
+    }
+
+    /// Adds a root object for every bundle, space for all of them is reserved up front
+    /// returns the ids of the new objects in order
+    pub fn spawn_batch<B: Bundle>(
+        &mut self,
+        bundles: impl IntoIterator<Item = B>,
+    ) -> Vec<ObjectId> {
+        let bundles = bundles.into_iter();
+        let (count, _) = bundles.size_hint();
+        self.scene.objects.reserve(count);
+
+        let mut ids = Vec::with_capacity(count);
+        for bundle in bundles {
+            ids.push(self.spawn(bundle));
+        }
+        ids
+    }
+}
+
+impl Commands {
+    /// Adds the components of a bundle to an object, see `World::insert_bundle`
+    /// Note: does nothing if the object was removed
+    pub fn insert_bundle<B: Bundle>(&mut self, id: ObjectId, bundle: B) -> &mut Self {
+        self.add(move |world| {
+            world.insert_bundle(id, bundle);
+        })
+    }
+}
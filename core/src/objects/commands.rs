@@ -0,0 +1,116 @@
+use crate::objects::{
+    Object, component::Component, resource::Resource, scene::ObjectId, world::World,
+};
+
+type Command = Box<dyn FnOnce(&mut World) + Send>;
+
+/// A list of changes to the world that are applied later
+/// Useful when the world is borrowed, for example while iterating a query
+/// Apply with `apply`, or pass to `World::queue_commands` to apply them once the running system
+/// returns
+#[derive(Default)]
+pub struct Commands {
+    commands: Vec<Command>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Queues any change to the world
+    pub fn add(&mut self, command: impl FnOnce(&mut World) + Send + 'static) -> &mut Self {
+        self.commands.push(Box::new(command));
+        self
+    }
+
+    /// Adds an object as a root object
+    pub fn spawn(&mut self, object: Object) -> &mut Self {
+        self.add(move |world| {
+            world.add_object(object);
+        })
+    }
+
+    /// Adds every object as a root object in one command
+    pub fn spawn_batch(&mut self, objects: impl IntoIterator<Item = Object>) -> &mut Self {
+        let objects: Vec<Object> = objects.into_iter().collect();
+        self.add(move |world| {
+            for object in objects {
+                world.add_object(object);
+            }
+        })
+    }
+
+    /// Adds a component to an object, replacing its component of the same type, and runs the
+    /// `OnInsert` observers
+    /// Note: does nothing if the object was removed
+    pub fn insert<T: Component + 'static>(&mut self, id: ObjectId, component: T) -> &mut Self {
+        self.add(move |world| {
+            world.insert_component(id, component);
+        })
+    }
+
+    /// Removes a component from an object, running its `on_remove` hook
+    pub fn remove<T: Component + 'static>(&mut self, id: ObjectId) -> &mut Self {
+        self.add(move |world| {
+            world.remove_component::<T>(id);
+        })
+    }
+
+    /// Removes an object and all of its descendants in one command, see `World::despawn_recursive`
+    /// Note: does nothing if the object was removed
+    pub fn despawn_recursive(&mut self, id: ObjectId) -> &mut Self {
+        self.add(move |world| {
+            world.despawn_recursive(id);
+        })
+    }
+
+    /// Removes only an object, its children are moved up to its parent instead of being orphaned
+    /// Note: does nothing if the object was removed
+    pub fn despawn(&mut self, id: ObjectId) -> &mut Self {
+        self.add(move |world| {
+            world.despawn(id);
+        })
+    }
+
+    pub fn insert_resource<T: Resource + Send + 'static>(&mut self, resource: T) -> &mut Self {
+        self.add(move |world| {
+            world.insert_resource(resource);
+        })
+    }
+
+    pub fn remove_resource<T: Resource + 'static>(&mut self) -> &mut Self {
+        self.add(|world| {
+            world.remove_resource::<T>();
+        })
+    }
+
+    /// Applies every command in the order they were queued
+    pub fn apply(self, world: &mut World) {
+        for command in self.commands {
+            command(world);
+        }
+    }
+}
+
+impl World {
+    /// Queues commands to be applied once the running system returns
+    pub fn queue_commands(&mut self, commands: Commands) {
+        self.queued_commands.commands.extend(commands.commands);
+    }
+
+    /// Applies every queued command, commands queued by the commands run too
+    pub fn apply_queued_commands(&mut self) {
+        while !self.queued_commands.is_empty() {
+            std::mem::take(&mut self.queued_commands).apply(self);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use apostasy_macros::{Resource, late_update};
+
+use crate::objects::world::World;
+
+/// Runs `World::compact` every `interval` frames, for games with heavy spawn and removal churn
+/// Note: the objects themselves stay dense on their own, this frees the spare room removals
+/// leave in the components, tags and children of the objects that are left
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AutoCompact {
+    pub interval: u32,
+    frames: u32,
+}
+
+impl AutoCompact {
+    pub fn every(interval: u32) -> Self {
+        Self {
+            interval: interval.max(1),
+            frames: 0,
+        }
+    }
+}
+
+impl Default for AutoCompact {
+    fn default() -> Self {
+        Self::every(600)
+    }
+}
+
+/// Compacts the world once the `AutoCompact` interval has passed, if something inserted it
+#[late_update]
+pub fn auto_compact(world: &mut World) -> Result<()> {
+    let Ok(auto_compact) = world.get_resource_mut::<AutoCompact>() else {
+        return Ok(());
+    };
+    auto_compact.frames += 1;
+    if auto_compact.frames < auto_compact.interval {
+        return Ok(());
+    }
+    auto_compact.frames = 0;
+    world.compact();
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use std::any::Any;
 
+use crate::objects::{registry::registered, scene::ObjectId, world::World};
+
 pub type BoxedComponent = Box<dyn Component + Send + Sync>;
 
 pub trait Component: Send + Sync + 'static + ComponentContainer + std::fmt::Debug {
@@ -9,6 +11,10 @@ pub trait Component: Send + Sync + 'static + ComponentContainer + std::fmt::Debu
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn type_name(&self) -> &'static str;
+    /// set with `#[component_group("name")]`, objects keep the components of a group together
+    fn group(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 pub trait ComponentContainer {
@@ -29,13 +35,97 @@ impl Clone for BoxedComponent {
 
 pub struct ComponentRegistration {
     pub type_name: &'static str,
+    /// the module the component is declared in, e.g. `apostasy_core::physics::velocity`
+    pub module: &'static str,
+    /// the `///` doc comment of the struct, one line per line, empty if it has none
+    pub doc: &'static str,
     pub create: fn() -> BoxedComponent,
+    /// None for components that are not `#[component_serde]`
+    pub serialize: Option<fn(&BoxedComponent) -> anyhow::Result<serde_yaml::Value>>,
     pub deserialize: fn(&mut BoxedComponent, &serde_yaml::Value) -> anyhow::Result<()>,
+    /// set with `#[component_on_remove(func)]`, releases what the component holds, e.g. GPU buffers
+    pub on_remove: Option<fn(&mut World, ObjectId, &mut BoxedComponent)>,
+    /// the named fields of the component, without the ones serde skips
+    pub fields: &'static [FieldInfo],
+    /// reads one field as yaml, None for components that are not `#[component_serde]`
+    pub get_field: Option<fn(&BoxedComponent, &str) -> anyhow::Result<serde_yaml::Value>>,
+    /// replaces one field from yaml, None for components that are not `#[component_serde]`
+    pub set_field: Option<fn(&mut BoxedComponent, &str, &serde_yaml::Value) -> anyhow::Result<()>>,
+    /// set with `#[component_version(n)]`, saved with the component, 0 if it is not set
+    pub version: u32,
+    /// set with `#[component_migrate(func)]`, upgrades a value saved at an older version
+    pub migrate: Option<fn(serde_yaml::Value, u32) -> anyhow::Result<serde_yaml::Value>>,
+    /// set with `#[component_group("name")]`, see `Component::group`
+    pub group: Option<&'static str>,
+}
+
+impl ComponentRegistration {
+    /// Brings a value saved at `from_version` up to `version` with the migrate hook
+    /// Note: values that are already current, or have no hook, are returned as they are
+    pub fn upgrade(
+        &self,
+        value: &serde_yaml::Value,
+        from_version: u32,
+    ) -> anyhow::Result<serde_yaml::Value> {
+        match self.migrate {
+            Some(migrate) if from_version < self.version => migrate(value.clone(), from_version)
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to migrate {} from version {}: {}",
+                        self.type_name,
+                        from_version,
+                        e
+                    )
+                }),
+            _ => Ok(value.clone()),
+        }
+    }
+}
+
+/// A field of a component, generated by the `Component` derive
+#[derive(Clone, Copy, Debug)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    /// the type as written in the struct, e.g. `Vector3<f32>`
+    pub type_name: &'static str,
+    /// the `///` doc comment of the field, empty if it has none
+    pub doc: &'static str,
+    pub inspect: InspectOptions,
+}
+
+/// How the editor inspector draws a field, set with `#[inspect(...)]` on the field, e.g.
+/// `#[inspect(range = 0.0..=1.0, label = "Max Speed")]`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InspectOptions {
+    /// the name shown instead of the field name
+    pub label: Option<&'static str>,
+    /// numbers are drawn as a slider between these
+    pub range: Option<(f64, f64)>,
+    /// three or four numbers are drawn as a color picker, 0 to 1 for floats and 0 to 255 otherwise
+    pub color: bool,
+    /// drawn but can't be edited
+    pub readonly: bool,
+    /// not drawn, it can still be read and set by name
+    pub hidden: bool,
 }
 
+#[cfg(not(target_family = "wasm"))]
 inventory::collect!(ComponentRegistration);
 
 pub fn get_component_registration(type_name: &str) -> Option<&'static ComponentRegistration> {
-    inventory::iter::<ComponentRegistration>()
+    registered::<ComponentRegistration>()
         .find(|r| r.type_name.to_lowercase() == type_name.to_lowercase())
 }
+
+/// Gets the registration of a component from its full type name
+pub fn get_component_registration_of(
+    component: &BoxedComponent,
+) -> Option<&'static ComponentRegistration> {
+    let name = short_type_name(component.type_name());
+    registered::<ComponentRegistration>().find(|r| r.type_name == name)
+}
+
+/// Gets the last segment of a type name, `a::b::Transform` is `Transform`
+pub fn short_type_name(type_name: &str) -> &str {
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
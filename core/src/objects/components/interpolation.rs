@@ -0,0 +1,96 @@
+use anyhow::Result;
+use apostasy_macros::Component;
+use cgmath::{Quaternion, Vector3, Zero};
+
+use crate::{
+    math::slerp,
+    objects::{
+        components::transform::{Transform, transform_update},
+        world::World,
+    },
+};
+
+/// Renders an object between its last two fixed update states instead of jumping every tick
+/// Note: only the fixed update movement is smoothed, anything moving the object in update is
+/// drawn a tick late
+#[derive(Component, Clone, Debug)]
+#[component_group("render")]
+pub struct InterpolatedTransform {
+    pub previous_position: Vector3<f32>,
+    pub previous_rotation: Quaternion<f32>,
+    pub current_position: Vector3<f32>,
+    pub current_rotation: Quaternion<f32>,
+    /// false until the first fixed update recorded a state
+    recorded: bool,
+}
+
+impl Default for InterpolatedTransform {
+    fn default() -> Self {
+        Self {
+            previous_position: Vector3::zero(),
+            previous_rotation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            current_position: Vector3::zero(),
+            current_rotation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            recorded: false,
+        }
+    }
+}
+
+impl InterpolatedTransform {
+    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> Result<()> {
+        Ok(())
+    }
+
+    /// Gets the position and rotation `fraction` of the way from the previous to the current state
+    pub fn lerp(&self, fraction: f32) -> (Vector3<f32>, Quaternion<f32>) {
+        (
+            self.previous_position + (self.current_position - self.previous_position) * fraction,
+            slerp(self.previous_rotation, self.current_rotation, fraction),
+        )
+    }
+
+    /// Forgets the previous state, call after teleporting so the object does not slide there
+    pub fn snap(&mut self, transform: &Transform) {
+        self.current_position = transform.global_position;
+        self.current_rotation = transform.global_rotation;
+        self.previous_position = self.current_position;
+        self.previous_rotation = self.current_rotation;
+        self.recorded = true;
+    }
+
+    fn record(&mut self, transform: &Transform) {
+        if !self.recorded {
+            self.snap(transform);
+            return;
+        }
+        self.previous_position = self.current_position;
+        self.previous_rotation = self.current_rotation;
+        self.current_position = transform.global_position;
+        self.current_rotation = transform.global_rotation;
+    }
+}
+
+impl World {
+    /// Records the state of every interpolated object after a fixed update step
+    pub(crate) fn record_interpolated_transforms(&mut self) {
+        if !self
+            .scene
+            .objects
+            .values()
+            .any(|object| object.has_component::<InterpolatedTransform>())
+        {
+            return;
+        }
+
+        // fixed systems move the local transform, the global one is updated once a frame
+        let _ = transform_update(self);
+        for (_, object) in self.scene.objects.iter_mut() {
+            let Ok(transform) = object.get_component::<Transform>().cloned() else {
+                continue;
+            };
+            if let Ok(interpolated) = object.get_component_mut::<InterpolatedTransform>() {
+                interpolated.record(&transform);
+            }
+        }
+    }
+}
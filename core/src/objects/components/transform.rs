@@ -1,14 +1,16 @@
 use anyhow::Result;
 use apostasy_macros::{Component, update};
 use cgmath::{Deg, Euler, Quaternion, Rotation, Vector3};
+use serde::{Deserialize, Serialize};
 
+use crate::math::look_euler_angles;
+pub use crate::math::{FORWARD, RIGHT, UP};
 use crate::objects::{scene::ObjectId, world::World};
 
-pub const UP: Vector3<f32> = Vector3::new(0.0, 1.0, 0.0);
-pub const RIGHT: Vector3<f32> = Vector3::new(1.0, 0.0, 0.0);
-pub const FORWARD: Vector3<f32> = Vector3::new(0.0, 0.0, -1.0);
-
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[component_group("render")]
+#[serde(default)]
 pub struct Transform {
     pub local_position: Vector3<f32>,
     pub local_euler_angles: Vector3<f32>,
@@ -36,9 +38,6 @@ impl Default for Transform {
 }
 
 impl Transform {
-    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> anyhow::Result<()> {
-        Ok(())
-    }
     pub fn calculate_up(&self) -> Vector3<f32> {
         self.local_rotation.rotate_vector(UP)
     }
@@ -61,9 +60,19 @@ impl Transform {
     pub fn calculate_global_right(&self) -> Vector3<f32> {
         self.global_rotation.rotate_vector(RIGHT)
     }
+
+    /// Points the local forward at a target position
+    /// Note: the rotation is applied on the next transform update
+    pub fn look_at(&mut self, target: Vector3<f32>) {
+        let direction = target - self.global_position;
+        if direction == Vector3::new(0.0, 0.0, 0.0) {
+            return;
+        }
+        self.local_euler_angles = look_euler_angles(direction);
+    }
 }
 
-#[update]
+#[update(while_paused = true)]
 pub fn transform_update(world: &mut World) -> Result<()> {
     let scene = &mut world.scene;
 
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    console::ConsoleCommand,
+    log, log_warn,
+    objects::{
+        component::ComponentRegistration,
+        registry::registered,
+        resource::ResourceRegistration,
+        systems::{
+            FixedUpdateSystem, LateUpdateSystem, ShutdownSystem, StartSystem, SystemKind,
+            UpdateSystem,
+        },
+        tag::TagRegistration,
+        world::World,
+    },
+};
+
+/// What was registered through inventory and the mistakes found in it, see
+/// `World::report_registrations`
+#[derive(Clone, Debug, Default)]
+pub struct RegistrationReport {
+    pub components: usize,
+    pub tags: usize,
+    pub systems: usize,
+    pub commands: usize,
+    /// components that are not `#[component_serde]`, scenes are saved without them
+    pub unsaved_components: Vec<&'static str>,
+    /// names that are registered more than once, lookups by name only find one of them
+    pub problems: Vec<String>,
+}
+
+/// Groups names and keeps the ones registered more than once, with where each came from
+fn duplicates<'a>(names: impl Iterator<Item = (&'a str, String)>) -> Vec<(&'a str, Vec<String>)> {
+    let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (name, source) in names {
+        groups.entry(name).or_default().push(source);
+    }
+    groups
+        .into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .collect()
+}
+
+impl RegistrationReport {
+    /// Checks every component, tag, resource, system and console command registration
+    pub fn new() -> Self {
+        let mut report = Self::default();
+
+        let components: Vec<&ComponentRegistration> =
+            registered::<ComponentRegistration>().collect();
+        let tags: Vec<&TagRegistration> = registered::<TagRegistration>().collect();
+        report.components = components.len();
+        report.tags = tags.len();
+        report.unsaved_components = components
+            .iter()
+            .filter(|c| c.serialize.is_none())
+            .map(|c| c.type_name)
+            .collect();
+        report.unsaved_components.sort();
+
+        // components and tags are added by name from scenes and the editor, so they share names
+        let types = components
+            .iter()
+            .map(|c| (c.type_name, "component".to_string()))
+            .chain(tags.iter().map(|t| (t.type_name, "tag".to_string())));
+        for (name, kinds) in duplicates(types) {
+            report.problems.push(format!(
+                "{} is registered {} times ({}), scenes and the editor only find one of them",
+                name,
+                kinds.len(),
+                kinds.join(", ")
+            ));
+        }
+        let resources =
+            registered::<ResourceRegistration>().map(|r| (r.type_name, "resource".to_string()));
+        for (name, sources) in duplicates(resources) {
+            report.problems.push(format!(
+                "Resource {} is registered {} times",
+                name,
+                sources.len()
+            ));
+        }
+
+        // `system enable <name>` and `before`/`after` match names across packages
+        let systems = registered::<StartSystem>()
+            .map(|s| (s.name, s.package, SystemKind::Start))
+            .chain(registered::<UpdateSystem>().map(|s| (s.name, s.package, SystemKind::Update)))
+            .chain(
+                registered::<FixedUpdateSystem>()
+                    .map(|s| (s.name, s.package, SystemKind::FixedUpdate)),
+            )
+            .chain(
+                registered::<LateUpdateSystem>()
+                    .map(|s| (s.name, s.package, SystemKind::LateUpdate)),
+            )
+            .chain(
+                registered::<ShutdownSystem>().map(|s| (s.name, s.package, SystemKind::Shutdown)),
+            )
+            .map(|(name, package, kind)| {
+                (name, format!("{}::{} ({})", package, name, kind.label()))
+            })
+            .collect::<Vec<_>>();
+        report.systems = systems.len();
+        for (name, sources) in duplicates(systems.into_iter()) {
+            report.problems.push(format!(
+                "The system name {} is used {} times: {}",
+                name,
+                sources.len(),
+                sources.join(", ")
+            ));
+        }
+
+        let commands = registered::<ConsoleCommand>()
+            .map(|c| (c.name, c.package.to_string()))
+            .collect::<Vec<_>>();
+        report.commands = commands.len();
+        for (name, packages) in duplicates(commands.into_iter()) {
+            report.problems.push(format!(
+                "The console command {} is registered by {}, only one of them runs",
+                name,
+                packages.join(", ")
+            ));
+        }
+
+        report
+    }
+}
+
+impl World {
+    /// Checks the inventory registrations and prints what is wrong to the log and console
+    /// Note: called once by `Core::new`, so silent registration mistakes show up at startup
+    pub fn report_registrations(&mut self) -> RegistrationReport {
+        let report = RegistrationReport::new();
+
+        let summary = format!(
+            "Registered {} components, {} tags, {} systems and {} console commands, {} problems",
+            report.components,
+            report.tags,
+            report.systems,
+            report.commands,
+            report.problems.len()
+        );
+        log!("{}", summary);
+        self.console_print(summary);
+
+        for problem in &report.problems {
+            log_warn!("{}", problem);
+            self.console_print(format!("Warning: {}", problem));
+        }
+        if !report.unsaved_components.is_empty() {
+            let unsaved = format!(
+                "These components are not #[component_serde] and are not saved with scenes: {}",
+                report.unsaved_components.join(", ")
+            );
+            log_warn!("{}", unsaved);
+            self.console_print(format!("Warning: {}", unsaved));
+        }
+
+        report
+    }
+}
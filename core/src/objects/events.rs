@@ -0,0 +1,150 @@
+use std::any::TypeId;
+
+use anyhow::Result;
+use apostasy_macros::Resource;
+
+use crate::objects::world::World;
+
+/// A double buffered channel of events of one type
+/// Events sent during a frame are read during the next frame, the buffers are swapped at the end
+/// of late update so every system that runs once a frame sees every event exactly once
+/// Note: register the event with `World::add_event` so the buffers are swapped
+#[derive(Resource, Clone)]
+pub struct Events<T: Clone + Send + Sync + 'static> {
+    /// the events sent last frame
+    previous: Vec<T>,
+    /// the events sent so far this frame
+    current: Vec<T>,
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Events<T> {
+    pub fn send(&mut self, event: T) {
+        self.current.push(event);
+    }
+
+    /// Gets the events sent last frame
+    pub fn read(&self) -> impl Iterator<Item = &T> {
+        self.previous.iter()
+    }
+
+    /// Gets the events sent so far this frame, for systems that need to react the same frame
+    pub fn read_current(&self) -> impl Iterator<Item = &T> {
+        self.current.iter()
+    }
+
+    /// Drops last frame's events and makes this frame's readable
+    pub fn update(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    /// Drops every event in both buffers
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.current.clear();
+    }
+
+    /// Gets the number of events readable this frame
+    pub fn len(&self) -> usize {
+        self.previous.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty()
+    }
+}
+
+/// Sends events of one type, get one with `World::event_writer`
+pub struct EventWriter<'w, T: Clone + Send + Sync + 'static> {
+    events: &'w mut Events<T>,
+}
+
+impl<T: Clone + Send + Sync + 'static> EventWriter<'_, T> {
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        self.events.current.extend(events);
+    }
+}
+
+/// Reads events of one type, get one with `World::event_reader`
+pub struct EventReader<'w, T: Clone + Send + Sync + 'static> {
+    events: &'w Events<T>,
+}
+
+impl<'w, T: Clone + Send + Sync + 'static> EventReader<'w, T> {
+    /// Gets the events sent last frame
+    pub fn read(&self) -> impl Iterator<Item = &'w T> + use<'w, T> {
+        self.events.previous.iter()
+    }
+
+    /// Gets the events sent so far this frame
+    pub fn read_current(&self) -> impl Iterator<Item = &'w T> + use<'w, T> {
+        self.events.current.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+/// Swaps the buffers of one event type
+fn update_events<T: Clone + Send + Sync + 'static>(world: &mut World) {
+    if let Ok(events) = world.get_resource_mut::<Events<T>>() {
+        events.update();
+    }
+}
+
+impl World {
+    /// Inserts the Events<T> resource and swaps its buffers at the end of every late update
+    /// Note: adding the same event twice keeps the existing events
+    pub fn add_event<T: Clone + Send + Sync + 'static>(&mut self) -> &mut Self {
+        if !self.has_resource::<Events<T>>() {
+            self.insert_resource(Events::<T>::default());
+        }
+        self.event_updaters
+            .insert(TypeId::of::<T>(), update_events::<T>);
+        self
+    }
+
+    pub fn event_writer<T: Clone + Send + Sync + 'static>(&mut self) -> Result<EventWriter<'_, T>> {
+        Ok(EventWriter {
+            events: self.get_resource_mut::<Events<T>>()?,
+        })
+    }
+
+    pub fn event_reader<T: Clone + Send + Sync + 'static>(&self) -> Result<EventReader<'_, T>> {
+        Ok(EventReader {
+            events: self.get_resource::<Events<T>>()?,
+        })
+    }
+
+    /// Sends a single event, errors if the event was never added
+    pub fn send_event<T: Clone + Send + Sync + 'static>(&mut self, event: T) -> Result<()> {
+        self.get_resource_mut::<Events<T>>()?.send(event);
+        Ok(())
+    }
+
+    /// Swaps the buffers of every added event
+    pub(crate) fn update_events(&mut self) {
+        let updaters: Vec<fn(&mut World)> = self.event_updaters.values().copied().collect();
+        for updater in updaters {
+            updater(self);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    any::TypeId,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use anyhow::{Result, anyhow};
+use apostasy_macros::Component;
+use hashbrown::{HashMap, HashSet};
+
+use crate::objects::{
+    Object,
+    component::{BoxedComponent, Component},
+    scene::ObjectId,
+    world::World,
+};
+
+static NEXT_WORLD_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Tells worlds apart, every `World` gets its own when it is created, forks included
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WorldId(pub u32);
+
+impl Default for WorldId {
+    fn default() -> Self {
+        Self(NEXT_WORLD_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// An ObjectId with the world it belongs to, so it is not used on another world by mistake
+/// Note: make one with `World::world_object_id` and check it with `World::resolve`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WorldObjectId {
+    pub world: WorldId,
+    pub id: ObjectId,
+}
+
+/// Put on the objects `extract_into` makes, the object they were copied from
+#[derive(Component, Clone, Debug)]
+pub struct ExtractedFrom(pub WorldObjectId);
+
+impl Default for ExtractedFrom {
+    /// An id no world resolves, `extract_into` sets the real one
+    fn default() -> Self {
+        Self(WorldObjectId {
+            world: WorldId(u32::MAX),
+            id: ObjectId::default(),
+        })
+    }
+}
+
+impl ExtractedFrom {
+    /// The source object lives in another world, so there is nothing to load
+    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// What `extract_into` copies out of a world and what it made in it
+#[derive(Clone, Default)]
+pub(crate) struct Extraction {
+    /// the components copied out of this world
+    components: HashSet<TypeId>,
+    /// the object made in this world for each object extracted into it
+    objects: HashMap<WorldObjectId, ObjectId>,
+}
+
+impl World {
+    pub fn id(&self) -> WorldId {
+        self.id
+    }
+
+    /// Gets an id of an object of this world that remembers the world
+    pub fn world_object_id(&self, id: ObjectId) -> WorldObjectId {
+        WorldObjectId { world: self.id, id }
+    }
+
+    /// Gets the ObjectId of an id made by `world_object_id`
+    /// errors if it belongs to another world or the object was removed
+    pub fn resolve(&self, id: WorldObjectId) -> Result<ObjectId> {
+        if id.world != self.id {
+            return Err(anyhow!(
+                "Object {:?} belongs to world {}, not world {}",
+                id.id,
+                id.world.0,
+                self.id.0
+            ));
+        }
+        if !self.scene.objects.contains_key(id.id) {
+            return Err(anyhow!("Object {:?} was removed", id.id));
+        }
+        Ok(id.id)
+    }
+
+    /// Makes `extract_into` copy a component out of this world, e.g. `Transform` and
+    /// `ModelRenderer` for a render world
+    pub fn extract_component<T: Component + 'static>(&mut self) -> &mut Self {
+        self.extraction.components.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// Copies the components added with `extract_component` into another world, one object there
+    /// for every object here that has any of them, tagged with `ExtractedFrom`
+    /// Note: objects made by an earlier extract are reused, and removed once their object is gone
+    /// or has none of the components, so the target mirrors one world at a time, the hierarchy
+    /// is not copied
+    pub fn extract_into(&self, target: &mut World) {
+        if self.extraction.components.is_empty() {
+            return;
+        }
+
+        let mut extracted = HashSet::new();
+        for (id, object) in self.scene.objects.iter() {
+            let mut components: Vec<BoxedComponent> = object
+                .components
+                .iter()
+                .filter(|component| {
+                    self.extraction
+                        .components
+                        .contains(&component.as_any().type_id())
+                })
+                .cloned()
+                .collect();
+            if components.is_empty() {
+                continue;
+            }
+            let source = self.world_object_id(id);
+            extracted.insert(source);
+            components.push(Box::new(ExtractedFrom(source)));
+
+            let existing = target
+                .extraction
+                .objects
+                .get(&source)
+                .copied()
+                .filter(|id| target.scene.objects.contains_key(*id));
+            match existing {
+                Some(copy) => target.scene.objects[copy].components = components,
+                None => {
+                    let mut copy = Object::new();
+                    copy.name = object.name.clone();
+                    copy.components = components;
+                    let copy = target.add_object(copy);
+                    target.extraction.objects.insert(source, copy);
+                }
+            }
+        }
+
+        let gone: Vec<(WorldObjectId, ObjectId)> = target
+            .extraction
+            .objects
+            .iter()
+            .filter(|(source, _)| !extracted.contains(*source))
+            .map(|(source, copy)| (*source, *copy))
+            .collect();
+        for (source, copy) in gone {
+            target.extraction.objects.remove(&source);
+            // no hooks, the copies share buffers and handles with the objects they came from
+            if target.scene.objects.contains_key(copy) {
+                target.scene.remove_object(copy);
+            }
+        }
+    }
+}
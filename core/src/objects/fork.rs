@@ -0,0 +1,127 @@
+use apostasy_macros::Resource;
+
+use crate::{
+    log,
+    objects::{
+        resource::Resource,
+        resources::{
+            cursor_manager::CursorManager, input_manager::InputManager,
+            window_manager::WindowManager,
+        },
+        scene::ObjectId,
+        world::World,
+    },
+    rendering::shared::{released_buffers::ReleasedBuffers, rendering_config::RenderingConfig},
+    states::GameState,
+    voxels::meshes::{NeedsRemeshing, VoxelChunkMesh, WaterMesh},
+};
+
+/// Starts or stops play mode at the end of the frame, see `World::play`
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayModeRequest {
+    Play,
+    Stop,
+}
+
+/// Copies a resource from one world to another, if the first has it
+fn carry<T: Resource + Clone + 'static>(from: &World, to: &mut World) {
+    if let Ok(resource) = from.get_resource::<T>() {
+        to.insert_resource(resource.clone());
+    }
+}
+
+impl World {
+    /// Copies the objects, resources, states and systems into a new world, so the game can run
+    /// without changing this one
+    /// Note: voxel meshes are rebuilt for the copy so each world frees only its own buffers, and
+    /// non send resources can't be copied
+    pub fn fork(&self) -> World {
+        let mut fork = World::default();
+        fork.scene.objects = self.scene.objects.clone();
+        fork.resources.map = self.resources.map.clone();
+        fork.chunk_position_index = self.chunk_position_index.clone();
+        fork.event_updaters = self.event_updaters.clone();
+        fork.state_updaters = self.state_updaters.clone();
+        fork.observers = self.observers.clone();
+        fork.extraction = self.extraction.clone();
+        fork.log_stale_objects = self.log_stale_objects;
+        fork.build_systems_like(self);
+        fork.insert_resource(ReleasedBuffers::default());
+
+        for (_, object) in fork.scene.objects.iter_mut() {
+            if object.has_component::<VoxelChunkMesh>() || object.has_component::<WaterMesh>() {
+                // the buffers belong to the original, so they are dropped without their hooks
+                object.remove_component::<VoxelChunkMesh>();
+                object.remove_component::<WaterMesh>();
+                object.add_tag(NeedsRemeshing);
+            }
+        }
+        fork
+    }
+
+    /// Returns true while this world is a copy made by `play`, the edited world is kept aside
+    pub fn is_in_play_mode(&self) -> bool {
+        self.edit_world.is_some()
+    }
+
+    /// Runs the game in a copy of the world from the end of the frame, `stop` throws the copy
+    /// away so nothing done while playing changes the edited scene
+    pub fn play(&mut self) {
+        self.insert_resource(PlayModeRequest::Play);
+    }
+
+    /// Goes back to the world from before `play` at the end of the frame
+    pub fn stop(&mut self) {
+        self.insert_resource(PlayModeRequest::Stop);
+    }
+
+    /// Starts or stops play mode, called once the frame's systems are done
+    pub(crate) fn apply_play_mode_request(&mut self) {
+        let Ok(request) = self.get_resource::<PlayModeRequest>().copied() else {
+            return;
+        };
+        self.remove_resource::<PlayModeRequest>();
+        match request {
+            PlayModeRequest::Play if !self.is_in_play_mode() => self.enter_play_mode(),
+            PlayModeRequest::Stop if self.is_in_play_mode() => self.exit_play_mode(),
+            _ => {}
+        }
+    }
+
+    fn enter_play_mode(&mut self) {
+        let mut runtime = self.fork();
+        std::mem::swap(&mut self.non_send, &mut runtime.non_send);
+        let edit = std::mem::replace(self, runtime);
+        self.edit_world = Some(Box::new(edit));
+        self.add_state(GameState::Playing);
+        log!("Entered play mode");
+    }
+
+    fn exit_play_mode(&mut self) {
+        let Some(edit) = self.edit_world.take() else {
+            return;
+        };
+        let mut runtime = std::mem::replace(self, *edit);
+        std::mem::swap(&mut self.non_send, &mut runtime.non_send);
+
+        // the window and input kept changing while playing
+        carry::<WindowManager>(&runtime, self);
+        carry::<InputManager>(&runtime, self);
+        carry::<CursorManager>(&runtime, self);
+        carry::<RenderingConfig>(&runtime, self);
+
+        // hand the buffers of the copy to the renderer
+        let meshes: Vec<ObjectId> = runtime.scene.objects.keys().collect();
+        for id in meshes {
+            runtime.remove_component::<VoxelChunkMesh>(id);
+            runtime.remove_component::<WaterMesh>(id);
+        }
+        if let Ok(released) = runtime.get_resource_mut::<ReleasedBuffers>() {
+            let buffers = std::mem::take(&mut released.0);
+            if let Ok(edit_released) = self.init_resource::<ReleasedBuffers>() {
+                edit_released.0.extend(buffers);
+            }
+        }
+        log!("Left play mode");
+    }
+}
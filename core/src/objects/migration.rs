@@ -0,0 +1,112 @@
+use anyhow::{Result, anyhow};
+use serde_yaml::Value;
+
+use crate::{
+    log_warn,
+    objects::registry::{Registry, registered},
+};
+
+/// The version of the engine that saved a scene
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The version of the scene layout, bump it and add a `SceneMigration` when the layout changes
+/// Note: scenes saved before versioning are format 0
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+/// Upgrades a scene from format `from` to `from + 1` before it is deserialized
+/// e.g. `inventory::submit! { SceneMigration { from: 1, migrate: rename_velocity } }`
+/// Note: with a `Registry` add it in a function given to `registry::install` instead
+pub struct SceneMigration {
+    pub from: u32,
+    pub migrate: fn(&mut Value) -> Result<()>,
+}
+#[cfg(not(target_family = "wasm"))]
+inventory::collect!(SceneMigration);
+
+/// Format 1 only added the version fields, which serde defaults for older scenes
+fn migrate_unversioned(_scene: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(target_family = "wasm"))]
+inventory::submit! {
+    SceneMigration {
+        from: 0,
+        migrate: migrate_unversioned,
+    }
+}
+
+/// Adds the migrations of the engine to a `Registry`, which doesn't see the ones above
+pub(crate) fn register_migrations(registry: &mut Registry) {
+    registry.add(SceneMigration {
+        from: 0,
+        migrate: migrate_unversioned,
+    });
+}
+
+/// Splits `major.minor.patch` into numbers, missing or invalid parts are 0
+fn parse_version(version: &str) -> (u32, u32, u32) {
+    let mut parts = version
+        .split(['.', '-', '+'])
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    (
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+    )
+}
+
+/// Brings a scene up to `SCENE_FORMAT_VERSION`, warning about scenes from a newer engine
+pub fn migrate_scene(value: &mut Value, path: &str) -> Result<()> {
+    let engine_version = value
+        .get("engine_version")
+        .and_then(Value::as_str)
+        .unwrap_or("0.0.0")
+        .to_string();
+    if parse_version(&engine_version) > parse_version(ENGINE_VERSION) {
+        log_warn!(
+            "{} was saved by engine {}, newer than {}, some data may not load",
+            path,
+            engine_version,
+            ENGINE_VERSION
+        );
+    }
+
+    let mut format_version = value
+        .get("format_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+    if format_version > SCENE_FORMAT_VERSION {
+        log_warn!(
+            "{} has scene format {}, newer than {}, loading it anyway",
+            path,
+            format_version,
+            SCENE_FORMAT_VERSION
+        );
+        return Ok(());
+    }
+
+    while format_version < SCENE_FORMAT_VERSION {
+        let migration = registered::<SceneMigration>()
+            .find(|migration| migration.from == format_version)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No migration for scene format {} of {}",
+                    format_version,
+                    path
+                )
+            })?;
+        (migration.migrate)(value).map_err(|e| {
+            anyhow!(
+                "Failed to migrate {} from scene format {}: {}",
+                path,
+                format_version,
+                e
+            )
+        })?;
+        format_version += 1;
+    }
+    if let Some(mapping) = value.as_mapping_mut() {
+        mapping.insert("format_version".into(), format_version.into());
+    }
+    Ok(())
+}
@@ -11,12 +11,31 @@ use crate::{
     },
 };
 
+pub mod bundle;
+pub mod commands;
+pub mod compaction;
 pub mod component;
 pub mod components;
+pub mod diagnostics;
+pub mod events;
+pub mod extract;
+pub mod fork;
+pub mod migration;
+pub mod non_send;
+pub mod object_ref;
+pub mod observers;
+pub mod pool;
+pub mod prefab;
 pub mod query;
+pub mod reflect;
+pub mod registry;
 pub mod resource;
 pub mod resources;
 pub mod scene;
+pub mod scene_lock;
+pub mod scene_search;
+pub mod snapshot;
+pub mod stats;
 pub mod systems;
 pub mod tag;
 pub mod tags;
@@ -24,10 +43,18 @@ pub mod world;
 
 use crate::objects::component::BoxedComponent;
 
+/// Where a component goes among an object's components, grouped components first with their
+/// group, then the rest in the order they were added
+fn component_order(component: &BoxedComponent) -> (bool, Option<&'static str>) {
+    let group = component.group();
+    (group.is_none(), group)
+}
+
 #[derive(Clone)]
 pub struct Object {
     pub id: ObjectId,
     pub name: String,
+    /// kept in `component_order`, add components with `push_component` rather than pushing
     pub components: Vec<BoxedComponent>,
     pub tags: Vec<Box<dyn Tag>>,
     pub parent: Option<ObjectId>,
@@ -130,10 +157,26 @@ impl Object {
             log_warn!("You can only have one of any component on an entity");
             return self.clone();
         }
-        self.components.push(Box::new(component));
+        self.push_component(Box::new(component));
         self.clone()
     }
 
+    /// Adds a component after the others of its group, without checking for one of its type
+    /// Note: queries stop scanning once they have every component they want, so components
+    /// iterated together are found sooner next to each other
+    pub fn push_component(&mut self, component: BoxedComponent) {
+        let order = component_order(&component);
+        let index = self
+            .components
+            .partition_point(|existing| component_order(existing) <= order);
+        self.components.insert(index, component);
+    }
+
+    /// Puts the components back in group order after they were pushed directly
+    pub fn sort_components(&mut self) {
+        self.components.sort_by_key(component_order);
+    }
+
     pub fn add_component_by_name(&mut self, component_name: &str) -> Result<()> {
         let mut component_name = component_name.to_string();
         component_name = component_name.replace(" ", "");
@@ -160,7 +203,7 @@ impl Object {
             return Ok(());
         }
 
-        self.components.push(component);
+        self.push_component(component);
         Ok(())
     }
 }
@@ -0,0 +1,155 @@
+use std::{
+    any::{Any, TypeId, type_name},
+    thread::{self, ThreadId},
+};
+
+use hashbrown::HashMap;
+
+use crate::{
+    log_error,
+    objects::{resource::ResourceError, world::World},
+};
+
+/// Values that must stay on the main thread, e.g. a winit `Window` or Vulkan handles
+/// Note: unlike resources they don't need to be Clone, Send or Sync, so they are kept apart from
+/// the `ResourceMap` and every access checks it is on the thread the world was made on
+pub struct NonSendMap {
+    map: HashMap<TypeId, Box<dyn Any>>,
+    /// the (type, system) of the values taken out for a `NonSendMut` parameter
+    locks: HashMap<TypeId, String>,
+    main_thread: ThreadId,
+}
+
+impl Default for NonSendMap {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+            locks: HashMap::new(),
+            main_thread: thread::current().id(),
+        }
+    }
+}
+
+impl NonSendMap {
+    pub fn is_main_thread(&self) -> bool {
+        thread::current().id() == self.main_thread
+    }
+
+    fn check_thread<T: 'static>(&self) -> Result<(), ResourceError> {
+        if self.is_main_thread() {
+            Ok(())
+        } else {
+            Err(ResourceError::NotMainThread {
+                name: type_name::<T>(),
+            })
+        }
+    }
+
+    fn error<T: 'static>(&self) -> ResourceError {
+        let name = type_name::<T>();
+        match self.locks.get(&TypeId::of::<T>()) {
+            Some(held_by) => ResourceError::Locked {
+                name,
+                held_by: held_by.clone(),
+            },
+            None => ResourceError::Missing { name },
+        }
+    }
+
+    pub fn insert<T: 'static>(&mut self, value: T) -> Result<(), ResourceError> {
+        self.check_thread::<T>()?;
+        self.map.insert(TypeId::of::<T>(), Box::new(value));
+        Ok(())
+    }
+
+    pub fn get<T: 'static>(&self) -> Result<&T, ResourceError> {
+        self.check_thread::<T>()?;
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+            .ok_or_else(|| self.error::<T>())
+    }
+
+    pub fn get_mut<T: 'static>(&mut self) -> Result<&mut T, ResourceError> {
+        self.check_thread::<T>()?;
+        let error = self.error::<T>();
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut::<T>())
+            .ok_or(error)
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Result<Option<T>, ResourceError> {
+        self.check_thread::<T>()?;
+        Ok(self
+            .map
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast::<T>().ok())
+            .map(|value| *value))
+    }
+
+    /// Takes a value out for a `NonSendMut` parameter until `restore` is called
+    pub(crate) fn take<T: 'static>(&mut self, held_by: &str) -> Result<Box<T>, ResourceError> {
+        self.check_thread::<T>()?;
+        let value = self
+            .map
+            .remove(&TypeId::of::<T>())
+            .ok_or_else(|| self.error::<T>())?;
+        self.locks.insert(TypeId::of::<T>(), held_by.to_string());
+        value.downcast::<T>().map_err(|_| ResourceError::Missing {
+            name: type_name::<T>(),
+        })
+    }
+
+    /// Puts a taken value back, unless it was inserted again while it was taken
+    pub(crate) fn restore<T: 'static>(&mut self, value: Box<T>) {
+        self.locks.remove(&TypeId::of::<T>());
+        self.map.entry(TypeId::of::<T>()).or_insert(value);
+    }
+}
+
+impl Drop for NonSendMap {
+    fn drop(&mut self) {
+        // the values may not be safe to drop anywhere else, leaking them is the lesser evil
+        if !self.is_main_thread() && !self.map.is_empty() {
+            log_error!(
+                "{} non send resources were dropped off the main thread and leaked",
+                self.map.len()
+            );
+            for (_, value) in self.map.drain() {
+                std::mem::forget(value);
+            }
+        }
+    }
+}
+
+impl World {
+    /// Inserts a value only the main thread may use, see `NonSendMap`
+    /// Note: systems read them with `NonSend<T>` and change them with `NonSendMut<T>`
+    pub fn insert_non_send<T: 'static>(&mut self, value: T) -> Result<&mut Self, ResourceError> {
+        self.non_send.insert(value)?;
+        Ok(self)
+    }
+
+    pub fn get_non_send<T: 'static>(&self) -> Result<&T, ResourceError> {
+        self.non_send.get::<T>()
+    }
+
+    pub fn get_non_send_mut<T: 'static>(&mut self) -> Result<&mut T, ResourceError> {
+        self.non_send.get_mut::<T>()
+    }
+
+    pub fn has_non_send<T: 'static>(&self) -> bool {
+        self.non_send.get::<T>().is_ok()
+    }
+
+    /// Removes a non send value and gives it back, none if it was never inserted
+    pub fn remove_non_send<T: 'static>(&mut self) -> Result<Option<T>, ResourceError> {
+        self.non_send.remove::<T>()
+    }
+
+    /// Returns true on the thread the world was made on, the only one main thread systems run on
+    pub fn is_main_thread(&self) -> bool {
+        self.non_send.is_main_thread()
+    }
+}
@@ -0,0 +1,105 @@
+use std::ops::{Deref, DerefMut};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+    log_warn,
+    objects::{
+        Object,
+        scene::{ObjectId, ObjectIdExt},
+        world::World,
+    },
+};
+
+/// A borrowed object and its id, from `World::object`
+pub struct ObjectRef<'w> {
+    id: ObjectId,
+    object: &'w Object,
+}
+
+impl ObjectRef<'_> {
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+}
+
+impl Deref for ObjectRef<'_> {
+    type Target = Object;
+
+    fn deref(&self) -> &Object {
+        self.object
+    }
+}
+
+/// A mutably borrowed object and its id, from `World::object_mut`
+pub struct ObjectMut<'w> {
+    id: ObjectId,
+    object: &'w mut Object,
+}
+
+impl ObjectMut<'_> {
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+}
+
+impl Deref for ObjectMut<'_> {
+    type Target = Object;
+
+    fn deref(&self) -> &Object {
+        self.object
+    }
+}
+
+impl DerefMut for ObjectMut<'_> {
+    fn deref_mut(&mut self) -> &mut Object {
+        self.object
+    }
+}
+
+impl World {
+    /// Returns true while the object has not been removed
+    /// Note: ids are safe to keep after the object is removed, they never find another object
+    pub fn is_alive(&self, id: ObjectId) -> bool {
+        self.scene.objects.contains_key(id)
+    }
+
+    /// Gets an object, errors if it was removed
+    pub fn object(&self, id: ObjectId) -> Result<ObjectRef<'_>> {
+        match self.scene.objects.get(id) {
+            Some(object) => Ok(ObjectRef { id, object }),
+            None => Err(self.stale_object(id)),
+        }
+    }
+
+    /// Gets an object mutably, errors if it was removed
+    pub fn object_mut(&mut self, id: ObjectId) -> Result<ObjectMut<'_>> {
+        if !self.scene.objects.contains_key(id) {
+            return Err(self.stale_object(id));
+        }
+        let object = &mut self.scene.objects[id];
+        Ok(ObjectMut { id, object })
+    }
+
+    /// Logs every use of a removed object through `object`, `object_mut` and `Commands`
+    /// Useful to find code holding onto ids of removed objects
+    pub fn set_stale_object_logging(&mut self, enabled: bool) {
+        self.log_stale_objects = enabled;
+    }
+
+    pub fn is_stale_object_logging(&self) -> bool {
+        self.log_stale_objects
+    }
+
+    fn stale_object(&self, id: ObjectId) -> anyhow::Error {
+        let error = anyhow!(
+            "Object {} (generation {}) does not exist, it may have been removed",
+            id.index(),
+            id.generation()
+        );
+        if self.log_stale_objects {
+            log_warn!("{}", error);
+        }
+        error
+    }
+}
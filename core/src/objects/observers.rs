@@ -0,0 +1,118 @@
+use std::{any::TypeId, marker::PhantomData};
+
+use hashbrown::HashMap;
+
+use crate::objects::{component::Component, scene::ObjectId, world::World};
+
+/// Called with the object whose component changed
+pub type Observer = fn(&mut World, ObjectId);
+
+/// What happened to a component
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ComponentChange {
+    Inserted,
+    Removed,
+}
+
+/// Something an observer can watch, see `World::observe`
+pub trait ObserverTrigger {
+    fn component() -> TypeId;
+    fn change() -> ComponentChange;
+}
+
+/// Fires when a `T` is added to an object or replaced, including when the object is spawned
+pub struct OnInsert<T>(PhantomData<T>);
+
+/// Fires after a `T` is removed from an object, including when the object is removed
+/// Note: the component is gone by then, and so is the object if it was removed
+pub struct OnRemove<T>(PhantomData<T>);
+
+impl<T: Component + 'static> ObserverTrigger for OnInsert<T> {
+    fn component() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn change() -> ComponentChange {
+        ComponentChange::Inserted
+    }
+}
+
+impl<T: Component + 'static> ObserverTrigger for OnRemove<T> {
+    fn component() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn change() -> ComponentChange {
+        ComponentChange::Removed
+    }
+}
+
+/// Every observer added with `World::observe`, by the component and change they watch
+#[derive(Clone, Default)]
+pub struct Observers {
+    map: HashMap<(TypeId, ComponentChange), Vec<Observer>>,
+}
+
+impl Observers {
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl World {
+    /// Calls `observer` whenever the trigger happens, e.g.
+    /// `world.observe::<OnInsert<Collider>>(add_to_broadphase)`
+    /// Note: changes made through the world and commands are seen, `Object::add_component` and
+    /// `Object::remove_component` can't reach the world so they are not
+    pub fn observe<E: ObserverTrigger>(&mut self, observer: Observer) -> &mut Self {
+        self.observers
+            .map
+            .entry((E::component(), E::change()))
+            .or_default()
+            .push(observer);
+        self
+    }
+
+    /// Runs the observers of a change to one component of an object
+    pub(crate) fn trigger(&mut self, id: ObjectId, component: TypeId, change: ComponentChange) {
+        let Some(observers) = self.observers.map.get(&(component, change)).cloned() else {
+            return;
+        };
+        for observer in observers {
+            observer(self, id);
+        }
+    }
+
+    /// Runs the `OnInsert` observers of every component of a new object
+    pub(crate) fn trigger_inserted_components(&mut self, id: ObjectId) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let Some(object) = self.scene.objects.get(id) else {
+            return;
+        };
+        let components: Vec<TypeId> = object
+            .components
+            .iter()
+            .map(|component| component.as_any().type_id())
+            .collect();
+        for component in components {
+            self.trigger(id, component, ComponentChange::Inserted);
+        }
+    }
+
+    /// Adds a component to an object, replacing its component of the same type, and runs the
+    /// `OnInsert` observers
+    /// returns false if the object doesn't exist
+    pub fn insert_component<T: Component + 'static>(&mut self, id: ObjectId, component: T) -> bool {
+        let Some(object) = self.scene.objects.get_mut(id) else {
+            return false;
+        };
+        match object.get_component_mut::<T>() {
+            Ok(existing) => *existing = component,
+            Err(_) => object.push_component(Box::new(component)),
+        }
+        self.trigger(id, TypeId::of::<T>(), ComponentChange::Inserted);
+        true
+    }
+}
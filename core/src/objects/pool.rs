@@ -0,0 +1,299 @@
+use anyhow::{Result, anyhow};
+use apostasy_macros::Resource;
+use hashbrown::HashMap;
+
+use crate::{
+    log_warn,
+    objects::{
+        Object, observers::ComponentChange, prefab::PrefabOverrides, scene::ObjectId, world::World,
+    },
+};
+
+/// What a pool spawns when it has no free object
+#[derive(Clone)]
+pub enum PoolSource {
+    /// a prefab file or a name in `PREFAB_DIR`
+    Prefab(String),
+    /// spawns the object and returns the id of its root
+    Spawn(fn(&mut World) -> Result<ObjectId>),
+}
+
+/// An object and its descendants taken out of the world, the root first
+/// Note: the parent is an index into the list, like `SerializedObject::parent`
+#[derive(Clone)]
+struct PooledObject {
+    objects: Vec<(Object, Option<usize>)>,
+}
+
+/// Objects spawned once and handed out again, for things spawned and removed in bursts like
+/// projectiles, e.g. `world.add_pool("bullet", ObjectPool::prefab("bullet.yaml").prewarm(64))`
+/// Note: a released object leaves the world with its descendants but keeps its components,
+/// `on_remove` hooks only run when a full pool despawns it
+#[derive(Clone)]
+pub struct ObjectPool {
+    pub source: PoolSource,
+    /// how many objects are spawned when the pool is added
+    pub prewarm: usize,
+    /// the most free objects kept, objects released past it are despawned
+    pub max_free: usize,
+    /// called after an object is taken from the pool, puts back the state its last use changed
+    pub on_acquire: Option<fn(&mut World, ObjectId)>,
+    /// called before an object goes back into the pool
+    pub on_release: Option<fn(&mut World, ObjectId)>,
+    free: Vec<PooledObject>,
+    /// objects the pool had to spawn, counting the prewarmed ones
+    spawned: usize,
+    /// objects handed out from the free list
+    reused: usize,
+    /// objects released into a full pool
+    despawned: usize,
+}
+
+impl ObjectPool {
+    pub fn new(source: PoolSource) -> Self {
+        Self {
+            source,
+            prewarm: 0,
+            max_free: usize::MAX,
+            on_acquire: None,
+            on_release: None,
+            free: Vec::new(),
+            spawned: 0,
+            reused: 0,
+            despawned: 0,
+        }
+    }
+
+    /// A pool of a prefab file or a name in `PREFAB_DIR`
+    pub fn prefab(path: &str) -> Self {
+        Self::new(PoolSource::Prefab(path.to_string()))
+    }
+
+    /// A pool of objects made by `spawn`
+    pub fn from_fn(spawn: fn(&mut World) -> Result<ObjectId>) -> Self {
+        Self::new(PoolSource::Spawn(spawn))
+    }
+
+    pub fn prewarm(mut self, count: usize) -> Self {
+        self.prewarm = count;
+        self
+    }
+
+    pub fn max_free(mut self, count: usize) -> Self {
+        self.max_free = count;
+        self
+    }
+
+    pub fn on_acquire(mut self, hook: fn(&mut World, ObjectId)) -> Self {
+        self.on_acquire = Some(hook);
+        self
+    }
+
+    pub fn on_release(mut self, hook: fn(&mut World, ObjectId)) -> Self {
+        self.on_release = Some(hook);
+        self
+    }
+}
+
+/// How a pool is used, shown in the world stats panel
+#[derive(Clone, Debug, Default)]
+pub struct PoolStats {
+    pub name: String,
+    /// objects handed out and still in the world
+    pub active: usize,
+    pub free: usize,
+    pub spawned: usize,
+    pub reused: usize,
+    pub despawned: usize,
+}
+
+/// The pools added with `World::add_pool`, by name
+#[derive(Resource, Clone, Default)]
+pub struct ObjectPools {
+    pub pools: HashMap<String, ObjectPool>,
+    /// the pool each handed out object came from
+    active: HashMap<ObjectId, String>,
+}
+
+impl World {
+    /// Adds a pool and spawns its prewarmed objects, replacing a pool with the same name
+    pub fn add_pool(&mut self, name: &str, mut pool: ObjectPool) -> Result<()> {
+        for _ in 0..pool.prewarm {
+            let id = self.spawn_from_source(&pool.source)?;
+            pool.free.push(self.take_subtree(id)?);
+            pool.spawned += 1;
+        }
+        let pools = self.init_resource::<ObjectPools>()?;
+        pools.active.retain(|_, owner| owner.as_str() != name);
+        pools.pools.insert(name.to_string(), pool);
+        Ok(())
+    }
+
+    /// Takes an object from a pool, spawning one if the pool has none free
+    pub fn acquire_from_pool(&mut self, name: &str) -> Result<ObjectId> {
+        let pool = self
+            .init_resource::<ObjectPools>()?
+            .pools
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("Pool {} does not exist", name))?;
+        let source = pool.source.clone();
+        let on_acquire = pool.on_acquire;
+        let pooled = pool.free.pop();
+        match pooled {
+            Some(_) => pool.reused += 1,
+            None => pool.spawned += 1,
+        }
+        let id = match pooled {
+            Some(pooled) => self.put_subtree(pooled)?,
+            None => self.spawn_from_source(&source)?,
+        };
+
+        self.init_resource::<ObjectPools>()?
+            .active
+            .insert(id, name.to_string());
+        if let Some(on_acquire) = on_acquire {
+            on_acquire(self, id);
+        }
+        Ok(id)
+    }
+
+    /// Puts an object from `acquire_from_pool` back into its pool, it is despawned if the pool is
+    /// full, returns false if the object did not come from a pool
+    pub fn release_to_pool(&mut self, id: ObjectId) -> bool {
+        let Ok(pools) = self.get_resource_mut::<ObjectPools>() else {
+            return false;
+        };
+        let Some(name) = pools.active.remove(&id) else {
+            return false;
+        };
+        let Some(pool) = pools.pools.get(&name) else {
+            return false;
+        };
+        let on_release = pool.on_release;
+        let is_full = pool.free.len() >= pool.max_free;
+        if !self.scene.objects.contains_key(id) {
+            return true;
+        }
+
+        if let Some(on_release) = on_release {
+            on_release(self, id);
+        }
+        let pooled = match is_full {
+            true => None,
+            false => match self.take_subtree(id) {
+                Ok(pooled) => Some(pooled),
+                Err(e) => {
+                    log_warn!("Failed to return {:?} to pool {}: {}", id, name, e);
+                    None
+                }
+            },
+        };
+
+        let Some(pool) = self
+            .get_resource_mut::<ObjectPools>()
+            .ok()
+            .and_then(|pools| pools.pools.get_mut(&name))
+        else {
+            return true;
+        };
+        match pooled {
+            Some(pooled) => pool.free.push(pooled),
+            None => {
+                pool.despawned += 1;
+                self.despawn_recursive(id);
+            }
+        }
+        true
+    }
+
+    /// The stats of every pool, sorted by name
+    pub fn pool_stats(&self) -> Vec<PoolStats> {
+        let Ok(pools) = self.get_resource::<ObjectPools>() else {
+            return Vec::new();
+        };
+        let mut stats: Vec<PoolStats> = pools
+            .pools
+            .iter()
+            .map(|(name, pool)| PoolStats {
+                name: name.clone(),
+                active: pools
+                    .active
+                    .iter()
+                    .filter(|(id, owner)| *owner == name && self.scene.objects.contains_key(**id))
+                    .count(),
+                free: pool.free.len(),
+                spawned: pool.spawned,
+                reused: pool.reused,
+                despawned: pool.despawned,
+            })
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
+    fn spawn_from_source(&mut self, source: &PoolSource) -> Result<ObjectId> {
+        match source {
+            PoolSource::Prefab(path) => self.spawn_prefab_with(path, &PrefabOverrides::default()),
+            PoolSource::Spawn(spawn) => spawn(self),
+        }
+    }
+
+    /// Takes an object and its descendants out of the scene without running `on_remove` hooks
+    /// Note: `Removed` observers still run, as the components leave the world
+    fn take_subtree(&mut self, id: ObjectId) -> Result<PooledObject> {
+        self.scene.set_parent(id, None)?;
+        let ids: Vec<ObjectId> = std::iter::once(id)
+            .chain(self.scene.get_descendants(id))
+            .collect();
+        self.chunk_position_index
+            .retain(|_, chunk| !ids.contains(chunk));
+
+        let parents: Vec<Option<usize>> = ids
+            .iter()
+            .map(|object_id| {
+                self.scene
+                    .objects
+                    .get(*object_id)
+                    .and_then(|object| object.parent)
+                    .and_then(|parent| ids.iter().position(|i| *i == parent))
+            })
+            .collect();
+        let mut objects = Vec::with_capacity(ids.len());
+        for (object_id, parent) in ids.iter().zip(parents) {
+            if let Some(mut object) = self.scene.objects.remove(*object_id) {
+                object.children.clear();
+                objects.push((object, parent));
+            }
+        }
+
+        for ((object, _), object_id) in objects.iter().zip(&ids) {
+            let components: Vec<_> = object
+                .components
+                .iter()
+                .map(|component| component.as_any().type_id())
+                .collect();
+            for component in components {
+                self.trigger(*object_id, component, ComponentChange::Removed);
+            }
+        }
+        Ok(PooledObject { objects })
+    }
+
+    /// Adds a subtree from `take_subtree` back, returns the new id of its root
+    fn put_subtree(&mut self, pooled: PooledObject) -> Result<ObjectId> {
+        let mut ids = Vec::with_capacity(pooled.objects.len());
+        let mut parents = Vec::with_capacity(pooled.objects.len());
+        for (object, parent) in pooled.objects {
+            ids.push(self.add_object(object));
+            parents.push(parent);
+        }
+        for (id, parent) in ids.iter().zip(parents).skip(1) {
+            if let Some(parent) = parent.and_then(|i| ids.get(i)) {
+                self.set_parent(*id, Some(*parent))?;
+            }
+        }
+        ids.first()
+            .copied()
+            .ok_or_else(|| anyhow!("Pooled object has no objects"))
+    }
+}
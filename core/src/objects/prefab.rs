@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use apostasy_macros::Resource;
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::{
+    objects::{
+        component::{Component, get_component_registration, short_type_name},
+        migration::{ENGINE_VERSION, SCENE_FORMAT_VERSION, migrate_scene},
+        scene::ObjectId,
+        snapshot::{SerializedComponent, SerializedObject},
+        world::World,
+    },
+    utils::atomic_write::write_atomic,
+};
+
+/// Where `World::spawn_prefab` looks for prefabs given by name, e.g. `enemy.yaml`
+pub const PREFAB_DIR: &str = "res/prefabs";
+
+/// An object and its children saved as a template, spawned with `World::spawn_prefab`
+/// Note: uses the same object layout and migrations as scenes
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prefab {
+    pub engine_version: String,
+    pub format_version: u32,
+    /// the root first, the parent of every other object is an index into this list
+    pub objects: Vec<SerializedObject>,
+}
+
+impl Prefab {
+    /// Gets the path of a prefab, bare names that are not a file are looked up in `PREFAB_DIR`
+    pub fn resolve(path: &str) -> PathBuf {
+        let direct = Path::new(path);
+        if direct.exists() || direct.components().count() > 1 {
+            return direct.to_path_buf();
+        }
+        Path::new(PREFAB_DIR).join(path)
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let file = Self::resolve(path);
+        let contents = std::fs::read_to_string(&file)
+            .map_err(|e| anyhow!("Failed to read prefab {}: {}", file.display(), e))?;
+        let mut value: Value = serde_yaml::from_str(&contents)?;
+        migrate_scene(&mut value, path)?;
+        Ok(serde_yaml::from_value(value)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut value = serde_yaml::to_value(self)?;
+        if let Some(mapping) = value.as_mapping_mut() {
+            mapping.insert("engine_version".into(), ENGINE_VERSION.into());
+            mapping.insert("format_version".into(), SCENE_FORMAT_VERSION.into());
+        }
+        write_atomic(&Self::resolve(path), serde_yaml::to_string(&value)?)
+    }
+}
+
+/// Changes made to the root of a prefab when it is spawned
+/// e.g. `PrefabOverrides::new().name("Boss").with("Health", yaml)`
+#[derive(Clone, Debug, Default)]
+pub struct PrefabOverrides {
+    pub name: Option<String>,
+    pub components: Vec<SerializedComponent>,
+}
+
+impl PrefabOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Overrides a component by its registered name, a mapping only replaces the fields it has
+    /// Note: components the prefab doesn't have are added
+    pub fn with(mut self, component: &str, value: Value) -> Self {
+        self.components.push(SerializedComponent {
+            name: component.to_string(),
+            value,
+            version: get_component_registration(component).map_or(0, |r| r.version),
+        });
+        self
+    }
+
+    /// Overrides a whole `#[component_serde]` component
+    pub fn with_component<T: Component + Serialize>(self, component: &T) -> Result<Self> {
+        let value = serde_yaml::to_value(component)?;
+        Ok(self.with(short_type_name(T::name()), value))
+    }
+
+    fn apply(&self, root: &mut SerializedObject) {
+        if let Some(name) = &self.name {
+            root.name = name.clone();
+        }
+        for component in &self.components {
+            match root
+                .components
+                .iter_mut()
+                .find(|c| c.name.eq_ignore_ascii_case(&component.name))
+            {
+                Some(existing) => merge(&mut existing.value, &component.value),
+                None => root.components.push(component.clone()),
+            }
+        }
+    }
+}
+
+/// Copies the fields of `value` over `target`, recursing into mappings
+fn merge(target: &mut Value, value: &Value) {
+    match (target, value) {
+        (Value::Mapping(target), Value::Mapping(value)) => {
+            for (key, value) in value {
+                match target.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, value) => *target = value.clone(),
+    }
+}
+
+/// Prefabs loaded by `World::spawn_prefab`, so spawning the same one again skips the disk
+#[derive(Resource, Clone, Default)]
+pub struct PrefabCache {
+    pub prefabs: HashMap<String, Prefab>,
+}
+
+impl World {
+    /// Spawns a prefab from a file or a name in `PREFAB_DIR`, returns the id of its root
+    pub fn spawn_prefab(&mut self, path: &str) -> Result<ObjectId> {
+        self.spawn_prefab_with(path, &PrefabOverrides::default())
+    }
+
+    /// Spawns a prefab with changes to its root, returns the id of the root
+    pub fn spawn_prefab_with(
+        &mut self,
+        path: &str,
+        overrides: &PrefabOverrides,
+    ) -> Result<ObjectId> {
+        let cache = self.init_resource::<PrefabCache>()?;
+        let prefab = match cache.prefabs.get(path) {
+            Some(prefab) => prefab.clone(),
+            None => {
+                let prefab = Prefab::load(path)?;
+                cache.prefabs.insert(path.to_string(), prefab.clone());
+                prefab
+            }
+        };
+        self.instantiate_prefab(&prefab, overrides)
+    }
+
+    /// Adds the objects of a prefab, returns the id of the root
+    pub fn instantiate_prefab(
+        &mut self,
+        prefab: &Prefab,
+        overrides: &PrefabOverrides,
+    ) -> Result<ObjectId> {
+        let mut serialized = prefab.objects.clone();
+        let root = serialized
+            .first_mut()
+            .ok_or_else(|| anyhow!("Prefab has no objects"))?;
+        overrides.apply(root);
+
+        let objects = serialized
+            .iter()
+            .map(SerializedObject::to_object)
+            .collect::<Result<Vec<_>>>()?;
+        let ids: Vec<ObjectId> = objects
+            .into_iter()
+            .map(|object| self.add_object(object))
+            .collect();
+        for (object, id) in serialized.iter().zip(&ids).skip(1) {
+            if let Some(parent) = object.parent.and_then(|i| ids.get(i)) {
+                self.set_parent(*id, Some(*parent))?;
+            }
+        }
+        Ok(ids[0])
+    }
+
+    /// Makes a prefab of an object and its descendants
+    pub fn prefab_of(&self, id: ObjectId) -> Result<Prefab> {
+        let ids: Vec<ObjectId> = std::iter::once(id)
+            .chain(self.scene.get_descendants(id))
+            .collect();
+        let objects = ids
+            .iter()
+            .map(|object_id| {
+                let parent = self
+                    .get_object(*object_id)
+                    .and_then(|object| object.parent)
+                    .filter(|_| *object_id != id)
+                    .and_then(|parent| ids.iter().position(|i| *i == parent));
+                self.serialize_object(*object_id, parent)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Prefab {
+            engine_version: ENGINE_VERSION.to_string(),
+            format_version: SCENE_FORMAT_VERSION,
+            objects,
+        })
+    }
+
+    /// Saves an object and its descendants as a prefab, replacing the cached copy
+    pub fn save_prefab(&mut self, id: ObjectId, path: &str) -> Result<()> {
+        let prefab = self.prefab_of(id)?;
+        prefab.save(path)?;
+        if let Ok(cache) = self.get_resource_mut::<PrefabCache>() {
+            cache.prefabs.insert(path.to_string(), prefab);
+        }
+        Ok(())
+    }
+}
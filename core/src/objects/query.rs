@@ -1,3 +1,15 @@
+use std::{any::TypeId, marker::PhantomData};
+
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::objects::{
+    Object,
+    component::{BoxedComponent, Component},
+    scene::{ObjectId, Scene},
+    tag::Tag,
+    world::World,
+};
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum QueryType {
     Include,
@@ -13,3 +25,296 @@ pub struct QueryComponent {
 pub struct Query {
     pub query_components: Vec<QueryComponent>,
 }
+
+/// Component data fetched by `World::query`, a `&T`, `&mut T`, `ObjectId` or a tuple of them
+/// Note: each component type can only appear once in a query
+pub trait QueryData {
+    type Item<'a>;
+    /// the components found so far on the object being fetched
+    type Slots<'a>: Default;
+
+    /// Takes the component if a slot wants it, otherwise gives it back
+    fn offer<'a>(
+        slots: &mut Self::Slots<'a>,
+        component: &'a mut BoxedComponent,
+    ) -> Option<&'a mut BoxedComponent>;
+
+    /// Gets the item if every slot was filled
+    fn finish(slots: Self::Slots<'_>, id: ObjectId) -> Option<Self::Item<'_>>;
+
+    /// Checks if every slot was filled, so the rest of the components can be skipped
+    fn is_filled(slots: &Self::Slots<'_>) -> bool;
+
+    /// Fetches the item from an object in a single pass over its components, stopping once every
+    /// slot is filled, see `Object::push_component` for how groups keep that pass short
+    fn fetch(id: ObjectId, object: &mut Object) -> Option<Self::Item<'_>> {
+        let mut slots = Self::Slots::default();
+        for component in object.components.iter_mut() {
+            Self::offer(&mut slots, component);
+            if Self::is_filled(&slots) {
+                break;
+            }
+        }
+        Self::finish(slots, id)
+    }
+}
+
+impl<T: Component> QueryData for &T {
+    type Item<'a> = &'a T;
+    type Slots<'a> = Option<&'a T>;
+
+    fn offer<'a>(
+        slots: &mut Self::Slots<'a>,
+        component: &'a mut BoxedComponent,
+    ) -> Option<&'a mut BoxedComponent> {
+        if slots.is_some() || component.as_any().type_id() != TypeId::of::<T>() {
+            return Some(component);
+        }
+        let component: &'a BoxedComponent = component;
+        *slots = component.as_any().downcast_ref::<T>();
+        None
+    }
+
+    fn finish(slots: Self::Slots<'_>, _id: ObjectId) -> Option<Self::Item<'_>> {
+        slots
+    }
+
+    fn is_filled(slots: &Self::Slots<'_>) -> bool {
+        slots.is_some()
+    }
+}
+
+impl<T: Component> QueryData for &mut T {
+    type Item<'a> = &'a mut T;
+    type Slots<'a> = Option<&'a mut T>;
+
+    fn offer<'a>(
+        slots: &mut Self::Slots<'a>,
+        component: &'a mut BoxedComponent,
+    ) -> Option<&'a mut BoxedComponent> {
+        if slots.is_some() || component.as_any().type_id() != TypeId::of::<T>() {
+            return Some(component);
+        }
+        *slots = component.as_any_mut().downcast_mut::<T>();
+        None
+    }
+
+    fn finish(slots: Self::Slots<'_>, _id: ObjectId) -> Option<Self::Item<'_>> {
+        slots
+    }
+
+    fn is_filled(slots: &Self::Slots<'_>) -> bool {
+        slots.is_some()
+    }
+}
+
+impl QueryData for ObjectId {
+    type Item<'a> = ObjectId;
+    type Slots<'a> = ();
+
+    fn offer<'a>(
+        _slots: &mut Self::Slots<'a>,
+        component: &'a mut BoxedComponent,
+    ) -> Option<&'a mut BoxedComponent> {
+        Some(component)
+    }
+
+    fn finish(_slots: Self::Slots<'_>, id: ObjectId) -> Option<Self::Item<'_>> {
+        Some(id)
+    }
+
+    fn is_filled(_slots: &Self::Slots<'_>) -> bool {
+        true
+    }
+}
+
+macro_rules! impl_query_data {
+    ($($data:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($data: QueryData),*> QueryData for ($($data,)*) {
+            type Item<'a> = ($($data::Item<'a>,)*);
+            type Slots<'a> = ($($data::Slots<'a>,)*);
+
+            fn offer<'a>(
+                slots: &mut Self::Slots<'a>,
+                component: &'a mut BoxedComponent,
+            ) -> Option<&'a mut BoxedComponent> {
+                let ($($data,)*) = slots;
+                $(let component = $data::offer($data, component)?;)*
+                Some(component)
+            }
+
+            fn finish(slots: Self::Slots<'_>, id: ObjectId) -> Option<Self::Item<'_>> {
+                let ($($data,)*) = slots;
+                Some(($($data::finish($data, id)?,)*))
+            }
+
+            fn is_filled(slots: &Self::Slots<'_>) -> bool {
+                let ($($data,)*) = slots;
+                true $(&& $data::is_filled($data))*
+            }
+        }
+    };
+}
+
+impl_query_data!(A);
+impl_query_data!(A, B);
+impl_query_data!(A, B, C);
+impl_query_data!(A, B, C, D);
+impl_query_data!(A, B, C, D, E);
+impl_query_data!(A, B, C, D, E, F);
+
+/// Limits which objects `World::query_filtered` visits without fetching anything
+pub trait QueryFilter {
+    fn matches(object: &Object) -> bool;
+}
+
+/// Only visits objects with a T component
+pub struct With<T>(PhantomData<T>);
+
+impl<T: Component> QueryFilter for With<T> {
+    fn matches(object: &Object) -> bool {
+        object.has_component::<T>()
+    }
+}
+
+/// Only visits objects without a T component
+pub struct Without<T>(PhantomData<T>);
+
+impl<T: Component> QueryFilter for Without<T> {
+    fn matches(object: &Object) -> bool {
+        !object.has_component::<T>()
+    }
+}
+
+/// Only visits objects with a T tag, for markers like `Selected` that don't hold data
+pub struct WithTag<T>(PhantomData<T>);
+
+impl<T: Tag + 'static> QueryFilter for WithTag<T> {
+    fn matches(object: &Object) -> bool {
+        object.has_tag::<T>()
+    }
+}
+
+/// Only visits objects without a T tag
+pub struct WithoutTag<T>(PhantomData<T>);
+
+impl<T: Tag + 'static> QueryFilter for WithoutTag<T> {
+    fn matches(object: &Object) -> bool {
+        !object.has_tag::<T>()
+    }
+}
+
+impl QueryFilter for () {
+    fn matches(_object: &Object) -> bool {
+        true
+    }
+}
+
+macro_rules! impl_query_filter {
+    ($($filter:ident),*) => {
+        impl<$($filter: QueryFilter),*> QueryFilter for ($($filter,)*) {
+            fn matches(object: &Object) -> bool {
+                $($filter::matches(object))&&*
+            }
+        }
+    };
+}
+
+impl_query_filter!(A);
+impl_query_filter!(A, B);
+impl_query_filter!(A, B, C);
+impl_query_filter!(A, B, C, D);
+
+/// Where `iter_batched` continues from, keep it in a resource or component between frames
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryCursor {
+    /// how many matching objects the previous batches visited
+    pub offset: usize,
+}
+
+impl World {
+    /// Iterates every object with all of the components in D
+    /// e.g. `for (transform, velocity) in world.query::<(&mut Transform, &Velocity)>()`
+    pub fn query<D: QueryData>(&mut self) -> impl Iterator<Item = D::Item<'_>> {
+        self.query_filtered::<D, ()>()
+    }
+
+    /// Iterates every object with all of the components in D that matches the filter F
+    /// e.g. `world.query_filtered::<&mut Transform, Without<Collider>>()`
+    pub fn query_filtered<D: QueryData, F: QueryFilter>(
+        &mut self,
+    ) -> impl Iterator<Item = D::Item<'_>> {
+        query_scene::<D, F>(&mut self.scene)
+    }
+
+    /// Runs `f` on every object matching D and F across the rayon pool, `chunk_size` objects per
+    /// task at least
+    /// Note: commands queued by `f` are still applied after the running system
+    pub fn par_query<'w, D: QueryData, F: QueryFilter>(
+        &'w mut self,
+        chunk_size: usize,
+        f: impl Fn(D::Item<'w>) + Send + Sync,
+    ) where
+        D::Item<'w>: Send,
+    {
+        par_for_each_scene::<D, F>(&mut self.scene, chunk_size, f);
+    }
+
+    /// Gets the next `n` objects matching D and F, starting where the cursor's last batch ended
+    /// Note: the cursor goes back to the start once a batch comes back short
+    pub fn query_batched<D: QueryData, F: QueryFilter>(
+        &mut self,
+        cursor: &mut QueryCursor,
+        n: usize,
+    ) -> Vec<D::Item<'_>> {
+        batch_scene::<D, F>(&mut self.scene, cursor, n)
+    }
+}
+
+/// Iterates the objects of a scene matching D and F, shared by `World::query_filtered` and the
+/// `Query` system parameter
+pub(crate) fn query_scene<D: QueryData, F: QueryFilter>(
+    scene: &mut Scene,
+) -> impl Iterator<Item = D::Item<'_>> {
+    scene
+        .objects
+        .iter_mut()
+        .filter(|(_, object)| F::matches(object))
+        .filter_map(|(id, object)| D::fetch(id, object))
+}
+
+/// Splits the objects of a scene matching D and F over the rayon pool, shared by
+/// `World::par_query` and `Query::par_for_each`
+/// Note: the matches are collected first, as the scene can only be walked on one thread
+pub(crate) fn par_for_each_scene<'s, D: QueryData, F: QueryFilter>(
+    scene: &'s mut Scene,
+    chunk_size: usize,
+    f: impl Fn(D::Item<'s>) + Send + Sync,
+) where
+    D::Item<'s>: Send,
+{
+    let items: Vec<D::Item<'s>> = query_scene::<D, F>(scene).collect();
+    items
+        .into_par_iter()
+        .with_min_len(chunk_size.max(1))
+        .for_each(f);
+}
+
+/// Gets the next batch of objects of a scene matching D and F, moving the cursor past them
+pub(crate) fn batch_scene<'s, D: QueryData, F: QueryFilter>(
+    scene: &'s mut Scene,
+    cursor: &mut QueryCursor,
+    n: usize,
+) -> Vec<D::Item<'s>> {
+    let items: Vec<D::Item<'s>> = query_scene::<D, F>(scene)
+        .skip(cursor.offset)
+        .take(n)
+        .collect();
+    if items.len() < n {
+        cursor.offset = 0;
+    } else {
+        cursor.offset += items.len();
+    }
+    items
+}
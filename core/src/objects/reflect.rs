@@ -0,0 +1,116 @@
+use anyhow::{Result, anyhow};
+
+use crate::objects::{
+    component::{
+        BoxedComponent, ComponentRegistration, get_component_registration,
+        get_component_registration_of, short_type_name,
+    },
+    observers::ComponentChange,
+    scene::ObjectId,
+    world::World,
+};
+
+/// Checks if a component is registered as `name`, ignoring case
+fn is_named(component: &BoxedComponent, name: &str) -> bool {
+    short_type_name(component.type_name()).eq_ignore_ascii_case(name)
+}
+
+fn registration_of(component: &BoxedComponent) -> Result<&'static ComponentRegistration> {
+    get_component_registration_of(component).ok_or_else(|| {
+        anyhow!(
+            "{} is not registered",
+            short_type_name(component.type_name())
+        )
+    })
+}
+
+impl World {
+    /// Gets the registrations of every component of an object, in the order they were added
+    pub fn component_registrations(&self, id: ObjectId) -> Vec<&'static ComponentRegistration> {
+        self.scene
+            .objects
+            .get(id)
+            .map(|object| {
+                object
+                    .components
+                    .iter()
+                    .filter_map(get_component_registration_of)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Reads a field of a component of an object as yaml, e.g.
+    /// `world.get_component_field(id, "Velocity", "linear")`
+    /// Note: only `#[component_serde]` components can be read this way
+    pub fn get_component_field(
+        &self,
+        id: ObjectId,
+        component: &str,
+        field: &str,
+    ) -> Result<serde_yaml::Value> {
+        let object = self
+            .scene
+            .objects
+            .get(id)
+            .ok_or_else(|| anyhow!("Object does not exist"))?;
+        let found = object
+            .components
+            .iter()
+            .find(|c| is_named(c, component))
+            .ok_or_else(|| anyhow!("{} has no {}", object.name, component))?;
+        let get_field = registration_of(found)?
+            .get_field
+            .ok_or_else(|| anyhow!("{} is not #[component_serde]", component))?;
+        get_field(found, field)
+    }
+
+    /// Replaces a field of a component of an object from yaml
+    /// Note: only `#[component_serde]` components can be changed this way, no hooks run
+    pub fn set_component_field(
+        &mut self,
+        id: ObjectId,
+        component: &str,
+        field: &str,
+        value: &serde_yaml::Value,
+    ) -> Result<()> {
+        let object = self
+            .scene
+            .objects
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("Object does not exist"))?;
+        let name = object.name.clone();
+        let found = object
+            .components
+            .iter_mut()
+            .find(|c| is_named(c, component))
+            .ok_or_else(|| anyhow!("{} has no {}", name, component))?;
+        let set_field = registration_of(found)?
+            .set_field
+            .ok_or_else(|| anyhow!("{} is not #[component_serde]", component))?;
+        set_field(found, field, value)
+            .map_err(|e| anyhow!("Failed to set {}.{}: {}", component, field, e))
+    }
+
+    /// Adds a registered component with its default values and runs the `OnInsert` observers,
+    /// e.g. `world.insert_component_by_name(id, "Velocity")`
+    /// Note: errors if the object already has one
+    pub fn insert_component_by_name(&mut self, id: ObjectId, component: &str) -> Result<()> {
+        let registration = get_component_registration(component)
+            .ok_or_else(|| anyhow!("Component {} is not registered", component))?;
+        let object = self
+            .scene
+            .objects
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("Object does not exist"))?;
+        if object.components.iter().any(|c| is_named(c, component)) {
+            return Err(anyhow!("{} already has {}", object.name, component));
+        }
+
+        let created = (registration.create)();
+        let type_id = created.as_any().type_id();
+        object.push_component(created);
+        self.trigger(id, type_id, ComponentChange::Inserted);
+        Ok(())
+    }
+}
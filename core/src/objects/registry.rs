@@ -0,0 +1,138 @@
+use std::sync::{OnceLock, RwLock};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+    console::ConsoleCommand,
+    objects::{
+        component::ComponentRegistration,
+        migration::SceneMigration,
+        resource::{ResourceLifecycle, ResourceRegistration},
+        systems::{FixedUpdateSystem, LateUpdateSystem, ShutdownSystem, StartSystem, UpdateSystem},
+        tag::TagRegistration,
+    },
+    ui::game_ui::GameUiSystem,
+};
+
+/// Everything the macros register, filled by the `register_all` functions `register_all!`
+/// generates instead of inventory
+/// Note: only read with the `explicit-registration` feature or on wasm, where link sections are
+/// not supported, see `install`, and for the libraries `hot_reload` loads
+#[derive(Default)]
+pub struct Registry {
+    components: Vec<ComponentRegistration>,
+    tags: Vec<TagRegistration>,
+    resources: Vec<ResourceRegistration>,
+    resource_lifecycles: Vec<ResourceLifecycle>,
+    start_systems: Vec<StartSystem>,
+    update_systems: Vec<UpdateSystem>,
+    fixed_update_systems: Vec<FixedUpdateSystem>,
+    late_update_systems: Vec<LateUpdateSystem>,
+    shutdown_systems: Vec<ShutdownSystem>,
+    game_ui_systems: Vec<GameUiSystem>,
+    console_commands: Vec<ConsoleCommand>,
+    scene_migrations: Vec<SceneMigration>,
+}
+
+impl Registry {
+    pub fn add<T: Registered>(&mut self, registration: T) {
+        T::list_mut(self).push(registration);
+    }
+}
+
+/// Something the macros register, collected by inventory or a `Registry`
+pub trait Registered: Sized + Sync + 'static {
+    fn list(registry: &Registry) -> &Vec<Self>;
+    fn list_mut(registry: &mut Registry) -> &mut Vec<Self>;
+}
+
+macro_rules! registered {
+    ($($ty:ty => $field:ident),* $(,)?) => {
+        $(
+            impl Registered for $ty {
+                fn list(registry: &Registry) -> &Vec<Self> {
+                    &registry.$field
+                }
+                fn list_mut(registry: &mut Registry) -> &mut Vec<Self> {
+                    &mut registry.$field
+                }
+            }
+        )*
+    };
+}
+
+registered!(
+    ComponentRegistration => components,
+    TagRegistration => tags,
+    ResourceRegistration => resources,
+    ResourceLifecycle => resource_lifecycles,
+    StartSystem => start_systems,
+    UpdateSystem => update_systems,
+    FixedUpdateSystem => fixed_update_systems,
+    LateUpdateSystem => late_update_systems,
+    ShutdownSystem => shutdown_systems,
+    GameUiSystem => game_ui_systems,
+    ConsoleCommand => console_commands,
+    SceneMigration => scene_migrations,
+);
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// The registries of the libraries `hot_reload` loaded, `registered` lists them after the
+/// registrations of the executable
+static DYNAMIC_REGISTRIES: RwLock<Vec<&'static Registry>> = RwLock::new(Vec::new());
+
+/// Fills the registry with the `register_all` of each crate, e.g.
+/// `install(&[apostasy_core::register_all, register_all])`
+/// Note: call it before `init_core`, the world collects its systems when it is made
+pub fn install(register: &[fn(&mut Registry)]) -> Result<()> {
+    let mut registry = Registry::default();
+    crate::objects::migration::register_migrations(&mut registry);
+    for register in register {
+        register(&mut registry);
+    }
+    REGISTRY
+        .set(registry)
+        .map_err(|_| anyhow!("The registry is already installed"))
+}
+
+/// Adds the registry of a loaded library to what `registered` lists
+#[cfg(feature = "hot-reload")]
+pub(crate) fn add_dynamic_registry(registry: &'static Registry) {
+    DYNAMIC_REGISTRIES.write().unwrap().push(registry);
+}
+
+/// Stops `registered` listing the registry of a library that is being unloaded
+#[cfg(feature = "hot-reload")]
+pub(crate) fn remove_dynamic_registry(registry: &'static Registry) {
+    DYNAMIC_REGISTRIES
+        .write()
+        .unwrap()
+        .retain(|r| !std::ptr::eq(*r, registry));
+}
+
+/// Everything of one kind the loaded libraries registered
+fn dynamic<T: Registered>() -> impl Iterator<Item = &'static T> {
+    let registries = DYNAMIC_REGISTRIES.read().unwrap().clone();
+    registries
+        .into_iter()
+        .flat_map(|registry| T::list(registry).iter())
+}
+
+/// Everything of one kind that was registered
+#[cfg(not(any(feature = "explicit-registration", target_family = "wasm")))]
+pub fn registered<T: Registered + inventory::Collect>() -> impl Iterator<Item = &'static T> {
+    inventory::iter::<T>().into_iter().chain(dynamic::<T>())
+}
+
+/// Everything of one kind that was registered
+/// Note: empty until `install` is called
+#[cfg(any(feature = "explicit-registration", target_family = "wasm"))]
+pub fn registered<T: Registered>() -> impl Iterator<Item = &'static T> {
+    REGISTRY
+        .get()
+        .map(|registry| T::list(registry).as_slice())
+        .unwrap_or(&[])
+        .iter()
+        .chain(dynamic::<T>())
+}
@@ -1,8 +1,16 @@
-use std::any::{Any, TypeId, type_name};
+use std::{
+    any::{Any, TypeId, type_name},
+    fmt,
+};
 
-use anyhow::{Result, anyhow};
 use hashbrown::HashMap;
 
+use crate::{
+    log,
+    objects::{registry::registered, world::World},
+    packages::Packages,
+};
+
 pub trait Resource: ResourceContainer {
     fn name() -> &'static str
     where
@@ -30,6 +38,19 @@ impl Clone for Box<dyn Resource> {
     }
 }
 
+/// Makes a resource from the rest of the world, used by `World::init_resource`
+/// Note: every `Default` resource gets this, implement it for resources that need others, e.g.
+/// one built from the `VulkanRenderingContext`
+pub trait FromWorld {
+    fn from_world(world: &mut World) -> Self;
+}
+
+impl<T: Default> FromWorld for T {
+    fn from_world(_world: &mut World) -> Self {
+        T::default()
+    }
+}
+
 pub struct ResourceRegistration {
     pub type_name: &'static str,
     // pub serialize: fn(&dyn Resource) -> serde_yaml::Value,
@@ -37,22 +58,108 @@ pub struct ResourceRegistration {
     pub create: fn() -> Box<dyn Resource>,
 }
 
+#[cfg(not(target_family = "wasm"))]
 inventory::collect!(ResourceRegistration);
 
+/// The init and shutdown hooks of a resource, registered by `#[resource_init]` and
+/// `#[resource_shutdown]` on a `#[derive(Resource)]`
+pub struct ResourceLifecycle {
+    pub type_name: &'static str,
+    /// the package the resource belongs to, None for resources every world has
+    pub package: Option<Packages>,
+    pub init: Option<fn(&mut World)>,
+    /// called on exit while the resource is in the world, the resource is removed after
+    pub shutdown: Option<fn(&mut World)>,
+}
+
+#[cfg(not(target_family = "wasm"))]
+inventory::collect!(ResourceLifecycle);
+
+impl World {
+    /// Inits every registered resource of a package, or the ones without a package for None
+    /// Note: `Core::new` and `add_package` call this
+    pub fn init_registered_resources(&mut self, package: Option<Packages>) {
+        for lifecycle in registered::<ResourceLifecycle>() {
+            if lifecycle.package == package
+                && let Some(init) = lifecycle.init
+            {
+                init(self);
+            }
+        }
+    }
+
+    /// Runs the shutdown of every registered resource that is in the world and removes it
+    /// Note: called when the engine exits
+    pub fn shutdown_resources(&mut self) {
+        for lifecycle in registered::<ResourceLifecycle>() {
+            if let Some(shutdown) = lifecycle.shutdown {
+                log!("Shutting down {}", lifecycle.type_name);
+                shutdown(self);
+            }
+        }
+    }
+}
+
 pub fn get_resource_registration(type_name: &str) -> Option<&'static ResourceRegistration> {
-    inventory::iter::<ResourceRegistration>()
+    registered::<ResourceRegistration>()
         .find(|r| r.type_name.to_lowercase() == type_name.to_lowercase())
 }
 
+/// Why a resource could not be accessed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResourceError {
+    /// the resource was never inserted or has been removed
+    Missing { name: &'static str },
+    /// the resource is taken out by `World::with_resource_mut`
+    Locked { name: &'static str, held_by: String },
+    /// a non send resource was used off the main thread
+    NotMainThread { name: &'static str },
+}
+
+impl fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceError::Missing { name } => write!(f, "Resource {} not found", name),
+            ResourceError::Locked { name, held_by } => {
+                write!(f, "Resource {} is locked by {}", name, held_by)
+            }
+            ResourceError::NotMainThread { name } => {
+                write!(f, "Resource {} can only be used on the main thread", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResourceError {}
+
+/// A resource taken out of the map, see `World::with_resource_mut`
+#[derive(Clone, Debug)]
+pub struct ResourceLock {
+    pub name: &'static str,
+    /// the system that took the resource, or "engine" outside of systems
+    pub held_by: String,
+}
+
 #[derive(Default)]
 pub struct ResourceMap {
     pub(crate) map: HashMap<TypeId, Box<dyn Resource>>,
+    /// the resources currently taken out of the map
+    pub(crate) locks: HashMap<TypeId, ResourceLock>,
 }
 
 impl ResourceMap {
     pub fn new() -> Self {
-        Self {
-            map: HashMap::new(),
+        Self::default()
+    }
+
+    fn error<T: Resource + 'static>(&self) -> ResourceError {
+        let name = type_name::<T>();
+        match self.locks.get(&TypeId::of::<T>()) {
+            Some(lock) => ResourceError::Locked {
+                name,
+                held_by: lock.held_by.clone(),
+            },
+            None => ResourceError::Missing { name },
         }
     }
 
@@ -66,23 +173,66 @@ impl ResourceMap {
     }
 
     /// Get a resource from the map
-    pub fn get<T: Resource + 'static>(&self) -> Result<&T> {
+    pub fn get<T: Resource + 'static>(&self) -> Result<&T, ResourceError> {
         self.map
             .get(&TypeId::of::<T>())
             .and_then(|r| r.as_any().downcast_ref::<T>())
-            .ok_or_else(|| anyhow!("[ERROR!] Resource {} not found", type_name::<T>()))
+            .ok_or_else(|| self.error::<T>())
     }
 
     /// Get a resource mutably from the map
-    pub fn get_mut<T: Resource + 'static>(&mut self) -> Result<&mut T> {
+    pub fn get_mut<T: Resource + 'static>(&mut self) -> Result<&mut T, ResourceError> {
+        let error = self.error::<T>();
         self.map
             .get_mut(&TypeId::of::<T>())
             .and_then(|r| r.as_any_mut().downcast_mut::<T>())
-            .ok_or_else(|| anyhow!("[ERROR!] Resource {} not found", type_name::<T>()))
+            .ok_or(error)
     }
 
     /// Remove a resource from the map
     pub fn remove<T: Resource + 'static>(&mut self) {
         self.map.remove(&TypeId::of::<T>());
     }
+
+    /// Takes a resource out of the map until `restore` is called, accessing it meanwhile gives
+    /// `ResourceError::Locked`
+    pub(crate) fn take<T: Resource + 'static>(
+        &mut self,
+        held_by: String,
+    ) -> Result<Box<dyn Resource>, ResourceError> {
+        let resource = self
+            .map
+            .remove(&TypeId::of::<T>())
+            .ok_or_else(|| self.error::<T>())?;
+        self.locks.insert(
+            TypeId::of::<T>(),
+            ResourceLock {
+                name: type_name::<T>(),
+                held_by,
+            },
+        );
+        Ok(resource)
+    }
+
+    /// Puts a taken resource back, unless it was inserted again while it was taken
+    pub(crate) fn restore<T: Resource + 'static>(&mut self, resource: Box<dyn Resource>) {
+        self.locks.remove(&TypeId::of::<T>());
+        self.map.entry(TypeId::of::<T>()).or_insert(resource);
+    }
+
+    /// Gets every resource currently taken out of the map
+    pub fn locks(&self) -> impl Iterator<Item = &ResourceLock> {
+        self.locks.values()
+    }
+
+    /// Describes who holds which resource, one line per resource
+    pub fn wait_graph(&self) -> String {
+        let mut lines: Vec<String> = self
+            .locks
+            .values()
+            .map(|lock| format!("{} holds {}", lock.held_by, lock.name))
+            .collect();
+        lines.sort();
+        lines.join("\n")
+    }
 }
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Resource, console_command};
+use cgmath::Vector2;
+use gilrs::{Axis, Button};
+use serde::{Deserialize, Serialize};
+use winit::{
+    event::MouseButton,
+    keyboard::{Key, PhysicalKey},
+};
+
+use crate::{
+    objects::{resources::input_manager::InputManager, world::World},
+    utils::yaml,
+};
+
+/// The file the project's actions are persisted to, next to the other `res` settings
+pub const INPUT_ACTIONS_PATH: &str = "res/input_actions.yaml";
+
+/// The context of the game itself, the only one on the stack at the start
+pub const GAMEPLAY_CONTEXT: &str = "Gameplay";
+pub const UI_CONTEXT: &str = "UI";
+pub const EDITOR_CONTEXT: &str = "Editor";
+
+/// An input an action can be bound to
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(PhysicalKey),
+    /// the key that types a character on the current layout, character keys lowercase, see
+    /// `KeyBindMode::Logical`
+    LogicalKey(Key),
+    Mouse(MouseButton),
+    Gamepad(Button),
+    /// a gamepad stick or trigger, from -1 to 1 after the deadzone
+    GamepadAxis(Axis),
+    /// a key that pushes the value to 1 and one that pushes it to -1
+    Keys {
+        positive: PhysicalKey,
+        negative: PhysicalKey,
+    },
+}
+
+impl Binding {
+    /// The value of the binding from -1 to 1, keys and buttons are 0 or 1
+    /// Note: keys read 0 while egui has the keyboard and mouse buttons while it has the pointer
+    pub fn value(&self, inputs: &InputManager) -> f32 {
+        let key = |key: &PhysicalKey| !inputs.keyboard_captured && inputs.keys_held.contains(key);
+        match self {
+            Binding::Key(k) => key(k) as i32 as f32,
+            Binding::LogicalKey(k) => {
+                (!inputs.keyboard_captured && inputs.logical_keys_held.contains(k)) as i32 as f32
+            }
+            Binding::Mouse(button) => {
+                (!inputs.pointer_captured && inputs.mouse_held.contains(button)) as i32 as f32
+            }
+            Binding::Gamepad(button) => inputs.gamepad.buttons_held.contains(button) as i32 as f32,
+            Binding::GamepadAxis(axis) => inputs.gamepad.axis(*axis),
+            Binding::Keys { positive, negative } => {
+                key(positive) as i32 as f32 - key(negative) as i32 as f32
+            }
+        }
+    }
+
+    /// Checks if the binding went down this frame, sticks and triggers never do
+    pub fn is_pressed(&self, inputs: &InputManager) -> bool {
+        let key =
+            |key: &PhysicalKey| !inputs.keyboard_captured && inputs.keys_pressed.contains(key);
+        match self {
+            Binding::Key(k) => key(k),
+            Binding::LogicalKey(k) => {
+                !inputs.keyboard_captured && inputs.logical_keys_pressed.contains(k)
+            }
+            Binding::Mouse(button) => {
+                !inputs.pointer_captured && inputs.mouse_pressed.contains(button)
+            }
+            Binding::Gamepad(button) => inputs.gamepad.buttons_pressed.contains(button),
+            Binding::GamepadAxis(_) => false,
+            Binding::Keys { positive, negative } => key(positive) || key(negative),
+        }
+    }
+
+    /// Checks if the binding went up this frame, sticks and triggers never do
+    pub fn is_released(&self, inputs: &InputManager) -> bool {
+        let key =
+            |key: &PhysicalKey| !inputs.keyboard_captured && inputs.keys_released.contains(key);
+        match self {
+            Binding::Key(k) => key(k),
+            Binding::LogicalKey(k) => {
+                !inputs.keyboard_captured && inputs.logical_keys_released.contains(k)
+            }
+            Binding::Mouse(button) => {
+                !inputs.pointer_captured && inputs.mouse_released.contains(button)
+            }
+            Binding::Gamepad(button) => inputs.gamepad.buttons_released.contains(button),
+            Binding::GamepadAxis(_) => false,
+            Binding::Keys { positive, negative } => key(positive) || key(negative),
+        }
+    }
+}
+
+/// A named thing the player does, e.g. Jump or MoveX, and every input that does it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    pub name: String,
+    pub bindings: Vec<Binding>,
+}
+
+impl Action {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, binding: Binding) -> Self {
+        self.bindings.push(binding);
+        self
+    }
+}
+
+/// The actions of one context, e.g. Gameplay or UI
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionContext {
+    pub actions: Vec<Action>,
+    /// stops the contexts under it being read while it is on the stack, e.g. a menu stopping
+    /// gameplay
+    pub exclusive: bool,
+}
+
+/// Named actions bound to keys, mouse buttons and gamepads, grouped into contexts that are
+/// pushed and popped, loaded from and saved to `INPUT_ACTIONS_PATH`
+/// Note: an action is read from the highest context on the stack that has it, so a context can
+/// bind the same name differently
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionMap {
+    pub contexts: BTreeMap<String, ActionContext>,
+    /// the active contexts, the last is on top
+    #[serde(skip)]
+    stack: Vec<String>,
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        Self {
+            contexts: BTreeMap::new(),
+            stack: vec![GAMEPLAY_CONTEXT.to_string()],
+        }
+    }
+}
+
+impl ActionMap {
+    /// Loads the actions from disk, falling back to none if they are missing or invalid
+    pub fn load() -> Self {
+        yaml::load_or_default(INPUT_ACTIONS_PATH)
+    }
+
+    /// Saves the actions to `INPUT_ACTIONS_PATH`, the stack is not saved
+    pub fn save(&self) -> Result<()> {
+        yaml::save(INPUT_ACTIONS_PATH, self)
+    }
+
+    /// Adds an action to a context, usage:
+    /// ```rust
+    /// actions.add_action(
+    ///     GAMEPLAY_CONTEXT,
+    ///     Action::new("Jump")
+    ///         .with(Binding::Key(PhysicalKey::Code(KeyCode::Space)))
+    ///         .with(Binding::Gamepad(Button::South)),
+    /// );
+    /// ```
+    /// Note: an action loaded from `INPUT_ACTIONS_PATH` is kept, so saved bindings win over the
+    /// defaults
+    pub fn add_action(&mut self, context: &str, action: Action) {
+        let context = self.contexts.entry(context.to_string()).or_default();
+        if context.actions.iter().any(|a| a.name == action.name) {
+            return;
+        }
+        context.actions.push(action);
+    }
+
+    pub fn set_exclusive(&mut self, context: &str, exclusive: bool) {
+        self.contexts
+            .entry(context.to_string())
+            .or_default()
+            .exclusive = exclusive;
+    }
+
+    /// Puts a context on top of the stack, its actions are read before the others
+    pub fn push_context(&mut self, context: &str) {
+        self.stack.push(context.to_string());
+    }
+
+    /// Takes the top context off the stack, the bottom one stays
+    pub fn pop_context(&mut self) -> Option<String> {
+        if self.stack.len() <= 1 {
+            return None;
+        }
+        self.stack.pop()
+    }
+
+    /// Takes a context off the stack wherever it is
+    pub fn remove_context(&mut self, context: &str) {
+        if let Some(index) = self.stack.iter().rposition(|c| c == context) {
+            self.stack.remove(index);
+        }
+    }
+
+    /// The active contexts, the last is on top
+    pub fn stack(&self) -> &[String] {
+        &self.stack
+    }
+
+    /// Finds the action that is read for a name, from the top of the stack down to the first
+    /// exclusive context
+    pub fn action(&self, name: &str) -> Option<&Action> {
+        for context in self.stack.iter().rev() {
+            let Some(context) = self.contexts.get(context) else {
+                continue;
+            };
+            if let Some(action) = context.actions.iter().find(|a| a.name == name) {
+                return Some(action);
+            }
+            if context.exclusive {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Gets the action in a context to change its bindings
+    pub fn action_mut(&mut self, context: &str, name: &str) -> Option<&mut Action> {
+        self.contexts
+            .get_mut(context)?
+            .actions
+            .iter_mut()
+            .find(|a| a.name == name)
+    }
+
+    /// Gets the value of an action from -1 to 1, the binding pushed furthest wins
+    pub fn value(&self, inputs: &InputManager, name: &str) -> f32 {
+        let Some(action) = self.action(name) else {
+            return 0.0;
+        };
+        action
+            .bindings
+            .iter()
+            .map(|binding| binding.value(inputs))
+            .fold(0.0, |best: f32, value| {
+                if value.abs() > best.abs() {
+                    value
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// Checks if any binding of an action is held or pushed more than halfway
+    pub fn is_held(&self, inputs: &InputManager, name: &str) -> bool {
+        self.value(inputs, name).abs() > 0.5
+    }
+
+    /// Checks if any binding of an action went down this frame
+    pub fn is_pressed(&self, inputs: &InputManager, name: &str) -> bool {
+        self.action(name)
+            .is_some_and(|action| action.bindings.iter().any(|b| b.is_pressed(inputs)))
+    }
+
+    /// Checks if any binding of an action went up this frame
+    pub fn is_released(&self, inputs: &InputManager, name: &str) -> bool {
+        self.action(name)
+            .is_some_and(|action| action.bindings.iter().any(|b| b.is_released(inputs)))
+    }
+
+    /// The values of two actions as a vector, e.g. `vector_2d(inputs, "MoveX", "MoveY")`
+    pub fn vector_2d(&self, inputs: &InputManager, x: &str, y: &str) -> Vector2<f32> {
+        Vector2::new(self.value(inputs, x), self.value(inputs, y))
+    }
+}
+
+/// Lists the action contexts, pushes or pops one, or saves the actions
+#[console_command(name = "actions", inputs = "list | push <context> | pop | save")]
+pub fn actions_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let actions = world.get_resource_mut::<ActionMap>()?;
+    match args {
+        ["list"] | [] => {
+            let mut lines = vec![format!("Stack: {}", actions.stack().join(" > "))];
+            for (name, context) in &actions.contexts {
+                let names: Vec<&str> = context.actions.iter().map(|a| a.name.as_str()).collect();
+                let exclusive = if context.exclusive {
+                    " (exclusive)"
+                } else {
+                    ""
+                };
+                lines.push(format!("{}{}: {}", name, exclusive, names.join(", ")));
+            }
+            for line in lines {
+                world.console_print(line);
+            }
+        }
+        ["push", context] => {
+            actions.push_context(context);
+            world.console_print(format!("Pushed {}", context));
+        }
+        ["pop"] => match actions.pop_context() {
+            Some(context) => world.console_print(format!("Popped {}", context)),
+            None => world.console_print("Only the bottom context is left"),
+        },
+        ["save"] => {
+            actions.save()?;
+            world.console_print(format!("Saved the actions to {}", INPUT_ACTIONS_PATH));
+        }
+        _ => {
+            return Err(anyhow!("Usage: actions list | push <context> | pop | save"));
+        }
+    }
+    Ok(())
+}
@@ -1,9 +1,27 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
 use apostasy_macros::Resource;
-use winit::window::CursorGrabMode;
+use winit::{
+    dpi::PhysicalPosition,
+    window::{CursorGrabMode, CursorIcon, Window},
+};
+
+use crate::objects::resources::window_manager::{PRIMARY_WINDOW, WindowManager};
 
-use crate::objects::resources::window_manager::WindowManager;
+/// How the cursor is held by a window
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CursorGrab {
+    /// the cursor moves freely and can leave the window
+    #[default]
+    Free,
+    /// the cursor can not leave the window
+    Confined,
+    /// the cursor stays where it is, only the mouse motion is read
+    Locked,
+}
 
-#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum CursorLockMode {
     #[default]
     NoneVisible,
@@ -14,55 +32,131 @@ pub enum CursorLockMode {
     LockedVisible,
 }
 
+impl CursorLockMode {
+    pub fn new(grab: CursorGrab, is_visible: bool) -> Self {
+        match (grab, is_visible) {
+            (CursorGrab::Free, true) => CursorLockMode::NoneVisible,
+            (CursorGrab::Free, false) => CursorLockMode::NoneHidden,
+            (CursorGrab::Confined, true) => CursorLockMode::ConfinedVisible,
+            (CursorGrab::Confined, false) => CursorLockMode::ConfinedHidden,
+            (CursorGrab::Locked, true) => CursorLockMode::LockedVisible,
+            (CursorGrab::Locked, false) => CursorLockMode::LockedHidden,
+        }
+    }
+
+    pub fn grab(&self) -> CursorGrab {
+        match self {
+            CursorLockMode::NoneVisible | CursorLockMode::NoneHidden => CursorGrab::Free,
+            CursorLockMode::ConfinedHidden | CursorLockMode::ConfinedVisible => {
+                CursorGrab::Confined
+            }
+            CursorLockMode::LockedHidden | CursorLockMode::LockedVisible => CursorGrab::Locked,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        matches!(
+            self,
+            CursorLockMode::NoneVisible
+                | CursorLockMode::ConfinedVisible
+                | CursorLockMode::LockedVisible
+        )
+    }
+}
+
+/// The cursor of one window
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CursorState {
+    pub mode: CursorLockMode,
+    pub icon: CursorIcon,
+}
+
+/// The grab, visibility and icon of the cursor in each window, applied by `update_cursor`
+/// Note: `cursor_lock_mode` and `icon` are the primary window's, the other windows keep a free
+/// visible cursor unless set with `set_window_mode`
 #[derive(Resource, Clone, Default)]
 pub struct CursorManager {
     pub cursor_lock_mode: CursorLockMode,
+    /// the icon over the primary window, `CursorIcon::Default` leaves it to egui
+    pub icon: CursorIcon,
+    /// the cursor of the windows opened through the `WindowManager`, by label
+    pub windows: HashMap<String, CursorState>,
+}
+
+/// Grabs the cursor, falling back to the other grab mode where a platform lacks one, e.g.
+/// Windows can't lock and macOS can't confine
+fn set_grab(window: &Window, grab: CursorGrab) {
+    let (mode, fallback) = match grab {
+        CursorGrab::Free => (CursorGrabMode::None, CursorGrabMode::None),
+        CursorGrab::Confined => (CursorGrabMode::Confined, CursorGrabMode::Locked),
+        CursorGrab::Locked => (CursorGrabMode::Locked, CursorGrabMode::Confined),
+    };
+    let _ = window
+        .set_cursor_grab(mode)
+        .or_else(|_| window.set_cursor_grab(fallback));
+}
+
+fn apply(window: &Window, state: CursorState) {
+    window.set_cursor_visible(state.mode.is_visible());
+    set_grab(window, state.mode.grab());
+    // egui sets the icon while the pointer is over ui, the default leaves it to egui
+    if state.icon != CursorIcon::Default {
+        window.set_cursor(state.icon);
+    }
 }
 
-#[allow(unused_must_use)]
 impl CursorManager {
+    /// Applies the cursor of every open window
     pub fn update_cursor(&self, window_manager: &mut WindowManager) {
-        match self.cursor_lock_mode {
-            CursorLockMode::NoneVisible => {
-                window_manager.windows[&window_manager.primary_window_id].set_cursor_visible(true);
-                window_manager.windows[&window_manager.primary_window_id]
-                    .set_cursor_grab(CursorGrabMode::None);
-            }
-            CursorLockMode::NoneHidden => {
-                window_manager.windows[&window_manager.primary_window_id].set_cursor_visible(false);
-                window_manager.windows[&window_manager.primary_window_id]
-                    .set_cursor_grab(CursorGrabMode::None);
-            }
+        for (label, window_id) in &window_manager.labels {
+            let Some(window) = window_manager.windows.get(window_id) else {
+                continue;
+            };
+            apply(window, self.window_state(label));
+        }
+    }
 
-            CursorLockMode::ConfinedHidden => {
-                window_manager.windows[&window_manager.primary_window_id].set_cursor_visible(false);
-                window_manager.windows[&window_manager.primary_window_id]
-                    .set_cursor_grab(CursorGrabMode::Confined);
-            }
+    /// The cursor of a window by label, see `PRIMARY_WINDOW`
+    pub fn window_state(&self, label: &str) -> CursorState {
+        if label == PRIMARY_WINDOW {
+            return CursorState {
+                mode: self.cursor_lock_mode,
+                icon: self.icon,
+            };
+        }
+        self.windows.get(label).copied().unwrap_or_default()
+    }
 
-            CursorLockMode::ConfinedVisible => {
-                window_manager.windows[&window_manager.primary_window_id].set_cursor_visible(true);
-                window_manager.windows[&window_manager.primary_window_id]
-                    .set_cursor_grab(CursorGrabMode::Confined);
-            }
+    /// Sets the mode of the primary window's cursor, applied on the next `update_cursor`
+    pub fn set_mode(&mut self, mode: CursorLockMode) {
+        self.cursor_lock_mode = mode;
+    }
 
-            CursorLockMode::LockedHidden => {
-                window_manager.windows[&window_manager.primary_window_id].set_cursor_visible(false);
-                window_manager.windows[&window_manager.primary_window_id]
-                    .set_cursor_grab(CursorGrabMode::Locked);
-            }
+    /// Sets the grab of the primary window's cursor and keeps its visibility
+    pub fn set_grab(&mut self, grab: CursorGrab) {
+        self.cursor_lock_mode = CursorLockMode::new(grab, self.cursor_lock_mode.is_visible());
+    }
 
-            CursorLockMode::LockedVisible => {
-                window_manager.windows[&window_manager.primary_window_id].set_cursor_visible(true);
-                window_manager.windows[&window_manager.primary_window_id]
-                    .set_cursor_grab(CursorGrabMode::Locked);
-            }
+    /// Sets the icon of the primary window's cursor, e.g. `CursorIcon::Crosshair`
+    pub fn set_icon(&mut self, icon: CursorIcon) {
+        self.icon = icon;
+    }
+
+    /// Sets the mode of a window's cursor by label, the primary window's is `cursor_lock_mode`
+    pub fn set_window_mode(&mut self, label: &str, mode: CursorLockMode) {
+        if label == PRIMARY_WINDOW {
+            self.cursor_lock_mode = mode;
+        } else {
+            self.windows.entry(label.to_string()).or_default().mode = mode;
         }
     }
 
-    /// If the current mode is unlocked, then lock it, otherwise unlock it
-    pub fn set_mode(&mut self, mode: CursorLockMode) {
-        self.cursor_lock_mode = mode;
+    pub fn set_window_icon(&mut self, label: &str, icon: CursorIcon) {
+        if label == PRIMARY_WINDOW {
+            self.icon = icon;
+        } else {
+            self.windows.entry(label.to_string()).or_default().icon = icon;
+        }
     }
 
     /// If the current mode is unlocked, then lock it, otherwise unlock it
@@ -79,13 +173,32 @@ impl CursorManager {
         }
     }
 
+    /// Hides and locks the cursor in the primary window, e.g. for a first person camera
     pub fn grab_cursor(&mut self, window_manager: &mut WindowManager) {
-        window_manager.windows[&window_manager.primary_window_id].set_cursor_visible(false);
-        let _ = window_manager.windows[&window_manager.primary_window_id]
-            .set_cursor_grab(CursorGrabMode::Locked)
-            .or_else(|_| {
-                window_manager.windows[&window_manager.primary_window_id]
-                    .set_cursor_grab(CursorGrabMode::Locked)
-            });
+        self.cursor_lock_mode = CursorLockMode::LockedHidden;
+        self.update_cursor(window_manager);
+    }
+
+    /// Shows the cursor and lets it leave the primary window, e.g. for a menu
+    pub fn release_cursor(&mut self, window_manager: &mut WindowManager) {
+        self.cursor_lock_mode = CursorLockMode::NoneVisible;
+        self.update_cursor(window_manager);
+    }
+
+    /// Moves the cursor to a position in pixels from the top left of a window, by label
+    /// Note: errors on platforms that can't move the cursor, e.g. Wayland and the web
+    pub fn set_cursor_position(
+        &self,
+        window_manager: &WindowManager,
+        label: &str,
+        x: f64,
+        y: f64,
+    ) -> Result<()> {
+        let window = window_manager
+            .window(label)
+            .ok_or_else(|| anyhow!("No window is open as {}", label))?;
+        window
+            .set_cursor_position(PhysicalPosition::new(x, y))
+            .map_err(|e| anyhow!("Failed to move the cursor: {}", e))
     }
 }
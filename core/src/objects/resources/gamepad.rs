@@ -0,0 +1,210 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use apostasy_macros::{late_update, update};
+use gilrs::{
+    Axis, Button, EventType, GamepadId, Gilrs,
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder},
+};
+use hashbrown::HashMap;
+
+use crate::{
+    log, log_warn,
+    objects::{
+        resources::input_manager::{CapturedInput, ConnectedGamepad, InputManager},
+        world::World,
+    },
+};
+
+/// Sent when a gamepad is plugged in or unplugged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GamepadConnection {
+    Connected { id: GamepadId, name: String },
+    Disconnected { id: GamepadId },
+}
+
+/// The gilrs context the gamepads are read from, a non send resource
+pub struct GamepadBackend {
+    pub gilrs: Gilrs,
+    /// the effect rumbling each gamepad and the low and high strength it plays at
+    rumbles: HashMap<GamepadId, ((f32, f32), Effect)>,
+}
+
+/// Opens the gamepad backend and lists the gamepads already plugged in
+/// Note: logs and carries on without gamepads if the platform has no backend
+pub fn init_gamepads(world: &mut World) {
+    world.add_event::<GamepadConnection>();
+    let gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(e) => {
+            log_warn!("Gamepads are unavailable: {}", e);
+            return;
+        }
+    };
+    let connected: Vec<ConnectedGamepad> = gilrs
+        .gamepads()
+        .map(|(id, gamepad)| ConnectedGamepad {
+            id,
+            name: gamepad.name().to_string(),
+        })
+        .collect();
+    if let Ok(inputs) = world.get_resource_mut::<InputManager>() {
+        inputs.gamepad.connected = connected;
+    }
+    let _ = world.insert_non_send(GamepadBackend {
+        gilrs,
+        rumbles: HashMap::new(),
+    });
+}
+
+/// The axis an analog trigger is stored as, gilrs reports them as buttons
+fn trigger_axis(button: Button) -> Option<Axis> {
+    match button {
+        Button::LeftTrigger2 => Some(Axis::LeftZ),
+        Button::RightTrigger2 => Some(Axis::RightZ),
+        _ => None,
+    }
+}
+
+/// Captures a stick or trigger once it is pushed past halfway, see `InputManager::start_capture`
+fn capture_axis(inputs: &mut InputManager, axis: Axis, value: f32) {
+    if value.abs() > 0.5 {
+        inputs.try_capture(CapturedInput::GamepadAxis(axis));
+    }
+}
+
+/// Reads the gamepad events into the `InputManager` before the systems that read input run
+/// Note: unplugging a gamepad releases every button and centres every axis
+#[update(priority = 100, while_paused = true, main_thread = true)]
+pub fn poll_gamepads(world: &mut World) -> Result<()> {
+    let Ok(backend) = world.get_non_send_mut::<GamepadBackend>() else {
+        return Ok(());
+    };
+    let mut events = Vec::new();
+    while let Some(event) = backend.gilrs.next_event() {
+        let name = backend.gilrs.gamepad(event.id).name().to_string();
+        events.push((event.id, event.event, name));
+    }
+
+    let mut connections = Vec::new();
+    let inputs = world.get_resource_mut::<InputManager>()?;
+    for (id, event, name) in events {
+        match event {
+            EventType::ButtonPressed(button, _) => {
+                if inputs.try_capture(CapturedInput::GamepadButton(button)) {
+                    continue;
+                }
+                let gamepad = &mut inputs.gamepad;
+                gamepad.buttons_pressed.insert(button);
+                gamepad.buttons_held.insert(button);
+            }
+            EventType::ButtonReleased(button, _) => {
+                let gamepad = &mut inputs.gamepad;
+                gamepad.buttons_released.insert(button);
+                gamepad.buttons_held.remove(&button);
+            }
+            EventType::ButtonChanged(button, value, _) => {
+                if let Some(axis) = trigger_axis(button) {
+                    capture_axis(inputs, axis, value);
+                    inputs.gamepad.axes.insert(axis, value);
+                }
+            }
+            EventType::AxisChanged(axis, value, _) => {
+                capture_axis(inputs, axis, value);
+                inputs.gamepad.axes.insert(axis, value);
+            }
+            EventType::Connected => {
+                let gamepad = &mut inputs.gamepad;
+                log!("Gamepad connected: {}", name);
+                gamepad.connected.retain(|g| g.id != id);
+                gamepad.connected.push(ConnectedGamepad {
+                    id,
+                    name: name.clone(),
+                });
+                connections.push(GamepadConnection::Connected { id, name });
+            }
+            EventType::Disconnected => {
+                let gamepad = &mut inputs.gamepad;
+                log!("Gamepad disconnected: {}", name);
+                gamepad.connected.retain(|g| g.id != id);
+                gamepad
+                    .buttons_released
+                    .extend(gamepad.buttons_held.drain());
+                gamepad.axes.clear();
+                gamepad.rumbles.retain(|rumble| rumble.gamepad != id);
+                connections.push(GamepadConnection::Disconnected { id });
+            }
+            _ => {}
+        }
+    }
+    for connection in connections {
+        world.send_event(connection)?;
+    }
+    Ok(())
+}
+
+/// Plays the effect rumbling a gamepad's low and high frequency motors at a strength
+fn rumble_effect(gilrs: &mut Gilrs, gamepad: GamepadId, low: f32, high: f32) -> Result<Effect> {
+    let magnitude = |strength: f32| (strength * u16::MAX as f32) as u16;
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Strong {
+                magnitude: magnitude(low),
+            },
+            ..Default::default()
+        })
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Weak {
+                magnitude: magnitude(high),
+            },
+            ..Default::default()
+        })
+        .gamepads(&[gamepad])
+        .finish(gilrs)?;
+    effect.play()?;
+    Ok(effect)
+}
+
+/// Plays the rumbles requested with `InputManager::rumble` once the systems have requested
+/// them, mixing the ones overlapping on a gamepad
+/// Note: gamepads without force feedback are skipped
+#[late_update(main_thread = true)]
+pub fn play_rumbles(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource_mut::<InputManager>()?;
+    let now = Instant::now();
+    inputs.gamepad.rumbles.retain(|rumble| rumble.until > now);
+    let strengths: Vec<(GamepadId, (f32, f32))> = inputs
+        .gamepad
+        .connected
+        .iter()
+        .map(|gamepad| (gamepad.id, inputs.gamepad.rumble_strength(gamepad.id)))
+        .collect();
+
+    let Ok(backend) = world.get_non_send_mut::<GamepadBackend>() else {
+        return Ok(());
+    };
+    // the effects of unplugged gamepads
+    backend
+        .rumbles
+        .retain(|gamepad, _| strengths.iter().any(|(id, _)| id == gamepad));
+    for (gamepad, strength) in strengths {
+        if backend.rumbles.get(&gamepad).map(|(playing, _)| *playing) == Some(strength) {
+            continue;
+        }
+        if let Some((_, effect)) = backend.rumbles.remove(&gamepad) {
+            let _ = effect.stop();
+        }
+        if strength == (0.0, 0.0) || !backend.gilrs.gamepad(gamepad).is_ff_supported() {
+            continue;
+        }
+        match rumble_effect(&mut backend.gilrs, gamepad, strength.0, strength.1) {
+            Ok(effect) => {
+                backend.rumbles.insert(gamepad, (strength, effect));
+            }
+            Err(e) => {
+                log_warn!("Failed to rumble a gamepad: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
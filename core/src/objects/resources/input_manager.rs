@@ -1,14 +1,20 @@
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
 use apostasy_macros::{Resource, late_update};
 use cgmath::{Vector2, Vector3};
+use gilrs::{Axis, Button, GamepadId};
 use hashbrown::{HashMap, HashSet};
 use winit::{
-    dpi::PhysicalPosition,
-    event::{DeviceEvent, MouseButton, MouseScrollDelta, WindowEvent},
-    keyboard::PhysicalKey,
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, Ime, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{Key, KeyCode, NativeKeyCode, PhysicalKey},
 };
 
-use crate::{log, log_warn, objects::world::World};
+use crate::{
+    log, log_warn,
+    objects::{resources::window_manager::WindowManager, world::World},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyAction {
@@ -16,9 +22,22 @@ pub enum KeyAction {
     Release,
     Hold,
 }
+/// Whether a keybind follows the key's place on the keyboard or the character printed on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyBindMode {
+    /// the same place on every layout, e.g. WASD stays under the left hand on AZERTY
+    #[default]
+    Physical,
+    /// the key that types the character on the current layout, e.g. `Z` for undo on AZERTY
+    Logical,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyBind {
     pub key: PhysicalKey,
+    /// the key matched in `KeyBindMode::Logical`, character keys are lowercase
+    pub logical_key: Option<Key>,
+    pub mode: KeyBindMode,
     pub action: KeyAction,
     pub name: String,
 }
@@ -26,12 +45,34 @@ impl KeyBind {
     pub fn new(key: PhysicalKey, action: KeyAction, name: &str) -> Self {
         Self {
             key,
+            logical_key: None,
+            mode: KeyBindMode::Physical,
+            action,
+            name: name.to_string(),
+        }
+    }
+
+    /// A keybind that follows the layout, usage:
+    /// `KeyBind::logical(Key::Character("z".into()), KeyAction::Press, "Undo")`
+    pub fn logical(key: Key, action: KeyAction, name: &str) -> Self {
+        Self {
+            key: PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+            logical_key: Some(normalize_key(&key)),
+            mode: KeyBindMode::Logical,
             action,
             name: name.to_string(),
         }
     }
 }
 
+/// Lowercases character keys so shift doesn't change which bind they match
+pub fn normalize_key(key: &Key) -> Key {
+    match key {
+        Key::Character(text) => Key::Character(text.to_lowercase().into()),
+        key => key.clone(),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MouseBind {
     pub key: MouseButton,
@@ -49,11 +90,183 @@ impl MouseBind {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GamepadBind {
+    pub button: Button,
+    pub action: KeyAction,
+    pub name: String,
+}
+
+impl GamepadBind {
+    pub fn new(button: Button, action: KeyAction, name: &str) -> Self {
+        Self {
+            button,
+            action,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// A value from -1 to 1 read with `InputManager::axis`, from a gamepad stick or trigger, a pair
+/// of keys, or both
+/// Note: the input that is pushed furthest wins
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisBind {
+    pub name: String,
+    pub gamepad_axis: Option<Axis>,
+    /// the key that pushes the axis to 1
+    pub positive: Option<PhysicalKey>,
+    /// the key that pushes the axis to -1
+    pub negative: Option<PhysicalKey>,
+    pub invert: bool,
+}
+
+impl AxisBind {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            gamepad_axis: None,
+            positive: None,
+            negative: None,
+            invert: false,
+        }
+    }
+
+    pub fn with_gamepad_axis(mut self, axis: Axis) -> Self {
+        self.gamepad_axis = Some(axis);
+        self
+    }
+
+    pub fn with_keys(mut self, positive: PhysicalKey, negative: PhysicalKey) -> Self {
+        self.positive = Some(positive);
+        self.negative = Some(negative);
+        self
+    }
+
+    pub fn inverted(mut self) -> Self {
+        self.invert = true;
+        self
+    }
+}
+
+/// A gamepad that is plugged in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectedGamepad {
+    pub id: GamepadId,
+    pub name: String,
+}
+
+/// A rumble requested with `InputManager::rumble`, played by `play_rumbles` until it ends
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rumble {
+    pub gamepad: GamepadId,
+    /// the strength of the strong, low frequency motor from 0 to 1
+    pub low: f32,
+    /// the strength of the weak, high frequency motor from 0 to 1
+    pub high: f32,
+    pub until: Instant,
+}
+
+/// The buttons and axes of every connected gamepad, filled by `poll_gamepads`
+/// Note: the gamepads are merged, a button held on any of them is held
+#[derive(Debug, Clone)]
+pub struct GamepadInput {
+    pub connected: Vec<ConnectedGamepad>,
+    pub buttons_held: HashSet<Button>,
+    /// the last value of each axis before the deadzone, from the gamepad that moved it last
+    pub axes: HashMap<Axis, f32>,
+    /// axis values closer to 0 than this read as 0, the rest is rescaled to start at 0
+    pub deadzone: f32,
+    /// the rumbles that are playing, see `InputManager::rumble`
+    pub rumbles: Vec<Rumble>,
+    /// false while the window is unfocused, nothing rumbles then
+    pub is_focused: bool,
+
+    // Resets each frame
+    pub buttons_pressed: HashSet<Button>,
+    pub buttons_released: HashSet<Button>,
+}
+
+impl Default for GamepadInput {
+    fn default() -> Self {
+        Self {
+            connected: Vec::new(),
+            buttons_held: HashSet::new(),
+            axes: HashMap::new(),
+            deadzone: 0.15,
+            rumbles: Vec::new(),
+            is_focused: true,
+            buttons_pressed: HashSet::new(),
+            buttons_released: HashSet::new(),
+        }
+    }
+}
+
+impl GamepadInput {
+    /// The value of an axis from -1 to 1 with the deadzone applied
+    pub fn axis(&self, axis: Axis) -> f32 {
+        let value = self
+            .axes
+            .get(&axis)
+            .copied()
+            .unwrap_or(0.0)
+            .clamp(-1.0, 1.0);
+        let deadzone = self.deadzone.clamp(0.0, 0.99);
+        if value.abs() <= deadzone {
+            return 0.0;
+        }
+        value.signum() * (value.abs() - deadzone) / (1.0 - deadzone)
+    }
+
+    /// The low and high strength a gamepad rumbles at, the rumbles playing on it added up
+    pub fn rumble_strength(&self, gamepad: GamepadId) -> (f32, f32) {
+        let (low, high) = self
+            .rumbles
+            .iter()
+            .filter(|rumble| rumble.gamepad == gamepad)
+            .fold((0.0, 0.0), |(low, high), rumble| {
+                (low + rumble.low, high + rumble.high)
+            });
+        (low.min(1.0), high.min(1.0))
+    }
+}
+
+/// An input recorded while capturing, see `InputManager::start_capture`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapturedInput {
+    Key(PhysicalKey),
+    Mouse(MouseButton),
+    GamepadButton(Button),
+    GamepadAxis(Axis),
+}
+
+/// Text typed this frame, for text fields outside egui, see `InputManager::text_input`
+/// Note: backspace, enter and the arrows are not text, read them from `keys_pressed`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextInput {
+    /// typed or committed by the IME, without control characters
+    Text(String),
+    /// the text the IME is composing, shown in the field until it is committed, an empty text
+    /// clears it
+    /// Note: the cursor is a byte range in the text
+    Preedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+}
+
 #[derive(Resource, Clone, Default)]
 pub struct InputManager {
     pub keybinds: HashMap<String, KeyBind>,
     pub mouse_keybinds: HashMap<String, MouseBind>,
+    pub gamepad_binds: HashMap<String, GamepadBind>,
+    pub axis_binds: HashMap<String, AxisBind>,
+    pub gamepad: GamepadInput,
     pub keys_held: HashSet<PhysicalKey>,
+    /// the logical keys held, for `KeyBindMode::Logical` binds
+    pub logical_keys_held: HashSet<Key>,
+    /// the logical key each physical key last typed, learned as keys are pressed
+    pub key_layout: HashMap<PhysicalKey, Key>,
     pub mouse_held: HashSet<MouseButton>,
     pub mouse_position: PhysicalPosition<f64>,
     pub mouse_delta: (f64, f64),
@@ -62,8 +275,27 @@ pub struct InputManager {
     // Resets each frame
     pub keys_pressed: HashSet<PhysicalKey>,
     pub keys_released: HashSet<PhysicalKey>,
+    pub logical_keys_pressed: HashSet<Key>,
+    pub logical_keys_released: HashSet<Key>,
     pub mouse_pressed: HashSet<MouseButton>,
     pub mouse_released: HashSet<MouseButton>,
+
+    /// true while egui is typing into a field, keybinds are inactive so typing doesn't move the
+    /// player, the raw key sets still fill
+    pub keyboard_captured: bool,
+    /// true while the pointer is over or dragging egui, mousebinds are inactive
+    pub pointer_captured: bool,
+    /// the binding waiting for the next input, see `start_capture`
+    pub capturing: Option<String>,
+    /// the last input assigned by a capture and the binding it went to
+    pub last_capture: Option<(String, CapturedInput)>,
+
+    /// the text typed this frame, filled whether or not egui has the keyboard
+    pub text_input: Vec<TextInput>,
+    /// true while the IME is composing, typed keys go to it instead of `text_input`
+    pub is_composing: bool,
+    /// set with `set_text_input_enabled`
+    pub text_input_enabled: bool,
 }
 
 /// TODO: DOCUMENT THIS
@@ -108,6 +340,112 @@ impl InputManager {
         // self.serialize_input_manager().unwrap();
     }
 
+    pub fn register_gamepad_bind(&mut self, bind: GamepadBind) {
+        log!("registering gamepad binding: {}", bind.name.clone());
+        if self.gamepad_binds.contains_key(&bind.name) {
+            log_warn!("Gamepad binding {} already exists", bind.name);
+            return;
+        }
+        self.gamepad_binds.insert(bind.name.clone(), bind);
+    }
+
+    /// Rumbles a gamepad for a while, `low` and `high` are the strength of its low and high
+    /// frequency motors from 0 to 1, usage:
+    /// `inputs.rumble(gamepad, 0.8, 0.3, Duration::from_millis(200))`
+    /// Note: rumbles overlapping on a gamepad are added up, requests are ignored and the
+    /// rumbles stop while the window is unfocused
+    pub fn rumble(&mut self, gamepad: GamepadId, low: f32, high: f32, duration: Duration) {
+        if !self.gamepad.is_focused {
+            return;
+        }
+        self.gamepad.rumbles.push(Rumble {
+            gamepad,
+            low: low.clamp(0.0, 1.0),
+            high: high.clamp(0.0, 1.0),
+            until: Instant::now() + duration,
+        });
+    }
+
+    /// Stops every rumble playing on a gamepad
+    pub fn stop_rumble(&mut self, gamepad: GamepadId) {
+        self.gamepad
+            .rumbles
+            .retain(|rumble| rumble.gamepad != gamepad);
+    }
+
+    /// Registers an axis read with `axis`, usage:
+    /// ```rust
+    /// inputs.register_axis(
+    ///     AxisBind::new("move_x")
+    ///         .with_gamepad_axis(Axis::LeftStickX)
+    ///         .with_keys(PhysicalKey::Code(KeyCode::KeyD), PhysicalKey::Code(KeyCode::KeyA)),
+    /// );
+    /// ```
+    pub fn register_axis(&mut self, axis: AxisBind) {
+        log!("registering axis: {}", axis.name.clone());
+        if self.axis_binds.contains_key(&axis.name) {
+            log_warn!("Axis {} already exists", axis.name);
+            return;
+        }
+        self.axis_binds.insert(axis.name.clone(), axis);
+    }
+
+    /// Assigns the next input that fits the binding called `name` to it, a key for a keybind, a
+    /// mouse button for a mousebind, a gamepad button for a gamepad binding, or a stick or
+    /// trigger pushed past halfway for an axis
+    /// Note: escape cancels, inputs that don't fit are ignored, the captured input doesn't
+    /// reach the binds
+    pub fn start_capture(&mut self, name: &str) {
+        self.capturing = Some(name.to_string());
+    }
+
+    pub fn cancel_capture(&mut self) {
+        self.capturing = None;
+    }
+
+    pub fn is_capturing(&self, name: &str) -> bool {
+        self.capturing.as_deref() == Some(name)
+    }
+
+    /// Assigns an input to the binding being captured, returns true if it was used
+    pub fn try_capture(&mut self, input: CapturedInput) -> bool {
+        let Some(name) = self.capturing.clone() else {
+            return false;
+        };
+        if input == CapturedInput::Key(PhysicalKey::Code(KeyCode::Escape)) {
+            self.capturing = None;
+            return true;
+        }
+        let assigned = match input {
+            CapturedInput::Key(key) => {
+                let logical_key = self.key_layout.get(&key).cloned();
+                self.keybinds.get_mut(&name).map(|bind| {
+                    bind.key = key;
+                    bind.logical_key = logical_key;
+                })
+            }
+            CapturedInput::Mouse(button) => self
+                .mouse_keybinds
+                .get_mut(&name)
+                .map(|bind| bind.key = button),
+            CapturedInput::GamepadButton(button) => self
+                .gamepad_binds
+                .get_mut(&name)
+                .map(|bind| bind.button = button),
+            CapturedInput::GamepadAxis(axis) => self
+                .axis_binds
+                .get_mut(&name)
+                .map(|bind| bind.gamepad_axis = Some(axis)),
+        };
+        if assigned.is_none() {
+            return false;
+        }
+        log!("Bound {} to {:?}", name, input);
+        self.capturing = None;
+        self.last_capture = Some((name, input));
+        true
+    }
+
     /// Detects if a keybind with the specified name is active
     pub fn is_keybind_active(&self, name: &str) -> bool {
         let key = self.keybinds.get(name);
@@ -115,7 +453,20 @@ impl InputManager {
             log_warn!("Key: {} does not exist", name.to_string());
             return false;
         }
+        if self.keyboard_captured {
+            return false;
+        }
         let key = key.unwrap();
+        if key.mode == KeyBindMode::Logical {
+            let Some(logical_key) = &key.logical_key else {
+                return false;
+            };
+            return match key.action {
+                KeyAction::Press => self.logical_keys_pressed.contains(logical_key),
+                KeyAction::Release => self.logical_keys_released.contains(logical_key),
+                KeyAction::Hold => self.logical_keys_held.contains(logical_key),
+            };
+        }
         match key.action {
             KeyAction::Press => self.keys_pressed.contains(&key.key),
             KeyAction::Release => self.keys_released.contains(&key.key),
@@ -129,6 +480,9 @@ impl InputManager {
         if key.is_none() {
             return false;
         }
+        if self.pointer_captured {
+            return false;
+        }
         let key = key.unwrap();
         match key.action {
             KeyAction::Press => self.mouse_pressed.contains(&key.key),
@@ -137,6 +491,41 @@ impl InputManager {
         }
     }
 
+    /// Detects if a gamepad binding with the specified name is active
+    pub fn is_gamepad_bind_active(&self, name: &str) -> bool {
+        let Some(bind) = self.gamepad_binds.get(name) else {
+            return false;
+        };
+        match bind.action {
+            KeyAction::Press => self.gamepad.buttons_pressed.contains(&bind.button),
+            KeyAction::Release => self.gamepad.buttons_released.contains(&bind.button),
+            KeyAction::Hold => self.gamepad.buttons_held.contains(&bind.button),
+        }
+    }
+
+    /// Gets the value of an axis from -1 to 1, 0 if there is no axis with the name
+    /// Note: its keys are ignored while egui has the keyboard, like keybinds
+    pub fn axis(&self, name: &str) -> f32 {
+        let Some(axis) = self.axis_binds.get(name) else {
+            log_warn!("Axis: {} does not exist", name);
+            return 0.0;
+        };
+        let held = |key: Option<PhysicalKey>| {
+            !self.keyboard_captured && key.is_some_and(|key| self.keys_held.contains(&key))
+        };
+        let keys = held(axis.positive) as i32 as f32 - held(axis.negative) as i32 as f32;
+        let stick = axis
+            .gamepad_axis
+            .map(|gamepad_axis| self.gamepad.axis(gamepad_axis))
+            .unwrap_or(0.0);
+        let value = if stick.abs() > keys.abs() {
+            stick
+        } else {
+            keys
+        };
+        if axis.invert { -value } else { value }
+    }
+
     pub fn input_vector_2d(&self, left: &str, right: &str, up: &str, down: &str) -> Vector2<f32> {
         let mut x = 0.0;
         let mut y = 0.0;
@@ -188,6 +577,48 @@ impl InputManager {
         Vector3::new(x, y, z)
     }
 
+    /// Allows the IME and shows the virtual keyboard on platforms that have one, call it when an
+    /// in game text field is focused and again with false when it is not
+    /// Note: egui turns the IME on and off for its own fields
+    pub fn set_text_input_enabled(&mut self, enabled: bool, window_manager: &WindowManager) {
+        self.text_input_enabled = enabled;
+        if !enabled {
+            self.is_composing = false;
+        }
+        if let Some(window) = window_manager
+            .windows
+            .get(&window_manager.primary_window_id)
+        {
+            window.set_ime_allowed(enabled);
+        }
+    }
+
+    /// Places the IME candidate box next to a text field, in window pixels
+    pub fn set_text_input_area(
+        &self,
+        position: PhysicalPosition<f64>,
+        size: PhysicalSize<f64>,
+        window_manager: &WindowManager,
+    ) {
+        if let Some(window) = window_manager
+            .windows
+            .get(&window_manager.primary_window_id)
+        {
+            window.set_ime_cursor_area(position, size);
+        }
+    }
+
+    /// All the text typed this frame joined, without the preedit
+    pub fn typed_text(&self) -> String {
+        self.text_input
+            .iter()
+            .filter_map(|input| match input {
+                TextInput::Text(text) => Some(text.as_str()),
+                TextInput::Preedit { .. } => None,
+            })
+            .collect()
+    }
+
     pub fn handle_device_event(&mut self, event: DeviceEvent) {
         if let DeviceEvent::MouseMotion { delta } = event {
             self.mouse_delta = delta;
@@ -197,15 +628,49 @@ impl InputManager {
     pub fn handle_input_event(&mut self, event: WindowEvent) {
         match event {
             WindowEvent::KeyboardInput { event, .. } => {
+                let logical_key = normalize_key(&event.logical_key);
+                if event.state.is_pressed() {
+                    self.key_layout
+                        .insert(event.physical_key, logical_key.clone());
+                }
+                if event.state.is_pressed()
+                    && !event.repeat
+                    && self.try_capture(CapturedInput::Key(event.physical_key))
+                {
+                    return;
+                }
+                if event.state.is_pressed()
+                    && !self.is_composing
+                    && let Some(text) = &event.text
+                {
+                    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+                    if !text.is_empty() {
+                        self.text_input.push(TextInput::Text(text));
+                    }
+                }
                 if event.state.is_pressed() {
                     self.keys_pressed.insert(event.physical_key);
                     self.keys_held.insert(event.physical_key);
+                    self.logical_keys_pressed.insert(logical_key.clone());
+                    self.logical_keys_held.insert(logical_key);
                 } else {
                     self.keys_released.insert(event.physical_key);
                     self.keys_held.remove(&event.physical_key);
+                    // the key typed when it went down, the modifiers may have changed since
+                    let pressed_as = self
+                        .key_layout
+                        .get(&event.physical_key)
+                        .cloned()
+                        .unwrap_or(logical_key.clone());
+                    self.logical_keys_held.remove(&pressed_as);
+                    self.logical_keys_held.remove(&logical_key);
+                    self.logical_keys_released.insert(pressed_as);
                 }
             }
             WindowEvent::MouseInput { state, button, .. } => {
+                if state.is_pressed() && self.try_capture(CapturedInput::Mouse(button)) {
+                    return;
+                }
                 if state.is_pressed() {
                     self.mouse_pressed.insert(button);
                     self.mouse_held.insert(button);
@@ -222,12 +687,30 @@ impl InputManager {
                 self.mouse_delta = delta;
                 self.mouse_position = position;
             }
+            WindowEvent::Ime(ime) => match ime {
+                Ime::Preedit(text, cursor) => {
+                    self.is_composing = !text.is_empty();
+                    self.text_input.push(TextInput::Preedit { text, cursor });
+                }
+                Ime::Commit(text) => {
+                    self.is_composing = false;
+                    self.text_input.push(TextInput::Text(text));
+                }
+                Ime::Disabled => self.is_composing = false,
+                Ime::Enabled => {}
+            },
             WindowEvent::MouseWheel { delta, .. } => match delta {
                 MouseScrollDelta::LineDelta(x, y) => {
                     self.scroll_delta = (x, y);
                 }
                 _ => {}
             },
+            WindowEvent::Focused(is_focused) => {
+                self.gamepad.is_focused = is_focused;
+                if !is_focused {
+                    self.gamepad.rumbles.clear();
+                }
+            }
             _ => {}
         }
     }
@@ -360,8 +843,13 @@ pub fn clear_actions(world: &mut World) -> Result<()> {
 
     input_manager.keys_pressed.clear();
     input_manager.keys_released.clear();
+    input_manager.logical_keys_pressed.clear();
+    input_manager.logical_keys_released.clear();
     input_manager.mouse_pressed.clear();
     input_manager.mouse_released.clear();
+    input_manager.gamepad.buttons_pressed.clear();
+    input_manager.gamepad.buttons_released.clear();
+    input_manager.text_input.clear();
     input_manager.mouse_delta = (0.0, 0.0);
     input_manager.scroll_delta = (0.0, 0.0);
 
@@ -1,3 +1,6 @@
+pub mod action_map;
 pub mod cursor_manager;
+pub mod gamepad;
 pub mod input_manager;
 pub mod window_manager;
+pub mod window_settings;
@@ -1,12 +1,84 @@
 use std::{collections::HashMap, sync::Arc};
 
-use apostasy_macros::Resource;
-use winit::window::{Window, WindowId};
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Resource, console_command};
+use winit::{
+    monitor::{MonitorHandle, VideoModeHandle},
+    window::{Fullscreen, Window, WindowAttributes, WindowId},
+};
+
+use crate::objects::world::World;
+
+/// Sent when a window moves to a monitor with another scale or the scale is changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleFactorChanged {
+    /// the label of the window, see `WindowManager::request_window`
+    pub window: String,
+    pub scale_factor: f64,
+}
+
+/// A resolution and refresh rate a monitor can go fullscreen at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate_millihertz: u32,
+    pub bit_depth: u16,
+}
+
+impl VideoMode {
+    fn from_handle(mode: &VideoModeHandle) -> Self {
+        Self {
+            width: mode.size().width,
+            height: mode.size().height,
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+            bit_depth: mode.bit_depth(),
+        }
+    }
+
+    pub fn refresh_rate(&self) -> f32 {
+        self.refresh_rate_millihertz as f32 / 1000.0
+    }
+}
+
+/// A monitor connected to the computer, listed by `WindowManager::monitors`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    /// the index passed to `WindowManager::set_fullscreen_on`
+    pub index: usize,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub position: (i32, i32),
+    /// the scale of the ui on it, 1 is 96 dpi
+    pub scale_factor: f64,
+    pub refresh_rate_millihertz: Option<u32>,
+    pub is_primary: bool,
+    /// the resolutions it can go fullscreen at, the largest and fastest first
+    pub video_modes: Vec<VideoMode>,
+}
+
+impl Monitor {
+    pub fn dpi(&self) -> f64 {
+        self.scale_factor * 96.0
+    }
+}
+
+/// The label of the window the engine opens, cameras with an empty `window` draw into it
+pub const PRIMARY_WINDOW: &str = "Primary";
 
 #[derive(Resource, Clone)]
 pub struct WindowManager {
     pub windows: HashMap<WindowId, Arc<Window>>,
     pub primary_window_id: WindowId,
+    /// the label of every open window, see `request_window`
+    pub labels: HashMap<String, WindowId>,
+    /// the egui context of every window but the primary one, which is `EguiContext`
+    pub egui_contexts: HashMap<String, egui::Context>,
+    /// windows to open, the engine opens them before the next frame
+    pub open_requests: Vec<(String, WindowAttributes)>,
+    /// windows to close, the engine closes them before the next frame
+    pub close_requests: Vec<String>,
 }
 
 impl Default for WindowManager {
@@ -14,6 +86,197 @@ impl Default for WindowManager {
         Self {
             windows: HashMap::new(),
             primary_window_id: WindowId::dummy(),
+            labels: HashMap::new(),
+            egui_contexts: HashMap::new(),
+            open_requests: Vec::new(),
+            close_requests: Vec::new(),
+        }
+    }
+}
+
+impl WindowManager {
+    /// Opens another window before the next frame, usage:
+    /// ```rust
+    /// window_manager.request_window(
+    ///     "Map",
+    ///     Window::default_attributes().with_title("Map"),
+    /// );
+    /// ```
+    /// Note: a `Camera` whose `window` is the label draws into it, nothing is drawn without one
+    pub fn request_window(&mut self, label: &str, attributes: WindowAttributes) {
+        self.open_requests.push((label.to_string(), attributes));
+    }
+
+    /// Closes a window opened with `request_window` before the next frame, the primary window
+    /// can not be closed this way, see `world.exit()`
+    pub fn close_window(&mut self, label: &str) {
+        if label != PRIMARY_WINDOW {
+            self.close_requests.push(label.to_string());
+        }
+    }
+
+    pub fn window_id(&self, label: &str) -> Option<WindowId> {
+        self.labels.get(label).copied()
+    }
+
+    pub fn window(&self, label: &str) -> Option<&Arc<Window>> {
+        self.windows.get(&self.window_id(label)?)
+    }
+
+    /// The label of an open window
+    pub fn label(&self, window_id: WindowId) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(_, id)| **id == window_id)
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// The egui context of a secondary window, draw into it to show ui in that window
+    pub fn egui_context(&self, label: &str) -> Option<egui::Context> {
+        self.egui_contexts.get(label).cloned()
+    }
+
+    pub fn is_primary(&self, window_id: WindowId) -> bool {
+        window_id == self.primary_window_id
+    }
+
+    fn monitor_handles(&self) -> Vec<MonitorHandle> {
+        match self.windows.get(&self.primary_window_id) {
+            Some(window) => window.available_monitors().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Lists the connected monitors, empty until the primary window is open
+    pub fn monitors(&self) -> Vec<Monitor> {
+        let primary = self
+            .windows
+            .get(&self.primary_window_id)
+            .and_then(|window| window.primary_monitor());
+        self.monitor_handles()
+            .into_iter()
+            .enumerate()
+            .map(|(index, monitor)| {
+                let mut video_modes: Vec<VideoMode> = monitor
+                    .video_modes()
+                    .map(|mode| VideoMode::from_handle(&mode))
+                    .collect();
+                video_modes.sort_by_key(|mode| {
+                    std::cmp::Reverse((
+                        mode.width * mode.height,
+                        mode.refresh_rate_millihertz,
+                        mode.bit_depth,
+                    ))
+                });
+                video_modes.dedup();
+                Monitor {
+                    index,
+                    name: monitor.name().unwrap_or(format!("Monitor {}", index)),
+                    width: monitor.size().width,
+                    height: monitor.size().height,
+                    position: (monitor.position().x, monitor.position().y),
+                    scale_factor: monitor.scale_factor(),
+                    refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+                    is_primary: primary.as_ref() == Some(&monitor),
+                    video_modes,
+                }
+            })
+            .collect()
+    }
+
+    /// Makes the primary window fullscreen on a monitor of `monitors`, at a video mode of it or
+    /// borderless if `mode` is none
+    /// Note: `WindowSettings` are applied over it when they change
+    pub fn set_fullscreen_on(&self, monitor: usize, mode: Option<VideoMode>) -> Result<()> {
+        let window = self
+            .windows
+            .get(&self.primary_window_id)
+            .ok_or_else(|| anyhow!("The window is not open"))?;
+        let handle = self
+            .monitor_handles()
+            .into_iter()
+            .nth(monitor)
+            .ok_or_else(|| anyhow!("No monitor {}", monitor))?;
+        let fullscreen = match mode {
+            Some(mode) => {
+                let video_mode = handle
+                    .video_modes()
+                    .find(|handle| VideoMode::from_handle(handle) == mode)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Monitor {} has no {}x{} mode at {}Hz",
+                            monitor,
+                            mode.width,
+                            mode.height,
+                            mode.refresh_rate()
+                        )
+                    })?;
+                Fullscreen::Exclusive(video_mode)
+            }
+            None => Fullscreen::Borderless(Some(handle)),
+        };
+        window.set_fullscreen(Some(fullscreen));
+        Ok(())
+    }
+}
+
+/// Lists the open windows or the monitors, opens another window or closes one
+#[console_command(
+    name = "window",
+    inputs = "list | open <label> | close <label> | monitors"
+)]
+pub fn window_command(world: &mut World, args: &[&str]) -> Result<()> {
+    let window_manager = world.get_resource_mut::<WindowManager>()?;
+    match args {
+        ["list"] | [] => {
+            let mut labels: Vec<String> = window_manager.labels.keys().cloned().collect();
+            labels.sort();
+            world.console_print(format!("Windows: {}", labels.join(", ")));
+        }
+        ["open", label] => {
+            window_manager.request_window(label, Window::default_attributes().with_title(*label));
+            world.console_print(format!("Opening {}, set a camera's window to it", label));
+        }
+        ["close", label] => {
+            if window_manager.window_id(label).is_none() || *label == PRIMARY_WINDOW {
+                return Err(anyhow!("No secondary window is open as {}", label));
+            }
+            window_manager.close_window(label);
+            world.console_print(format!("Closing {}", label));
+        }
+        ["monitors"] => {
+            let mut lines = Vec::new();
+            for monitor in window_manager.monitors() {
+                let primary = if monitor.is_primary { " (primary)" } else { "" };
+                let best = monitor.video_modes.first().map(|mode| {
+                    format!(
+                        "{}x{} at {:.0}Hz",
+                        mode.width,
+                        mode.height,
+                        mode.refresh_rate()
+                    )
+                });
+                lines.push(format!(
+                    "{}: {}{}, {}x{}, {:.0} dpi, {} modes, best {}",
+                    monitor.index,
+                    monitor.name,
+                    primary,
+                    monitor.width,
+                    monitor.height,
+                    monitor.dpi(),
+                    monitor.video_modes.len(),
+                    best.unwrap_or("-".to_string())
+                ));
+            }
+            for line in lines {
+                world.console_print(line);
+            }
+        }
+        _ => {
+            return Err(anyhow!(
+                "Usage: window list | open <label> | close <label> | monitors"
+            ));
         }
     }
+    Ok(())
 }
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use apostasy_macros::{Resource, update};
+use serde::{Deserialize, Serialize};
+use winit::{
+    dpi::PhysicalSize,
+    window::{Fullscreen, Window, WindowAttributes},
+};
+
+use crate::{
+    objects::{resources::window_manager::WindowManager, world::World},
+    rendering::shared::rendering_config::RenderingConfig,
+    utils::yaml,
+};
+
+/// The file the window settings are persisted to
+pub const WINDOW_SETTINGS_PATH: &str = "res/window.yaml";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    /// a window without decorations covering the monitor
+    Borderless,
+    /// takes over the monitor at `width` by `height`, borderless if the monitor has no such mode
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Borderless,
+        WindowMode::Fullscreen,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Borderless => "Borderless",
+            WindowMode::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+/// The title, size and mode of the primary window, loaded from and saved to
+/// `WINDOW_SETTINGS_PATH`
+/// Note: changes are applied to the window at the start of the next frame when `is_dirty` is set
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub title: String,
+    /// the size in pixels, the resolution while fullscreen
+    pub width: u32,
+    pub height: u32,
+    /// the smallest the window can be resized to, 0 for no limit
+    pub min_width: u32,
+    pub min_height: u32,
+    pub mode: WindowMode,
+    /// presents with vsync whatever the present mode of the `RenderingConfig` is
+    pub vsync: bool,
+    pub resizable: bool,
+    /// set when the settings need to be applied to the window
+    #[serde(skip)]
+    pub is_dirty: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            title: "Apostasy".to_string(),
+            width: 1280,
+            height: 720,
+            min_width: 0,
+            min_height: 0,
+            mode: WindowMode::Windowed,
+            vsync: false,
+            resizable: true,
+            is_dirty: true,
+        }
+    }
+}
+
+impl WindowSettings {
+    /// Loads the settings from disk, falling back to the defaults if they are missing or invalid
+    pub fn load() -> Self {
+        let mut settings: Self = yaml::load_or_default(WINDOW_SETTINGS_PATH);
+        settings.is_dirty = true;
+        settings
+    }
+
+    /// Saves the settings to `WINDOW_SETTINGS_PATH`
+    pub fn save(&self) -> Result<()> {
+        yaml::save(WINDOW_SETTINGS_PATH, self)
+    }
+
+    fn min_size(&self) -> Option<PhysicalSize<u32>> {
+        (self.min_width > 0 || self.min_height > 0)
+            .then(|| PhysicalSize::new(self.min_width, self.min_height))
+    }
+
+    /// The attributes the primary window is opened with
+    /// Note: a fullscreen window opens borderless, the video mode is picked once it is open
+    pub fn attributes(&self) -> WindowAttributes {
+        let fullscreen =
+            (self.mode != WindowMode::Windowed).then_some(Fullscreen::Borderless(None));
+        let mut attributes = Window::default_attributes()
+            .with_title(self.title.clone())
+            .with_inner_size(PhysicalSize::new(self.width.max(1), self.height.max(1)))
+            .with_resizable(self.resizable)
+            .with_fullscreen(fullscreen);
+        if let Some(min_size) = self.min_size() {
+            attributes = attributes.with_min_inner_size(min_size);
+        }
+        attributes
+    }
+
+    /// The fullscreen of the window for the mode, the monitor's video mode closest to the size
+    /// with the highest refresh rate while fullscreen
+    fn fullscreen(&self, window: &Window) -> Option<Fullscreen> {
+        match self.mode {
+            WindowMode::Windowed => None,
+            WindowMode::Borderless => Some(Fullscreen::Borderless(None)),
+            WindowMode::Fullscreen => {
+                let video_mode = window.current_monitor().and_then(|monitor| {
+                    monitor.video_modes().min_by_key(|mode| {
+                        let size = mode.size();
+                        (
+                            size.width.abs_diff(self.width) + size.height.abs_diff(self.height),
+                            u32::MAX - mode.refresh_rate_millihertz(),
+                        )
+                    })
+                });
+                match video_mode {
+                    Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                    None => Some(Fullscreen::Borderless(None)),
+                }
+            }
+        }
+    }
+}
+
+impl WindowManager {
+    /// Applies the title, size and mode of the settings to the primary window
+    pub fn apply_settings(&self, settings: &WindowSettings) {
+        let Some(window) = self.windows.get(&self.primary_window_id) else {
+            return;
+        };
+        window.set_title(&settings.title);
+        window.set_resizable(settings.resizable);
+        window.set_min_inner_size(settings.min_size());
+        let fullscreen = settings.fullscreen(window);
+        if fullscreen.is_none() {
+            let _ = window.request_inner_size(PhysicalSize::new(
+                settings.width.max(1),
+                settings.height.max(1),
+            ));
+        }
+        window.set_fullscreen(fullscreen);
+    }
+}
+
+/// Applies the `WindowSettings` to the primary window once they change
+#[update(while_paused = true, main_thread = true)]
+pub fn apply_window_settings(world: &mut World) -> Result<()> {
+    let Ok(settings) = world.get_resource_mut::<WindowSettings>() else {
+        return Ok(());
+    };
+    if !settings.is_dirty {
+        return Ok(());
+    }
+    settings.is_dirty = false;
+    let settings = settings.clone();
+
+    world
+        .get_resource::<WindowManager>()?
+        .apply_settings(&settings);
+    // vsync is applied with the rendering config
+    if let Ok(config) = world.get_resource_mut::<RenderingConfig>() {
+        config.is_dirty = true;
+    }
+    Ok(())
+}
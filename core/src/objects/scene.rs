@@ -1,5 +1,5 @@
 use anyhow::{Error, Result, anyhow};
-use slotmap::{DefaultKey, SlotMap};
+use slotmap::{DefaultKey, DenseSlotMap, Key};
 
 use crate::{
     log_error,
@@ -8,14 +8,38 @@ use crate::{
 
 pub type ObjectId = DefaultKey;
 
+/// The parts of an ObjectId
+/// A removed object's slot is reused by a later object with a higher generation, so an id of a
+/// removed object never finds the object that replaced it
+pub trait ObjectIdExt {
+    /// The slot of the object in the scene
+    fn index(&self) -> u32;
+    /// Increases every time the slot is reused
+    fn generation(&self) -> u32;
+}
+
+impl ObjectIdExt for ObjectId {
+    fn index(&self) -> u32 {
+        self.data().as_ffi() as u32
+    }
+
+    fn generation(&self) -> u32 {
+        (self.data().as_ffi() >> 32) as u32
+    }
+}
+
 pub struct Scene {
-    pub(crate) objects: SlotMap<ObjectId, Object>,
+    /// objects are stored densely, removing one moves the last object into its place and updates
+    /// the slot its id points to, so ids stay valid and iteration never walks over holes
+    /// Note: removals change the iteration order, and they can't happen during an iteration as
+    /// queries borrow the scene and commands are applied between systems
+    pub(crate) objects: DenseSlotMap<ObjectId, Object>,
 }
 
 impl Default for Scene {
     fn default() -> Self {
         let mut scene = Scene {
-            objects: SlotMap::new(),
+            objects: DenseSlotMap::new(),
         };
         scene.add_default_objects();
         scene
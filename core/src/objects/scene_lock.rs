@@ -0,0 +1,211 @@
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Resource, update};
+use serde::{Deserialize, Serialize};
+
+use crate::{log, log_warn, objects::world::World};
+
+/// The holder rewrites its lock file this often so others can tell it is still open
+pub const LOCK_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+/// A lock file not rewritten for this long is left over from a crash and is ignored
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Who has a scene open, written to `<scene>.lock` next to it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub user: String,
+    pub host: String,
+    pub pid: u32,
+    /// seconds since the unix epoch of the last time the holder rewrote the lock
+    pub refreshed: u64,
+}
+
+impl LockOwner {
+    /// This engine instance
+    pub fn current() -> Self {
+        let env = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| std::env::var(name).ok())
+                .unwrap_or_else(|| "unknown".to_string())
+        };
+        Self {
+            user: env(&["USER", "USERNAME"]),
+            host: env(&["HOSTNAME", "COMPUTERNAME"]),
+            pid: std::process::id(),
+            refreshed: unix_time(),
+        }
+    }
+
+    /// Checks if two owners are the same engine instance
+    pub fn is_same_instance(&self, other: &LockOwner) -> bool {
+        self.user == other.user && self.host == other.host && self.pid == other.pid
+    }
+
+    /// Checks if the holder stopped refreshing the lock, e.g. because it crashed
+    pub fn is_stale(&self) -> bool {
+        unix_time().saturating_sub(self.refreshed) > LOCK_TIMEOUT.as_secs()
+    }
+}
+
+impl fmt::Display for LockOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{} (pid {})", self.user, self.host, self.pid)
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
+/// Gets `<scene>.lock`
+pub fn lock_path(scene: &str) -> PathBuf {
+    PathBuf::from(format!("{}.lock", scene))
+}
+
+/// Reads the lock file of a scene, None if there is none or it can't be read
+pub fn read_lock(scene: &str) -> Option<LockOwner> {
+    let contents = std::fs::read_to_string(lock_path(scene)).ok()?;
+    serde_yaml::from_str(&contents).ok()
+}
+
+fn write_lock(scene: &str) -> Result<()> {
+    let path = lock_path(scene);
+    std::fs::write(&path, serde_yaml::to_string(&LockOwner::current())?)
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Creates the lock file of a scene if there is none, returns false if it already exists
+/// Note: checking and creating the file is one step, so two instances opening the scene at once
+/// can't both take it
+fn create_lock(scene: &str) -> Result<bool> {
+    let path = lock_path(scene);
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => return Ok(false),
+        Err(e) => return Err(anyhow!("Failed to create {}: {}", path.display(), e)),
+    };
+    file.write_all(serde_yaml::to_string(&LockOwner::current())?.as_bytes())
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+/// The scene this instance has open and whether it holds its lock
+/// Note: only inserted by `World::lock_scene`, so games that load scenes never lock them
+#[derive(Resource, Clone)]
+#[resource_shutdown(World::release_scene_lock)]
+pub struct SceneLock {
+    pub path: String,
+    /// someone else has the scene open, saving over it is refused until `take_over_scene`
+    pub held_by: Option<LockOwner>,
+    last_refresh: Instant,
+}
+
+impl SceneLock {
+    pub fn is_read_only(&self) -> bool {
+        self.held_by.is_some()
+    }
+}
+
+impl World {
+    /// Locks a scene for this instance, releasing the lock of the previous scene
+    /// returns the owner if another live instance has the scene open, the scene is then read only
+    pub fn lock_scene(&mut self, path: &str) -> Result<Option<LockOwner>> {
+        if let Ok(lock) = self.get_resource::<SceneLock>()
+            && lock.path != path
+        {
+            self.release_scene_lock();
+        }
+
+        let held_by = if create_lock(path)? {
+            None
+        } else {
+            // a stale lock or our own is taken over, a live one makes the scene read only
+            let current = LockOwner::current();
+            let held_by = read_lock(path)
+                .filter(|owner| !owner.is_stale() && !owner.is_same_instance(&current));
+            match &held_by {
+                Some(owner) => {
+                    log_warn!("{} is open in {}, opening it read only", path, owner);
+                }
+                None => write_lock(path)?,
+            }
+            held_by
+        };
+        self.insert_resource(SceneLock {
+            path: path.to_string(),
+            held_by: held_by.clone(),
+            last_refresh: Instant::now(),
+        });
+        Ok(held_by)
+    }
+
+    /// Takes the lock of the open scene from whoever holds it, they become read only
+    pub fn take_over_scene(&mut self) -> Result<()> {
+        let lock = self.get_resource_mut::<SceneLock>()?;
+        write_lock(&lock.path)?;
+        if let Some(owner) = lock.held_by.take() {
+            log!("Took {} over from {}", lock.path, owner);
+        }
+        lock.last_refresh = Instant::now();
+        Ok(())
+    }
+
+    /// Removes the lock file of the open scene if this instance holds it
+    pub fn release_scene_lock(&mut self) {
+        let Ok(lock) = self.get_resource::<SceneLock>() else {
+            return;
+        };
+        if lock.is_read_only() {
+            return;
+        }
+        let path = lock_path(&lock.path);
+        if read_lock(&lock.path).is_some_and(|owner| owner.is_same_instance(&LockOwner::current()))
+            && let Err(e) = std::fs::remove_file(&path)
+        {
+            log_warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+
+    /// Checks if a scene is open in another instance, saving over it would lose their work
+    pub fn is_scene_read_only(&self, path: &str) -> bool {
+        self.get_resource::<SceneLock>()
+            .is_ok_and(|lock| lock.is_read_only() && Path::new(&lock.path) == Path::new(path))
+    }
+}
+
+/// Rewrites the lock of the open scene, and makes it read only if another instance took it over
+#[update(while_paused = true)]
+pub fn scene_lock_refresh(world: &mut World) -> Result<()> {
+    let Ok(lock) = world.get_resource_mut::<SceneLock>() else {
+        return Ok(());
+    };
+    if lock.is_read_only() || lock.last_refresh.elapsed() < LOCK_REFRESH_INTERVAL {
+        return Ok(());
+    }
+    lock.last_refresh = Instant::now();
+
+    let current = LockOwner::current();
+    match read_lock(&lock.path) {
+        Some(owner) if !owner.is_same_instance(&current) && !owner.is_stale() => {
+            log_warn!(
+                "{} was taken over by {}, it is now read only",
+                lock.path,
+                owner
+            );
+            lock.held_by = Some(owner);
+        }
+        _ => write_lock(&lock.path)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,296 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde_yaml::Value;
+
+use crate::{
+    log_warn,
+    objects::{
+        component::{BoxedComponent, ComponentRegistration, get_component_registration},
+        migration::{ENGINE_VERSION, SCENE_FORMAT_VERSION, migrate_scene},
+        snapshot::{SerializedComponent, SerializedObject},
+    },
+    utils::atomic_write::write_atomic,
+};
+
+/// Where the editor's Find in Scenes looks for scenes and prefabs
+pub const SCENE_SEARCH_ROOT: &str = "res";
+
+/// The objects `find_in_scenes` looks for, e.g.
+/// `SceneQuery::component("Sprite").with_field("texture", "player.png".into())`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneQuery {
+    /// the registered name of the component, any case, every component matches without one
+    pub component: Option<String>,
+    /// a field of the component, the whole component matches without one
+    pub field: Option<String>,
+    /// the value the field must have, any value matches without one
+    pub value: Option<Value>,
+}
+
+impl SceneQuery {
+    /// Matches every object with a component
+    pub fn component(name: &str) -> Self {
+        Self {
+            component: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Matches every component with a field set to a value
+    pub fn field(field: &str, value: Value) -> Self {
+        Self {
+            field: Some(field.to_string()),
+            value: Some(value),
+            ..Default::default()
+        }
+    }
+
+    /// Only matches components whose field is set to a value
+    pub fn with_field(mut self, field: &str, value: Value) -> Self {
+        self.field = Some(field.to_string());
+        self.value = Some(value);
+        self
+    }
+
+    /// Gets the value the query matches in a saved component, None if it doesn't match
+    /// Note: the component is loaded through its registration, so the fields are compared
+    /// after migrations and serde defaults, not as they are written in the file
+    fn matched_value(&self, component: &SerializedComponent) -> Result<Option<Value>> {
+        if let Some(name) = &self.component
+            && !name.eq_ignore_ascii_case(&component.name)
+        {
+            return Ok(None);
+        }
+        let (registration, loaded) = load_component(component)?;
+
+        let Some(field) = &self.field else {
+            let serialize = registration
+                .serialize
+                .ok_or_else(|| anyhow!("{} is not #[component_serde]", component.name))?;
+            return Ok(Some(serialize(&loaded)?));
+        };
+        if !registration.fields.iter().any(|info| info.name == field) {
+            return Ok(None);
+        }
+        let get_field = registration
+            .get_field
+            .ok_or_else(|| anyhow!("{} is not #[component_serde]", component.name))?;
+        let value = get_field(&loaded, field)?;
+        match &self.value {
+            Some(expected) if *expected != value => Ok(None),
+            _ => Ok(Some(value)),
+        }
+    }
+}
+
+/// An object of a scene matched by a `SceneQuery`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneMatch {
+    pub path: PathBuf,
+    /// the index of the object in the scene
+    pub object: usize,
+    pub object_name: String,
+    pub component: String,
+    /// the value of the queried field, the whole component if the query has no field
+    pub value: Value,
+}
+
+/// Creates a saved component through its registration, upgraded to the registered version
+fn load_component(
+    component: &SerializedComponent,
+) -> Result<(&'static ComponentRegistration, BoxedComponent)> {
+    let registration = get_component_registration(&component.name)
+        .ok_or_else(|| anyhow!("Component '{}' is not registered", component.name))?;
+    let value = registration.upgrade(&component.value, component.version)?;
+    let mut loaded = (registration.create)();
+    (registration.deserialize)(&mut loaded, &value)?;
+    Ok((registration, loaded))
+}
+
+/// Lists the yaml files under a directory
+fn yaml_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            yaml_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "yaml")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// A scene or prefab read for searching, other yaml files are skipped
+struct SceneFile {
+    path: PathBuf,
+    /// the whole file, migrated, so the keys that aren't objects are saved back as they were
+    document: Value,
+    objects: Vec<SerializedObject>,
+}
+
+impl SceneFile {
+    /// Reads a yaml file, None if it has no objects so isn't a scene or prefab
+    fn read(path: &Path) -> Result<Option<Self>> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut document: Value = serde_yaml::from_str(&contents)?;
+        if document
+            .get("objects")
+            .is_none_or(|objects| !objects.is_sequence())
+        {
+            return Ok(None);
+        }
+        migrate_scene(&mut document, &path.display().to_string())?;
+        let objects = serde_yaml::from_value(document["objects"].clone())?;
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            document,
+            objects,
+        }))
+    }
+
+    fn save(&mut self) -> Result<()> {
+        let mapping = self
+            .document
+            .as_mapping_mut()
+            .ok_or_else(|| anyhow!("{} is not a mapping", self.path.display()))?;
+        mapping.insert("objects".into(), serde_yaml::to_value(&self.objects)?);
+        mapping.insert("engine_version".into(), ENGINE_VERSION.into());
+        mapping.insert("format_version".into(), SCENE_FORMAT_VERSION.into());
+        write_atomic(&self.path, serde_yaml::to_string(&self.document)?)
+    }
+
+    /// Finds the components of the objects matched by a query
+    fn find(&self, query: &SceneQuery) -> Vec<SceneMatch> {
+        let mut matches = Vec::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            for component in &object.components {
+                match query.matched_value(component) {
+                    Ok(Some(value)) => matches.push(SceneMatch {
+                        path: self.path.clone(),
+                        object: index,
+                        object_name: object.name.clone(),
+                        component: component.name.clone(),
+                        value,
+                    }),
+                    Ok(None) => {}
+                    Err(e) => {
+                        log_warn!(
+                            "Skipping {} of {} in {}: {}",
+                            component.name,
+                            object.name,
+                            self.path.display(),
+                            e
+                        );
+                    }
+                }
+            }
+        }
+        matches
+    }
+}
+
+/// Reads every scene and prefab under a directory, files that fail to read are skipped
+fn scene_files(root: &Path) -> Result<Vec<SceneFile>> {
+    let mut paths = Vec::new();
+    yaml_files(root, &mut paths)
+        .map_err(|e| anyhow!("Failed to list {}: {}", root.display(), e))?;
+    paths.sort();
+
+    let mut scenes = Vec::new();
+    for path in paths {
+        match SceneFile::read(&path) {
+            Ok(Some(scene)) => scenes.push(scene),
+            Ok(None) => {}
+            Err(e) => {
+                log_warn!("Skipping {}: {}", path.display(), e);
+            }
+        }
+    }
+    Ok(scenes)
+}
+
+/// Finds the objects matched by a query in every scene and prefab under a directory, e.g.
+/// `find_in_scenes(Path::new(SCENE_SEARCH_ROOT), &SceneQuery::component("Velocity"))`
+/// Note: components that aren't registered or fail to load are skipped with a warning
+pub fn find_in_scenes(root: &Path, query: &SceneQuery) -> Result<Vec<SceneMatch>> {
+    Ok(scene_files(root)?
+        .iter()
+        .flat_map(|scene| scene.find(query))
+        .collect())
+}
+
+/// Sets the queried field to a value in every match of `find_in_scenes`, the changed scenes are
+/// saved with `write_atomic`, returns the matches that were changed
+/// Note: each component is changed through its registration, so the value must be valid for
+/// the field, nothing is saved unless every match takes the value, scenes that are loaded are
+/// not reloaded
+pub fn replace_in_scenes(
+    root: &Path,
+    query: &SceneQuery,
+    value: &Value,
+) -> Result<Vec<SceneMatch>> {
+    let field = query
+        .field
+        .clone()
+        .ok_or_else(|| anyhow!("Pick the field to replace"))?;
+
+    // change every scene before saving any, so a bad match leaves them all as they were
+    let mut changed = Vec::new();
+    let mut replaced = Vec::new();
+    for mut scene in scene_files(root)? {
+        let matches = scene.find(query);
+        if matches.is_empty() {
+            continue;
+        }
+        for found in &matches {
+            let component = scene.objects[found.object]
+                .components
+                .iter_mut()
+                .find(|component| component.name == found.component)
+                .ok_or_else(|| anyhow!("{} has no {}", found.object_name, found.component))?;
+            let (registration, mut loaded) = load_component(component)?;
+            let (Some(set_field), Some(serialize)) =
+                (registration.set_field, registration.serialize)
+            else {
+                return Err(anyhow!("{} is not #[component_serde]", component.name));
+            };
+            set_field(&mut loaded, &field, value).map_err(|e| {
+                anyhow!(
+                    "Failed to set {}.{} of {} in {}: {}",
+                    component.name,
+                    field,
+                    found.object_name,
+                    scene.path.display(),
+                    e
+                )
+            })?;
+            component.value = serialize(&loaded)?;
+            component.version = registration.version;
+        }
+        changed.push(scene);
+        replaced.extend(matches);
+    }
+
+    let mut saved = Vec::new();
+    for scene in &mut changed {
+        if let Err(e) = scene.save() {
+            let saved = if saved.is_empty() {
+                "none".to_string()
+            } else {
+                saved.join(", ")
+            };
+            return Err(anyhow!(
+                "Failed to save {}: {} (already saved: {})",
+                scene.path.display(),
+                e,
+                saved
+            ));
+        }
+        saved.push(scene.path.display().to_string());
+    }
+    Ok(replaced)
+}
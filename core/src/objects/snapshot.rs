@@ -0,0 +1,329 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use slotmap::DenseSlotMap;
+
+use crate::{
+    log_warn,
+    objects::{
+        Object,
+        component::{get_component_registration, get_component_registration_of, short_type_name},
+        events::Events,
+        migration::{ENGINE_VERSION, SCENE_FORMAT_VERSION, migrate_scene},
+        scene::ObjectId,
+        tag::get_tag_registration,
+        world::World,
+    },
+    physics::physics_settings::ScenePhysicsSettings,
+    utils::atomic_write::{backup_path, write_atomic},
+    voxels::meshes::{NeedsRemeshing, VoxelChunkMesh, WaterMesh},
+    weather::Weather,
+};
+
+/// A copy of every object in the world, restored exactly
+/// Used for play mode rollback, resources are not included
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    objects: DenseSlotMap<ObjectId, Object>,
+    chunk_position_index: HashMap<(i32, i32, i32), ObjectId>,
+}
+
+/// Sent by `World::load_objects` after a scene is loaded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SceneLoaded {
+    pub path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializedComponent {
+    /// the name the component is registered with
+    pub name: String,
+    pub value: serde_yaml::Value,
+    /// the `#[component_version]` it was saved at, 0 for components saved before versioning
+    #[serde(default, skip_serializing_if = "is_unversioned")]
+    pub version: u32,
+}
+
+fn is_unversioned(version: &u32) -> bool {
+    *version == 0
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerializedObject {
+    pub name: String,
+    /// the index of the parent in `SerializedWorld::objects`
+    pub parent: Option<usize>,
+    pub tags: Vec<String>,
+    pub components: Vec<SerializedComponent>,
+}
+
+impl SerializedObject {
+    /// Creates the object with its tags and components, unregistered ones are skipped
+    /// Note: the parent is left unset, it is an index into the list the object was saved in
+    pub fn to_object(&self) -> Result<Object> {
+        let mut object = Object::new();
+        object.name = self.name.clone();
+
+        for tag in &self.tags {
+            match get_tag_registration(tag) {
+                Some(registration) => object.tags.push((registration.create)()),
+                None => {
+                    log_warn!("Tag '{}' is not registered, skipping it", tag);
+                }
+            }
+        }
+        for component in &self.components {
+            let Some(registration) = get_component_registration(&component.name) else {
+                log_warn!(
+                    "Component '{}' is not registered, skipping it",
+                    component.name
+                );
+                continue;
+            };
+            if component.version > registration.version {
+                log_warn!(
+                    "{} of {} was saved at version {}, newer than {}, it may not load",
+                    component.name,
+                    self.name,
+                    component.version,
+                    registration.version
+                );
+            }
+            let value = registration.upgrade(&component.value, component.version)?;
+            let mut boxed = (registration.create)();
+            (registration.deserialize)(&mut boxed, &value).map_err(|e| {
+                anyhow!(
+                    "Failed to deserialize {} of {}: {}",
+                    component.name,
+                    self.name,
+                    e
+                )
+            })?;
+            object.push_component(boxed);
+        }
+        Ok(object)
+    }
+}
+
+/// The objects of a world as yaml, made with `World::serialize_objects`
+/// Note: only `#[component_serde]` components and unit tags are saved, voxel chunks are not saved
+/// as streaming generates them again
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerializedWorld {
+    /// the `ENGINE_VERSION` that saved the scene
+    pub engine_version: String,
+    /// the `SCENE_FORMAT_VERSION` the scene was saved with, 0 for scenes from before versioning
+    pub format_version: u32,
+    pub objects: Vec<SerializedObject>,
+    pub physics: ScenePhysicsSettings,
+    pub weather: Weather,
+}
+
+impl SerializedWorld {
+    /// Loads a scene, falling back to the `.bak` copy `save` keeps if the scene can't be read
+    pub fn load(path: &str) -> Result<Self> {
+        let read = |file: &Path| -> Result<Self> {
+            let contents = std::fs::read_to_string(file)?;
+            let mut value: serde_yaml::Value = serde_yaml::from_str(&contents)?;
+            migrate_scene(&mut value, path)?;
+            Ok(serde_yaml::from_value(value)?)
+        };
+        match read(Path::new(path)) {
+            Ok(world) => Ok(world),
+            Err(e) => {
+                let backup = backup_path(Path::new(path));
+                if !backup.exists() {
+                    return Err(e);
+                }
+                log_warn!(
+                    "Failed to load {}: {}, loading the backup {}",
+                    path,
+                    e,
+                    backup.display()
+                );
+                read(&backup).map_err(|backup_error| {
+                    anyhow!("Failed to load {}: {} (backup: {})", path, e, backup_error)
+                })
+            }
+        }
+    }
+
+    /// Saves the scene with `write_atomic`, the previous version is kept as `<path>.bak`
+    /// Note: the scene is stamped with the current engine and format version
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut value = serde_yaml::to_value(self)?;
+        if let Some(mapping) = value.as_mapping_mut() {
+            mapping.insert("engine_version".into(), ENGINE_VERSION.into());
+            mapping.insert("format_version".into(), SCENE_FORMAT_VERSION.into());
+        }
+        write_atomic(Path::new(path), serde_yaml::to_string(&value)?)
+    }
+}
+
+impl World {
+    /// Copies every object, restore it with `restore_snapshot`
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            objects: self.scene.objects.clone(),
+            chunk_position_index: self.chunk_position_index.clone(),
+        }
+    }
+
+    /// Replaces every object with the objects of a snapshot, ObjectIds from the snapshot are valid
+    /// Note: the `on_remove` hooks of the replaced objects run once they are gone, and voxel
+    /// meshes are rebuilt for the restored objects as their buffers may have been freed since
+    pub fn restore_snapshot(&mut self, snapshot: WorldSnapshot) {
+        let mut replaced = std::mem::replace(&mut self.scene.objects, snapshot.objects);
+        self.chunk_position_index = snapshot.chunk_position_index;
+
+        for (_, object) in self.scene.objects.iter_mut() {
+            if object.has_component::<VoxelChunkMesh>() || object.has_component::<WaterMesh>() {
+                // the buffers belong to the replaced objects, so they are dropped without hooks
+                object.remove_component::<VoxelChunkMesh>();
+                object.remove_component::<WaterMesh>();
+                object.add_tag(NeedsRemeshing);
+            }
+        }
+        for (id, object) in replaced.iter_mut() {
+            let components = std::mem::take(&mut object.components);
+            self.run_remove_hooks(id, components);
+        }
+    }
+
+    /// Serializes the `#[component_serde]` components and unit tags of one object
+    pub fn serialize_object(
+        &self,
+        id: ObjectId,
+        parent: Option<usize>,
+    ) -> Result<SerializedObject> {
+        let object = self
+            .scene
+            .objects
+            .get(id)
+            .ok_or_else(|| anyhow!("Object does not exist"))?;
+
+        let mut components = Vec::new();
+        for component in &object.components {
+            let Some(registration) = get_component_registration_of(component) else {
+                continue;
+            };
+            let Some(serialize) = registration.serialize else {
+                continue;
+            };
+            let name = short_type_name(component.type_name()).to_string();
+            let value = serialize(component)
+                .map_err(|e| anyhow!("Failed to serialize {} of {}: {}", name, object.name, e))?;
+            components.push(SerializedComponent {
+                name,
+                value,
+                version: registration.version,
+            });
+        }
+
+        Ok(SerializedObject {
+            name: object.name.clone(),
+            parent,
+            tags: object
+                .tags
+                .iter()
+                .map(|tag| short_type_name(tag.type_name()).to_string())
+                .collect(),
+            components,
+        })
+    }
+
+    /// Serializes every object that is not a voxel chunk
+    pub fn serialize_objects(&self) -> Result<SerializedWorld> {
+        let chunks: HashSet<ObjectId> = self.chunk_position_index.values().copied().collect();
+        let ids: Vec<ObjectId> = self
+            .scene
+            .objects
+            .keys()
+            .filter(|id| !chunks.contains(id))
+            .collect();
+        let indices: HashMap<ObjectId, usize> =
+            ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+        let mut objects = Vec::with_capacity(ids.len());
+        for id in &ids {
+            let parent = self.scene.objects[*id]
+                .parent
+                .and_then(|parent| indices.get(&parent).copied());
+            objects.push(self.serialize_object(*id, parent)?);
+        }
+
+        Ok(SerializedWorld {
+            engine_version: ENGINE_VERSION.to_string(),
+            format_version: SCENE_FORMAT_VERSION,
+            objects,
+            physics: self
+                .get_resource::<ScenePhysicsSettings>()
+                .cloned()
+                .unwrap_or_default(),
+            weather: self.get_resource::<Weather>().cloned().unwrap_or_default(),
+        })
+    }
+
+    /// Removes every object that is not a voxel chunk and adds the serialized objects, the scene's
+    /// physics settings and weather replace the current ones
+    /// returns the ids of the new objects in the order they were serialized
+    pub fn deserialize_objects(&mut self, world: &SerializedWorld) -> Result<Vec<ObjectId>> {
+        let objects = world
+            .objects
+            .iter()
+            .map(SerializedObject::to_object)
+            .collect::<Result<Vec<Object>>>()?;
+
+        let chunks: HashSet<ObjectId> = self.chunk_position_index.values().copied().collect();
+        self.scene.objects.retain(|id, _| chunks.contains(&id));
+
+        let ids: Vec<ObjectId> = objects
+            .into_iter()
+            .map(|object| self.add_object(object))
+            .collect();
+        for (serialized, id) in world.objects.iter().zip(&ids) {
+            if let Some(parent) = serialized.parent.and_then(|i| ids.get(i)) {
+                self.set_parent(*id, Some(*parent))?;
+            }
+        }
+        self.insert_resource(world.physics.clone());
+
+        let mut weather = world.weather.clone();
+        if let Ok(current) = self.get_resource::<Weather>() {
+            // keep the game's hook, it runs again if the scene changed the preset
+            weather.on_changed = current.on_changed.clone();
+            weather.applied = current.applied;
+        }
+        self.insert_resource(weather);
+
+        Ok(ids)
+    }
+
+    /// Saves every object that is not a voxel chunk to a yaml file
+    /// Note: fails if the scene is open read only, see `World::lock_scene`
+    pub fn save_objects(&self, path: &str) -> Result<()> {
+        if self.is_scene_read_only(path) {
+            return Err(anyhow!(
+                "{} is open in another instance, take it over to save",
+                path
+            ));
+        }
+        self.serialize_objects()?.save(path)
+    }
+
+    /// Replaces every object that is not a voxel chunk with the objects in a yaml file
+    pub fn load_objects(&mut self, path: &str) -> Result<Vec<ObjectId>> {
+        let world = SerializedWorld::load(path)?;
+        let ids = self.deserialize_objects(&world)?;
+        if self.has_resource::<Events<SceneLoaded>>() {
+            self.send_event(SceneLoaded {
+                path: path.to_string(),
+            })?;
+        }
+        Ok(ids)
+    }
+}
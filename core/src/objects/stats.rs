@@ -0,0 +1,136 @@
+use anyhow::Result;
+use apostasy_macros::{Resource, late_update};
+use hashbrown::HashMap;
+
+use crate::objects::{component::short_type_name, pool::PoolStats, world::World};
+
+/// How many objects have a component and the memory the values take
+/// Note: `bytes` is the inline size, memory the component allocates itself is not counted
+#[derive(Clone, Debug, Default)]
+pub struct ComponentStats {
+    pub name: String,
+    pub count: usize,
+    /// the size of one value
+    pub size: usize,
+    pub bytes: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TagStats {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Objects with the same set of components, the closest thing the world has to an archetype
+#[derive(Clone, Debug, Default)]
+pub struct LayoutStats {
+    /// sorted component names
+    pub components: Vec<String>,
+    pub object_count: usize,
+}
+
+/// A summary of what the world holds, made with `World::stats`
+/// Note: insert it as a resource to have it refreshed at the end of every frame
+#[derive(Resource, Clone, Debug, Default)]
+pub struct WorldStats {
+    pub object_count: usize,
+    pub object_capacity: usize,
+    pub chunk_count: usize,
+    pub resource_count: usize,
+    /// commands waiting for the running system to finish
+    pub queued_commands: usize,
+    /// sorted by total bytes, largest first
+    pub components: Vec<ComponentStats>,
+    /// sorted by count, largest first
+    pub tags: Vec<TagStats>,
+    /// sorted by object count, largest first
+    pub layouts: Vec<LayoutStats>,
+    /// sorted by name
+    pub pools: Vec<PoolStats>,
+}
+
+impl WorldStats {
+    pub fn layout_count(&self) -> usize {
+        self.layouts.len()
+    }
+
+    pub fn component_bytes(&self) -> usize {
+        self.components.iter().map(|c| c.bytes).sum()
+    }
+}
+
+impl World {
+    /// Counts the objects, components, tags and layouts of the world
+    pub fn stats(&self) -> WorldStats {
+        let mut components: HashMap<&str, ComponentStats> = HashMap::new();
+        let mut tags: HashMap<&str, usize> = HashMap::new();
+        let mut layouts: HashMap<Vec<String>, usize> = HashMap::new();
+
+        for (_, object) in self.scene.objects.iter() {
+            let mut layout = Vec::with_capacity(object.components.len());
+            for component in &object.components {
+                let name = short_type_name(component.type_name());
+                let size = std::mem::size_of_val(&**component);
+                let stats = components.entry(name).or_insert_with(|| ComponentStats {
+                    name: name.to_string(),
+                    size,
+                    ..Default::default()
+                });
+                stats.count += 1;
+                stats.bytes += size;
+                layout.push(name.to_string());
+            }
+            for tag in &object.tags {
+                *tags.entry(short_type_name(tag.type_name())).or_insert(0) += 1;
+            }
+            layout.sort();
+            *layouts.entry(layout).or_insert(0) += 1;
+        }
+
+        let mut components: Vec<ComponentStats> = components.into_values().collect();
+        components.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        let mut tags: Vec<TagStats> = tags
+            .into_iter()
+            .map(|(name, count)| TagStats {
+                name: name.to_string(),
+                count,
+            })
+            .collect();
+        tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        let mut layouts: Vec<LayoutStats> = layouts
+            .into_iter()
+            .map(|(components, object_count)| LayoutStats {
+                components,
+                object_count,
+            })
+            .collect();
+        layouts.sort_by(|a, b| {
+            b.object_count
+                .cmp(&a.object_count)
+                .then_with(|| a.components.cmp(&b.components))
+        });
+
+        WorldStats {
+            object_count: self.object_count(),
+            object_capacity: self.object_capacity(),
+            chunk_count: self.chunk_position_index.len(),
+            resource_count: self.resources.map.len(),
+            queued_commands: self.queued_commands.len(),
+            components,
+            tags,
+            layouts,
+            pools: self.pool_stats(),
+        }
+    }
+}
+
+/// Refreshes the `WorldStats` resource if something inserted it
+#[late_update]
+pub fn refresh_world_stats(world: &mut World) -> Result<()> {
+    if !world.has_resource::<WorldStats>() {
+        return Ok(());
+    }
+    let stats = world.stats();
+    world.insert_resource(stats);
+    Ok(())
+}
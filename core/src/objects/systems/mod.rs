@@ -1,18 +1,104 @@
 use anyhow::Result;
 use apostasy_macros::Resource;
 
-use crate::objects::world::World;
+use crate::objects::{systems::ordering::OrderedSystem, world::World};
+
+pub mod ordering;
+pub mod params;
+pub mod schedule;
+pub mod stats;
 
 pub trait HasPriority {
     fn priority(&self) -> u32;
 }
 
+/// Where a system runs relative to others, set with `before`, `after` and `set` in the system
+/// macros, e.g. `#[update(set = "physics", after = "apply_input")]`
+/// Note: the targets are system names, `package::name`, or set names, systems of other kinds
+/// are not ordered against, priority only orders systems with no constraints between them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemOrder {
+    /// the systems and sets this system runs before
+    pub before: &'static [&'static str],
+    /// the systems and sets this system runs after
+    pub after: &'static [&'static str],
+    /// the set the system is in, so others can run before or after the whole set
+    pub set: Option<&'static str>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SystemKind {
+    Start,
+    Update,
+    FixedUpdate,
+    LateUpdate,
+    Shutdown,
+}
+
+impl SystemKind {
+    pub const ALL: [SystemKind; 5] = [
+        SystemKind::Start,
+        SystemKind::Update,
+        SystemKind::FixedUpdate,
+        SystemKind::LateUpdate,
+        SystemKind::Shutdown,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SystemKind::Start => "Start",
+            SystemKind::Update => "Update",
+            SystemKind::FixedUpdate => "Fixed Update",
+            SystemKind::LateUpdate => "Late Update",
+            SystemKind::Shutdown => "Shutdown",
+        }
+    }
+}
+
+/// A registered system, listed by `World::systems`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SystemInfo {
+    pub name: &'static str,
+    pub kind: SystemKind,
+    pub priority: u32,
+    pub order: SystemOrder,
+    pub package: &'static str,
+    pub is_enabled: bool,
+    pub main_thread: bool,
+}
+
+/// A part of the frame, see `Core::FRAME_STAGES`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameStage {
+    /// the systems of a kind in the order they run
+    Systems(SystemKind),
+    /// engine work between systems
+    Flush(&'static str),
+}
+
+/// A step of a frame in the order it runs, listed by `World::frame_graph`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameStep {
+    /// a system, skipped while it is disabled
+    System(SystemInfo),
+    /// engine work between systems that sees every change made before it
+    Flush(&'static str),
+}
+
 /// A system that happens every frame
 pub struct UpdateSystem {
     pub name: &'static str,
     pub func: fn(&mut World) -> Result<()>,
     pub priority: u32,
+    pub order: SystemOrder,
+    /// the cargo package the system was registered in
+    pub package: &'static str,
+    /// set for systems using non send resources, they are skipped off the main thread
+    pub main_thread: bool,
+    /// set with `while_paused = true`, other systems are skipped while the game is paused
+    pub while_paused: bool,
 }
+#[cfg(not(target_family = "wasm"))]
 inventory::collect!(UpdateSystem);
 impl HasPriority for UpdateSystem {
     fn priority(&self) -> u32 {
@@ -20,20 +106,106 @@ impl HasPriority for UpdateSystem {
     }
 }
 
+impl OrderedSystem for UpdateSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn package(&self) -> &'static str {
+        self.package
+    }
+    fn order(&self) -> SystemOrder {
+        self.order
+    }
+    fn main_thread(&self) -> bool {
+        self.main_thread
+    }
+}
+
 /// A system that happens once at the start of the application
 pub struct StartSystem {
     pub name: &'static str,
     pub func: fn(&mut World) -> Result<()>,
     pub priority: u32,
+    pub order: SystemOrder,
+    /// the cargo package the system was registered in
+    pub package: &'static str,
+    /// set for systems using non send resources, they are skipped off the main thread
+    pub main_thread: bool,
 }
+#[cfg(not(target_family = "wasm"))]
 inventory::collect!(StartSystem);
 
+impl HasPriority for StartSystem {
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+}
+
+impl OrderedSystem for StartSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn package(&self) -> &'static str {
+        self.package
+    }
+    fn order(&self) -> SystemOrder {
+        self.order
+    }
+    fn main_thread(&self) -> bool {
+        self.main_thread
+    }
+}
+
+/// A system that happens once when the application closes, before the window is gone
+/// Note: runs on an `AppExit`, whether it was sent or the window was closed
+pub struct ShutdownSystem {
+    pub name: &'static str,
+    pub func: fn(&mut World) -> Result<()>,
+    pub priority: u32,
+    pub order: SystemOrder,
+    /// the cargo package the system was registered in
+    pub package: &'static str,
+    /// set for systems using non send resources, they are skipped off the main thread
+    pub main_thread: bool,
+}
+#[cfg(not(target_family = "wasm"))]
+inventory::collect!(ShutdownSystem);
+
+impl HasPriority for ShutdownSystem {
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+}
+
+impl OrderedSystem for ShutdownSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn package(&self) -> &'static str {
+        self.package
+    }
+    fn order(&self) -> SystemOrder {
+        self.order
+    }
+    fn main_thread(&self) -> bool {
+        self.main_thread
+    }
+}
+
 /// A system that happens x amount of times per second
 pub struct FixedUpdateSystem {
     pub name: &'static str,
     pub func: fn(&mut World, delta: f32) -> Result<()>,
     pub priority: u32,
+    pub order: SystemOrder,
+    /// the cargo package the system was registered in
+    pub package: &'static str,
+    /// set for systems using non send resources, they are skipped off the main thread
+    pub main_thread: bool,
+    /// set with `while_paused = true`, other systems are skipped while the game is paused
+    pub while_paused: bool,
 }
+#[cfg(not(target_family = "wasm"))]
 inventory::collect!(FixedUpdateSystem);
 
 impl HasPriority for FixedUpdateSystem {
@@ -42,12 +214,82 @@ impl HasPriority for FixedUpdateSystem {
     }
 }
 
+impl OrderedSystem for FixedUpdateSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn package(&self) -> &'static str {
+        self.package
+    }
+    fn order(&self) -> SystemOrder {
+        self.order
+    }
+    fn main_thread(&self) -> bool {
+        self.main_thread
+    }
+}
+
+/// The seconds the frame took, multiplied by the time scale
 #[derive(Resource, Clone, Default)]
 pub struct DeltaTime(pub f32);
 
+/// How fast game time runs, 1 is real time and 0 stops update deltas and fixed updates
+/// Note: `DeltaTime`, `EngineTimer` and fixed updates are scaled, UI and editor cameras should
+/// use the unscaled clock so they keep working in slow motion
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Time {
+    scale: f32,
+    /// the real seconds the frame took
+    pub unscaled_delta: f32,
+    /// the real seconds since the first frame
+    pub unscaled_elapsed: f32,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            unscaled_delta: 0.0,
+            unscaled_elapsed: 0.0,
+        }
+    }
+}
+
+impl Time {
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Sets how fast game time runs, e.g. 0.25 for slow motion, negative scales are clamped to 0
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = if scale.is_finite() {
+            scale.max(0.0)
+        } else {
+            1.0
+        };
+    }
+
+    /// The seconds of the frame in game time
+    pub fn delta(&self) -> f32 {
+        self.unscaled_delta * self.scale
+    }
+}
+
 #[derive(Resource, Clone, Default)]
 pub struct EngineTimer(pub f32);
 
+/// How far the frame is between fixed update steps, updated after the fixed update systems ran
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct FixedTime {
+    /// the seconds of each fixed update step
+    pub timestep: f32,
+    /// how far into the next step the frame is, from 0 to 1
+    /// Note: rendering uses it to interpolate between the last two steps
+    pub overstep_fraction: f32,
+    /// how many steps ran this frame
+    pub steps: u32,
+}
+
 #[derive(Resource, Clone, Default)]
 pub struct FixedUpdateTimer {
     pub accumulator: f32,
@@ -60,7 +302,13 @@ pub struct LateUpdateSystem {
     pub name: &'static str,
     pub func: fn(&mut World) -> Result<()>,
     pub priority: u32,
+    pub order: SystemOrder,
+    /// the cargo package the system was registered in
+    pub package: &'static str,
+    /// set for systems using non send resources, they are skipped off the main thread
+    pub main_thread: bool,
 }
+#[cfg(not(target_family = "wasm"))]
 inventory::collect!(LateUpdateSystem);
 
 impl HasPriority for LateUpdateSystem {
@@ -68,3 +316,18 @@ impl HasPriority for LateUpdateSystem {
         self.priority
     }
 }
+
+impl OrderedSystem for LateUpdateSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn package(&self) -> &'static str {
+        self.package
+    }
+    fn order(&self) -> SystemOrder {
+        self.order
+    }
+    fn main_thread(&self) -> bool {
+        self.main_thread
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Result, anyhow};
+use hashbrown::HashSet;
+
+use crate::objects::systems::{HasPriority, SystemOrder};
+
+/// A system that can be ordered with `before`, `after` and `set`
+pub trait OrderedSystem: HasPriority {
+    fn name(&self) -> &'static str;
+    fn package(&self) -> &'static str;
+    fn order(&self) -> SystemOrder;
+    fn main_thread(&self) -> bool;
+}
+
+/// Systems in the order they run and the constraints that named nothing
+pub struct OrderedSystems<T: 'static> {
+    pub systems: Vec<&'static T>,
+    /// `before` and `after` targets that are neither a system nor a set of this kind
+    pub unknown: Vec<String>,
+    /// the constraints that matched a system, between indices of `systems`
+    pub edges: Vec<OrderEdge>,
+}
+
+/// A constraint between two systems, `from` runs before `to`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderEdge {
+    pub from: usize,
+    pub to: usize,
+    /// the constraint as it was written, e.g. `game::move_player after apply_input`
+    pub constraint: String,
+}
+
+/// Checks if a `before` or `after` target names a system, as `name` or `package::name`, or its set
+fn targets<T: OrderedSystem>(target: &str, system: &T) -> bool {
+    system.name() == target
+        || system.order().set == Some(target)
+        || target
+            .split_once("::")
+            .is_some_and(|(package, name)| package == system.package() && name == system.name())
+}
+
+/// Sorts systems so each one runs after everything it is `after` and before everything it is
+/// `before`, systems without constraints between them keep the order they are given in
+/// errors naming the systems if the constraints form a cycle
+pub fn order_systems<T: OrderedSystem>(systems: &[&'static T]) -> Result<OrderedSystems<T>> {
+    let count = systems.len();
+    // (a, b) means a runs before b
+    let mut edges = HashSet::new();
+    let mut constraints_of = Vec::new();
+    let mut unknown = Vec::new();
+    for (index, system) in systems.iter().enumerate() {
+        let order = system.order();
+        let constraints = order
+            .before
+            .iter()
+            .map(|target| (target, true))
+            .chain(order.after.iter().map(|target| (target, false)));
+        for (target, is_before) in constraints {
+            let constraint = format!(
+                "{}::{} {} {}",
+                system.package(),
+                system.name(),
+                if is_before { "before" } else { "after" },
+                target
+            );
+            let mut found = false;
+            for (other, other_system) in systems.iter().enumerate() {
+                if other == index || !targets(target, *other_system) {
+                    continue;
+                }
+                found = true;
+                let edge = if is_before {
+                    (index, other)
+                } else {
+                    (other, index)
+                };
+                if edges.insert(edge) {
+                    constraints_of.push((edge, constraint.clone()));
+                }
+            }
+            if !found {
+                unknown.push(constraint);
+            }
+        }
+    }
+
+    let mut next = vec![Vec::new(); count];
+    let mut previous = vec![Vec::new(); count];
+    for (from, to) in &edges {
+        next[*from].push(*to);
+        previous[*to].push(*from);
+    }
+
+    // the lowest index first, so unconstrained systems keep their order
+    let mut waiting: Vec<usize> = previous.iter().map(Vec::len).collect();
+    let mut ready: BTreeSet<usize> = (0..count).filter(|i| waiting[*i] == 0).collect();
+    let mut ordered = Vec::with_capacity(count);
+    let mut position = vec![0; count];
+    while let Some(index) = ready.pop_first() {
+        position[index] = ordered.len();
+        ordered.push(systems[index]);
+        for to in &next[index] {
+            waiting[*to] -= 1;
+            if waiting[*to] == 0 {
+                ready.insert(*to);
+            }
+        }
+    }
+
+    if ordered.len() < count {
+        // every system left waits on another system left, walking back through them finds a cycle
+        let mut walked = vec![(0..count).find(|i| waiting[*i] > 0).unwrap()];
+        loop {
+            let current = *walked.last().unwrap();
+            let back = *previous[current]
+                .iter()
+                .find(|from| waiting[**from] > 0)
+                .unwrap();
+            if let Some(start) = walked.iter().position(|index| *index == back) {
+                let mut cycle: Vec<String> = walked[start..]
+                    .iter()
+                    .rev()
+                    .map(|index| {
+                        format!("{}::{}", systems[*index].package(), systems[*index].name())
+                    })
+                    .collect();
+                cycle.push(cycle[0].clone());
+                return Err(anyhow!(
+                    "The before and after constraints of these systems form a cycle: {}",
+                    cycle.join(" -> ")
+                ));
+            }
+            walked.push(back);
+        }
+    }
+
+    let edges = constraints_of
+        .into_iter()
+        .map(|((from, to), constraint)| OrderEdge {
+            from: position[from],
+            to: position[to],
+            constraint,
+        })
+        .collect();
+    Ok(OrderedSystems {
+        systems: ordered,
+        unknown,
+        edges,
+    })
+}
@@ -0,0 +1,249 @@
+use std::{
+    any::type_name,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::objects::{
+    non_send::NonSendMap,
+    query::{QueryCursor, QueryData, QueryFilter, batch_scene, par_for_each_scene, query_scene},
+    resource::{Resource, ResourceError, ResourceMap},
+    scene::{ObjectId, Scene},
+    world::World,
+};
+
+/// A resource a system reads, declared as a `Res<T>` parameter
+/// e.g. `#[update] fn move_player(delta: Res<DeltaTime>, ...) -> Result<()>`
+pub struct Res<'w, T: Resource> {
+    value: &'w T,
+}
+
+impl<'w, T: Resource> Res<'w, T> {
+    pub fn new(value: &'w T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T: Resource> Deref for Res<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+/// A resource a system changes, declared as a `ResMut<T>` parameter
+/// Note: the resource is locked while the system runs, so the same resource can't be a `Res`
+/// and a `ResMut` of one system
+pub struct ResMut<'w, T: Resource> {
+    value: &'w mut T,
+}
+
+impl<'w, T: Resource> ResMut<'w, T> {
+    pub fn new(value: &'w mut T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T: Resource> Deref for ResMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: Resource> DerefMut for ResMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+/// A non send resource a system reads, declared as a `NonSend<T>` parameter
+/// Note: systems with `NonSend` or `NonSendMut` parameters only run on the main thread
+pub struct NonSend<'w, T: 'static> {
+    value: &'w T,
+}
+
+impl<'w, T: 'static> NonSend<'w, T> {
+    pub fn new(value: &'w T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T: 'static> Deref for NonSend<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+/// A non send resource a system changes, declared as a `NonSendMut<T>` parameter
+pub struct NonSendMut<'w, T: 'static> {
+    value: &'w mut T,
+}
+
+impl<'w, T: 'static> NonSendMut<'w, T> {
+    pub fn new(value: &'w mut T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T: 'static> Deref for NonSendMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: 'static> DerefMut for NonSendMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+/// The objects a system visits, declared as a `Query<D>` or `Query<D, F>` parameter
+/// e.g. `mut objects: Query<(&Velocity, &mut Transform), Without<Player>>`
+/// Note: a system can only have one Query, put every component it needs in the same query
+pub struct Query<'w, D: QueryData, F: QueryFilter = ()> {
+    scene: &'w mut Scene,
+    _marker: PhantomData<fn() -> (D, F)>,
+}
+
+impl<D: QueryData, F: QueryFilter> Query<'_, D, F> {
+    /// Iterates every matching object
+    pub fn iter(&mut self) -> impl Iterator<Item = D::Item<'_>> {
+        query_scene::<D, F>(self.scene)
+    }
+
+    /// Gets one object if it exists and matches the query
+    pub fn get(&mut self, id: ObjectId) -> Option<D::Item<'_>> {
+        let object = self.scene.objects.get_mut(id)?;
+        if !F::matches(object) {
+            return None;
+        }
+        D::fetch(id, object)
+    }
+
+    /// Runs `f` on every matching object across the rayon pool, `chunk_size` objects per task
+    /// at least, e.g. `objects.par_for_each(64, |(velocity, transform)| ...)`
+    pub fn par_for_each<'q>(&'q mut self, chunk_size: usize, f: impl Fn(D::Item<'q>) + Send + Sync)
+    where
+        D::Item<'q>: Send,
+    {
+        par_for_each_scene::<D, F>(self.scene, chunk_size, f);
+    }
+
+    /// Gets the next `n` matching objects, starting where the cursor's last batch ended
+    /// Note: for work spread over frames, keep the cursor in a resource
+    pub fn iter_batched(&mut self, cursor: &mut QueryCursor, n: usize) -> Vec<D::Item<'_>> {
+        batch_scene::<D, F>(self.scene, cursor, n)
+    }
+}
+
+/// A resource taken out of the world for a `ResMut` parameter, see `World::take_system_resource`
+pub struct TakenResource<T: Resource> {
+    resource: Box<dyn Resource>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Resource + 'static> TakenResource<T> {
+    pub fn get_mut(&mut self) -> &mut T {
+        self.resource
+            .as_any_mut()
+            .downcast_mut::<T>()
+            .expect("taken resource has the wrong type")
+    }
+}
+
+/// A non send resource taken out of the world for a `NonSendMut` parameter
+pub struct TakenNonSend<T: 'static> {
+    value: Box<T>,
+}
+
+impl<T: 'static> TakenNonSend<T> {
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// The parts of the world the parameters of a system borrow from
+/// Note: used by the system macros, prefer declaring parameters
+pub struct SystemParams<'w> {
+    resources: &'w ResourceMap,
+    non_send: &'w NonSendMap,
+    scene: Option<&'w mut Scene>,
+}
+
+impl<'w> SystemParams<'w> {
+    pub fn res<T: Resource + 'static>(&self) -> Result<Res<'w, T>, ResourceError> {
+        self.resources.get::<T>().map(Res::new)
+    }
+
+    pub fn non_send<T: 'static>(&self) -> Result<NonSend<'w, T>, ResourceError> {
+        self.non_send.get::<T>().map(NonSend::new)
+    }
+
+    /// Gets the query of the system, errors if it already has one
+    pub fn query<D: QueryData, F: QueryFilter>(&mut self) -> Result<Query<'w, D, F>> {
+        let scene = self
+            .scene
+            .take()
+            .ok_or_else(|| anyhow!("A system can only have one Query parameter"))?;
+        Ok(Query {
+            scene,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl World {
+    /// Takes a resource out for a `ResMut` parameter until `restore_system_resource` is called
+    pub fn take_system_resource<T: Resource + 'static>(
+        &mut self,
+        held_by: &str,
+    ) -> Result<TakenResource<T>, ResourceError> {
+        let resource = self.resources.take::<T>(held_by.to_string())?;
+        if resource.as_any().downcast_ref::<T>().is_none() {
+            self.resources.restore::<T>(resource);
+            return Err(ResourceError::Missing {
+                name: type_name::<T>(),
+            });
+        }
+        Ok(TakenResource {
+            resource,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn restore_system_resource<T: Resource + 'static>(&mut self, taken: TakenResource<T>) {
+        self.resources.restore::<T>(taken.resource);
+    }
+
+    /// Takes a non send resource out for a `NonSendMut` parameter until
+    /// `restore_system_non_send` is called
+    pub fn take_system_non_send<T: 'static>(
+        &mut self,
+        held_by: &str,
+    ) -> Result<TakenNonSend<T>, ResourceError> {
+        let value = self.non_send.take::<T>(held_by)?;
+        Ok(TakenNonSend { value })
+    }
+
+    pub fn restore_system_non_send<T: 'static>(&mut self, taken: TakenNonSend<T>) {
+        self.non_send.restore(taken.value);
+    }
+
+    /// Splits the world for the `Res`, `NonSend` and `Query` parameters of a system
+    pub fn system_params(&mut self) -> SystemParams<'_> {
+        SystemParams {
+            resources: &self.resources,
+            non_send: &self.non_send,
+            scene: Some(&mut self.scene),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+use crate::objects::{
+    registry::registered,
+    systems::{
+        FixedUpdateSystem, LateUpdateSystem, ShutdownSystem, StartSystem, SystemKind, UpdateSystem,
+        ordering::{OrderedSystem, order_systems},
+    },
+    world::World,
+};
+
+/// A system in the schedule, listed by `World::schedule_graph`
+#[derive(Clone, Debug, Serialize)]
+pub struct ScheduleNode {
+    pub name: &'static str,
+    pub package: &'static str,
+    pub stage: &'static str,
+    /// where it runs in its stage, from 0
+    pub position: usize,
+    pub priority: u32,
+    pub set: Option<&'static str>,
+    pub is_enabled: bool,
+    pub main_thread: bool,
+}
+
+/// A `before` or `after` constraint between two systems, `from` runs before `to`
+#[derive(Clone, Debug, Serialize)]
+pub struct ScheduleEdge {
+    /// the index of the node in `ScheduleGraph::nodes`
+    pub from: usize,
+    pub to: usize,
+    /// the constraint as it was written, e.g. `game::move_player after apply_input`
+    pub constraint: String,
+}
+
+/// Every system in the order it runs after priorities and constraints, and the constraints
+/// that ordered them
+/// Note: the order inside a stage is `position`, systems without an edge between them are
+/// ordered by priority
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ScheduleGraph {
+    pub nodes: Vec<ScheduleNode>,
+    pub edges: Vec<ScheduleEdge>,
+    /// constraints that name no system or set of their stage
+    pub unknown: Vec<String>,
+    /// stages whose constraints form a cycle, they run in priority order
+    pub errors: Vec<String>,
+}
+
+impl ScheduleGraph {
+    /// The systems of one stage in the order they run
+    pub fn stage(&self, kind: SystemKind) -> impl Iterator<Item = &ScheduleNode> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.stage == kind.label())
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow!("Failed to write the schedule as json: {}", e))
+    }
+
+    /// Writes the graph for graphviz, a cluster per stage with dashed edges for the run order
+    /// and labelled edges for the constraints
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph schedule {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (cluster, kind) in SystemKind::ALL.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    subgraph cluster_{} {{\n        label=\"{}\";",
+                cluster,
+                kind.label()
+            );
+            let mut previous = None;
+            for (index, node) in self.nodes.iter().enumerate() {
+                if node.stage != kind.label() {
+                    continue;
+                }
+                let style = if node.is_enabled {
+                    ""
+                } else {
+                    ", style=dashed"
+                };
+                let _ = writeln!(
+                    dot,
+                    "        n{} [label=\"{}::{}\\npriority {}\"{}];",
+                    index,
+                    escape(node.package),
+                    escape(node.name),
+                    node.priority,
+                    style
+                );
+                if let Some(previous) = previous {
+                    let _ = writeln!(
+                        dot,
+                        "        n{} -> n{} [style=dashed, color=gray];",
+                        previous, index
+                    );
+                }
+                previous = Some(index);
+            }
+            dot.push_str("    }\n");
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"];",
+                edge.from,
+                edge.to,
+                escape(&edge.constraint)
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes the graph as dot, or as json if the path ends in `.json`
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => self.to_json()?,
+            _ => self.to_dot(),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)
+            .map_err(|e| anyhow!("Failed to write the schedule to {}: {}", path.display(), e))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl World {
+    /// Gets every registered system in the order it runs and the constraints that ordered it
+    /// Note: collected from the registry like `systems`, the order is the same every call
+    pub fn schedule_graph(&self) -> ScheduleGraph {
+        let mut graph = ScheduleGraph::default();
+        self.add_stage(&mut graph, registered::<StartSystem>(), SystemKind::Start);
+        self.add_stage(&mut graph, registered::<UpdateSystem>(), SystemKind::Update);
+        self.add_stage(
+            &mut graph,
+            registered::<FixedUpdateSystem>(),
+            SystemKind::FixedUpdate,
+        );
+        self.add_stage(
+            &mut graph,
+            registered::<LateUpdateSystem>(),
+            SystemKind::LateUpdate,
+        );
+        self.add_stage(
+            &mut graph,
+            registered::<ShutdownSystem>(),
+            SystemKind::Shutdown,
+        );
+        graph
+    }
+
+    fn add_stage<T: OrderedSystem + 'static>(
+        &self,
+        graph: &mut ScheduleGraph,
+        iter: impl Iterator<Item = &'static T>,
+        kind: SystemKind,
+    ) {
+        let systems = Self::by_priority(iter, kind);
+        let (systems, edges) = match order_systems(&systems) {
+            Ok(ordered) => {
+                graph.unknown.extend(ordered.unknown);
+                (ordered.systems, ordered.edges)
+            }
+            Err(e) => {
+                graph.errors.push(format!("{}: {}", kind.label(), e));
+                (systems, Vec::new())
+            }
+        };
+
+        let first = graph.nodes.len();
+        for (position, system) in systems.into_iter().enumerate() {
+            graph.nodes.push(ScheduleNode {
+                name: system.name(),
+                package: system.package(),
+                stage: kind.label(),
+                position,
+                priority: system.priority(),
+                set: system.order().set,
+                is_enabled: self.is_system_enabled(system.package(), system.name()),
+                main_thread: system.main_thread(),
+            });
+        }
+        graph
+            .edges
+            .extend(edges.into_iter().map(|edge| ScheduleEdge {
+                from: first + edge.from,
+                to: first + edge.to,
+                constraint: edge.constraint,
+            }));
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use apostasy_macros::Resource;
+use hashbrown::HashMap;
+
+use crate::objects::{systems::SystemKind, world::World};
+
+/// How long one system takes, kept by `SystemProfiler`
+#[derive(Clone, Copy, Debug)]
+pub struct SystemStats {
+    pub name: &'static str,
+    pub package: &'static str,
+    pub kind: SystemKind,
+    /// the average of the recent calls, see `SystemProfiler::smoothing`
+    pub avg_ms: f32,
+    /// the slowest call since the stats were reset
+    pub max_ms: f32,
+    pub last_ms: f32,
+    pub calls: u64,
+}
+
+/// Times every update, fixed update and late update system, to find the one eating the frame
+/// Note: the average moves towards each call by `smoothing`, so it follows the recent frames
+#[derive(Resource, Clone)]
+pub struct SystemProfiler {
+    pub enabled: bool,
+    /// how far each call moves the average, from 0 to 1
+    pub smoothing: f32,
+    stats: HashMap<(&'static str, &'static str, SystemKind), SystemStats>,
+}
+
+impl Default for SystemProfiler {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            smoothing: 0.05,
+            stats: HashMap::new(),
+        }
+    }
+}
+
+impl SystemProfiler {
+    pub fn record(
+        &mut self,
+        package: &'static str,
+        name: &'static str,
+        kind: SystemKind,
+        elapsed: Duration,
+    ) {
+        let ms = elapsed.as_secs_f32() * 1000.0;
+        let smoothing = self.smoothing.clamp(0.0, 1.0);
+        let stats = self
+            .stats
+            .entry((package, name, kind))
+            .or_insert(SystemStats {
+                name,
+                package,
+                kind,
+                avg_ms: ms,
+                max_ms: 0.0,
+                last_ms: 0.0,
+                calls: 0,
+            });
+        stats.avg_ms += (ms - stats.avg_ms) * smoothing;
+        stats.max_ms = stats.max_ms.max(ms);
+        stats.last_ms = ms;
+        stats.calls += 1;
+    }
+
+    /// Every system that has run, in no order
+    pub fn stats(&self) -> impl Iterator<Item = &SystemStats> {
+        self.stats.values()
+    }
+
+    /// The systems with the highest average, slowest first
+    pub fn top(&self, count: usize) -> Vec<SystemStats> {
+        let mut stats: Vec<SystemStats> = self.stats.values().copied().collect();
+        stats.sort_by(|a, b| b.avg_ms.total_cmp(&a.avg_ms));
+        stats.truncate(count);
+        stats
+    }
+
+    /// The averages of the systems of one stage added up
+    pub fn stage_avg_ms(&self, kind: SystemKind) -> f32 {
+        self.stats
+            .values()
+            .filter(|stats| stats.kind == kind)
+            .map(|stats| stats.avg_ms)
+            .sum()
+    }
+
+    pub fn reset(&mut self) {
+        self.stats.clear();
+    }
+}
+
+impl World {
+    /// Adds a call of a system to the `SystemProfiler`, if it is in the world and enabled
+    pub(crate) fn record_system_time(
+        &mut self,
+        package: &'static str,
+        name: &'static str,
+        kind: SystemKind,
+        elapsed: Duration,
+    ) {
+        if let Ok(profiler) = self.get_resource_mut::<SystemProfiler>()
+            && profiler.enabled
+        {
+            profiler.record(package, name, kind, elapsed);
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use std::any::Any;
 
-pub trait Tag: TagContainer {
+use crate::objects::registry::registered;
+
+pub trait Tag: TagContainer + Send + Sync {
     fn name() -> &'static str
     where
         Self: Sized;
@@ -45,9 +47,9 @@ pub struct TagRegistration {
     pub create: fn() -> Box<dyn Tag>,
 }
 
+#[cfg(not(target_family = "wasm"))]
 inventory::collect!(TagRegistration);
 
 pub fn get_tag_registration(type_name: &str) -> Option<&'static TagRegistration> {
-    inventory::iter::<TagRegistration>()
-        .find(|r| r.type_name.to_lowercase() == type_name.to_lowercase())
+    registered::<TagRegistration>().find(|r| r.type_name.to_lowercase() == type_name.to_lowercase())
 }
@@ -4,3 +4,7 @@ pub mod skips_serilization;
 
 #[derive(Tag, Clone)]
 pub struct Player;
+
+/// Marks an object that never moves, static models get baked ambient occlusion
+#[derive(Tag, Clone)]
+pub struct Static;
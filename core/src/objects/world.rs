@@ -1,20 +1,29 @@
-use std::cmp::Reverse;
+use std::{any::TypeId, cmp::Reverse, time::Instant};
 
 use anyhow::Result;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
 use crate::{
+    Core, log_error, log_warn,
     objects::{
         Object,
-        component::Component,
-        resource::{Resource, ResourceMap},
+        commands::Commands,
+        component::{BoxedComponent, Component, get_component_registration_of, short_type_name},
+        extract::{Extraction, WorldId},
+        non_send::NonSendMap,
+        observers::{ComponentChange, Observers},
+        registry::registered,
+        resource::{FromWorld, Resource, ResourceError, ResourceMap},
         scene::{ObjectId, Scene},
         systems::{
-            DeltaTime, EngineTimer, FixedUpdateSystem, FixedUpdateTimer, HasPriority,
-            LateUpdateSystem, StartSystem, UpdateSystem,
+            DeltaTime, EngineTimer, FixedTime, FixedUpdateSystem, FixedUpdateTimer, FrameStage,
+            FrameStep, LateUpdateSystem, ShutdownSystem, StartSystem, SystemInfo, SystemKind, Time,
+            UpdateSystem,
+            ordering::{OrderedSystem, order_systems},
         },
         tag::Tag,
     },
+    profiler,
     utils::flatten::flatten,
     voxels::{VoxelTransform, chunk::Chunk, meshes::NeedsRemeshing, voxel::VoxelId},
 };
@@ -23,11 +32,35 @@ use crate::{
 pub struct World {
     pub(crate) scene: Scene,
     pub(crate) resources: ResourceMap,
+    /// resources that must stay on the main thread, see `insert_non_send`
+    pub(crate) non_send: NonSendMap,
     pub(crate) chunk_position_index: HashMap<(i32, i32, i32), ObjectId>,
 
     update_systems: Vec<&'static UpdateSystem>,
     fixed_update_systems: Vec<&'static FixedUpdateSystem>,
     late_update_systems: Vec<&'static LateUpdateSystem>,
+    /// the (package, name) of every system that has been disabled
+    disabled_systems: HashSet<(&'static str, &'static str)>,
+    /// how many times the fixed update systems ran last frame
+    fixed_steps: u32,
+    /// applied after every system, see `queue_commands`
+    pub(crate) queued_commands: Commands,
+    /// swaps the buffers of every event added with `add_event`
+    pub(crate) event_updaters: HashMap<TypeId, fn(&mut World)>,
+    /// applies the changes of every state added with `add_state`
+    pub(crate) state_updaters: HashMap<TypeId, fn(&mut World)>,
+    /// logs uses of removed objects, see `set_stale_object_logging`
+    pub(crate) log_stale_objects: bool,
+    /// the (package, name) of the system running right now
+    current_system: Option<(&'static str, &'static str)>,
+    /// the world being edited, kept aside while this copy of it plays, see `play`
+    pub(crate) edit_world: Option<Box<World>>,
+    /// called when components are inserted or removed, see `observe`
+    pub(crate) observers: Observers,
+    /// tells this world apart from the others, see `world_object_id`
+    pub(crate) id: WorldId,
+    /// the components copied out by `extract_into` and the objects it made here
+    pub(crate) extraction: Extraction,
 }
 
 #[allow(unused)]
@@ -36,31 +69,101 @@ impl World {
 
     /// Collects and caches all systems
     pub fn build_systems(&mut self) {
-        self.update_systems = Self::collect_sorted(inventory::iter::<UpdateSystem>());
-        self.fixed_update_systems = Self::collect_sorted(inventory::iter::<FixedUpdateSystem>());
-        self.late_update_systems = Self::collect_sorted(inventory::iter::<LateUpdateSystem>());
+        self.collect_systems();
         self.insert_resource(FixedUpdateTimer {
             accumulator: 0.0,
             fixed_timestep: 1.0 / 20.0,
             last_time: None,
         });
         self.insert_resource(DeltaTime(0.0));
+        self.insert_resource(Time::default());
     }
 
-    /// Collects and sorts the Iterator
-    fn collect_sorted<T: HasPriority>(iter: impl Iterator<Item = &'static T>) -> Vec<&'static T> {
+    /// Collects the systems again without resetting the timers, e.g. after a library with
+    /// systems was loaded
+    /// Note: never call it while a stage is running, the stage puts back the systems it took
+    pub(crate) fn collect_systems(&mut self) {
+        self.update_systems =
+            Self::collect_sorted(registered::<UpdateSystem>(), SystemKind::Update);
+        self.fixed_update_systems =
+            Self::collect_sorted(registered::<FixedUpdateSystem>(), SystemKind::FixedUpdate);
+        self.late_update_systems =
+            Self::collect_sorted(registered::<LateUpdateSystem>(), SystemKind::LateUpdate);
+    }
+
+    /// Builds the systems of a world copied from another, keeping the ones it disabled
+    pub(crate) fn build_systems_like(&mut self, other: &World) {
+        self.build_systems();
+        self.disabled_systems = other.disabled_systems.clone();
+    }
+
+    /// Collects systems by priority, start systems run the lowest priority first
+    pub(crate) fn by_priority<T: OrderedSystem>(
+        iter: impl Iterator<Item = &'static T>,
+        kind: SystemKind,
+    ) -> Vec<&'static T> {
         let mut systems: Vec<_> = iter.collect();
         systems.sort_by_key(|s| Reverse(s.priority()));
+        if kind == SystemKind::Start {
+            systems.reverse();
+        }
         systems
     }
+
+    /// Collects and sorts the Iterator by the `before` and `after` constraints, then by priority
+    /// Note: logs constraints that name nothing, and falls back to the priority order on a cycle
+    fn collect_sorted<T: OrderedSystem>(
+        iter: impl Iterator<Item = &'static T>,
+        kind: SystemKind,
+    ) -> Vec<&'static T> {
+        let systems = Self::by_priority(iter, kind);
+        match order_systems(&systems) {
+            Ok(ordered) => {
+                for constraint in ordered.unknown {
+                    log_warn!(
+                        "No {} system or set matches the constraint {}",
+                        kind.label(),
+                        constraint
+                    );
+                }
+                ordered.systems
+            }
+            Err(e) => {
+                log_error!("Failed to order the {} systems: {}", kind.label(), e);
+                systems
+            }
+        }
+    }
+
     /// Runs all start systems
     pub(crate) fn start(&mut self) {
-        let mut systems = inventory::iter::<StartSystem>().collect::<Vec<_>>();
+        let _stage = profiler::scope("Start");
+        let systems = Self::collect_sorted(registered::<StartSystem>(), SystemKind::Start);
+        for system in systems {
+            if !self.should_run_system(system.package, system.name, system.main_thread) {
+                continue;
+            }
+            self.current_system = Some((system.package, system.name));
+            let _scope = profiler::scope(system.name);
+            (system.func)(self);
+            self.current_system = None;
+            self.apply_queued_commands();
+        }
+    }
 
-        systems.sort_by_key(|s| Reverse(s.priority));
-        systems.reverse();
-        for system in systems.iter_mut() {
+    /// Runs all shutdown systems, see `Core::shutdown`
+    pub(crate) fn shutdown(&mut self) {
+        let _stage = profiler::scope("Shutdown");
+        let systems = Self::collect_sorted(registered::<ShutdownSystem>(), SystemKind::Shutdown);
+        for system in systems {
+            if !self.should_run_system(system.package, system.name, system.main_thread) {
+                continue;
+            }
+            self.current_system = Some((system.package, system.name));
+            let _scope = profiler::scope(system.name);
             (system.func)(self);
+            self.current_system = None;
+            self.apply_queued_commands();
         }
     }
 
@@ -68,12 +171,19 @@ impl World {
         // update delta time
         {
             let timer = self.get_resource_mut::<FixedUpdateTimer>().unwrap();
-            let now = std::time::Instant::now();
+            let now = Instant::now();
             let delta = match timer.last_time {
                 Some(last) => now.duration_since(last).as_secs_f32().min(0.25),
                 None => 0.0,
             };
             timer.last_time = Some(now);
+
+            let time = self.get_resource_mut::<Time>().unwrap();
+            time.unscaled_delta = delta;
+            time.unscaled_elapsed += delta;
+            let delta = time.delta();
+
+            let timer = self.get_resource_mut::<FixedUpdateTimer>().unwrap();
             timer.accumulator += delta;
 
             timer.accumulator = timer.accumulator.min(timer.fixed_timestep * 5.0);
@@ -85,15 +195,38 @@ impl World {
             dt.0 = delta;
         }
 
+        let _stage = profiler::scope("Update");
+        let is_paused = self.is_paused();
         let systems = std::mem::take(&mut self.update_systems);
         for system in &systems {
-            (system.func)(self).unwrap();
+            if is_paused && !system.while_paused {
+                continue;
+            }
+            if !self.should_run_system(system.package, system.name, system.main_thread) {
+                continue;
+            }
+            self.current_system = Some((system.package, system.name));
+            let _scope = profiler::scope(system.name);
+            let started = Instant::now();
+            if let Err(e) = (system.func)(self) {
+                log_error!("System {}::{} failed: {}", system.package, system.name, e);
+            }
+            self.record_system_time(
+                system.package,
+                system.name,
+                SystemKind::Update,
+                started.elapsed(),
+            );
+            self.current_system = None;
+            self.apply_queued_commands();
         }
 
         self.update_systems = systems;
     }
 
     pub(crate) fn fixed_update(&mut self) {
+        self.fixed_steps = 0;
+        let _stage = profiler::scope("FixedUpdate");
         loop {
             let (should_run, timestep) = {
                 let timer = self.get_resource::<FixedUpdateTimer>().unwrap();
@@ -110,37 +243,350 @@ impl World {
             self.get_resource_mut::<FixedUpdateTimer>()
                 .unwrap()
                 .accumulator -= timestep;
+            self.fixed_steps += 1;
 
+            let is_paused = self.is_paused();
             let systems = std::mem::take(&mut self.fixed_update_systems);
             for system in &systems {
-                (system.func)(self, timestep).unwrap();
+                if is_paused && !system.while_paused {
+                    continue;
+                }
+                if !self.should_run_system(system.package, system.name, system.main_thread) {
+                    continue;
+                }
+                self.current_system = Some((system.package, system.name));
+                let _scope = profiler::scope(system.name);
+                let started = Instant::now();
+                if let Err(e) = (system.func)(self, timestep) {
+                    log_error!("System {}::{} failed: {}", system.package, system.name, e);
+                }
+                self.record_system_time(
+                    system.package,
+                    system.name,
+                    SystemKind::FixedUpdate,
+                    started.elapsed(),
+                );
+                self.current_system = None;
+                self.apply_queued_commands();
             }
             self.fixed_update_systems = systems;
+            self.record_interpolated_transforms();
         }
+
+        let timer = self.get_resource::<FixedUpdateTimer>().unwrap();
+        let fixed_time = FixedTime {
+            timestep: timer.fixed_timestep,
+            overstep_fraction: (timer.accumulator / timer.fixed_timestep).clamp(0.0, 1.0),
+            steps: self.fixed_steps,
+        };
+        self.insert_resource(fixed_time);
     }
 
     /// Runs all late update systems
     pub(crate) fn late_update(&mut self) {
+        let _stage = profiler::scope("LateUpdate");
         let systems = std::mem::take(&mut self.late_update_systems);
         for system in &systems {
+            if !self.should_run_system(system.package, system.name, system.main_thread) {
+                continue;
+            }
+            self.current_system = Some((system.package, system.name));
+            let _scope = profiler::scope(system.name);
+            let started = Instant::now();
             (system.func)(self);
+            self.record_system_time(
+                system.package,
+                system.name,
+                SystemKind::LateUpdate,
+                started.elapsed(),
+            );
+            self.current_system = None;
+            self.apply_queued_commands();
         }
         self.late_update_systems = systems;
-    } // ========== ========== Objects ========== ==========
+
+        self.update_events();
+        self.apply_state_transitions();
+        self.apply_play_mode_request();
+        #[cfg(feature = "hot-reload")]
+        self.apply_dynamic_plugin_swaps();
+    }
+
+    /// Gets every registered system in the order they run, grouped by kind
+    /// Note: collected from the registry so it is complete while systems are running
+    pub fn systems(&self) -> Vec<SystemInfo> {
+        [
+            self.system_infos(registered::<StartSystem>(), SystemKind::Start),
+            self.system_infos(registered::<UpdateSystem>(), SystemKind::Update),
+            self.system_infos(registered::<FixedUpdateSystem>(), SystemKind::FixedUpdate),
+            self.system_infos(registered::<LateUpdateSystem>(), SystemKind::LateUpdate),
+            self.system_infos(registered::<ShutdownSystem>(), SystemKind::Shutdown),
+        ]
+        .concat()
+    }
+
+    /// Lists the systems of one kind in the order they run, without logging like `build_systems`
+    fn system_infos<T: OrderedSystem + 'static>(
+        &self,
+        iter: impl Iterator<Item = &'static T>,
+        kind: SystemKind,
+    ) -> Vec<SystemInfo> {
+        let systems = Self::by_priority(iter, kind);
+        let systems = order_systems(&systems)
+            .map(|ordered| ordered.systems)
+            .unwrap_or(systems);
+        systems
+            .into_iter()
+            .map(|s| SystemInfo {
+                name: s.name(),
+                kind,
+                priority: s.priority(),
+                order: s.order(),
+                package: s.package(),
+                is_enabled: self.is_system_enabled(s.package(), s.name()),
+                main_thread: s.main_thread(),
+            })
+            .collect()
+    }
+
+    /// Gets the steps of a frame in the order they run, built from `Core::FRAME_STAGES`
+    /// Note: start and shutdown systems run once, not every frame, so they are not included
+    pub fn frame_graph(&self) -> Vec<FrameStep> {
+        let systems = self.systems();
+        let mut steps = Vec::new();
+        for stage in Core::FRAME_STAGES {
+            match stage {
+                FrameStage::Systems(kind) => steps.extend(
+                    systems
+                        .iter()
+                        .filter(|s| s.kind == *kind)
+                        .map(|s| FrameStep::System(*s)),
+                ),
+                FrameStage::Flush(label) => steps.push(FrameStep::Flush(label)),
+            }
+        }
+        steps
+    }
+
+    /// Gets how many times the fixed update systems ran last frame
+    pub fn fixed_steps(&self) -> u32 {
+        self.fixed_steps
+    }
+
+    pub fn is_system_enabled(&self, package: &str, name: &str) -> bool {
+        !self.disabled_systems.contains(&(package, name))
+    }
+
+    /// Returns false for disabled systems, and for main thread systems off the main thread
+    fn should_run_system(&self, package: &str, name: &str, main_thread: bool) -> bool {
+        if main_thread && !self.is_main_thread() {
+            log_error!(
+                "System {}::{} uses non send resources and was skipped off the main thread",
+                package,
+                name
+            );
+            return false;
+        }
+        self.is_system_enabled(package, name)
+    }
+
+    /// Enables or disables every system called `name` or `package::name`
+    /// returns how many systems matched
+    pub fn set_system_enabled(&mut self, name: &str, is_enabled: bool) -> Result<usize> {
+        let matching: Vec<SystemInfo> = self
+            .systems()
+            .into_iter()
+            .filter(|s| s.name == name || format!("{}::{}", s.package, s.name) == name)
+            .collect();
+        if matching.is_empty() {
+            return Err(anyhow::anyhow!("No system called {}", name));
+        }
+
+        for system in &matching {
+            if is_enabled {
+                self.disabled_systems.remove(&(system.package, system.name));
+            } else {
+                self.disabled_systems.insert((system.package, system.name));
+            }
+        }
+        Ok(matching.len())
+    }
+
+    pub fn enable_all_systems(&mut self) {
+        self.disabled_systems.clear();
+    }
+
+    // ========== ========== Objects ========== ==========
 
     /// Adds a new Object to the world
     pub fn add_new_object(&mut self) -> ObjectId {
         self.scene.add_new_object()
     }
 
-    /// Adds an Object to the world
+    /// Adds an Object to the world, running the `OnInsert` observers of its components
     pub fn add_object(&mut self, object: Object) -> ObjectId {
+        let id = self.scene.add_object(object);
+        self.trigger_inserted_components(id);
+        id
+    }
+
+    /// Adds a new Object with a name, so it can be found with `find_by_name` and reads well in logs
+    pub fn spawn_named(&mut self, name: &str) -> ObjectId {
+        let mut object = Object::new();
+        object.name = name.to_string();
         self.scene.add_object(object)
     }
 
-    /// Removes an Object from the world
+    /// Gets the first object called `name`
+    /// Note: names don't have to be unique, use `find_all_by_name` to get every match
+    pub fn find_by_name(&self, name: &str) -> Option<ObjectId> {
+        self.scene
+            .objects
+            .iter()
+            .find(|(_, object)| object.name == name)
+            .map(|(id, _)| id)
+    }
+
+    /// Gets every object called `name`
+    pub fn find_all_by_name(&self, name: &str) -> Vec<ObjectId> {
+        self.scene
+            .objects
+            .iter()
+            .filter(|(_, object)| object.name == name)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Copies an object and its descendants with new ids and cloned components and tags
+    /// the copy gets the same parent as the object, returns the id of the copy
+    /// Note: voxel chunks can't be copied, their meshes and index entries belong to one object
+    pub fn clone_object(&mut self, id: ObjectId) -> Result<ObjectId> {
+        let Some(object) = self.scene.objects.get(id) else {
+            return Err(anyhow::anyhow!("Object does not exist"));
+        };
+        let parent = object.parent;
+        let has_chunk = std::iter::once(id)
+            .chain(self.scene.get_descendants(id))
+            .any(|id| self.chunk_position_index.values().any(|chunk| *chunk == id));
+        if has_chunk {
+            return Err(anyhow::anyhow!("Voxel chunks can't be copied"));
+        }
+
+        let copy = self.clone_subtree(id);
+        if parent.is_some() {
+            self.set_parent(copy, parent)?;
+        }
+        Ok(copy)
+    }
+
+    fn clone_subtree(&mut self, id: ObjectId) -> ObjectId {
+        let mut object = self.scene.objects[id].clone();
+        let children = std::mem::take(&mut object.children);
+        let copy = self.scene.add_object(object);
+        self.trigger_inserted_components(copy);
+        for child in children {
+            let child_copy = self.clone_subtree(child);
+            self.scene.objects[child_copy].parent = Some(copy);
+            self.scene.objects[copy].children.push(child_copy);
+        }
+        copy
+    }
+
+    /// Describes an object as `name (id): components #tags`, for logs and the console
+    pub fn object_to_string(&self, id: ObjectId) -> Option<String> {
+        let object = self.scene.objects.get(id)?;
+        let components: Vec<&str> = object
+            .components
+            .iter()
+            .map(|component| short_type_name(component.type_name()))
+            .collect();
+        let mut description = format!("{} ({:?}): {}", object.name, id, components.join(", "));
+        for tag in &object.tags {
+            description.push_str(" #");
+            description.push_str(short_type_name(tag.type_name()));
+        }
+        Some(description)
+    }
+
+    /// Removes an Object and its descendants from the world, running the `on_remove` hooks of
+    /// their components once they are gone
     pub fn remove_object(&mut self, id: ObjectId) {
+        let removed: Vec<(ObjectId, Vec<BoxedComponent>)> = std::iter::once(id)
+            .chain(self.scene.get_descendants(id))
+            .filter_map(|id| {
+                let object = self.scene.objects.get_mut(id)?;
+                Some((id, std::mem::take(&mut object.components)))
+            })
+            .collect();
         self.scene.remove_object(id);
+
+        for (id, components) in removed {
+            self.run_remove_hooks(id, components);
+        }
+    }
+
+    /// Removes an object and every descendant in one go, including the chunks among them
+    /// returns false if the object doesn't exist
+    /// Note: no hook runs until the whole subtree is gone, so hooks never see half of it
+    pub fn despawn_recursive(&mut self, id: ObjectId) -> bool {
+        if !self.scene.objects.contains_key(id) {
+            return false;
+        }
+        let subtree: HashSet<ObjectId> = std::iter::once(id)
+            .chain(self.scene.get_descendants(id))
+            .collect();
+        self.chunk_position_index
+            .retain(|_, chunk| !subtree.contains(chunk));
+        self.remove_object(id);
+        true
+    }
+
+    /// Removes only an object, its children take its place under its parent
+    /// returns false if the object doesn't exist
+    pub fn despawn(&mut self, id: ObjectId) -> bool {
+        if !self.scene.objects.contains_key(id) {
+            return false;
+        }
+        let parent = self.get_parent_id(id);
+        for child in self.get_children_ids(id).to_vec() {
+            if let Err(e) = self.set_parent(child, parent) {
+                log_warn!("Failed to reparent a child of {:?}: {}", id, e);
+            }
+        }
+        self.chunk_position_index.retain(|_, chunk| *chunk != id);
+        self.remove_object(id);
+        true
+    }
+
+    /// Removes a component from an object and runs its `on_remove` hook
+    /// returns false if the object doesn't have the component
+    /// Note: `Object::remove_component` can't reach the world so it skips the hook
+    pub fn remove_component<T: Component + 'static>(&mut self, id: ObjectId) -> bool {
+        let Some(object) = self.scene.objects.get_mut(id) else {
+            return false;
+        };
+        let Some(index) = object
+            .components
+            .iter()
+            .position(|c| c.as_any().type_id() == TypeId::of::<T>())
+        else {
+            return false;
+        };
+        let component = object.components.remove(index);
+        self.run_remove_hooks(id, vec![component]);
+        true
+    }
+
+    pub(crate) fn run_remove_hooks(&mut self, id: ObjectId, components: Vec<BoxedComponent>) {
+        for mut component in components {
+            if let Some(on_remove) =
+                get_component_registration_of(&component).and_then(|r| r.on_remove)
+            {
+                on_remove(self, id, &mut component);
+            }
+            let type_id = component.as_any().type_id();
+            self.trigger(id, type_id, ComponentChange::Removed);
+        }
     }
 
     pub fn debug_objects(&self) {
@@ -151,6 +597,30 @@ impl World {
         self.scene.objects.len()
     }
 
+    /// How many objects fit before the scene has to grow
+    pub fn object_capacity(&self) -> usize {
+        self.scene.objects.capacity()
+    }
+
+    /// Makes room for `additional` more objects, so a burst of spawns does not grow the scene
+    /// mid frame
+    pub fn reserve_objects(&mut self, additional: usize) {
+        self.scene.objects.reserve(additional);
+    }
+
+    /// Frees the spare capacity left in every object's components, tags and children by removals
+    /// Note: the scene's slots are not released, removed slots are reused under a new generation
+    /// so old ids fail the generation check instead of finding the new object
+    /// insert `AutoCompact` to run it periodically
+    pub fn compact(&mut self) {
+        for (_, object) in self.scene.objects.iter_mut() {
+            object.components.shrink_to_fit();
+            object.tags.shrink_to_fit();
+            object.children.shrink_to_fit();
+        }
+        self.chunk_position_index.shrink_to_fit();
+    }
+
     pub fn get_object(&self, id: ObjectId) -> Option<&Object> {
         self.scene.get_object(id)
     }
@@ -244,8 +714,33 @@ impl World {
         self
     }
 
+    /// Inserts a resource made with `FromWorld` unless it is already there, then gets it
+    /// Note: a resource taken by `with_resource_mut` gives `ResourceError::Locked` rather than
+    /// being made twice
+    pub fn init_resource<T: Resource + FromWorld + 'static>(
+        &mut self,
+    ) -> Result<&mut T, ResourceError> {
+        self.get_or_insert_resource_with(T::from_world)
+    }
+
+    /// Gets a resource, inserting the one `f` makes from the world if it is missing
+    pub fn get_or_insert_resource_with<T: Resource + 'static>(
+        &mut self,
+        f: impl FnOnce(&mut World) -> T,
+    ) -> Result<&mut T, ResourceError> {
+        match self.resources.get::<T>().err() {
+            None => {}
+            Some(ResourceError::Missing { .. }) => {
+                let resource = f(self);
+                self.resources.insert(resource);
+            }
+            Some(e) => return Err(e),
+        }
+        self.resources.get_mut::<T>()
+    }
+
     /// Get a resource from the map
-    pub fn get_resource<T: Resource + 'static>(&self) -> Result<&T> {
+    pub fn get_resource<T: Resource + 'static>(&self) -> Result<&T, ResourceError> {
         self.resources.get::<T>()
     }
 
@@ -255,7 +750,7 @@ impl World {
     }
 
     /// Get a resource mutably from the map
-    pub fn get_resource_mut<T: Resource + 'static>(&mut self) -> Result<&mut T> {
+    pub fn get_resource_mut<T: Resource + 'static>(&mut self) -> Result<&mut T, ResourceError> {
         self.resources.get_mut::<T>()
     }
 
@@ -265,6 +760,46 @@ impl World {
         self
     }
 
+    /// Takes a resource out of the world while `f` runs, so `f` can use the resource and the rest
+    /// of the world at once
+    /// Accessing the resource inside `f` gives `ResourceError::Locked`, taking it again logs who
+    /// holds which resource
+    pub fn with_resource_mut<T: Resource + 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut World, &mut T) -> R,
+    ) -> Result<R, ResourceError> {
+        let held_by = match self.current_system {
+            Some((package, name)) => format!("{}::{}", package, name),
+            None => "engine".to_string(),
+        };
+        let mut resource = match self.resources.take::<T>(held_by.clone()) {
+            Ok(resource) => resource,
+            Err(e) => {
+                if let ResourceError::Locked { .. } = e {
+                    log_error!(
+                        "Re-entrant resource lock in {}: {}\nHeld resources:\n{}",
+                        held_by,
+                        e,
+                        self.resources.wait_graph()
+                    );
+                }
+                return Err(e);
+            }
+        };
+
+        let result = match resource.as_any_mut().downcast_mut::<T>() {
+            Some(value) => f(self, value),
+            None => {
+                self.resources.restore::<T>(resource);
+                return Err(ResourceError::Missing {
+                    name: std::any::type_name::<T>(),
+                });
+            }
+        };
+        self.resources.restore::<T>(resource);
+        Ok(result)
+    }
+
     // ========== ========== Voxel Specific ========== ==========
 
     pub fn register_chunk(&mut self, id: ObjectId) {
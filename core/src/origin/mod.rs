@@ -0,0 +1,122 @@
+use anyhow::Result;
+use apostasy_macros::{Resource, update};
+use cgmath::{Vector3, Zero};
+
+use crate::{
+    log,
+    objects::{components::transform::Transform, scene::ObjectId, world::World},
+    rendering::components::camera::ActiveCamera,
+};
+
+/// A resource that enables origin rebasing for large worlds
+/// Once the active camera is further than `threshold` from (0, 0, 0) every root transform is
+/// shifted back by a whole number of chunks, keeping floats near the origin precise
+/// Transforms are relative to the origin, voxel and chunk coordinates stay absolute
+/// Note: rebasing only runs while this resource exists
+#[derive(Resource, Clone)]
+pub struct WorldOrigin {
+    /// the absolute voxel position of (0, 0, 0), always a multiple of the chunk size
+    pub offset: Vector3<i32>,
+    /// the distance from (0, 0, 0) the camera can reach before the origin is moved
+    pub threshold: f32,
+}
+
+impl Default for WorldOrigin {
+    fn default() -> Self {
+        Self {
+            offset: Vector3::zero(),
+            threshold: 1024.0,
+        }
+    }
+}
+
+impl WorldOrigin {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the absolute voxel containing a position relative to the origin
+    pub fn to_absolute_voxel(&self, position: Vector3<f32>) -> Vector3<i32> {
+        Vector3::new(
+            position.x.floor() as i32,
+            position.y.floor() as i32,
+            position.z.floor() as i32,
+        ) + self.offset
+    }
+
+    /// Gets a position relative to the origin from an absolute voxel position
+    pub fn to_relative(&self, position: Vector3<i32>) -> Vector3<f32> {
+        (position - self.offset).cast::<f32>().unwrap()
+    }
+}
+
+impl World {
+    /// Gets the offset of the world origin, zero if origin rebasing is disabled
+    pub fn origin_offset(&self) -> Vector3<i32> {
+        self.get_resource::<WorldOrigin>()
+            .map(|origin| origin.offset)
+            .unwrap_or(Vector3::zero())
+    }
+
+    /// Moves every root transform by -shift and the origin by +shift
+    /// Note: shift should be a multiple of the chunk size so chunk meshes stay aligned
+    pub fn shift_origin(&mut self, shift: Vector3<i32>) -> Result<()> {
+        let origin = self.get_resource_mut::<WorldOrigin>()?;
+        origin.offset += shift;
+
+        let delta = shift.cast::<f32>().unwrap();
+        let roots: Vec<ObjectId> = self
+            .scene
+            .objects
+            .iter()
+            .filter(|(_, object)| object.parent.is_none())
+            .map(|(id, _)| id)
+            .collect();
+
+        // children are relative to their parent and follow through transform_update
+        for id in roots {
+            if let Some(object) = self.get_object_mut(id)
+                && let Ok(transform) = object.get_component_mut::<Transform>()
+            {
+                transform.local_position -= delta;
+                transform.global_position -= delta;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[update]
+pub fn origin_rebase_system(world: &mut World) -> Result<()> {
+    let Ok(origin) = world.get_resource::<WorldOrigin>() else {
+        return Ok(());
+    };
+    let threshold = origin.threshold;
+
+    let Ok(camera) = world.get_object_with_tag::<ActiveCamera>() else {
+        return Ok(());
+    };
+    let camera_position = camera.get_component::<Transform>()?.global_position;
+
+    if camera_position.x.abs() <= threshold
+        && camera_position.y.abs() <= threshold
+        && camera_position.z.abs() <= threshold
+    {
+        return Ok(());
+    }
+
+    // round down to whole chunks so the camera ends up within a chunk of (0, 0, 0)
+    let shift = Vector3::new(
+        (camera_position.x / 32.0).floor() as i32 * 32,
+        (camera_position.y / 32.0).floor() as i32 * 32,
+        (camera_position.z / 32.0).floor() as i32 * 32,
+    );
+    world.shift_origin(shift)?;
+
+    log!("Shifted world origin by {:?}", shift);
+    Ok(())
+}
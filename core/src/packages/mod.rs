@@ -1,4 +1,7 @@
+use apostasy_macros::Resource;
+
 use crate::{
+    log, log_error, log_warn,
     objects::world::World,
     packages::{item_system_package::add_item_system_package, voxel_package::add_voxel_package},
 };
@@ -6,19 +9,85 @@ use crate::{
 pub mod item_system_package;
 pub mod voxel_package;
 
-#[derive(Clone, Copy)]
+/// Sets up what a crate needs in the world, e.g. its resources, events and states, added with
+/// `world.add_plugin(MyPlugin)`
+/// Note: systems, components and console commands register themselves through the macros, so a
+/// plugin only adds what they use
+pub trait Plugin: Send + Sync + 'static {
+    fn build(&self, world: &mut World);
+
+    /// Logged when it is added, a plugin with the same name is only added once
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// The names of the plugins added to the world, see `World::add_plugin`
+#[derive(Resource, Clone, Default)]
+pub struct AddedPlugins(pub Vec<&'static str>);
+
+/// The plugins built into the engine
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Packages {
     Voxel,
     ItemSystem,
 }
 
-pub fn add_package(world: &mut World, package: Packages) {
-    match package {
-        Packages::Voxel => {
-            add_voxel_package(world);
+impl Plugin for Packages {
+    fn build(&self, world: &mut World) {
+        match self {
+            Packages::Voxel => {
+                add_voxel_package(world);
+            }
+            Packages::ItemSystem => {
+                add_item_system_package(world);
+            }
         }
-        Packages::ItemSystem => {
-            add_item_system_package(world);
+        world.init_registered_resources(Some(*self));
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Packages::Voxel => "Voxel",
+            Packages::ItemSystem => "ItemSystem",
         }
     }
 }
+
+pub fn add_package(world: &mut World, package: Packages) {
+    world.add_plugin(package);
+}
+
+impl World {
+    /// Builds a plugin into the world, skipping it if a plugin with its name was already added
+    pub fn add_plugin(&mut self, plugin: impl Plugin) -> &mut Self {
+        self.add_boxed_plugin(&plugin)
+    }
+
+    /// Builds a plugin that is only known as `dyn Plugin`, see `add_plugin`
+    pub fn add_boxed_plugin(&mut self, plugin: &dyn Plugin) -> &mut Self {
+        let name = plugin.name();
+        let added = match self.init_resource::<AddedPlugins>() {
+            Ok(added) => &mut added.0,
+            Err(e) => {
+                log_error!("Failed to add plugin {}: {}", name, e);
+                return self;
+            }
+        };
+        if added.contains(&name) {
+            log_warn!("Plugin {} was already added", name);
+            return self;
+        }
+        added.push(name);
+
+        log!("Adding plugin {}", name);
+        plugin.build(self);
+        self
+    }
+
+    /// Returns true if a plugin with this name was added
+    pub fn has_plugin(&self, name: &str) -> bool {
+        self.get_resource::<AddedPlugins>()
+            .is_ok_and(|added| added.0.iter().any(|added| *added == name))
+    }
+}
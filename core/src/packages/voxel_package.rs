@@ -16,8 +16,7 @@ use crate::{
     objects::world::World,
     voxels::{
         biome::BiomeRegistry,
-        chunk::VoxelBreakProgress,
-        chunk_loader::{ChunkLoadBounds, ChunkPositionMap},
+        chunk_loader::ChunkLoadBounds,
         structure::StructureRegistry,
         texture_atlas::{AtlasBuilder, PendingAtlas},
         voxel::VoxelRegistry,
@@ -82,14 +81,19 @@ pub(crate) fn add_voxel_package(world: &mut World) {
         .unwrap();
 
     let (atlas_image, atlas_tiles) = atlas_builder.build();
+    let atlas_paths = atlas_builder
+        .tiles
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect();
 
     world.insert_resource(registry);
     world.insert_resource(biome_registry);
     world.insert_resource(structure_registry);
-    world.insert_resource(VoxelBreakProgress::default());
-    world.insert_resource(ChunkPositionMap::default());
     world.insert_resource(PendingAtlas {
         image: atlas_image,
         tiles: atlas_tiles,
+        tile_size: atlas_builder.tile_size,
+        paths: atlas_paths,
     });
 }
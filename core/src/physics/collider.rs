@@ -1,7 +1,11 @@
 use apostasy_macros::Component;
 use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[component_serde]
+#[component_group("physics")]
+#[serde(default)]
 pub struct Collider {
     pub half_extents: Vector3<f32>,
 }
@@ -15,10 +19,6 @@ impl Default for Collider {
 }
 
 impl Collider {
-    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> anyhow::Result<()> {
-        Ok(())
-    }
-
     pub fn player() -> Self {
         Self {
             half_extents: Vector3::new(0.2, 0.9, 0.2),
@@ -1,12 +1,18 @@
 use anyhow::Result;
-use apostasy_macros::update;
+use apostasy_macros::fixed_update;
 use cgmath::{Vector3, Zero};
 
 use crate::{
+    math::Aabb,
     objects::{
         components::transform::Transform, scene::ObjectId, systems::DeltaTime, world::World,
     },
-    physics::{collider::Collider, velocity::Velocity},
+    physics::{
+        collider::Collider,
+        contacts::{Contact, Contacts},
+        physics_settings::PhysicsSettings,
+        velocity::Velocity,
+    },
     voxels::{voxel::VoxelRegistry, voxel_components::is_solid::IsSolid},
 };
 
@@ -15,6 +21,8 @@ pub struct CollisionFlags {
     pub grounded: bool,
     pub hit_ceil: bool,
     pub hit_wall: bool,
+    /// the objects pushed out of
+    pub hit: Vec<ObjectId>,
 }
 
 pub struct ColliderData {
@@ -23,9 +31,111 @@ pub struct ColliderData {
     half_extents: Vector3<f32>,
 }
 
-#[update]
-pub fn voxel_collision_system(world: &mut World) -> Result<()> {
-    let delta = world.get_resource::<DeltaTime>()?.0;
+/// Finds how far to push an aabb at position out of the solid voxels it overlaps
+/// returns the correction and whether the aabb is standing on a voxel
+fn resolve_voxel_overlaps(
+    world: &World,
+    registry: &VoxelRegistry,
+    origin: Vector3<i32>,
+    position: Vector3<f32>,
+    half_extents: Vector3<f32>,
+) -> (Vector3<f32>, bool) {
+    let min = position - half_extents;
+    let max = position + half_extents;
+
+    let min_vox = Vector3::new(
+        min.x.floor() as i32 - 1,
+        min.y.floor() as i32 - 1,
+        min.z.floor() as i32 - 1,
+    );
+    let max_vox = Vector3::new(
+        max.x.ceil() as i32 + 1,
+        max.y.ceil() as i32 + 1,
+        max.z.ceil() as i32 + 1,
+    );
+
+    let mut total_correction = Vector3::zero();
+    let mut grounded = false;
+
+    for vx in min_vox.x..max_vox.x {
+        for vy in min_vox.y..max_vox.y {
+            for vz in min_vox.z..max_vox.z {
+                // get the voxel info
+                let voxel_id = match world.get_voxel(vx + origin.x, vy + origin.y, vz + origin.z) {
+                    Some(id) if id != 0 => id,
+                    _ => continue,
+                };
+                let def = match registry.get_def(voxel_id) {
+                    Ok(d) => d,
+                    Err(_) => continue,
+                };
+
+                // if its not a solid voxel return as it doesn't have collision
+                if !def.has_component::<IsSolid>() {
+                    continue;
+                }
+
+                // calculate the extents of the object and voxel
+                let pos = position + total_correction;
+                let bounds = Aabb::from_center_half_extents(pos, half_extents);
+
+                let vox_min = Vector3::new(vx as f32, vy as f32, vz as f32);
+                let vox_max = vox_min + Vector3::new(1.0, 1.0, 1.0);
+
+                // detects overlap between the object and voxels collider
+                let overlap = bounds.overlap(&Aabb::new(vox_min, vox_max));
+                let (overlap_x, overlap_y, overlap_z) = (overlap.x, overlap.y, overlap.z);
+
+                // detect if theres no overlap
+                // FIX: setting the overlap_y to be <= 0.01 fixes an issue where you get stuck
+                // on voxels, but it also breaks ground detection
+                if overlap_x <= 0.0 || overlap_y <= 0.00 || overlap_z <= 0.0 {
+                    continue;
+                }
+
+                let vox_center = vox_min + Vector3::new(0.5, 0.5, 0.5);
+
+                // collision correction maths, determines how far to push the object out of the
+                // collider
+                if overlap_y <= overlap_x && overlap_y <= overlap_z {
+                    if pos.y > vox_center.y {
+                        total_correction.y += overlap_y;
+                        let feet = pos.y - half_extents.y;
+
+                        // grounded detection 1
+                        if (feet - vox_max.y).abs() < 0.2 {
+                            grounded = true;
+                        }
+                    } else {
+                        total_correction.y -= overlap_y;
+                    }
+                } else if overlap_x <= overlap_z {
+                    if pos.x > vox_center.x {
+                        total_correction.x += overlap_x;
+                    } else {
+                        total_correction.x -= overlap_x;
+                    }
+                } else {
+                    if pos.z > vox_center.z {
+                        total_correction.z += overlap_z;
+                    } else {
+                        total_correction.z -= overlap_z;
+                    }
+                }
+            }
+        }
+    }
+
+    (total_correction, grounded)
+}
+
+/// Moves the colliders by their velocity in substeps of the fixed update step, pushing them out
+/// of the solid voxels they enter
+#[fixed_update]
+pub fn voxel_collision_system(world: &mut World, delta: f32) -> Result<()> {
+    if world.get_resource::<PhysicsSettings>()?.paused {
+        return Ok(());
+    }
 
     let collider_data: Vec<ColliderData> = world
         .get_objects_with_component_with_ids::<Collider>()
@@ -48,6 +158,10 @@ pub fn voxel_collision_system(world: &mut World) -> Result<()> {
         .collect();
 
     let registry = world.get_resource::<VoxelRegistry>()?.clone();
+    let settings = world.get_resource::<PhysicsSettings>()?.clone();
+    let mut touching = Vec::new();
+    // positions are relative to the world origin, voxels are absolute
+    let origin = world.origin_offset();
 
     for data in collider_data {
         let obj = world
@@ -57,111 +171,60 @@ pub fn voxel_collision_system(world: &mut World) -> Result<()> {
             break;
         }
 
-        let velocity_snapshot = world
+        let mut linear_velocity = world
             .get_object(data.id)
             .and_then(|o| o.get_component::<Velocity>().ok())
             .map(|v| v.linear_velocity)
             .unwrap_or(Vector3::zero());
 
-        let current_pos = data.position + velocity_snapshot * delta;
-
-        if let Some(obj) = world.get_object_mut(data.id) {
-            if let Ok(t) = obj.get_component_mut::<Transform>() {
-                t.local_position = current_pos;
-                t.global_position = current_pos;
-            }
-        }
-
-        let min = current_pos - data.half_extents;
-        let max = current_pos + data.half_extents;
-
-        let min_vox = Vector3::new(
-            min.x.floor() as i32 - 1,
-            min.y.floor() as i32 - 1,
-            min.z.floor() as i32 - 1,
-        );
-        let max_vox = Vector3::new(
-            max.x.ceil() as i32 + 1,
-            max.y.ceil() as i32 + 1,
-            max.z.ceil() as i32 + 1,
-        );
-
+        // every substep moves a fraction of the step and is pushed out of the voxels it enters,
+        // so fast objects can't pass through thin walls
+        let step = delta / settings.substeps.max(1) as f32;
+        let mut current_pos = data.position;
         let mut total_correction = Vector3::zero();
         let mut grounded = false;
+        for _ in 0..settings.substeps.max(1) {
+            current_pos += linear_velocity * step;
+
+            for _ in 0..settings.solver_iterations.max(1) {
+                let (correction, is_standing) = resolve_voxel_overlaps(
+                    world,
+                    &registry,
+                    origin,
+                    current_pos,
+                    data.half_extents,
+                );
+                grounded |= is_standing;
+                if correction == Vector3::zero() {
+                    break;
+                }
+                current_pos += correction;
+                total_correction += correction;
 
-        for vx in min_vox.x..max_vox.x {
-            for vy in min_vox.y..max_vox.y {
-                for vz in min_vox.z..max_vox.z {
-                    // get the voxel info
-                    let voxel_id = match world.get_voxel(vx, vy, vz) {
-                        Some(id) if id != 0 => id,
-                        _ => continue,
-                    };
-                    let def = match registry.get_def(voxel_id) {
-                        Ok(d) => d,
-                        Err(_) => continue,
-                    };
-
-                    // if its not a solid voxel return as it doesn't have collision
-                    if !def.has_component::<IsSolid>() {
-                        continue;
-                    }
-
-                    // calculate the extents of the object and voxel
-                    let pos = current_pos + total_correction;
-                    let cur_min = pos - data.half_extents;
-                    let cur_max = pos + data.half_extents;
-
-                    let vox_min = Vector3::new(vx as f32, vy as f32, vz as f32);
-                    let vox_max = vox_min + Vector3::new(1.0, 1.0, 1.0);
-
-                    // detects overlap between the object and voxels collider
-                    let overlap_x = (cur_max.x.min(vox_max.x) - cur_min.x.max(vox_min.x)).max(0.0);
-                    let overlap_y = (cur_max.y.min(vox_max.y) - cur_min.y.max(vox_min.y)).max(0.0);
-                    let overlap_z = (cur_max.z.min(vox_max.z) - cur_min.z.max(vox_min.z)).max(0.0);
-
-                    // detect if theres no overlap
-                    // FIX: setting the overlap_y to be <= 0.01 fixes an issue where you get stuck
-                    // on voxels, but it also breaks ground detection
-                    if overlap_x <= 0.0 || overlap_y <= 0.00 || overlap_z <= 0.0 {
-                        continue;
-                    }
-
-                    let vox_center = vox_min + Vector3::new(0.5, 0.5, 0.5);
-
-                    // collision correction maths, determines how far to push the object out of the
-                    // collider
-                    if overlap_y <= overlap_x && overlap_y <= overlap_z {
-                        if pos.y > vox_center.y {
-                            total_correction.y += overlap_y;
-                            let feet = pos.y - data.half_extents.y;
-
-                            // grounded detection 1
-                            if (feet - vox_max.y).abs() < 0.2 {
-                                grounded = true;
-                            }
-                        } else {
-                            total_correction.y -= overlap_y;
-                        }
-                    } else if overlap_x <= overlap_z {
-                        if pos.x > vox_center.x {
-                            total_correction.x += overlap_x;
-                        } else {
-                            total_correction.x -= overlap_x;
-                        }
-                    } else {
-                        if pos.z > vox_center.z {
-                            total_correction.z += overlap_z;
-                        } else {
-                            total_correction.z -= overlap_z;
-                        }
-                    }
+                // stop moving into what was hit for the rest of the step
+                if correction.x != 0.0 {
+                    linear_velocity.x = 0.0;
+                }
+                if (correction.y > 0.0 && linear_velocity.y < 0.0)
+                    || (correction.y < 0.0 && linear_velocity.y > 0.0)
+                {
+                    linear_velocity.y = 0.0;
+                }
+                if correction.z != 0.0 {
+                    linear_velocity.z = 0.0;
                 }
             }
         }
 
+        if let Some(obj) = world.get_object_mut(data.id)
+            && let Ok(t) = obj.get_component_mut::<Transform>()
+        {
+            t.local_position = current_pos;
+            t.global_position = current_pos;
+        }
+
         // Calculations for determing where the "feet" of an entity are
-        let feet_pos = current_pos + total_correction;
+        let feet_pos = current_pos;
         let feet_y = feet_pos.y - data.half_extents.y;
         let foot_vox_y = feet_y.floor() as i32 - 1;
 
@@ -173,7 +236,9 @@ pub fn voxel_collision_system(world: &mut World) -> Result<()> {
         // detect if the feet are on the ground
         for fx in foot_min_x..foot_max_x {
             for fz in foot_min_z..foot_max_z {
-                if let Some(voxel_id) = world.get_voxel(fx, foot_vox_y, fz) {
+                if let Some(voxel_id) =
+                    world.get_voxel(fx + origin.x, foot_vox_y + origin.y, fz + origin.z)
+                {
                     if voxel_id == 0 {
                         continue;
                     }
@@ -190,14 +255,12 @@ pub fn voxel_collision_system(world: &mut World) -> Result<()> {
             }
         }
 
+        if grounded || total_correction != Vector3::zero() {
+            touching.push(data.id);
+        }
+
         // resolve the colisions pushing the entity out of the ground/walls
         if let Some(obj) = world.get_object_mut(data.id) {
-            if total_correction != Vector3::zero() {
-                if let Ok(t) = obj.get_component_mut::<Transform>() {
-                    t.local_position += total_correction;
-                    t.global_position += total_correction;
-                }
-            }
             if let Ok(v) = obj.get_component_mut::<Velocity>() {
                 v.is_grounded = grounded;
                 if total_correction.y > 0.05 && v.linear_velocity.y < 0.05 {
@@ -217,6 +280,11 @@ pub fn voxel_collision_system(world: &mut World) -> Result<()> {
         }
     }
 
+    let contacts = world.get_resource_mut::<Contacts>()?;
+    for id in touching {
+        contacts.record(id, Contact::Voxels);
+    }
+
     Ok(())
 }
 
@@ -288,6 +356,9 @@ pub fn resolve_object_collisions(
             if !is_penetrating {
                 continue;
             }
+            if !flags.hit.contains(id) {
+                flags.hit.push(*id);
+            }
 
             if overlap.abs() > best_overlap.abs() {
                 best_overlap = overlap;
@@ -331,6 +402,9 @@ pub fn resolve_object_collisions(
 
 // #[update]
 pub fn resolve_object_collisions_system(world: &mut World) -> Result<()> {
+    if world.get_resource::<PhysicsSettings>()?.paused {
+        return Ok(());
+    }
     let delta = world.get_resource::<DeltaTime>()?.0;
 
     let collider_snapshot: Vec<(ObjectId, Vector3<f32>, Vector3<f32>)> = world
@@ -348,6 +422,7 @@ pub fn resolve_object_collisions_system(world: &mut World) -> Result<()> {
         })
         .collect();
 
+    let mut touching = Vec::new();
     let mut objects = world.get_objects_with_component_mut::<Collider>();
     for (i, object) in objects.iter_mut().enumerate() {
         let Ok(collider) = object.get_component::<Collider>() else {
@@ -387,6 +462,12 @@ pub fn resolve_object_collisions_system(world: &mut World) -> Result<()> {
         if flags.grounded {
             vel.is_grounded = true;
         }
+        touching.extend(flags.hit.iter().map(|other| (self_id, *other)));
+    }
+
+    let contacts = world.get_resource_mut::<Contacts>()?;
+    for (id, other) in touching {
+        contacts.record(id, Contact::Object(other));
     }
 
     Ok(())
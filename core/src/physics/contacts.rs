@@ -0,0 +1,94 @@
+use anyhow::Result;
+use apostasy_macros::{Resource, late_update};
+use hashbrown::HashSet;
+
+use crate::{
+    objects::{scene::ObjectId, world::World},
+    physics::physics_settings::PhysicsSettings,
+};
+
+/// What an object touched
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Contact {
+    Voxels,
+    Object(ObjectId),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionKind {
+    Enter,
+    Exit,
+}
+
+/// Sent when an object starts or stops touching something, read with `World::event_reader`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionEvent {
+    pub object: ObjectId,
+    pub other: Contact,
+    pub kind: CollisionKind,
+}
+
+/// The contacts found by the collision systems, compared every frame to send `CollisionEvent`s
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Contacts {
+    /// the contacts of the last frame
+    previous: HashSet<(ObjectId, Contact)>,
+    /// the contacts recorded so far this frame
+    current: HashSet<(ObjectId, Contact)>,
+}
+
+impl Contacts {
+    pub fn record(&mut self, object: ObjectId, other: Contact) {
+        self.current.insert((object, other));
+    }
+
+    /// Returns true if the object touched `other` last frame
+    pub fn is_touching(&self, object: ObjectId, other: Contact) -> bool {
+        self.previous.contains(&(object, other))
+    }
+
+    /// Gets what the object touched last frame
+    pub fn touching(&self, object: ObjectId) -> impl Iterator<Item = Contact> + '_ {
+        self.previous
+            .iter()
+            .filter(move |(id, _)| *id == object)
+            .map(|(_, other)| *other)
+    }
+
+    /// Compares this frame's contacts to last frame's and starts the next frame
+    fn finish(&mut self) -> Vec<CollisionEvent> {
+        let entered = self
+            .current
+            .difference(&self.previous)
+            .map(|(object, other)| (*object, *other, CollisionKind::Enter));
+        let exited = self
+            .previous
+            .difference(&self.current)
+            .map(|(object, other)| (*object, *other, CollisionKind::Exit));
+        let events = entered
+            .chain(exited)
+            .map(|(object, other, kind)| CollisionEvent {
+                object,
+                other,
+                kind,
+            })
+            .collect();
+
+        self.previous = std::mem::take(&mut self.current);
+        events
+    }
+}
+
+/// Sends the collision events of the frame
+#[late_update(priority = 10)]
+pub fn send_collision_events(world: &mut World) -> Result<()> {
+    // nothing records contacts while paused, keep them so nothing exits
+    if world.get_resource::<PhysicsSettings>()?.paused {
+        return Ok(());
+    }
+    let events = world.get_resource_mut::<Contacts>()?.finish();
+    if !events.is_empty() {
+        world.event_writer::<CollisionEvent>()?.send_batch(events);
+    }
+    Ok(())
+}
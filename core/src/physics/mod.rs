@@ -1,10 +1,18 @@
 use anyhow::Result;
 use apostasy_macros::{Component, fixed_update};
 
-use crate::{objects::world::World, physics::velocity::Velocity};
+use crate::{
+    objects::world::World,
+    physics::{
+        physics_settings::{PhysicsSettings, ScenePhysicsSettings},
+        velocity::Velocity,
+    },
+};
 
 pub mod collider;
 pub mod collision_system;
+pub mod contacts;
+pub mod physics_settings;
 pub mod velocity;
 
 #[derive(Component, Clone, Debug)]
@@ -25,6 +33,12 @@ impl Gravity {
 }
 #[fixed_update(priority = 10)]
 pub fn apply_gravity(world: &mut World, delta: f32) -> Result<()> {
+    let settings = world.get_resource::<PhysicsSettings>()?.clone();
+    if settings.paused {
+        return Ok(());
+    }
+    let gravity = settings.gravity(world.get_resource::<ScenePhysicsSettings>().ok());
+
     for object in world.get_objects_with_component_mut::<Velocity>() {
         let velocity = object.get_component_mut::<Velocity>()?;
         if velocity.is_grounded {
@@ -32,8 +46,8 @@ pub fn apply_gravity(world: &mut World, delta: f32) -> Result<()> {
                 velocity.linear_velocity.y = 0.0;
             }
         } else {
-            velocity.linear_velocity.y -= 9.8 * delta;
-            velocity.linear_velocity.y = velocity.linear_velocity.y.max(-50.0);
+            velocity.linear_velocity.y -= gravity * delta;
+            velocity.linear_velocity.y = velocity.linear_velocity.y.max(-settings.max_fall_speed);
         }
     }
     Ok(())
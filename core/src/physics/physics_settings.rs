@@ -0,0 +1,66 @@
+use anyhow::Result;
+use apostasy_macros::Resource;
+
+use crate::utils::yaml;
+
+/// The file the project's physics settings are persisted to
+pub const PHYSICS_SETTINGS_PATH: &str = "res/physics.yaml";
+
+/// The project's physics settings, loaded from and saved to `PHYSICS_SETTINGS_PATH`
+#[derive(Resource, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PhysicsSettings {
+    /// how many substeps each fixed update step is split into, more substeps stop fast objects
+    /// passing through voxels
+    pub substeps: u32,
+    /// how many times each step is pushed out of voxels, more iterations settle stacked
+    /// overlaps instead of jittering
+    pub solver_iterations: u32,
+    /// the downwards acceleration in units per second squared
+    pub gravity: f32,
+    /// the fastest an object can fall in units per second
+    pub max_fall_speed: f32,
+    /// stops gravity, velocity and collisions so objects can be arranged in the editor
+    /// Note: not saved, physics always starts running
+    #[serde(skip)]
+    pub paused: bool,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            substeps: 1,
+            solver_iterations: 1,
+            gravity: 9.8,
+            max_fall_speed: 50.0,
+            paused: false,
+        }
+    }
+}
+
+impl PhysicsSettings {
+    /// Loads the settings from disk, falling back to the defaults if they are missing or invalid
+    pub fn load() -> Self {
+        yaml::load_or_default(PHYSICS_SETTINGS_PATH)
+    }
+
+    /// The gravity to simulate, the scene's gravity if it overrides the project's
+    pub fn gravity(&self, scene: Option<&ScenePhysicsSettings>) -> f32 {
+        scene
+            .and_then(|scene| scene.gravity)
+            .unwrap_or(self.gravity)
+    }
+
+    /// Saves the settings to `PHYSICS_SETTINGS_PATH`
+    pub fn save(&self) -> Result<()> {
+        yaml::save(PHYSICS_SETTINGS_PATH, self)
+    }
+}
+
+/// The physics settings of the loaded scene, saved with its objects by `World::save_objects`
+#[derive(Resource, Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ScenePhysicsSettings {
+    /// replaces `PhysicsSettings::gravity` while the scene is loaded
+    pub gravity: Option<f32>,
+}
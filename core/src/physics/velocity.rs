@@ -1,13 +1,23 @@
 use anyhow::Result;
-use apostasy_macros::{Component,  update};
+use apostasy_macros::{Component, fixed_update};
 use cgmath::{Vector3, Zero};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     log,
-    objects::{components::transform::Transform, systems::DeltaTime, tags::Player, world::World},
+    objects::{
+        components::transform::Transform,
+        systems::params::{Query, Res},
+        tags::Player,
+        world::World,
+    },
+    physics::physics_settings::PhysicsSettings,
 };
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[component_group("physics")]
+#[serde(default)]
 pub struct Velocity {
     pub angular_velocity: Vector3<f32>,
     pub linear_velocity: Vector3<f32>,
@@ -28,25 +38,23 @@ impl Default for Velocity {
     }
 }
 
-impl Velocity {
-    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> anyhow::Result<()> {
-        Ok(())
+/// Moves the objects by their velocity each fixed update step, add an `InterpolatedTransform` to
+/// draw them smoothly between steps
+#[fixed_update]
+fn velocity_process(
+    delta: f32,
+    settings: Res<PhysicsSettings>,
+    mut objects: Query<(&Velocity, &mut Transform)>,
+) -> Result<()> {
+    if settings.paused {
+        return Ok(());
     }
-}
-#[update]
-fn velocity_process(world: &mut World) -> Result<()> {
-    let delta = world.get_resource::<DeltaTime>()?.0;
 
-    for node in world.get_objects_with_component_mut::<Velocity>() {
-        if !node.get_component::<Velocity>()?.process {
+    for (velocity, transform) in objects.iter() {
+        if !velocity.process {
             continue;
         }
-        // if node.get_component::<Collider>().is_ok() {
-        //     continue;
-        // }
-        let linear = node.get_component::<Velocity>()?.linear_velocity;
-        let transform = node.get_component_mut::<Transform>()?;
-        transform.local_position += linear * delta;
+        transform.local_position += velocity.linear_velocity * delta;
     }
     Ok(())
 }
@@ -0,0 +1,89 @@
+// The supported user facing surface of the engine
+// Note: `use apostasy_core::prelude::*;` should be enough to write most systems
+
+// ========== ========== Core ========== ==========
+pub use crate::{
+    Core, init_core, init_core_with_plugins,
+    packages::{Packages, Plugin},
+    rendering::RenderingBackend,
+};
+pub use anyhow::{Result, anyhow};
+
+// ========== ========== Objects ========== ==========
+pub use crate::objects::{
+    Object,
+    bundle::Bundle,
+    commands::Commands,
+    component::Component,
+    events::{EventReader, EventWriter, Events},
+    extract::{ExtractedFrom, WorldId, WorldObjectId},
+    object_ref::{ObjectMut, ObjectRef},
+    observers::{OnInsert, OnRemove},
+    pool::{ObjectPool, PoolSource},
+    prefab::{Prefab, PrefabOverrides},
+    query::{QueryCursor, With, WithTag, Without, WithoutTag},
+    resource::{FromWorld, Resource, ResourceError},
+    scene::{ObjectId, ObjectIdExt, Scene},
+    snapshot::SceneLoaded,
+    tag::Tag,
+    world::World,
+};
+pub use apostasy_macros::{Bundle, Component, Resource, Tag};
+
+// ========== ========== Systems ========== ==========
+pub use crate::objects::systems::{
+    DeltaTime, EngineTimer, FixedTime, Time,
+    params::{NonSend, NonSendMut, Query, Res, ResMut},
+};
+pub use apostasy_macros::{
+    console_command, fixed_update, game_ui, late_update, shutdown, start, system, update,
+};
+
+// ========== ========== Components ========== ==========
+pub use crate::minimap::MapMarker;
+pub use crate::objects::components::{interpolation::InterpolatedTransform, transform::Transform};
+pub use crate::objects::tags::Player;
+pub use crate::physics::{collider::Collider, velocity::Velocity};
+pub use crate::rendering::components::{
+    camera::{ActiveCamera, Camera, EditorCamera, GameCamera},
+    camera_controller::{FpsCameraController, OrbitCameraController},
+    model_renderer::ModelRenderer,
+    sockets::{ModelSocket, ModelSockets, SocketAttachment},
+};
+pub use crate::scripting::{Script, Scripts};
+pub use crate::sequencer::SequencePlayer;
+
+// ========== ========== Resources ========== ==========
+pub use crate::console::{Console, RuntimeCommand};
+pub use crate::minimap::Minimap;
+pub use crate::net::voxel_sync::{VoxelSync, VoxelSyncRole};
+pub use crate::net::{Delivery, Net, NetMessage, PeerId};
+pub use crate::objects::resources::{
+    action_map::{Action, ActionMap, Binding, EDITOR_CONTEXT, GAMEPLAY_CONTEXT, UI_CONTEXT},
+    cursor_manager::{CursorGrab, CursorLockMode, CursorManager, CursorState},
+    gamepad::GamepadConnection,
+    input_manager::{
+        AxisBind, GamepadBind, InputManager, KeyAction, KeyBind, KeyBindMode, MouseBind, TextInput,
+    },
+    window_manager::{Monitor, PRIMARY_WINDOW, ScaleFactorChanged, VideoMode, WindowManager},
+    window_settings::{WindowMode, WindowSettings},
+};
+pub use crate::objects::scene_lock::SceneLock;
+pub use crate::origin::WorldOrigin;
+pub use crate::physics::contacts::{CollisionEvent, CollisionKind, Contact, Contacts};
+pub use crate::physics::physics_settings::{PhysicsSettings, ScenePhysicsSettings};
+pub use crate::safe_mode::SafeMode;
+pub use crate::states::{AppExit, GameState, State, States, pause::PauseSettings, resource_exists};
+pub use crate::ui::{game_ui::HudStyle, ui_context::EguiContext};
+pub use crate::utils::rng::{RngStream, WorldRng};
+pub use crate::weather::{Weather, WeatherPreset};
+
+// ========== ========== Math ========== ==========
+pub use crate::math::{
+    Aabb, Deg, EuclideanSpace, FORWARD, InnerSpace, Matrix3, Matrix4, Plane, Point3, Quaternion,
+    RIGHT, Rad, Ray, Rotation, Rotation3, SquareMatrix, UP, Vector2, Vector3, Vector4, Zero,
+    deg_to_rad, inverse_lerp, lerp, look_rotation, move_towards, rad_to_deg, slerp,
+};
+
+// ========== ========== Logging ========== ==========
+pub use crate::{log, log_error, log_warn};
@@ -0,0 +1,191 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    path::Path,
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+use anyhow::Result;
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// How many finished frames are kept for the profiler panel and exports
+const FRAME_HISTORY: usize = 240;
+
+static PROFILER: OnceLock<Mutex<Profiler>> = OnceLock::new();
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+thread_local! {
+    /// the paths of the scopes open on this thread, innermost last
+    static OPEN_SCOPES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn profiler() -> &'static Mutex<Profiler> {
+    PROFILER.get_or_init(|| Mutex::new(Profiler::default()))
+}
+
+/// The time spent in one scope over a frame
+/// Note: `path` joins the names of the scopes it was opened in with `/`, e.g. `Update/ai/pathing`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProfileScope {
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    pub calls: u32,
+    pub total_ms: f32,
+}
+
+/// Everything recorded between two calls to `end_frame`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProfileFrame {
+    pub frame: u64,
+    /// scopes in the order they were first opened, so children follow their parents
+    pub scopes: Vec<ProfileScope>,
+    /// counters added to this frame, they start at 0 each frame
+    pub counters: BTreeMap<String, i64>,
+    /// the last value of every gauge, they keep their value between frames
+    pub gauges: BTreeMap<String, f64>,
+}
+
+#[derive(Default)]
+struct Profiler {
+    frame: ProfileFrame,
+    scope_index: HashMap<String, usize>,
+    history: VecDeque<ProfileFrame>,
+}
+
+/// Times everything until it is dropped, made by `scope`
+pub struct ProfileGuard {
+    start: Option<Instant>,
+}
+
+impl Drop for ProfileGuard {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+        let elapsed_ms = start.elapsed().as_secs_f32() * 1000.0;
+        let Some(path) = OPEN_SCOPES.with(|scopes| scopes.borrow_mut().pop()) else {
+            return;
+        };
+
+        let mut profiler = profiler().lock();
+        let profiler = &mut *profiler;
+        match profiler.scope_index.get(&path) {
+            Some(&index) => {
+                let scope = &mut profiler.frame.scopes[index];
+                scope.calls += 1;
+                scope.total_ms += elapsed_ms;
+            }
+            None => {
+                let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+                let depth = path.matches('/').count();
+                profiler
+                    .scope_index
+                    .insert(path.clone(), profiler.frame.scopes.len());
+                profiler.frame.scopes.push(ProfileScope {
+                    path,
+                    name,
+                    depth,
+                    calls: 1,
+                    total_ms: elapsed_ms,
+                });
+            }
+        }
+    }
+}
+
+/// Times a scope until the returned guard is dropped, nested scopes show under it
+/// e.g. `let _scope = profiler::scope("ai");`
+/// Note: the name can't contain `/`, it separates the scopes of a path
+pub fn scope(name: &str) -> ProfileGuard {
+    if !is_enabled() {
+        return ProfileGuard { start: None };
+    }
+    OPEN_SCOPES.with(|scopes| {
+        let mut scopes = scopes.borrow_mut();
+        let path = match scopes.last() {
+            Some(parent) => format!("{}/{}", parent, name),
+            None => name.to_string(),
+        };
+        scopes.push(path);
+    });
+    ProfileGuard {
+        start: Some(Instant::now()),
+    }
+}
+
+/// Adds to a counter of the current frame, e.g. `profiler::counter("ai.paths", 1)`
+pub fn counter(name: &str, amount: i64) {
+    if !is_enabled() {
+        return;
+    }
+    *profiler()
+        .lock()
+        .frame
+        .counters
+        .entry(name.to_string())
+        .or_insert(0) += amount;
+}
+
+/// Sets a gauge, it keeps its value until it is set again
+pub fn gauge(name: &str, value: f64) {
+    if !is_enabled() {
+        return;
+    }
+    profiler()
+        .lock()
+        .frame
+        .gauges
+        .insert(name.to_string(), value);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Turns recording on or off, scopes are free while it is off
+pub fn set_enabled(is_enabled: bool) {
+    ENABLED.store(is_enabled, Ordering::Relaxed);
+}
+
+/// Finishes the current frame and starts the next one
+/// Note: called by the engine after late update, games don't need to call it
+pub fn end_frame() {
+    let mut profiler = profiler().lock();
+    let next = ProfileFrame {
+        frame: profiler.frame.frame + 1,
+        gauges: profiler.frame.gauges.clone(),
+        ..Default::default()
+    };
+    let finished = std::mem::replace(&mut profiler.frame, next);
+    profiler.scope_index.clear();
+    if profiler.history.len() == FRAME_HISTORY {
+        profiler.history.pop_front();
+    }
+    profiler.history.push_back(finished);
+}
+
+/// The last finished frame, none before the first frame ends
+pub fn last_frame() -> Option<ProfileFrame> {
+    profiler().lock().history.back().cloned()
+}
+
+/// The finished frames that are kept, oldest first
+pub fn history() -> Vec<ProfileFrame> {
+    profiler().lock().history.iter().cloned().collect()
+}
+
+/// Writes the kept frames to a yaml file
+pub fn export(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_yaml::to_string(&history())?)?;
+    Ok(())
+}
@@ -1,14 +1,25 @@
 use apostasy_macros::{Component, Tag};
-use cgmath::{Deg, Matrix4, PerspectiveFov, Point3};
+use cgmath::{Deg, Matrix4, PerspectiveFov, Point3, ortho};
+use serde::{Deserialize, Serialize};
 
 use crate::objects::components::transform::Transform;
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
 pub struct Camera {
+    #[inspect(range = 1.0..=179.0, label = "Field of View")]
     pub fov_y: f32,
     pub near: f32,
     pub far: f32,
     pub is_main: bool,
+    /// draws without perspective, see `ortho_size`
+    pub is_orthographic: bool,
+    /// half the height of the view in units while orthographic
+    pub ortho_size: f32,
+    /// the label of the window it draws into, empty for the primary window, see
+    /// `WindowManager::request_window`
+    pub window: String,
 }
 
 impl Default for Camera {
@@ -18,16 +29,13 @@ impl Default for Camera {
             near: 0.001,
             far: 10000.0,
             is_main: false,
+            is_orthographic: false,
+            ortho_size: 20.0,
+            window: String::new(),
         }
     }
 }
 
-impl Camera {
-    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> anyhow::Result<()> {
-        Ok(())
-    }
-}
-
 pub fn get_perspective_projection(camera: &Camera, aspect: f32) -> Matrix4<f32> {
     let mut proj: Matrix4<f32> = PerspectiveFov::to_perspective(&PerspectiveFov {
         fovy: Deg(camera.fov_y).into(),
@@ -42,6 +50,33 @@ pub fn get_perspective_projection(camera: &Camera, aspect: f32) -> Matrix4<f32>
     proj
 }
 
+pub fn get_orthographic_projection(camera: &Camera, aspect: f32) -> Matrix4<f32> {
+    let half_height = camera.ortho_size;
+    let half_width = half_height * aspect;
+    // orthographic views also show what is just behind the camera, like most editors
+    let mut proj = ortho(
+        -half_width,
+        half_width,
+        -half_height,
+        half_height,
+        -camera.far,
+        camera.far,
+    );
+
+    proj[1][1] *= -1.0;
+
+    proj
+}
+
+/// Gets the perspective or orthographic projection of a camera
+pub fn get_projection(camera: &Camera, aspect: f32) -> Matrix4<f32> {
+    if camera.is_orthographic {
+        get_orthographic_projection(camera, aspect)
+    } else {
+        get_perspective_projection(camera, aspect)
+    }
+}
+
 pub fn get_view_matrix(transform: &Transform) -> Matrix4<f32> {
     let eye = Point3::new(
         transform.global_position.x,
@@ -0,0 +1,268 @@
+use anyhow::Result;
+use apostasy_macros::{Component, update};
+use cgmath::{Vector2, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    math::{Ray, euler_forward},
+    objects::{
+        components::transform::Transform, resources::input_manager::InputManager,
+        systems::DeltaTime, world::World,
+    },
+    physics::velocity::Velocity,
+    voxels::{voxel::VoxelRegistry, voxel_raycast::raycast_raw},
+};
+
+/// How far an orbit camera stays in front of the voxel it hits
+const ORBIT_COLLISION_MARGIN: f32 = 0.2;
+
+/// A first person camera, the mouse and the `LookLeft`, `LookRight`, `LookUp` and `LookDown`
+/// actions turn it and `Left`, `Right`, `Forwards` and `Backwards` move it through its `Velocity`
+/// Note: with `yaw_parent` the parent turns left and right and moves, the camera only looks up
+/// and down, for a camera on a player body
+/// Note: actions that are not registered are skipped
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
+pub struct FpsCameraController {
+    /// degrees turned per unit the mouse moves
+    pub sensitivity: f32,
+    /// degrees per second the look actions turn
+    pub look_speed: f32,
+    /// units per second
+    pub speed: f32,
+    /// the furthest it can look up or down in degrees
+    #[inspect(range = 0.0..=90.0)]
+    pub pitch_clamp: f32,
+    /// moves where it looks and up and down with `Upwards` and `Downwards`, otherwise it moves
+    /// flat and leaves the vertical velocity to gravity
+    pub fly: bool,
+    pub yaw_parent: bool,
+    pub enabled: bool,
+}
+
+impl Default for FpsCameraController {
+    fn default() -> Self {
+        Self {
+            sensitivity: 1.0,
+            look_speed: 300.0,
+            speed: 5.0,
+            pitch_clamp: 89.0,
+            fly: false,
+            yaw_parent: false,
+            enabled: true,
+        }
+    }
+}
+
+/// A camera that turns around a point, dragging with `orbit_button` held turns it and scrolling
+/// zooms
+/// Note: it sets its own position and rotation, so it should not have a parent
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
+pub struct OrbitCameraController {
+    /// the point it turns around
+    pub focus: Vector3<f32>,
+    /// the name of an object to follow, its position is used as the focus
+    pub target: Option<String>,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// degrees turned per unit the mouse moves
+    pub sensitivity: f32,
+    /// units zoomed per scroll step
+    pub zoom_speed: f32,
+    /// the furthest it can look up or down in degrees
+    #[inspect(range = 0.0..=90.0)]
+    pub pitch_clamp: f32,
+    /// the mousebind held to turn, turns all the time if it is not registered
+    pub orbit_button: String,
+    /// moves in front of voxels between the focus and the camera
+    pub collide: bool,
+    pub enabled: bool,
+}
+
+impl Default for OrbitCameraController {
+    fn default() -> Self {
+        Self {
+            focus: Vector3::new(0.0, 0.0, 0.0),
+            target: None,
+            distance: 10.0,
+            min_distance: 1.0,
+            max_distance: 100.0,
+            sensitivity: 1.0,
+            zoom_speed: 1.0,
+            pitch_clamp: 89.0,
+            orbit_button: "Orbit".to_string(),
+            collide: true,
+            enabled: true,
+        }
+    }
+}
+
+/// Checks an action without the warning for ones that are not registered
+fn action(inputs: &InputManager, name: &str) -> bool {
+    inputs.keybinds.contains_key(name) && inputs.is_keybind_active(name)
+}
+
+fn action_axis(inputs: &InputManager, positive: &str, negative: &str) -> f32 {
+    action(inputs, positive) as i32 as f32 - action(inputs, negative) as i32 as f32
+}
+
+#[update]
+pub fn fps_camera_controller(world: &mut World) -> Result<()> {
+    let delta = world.get_resource::<DeltaTime>()?.0;
+    let inputs = world.get_resource::<InputManager>()?;
+    let mouse = Vector2::new(inputs.mouse_delta.0 as f32, inputs.mouse_delta.1 as f32);
+    let look = Vector2::new(
+        action_axis(inputs, "LookLeft", "LookRight"),
+        action_axis(inputs, "LookUp", "LookDown"),
+    );
+    let direction = Vector3::new(
+        action_axis(inputs, "Right", "Left"),
+        action_axis(inputs, "Upwards", "Downwards"),
+        action_axis(inputs, "Backwards", "Forwards"),
+    );
+
+    let controllers: Vec<_> = world
+        .get_objects_with_component_with_ids::<FpsCameraController>()
+        .into_iter()
+        .filter_map(|(id, object)| {
+            let controller = object.get_component::<FpsCameraController>().ok()?;
+            controller
+                .enabled
+                .then(|| (id, controller.clone(), object.parent))
+        })
+        .collect();
+
+    for (id, controller, parent) in controllers {
+        let yaw = look.x * controller.look_speed * delta - mouse.x * controller.sensitivity;
+        let pitch = look.y * controller.look_speed * delta - mouse.y * controller.sensitivity;
+        let body = match (controller.yaw_parent, parent) {
+            (true, Some(parent)) => parent,
+            _ => id,
+        };
+
+        let Some(transform) = world
+            .get_object_mut(id)
+            .and_then(|camera| camera.get_component_mut::<Transform>().ok())
+        else {
+            continue;
+        };
+        transform.local_euler_angles.x = (transform.local_euler_angles.x + pitch)
+            .clamp(-controller.pitch_clamp, controller.pitch_clamp);
+        let pitch = transform.local_euler_angles.x;
+
+        let Some(body) = world.get_object_mut(body) else {
+            continue;
+        };
+        let Ok(transform) = body.get_component_mut::<Transform>() else {
+            continue;
+        };
+        transform.local_euler_angles.y += yaw;
+        let mut angles = transform.local_euler_angles;
+        if let Ok(velocity) = body.get_component_mut::<Velocity>() {
+            if controller.fly {
+                angles.x = pitch;
+                let forward = euler_forward(angles);
+                let right = euler_forward(Vector3::new(0.0, angles.y - 90.0, 0.0));
+                let up = right.cross(forward);
+                velocity.linear_velocity = (right * direction.x + up * direction.y
+                    - forward * direction.z)
+                    * controller.speed;
+            } else {
+                let forward = euler_forward(Vector3::new(0.0, angles.y, 0.0));
+                let right = euler_forward(Vector3::new(0.0, angles.y - 90.0, 0.0));
+                let wish = (right * direction.x - forward * direction.z) * controller.speed;
+                velocity.linear_velocity.x = wish.x;
+                velocity.linear_velocity.z = wish.z;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[update]
+pub fn orbit_camera_controller(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+    let mouse = Vector2::new(inputs.mouse_delta.0 as f32, inputs.mouse_delta.1 as f32);
+    let scroll = if inputs.pointer_captured {
+        0.0
+    } else {
+        inputs.scroll_delta.1
+    };
+    let held = |button: &str| {
+        !inputs.mouse_keybinds.contains_key(button) || inputs.is_mousebind_active(button)
+    };
+
+    let controllers: Vec<_> = world
+        .get_objects_with_component_with_ids::<OrbitCameraController>()
+        .into_iter()
+        .filter_map(|(id, object)| {
+            let controller = object.get_component::<OrbitCameraController>().ok()?;
+            controller
+                .enabled
+                .then(|| (id, controller.clone(), held(&controller.orbit_button)))
+        })
+        .collect();
+    if controllers.is_empty() {
+        return Ok(());
+    }
+    let chunk_map = world.build_raw_chunk_lookup();
+
+    for (id, mut controller, turning) in controllers {
+        if let Some(target) = &controller.target
+            && let Some(target) = world.find_by_name(target)
+            && let Some(object) = world.get_object(target)
+            && let Ok(transform) = object.get_component::<Transform>()
+        {
+            controller.focus = transform.global_position;
+        }
+        controller.distance = (controller.distance - scroll * controller.zoom_speed)
+            .clamp(controller.min_distance, controller.max_distance);
+
+        let Some(mut angles) = world
+            .get_object(id)
+            .and_then(|camera| camera.get_component::<Transform>().ok())
+            .map(|transform| transform.local_euler_angles)
+        else {
+            continue;
+        };
+        if turning {
+            angles.y -= mouse.x * controller.sensitivity;
+            angles.x = (angles.x - mouse.y * controller.sensitivity)
+                .clamp(-controller.pitch_clamp, controller.pitch_clamp);
+        }
+        let forward = euler_forward(angles);
+
+        // pulls in to the first voxel between the focus and where the camera would be
+        let mut distance = controller.distance;
+        if controller.collide
+            && let Ok(registry) = world.get_resource::<VoxelRegistry>()
+            && let Some(hit) = raycast_raw(
+                &Ray::new(controller.focus, -forward),
+                distance,
+                &chunk_map,
+                None,
+                registry,
+                world.origin_offset(),
+            )
+        {
+            distance = (hit.distance - ORBIT_COLLISION_MARGIN).max(controller.min_distance);
+        }
+
+        let Some(camera) = world.get_object_mut(id) else {
+            continue;
+        };
+        let transform = camera.get_component_mut::<Transform>()?;
+        transform.local_euler_angles = angles;
+        transform.local_position = controller.focus - forward * distance;
+        let orbit = camera.get_component_mut::<OrbitCameraController>()?;
+        orbit.focus = controller.focus;
+        orbit.distance = controller.distance;
+    }
+
+    Ok(())
+}
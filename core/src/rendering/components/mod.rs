@@ -1,2 +1,4 @@
 pub mod camera;
+pub mod camera_controller;
 pub mod model_renderer;
+pub mod sockets;
@@ -1,18 +1,22 @@
 use apostasy_macros::Component;
+use serde::{Deserialize, Serialize};
 
 use crate::rendering::shared::model::GpuModel;
 
-#[derive(Component, Default, Clone, Debug)]
+#[derive(Component, Default, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[component_group("render")]
+#[serde(default)]
 pub struct ModelRenderer {
+    /// loaded from model_path when first drawn
+    #[serde(skip)]
     pub model: Option<Box<GpuModel>>,
+    /// a glTF model or a MagicaVoxel `.vox` model
     pub model_path: String,
     pub is_wireframe: bool,
 }
 
 impl ModelRenderer {
-    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> anyhow::Result<()> {
-        Ok(())
-    }
     pub fn from_path(path: &str) -> Self {
         let path = format!("{}{}", "res/", path.to_string());
 
@@ -0,0 +1,209 @@
+use anyhow::{Result, anyhow};
+use apostasy_macros::{Component, update};
+use cgmath::{InnerSpace, Matrix3, Matrix4, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    math::euler_angles_from_matrix,
+    objects::{components::transform::Transform, scene::ObjectId, world::World},
+    rendering::components::model_renderer::ModelRenderer,
+};
+
+/// A named point on a model that other objects can be attached to, e.g. `hand_r` or `muzzle`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelSocket {
+    pub name: String,
+    /// relative to the model
+    pub position: Vector3<f32>,
+    /// in degrees, in the same order as `Transform::local_euler_angles`
+    pub euler_angles: Vector3<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Default for ModelSocket {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            position: Vector3::new(0.0, 0.0, 0.0),
+            euler_angles: Vector3::new(0.0, 0.0, 0.0),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// The sockets of a model, read from the empties of its glTF or authored in the editor
+#[derive(Component, Default, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
+pub struct ModelSockets {
+    pub sockets: Vec<ModelSocket>,
+}
+
+impl ModelSockets {
+    /// Reads the sockets of a glTF, every node without a mesh or camera is a socket
+    /// Note: only the document is read, buffers and images are not loaded
+    pub fn from_gltf(path: &str) -> Result<Self> {
+        let gltf =
+            gltf::Gltf::open(path).map_err(|e| anyhow!("Failed to open model {}: {}", path, e))?;
+        let Some(scene) = gltf
+            .document
+            .default_scene()
+            .or_else(|| gltf.document.scenes().next())
+        else {
+            return Ok(Self::default());
+        };
+
+        let mut sockets = Vec::new();
+        let mut stack: Vec<(gltf::Node, Matrix4<f32>)> = scene
+            .nodes()
+            .map(|node| (node, Matrix4::from_scale(1.0)))
+            .collect();
+        while let Some((node, parent)) = stack.pop() {
+            let matrix = parent * Matrix4::from(node.transform().matrix());
+            if node.mesh().is_none()
+                && node.camera().is_none()
+                && let Some(name) = node.name()
+            {
+                sockets.push(socket_from_matrix(name, matrix));
+            }
+            stack.extend(node.children().map(|child| (child, matrix)));
+        }
+        sockets.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self { sockets })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ModelSocket> {
+        self.sockets.iter().find(|socket| socket.name == name)
+    }
+}
+
+/// Splits a node matrix into a socket
+/// Note: shear can't be represented and is dropped
+fn socket_from_matrix(name: &str, matrix: Matrix4<f32>) -> ModelSocket {
+    let scale = Vector3::new(
+        matrix.x.truncate().magnitude(),
+        matrix.y.truncate().magnitude(),
+        matrix.z.truncate().magnitude(),
+    );
+    let axis = |column: Vector3<f32>, length: f32| {
+        if length > f32::EPSILON {
+            column / length
+        } else {
+            column
+        }
+    };
+    let rotation = Matrix3::from_cols(
+        axis(matrix.x.truncate(), scale.x),
+        axis(matrix.y.truncate(), scale.y),
+        axis(matrix.z.truncate(), scale.z),
+    );
+    ModelSocket {
+        name: name.to_string(),
+        position: matrix.w.truncate(),
+        euler_angles: euler_angles_from_matrix(rotation),
+        scale,
+    }
+}
+
+/// Keeps an object on a socket of its parent, added by `World::attach_to_socket`
+#[derive(Component, Default, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
+pub struct SocketAttachment {
+    pub socket: String,
+}
+
+impl World {
+    /// Parents an object to a socket of a model, e.g. a sword to `hand_r`
+    /// Note: the sockets are read from the model's glTF the first time, if it has no
+    /// `ModelSockets` yet
+    pub fn attach_to_socket(
+        &mut self,
+        child: ObjectId,
+        model: ObjectId,
+        socket: &str,
+    ) -> Result<()> {
+        let object = self
+            .get_object(model)
+            .ok_or_else(|| anyhow!("Failed to attach to socket {}: model is missing", socket))?;
+        if !object.has_component::<ModelSockets>() {
+            let sockets = match object.get_component::<ModelRenderer>() {
+                Ok(renderer) => ModelSockets::from_gltf(&renderer.model_path)?,
+                Err(_) => ModelSockets::default(),
+            };
+            self.get_object_mut(model)
+                .ok_or_else(|| anyhow!("Failed to attach to socket {}: model is missing", socket))?
+                .add_component(sockets);
+        }
+
+        let found = self
+            .get_object(model)
+            .and_then(|object| object.get_component::<ModelSockets>().ok())
+            .and_then(|sockets| sockets.get(socket))
+            .cloned()
+            .ok_or_else(|| anyhow!("Model has no socket named {}", socket))?;
+
+        self.set_parent(child, Some(model))?;
+        let object = self
+            .get_object_mut(child)
+            .ok_or_else(|| anyhow!("Failed to attach to socket {}: object is missing", socket))?;
+        object.remove_component::<SocketAttachment>();
+        object.add_component(SocketAttachment {
+            socket: socket.to_string(),
+        });
+        if !object.has_component::<Transform>() {
+            object.add_component(Transform::default());
+        }
+        if let Ok(transform) = object.get_component_mut::<Transform>() {
+            place_on_socket(transform, &found);
+        }
+        Ok(())
+    }
+
+    /// Takes an object off its socket, it stays where it is relative to the model
+    pub fn detach_from_socket(&mut self, child: ObjectId) {
+        if let Some(object) = self.get_object_mut(child) {
+            object.remove_component::<SocketAttachment>();
+        }
+    }
+}
+
+fn place_on_socket(transform: &mut Transform, socket: &ModelSocket) {
+    transform.local_position = socket.position;
+    transform.local_euler_angles = socket.euler_angles;
+    transform.local_scale = socket.scale;
+}
+
+/// Moves attached objects onto their sockets before transforms are propagated, so they follow
+/// sockets that are moved in the editor or by gameplay
+#[update(before = "transform_update", while_paused = true)]
+pub fn socket_update(world: &mut World) -> Result<()> {
+    let attached: Vec<(ObjectId, ObjectId, String)> = world
+        .get_objects_with_component_with_ids::<SocketAttachment>()
+        .into_iter()
+        .filter_map(|(id, object)| {
+            let socket = object.get_component::<SocketAttachment>().ok()?;
+            Some((id, object.parent?, socket.socket.clone()))
+        })
+        .collect();
+
+    for (id, parent, socket) in attached {
+        let Some(found) = world
+            .get_object(parent)
+            .and_then(|object| object.get_component::<ModelSockets>().ok())
+            .and_then(|sockets| sockets.get(&socket))
+            .cloned()
+        else {
+            continue;
+        };
+        if let Some(transform) = world
+            .get_object_mut(id)
+            .and_then(|object| object.get_component_mut::<Transform>().ok())
+        {
+            place_on_socket(transform, &found);
+        }
+    }
+
+    Ok(())
+}
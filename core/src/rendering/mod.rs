@@ -1,15 +1,21 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use ash::vk::{self, CommandPool};
-use egui::Context;
+use egui::{Context, TextureId};
 use winit::event::WindowEvent;
-use winit::{event_loop::ActiveEventLoop, window::Window};
+use winit::{
+    event_loop::ActiveEventLoop,
+    window::{Window, WindowAttributes, WindowId},
+};
 
 use crate::rendering::shared::model::GpuMesh;
 use crate::rendering::shared::push_constants::{
-    ModelPushConstants, PushConstants, VoxelPushConstants,
+    GridPushConstants, ModelPushConstants, OutlinePushConstants, ParticlePushConstants,
+    PushConstants, VoxelPushConstants,
 };
+use crate::rendering::shared::rendering_config::{RenderingConfig, RenderingStats};
 use crate::rendering::{
     shared::rendering_settings::RenderingSettings,
     vulkan::{
@@ -37,6 +43,8 @@ pub struct RenderingInfo {
     pub window: Arc<Window>,
     pub settings: RenderingSettings,
     pub renderer: Option<Box<dyn RenderingAPI>>,
+    /// the renderers of the windows opened after the primary one, they share the context
+    pub window_renderers: HashMap<WindowId, Box<dyn RenderingAPI>>,
     pub push_constants: PushConstants,
     pub model_push_constants: ModelPushConstants,
     pub voxel_push_constants: VoxelPushConstants,
@@ -77,6 +85,49 @@ pub trait RenderingAPI {
         voxel_push_constants: &VoxelPushConstants,
     ) -> Result<()>;
 
+    /// Draws `count` gpu simulated particles around the camera
+    fn particle_render(
+        &mut self,
+        push_constants: &PushConstants,
+        particle_push_constants: &ParticlePushConstants,
+    ) -> Result<()>;
+
+    /// Draws the editor grid on a plane under the camera
+    fn grid_render(
+        &mut self,
+        push_constants: &PushConstants,
+        grid_push_constants: &GridPushConstants,
+    ) -> Result<()>;
+
+    /// Darkens creases in what has been drawn so far with screen space ambient occlusion
+    /// Note: does nothing unless enabled in the rendering config and supported by the renderer
+    fn ambient_occlusion_render(&mut self, push_constants: &PushConstants) -> Result<()>;
+
+    /// Queues a mesh to be outlined by `outline_render`
+    fn outline_mesh(
+        &mut self,
+        mesh: Box<dyn GpuMesh>,
+        push_constants: &PushConstants,
+        model_push_constants: &ModelPushConstants,
+    );
+    /// Draws the outline around the meshes queued this frame over what has been drawn so far
+    /// Note: does nothing if no mesh was queued or the outline shaders could not be loaded
+    fn outline_render(&mut self, outline_push_constants: &OutlinePushConstants) -> Result<()>;
+
+    /// Requests the minimap this frame, drawn into a square of `size` pixels cleared to a colour
+    fn begin_map(&mut self, size: u32, clear_color: [f32; 4]);
+    /// Queues a chunk or water mesh for the minimap, ignored if it wasn't requested this frame
+    fn map_render(
+        &mut self,
+        mesh: Box<dyn GpuMesh>,
+        atlas: &VoxelTextureAtlas,
+        push_constants: &PushConstants,
+        voxel_push_constants: &VoxelPushConstants,
+        is_water: bool,
+    );
+    /// Gets the egui texture the minimap is drawn into, none until it has been drawn
+    fn map_texture(&self) -> Option<TextureId>;
+
     fn begin_ui(&mut self);
     fn end_ui(&mut self) -> Result<()>;
     fn handle_ui_event(&mut self, event: &WindowEvent) -> bool;
@@ -91,6 +142,33 @@ pub trait RenderingAPI {
     fn get_aspect(&self) -> f32;
     fn get_descriptor_pool(&self) -> vk::DescriptorPool;
     fn get_voxel_descriptor_set_layout(&self) -> vk::DescriptorSetLayout;
+    fn get_material_descriptor_pool(&self) -> vk::DescriptorPool;
+    fn get_material_descriptor_set_layout(&self) -> vk::DescriptorSetLayout;
+
+    /// Applies the user facing rendering config, swapchain changes happen on the next frame
+    fn apply_config(&mut self, config: &RenderingConfig);
+    /// Gets the statistics of the last recorded frame
+    fn get_stats(&self) -> RenderingStats;
+    /// Sets the colour frames are cleared to, applies from the next frame
+    fn set_clear_color(&mut self, color: [f32; 4]);
+
+    /// Requests the id under a pixel, the id pass only runs on frames with a request
+    /// Returns the frame slot the pick is recorded in, its result is tagged with the same slot
+    fn request_pick(&mut self, x: u32, y: u32) -> usize;
+    /// Queues a mesh for the id pass, ignored if no pick was requested this frame
+    fn id_render(
+        &mut self,
+        mesh: Box<dyn GpuMesh>,
+        push_constants: &PushConstants,
+        model_push_constants: &ModelPushConstants,
+        id: u32,
+    );
+    /// Takes the frame slot and id read back from the last finished pick, 0 means nothing was
+    /// drawn at the pixel
+    fn take_pick_result(&mut self) -> Option<(usize, u32)>;
+    /// Frees what the renderer made for its window, the swapchain, surface and pipelines
+    /// Note: waits for the device to be idle, the rendering context and the meshes are kept
+    fn destroy(&mut self);
     /// Assigns the rendering_info's renderer the the value created via this
     fn new(rendering_info: Arc<Mutex<RenderingInfo>>, window: Arc<Window>) -> Result<()>
     where
@@ -98,18 +176,27 @@ pub trait RenderingAPI {
 }
 
 impl RenderingInfo {
-    pub fn new(event_loop: &ActiveEventLoop, rendering_api: RenderingBackend) -> Arc<Mutex<Self>> {
-        let window = Arc::new(event_loop.create_window(Default::default()).unwrap());
+    /// Opens the window with the attributes, e.g. `WindowSettings::attributes`, and creates the
+    /// renderer on the preferred adapter, see `pick_adapter`
+    pub fn new(
+        event_loop: &ActiveEventLoop,
+        rendering_api: RenderingBackend,
+        preferred_gpu: Option<String>,
+        attributes: WindowAttributes,
+    ) -> Arc<Mutex<Self>> {
+        let window = Arc::new(event_loop.create_window(attributes).unwrap());
 
         let rendering_info = Arc::new(Mutex::new(RenderingInfo {
             context: VulkanRenderingContext::new(RenderingContextAttributes {
                 compatability_window: &window,
                 queue_family_picker: queue_family_picker::single_queue_family,
+                preferred_gpu,
             })
             .unwrap(),
             window: window.clone(),
             settings: RenderingSettings::default(),
             renderer: None,
+            window_renderers: HashMap::new(),
             push_constants: PushConstants::default(),
             voxel_push_constants: VoxelPushConstants::default(),
             model_push_constants: ModelPushConstants::default(),
@@ -126,4 +213,19 @@ impl RenderingInfo {
 
         rendering_info
     }
+
+    /// Creates a renderer for another window on the context of the primary one
+    pub fn add_window(&mut self, window: Arc<Window>) -> Result<()> {
+        let renderer = VulkanRenderer::create(self, window.clone())?;
+        self.window_renderers
+            .insert(window.id(), Box::new(renderer));
+        Ok(())
+    }
+
+    /// Destroys the renderer of a window opened with `add_window`, the context is kept
+    pub fn remove_window(&mut self, window_id: WindowId) {
+        if let Some(mut renderer) = self.window_renderers.remove(&window_id) {
+            renderer.destroy();
+        }
+    }
 }
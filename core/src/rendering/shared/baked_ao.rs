@@ -0,0 +1,282 @@
+use std::path::Path;
+
+use anyhow::Result;
+use apostasy_macros::{Component, Resource};
+use cgmath::{ElementWise, InnerSpace, Rotation, Vector3};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::gltf::load_geometry,
+    log,
+    math::{Aabb, Ray},
+    objects::{components::transform::Transform, scene::ObjectId, tags::Static, world::World},
+    rendering::components::model_renderer::ModelRenderer,
+    utils::rng::{DEFAULT_SEED, RngStream},
+};
+
+/// How far rays start off the surface, keeps a vertex from occluding itself
+const SURFACE_BIAS: f32 = 0.001;
+
+/// The ambient occlusion of every vertex of a static model, made by `World::bake_ambient_occlusion`
+/// and saved with the scene
+/// Note: the bake is dropped when the model changes, bake again after moving static objects
+#[derive(Component, Default, Clone, Debug, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
+pub struct BakedAmbientOcclusion {
+    /// the occlusion of each vertex, per mesh in the order the model loads them
+    pub meshes: Vec<Vec<f32>>,
+}
+
+/// How `World::bake_ambient_occlusion` bakes, a resource so the editor keeps its choice
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct AoBakeSettings {
+    /// rays cast from each vertex
+    pub samples: u32,
+    /// how far away something still occludes a vertex
+    pub max_distance: f32,
+}
+
+impl Default for AoBakeSettings {
+    fn default() -> Self {
+        Self {
+            samples: 64,
+            max_distance: 2.0,
+        }
+    }
+}
+
+struct Triangle {
+    a: Vector3<f32>,
+    b: Vector3<f32>,
+    c: Vector3<f32>,
+    bounds: Aabb,
+}
+
+impl Triangle {
+    fn new(a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>) -> Self {
+        let min = Vector3::new(
+            a.x.min(b.x).min(c.x),
+            a.y.min(b.y).min(c.y),
+            a.z.min(b.z).min(c.z),
+        );
+        let max = Vector3::new(
+            a.x.max(b.x).max(c.x),
+            a.y.max(b.y).max(c.y),
+            a.z.max(b.z).max(c.z),
+        );
+        Self {
+            a,
+            b,
+            c,
+            bounds: Aabb::new(min, max),
+        }
+    }
+
+    /// Returns true if the ray hits the triangle within max_distance, Möller–Trumbore
+    fn is_hit(&self, ray: &Ray, max_distance: f32) -> bool {
+        if !self
+            .bounds
+            .intersect_ray(ray)
+            .is_some_and(|distance| distance <= max_distance)
+        {
+            return false;
+        }
+
+        let edge1 = self.b - self.a;
+        let edge2 = self.c - self.a;
+        let p = ray.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() < f32::EPSILON {
+            return false;
+        }
+
+        let inverse = 1.0 / determinant;
+        let s = ray.origin - self.a;
+        let u = s.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return false;
+        }
+        let q = s.cross(edge1);
+        let v = ray.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return false;
+        }
+
+        let distance = edge2.dot(q) * inverse;
+        distance > 0.0 && distance <= max_distance
+    }
+}
+
+/// A mesh of a static model moved into the world
+struct BakeMesh {
+    positions: Vec<Vector3<f32>>,
+    normals: Vec<Vector3<f32>>,
+}
+
+/// Gets cosine weighted directions around +z, the same ones are used for every vertex so bakes
+/// are repeatable
+fn hemisphere_samples(count: u32) -> Vec<Vector3<f32>> {
+    let mut rng = RngStream::new(DEFAULT_SEED);
+    (0..count)
+        .map(|_| {
+            let radius = rng.unit().sqrt();
+            let angle = rng.range(0.0..std::f32::consts::TAU);
+            let x = radius * angle.cos();
+            let y = radius * angle.sin();
+            Vector3::new(x, y, (1.0 - x * x - y * y).max(0.0).sqrt())
+        })
+        .collect()
+}
+
+/// Gets how much of the sky a vertex sees, 1 is unoccluded
+fn vertex_occlusion(
+    position: Vector3<f32>,
+    normal: Vector3<f32>,
+    samples: &[Vector3<f32>],
+    triangles: &[Triangle],
+    max_distance: f32,
+) -> f32 {
+    if samples.is_empty() || normal.magnitude2() < f32::EPSILON {
+        return 1.0;
+    }
+
+    let helper = if normal.x.abs() < 0.9 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    let tangent = normal.cross(helper).normalize();
+    let bitangent = normal.cross(tangent);
+    let origin = position + normal * SURFACE_BIAS;
+
+    let hits = samples
+        .iter()
+        .filter(|sample| {
+            let ray = Ray::new(
+                origin,
+                tangent * sample.x + bitangent * sample.y + normal * sample.z,
+            );
+            triangles
+                .iter()
+                .any(|triangle| triangle.is_hit(&ray, max_distance))
+        })
+        .count();
+    1.0 - hits as f32 / samples.len() as f32
+}
+
+impl World {
+    /// Ray traces the ambient occlusion of every `Static` object with a model, each vertex is
+    /// occluded by every static model, returns how many objects were baked
+    /// Note: the models are reloaded with the occlusion the next time they are drawn
+    pub fn bake_ambient_occlusion(&mut self, settings: &AoBakeSettings) -> Result<usize> {
+        let targets: Vec<(ObjectId, String, Transform)> = self
+            .get_objects_with_tag_with_ids::<Static>()
+            .into_iter()
+            .filter_map(|(id, object)| {
+                let model = object.get_component::<ModelRenderer>().ok()?;
+                let transform = object.get_component::<Transform>().ok()?;
+                Some((id, model.model_path.clone(), transform.clone()))
+            })
+            .collect();
+
+        let mut objects = Vec::new();
+        for (id, path, transform) in targets {
+            let meshes = load_geometry(Path::new(&path))?
+                .into_iter()
+                .map(|geometry| {
+                    let scale = transform.global_scale;
+                    let rotation = transform.global_rotation;
+                    BakeMesh {
+                        positions: geometry
+                            .positions
+                            .iter()
+                            .map(|p| {
+                                rotation.rotate_vector(Vector3::from(*p).mul_element_wise(scale))
+                                    + transform.global_position
+                            })
+                            .collect(),
+                        // the inverse scale keeps normals perpendicular, as in shader.vert
+                        normals: geometry
+                            .normals
+                            .iter()
+                            .map(|n| {
+                                let normal = rotation
+                                    .rotate_vector(Vector3::from(*n).div_element_wise(scale));
+                                if normal.magnitude2() > f32::EPSILON {
+                                    normal.normalize()
+                                } else {
+                                    normal
+                                }
+                            })
+                            .collect(),
+                    }
+                    .with_triangles(&geometry.indices)
+                })
+                .collect::<Vec<_>>();
+            objects.push((id, meshes));
+        }
+
+        let triangles: Vec<Triangle> = objects
+            .iter_mut()
+            .flat_map(|(_, meshes)| meshes.iter_mut())
+            .flat_map(|(_, triangles)| std::mem::take(triangles))
+            .collect();
+        let samples = hemisphere_samples(settings.samples);
+
+        let baked = objects.len();
+        for (id, meshes) in objects {
+            let occlusion: Vec<Vec<f32>> = meshes
+                .iter()
+                .map(|(mesh, _)| {
+                    mesh.positions
+                        .par_iter()
+                        .zip(mesh.normals.par_iter())
+                        .map(|(position, normal)| {
+                            vertex_occlusion(
+                                *position,
+                                *normal,
+                                &samples,
+                                &triangles,
+                                settings.max_distance,
+                            )
+                        })
+                        .collect()
+                })
+                .collect();
+
+            if let Some(object) = self.get_object_mut(id) {
+                object.remove_component::<BakedAmbientOcclusion>();
+                object.add_component(BakedAmbientOcclusion { meshes: occlusion });
+                if let Ok(model) = object.get_component_mut::<ModelRenderer>() {
+                    model.model = None;
+                }
+            }
+        }
+
+        log!(
+            "Baked ambient occlusion of {} objects against {} triangles",
+            baked,
+            triangles.len()
+        );
+        Ok(baked)
+    }
+}
+
+impl BakeMesh {
+    /// Pairs the mesh with its triangles, indices past the end of the mesh are skipped
+    fn with_triangles(self, indices: &[u32]) -> (Self, Vec<Triangle>) {
+        let triangles = indices
+            .chunks_exact(3)
+            .filter_map(|face| {
+                Some(Triangle::new(
+                    *self.positions.get(face[0] as usize)?,
+                    *self.positions.get(face[1] as usize)?,
+                    *self.positions.get(face[2] as usize)?,
+                ))
+            })
+            .collect();
+        (self, triangles)
+    }
+}
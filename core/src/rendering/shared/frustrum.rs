@@ -1,8 +1,10 @@
 use apostasy_macros::Resource;
 use cgmath::{Matrix4, Vector3, Vector4};
 
+use crate::math::{Aabb, Plane};
+
 pub struct Frustum {
-    planes: [Vector4<f32>; 6],
+    planes: [Plane; 6],
 }
 
 impl Frustum {
@@ -13,34 +15,33 @@ impl Frustum {
         let r2 = Vector4::new(vp.x.z, vp.y.z, vp.z.z, vp.w.z);
         let r3 = Vector4::new(vp.x.w, vp.y.w, vp.z.w, vp.w.w);
 
-        let mut planes = [
-            r3 + r0, // left
-            r3 - r0, // right
-            r3 + r1, // bottom
-            r3 - r1, // top
-            r3 + r2, // near
-            r3 - r2, // far
+        let planes = [
+            Plane::from_coefficients(r3 + r0), // left
+            Plane::from_coefficients(r3 - r0), // right
+            Plane::from_coefficients(r3 + r1), // bottom
+            Plane::from_coefficients(r3 - r1), // top
+            Plane::from_coefficients(r3 + r2), // near
+            Plane::from_coefficients(r3 - r2), // far
         ];
 
-        // Normalize each plane by its xyz length
-        for plane in &mut planes {
-            let len = (plane.x * plane.x + plane.y * plane.y + plane.z * plane.z).sqrt();
-            plane.x /= len;
-            plane.y /= len;
-            plane.z /= len;
-            plane.w /= len;
-        }
-
         Self { planes }
     }
 
     pub fn contains_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> bool {
-        for plane in &self.planes {
-            let px = if plane.x >= 0.0 { max.x } else { min.x };
-            let py = if plane.y >= 0.0 { max.y } else { min.y };
-            let pz = if plane.z >= 0.0 { max.z } else { min.z };
+        self.contains(&Aabb::new(min, max))
+    }
 
-            if plane.x * px + plane.y * py + plane.z * pz + plane.w < 0.0 {
+    pub fn contains(&self, aabb: &Aabb) -> bool {
+        for plane in &self.planes {
+            // the corner furthest along the plane normal
+            let furthest = |normal: f32, min: f32, max: f32| if normal >= 0.0 { max } else { min };
+            let corner = Vector3::new(
+                furthest(plane.normal.x, aabb.min.x, aabb.max.x),
+                furthest(plane.normal.y, aabb.min.y, aabb.max.y),
+                furthest(plane.normal.z, aabb.min.z, aabb.max.z),
+            );
+
+            if plane.signed_distance(corner) < 0.0 {
                 return false;
             }
         }
@@ -0,0 +1,112 @@
+use std::fmt;
+
+use apostasy_macros::Resource;
+
+use crate::log_warn;
+
+/// What kind of device a graphics adapter is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpuKind {
+    Discrete,
+    Integrated,
+    Virtual,
+    Cpu,
+    Other,
+}
+
+impl GpuKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GpuKind::Discrete => "Discrete",
+            GpuKind::Integrated => "Integrated",
+            GpuKind::Virtual => "Virtual",
+            GpuKind::Cpu => "CPU",
+            GpuKind::Other => "Other",
+        }
+    }
+
+    /// Lower is picked first when no adapter is preferred
+    fn rank(&self) -> u32 {
+        match self {
+            GpuKind::Discrete => 0,
+            GpuKind::Integrated => 1,
+            GpuKind::Virtual => 2,
+            GpuKind::Other => 3,
+            GpuKind::Cpu => 4,
+        }
+    }
+}
+
+/// A graphics adapter that can draw to the window
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GpuInfo {
+    pub name: String,
+    pub kind: GpuKind,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub driver_version: String,
+    /// the newest graphics api version the driver supports
+    pub api_version: String,
+    /// the memory on the device, shared memory for integrated adapters
+    pub memory_bytes: u64,
+}
+
+impl fmt::Display for GpuInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, driver {}, api {})",
+            self.name,
+            self.kind.label(),
+            self.driver_version,
+            self.api_version
+        )
+    }
+}
+
+/// Every adapter that can draw to the window and the one the renderer uses
+/// Note: the adapter is chosen when the window opens, see `RenderingConfig::preferred_gpu`
+#[derive(Resource, Clone, Debug, Default)]
+pub struct GpuAdapters {
+    pub adapters: Vec<GpuInfo>,
+    /// the index of the adapter in use
+    pub active: usize,
+}
+
+impl GpuAdapters {
+    pub fn active(&self) -> Option<&GpuInfo> {
+        self.adapters.get(self.active)
+    }
+}
+
+/// Reads `--gpu <name or index>` from the command line
+pub fn gpu_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--gpu")
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+/// Picks the adapter to use, the preferred one matched by its index or part of its name ignoring
+/// case, otherwise the first discrete adapter, then integrated, virtual and finally software ones
+/// returns None if there are no adapters
+pub fn pick_adapter(adapters: &[GpuInfo], preferred: Option<&str>) -> Option<usize> {
+    if let Some(preferred) = preferred {
+        let by_index = preferred
+            .parse::<usize>()
+            .ok()
+            .filter(|index| *index < adapters.len());
+        let lowercase = preferred.to_lowercase();
+        let by_name = adapters
+            .iter()
+            .position(|adapter| adapter.name.to_lowercase().contains(&lowercase));
+        match by_index.or(by_name) {
+            Some(index) => return Some(index),
+            None => {
+                log_warn!("No GPU matches '{}', picking one", preferred);
+            }
+        }
+    }
+    (0..adapters.len()).min_by_key(|index| adapters[*index].kind.rank())
+}
@@ -0,0 +1,36 @@
+use apostasy_macros::Resource;
+
+/// An infinite ground grid drawn under the scene, fading out with distance
+/// Note: the grid only renders while this resource exists, the editor inserts it
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct EditorGrid {
+    pub is_visible: bool,
+    /// the size of a cell in units
+    pub cell_size: f32,
+    /// every nth line is drawn brighter
+    pub major_every: u32,
+    /// the height of the grid plane
+    pub height: f32,
+    /// the distance from the camera the grid fades out by
+    pub fade_distance: f32,
+    /// draws faint lines every `snap_size` units when set
+    pub snap_size: Option<f32>,
+    pub color: [f32; 4],
+    /// draws the world axes in the corner of the viewport
+    pub show_axes_gizmo: bool,
+}
+
+impl Default for EditorGrid {
+    fn default() -> Self {
+        Self {
+            is_visible: true,
+            cell_size: 1.0,
+            major_every: 10,
+            height: 0.0,
+            fade_distance: 150.0,
+            snap_size: None,
+            color: [0.6, 0.6, 0.6, 0.8],
+            show_axes_gizmo: true,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use ash::vk;
+
+/// The textures of a material in binding order
+pub const MATERIAL_TEXTURE_COUNT: usize = 5;
+pub const BASE_COLOR_BINDING: usize = 0;
+pub const NORMAL_BINDING: usize = 1;
+pub const METALLIC_ROUGHNESS_BINDING: usize = 2;
+pub const EMISSIVE_BINDING: usize = 3;
+pub const OCCLUSION_BINDING: usize = 4;
+
+/// A glTF metallic-roughness material
+/// Missing textures are bound to a 1x1 texture that leaves the factor unchanged
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub name: String,
+    /// multiplied with the base colour texture
+    pub base_color_factor: [f32; 4],
+    /// multiplied with the blue channel of the metallic-roughness texture
+    pub metallic_factor: f32,
+    /// multiplied with the green channel of the metallic-roughness texture
+    pub roughness_factor: f32,
+    /// multiplied with the emissive texture
+    pub emissive_factor: [f32; 3],
+    /// scales the x and y of the normal map
+    pub normal_scale: f32,
+    /// how much of the occlusion texture is applied, 0 to 1
+    pub occlusion_strength: f32,
+    /// the base colour, normal, metallic-roughness, emissive and occlusion textures
+    pub descriptor_set: vk::DescriptorSet,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            name: "material".to_string(),
+            base_color_factor: [1.0, 1.0, 1.0, 1.0],
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            emissive_factor: [0.0, 0.0, 0.0],
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            descriptor_set: vk::DescriptorSet::null(),
+        }
+    }
+}
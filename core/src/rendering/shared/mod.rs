@@ -1,7 +1,16 @@
+pub mod baked_ao;
 pub mod culling;
 pub mod frustrum;
+pub mod gpu;
+pub mod grid;
+pub mod material;
 pub mod model;
+pub mod outline;
+pub mod picking;
 pub mod push_constants;
+pub mod released_buffers;
+pub mod rendering_config;
 pub mod rendering_settings;
 pub mod texture;
 pub mod vertex;
+pub mod windows;
@@ -1,5 +1,7 @@
 use ash::vk::{Buffer, DeviceMemory};
 
+use crate::rendering::shared::material::Material;
+
 #[derive(Clone, Debug)]
 pub struct GpuModel {
     pub meshes: Vec<Mesh>,
@@ -13,7 +15,7 @@ pub struct Mesh {
     pub index_buffer: Buffer,
     pub index_buffer_memory: DeviceMemory,
     pub index_count: u32,
-    pub material_name: String,
+    pub material: Material,
 }
 
 impl GpuMesh for Mesh {
@@ -26,10 +28,17 @@ impl GpuMesh for Mesh {
     fn get_index_count(&self) -> u32 {
         self.index_count
     }
+    fn get_material(&self) -> Option<&Material> {
+        Some(&self.material)
+    }
 }
 
 pub trait GpuMesh {
     fn get_vertex_buffer(&self) -> Buffer;
     fn get_index_buffer(&self) -> Buffer;
     fn get_index_count(&self) -> u32;
+    /// Gets the material models are shaded with, voxel meshes use the atlas instead
+    fn get_material(&self) -> Option<&Material> {
+        None
+    }
 }
@@ -0,0 +1,30 @@
+use apostasy_macros::Resource;
+
+use crate::objects::scene::ObjectId;
+
+/// An outline drawn around the meshes of an object and its children, over everything else
+/// Note: the outline only renders while this resource exists, the editor inserts it and sets
+/// `object` to the selection
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct SelectionOutline {
+    pub is_visible: bool,
+    pub object: Option<ObjectId>,
+    pub color: [f32; 4],
+    /// the width of the outline in pixels
+    pub thickness: f32,
+}
+
+impl SelectionOutline {
+    pub const MAX_THICKNESS: f32 = 8.0;
+}
+
+impl Default for SelectionOutline {
+    fn default() -> Self {
+        Self {
+            is_visible: true,
+            object: None,
+            color: [1.0, 0.6, 0.1, 1.0],
+            thickness: 2.0,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use apostasy_macros::Resource;
+
+use crate::objects::scene::ObjectId;
+
+/// The result of a finished pick
+#[derive(Clone, Copy, Debug)]
+pub struct PickResult {
+    pub pixel: (u32, u32),
+    /// the object under the pixel, none if nothing was drawn there
+    pub object: Option<ObjectId>,
+}
+
+/// Pixel accurate object picking using the renderer's id pass
+/// Note: results arrive once the frame that drew the pick has finished, up to the number of
+/// frames in flight after the request, call `pick` every frame to track hovering
+#[derive(Resource, Clone, Default)]
+pub struct ObjectPicking {
+    /// the pixel (in physical window coordinates) to pick on the next frame
+    pub request: Option<(u32, u32)>,
+    /// the last finished pick, take it to consume it
+    pub result: Option<PickResult>,
+    /// the pixel and objects drawn by the id pass still waiting for readback, per frame slot
+    pub(crate) pending: Vec<Option<((u32, u32), Vec<ObjectId>)>>,
+}
+
+impl ObjectPicking {
+    /// Requests the object under a pixel
+    pub fn pick(&mut self, x: u32, y: u32) {
+        self.request = Some((x, y));
+    }
+
+    /// Keeps the objects the id pass drew in a frame slot until its id is read back
+    pub(crate) fn queue(&mut self, frame: usize, pixel: (u32, u32), ids: Vec<ObjectId>) {
+        if self.pending.len() <= frame {
+            self.pending.resize(frame + 1, None);
+        }
+        self.pending[frame] = Some((pixel, ids));
+    }
+
+    /// Resolves a raw id read back from the id pass against the objects drawn in the same frame
+    /// slot, 0 means nothing was drawn
+    pub(crate) fn resolve(&mut self, frame: usize, raw_id: u32) {
+        let Some((pixel, ids)) = self.pending.get_mut(frame).and_then(Option::take) else {
+            return;
+        };
+        let object = raw_id
+            .checked_sub(1)
+            .and_then(|index| ids.get(index as usize).copied());
+        self.result = Some(PickResult { pixel, object });
+    }
+}
@@ -1,10 +1,14 @@
 use std::mem::transmute;
 
-use cgmath::{Matrix4, Quaternion, SquareMatrix, Vector3, Zero};
+use cgmath::{Matrix4, Quaternion, SquareMatrix, Vector3, Vector4, Zero};
 
 use crate::{
     objects::{Object, components::transform::Transform},
-    rendering::components::camera::{Camera, get_perspective_projection, get_view_matrix},
+    rendering::{
+        components::camera::{Camera, get_projection, get_view_matrix},
+        shared::{grid::EditorGrid, material::Material, outline::SelectionOutline},
+    },
+    weather::{Fog, ParticleEmitter},
 };
 
 #[derive(Clone, Debug)]
@@ -41,7 +45,7 @@ impl PushConstants {
         let transform = camera.get_component::<Transform>().unwrap();
         let cam = camera.get_component::<Camera>().unwrap();
         self.view_matrix = get_view_matrix(transform);
-        self.projection_matrix = get_perspective_projection(cam, aspect);
+        self.projection_matrix = get_projection(cam, aspect);
         self.model_matrix = Matrix4::identity();
     }
 }
@@ -87,6 +91,8 @@ pub struct VoxelPushConstants {
     pub atlas_tiles: u32, // how many tiles per row in the atlas
     pub world_position: Vector3<i32>,
     pub time: f32,
+    pub fog_color: Vector3<f32>,
+    pub fog_density: f32,
 }
 
 impl Default for VoxelPushConstants {
@@ -95,6 +101,8 @@ impl Default for VoxelPushConstants {
             atlas_tiles: 1,
             world_position: Vector3::zero(),
             time: 0.0,
+            fog_color: Vector3::zero(),
+            fog_density: 0.0,
         }
     }
 }
@@ -103,19 +111,28 @@ impl VoxelPushConstants {
     #[allow(unnecessary_transmutes)]
     pub fn return_renderable(&self) -> Vec<u8> {
         unsafe {
-            let mut data = Vec::with_capacity(28);
+            let mut data = Vec::with_capacity(48);
             let atlas: [u8; 4] = transmute(self.atlas_tiles);
             let pad: [u8; 12] = [0u8; 12];
             let position: [u8; 12] = transmute(self.world_position);
             let time: [u8; 4] = transmute(self.time);
+            let fog_color: [u8; 12] = transmute(self.fog_color);
+            let fog_density: [u8; 4] = transmute(self.fog_density);
             data.extend_from_slice(&atlas);
             data.extend_from_slice(&pad);
             data.extend_from_slice(&position);
             data.extend_from_slice(&time);
-            data // 32 bytes total
+            data.extend_from_slice(&fog_color);
+            data.extend_from_slice(&fog_density);
+            data // 48 bytes total
         }
     }
 
+    pub fn set_fog(&mut self, fog: &Fog) {
+        self.fog_color = Vector3::from(fog.color);
+        self.fog_density = fog.density;
+    }
+
     pub fn set_position(&mut self, position: Vector3<i32>) {
         self.world_position = position;
     }
@@ -124,3 +141,185 @@ impl VoxelPushConstants {
         self.atlas_tiles = tiles;
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct ParticlePushConstants {
+    /// the centre of the particle box
+    pub camera_position: Vector3<f32>,
+    pub time: f32,
+    pub area: Vector3<f32>,
+    pub size: f32,
+    pub velocity: Vector3<f32>,
+    pub streak: f32,
+    pub color: Vector4<f32>,
+    /// the amount of particles to draw, not pushed
+    pub count: u32,
+}
+
+impl ParticlePushConstants {
+    pub fn new(emitter: &ParticleEmitter, camera_position: Vector3<f32>, time: f32) -> Self {
+        Self {
+            camera_position,
+            time,
+            area: Vector3::from(emitter.area),
+            size: emitter.size,
+            velocity: Vector3::from(emitter.velocity),
+            streak: emitter.streak,
+            color: Vector4::from(emitter.color),
+            count: emitter.count,
+        }
+    }
+
+    #[allow(unnecessary_transmutes)]
+    pub fn return_renderable(&self) -> Vec<u8> {
+        unsafe {
+            let mut data = Vec::with_capacity(64);
+            let camera_position: [u8; 12] = transmute(self.camera_position);
+            let time: [u8; 4] = transmute(self.time);
+            let area: [u8; 12] = transmute(self.area);
+            let size: [u8; 4] = transmute(self.size);
+            let velocity: [u8; 12] = transmute(self.velocity);
+            let streak: [u8; 4] = transmute(self.streak);
+            let color: [u8; 16] = transmute(self.color);
+            data.extend_from_slice(&camera_position);
+            data.extend_from_slice(&time);
+            data.extend_from_slice(&area);
+            data.extend_from_slice(&size);
+            data.extend_from_slice(&velocity);
+            data.extend_from_slice(&streak);
+            data.extend_from_slice(&color);
+            data // 64 bytes total
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GridPushConstants {
+    /// the grid is centred under the camera
+    pub camera_position: Vector3<f32>,
+    pub cell_size: f32,
+    pub height: f32,
+    pub fade_distance: f32,
+    pub major_every: f32,
+    /// 0 disables the snap lines
+    pub snap_size: f32,
+    pub color: Vector4<f32>,
+}
+
+impl GridPushConstants {
+    pub fn new(grid: &EditorGrid, camera_position: Vector3<f32>) -> Self {
+        Self {
+            camera_position,
+            cell_size: grid.cell_size.max(0.001),
+            height: grid.height,
+            fade_distance: grid.fade_distance,
+            major_every: grid.major_every.max(1) as f32,
+            snap_size: grid.snap_size.unwrap_or(0.0).max(0.0),
+            color: Vector4::from(grid.color),
+        }
+    }
+
+    #[allow(unnecessary_transmutes)]
+    pub fn return_renderable(&self) -> Vec<u8> {
+        unsafe {
+            let mut data = Vec::with_capacity(48);
+            let camera_position: [u8; 12] = transmute(self.camera_position);
+            let cell_size: [u8; 4] = transmute(self.cell_size);
+            let height: [u8; 4] = transmute(self.height);
+            let fade_distance: [u8; 4] = transmute(self.fade_distance);
+            let major_every: [u8; 4] = transmute(self.major_every);
+            let snap_size: [u8; 4] = transmute(self.snap_size);
+            let color: [u8; 16] = transmute(self.color);
+            data.extend_from_slice(&camera_position);
+            data.extend_from_slice(&cell_size);
+            data.extend_from_slice(&height);
+            data.extend_from_slice(&fade_distance);
+            data.extend_from_slice(&major_every);
+            data.extend_from_slice(&snap_size);
+            data.extend_from_slice(&color);
+            data // 48 bytes total
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OutlinePushConstants {
+    pub color: Vector4<f32>,
+    pub thickness: f32,
+}
+
+impl OutlinePushConstants {
+    pub fn new(outline: &SelectionOutline) -> Self {
+        Self {
+            color: Vector4::from(outline.color),
+            thickness: outline
+                .thickness
+                .clamp(1.0, SelectionOutline::MAX_THICKNESS),
+        }
+    }
+
+    #[allow(unnecessary_transmutes)]
+    pub fn return_renderable(&self) -> Vec<u8> {
+        unsafe {
+            let mut data = Vec::with_capacity(32);
+            let color: [u8; 16] = transmute(self.color);
+            let thickness: [u8; 4] = transmute(self.thickness);
+            data.extend_from_slice(&color);
+            data.extend_from_slice(&thickness);
+            data.extend_from_slice(&[0u8; 12]);
+            data // 32 bytes total
+        }
+    }
+}
+
+/// The factors of a material, pushed to the model fragment shader after ModelPushConstants
+#[derive(Clone, Debug)]
+pub struct MaterialPushConstants {
+    pub base_color_factor: Vector4<f32>,
+    pub emissive_factor: Vector3<f32>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+    /// used for the view direction of the specular highlights
+    pub camera_position: Vector3<f32>,
+}
+
+impl MaterialPushConstants {
+    pub fn new(material: &Material, camera_position: Vector3<f32>) -> Self {
+        Self {
+            base_color_factor: Vector4::from(material.base_color_factor),
+            emissive_factor: Vector3::from(material.emissive_factor),
+            metallic_factor: material.metallic_factor,
+            roughness_factor: material.roughness_factor,
+            normal_scale: material.normal_scale,
+            occlusion_strength: material.occlusion_strength,
+            camera_position,
+        }
+    }
+
+    #[allow(unnecessary_transmutes)]
+    pub fn return_renderable(&self) -> Vec<u8> {
+        unsafe {
+            let mut data = Vec::with_capacity(64);
+            let base_color: [u8; 16] = transmute(self.base_color_factor);
+            let emissive: [u8; 12] = transmute(self.emissive_factor);
+            let metallic: [u8; 4] = transmute(self.metallic_factor);
+            let roughness: [u8; 4] = transmute(self.roughness_factor);
+            let normal_scale: [u8; 4] = transmute(self.normal_scale);
+            let occlusion: [u8; 4] = transmute(self.occlusion_strength);
+            let camera_position: [u8; 12] = transmute(self.camera_position);
+            let pad: [u8; 4] = [0u8; 4];
+            data.extend_from_slice(&base_color);
+            data.extend_from_slice(&emissive);
+            data.extend_from_slice(&metallic);
+            data.extend_from_slice(&roughness);
+            data.extend_from_slice(&normal_scale);
+            data.extend_from_slice(&occlusion);
+            data.extend_from_slice(&pad);
+            data.extend_from_slice(&camera_position);
+            data.extend_from_slice(&pad);
+            data // 64 bytes total
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use apostasy_macros::Resource;
+use ash::vk::{self, Buffer, DeviceMemory};
+
+/// Buffers whose owner was removed, handed to the renderer to be destroyed once the frames using
+/// them are done
+/// Note: filled by `#[component_on_remove]` hooks, which can't reach the renderer
+#[derive(Resource, Clone, Default)]
+pub struct ReleasedBuffers(pub Vec<(Buffer, DeviceMemory)>);
+
+impl ReleasedBuffers {
+    pub fn release(&mut self, buffer: Buffer, memory: DeviceMemory) {
+        if buffer != vk::Buffer::null() {
+            self.0.push((buffer, memory));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use apostasy_macros::Resource;
+use ash::vk;
+
+use crate::utils::yaml;
+
+/// The file user facing rendering choices are persisted to
+pub const RENDERING_CONFIG_PATH: &str = "res/rendering.yaml";
+
+/// How frames are presented to the window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PresentMode {
+    /// waits for vertical blank, never tears
+    Vsync,
+    /// replaces queued frames with newer ones, falls back to vsync if unsupported
+    #[default]
+    Mailbox,
+    /// presents as soon as possible, may tear, falls back to vsync if unsupported
+    Immediate,
+}
+
+impl PresentMode {
+    pub const ALL: [PresentMode; 3] = [
+        PresentMode::Vsync,
+        PresentMode::Mailbox,
+        PresentMode::Immediate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PresentMode::Vsync => "VSync",
+            PresentMode::Mailbox => "Mailbox",
+            PresentMode::Immediate => "Immediate",
+        }
+    }
+
+    pub fn to_vk(&self) -> vk::PresentModeKHR {
+        match self {
+            PresentMode::Vsync => vk::PresentModeKHR::FIFO,
+            PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX,
+            PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
+        }
+    }
+}
+
+/// Screen space ambient occlusion, computed from the depth buffer in a compute shader and
+/// multiplied over voxels and models before water, particles and the grid are drawn
+/// Note: adds to the per vertex occlusion of voxels, costs more the more samples it takes
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct AmbientOcclusionConfig {
+    pub enabled: bool,
+    /// the samples taken per pixel, the quality slider of the rendering window
+    pub samples: u32,
+    /// how far away geometry still occludes, in world units
+    pub radius: f32,
+    /// how dark fully occluded pixels get, from 0 to 1
+    pub intensity: f32,
+}
+
+impl AmbientOcclusionConfig {
+    /// The most samples the compute shader takes
+    pub const MAX_SAMPLES: u32 = 64;
+}
+
+impl Default for AmbientOcclusionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            samples: 16,
+            radius: 1.0,
+            intensity: 0.8,
+        }
+    }
+}
+
+/// The user facing rendering settings, loaded from and saved to `RENDERING_CONFIG_PATH`
+/// Note: changes are applied to the renderer at the start of the next frame when `is_dirty` is set
+/// Note: MSAA, render scale and shadow quality are not settings, the renderer draws single
+/// sampled at the window's resolution and has no shadows
+#[derive(Resource, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RenderingConfig {
+    pub present_mode: PresentMode,
+    /// draws every model and voxel chunk as wireframe
+    pub wireframe: bool,
+    /// the graphics adapter to use, its index or part of its name, the best one if unset
+    /// Note: applied on the next start, `--gpu <name or index>` overrides it
+    pub preferred_gpu: Option<String>,
+    pub ambient_occlusion: AmbientOcclusionConfig,
+    /// set when the config needs to be applied to the renderer
+    #[serde(skip)]
+    pub is_dirty: bool,
+}
+
+impl Default for RenderingConfig {
+    fn default() -> Self {
+        Self {
+            present_mode: PresentMode::default(),
+            wireframe: false,
+            preferred_gpu: None,
+            ambient_occlusion: AmbientOcclusionConfig::default(),
+            is_dirty: true,
+        }
+    }
+}
+
+impl RenderingConfig {
+    /// Loads the config from disk, falling back to the defaults if it is missing or invalid
+    pub fn load() -> Self {
+        let mut config: Self = yaml::load_or_default(RENDERING_CONFIG_PATH);
+        config.is_dirty = true;
+        config
+    }
+
+    /// Saves the config to `RENDERING_CONFIG_PATH`
+    pub fn save(&self) -> Result<()> {
+        yaml::save(RENDERING_CONFIG_PATH, self)
+    }
+}
+
+/// Rendering statistics from the last finished frame
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct RenderingStats {
+    pub draw_calls: u32,
+    /// the gpu time of the last finished frame, none if timestamps are unsupported
+    pub gpu_time_ms: Option<f32>,
+    /// the device memory allocated by the renderer and not freed yet
+    pub gpu_memory_bytes: u64,
+    /// if the ambient occlusion pass could be created, its compute shader needs compiling
+    pub ambient_occlusion_available: bool,
+}
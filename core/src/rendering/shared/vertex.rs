@@ -11,6 +11,10 @@ pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
     pub tex_coord: [f32; 2],
+    /// xyz points along +u of the texture, w is the handedness of the bitangent (1 or -1)
+    pub tangent: [f32; 4],
+    /// baked ambient occlusion, 1 is unoccluded
+    pub ao: f32,
 }
 
 impl VertexDefinition for Vertex {
@@ -41,6 +45,18 @@ impl VertexDefinition for Vertex {
                 .location(2)
                 .format(vk::Format::R32G32_SFLOAT)
                 .offset(24),
+            // Tangent
+            vk::VertexInputAttributeDescription::default()
+                .binding(0)
+                .location(3)
+                .format(vk::Format::R32G32B32A32_SFLOAT)
+                .offset(32),
+            // Ambient Occlusion
+            vk::VertexInputAttributeDescription::default()
+                .binding(0)
+                .location(4)
+                .format(vk::Format::R32_SFLOAT)
+                .offset(48),
         ]
     }
 }
@@ -0,0 +1,272 @@
+use cgmath::{InnerSpace, Matrix4, Vector3};
+
+use crate::{
+    log_error,
+    objects::{
+        Object,
+        components::{interpolation::InterpolatedTransform, transform::Transform},
+        systems::EngineTimer,
+        world::World,
+    },
+    rendering::{
+        RenderingAPI,
+        components::{
+            camera::{Camera, get_projection, get_view_matrix},
+            model_renderer::ModelRenderer,
+        },
+        shared::{
+            frustrum::Frustum,
+            model::GpuMesh,
+            push_constants::{ModelPushConstants, PushConstants, VoxelPushConstants},
+        },
+    },
+    voxels::{
+        VoxelTransform,
+        meshes::{VoxelChunkMesh, WaterMesh},
+        texture_atlas::VoxelTextureAtlas,
+    },
+    weather::Weather,
+};
+
+/// Draws the chunks in view, the ambient occlusion over them and the water back to front,
+/// returns how many chunks were drawn
+pub fn draw_voxels(
+    world: &World,
+    renderer: &mut dyn RenderingAPI,
+    push_constants: &PushConstants,
+    voxel_push_constants: &VoxelPushConstants,
+    view_proj: Matrix4<f32>,
+    camera_pos: Vector3<f32>,
+) -> u32 {
+    let Ok(texture_atlas) = world.get_resource::<VoxelTextureAtlas>() else {
+        return 0;
+    };
+    let mut objects_drawn = 0;
+    let frustum = Frustum::from_view_proj(&view_proj);
+    let mut water_draws: Vec<(f32, Box<dyn GpuMesh>, PushConstants, VoxelPushConstants)> =
+        Vec::new();
+    let origin_offset = world.origin_offset();
+    for object in world.get_objects_with_component::<VoxelChunkMesh>() {
+        let transform = object.get_component::<VoxelTransform>().unwrap();
+        // chunks are positioned relative to the world origin
+        let chunk_origin = transform.position * 32 - origin_offset;
+        let world_pos = Vector3::new(
+            chunk_origin.x as f32,
+            chunk_origin.y as f32,
+            chunk_origin.z as f32,
+        );
+
+        if !frustum.contains_aabb(world_pos, world_pos + Vector3::new(32.0, 32.0, 32.0)) {
+            continue;
+        }
+        objects_drawn += 1;
+        let voxel_mesh = object.get_component::<VoxelChunkMesh>().unwrap();
+
+        let delta = world.get_resource::<EngineTimer>().unwrap();
+
+        let chunk_push = push_constants.clone();
+        let mut voxel_chunk_push = voxel_push_constants.clone();
+
+        voxel_chunk_push.time = delta.0;
+        voxel_chunk_push.set_position(chunk_origin);
+
+        if let Err(e) = renderer.voxel_render(
+            Box::new(voxel_mesh.clone()),
+            texture_atlas,
+            &chunk_push,
+            &voxel_chunk_push,
+        ) {
+            log_error!("Failed to render voxel: {}", e);
+        }
+
+        if let Ok(water_mesh) = object.get_component::<WaterMesh>() {
+            let chunk_center = world_pos + Vector3::new(16.0, 16.0, 16.0);
+            let distance = (chunk_center - camera_pos).magnitude2();
+            water_draws.push((
+                distance,
+                Box::new(water_mesh.clone()),
+                chunk_push.clone(),
+                voxel_chunk_push.clone(),
+            ));
+        }
+    }
+
+    // occlusion darkens the opaque models and chunks, water and particles are drawn over it
+    if let Err(e) = renderer.ambient_occlusion_render(push_constants) {
+        log_error!("Failed to render ambient occlusion: {}", e);
+    }
+
+    water_draws.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    for (_, mesh, chunk_push, voxel_chunk_push) in water_draws {
+        if let Err(e) = renderer.water_render(mesh, texture_atlas, &chunk_push, &voxel_chunk_push) {
+            log_error!("Failed to render water: {}", e);
+        }
+    }
+    objects_drawn
+}
+
+/// Queues the chunks and water in view of the minimap's camera for the map pass, the push
+/// constants are the map camera's, see `Minimap::push_constants`
+pub fn draw_map(
+    world: &World,
+    renderer: &mut dyn RenderingAPI,
+    push_constants: &PushConstants,
+    voxel_push_constants: &VoxelPushConstants,
+) {
+    let Ok(texture_atlas) = world.get_resource::<VoxelTextureAtlas>() else {
+        return;
+    };
+    let frustum =
+        Frustum::from_view_proj(&(push_constants.projection_matrix * push_constants.view_matrix));
+    let time = world
+        .get_resource::<EngineTimer>()
+        .map_or(0.0, |timer| timer.0);
+    // the map is seen from far above, fog would hide all of it
+    let mut voxel_push_constants = voxel_push_constants.clone();
+    voxel_push_constants.fog_density = 0.0;
+    voxel_push_constants.time = time;
+
+    let mut water_draws: Vec<(Box<dyn GpuMesh>, VoxelPushConstants)> = Vec::new();
+    let origin_offset = world.origin_offset();
+    for object in world.get_objects_with_component::<VoxelChunkMesh>() {
+        let Ok(transform) = object.get_component::<VoxelTransform>() else {
+            continue;
+        };
+        let chunk_origin = transform.position * 32 - origin_offset;
+        let world_pos = Vector3::new(
+            chunk_origin.x as f32,
+            chunk_origin.y as f32,
+            chunk_origin.z as f32,
+        );
+        if !frustum.contains_aabb(world_pos, world_pos + Vector3::new(32.0, 32.0, 32.0)) {
+            continue;
+        }
+
+        let mut voxel_chunk_push = voxel_push_constants.clone();
+        voxel_chunk_push.set_position(chunk_origin);
+        let voxel_mesh = object.get_component::<VoxelChunkMesh>().unwrap();
+        renderer.map_render(
+            Box::new(voxel_mesh.clone()),
+            texture_atlas,
+            push_constants,
+            &voxel_chunk_push,
+            false,
+        );
+        if let Ok(water_mesh) = object.get_component::<WaterMesh>() {
+            water_draws.push((Box::new(water_mesh.clone()), voxel_chunk_push));
+        }
+    }
+
+    // water is blended over the ground under it
+    for (mesh, voxel_chunk_push) in water_draws {
+        renderer.map_render(mesh, texture_atlas, push_constants, &voxel_chunk_push, true);
+    }
+}
+
+/// Gets a copy of a camera moved between its last two fixed update positions, see
+/// `InterpolatedTransform`
+/// Note: the rotation is kept as it is, cameras are turned every frame
+pub fn interpolated_camera(camera: &Object, overstep_fraction: f32) -> Object {
+    let mut camera = camera.clone();
+    let position = camera
+        .get_component::<InterpolatedTransform>()
+        .ok()
+        .map(|interpolated| interpolated.lerp(overstep_fraction).0);
+    if let (Some(position), Ok(transform)) = (position, camera.get_component_mut::<Transform>()) {
+        transform.global_position = position;
+    }
+    camera
+}
+
+/// Finds the camera drawing into a window, see `Camera::window`
+pub fn window_camera<'a>(world: &'a World, label: &str) -> Option<&'a Object> {
+    world
+        .get_objects_with_component::<Camera>()
+        .into_iter()
+        .find(|object| {
+            object
+                .get_component::<Camera>()
+                .is_ok_and(|camera| camera.window == label)
+        })
+}
+
+/// Draws the scene into a window opened through the `WindowManager`, from the camera assigned
+/// to it, between its `begin_frame` and `end_ui`
+/// Note: only models the primary window has loaded are drawn, the editor grid, outline and
+/// picking stay in the primary window
+pub fn draw_window(
+    world: &World,
+    renderer: &mut dyn RenderingAPI,
+    camera: &Object,
+    push_constants: &PushConstants,
+    voxel_push_constants: &VoxelPushConstants,
+    model_push: &ModelPushConstants,
+    overstep_fraction: f32,
+) {
+    let camera = &interpolated_camera(camera, overstep_fraction);
+    let (Ok(camera_transform), Ok(camera_component)) = (
+        camera.get_component::<Transform>(),
+        camera.get_component::<Camera>(),
+    ) else {
+        return;
+    };
+    let aspect = renderer.get_aspect();
+    let view_proj = get_projection(camera_component, aspect) * get_view_matrix(camera_transform);
+    let camera_pos = camera_transform.global_position;
+
+    let mut push_constants = push_constants.clone();
+    push_constants.set_camera_constants(camera.to_owned(), aspect);
+    let mut voxel_push_constants = voxel_push_constants.clone();
+
+    for object in world.get_objects_with_component::<ModelRenderer>() {
+        let model_renderer = object.get_component::<ModelRenderer>().unwrap();
+        let (Some(model), Ok(transform)) =
+            (&model_renderer.model, object.get_component::<Transform>())
+        else {
+            continue;
+        };
+        let (position, rotation) = match object.get_component::<InterpolatedTransform>() {
+            Ok(interpolated) => interpolated.lerp(overstep_fraction),
+            Err(_) => (transform.global_position, transform.global_rotation),
+        };
+
+        let mut frame_model_push = model_push.clone();
+        frame_model_push.world_position = position;
+        frame_model_push.world_scale = transform.global_scale;
+        frame_model_push.world_rotation = rotation;
+
+        for mesh in &model.meshes {
+            let result = if model_renderer.is_wireframe {
+                renderer.wireframe_render(
+                    Box::new(mesh.clone()),
+                    push_constants.clone(),
+                    &frame_model_push,
+                )
+            } else {
+                renderer.render(
+                    Box::new(mesh.clone()),
+                    push_constants.clone(),
+                    &frame_model_push,
+                )
+            };
+            if let Err(e) = result {
+                log_error!("Failed to render model: {}", e);
+            }
+        }
+    }
+
+    if let Ok(weather) = world.get_resource::<Weather>() {
+        voxel_push_constants.set_fog(&weather.fog);
+        let [r, g, b] = weather.sky_color;
+        renderer.set_clear_color([r, g, b, 1.0]);
+    }
+
+    draw_voxels(
+        world,
+        renderer,
+        &push_constants,
+        &voxel_push_constants,
+        view_proj,
+        camera_pos,
+    );
+}
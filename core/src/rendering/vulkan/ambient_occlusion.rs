@@ -0,0 +1,485 @@
+use std::mem::transmute;
+
+use anyhow::Result;
+use ash::vk::{self, Handle};
+use cgmath::Matrix4;
+
+use crate::assets::shader_loader::load_shader_bytes;
+use crate::rendering::{
+    shared::rendering_config::AmbientOcclusionConfig,
+    vulkan::{image_layout::ImageLayoutState, rendering_context::VulkanRenderingContext},
+};
+
+/// The size of the projection, radius, intensity and sample count push constants
+pub const AO_PUSH_CONSTANTS_SIZE: u32 = 80;
+/// The width and height of the compute shader's work groups
+const WORK_GROUP_SIZE: u32 = 8;
+
+/// A screen space ambient occlusion pass, a compute shader writes the occlusion of each pixel
+/// from the depth buffer, then a fullscreen triangle multiplies it over the frame
+/// Note: the targets are recreated when the swapchain's depth image changes
+pub struct AoPass {
+    pub compute_pipeline: vk::Pipeline,
+    pub compute_pipeline_layout: vk::PipelineLayout,
+    pub compute_set_layout: vk::DescriptorSetLayout,
+    pub composite_pipeline: vk::Pipeline,
+    pub composite_pipeline_layout: vk::PipelineLayout,
+    pub composite_set_layout: vk::DescriptorSetLayout,
+    pub descriptor_pool: vk::DescriptorPool,
+    pub compute_set: vk::DescriptorSet,
+    pub composite_set: vk::DescriptorSet,
+    pub sampler: vk::Sampler,
+    pub extent: vk::Extent2D,
+    pub image: vk::Image,
+    pub memory: vk::DeviceMemory,
+    pub view: vk::ImageView,
+    /// the depth view the compute set samples, the sets are rewritten when it changes
+    pub depth_view: vk::ImageView,
+}
+
+/// The push constants of the compute shader
+#[allow(unnecessary_transmutes)]
+fn push_constants(projection: Matrix4<f32>, config: &AmbientOcclusionConfig) -> Vec<u8> {
+    unsafe {
+        let mut data = Vec::with_capacity(AO_PUSH_CONSTANTS_SIZE as usize);
+        let projection: [u8; 64] = transmute(projection);
+        let radius: [u8; 4] = transmute(config.radius.max(0.01));
+        let intensity: [u8; 4] = transmute(config.intensity.clamp(0.0, 1.0));
+        let samples: [u8; 4] =
+            transmute(config.samples.clamp(1, AmbientOcclusionConfig::MAX_SAMPLES));
+        data.extend_from_slice(&projection);
+        data.extend_from_slice(&radius);
+        data.extend_from_slice(&intensity);
+        data.extend_from_slice(&samples);
+        data.extend_from_slice(&[0u8; 4]);
+        data // 80 bytes total
+    }
+}
+
+impl AoPass {
+    /// Loads the shaders and creates the pass, errors if the compute shader can't be loaded
+    pub fn load(
+        context: &VulkanRenderingContext,
+        image_format: vk::Format,
+        depth_format: vk::Format,
+    ) -> Result<Self> {
+        let mut modules = Vec::new();
+        for name in [
+            "ambient_occlusion.comp",
+            "fullscreen.vert",
+            "ambient_occlusion.frag",
+        ] {
+            match load_shader_bytes(name).and_then(|code| Ok(context.create_shader_module(&code)?))
+            {
+                Ok(module) => modules.push(module),
+                Err(e) => {
+                    for module in modules {
+                        unsafe { context.device.destroy_shader_module(module, None) };
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let pass = Self::new(
+            context,
+            modules[0],
+            modules[1],
+            modules[2],
+            image_format,
+            depth_format,
+        );
+        for module in modules {
+            unsafe { context.device.destroy_shader_module(module, None) };
+        }
+        pass
+    }
+
+    pub fn new(
+        context: &VulkanRenderingContext,
+        compute_shader: vk::ShaderModule,
+        vertex_shader: vk::ShaderModule,
+        fragment_shader: vk::ShaderModule,
+        image_format: vk::Format,
+        depth_format: vk::Format,
+    ) -> Result<Self> {
+        unsafe {
+            let compute_set_layout = context.device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&[
+                    vk::DescriptorSetLayoutBinding::default()
+                        .binding(0)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::COMPUTE),
+                    vk::DescriptorSetLayoutBinding::default()
+                        .binding(1)
+                        .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::COMPUTE),
+                ]),
+                None,
+            )?;
+            let composite_set_layout = context.device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&[
+                    vk::DescriptorSetLayoutBinding::default()
+                        .binding(0)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::FRAGMENT),
+                ]),
+                None,
+            )?;
+            let descriptor_pool = context.device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default()
+                    .max_sets(2)
+                    .pool_sizes(&[
+                        vk::DescriptorPoolSize {
+                            ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                            descriptor_count: 2,
+                        },
+                        vk::DescriptorPoolSize {
+                            ty: vk::DescriptorType::STORAGE_IMAGE,
+                            descriptor_count: 1,
+                        },
+                    ]),
+                None,
+            )?;
+            let sets = context.device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(descriptor_pool)
+                    .set_layouts(&[compute_set_layout, composite_set_layout]),
+            )?;
+
+            // depth can't be filtered on every gpu, the blur takes care of smoothing
+            let sampler = context.device.create_sampler(
+                &vk::SamplerCreateInfo::default()
+                    .mag_filter(vk::Filter::NEAREST)
+                    .min_filter(vk::Filter::NEAREST)
+                    .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                    .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                    .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE),
+                None,
+            )?;
+
+            let compute_pipeline_layout = context.device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default()
+                    .set_layouts(&[compute_set_layout])
+                    .push_constant_ranges(&[vk::PushConstantRange::default()
+                        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                        .offset(0)
+                        .size(AO_PUSH_CONSTANTS_SIZE)]),
+                None,
+            )?;
+            let compute_pipeline =
+                context.create_compute_pipeline(compute_shader, compute_pipeline_layout)?;
+
+            let composite_pipeline_layout = context.device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default().set_layouts(&[composite_set_layout]),
+                None,
+            )?;
+            let composite_pipeline = context.create_multiply_pipeline(
+                vertex_shader,
+                fragment_shader,
+                image_format,
+                depth_format,
+                composite_pipeline_layout,
+            )?;
+
+            Ok(Self {
+                compute_pipeline,
+                compute_pipeline_layout,
+                compute_set_layout,
+                composite_pipeline,
+                composite_pipeline_layout,
+                composite_set_layout,
+                descriptor_pool,
+                compute_set: sets[0],
+                composite_set: sets[1],
+                sampler,
+                extent: vk::Extent2D::default(),
+                image: vk::Image::null(),
+                memory: vk::DeviceMemory::null(),
+                view: vk::ImageView::null(),
+                depth_view: vk::ImageView::null(),
+            })
+        }
+    }
+
+    /// Recreates the occlusion image and rewrites the descriptor sets if the depth image changed
+    fn resize(
+        &mut self,
+        context: &VulkanRenderingContext,
+        extent: vk::Extent2D,
+        depth_view: vk::ImageView,
+    ) -> Result<()> {
+        if self.extent == extent && self.depth_view == depth_view && !self.image.is_null() {
+            return Ok(());
+        }
+
+        unsafe {
+            if !self.image.is_null() {
+                // earlier frames may still read the image and sets
+                context.device.device_wait_idle()?;
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+            }
+
+            let (image, memory) = context.create_image(
+                extent,
+                vk::Format::R8_UNORM,
+                vk::ImageTiling::OPTIMAL,
+                vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            let view = context.create_image_view(
+                image,
+                vk::Format::R8_UNORM,
+                vk::ImageAspectFlags::COLOR,
+            )?;
+
+            let depth_info = [vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+                .image_view(depth_view)
+                .sampler(self.sampler)];
+            let storage_info = [vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::GENERAL)
+                .image_view(view)];
+            let sampled_info = [vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .image_view(view)
+                .sampler(self.sampler)];
+            context.device.update_descriptor_sets(
+                &[
+                    vk::WriteDescriptorSet::default()
+                        .dst_set(self.compute_set)
+                        .dst_binding(0)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .image_info(&depth_info),
+                    vk::WriteDescriptorSet::default()
+                        .dst_set(self.compute_set)
+                        .dst_binding(1)
+                        .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+                        .image_info(&storage_info),
+                    vk::WriteDescriptorSet::default()
+                        .dst_set(self.composite_set)
+                        .dst_binding(0)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .image_info(&sampled_info),
+                ],
+                &[],
+            );
+
+            self.image = image;
+            self.memory = memory;
+            self.view = view;
+        }
+        self.extent = extent;
+        self.depth_view = depth_view;
+        Ok(())
+    }
+
+    /// Frees the pipelines, sets and image of the pass, the device must be idle
+    pub fn destroy(&mut self, context: &VulkanRenderingContext) {
+        unsafe {
+            if !self.image.is_null() {
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+                self.image = vk::Image::null();
+            }
+            context.device.destroy_sampler(self.sampler, None);
+            context
+                .device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+            for pipeline in [self.compute_pipeline, self.composite_pipeline] {
+                context.device.destroy_pipeline(pipeline, None);
+            }
+            for layout in [self.compute_pipeline_layout, self.composite_pipeline_layout] {
+                context.device.destroy_pipeline_layout(layout, None);
+            }
+            for layout in [self.compute_set_layout, self.composite_set_layout] {
+                context.device.destroy_descriptor_set_layout(layout, None);
+            }
+        }
+    }
+
+    /// Computes the occlusion of what has been drawn so far and multiplies it over the frame
+    /// Note: must be called while rendering to `color_view` and `depth_view`, the rendering is
+    /// ended for the compute shader and resumed with the targets' contents kept
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &mut self,
+        context: &VulkanRenderingContext,
+        command_buffer: vk::CommandBuffer,
+        extent: vk::Extent2D,
+        color_view: vk::ImageView,
+        depth_image: vk::Image,
+        depth_view: vk::ImageView,
+        projection: Matrix4<f32>,
+        config: &AmbientOcclusionConfig,
+    ) -> Result<()> {
+        if extent.width == 0 || extent.height == 0 {
+            return Ok(());
+        }
+
+        let depth_written = ImageLayoutState {
+            layout: vk::ImageLayout::DEPTH_ATTACHMENT_OPTIMAL,
+            access_mask: vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+            stage_mask: vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let depth_sampled = ImageLayoutState {
+            layout: vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+            access_mask: vk::AccessFlags::SHADER_READ,
+            stage_mask: vk::PipelineStageFlags::COMPUTE_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let depth_loaded = ImageLayoutState {
+            layout: vk::ImageLayout::DEPTH_ATTACHMENT_OPTIMAL,
+            access_mask: vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+            stage_mask: vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        // the last frame's composite may still read the image, its contents are not needed
+        let previous_frame = ImageLayoutState {
+            layout: vk::ImageLayout::UNDEFINED,
+            access_mask: vk::AccessFlags::empty(),
+            stage_mask: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let storage = ImageLayoutState {
+            layout: vk::ImageLayout::GENERAL,
+            access_mask: vk::AccessFlags::SHADER_WRITE,
+            stage_mask: vk::PipelineStageFlags::COMPUTE_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let sampled = ImageLayoutState {
+            layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            access_mask: vk::AccessFlags::SHADER_READ,
+            stage_mask: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+
+        unsafe {
+            context.device.cmd_end_rendering(command_buffer);
+            self.resize(context, extent, depth_view)?;
+
+            context.transition_image_layout(
+                command_buffer,
+                depth_image,
+                depth_written,
+                depth_sampled,
+                vk::ImageAspectFlags::DEPTH,
+            );
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                previous_frame,
+                storage,
+                vk::ImageAspectFlags::COLOR,
+            );
+
+            context.device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.compute_pipeline,
+            );
+            context.device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.compute_pipeline_layout,
+                0,
+                &[self.compute_set],
+                &[],
+            );
+            context.device.cmd_push_constants(
+                command_buffer,
+                self.compute_pipeline_layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                &push_constants(projection, config),
+            );
+            context.device.cmd_dispatch(
+                command_buffer,
+                extent.width.div_ceil(WORK_GROUP_SIZE),
+                extent.height.div_ceil(WORK_GROUP_SIZE),
+                1,
+            );
+
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                storage,
+                sampled,
+                vk::ImageAspectFlags::COLOR,
+            );
+            context.transition_image_layout(
+                command_buffer,
+                depth_image,
+                depth_sampled,
+                depth_loaded,
+                vk::ImageAspectFlags::DEPTH,
+            );
+
+            // the colour written before the pass is loaded again
+            let color_barrier = vk::MemoryBarrier::default()
+                .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                .dst_access_mask(
+                    vk::AccessFlags::COLOR_ATTACHMENT_READ
+                        | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                );
+            context.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::DependencyFlags::empty(),
+                &[color_barrier],
+                &[],
+                &[],
+            );
+
+            context.resume_rendering(
+                command_buffer,
+                color_view,
+                depth_view,
+                vk::Rect2D::default().extent(extent),
+            );
+            context.device.cmd_set_viewport(
+                command_buffer,
+                0,
+                &[vk::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: extent.width as f32,
+                    height: extent.height as f32,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                }],
+            );
+            context.device.cmd_set_scissor(
+                command_buffer,
+                0,
+                &[vk::Rect2D::default().extent(extent)],
+            );
+            context.device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.composite_pipeline,
+            );
+            context.device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.composite_pipeline_layout,
+                0,
+                &[self.composite_set],
+                &[],
+            );
+            context.device.cmd_draw(command_buffer, 3, 1, 0, 0);
+        }
+        Ok(())
+    }
+}
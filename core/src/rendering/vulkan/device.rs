@@ -1,6 +1,9 @@
 use ash::vk;
 
-use crate::rendering::vulkan::queue_family::QueueFamily;
+use crate::rendering::{
+    shared::gpu::{GpuInfo, GpuKind},
+    vulkan::queue_family::QueueFamily,
+};
 
 #[derive(Debug, Clone)]
 pub struct PhysicalDevice {
@@ -10,3 +13,63 @@ pub struct PhysicalDevice {
     pub memory_properties: vk::PhysicalDeviceMemoryProperties,
     pub queue_families: Vec<QueueFamily>,
 }
+
+impl PhysicalDevice {
+    /// Describes the device for the editor and logs
+    pub fn info(&self) -> GpuInfo {
+        let properties = &self.properties;
+        let name = properties
+            .device_name_as_c_str()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| "Unknown GPU".to_string());
+        let kind = match properties.device_type {
+            vk::PhysicalDeviceType::DISCRETE_GPU => GpuKind::Discrete,
+            vk::PhysicalDeviceType::INTEGRATED_GPU => GpuKind::Integrated,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => GpuKind::Virtual,
+            vk::PhysicalDeviceType::CPU => GpuKind::Cpu,
+            _ => GpuKind::Other,
+        };
+        let memory_bytes = self.memory_properties.memory_heaps
+            [..self.memory_properties.memory_heap_count as usize]
+            .iter()
+            .filter(|heap| heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL))
+            .map(|heap| heap.size)
+            .sum();
+
+        GpuInfo {
+            name,
+            kind,
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            driver_version: driver_version(properties.vendor_id, properties.driver_version),
+            api_version: format!(
+                "{}.{}.{}",
+                vk::api_version_major(properties.api_version),
+                vk::api_version_minor(properties.api_version),
+                vk::api_version_patch(properties.api_version)
+            ),
+            memory_bytes,
+        }
+    }
+}
+
+/// Decodes a driver version, vendors pack it differently
+fn driver_version(vendor_id: u32, version: u32) -> String {
+    const NVIDIA: u32 = 0x10de;
+    const INTEL: u32 = 0x8086;
+    match vendor_id {
+        NVIDIA => format!(
+            "{}.{}.{}",
+            (version >> 22) & 0x3ff,
+            (version >> 14) & 0xff,
+            (version >> 6) & 0xff
+        ),
+        INTEL if cfg!(target_os = "windows") => format!("{}.{}", version >> 14, version & 0x3fff),
+        _ => format!(
+            "{}.{}.{}",
+            vk::api_version_major(version),
+            vk::api_version_minor(version),
+            vk::api_version_patch(version)
+        ),
+    }
+}
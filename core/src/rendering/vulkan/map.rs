@@ -0,0 +1,335 @@
+use anyhow::Result;
+use ash::vk::{self, Handle};
+use egui::TextureId;
+
+use crate::rendering::{
+    shared::model::GpuMesh,
+    vulkan::{
+        image_layout::{ImageLayoutState, ImageLayouts},
+        rendering_context::VulkanRenderingContext,
+    },
+};
+
+/// A chunk or water mesh queued for the map pass with the pipeline it is drawn with
+pub struct MapDraw {
+    pub mesh: Box<dyn GpuMesh>,
+    pub pipeline: vk::Pipeline,
+    pub pipeline_layout: vk::PipelineLayout,
+    pub descriptor_set: vk::DescriptorSet,
+    pub data: Vec<u8>,
+}
+
+/// An offscreen pass drawing the voxels seen by the minimap's camera into a square target that
+/// egui samples as a user texture
+/// Only recorded on frames where the map was requested, the ui shows the last map recorded
+pub struct MapPass {
+    pub set_layout: vk::DescriptorSetLayout,
+    pub descriptor_pool: vk::DescriptorPool,
+    pub set: vk::DescriptorSet,
+    pub sampler: vk::Sampler,
+    pub extent: vk::Extent2D,
+    pub image: vk::Image,
+    pub memory: vk::DeviceMemory,
+    pub view: vk::ImageView,
+    pub depth_image: vk::Image,
+    pub depth_memory: vk::DeviceMemory,
+    pub depth_view: vk::ImageView,
+    pub draws: Vec<MapDraw>,
+    /// the size of the map and the colour it is cleared to this frame
+    pub request: Option<(u32, [f32; 4])>,
+    /// the egui texture of the set, see `Renderer::add_user_texture`
+    pub texture_id: Option<TextureId>,
+    /// set once the target has been drawn, egui can't sample it before
+    pub is_drawn: bool,
+}
+
+impl MapPass {
+    pub fn new(context: &VulkanRenderingContext) -> Result<Self> {
+        unsafe {
+            // the layout egui's pipeline expects of user textures
+            let set_layout = context.device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&[
+                    vk::DescriptorSetLayoutBinding::default()
+                        .binding(0)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::FRAGMENT),
+                ]),
+                None,
+            )?;
+            let descriptor_pool = context.device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default()
+                    .max_sets(1)
+                    .pool_sizes(&[vk::DescriptorPoolSize {
+                        ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                        descriptor_count: 1,
+                    }]),
+                None,
+            )?;
+            let set = context.device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(descriptor_pool)
+                    .set_layouts(&[set_layout]),
+            )?[0];
+            // voxels stay sharp when the map is shown larger than it is drawn
+            let sampler = context.device.create_sampler(
+                &vk::SamplerCreateInfo::default()
+                    .mag_filter(vk::Filter::NEAREST)
+                    .min_filter(vk::Filter::LINEAR)
+                    .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                    .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                    .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE),
+                None,
+            )?;
+
+            Ok(Self {
+                set_layout,
+                descriptor_pool,
+                set,
+                sampler,
+                extent: vk::Extent2D::default(),
+                image: vk::Image::null(),
+                memory: vk::DeviceMemory::null(),
+                view: vk::ImageView::null(),
+                depth_image: vk::Image::null(),
+                depth_memory: vk::DeviceMemory::null(),
+                depth_view: vk::ImageView::null(),
+                draws: Vec::new(),
+                request: None,
+                texture_id: None,
+                is_drawn: false,
+            })
+        }
+    }
+
+    /// Recreates the target and rewrites the descriptor set if the extent has changed
+    fn resize(
+        &mut self,
+        context: &VulkanRenderingContext,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        depth_format: vk::Format,
+    ) -> Result<()> {
+        if self.extent == extent && !self.image.is_null() {
+            return Ok(());
+        }
+
+        unsafe {
+            if !self.image.is_null() {
+                // earlier frames may still sample the map
+                context.device.device_wait_idle()?;
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+                context.device.destroy_image_view(self.depth_view, None);
+                context.device.destroy_image(self.depth_image, None);
+                context.free_memory(self.depth_memory);
+            }
+            self.is_drawn = false;
+
+            let (image, memory) = context.create_image(
+                extent,
+                format,
+                vk::ImageTiling::OPTIMAL,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            let (depth_image, depth_memory) = context.create_image(
+                extent,
+                depth_format,
+                vk::ImageTiling::OPTIMAL,
+                vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            let view = context.create_image_view(image, format, vk::ImageAspectFlags::COLOR)?;
+            let depth_view = context.create_image_view(
+                depth_image,
+                depth_format,
+                vk::ImageAspectFlags::DEPTH,
+            )?;
+
+            let map_info = [vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .image_view(view)
+                .sampler(self.sampler)];
+            context.device.update_descriptor_sets(
+                &[vk::WriteDescriptorSet::default()
+                    .dst_set(self.set)
+                    .dst_binding(0)
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .image_info(&map_info)],
+                &[],
+            );
+
+            self.image = image;
+            self.memory = memory;
+            self.view = view;
+            self.depth_image = depth_image;
+            self.depth_memory = depth_memory;
+            self.depth_view = depth_view;
+        }
+        self.extent = extent;
+        Ok(())
+    }
+
+    /// Frees the set and target of the pass, the device must be idle
+    pub fn destroy(&mut self, context: &VulkanRenderingContext) {
+        unsafe {
+            if !self.image.is_null() {
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+                context.device.destroy_image_view(self.depth_view, None);
+                context.device.destroy_image(self.depth_image, None);
+                context.free_memory(self.depth_memory);
+                self.image = vk::Image::null();
+            }
+            context.device.destroy_sampler(self.sampler, None);
+            context
+                .device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+            context
+                .device
+                .destroy_descriptor_set_layout(self.set_layout, None);
+        }
+        self.draws.clear();
+        self.is_drawn = false;
+    }
+
+    /// Draws the queued meshes into the map, nothing if the map wasn't requested this frame
+    /// Note: must be called outside of rendering, the map is left ready to be sampled
+    pub fn record(
+        &mut self,
+        context: &VulkanRenderingContext,
+        command_buffer: vk::CommandBuffer,
+        format: vk::Format,
+        depth_format: vk::Format,
+        layouts: &ImageLayouts,
+    ) -> Result<()> {
+        let Some((size, clear_color)) = self.request.take() else {
+            self.draws.clear();
+            return Ok(());
+        };
+        if size == 0 {
+            self.draws.clear();
+            return Ok(());
+        }
+        let extent = vk::Extent2D {
+            width: size,
+            height: size,
+        };
+
+        // the last frame's ui may still sample the map, its contents are not needed
+        let previous_frame = ImageLayoutState {
+            layout: vk::ImageLayout::UNDEFINED,
+            access_mask: vk::AccessFlags::empty(),
+            stage_mask: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let sampled = ImageLayoutState {
+            layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            access_mask: vk::AccessFlags::SHADER_READ,
+            stage_mask: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+
+        unsafe {
+            self.resize(context, extent, format, depth_format)?;
+
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                previous_frame,
+                layouts.renderable,
+                vk::ImageAspectFlags::COLOR,
+            );
+            context.transition_image_layout(
+                command_buffer,
+                self.depth_image,
+                layouts.undefined,
+                layouts.depth,
+                vk::ImageAspectFlags::DEPTH,
+            );
+
+            context.begin_rendering(
+                command_buffer,
+                self.view,
+                self.depth_view,
+                vk::ClearColorValue {
+                    float32: clear_color,
+                },
+                vk::Rect2D::default().extent(extent),
+            );
+            context.device.cmd_set_viewport(
+                command_buffer,
+                0,
+                &[vk::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: size as f32,
+                    height: size as f32,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                }],
+            );
+            context.device.cmd_set_scissor(
+                command_buffer,
+                0,
+                &[vk::Rect2D::default().extent(extent)],
+            );
+            for draw in self.draws.drain(..) {
+                context.device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    draw.pipeline,
+                );
+                context.device.cmd_push_constants(
+                    command_buffer,
+                    draw.pipeline_layout,
+                    vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    &draw.data,
+                );
+                context.device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    draw.pipeline_layout,
+                    0,
+                    &[draw.descriptor_set],
+                    &[],
+                );
+                context.device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &[draw.mesh.get_vertex_buffer()],
+                    &[0],
+                );
+                context.device.cmd_bind_index_buffer(
+                    command_buffer,
+                    draw.mesh.get_index_buffer(),
+                    0,
+                    vk::IndexType::UINT32,
+                );
+                context.device.cmd_draw_indexed(
+                    command_buffer,
+                    draw.mesh.get_index_count(),
+                    1,
+                    0,
+                    0,
+                    0,
+                );
+            }
+            context.device.cmd_end_rendering(command_buffer);
+
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                layouts.renderable,
+                sampled,
+                vk::ImageAspectFlags::COLOR,
+            );
+        }
+        self.is_drawn = true;
+        Ok(())
+    }
+}
@@ -1,11 +1,21 @@
 use std::sync::{Arc, Mutex};
 
 use crate::assets::shader_loader::load_shader_bytes;
+use crate::log_warn;
+use crate::rendering::shared::material::MATERIAL_TEXTURE_COUNT;
 use crate::rendering::shared::model::GpuMesh;
 use crate::rendering::shared::push_constants::{
-    ModelPushConstants, PushConstants, VoxelPushConstants,
+    GridPushConstants, MaterialPushConstants, ModelPushConstants, OutlinePushConstants,
+    ParticlePushConstants, PushConstants, VoxelPushConstants,
 };
+use crate::rendering::shared::rendering_config::{
+    AmbientOcclusionConfig, RenderingConfig, RenderingStats,
+};
+use crate::rendering::vulkan::ambient_occlusion::AoPass;
 use crate::rendering::vulkan::image_layout::ImageLayouts;
+use crate::rendering::vulkan::map::{MapDraw, MapPass};
+use crate::rendering::vulkan::outline::OutlinePass;
+use crate::rendering::vulkan::picking::{IdDraw, IdPass};
 use crate::rendering::vulkan::rendering_context::VulkanRenderingContext;
 use crate::rendering::vulkan::{frame::VulkanFrame, swapchain::VulkanSwapchain};
 use crate::rendering::{RenderingAPI, RenderingInfo};
@@ -13,18 +23,24 @@ use crate::ui::UIRenderer;
 use crate::voxels::texture_atlas::VoxelTextureAtlas;
 use anyhow::Result;
 use ash::vk::{
-    self, ClearColorValue, CommandBufferResetFlags, CommandPool, Pipeline, PipelineLayout,
+    self, ClearColorValue, CommandBufferResetFlags, CommandPool, Handle, Pipeline, PipelineLayout,
     PipelineLayoutCreateInfo,
 };
+use cgmath::{SquareMatrix, Vector3, Zero};
 use egui::{Context, TextureId};
 use epaint::ImageDelta;
 use winit::event::WindowEvent;
 use winit::window::Window;
 
+pub mod ambient_occlusion;
 pub mod device;
 pub mod frame;
 pub mod image_layout;
+pub mod map;
+pub mod outline;
+pub mod picking;
 pub mod queue_family;
+pub mod readback;
 pub mod rendering_context;
 pub mod surface;
 pub mod swapchain;
@@ -60,19 +76,94 @@ pub struct VulkanRenderer {
     pub voxel_pipeline_layout: PipelineLayout,
     pub water_pipeline: Pipeline,
     pub water_pipeline_layout: PipelineLayout,
+    pub particle_pipeline: Pipeline,
+    pub particle_pipeline_layout: PipelineLayout,
+    pub grid_pipeline: Pipeline,
+    pub grid_pipeline_layout: PipelineLayout,
     pub voxel_descriptor_pool: vk::DescriptorPool,
     pub voxel_descriptor_set_layout: vk::DescriptorSetLayout,
+    pub material_descriptor_pool: vk::DescriptorPool,
+    pub material_descriptor_set_layout: vk::DescriptorSetLayout,
 
     pub buffer_graveyard: Vec<(vk::Buffer, vk::DeviceMemory)>,
+    /// the graveyard of each frame slot, destroyed once the slot's fence is signalled again
+    pub retired_buffers: Vec<Vec<(vk::Buffer, vk::DeviceMemory)>>,
 
     pub ui_renderer: UIRenderer,
 
     pub push_constants: PushConstants,
+    /// the colour the frame is cleared to
+    pub clear_color: [f32; 4],
+
+    /// draws models and voxels with the wireframe pipelines
+    pub wireframe: bool,
+    /// the draw calls recorded in the current frame
+    pub draw_calls: u32,
+    /// two timestamps per in flight frame, null if timestamps are unsupported
+    pub timestamp_pool: vk::QueryPool,
+    pub timestamps_written: Vec<bool>,
+    pub gpu_time_ms: Option<f32>,
+
+    pub id_pass: IdPass,
+    /// none if the compute shader could not be loaded, the option is then ignored
+    pub ao_pass: Option<AoPass>,
+    pub ambient_occlusion: AmbientOcclusionConfig,
+    /// none if the outline shaders could not be loaded, nothing is outlined then
+    pub outline_pass: Option<OutlinePass>,
+    pub map_pass: MapPass,
 
     pub ubo: Ubo,
     context: Arc<VulkanRenderingContext>,
 }
 
+/// The most materials that can be loaded, each takes a descriptor set
+const MAX_MATERIALS: u32 = 256;
+
+impl VulkanRenderer {
+    /// Binds the material of a model mesh and pushes the model and material constants
+    fn bind_material(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        mesh: &dyn GpuMesh,
+        push_constants: &PushConstants,
+        model_push_constants: &ModelPushConstants,
+    ) -> Result<()> {
+        let material = mesh
+            .get_material()
+            .filter(|material| !material.descriptor_set.is_null())
+            .ok_or_else(|| anyhow::anyhow!("Mesh has no material"))?;
+        let camera_position = push_constants
+            .view_matrix
+            .invert()
+            .map(|inverse| inverse.w.truncate())
+            .unwrap_or(Vector3::zero());
+
+        let mut data = push_constants.return_renderable();
+        data.extend(model_push_constants.return_renderable());
+        data.extend(MaterialPushConstants::new(material, camera_position).return_renderable());
+
+        unsafe {
+            self.context.device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline_layout,
+                0,
+                &[material.descriptor_set],
+                &[],
+            );
+            self.context.device.cmd_push_constants(
+                command_buffer,
+                self.pipeline_layout,
+                vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                0,
+                &data,
+            );
+        }
+
+        Ok(())
+    }
+}
+
 fn load_shader_module(
     context: &Arc<VulkanRenderingContext>,
     path: &str,
@@ -81,13 +172,11 @@ fn load_shader_module(
     Ok(context.create_shader_module(&code)?)
 }
 
-impl RenderingAPI for VulkanRenderer {
-    fn new(rendering_info: Arc<Mutex<RenderingInfo>>, window: Arc<Window>) -> Result<()> {
-        let mut rendering_info = rendering_info.lock().unwrap();
-        let mut swapchain = VulkanSwapchain::new(
-            rendering_info.context.clone().into(),
-            rendering_info.window.clone(),
-        )?;
+impl VulkanRenderer {
+    /// Creates a renderer drawing into a window on the device of the rendering context
+    pub fn create(rendering_info: &RenderingInfo, window: Arc<Window>) -> Result<Self> {
+        let mut swapchain =
+            VulkanSwapchain::new(rendering_info.context.clone().into(), window.clone())?;
         swapchain.resize()?;
 
         let vertex_shader = load_shader_module(
@@ -102,16 +191,42 @@ impl RenderingAPI for VulkanRenderer {
             load_shader_module(&rendering_info.context.clone().into(), "voxel.vert")?;
         let voxel_fragment_shader =
             load_shader_module(&rendering_info.context.clone().into(), "voxel.frag")?;
+        let id_fragment_shader =
+            load_shader_module(&rendering_info.context.clone().into(), "id.frag")?;
 
         unsafe {
             let context = rendering_info.context.clone();
+            let material_bindings: Vec<_> = (0..MATERIAL_TEXTURE_COUNT as u32)
+                .map(|binding| {
+                    vk::DescriptorSetLayoutBinding::default()
+                        .binding(binding)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                })
+                .collect();
+            let material_descriptor_set_layout = context.device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&material_bindings),
+                None,
+            )?;
+            let material_descriptor_pool = context.device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default()
+                    .max_sets(MAX_MATERIALS)
+                    .pool_sizes(&[vk::DescriptorPoolSize {
+                        ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                        descriptor_count: MAX_MATERIALS * MATERIAL_TEXTURE_COUNT as u32,
+                    }]),
+                None,
+            )?;
+
+            // the model, then the material factors for the fragment shader
             let pipeline_layout = rendering_info.context.device.create_pipeline_layout(
-                &PipelineLayoutCreateInfo::default().push_constant_ranges(&[
-                    vk::PushConstantRange::default()
-                        .stage_flags(vk::ShaderStageFlags::VERTEX)
+                &PipelineLayoutCreateInfo::default()
+                    .push_constant_ranges(&[vk::PushConstantRange::default()
+                        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
                         .offset(0)
-                        .size(176),
-                ]),
+                        .size(240)])
+                    .set_layouts(&[material_descriptor_set_layout]),
                 None,
             )?;
 
@@ -160,7 +275,7 @@ impl RenderingAPI for VulkanRenderer {
                     .push_constant_ranges(&[vk::PushConstantRange::default()
                         .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
                         .offset(0)
-                        .size(160)])
+                        .size(176)])
                     .set_layouts(&[descriptor_set_layout]),
                 None,
             )?;
@@ -195,7 +310,7 @@ impl RenderingAPI for VulkanRenderer {
                     .push_constant_ranges(&[vk::PushConstantRange::default()
                         .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
                         .offset(0)
-                        .size(160)])
+                        .size(176)])
                     .set_layouts(&[descriptor_set_layout]),
                 None,
             )?;
@@ -210,7 +325,92 @@ impl RenderingAPI for VulkanRenderer {
                 Default::default(),
             )?;
 
+            let particle_vertex_shader =
+                load_shader_module(&rendering_info.context.clone().into(), "particle.vert")?;
+            let particle_fragment_shader =
+                load_shader_module(&rendering_info.context.clone().into(), "particle.frag")?;
+
+            let particle_pipeline_layout = context.device.create_pipeline_layout(
+                &PipelineLayoutCreateInfo::default().push_constant_ranges(&[
+                    vk::PushConstantRange::default()
+                        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+                        .offset(0)
+                        .size(192),
+                ]),
+                None,
+            )?;
+
+            let particle_pipeline = context.create_particle_pipeline(
+                particle_vertex_shader,
+                particle_fragment_shader,
+                swapchain.extent,
+                swapchain.format,
+                swapchain.depth_format,
+                particle_pipeline_layout,
+                Default::default(),
+            )?;
+
+            let grid_vertex_shader =
+                load_shader_module(&rendering_info.context.clone().into(), "grid.vert")?;
+            let grid_fragment_shader =
+                load_shader_module(&rendering_info.context.clone().into(), "grid.frag")?;
+
+            let grid_pipeline_layout = context.device.create_pipeline_layout(
+                &PipelineLayoutCreateInfo::default().push_constant_ranges(&[
+                    vk::PushConstantRange::default()
+                        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+                        .offset(0)
+                        .size(176),
+                ]),
+                None,
+            )?;
+
+            // the grid is blended and depth tested without writing depth, like particles
+            let grid_pipeline = context.create_particle_pipeline(
+                grid_vertex_shader,
+                grid_fragment_shader,
+                swapchain.extent,
+                swapchain.format,
+                swapchain.depth_format,
+                grid_pipeline_layout,
+                Default::default(),
+            )?;
+
+            let in_flight_frames_count = 3;
+
+            let id_pass = IdPass::new(
+                &context,
+                vertex_shader,
+                id_fragment_shader,
+                swapchain.depth_format,
+                in_flight_frames_count,
+            )?;
+
+            let ao_pass = match AoPass::load(&context, swapchain.format, swapchain.depth_format) {
+                Ok(ao_pass) => Some(ao_pass),
+                Err(e) => {
+                    log_warn!("Ambient occlusion is unavailable: {}", e);
+                    None
+                }
+            };
+            let outline_pass = match OutlinePass::load(
+                &context,
+                vertex_shader,
+                id_fragment_shader,
+                swapchain.format,
+                swapchain.depth_format,
+            ) {
+                Ok(outline_pass) => Some(outline_pass),
+                Err(e) => {
+                    log_warn!("The selection outline is unavailable: {}", e);
+                    None
+                }
+            };
+
             context.device.destroy_shader_module(vertex_shader, None);
+            context
+                .device
+                .destroy_shader_module(id_fragment_shader, None);
             context
                 .device
                 .destroy_shader_module(voxel_vertex_shader, None);
@@ -224,6 +424,18 @@ impl RenderingAPI for VulkanRenderer {
             context
                 .device
                 .destroy_shader_module(water_fragment_shader, None);
+            context
+                .device
+                .destroy_shader_module(particle_vertex_shader, None);
+            context
+                .device
+                .destroy_shader_module(particle_fragment_shader, None);
+            context
+                .device
+                .destroy_shader_module(grid_vertex_shader, None);
+            context
+                .device
+                .destroy_shader_module(grid_fragment_shader, None);
 
             let command_pool = context.device.create_command_pool(
                 &ash::vk::CommandPoolCreateInfo::default()
@@ -232,8 +444,6 @@ impl RenderingAPI for VulkanRenderer {
                 None,
             )?;
 
-            let in_flight_frames_count = 3;
-
             let command_buffers = context.device.allocate_command_buffers(
                 &ash::vk::CommandBufferAllocateInfo::default()
                     .command_pool(command_pool)
@@ -273,7 +483,26 @@ impl RenderingAPI for VulkanRenderer {
                 memory: default_ubo_mem,
             };
 
-            let ui_renderer = UIRenderer::new(context.clone(), &swapchain, window)?;
+            let mut ui_renderer = UIRenderer::new(context.clone(), &swapchain, window)?;
+            let mut map_pass = MapPass::new(&context)?;
+            map_pass.texture_id = Some(ui_renderer.renderer.add_user_texture(map_pass.set));
+
+            let timestamp_pool = if context
+                .physical_device
+                .properties
+                .limits
+                .timestamp_compute_and_graphics
+                == vk::TRUE
+            {
+                context.device.create_query_pool(
+                    &vk::QueryPoolCreateInfo::default()
+                        .query_type(vk::QueryType::TIMESTAMP)
+                        .query_count(in_flight_frames_count as u32 * 2),
+                    None,
+                )?
+            } else {
+                vk::QueryPool::null()
+            };
 
             let renderer = VulkanRenderer {
                 current_image_index: 0,
@@ -289,6 +518,7 @@ impl RenderingAPI for VulkanRenderer {
                 voxel_pipeline_layout,
 
                 buffer_graveyard: Vec::new(),
+                retired_buffers: vec![Vec::new(); in_flight_frames_count],
 
                 ui_renderer,
 
@@ -296,18 +526,42 @@ impl RenderingAPI for VulkanRenderer {
                 voxel_wireframe_pipeline,
                 voxel_descriptor_pool: descriptor_pool,
                 voxel_descriptor_set_layout: descriptor_set_layout,
+                material_descriptor_pool,
+                material_descriptor_set_layout,
                 water_pipeline,
                 water_pipeline_layout,
+                particle_pipeline,
+                particle_pipeline_layout,
+                grid_pipeline,
+                grid_pipeline_layout,
 
                 push_constants: PushConstants::default(),
+                clear_color: [0.0, 0.2, 0.8, 1.0],
+                wireframe: false,
+                draw_calls: 0,
+                timestamp_pool,
+                timestamps_written: vec![false; in_flight_frames_count],
+                gpu_time_ms: None,
+                id_pass,
+                ao_pass,
+                ambient_occlusion: AmbientOcclusionConfig::default(),
+                outline_pass,
+                map_pass,
                 ubo,
                 context: Arc::new(rendering_info.context.clone()),
                 swapchain,
             };
 
-            rendering_info.renderer = Some(Box::new(renderer));
+            Ok(renderer)
         }
+    }
+}
 
+impl RenderingAPI for VulkanRenderer {
+    fn new(rendering_info: Arc<Mutex<RenderingInfo>>, window: Arc<Window>) -> Result<()> {
+        let mut rendering_info = rendering_info.lock().unwrap();
+        let renderer = VulkanRenderer::create(&rendering_info, window)?;
+        rendering_info.renderer = Some(Box::new(renderer));
         Ok(())
     }
 
@@ -332,7 +586,14 @@ impl RenderingAPI for VulkanRenderer {
                 FENCE_TIMEOUT_NS,
             ) {
                 Ok(()) => {
-                    self.buffer_graveyard.drain(..);
+                    // buffers retired by this slot are no longer used by any frame in flight
+                    for (buffer, memory) in self.retired_buffers[self.current_frame].drain(..) {
+                        self.context.device.destroy_buffer(buffer, None);
+                        self.context.free_memory(memory);
+                    }
+                    self.retired_buffers[self.current_frame].append(&mut self.buffer_graveyard);
+                    self.id_pass
+                        .read_result(&self.context, self.current_frame)?;
                 }
                 Err(e) => {
                     eprintln!("Fence wait failed (likely device timeout): {}", e);
@@ -395,6 +656,50 @@ impl RenderingAPI for VulkanRenderer {
                 return Err(anyhow::anyhow!("Failed to begin command buffer: {}", e));
             }
 
+            self.draw_calls = 0;
+            if !self.timestamp_pool.is_null() {
+                let first_query = self.current_frame as u32 * 2;
+
+                // the fence has been waited on so this frame's previous timestamps are ready
+                if self.timestamps_written[self.current_frame] {
+                    let mut timestamps = [0u64; 2];
+                    if self
+                        .context
+                        .device
+                        .get_query_pool_results(
+                            self.timestamp_pool,
+                            first_query,
+                            &mut timestamps,
+                            vk::QueryResultFlags::TYPE_64,
+                        )
+                        .is_ok()
+                    {
+                        let period = self
+                            .context
+                            .physical_device
+                            .properties
+                            .limits
+                            .timestamp_period;
+                        let ticks = timestamps[1].saturating_sub(timestamps[0]);
+                        self.gpu_time_ms =
+                            Some((ticks as f64 * period as f64 / 1_000_000.0) as f32);
+                    }
+                }
+
+                self.context.device.cmd_reset_query_pool(
+                    frame.command_buffer,
+                    self.timestamp_pool,
+                    first_query,
+                    2,
+                );
+                self.context.device.cmd_write_timestamp(
+                    frame.command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    self.timestamp_pool,
+                    first_query,
+                );
+            }
+
             self.context.transition_image_layout(
                 frame.command_buffer,
                 self.swapchain.images[self.current_image_index as usize],
@@ -415,7 +720,7 @@ impl RenderingAPI for VulkanRenderer {
                 self.swapchain.views[self.current_image_index as usize],
                 self.swapchain.depth_image_view,
                 ClearColorValue {
-                    float32: [0.0, 0.2, 0.8, 1.0],
+                    float32: self.clear_color,
                 },
                 vk::Rect2D::default().extent(self.swapchain.extent),
             );
@@ -457,6 +762,34 @@ impl RenderingAPI for VulkanRenderer {
                 vk::ImageAspectFlags::COLOR,
             );
 
+            self.draw_calls += self.map_pass.draws.len() as u32;
+            self.map_pass.record(
+                &self.context,
+                frame.command_buffer,
+                self.swapchain.format,
+                self.swapchain.depth_format,
+                &self.image_layouts,
+            )?;
+
+            self.id_pass.record(
+                &self.context,
+                frame.command_buffer,
+                self.current_frame,
+                self.swapchain.extent,
+                self.swapchain.depth_format,
+                &self.image_layouts,
+            )?;
+
+            if !self.timestamp_pool.is_null() {
+                self.context.device.cmd_write_timestamp(
+                    frame.command_buffer,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    self.timestamp_pool,
+                    self.current_frame as u32 * 2 + 1,
+                );
+                self.timestamps_written[self.current_frame] = true;
+            }
+
             if let Err(e) = self.context.device.end_command_buffer(frame.command_buffer) {
                 eprintln!("Failed to end command buffer: {}", e);
                 return Err(anyhow::anyhow!("Failed to end command buffer: {}", e));
@@ -496,23 +829,25 @@ impl RenderingAPI for VulkanRenderer {
         model_push_constants: &ModelPushConstants,
     ) -> anyhow::Result<()> {
         let frame = &self.frames[self.current_frame];
+        let pipeline = if self.wireframe {
+            self.wireframe_pipeline
+        } else {
+            self.pipeline
+        };
 
         unsafe {
             self.context.device.cmd_bind_pipeline(
                 frame.command_buffer,
                 vk::PipelineBindPoint::GRAPHICS,
-                self.pipeline,
+                pipeline,
             );
 
-            let mut data = push_constants.return_renderable();
-            data.extend(model_push_constants.return_renderable());
-            self.context.device.cmd_push_constants(
+            self.bind_material(
                 frame.command_buffer,
-                self.pipeline_layout,
-                vk::ShaderStageFlags::VERTEX,
-                0,
-                &data,
-            );
+                mesh.as_ref(),
+                &push_constants,
+                model_push_constants,
+            )?;
 
             self.context.device.cmd_bind_vertex_buffers(
                 frame.command_buffer,
@@ -535,6 +870,7 @@ impl RenderingAPI for VulkanRenderer {
                 0,
             );
         }
+        self.draw_calls += 1;
 
         Ok(())
     }
@@ -546,9 +882,6 @@ impl RenderingAPI for VulkanRenderer {
     ) -> anyhow::Result<()> {
         let frame = &self.frames[self.current_frame];
 
-        let mut data = push_constants.return_renderable();
-        data.extend(model_push_constants.return_renderable());
-
         unsafe {
             self.context.device.cmd_bind_pipeline(
                 frame.command_buffer,
@@ -556,13 +889,12 @@ impl RenderingAPI for VulkanRenderer {
                 self.wireframe_pipeline,
             );
 
-            self.context.device.cmd_push_constants(
+            self.bind_material(
                 frame.command_buffer,
-                self.pipeline_layout,
-                vk::ShaderStageFlags::VERTEX,
-                0,
-                &data,
-            );
+                mesh.as_ref(),
+                &push_constants,
+                model_push_constants,
+            )?;
 
             self.context.device.cmd_bind_vertex_buffers(
                 frame.command_buffer,
@@ -585,6 +917,7 @@ impl RenderingAPI for VulkanRenderer {
                 0,
             );
         }
+        self.draw_calls += 1;
 
         Ok(())
     }
@@ -599,11 +932,16 @@ impl RenderingAPI for VulkanRenderer {
         let frame = &self.frames[self.current_frame];
         let mut data = push_constants.return_renderable();
         data.extend(voxel_push_constants.return_renderable());
+        let pipeline = if self.wireframe {
+            self.voxel_wireframe_pipeline
+        } else {
+            self.voxel_pipeline
+        };
         unsafe {
             self.context.device.cmd_bind_pipeline(
                 frame.command_buffer,
                 vk::PipelineBindPoint::GRAPHICS,
-                self.voxel_pipeline,
+                pipeline,
             );
             self.context.device.cmd_push_constants(
                 frame.command_buffer,
@@ -641,6 +979,7 @@ impl RenderingAPI for VulkanRenderer {
                 0,
             );
         }
+        self.draw_calls += 1;
         Ok(())
     }
 
@@ -696,9 +1035,169 @@ impl RenderingAPI for VulkanRenderer {
                 0,
             );
         }
+        self.draw_calls += 1;
+        Ok(())
+    }
+
+    fn particle_render(
+        &mut self,
+        push_constants: &PushConstants,
+        particle_push_constants: &ParticlePushConstants,
+    ) -> Result<()> {
+        if particle_push_constants.count == 0 {
+            return Ok(());
+        }
+
+        let frame = &self.frames[self.current_frame];
+        let mut data = push_constants.return_renderable();
+        data.extend(particle_push_constants.return_renderable());
+        unsafe {
+            self.context.device.cmd_bind_pipeline(
+                frame.command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.particle_pipeline,
+            );
+            self.context.device.cmd_push_constants(
+                frame.command_buffer,
+                self.particle_pipeline_layout,
+                vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                0,
+                &data,
+            );
+            // one quad per instance
+            self.context.device.cmd_draw(
+                frame.command_buffer,
+                6,
+                particle_push_constants.count,
+                0,
+                0,
+            );
+        }
+        self.draw_calls += 1;
         Ok(())
     }
 
+    fn grid_render(
+        &mut self,
+        push_constants: &PushConstants,
+        grid_push_constants: &GridPushConstants,
+    ) -> Result<()> {
+        let frame = &self.frames[self.current_frame];
+        let mut data = push_constants.return_renderable();
+        data.extend(grid_push_constants.return_renderable());
+        unsafe {
+            self.context.device.cmd_bind_pipeline(
+                frame.command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.grid_pipeline,
+            );
+            self.context.device.cmd_push_constants(
+                frame.command_buffer,
+                self.grid_pipeline_layout,
+                vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                0,
+                &data,
+            );
+            // a single quad generated in the vertex shader
+            self.context
+                .device
+                .cmd_draw(frame.command_buffer, 6, 1, 0, 0);
+        }
+        self.draw_calls += 1;
+        Ok(())
+    }
+
+    fn ambient_occlusion_render(&mut self, push_constants: &PushConstants) -> Result<()> {
+        if !self.ambient_occlusion.enabled {
+            return Ok(());
+        }
+        let Some(ao_pass) = &mut self.ao_pass else {
+            return Ok(());
+        };
+        ao_pass.record(
+            &self.context,
+            self.frames[self.current_frame].command_buffer,
+            self.swapchain.extent,
+            self.swapchain.views[self.current_image_index as usize],
+            self.swapchain.depth_image,
+            self.swapchain.depth_image_view,
+            push_constants.projection_matrix,
+            &self.ambient_occlusion,
+        )?;
+        self.draw_calls += 1;
+        Ok(())
+    }
+
+    fn outline_mesh(
+        &mut self,
+        mesh: Box<dyn GpuMesh>,
+        push_constants: &PushConstants,
+        model_push_constants: &ModelPushConstants,
+    ) {
+        let Some(outline_pass) = &mut self.outline_pass else {
+            return;
+        };
+        let mut data = push_constants.return_renderable();
+        data.extend(model_push_constants.return_renderable());
+        data.extend(1u32.to_ne_bytes());
+        outline_pass.draws.push(IdDraw { mesh, data });
+    }
+
+    fn outline_render(&mut self, outline_push_constants: &OutlinePushConstants) -> Result<()> {
+        let Some(outline_pass) = &mut self.outline_pass else {
+            return Ok(());
+        };
+        if outline_pass.draws.is_empty() {
+            return Ok(());
+        }
+        self.draw_calls += outline_pass.draws.len() as u32 + 1;
+        outline_pass.record(
+            &self.context,
+            self.frames[self.current_frame].command_buffer,
+            self.swapchain.extent,
+            self.swapchain.views[self.current_image_index as usize],
+            self.swapchain.depth_image_view,
+            self.swapchain.depth_format,
+            &self.image_layouts,
+            &outline_push_constants.return_renderable(),
+        )
+    }
+
+    fn begin_map(&mut self, size: u32, clear_color: [f32; 4]) {
+        self.map_pass.request = Some((size, clear_color));
+    }
+
+    fn map_render(
+        &mut self,
+        mesh: Box<dyn GpuMesh>,
+        atlas: &VoxelTextureAtlas,
+        push_constants: &PushConstants,
+        voxel_push_constants: &VoxelPushConstants,
+        is_water: bool,
+    ) {
+        if self.map_pass.request.is_none() {
+            return;
+        }
+        let mut data = push_constants.return_renderable();
+        data.extend(voxel_push_constants.return_renderable());
+        let (pipeline, pipeline_layout) = if is_water {
+            (self.water_pipeline, self.water_pipeline_layout)
+        } else {
+            (self.voxel_pipeline, self.voxel_pipeline_layout)
+        };
+        self.map_pass.draws.push(MapDraw {
+            mesh,
+            pipeline,
+            pipeline_layout,
+            descriptor_set: atlas.descriptor_set,
+            data,
+        });
+    }
+
+    fn map_texture(&self) -> Option<TextureId> {
+        self.map_pass.texture_id.filter(|_| self.map_pass.is_drawn)
+    }
+
     fn begin_ui(&mut self) {
         let raw_input = self
             .ui_renderer
@@ -782,4 +1281,137 @@ impl RenderingAPI for VulkanRenderer {
     fn get_voxel_descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
         self.voxel_descriptor_set_layout
     }
+    fn get_material_descriptor_pool(&self) -> vk::DescriptorPool {
+        self.material_descriptor_pool
+    }
+    fn get_material_descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
+        self.material_descriptor_set_layout
+    }
+
+    fn apply_config(&mut self, config: &RenderingConfig) {
+        let present_mode = config.present_mode.to_vk();
+        if self.swapchain.present_mode != present_mode {
+            self.swapchain.present_mode = present_mode;
+            self.swapchain.is_dirty = true;
+        }
+        self.wireframe = config.wireframe;
+        self.ambient_occlusion = config.ambient_occlusion;
+    }
+
+    fn set_clear_color(&mut self, color: [f32; 4]) {
+        self.clear_color = color;
+    }
+
+    fn request_pick(&mut self, x: u32, y: u32) -> usize {
+        self.id_pass.request = Some((x, y));
+        self.current_frame
+    }
+
+    fn id_render(
+        &mut self,
+        mesh: Box<dyn GpuMesh>,
+        push_constants: &PushConstants,
+        model_push_constants: &ModelPushConstants,
+        id: u32,
+    ) {
+        if self.id_pass.request.is_none() {
+            return;
+        }
+        let mut data = push_constants.return_renderable();
+        data.extend(model_push_constants.return_renderable());
+        data.extend(id.to_ne_bytes());
+        self.id_pass.draws.push(IdDraw { mesh, data });
+    }
+
+    fn take_pick_result(&mut self) -> Option<(usize, u32)> {
+        self.id_pass.result.take()
+    }
+
+    fn destroy(&mut self) {
+        unsafe {
+            if let Err(e) = self.context.device.device_wait_idle() {
+                log_warn!("Failed to wait for the device to destroy a renderer: {}", e);
+            }
+            for (buffer, memory) in self
+                .retired_buffers
+                .iter_mut()
+                .flat_map(|buffers| buffers.drain(..))
+                .chain(self.buffer_graveyard.drain(..))
+            {
+                self.context.device.destroy_buffer(buffer, None);
+                self.context.free_memory(memory);
+            }
+            for frame in self.frames.drain(..) {
+                self.context
+                    .device
+                    .destroy_semaphore(frame.image_available_semaphore, None);
+                self.context
+                    .device
+                    .destroy_semaphore(frame.render_finished_semaphore, None);
+                self.context
+                    .device
+                    .destroy_fence(frame.in_flight_fence, None);
+            }
+            for pipeline in [
+                self.pipeline,
+                self.wireframe_pipeline,
+                self.voxel_pipeline,
+                self.voxel_wireframe_pipeline,
+                self.water_pipeline,
+                self.particle_pipeline,
+                self.grid_pipeline,
+            ] {
+                self.context.device.destroy_pipeline(pipeline, None);
+            }
+            for layout in [
+                self.pipeline_layout,
+                self.voxel_pipeline_layout,
+                self.water_pipeline_layout,
+                self.particle_pipeline_layout,
+                self.grid_pipeline_layout,
+            ] {
+                self.context.device.destroy_pipeline_layout(layout, None);
+            }
+            self.context
+                .device
+                .destroy_descriptor_pool(self.voxel_descriptor_pool, None);
+            self.context
+                .device
+                .destroy_descriptor_set_layout(self.voxel_descriptor_set_layout, None);
+            self.context
+                .device
+                .destroy_descriptor_pool(self.material_descriptor_pool, None);
+            self.context
+                .device
+                .destroy_descriptor_set_layout(self.material_descriptor_set_layout, None);
+            if !self.timestamp_pool.is_null() {
+                self.context
+                    .device
+                    .destroy_query_pool(self.timestamp_pool, None);
+            }
+            self.context.device.destroy_buffer(self.ubo.buffer, None);
+            self.context.free_memory(self.ubo.memory);
+            self.context
+                .device
+                .destroy_command_pool(self.command_pool, None);
+        }
+        self.id_pass.destroy(&self.context);
+        if let Some(ao_pass) = &mut self.ao_pass {
+            ao_pass.destroy(&self.context);
+        }
+        if let Some(outline_pass) = &mut self.outline_pass {
+            outline_pass.destroy(&self.context);
+        }
+        self.map_pass.destroy(&self.context);
+        self.swapchain.destroy();
+    }
+
+    fn get_stats(&self) -> RenderingStats {
+        RenderingStats {
+            draw_calls: self.draw_calls,
+            gpu_time_ms: self.gpu_time_ms,
+            gpu_memory_bytes: self.context.allocated_bytes(),
+            ambient_occlusion_available: self.ao_pass.is_some(),
+        }
+    }
 }
@@ -0,0 +1,466 @@
+use anyhow::Result;
+use ash::vk::{self, Handle};
+
+use crate::assets::shader_loader::load_shader_bytes;
+use crate::rendering::vulkan::{
+    image_layout::{ImageLayoutState, ImageLayouts},
+    picking::{ID_PUSH_CONSTANTS_SIZE, IdDraw},
+    rendering_context::VulkanRenderingContext,
+};
+
+/// The size of the colour and thickness push constants
+pub const OUTLINE_PUSH_CONSTANTS_SIZE: u32 = 32;
+
+/// Outlines the queued meshes, they are drawn with the id pipeline into a mask and a fullscreen
+/// triangle colours the empty pixels next to covered ones
+/// Note: the mask has its own depth, so the outline shows through whatever is in front
+pub struct OutlinePass {
+    pub mask_pipeline: vk::Pipeline,
+    pub mask_pipeline_layout: vk::PipelineLayout,
+    pub composite_pipeline: vk::Pipeline,
+    pub composite_pipeline_layout: vk::PipelineLayout,
+    pub set_layout: vk::DescriptorSetLayout,
+    pub descriptor_pool: vk::DescriptorPool,
+    pub set: vk::DescriptorSet,
+    pub sampler: vk::Sampler,
+    pub extent: vk::Extent2D,
+    pub image: vk::Image,
+    pub memory: vk::DeviceMemory,
+    pub view: vk::ImageView,
+    pub depth_image: vk::Image,
+    pub depth_memory: vk::DeviceMemory,
+    pub depth_view: vk::ImageView,
+    pub draws: Vec<IdDraw>,
+}
+
+impl OutlinePass {
+    /// Loads the composite shaders and creates the pass, the mask is drawn with the model vertex
+    /// shader and the id fragment shader
+    pub fn load(
+        context: &VulkanRenderingContext,
+        vertex_shader: vk::ShaderModule,
+        id_fragment_shader: vk::ShaderModule,
+        image_format: vk::Format,
+        depth_format: vk::Format,
+    ) -> Result<Self> {
+        let mut modules = Vec::new();
+        for name in ["fullscreen.vert", "outline.frag"] {
+            match load_shader_bytes(name).and_then(|code| Ok(context.create_shader_module(&code)?))
+            {
+                Ok(module) => modules.push(module),
+                Err(e) => {
+                    for module in modules {
+                        unsafe { context.device.destroy_shader_module(module, None) };
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let pass = Self::new(
+            context,
+            vertex_shader,
+            id_fragment_shader,
+            modules[0],
+            modules[1],
+            image_format,
+            depth_format,
+        );
+        for module in modules {
+            unsafe { context.device.destroy_shader_module(module, None) };
+        }
+        pass
+    }
+
+    pub fn new(
+        context: &VulkanRenderingContext,
+        mask_vertex_shader: vk::ShaderModule,
+        mask_fragment_shader: vk::ShaderModule,
+        composite_vertex_shader: vk::ShaderModule,
+        composite_fragment_shader: vk::ShaderModule,
+        image_format: vk::Format,
+        depth_format: vk::Format,
+    ) -> Result<Self> {
+        unsafe {
+            let mask_pipeline_layout = context.device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default().push_constant_ranges(&[
+                    vk::PushConstantRange::default()
+                        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+                        .offset(0)
+                        .size(ID_PUSH_CONSTANTS_SIZE),
+                ]),
+                None,
+            )?;
+            let mask_pipeline = context.create_id_pipeline(
+                mask_vertex_shader,
+                mask_fragment_shader,
+                vk::Extent2D {
+                    width: 1,
+                    height: 1,
+                },
+                depth_format,
+                mask_pipeline_layout,
+                Default::default(),
+            )?;
+
+            let set_layout = context.device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&[
+                    vk::DescriptorSetLayoutBinding::default()
+                        .binding(0)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::FRAGMENT),
+                ]),
+                None,
+            )?;
+            let descriptor_pool = context.device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default()
+                    .max_sets(1)
+                    .pool_sizes(&[vk::DescriptorPoolSize {
+                        ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                        descriptor_count: 1,
+                    }]),
+                None,
+            )?;
+            let set = context.device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(descriptor_pool)
+                    .set_layouts(&[set_layout]),
+            )?[0];
+            // the mask holds integers, which can't be filtered
+            let sampler = context.device.create_sampler(
+                &vk::SamplerCreateInfo::default()
+                    .mag_filter(vk::Filter::NEAREST)
+                    .min_filter(vk::Filter::NEAREST)
+                    .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                    .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                    .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE),
+                None,
+            )?;
+
+            let composite_pipeline_layout = context.device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default()
+                    .set_layouts(&[set_layout])
+                    .push_constant_ranges(&[vk::PushConstantRange::default()
+                        .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                        .offset(0)
+                        .size(OUTLINE_PUSH_CONSTANTS_SIZE)]),
+                None,
+            )?;
+            let composite_pipeline = context.create_overlay_pipeline(
+                composite_vertex_shader,
+                composite_fragment_shader,
+                image_format,
+                depth_format,
+                composite_pipeline_layout,
+            )?;
+
+            Ok(Self {
+                mask_pipeline,
+                mask_pipeline_layout,
+                composite_pipeline,
+                composite_pipeline_layout,
+                set_layout,
+                descriptor_pool,
+                set,
+                sampler,
+                extent: vk::Extent2D::default(),
+                image: vk::Image::null(),
+                memory: vk::DeviceMemory::null(),
+                view: vk::ImageView::null(),
+                depth_image: vk::Image::null(),
+                depth_memory: vk::DeviceMemory::null(),
+                depth_view: vk::ImageView::null(),
+                draws: Vec::new(),
+            })
+        }
+    }
+
+    /// Recreates the mask and rewrites the descriptor set if the extent has changed
+    fn resize(
+        &mut self,
+        context: &VulkanRenderingContext,
+        extent: vk::Extent2D,
+        depth_format: vk::Format,
+    ) -> Result<()> {
+        if self.extent == extent && !self.image.is_null() {
+            return Ok(());
+        }
+
+        unsafe {
+            if !self.image.is_null() {
+                // earlier frames may still read the mask and set
+                context.device.device_wait_idle()?;
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+                context.device.destroy_image_view(self.depth_view, None);
+                context.device.destroy_image(self.depth_image, None);
+                context.free_memory(self.depth_memory);
+            }
+
+            let (image, memory) = context.create_image(
+                extent,
+                vk::Format::R32_UINT,
+                vk::ImageTiling::OPTIMAL,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            let (depth_image, depth_memory) = context.create_image(
+                extent,
+                depth_format,
+                vk::ImageTiling::OPTIMAL,
+                vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            let view = context.create_image_view(
+                image,
+                vk::Format::R32_UINT,
+                vk::ImageAspectFlags::COLOR,
+            )?;
+            let depth_view = context.create_image_view(
+                depth_image,
+                depth_format,
+                vk::ImageAspectFlags::DEPTH,
+            )?;
+
+            let mask_info = [vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .image_view(view)
+                .sampler(self.sampler)];
+            context.device.update_descriptor_sets(
+                &[vk::WriteDescriptorSet::default()
+                    .dst_set(self.set)
+                    .dst_binding(0)
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .image_info(&mask_info)],
+                &[],
+            );
+
+            self.image = image;
+            self.memory = memory;
+            self.view = view;
+            self.depth_image = depth_image;
+            self.depth_memory = depth_memory;
+            self.depth_view = depth_view;
+        }
+        self.extent = extent;
+        Ok(())
+    }
+
+    /// Frees the pipelines, set and targets of the pass, the device must be idle
+    pub fn destroy(&mut self, context: &VulkanRenderingContext) {
+        unsafe {
+            if !self.image.is_null() {
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+                context.device.destroy_image_view(self.depth_view, None);
+                context.device.destroy_image(self.depth_image, None);
+                context.free_memory(self.depth_memory);
+                self.image = vk::Image::null();
+            }
+            context.device.destroy_sampler(self.sampler, None);
+            context
+                .device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+            context
+                .device
+                .destroy_descriptor_set_layout(self.set_layout, None);
+            for pipeline in [self.mask_pipeline, self.composite_pipeline] {
+                context.device.destroy_pipeline(pipeline, None);
+            }
+            for layout in [self.mask_pipeline_layout, self.composite_pipeline_layout] {
+                context.device.destroy_pipeline_layout(layout, None);
+            }
+        }
+        self.draws.clear();
+    }
+
+    /// Draws the queued meshes into the mask and the outline over the frame, nothing if no mesh
+    /// was queued
+    /// Note: must be called while rendering to `color_view` and `depth_view`, the rendering is
+    /// ended for the mask and resumed with the targets' contents kept
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &mut self,
+        context: &VulkanRenderingContext,
+        command_buffer: vk::CommandBuffer,
+        extent: vk::Extent2D,
+        color_view: vk::ImageView,
+        depth_view: vk::ImageView,
+        depth_format: vk::Format,
+        layouts: &ImageLayouts,
+        push_constants: &[u8],
+    ) -> Result<()> {
+        if self.draws.is_empty() {
+            return Ok(());
+        }
+        if extent.width == 0 || extent.height == 0 {
+            self.draws.clear();
+            return Ok(());
+        }
+
+        // the last frame's composite may still read the mask, its contents are not needed
+        let previous_frame = ImageLayoutState {
+            layout: vk::ImageLayout::UNDEFINED,
+            access_mask: vk::AccessFlags::empty(),
+            stage_mask: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let sampled = ImageLayoutState {
+            layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            access_mask: vk::AccessFlags::SHADER_READ,
+            stage_mask: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let viewport = vk::Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: extent.width as f32,
+            height: extent.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        };
+
+        unsafe {
+            context.device.cmd_end_rendering(command_buffer);
+            self.resize(context, extent, depth_format)?;
+
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                previous_frame,
+                layouts.renderable,
+                vk::ImageAspectFlags::COLOR,
+            );
+            context.transition_image_layout(
+                command_buffer,
+                self.depth_image,
+                layouts.undefined,
+                layouts.depth,
+                vk::ImageAspectFlags::DEPTH,
+            );
+
+            context.begin_rendering(
+                command_buffer,
+                self.view,
+                self.depth_view,
+                vk::ClearColorValue { uint32: [0; 4] },
+                vk::Rect2D::default().extent(extent),
+            );
+            context
+                .device
+                .cmd_set_viewport(command_buffer, 0, &[viewport]);
+            context.device.cmd_set_scissor(
+                command_buffer,
+                0,
+                &[vk::Rect2D::default().extent(extent)],
+            );
+            context.device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.mask_pipeline,
+            );
+            for draw in self.draws.drain(..) {
+                context.device.cmd_push_constants(
+                    command_buffer,
+                    self.mask_pipeline_layout,
+                    vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    &draw.data,
+                );
+                context.device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &[draw.mesh.get_vertex_buffer()],
+                    &[0],
+                );
+                context.device.cmd_bind_index_buffer(
+                    command_buffer,
+                    draw.mesh.get_index_buffer(),
+                    0,
+                    vk::IndexType::UINT32,
+                );
+                context.device.cmd_draw_indexed(
+                    command_buffer,
+                    draw.mesh.get_index_count(),
+                    1,
+                    0,
+                    0,
+                    0,
+                );
+            }
+            context.device.cmd_end_rendering(command_buffer);
+
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                layouts.renderable,
+                sampled,
+                vk::ImageAspectFlags::COLOR,
+            );
+
+            // the colour and depth written before the pass are loaded again
+            let barrier = vk::MemoryBarrier::default()
+                .src_access_mask(
+                    vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                        | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                )
+                .dst_access_mask(
+                    vk::AccessFlags::COLOR_ATTACHMENT_READ
+                        | vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                        | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                        | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                );
+            context.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                    | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                    | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+                vk::DependencyFlags::empty(),
+                &[barrier],
+                &[],
+                &[],
+            );
+
+            context.resume_rendering(
+                command_buffer,
+                color_view,
+                depth_view,
+                vk::Rect2D::default().extent(extent),
+            );
+            context
+                .device
+                .cmd_set_viewport(command_buffer, 0, &[viewport]);
+            context.device.cmd_set_scissor(
+                command_buffer,
+                0,
+                &[vk::Rect2D::default().extent(extent)],
+            );
+            context.device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.composite_pipeline,
+            );
+            context.device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.composite_pipeline_layout,
+                0,
+                &[self.set],
+                &[],
+            );
+            context.device.cmd_push_constants(
+                command_buffer,
+                self.composite_pipeline_layout,
+                vk::ShaderStageFlags::FRAGMENT,
+                0,
+                push_constants,
+            );
+            context.device.cmd_draw(command_buffer, 3, 1, 0, 0);
+        }
+        Ok(())
+    }
+}
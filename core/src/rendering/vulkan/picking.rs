@@ -0,0 +1,322 @@
+use anyhow::Result;
+use ash::vk::{self, Handle};
+
+use crate::rendering::{
+    shared::model::GpuMesh,
+    vulkan::{
+        image_layout::{ImageLayoutState, ImageLayouts},
+        readback::Readback,
+        rendering_context::VulkanRenderingContext,
+    },
+};
+
+/// The size of the camera, model and id push constants
+pub const ID_PUSH_CONSTANTS_SIZE: u32 = 180;
+
+/// A mesh queued for the id pass
+pub struct IdDraw {
+    pub mesh: Box<dyn GpuMesh>,
+    pub data: Vec<u8>,
+}
+
+/// An offscreen pass writing an id per pixel to an R32_UINT target
+/// Only recorded on frames where a pixel was requested, the pixel is read back once the frame
+/// that recorded it has finished, each frame in flight copies into its own readback
+pub struct IdPass {
+    pub pipeline: vk::Pipeline,
+    pub pipeline_layout: vk::PipelineLayout,
+    pub extent: vk::Extent2D,
+    pub image: vk::Image,
+    pub memory: vk::DeviceMemory,
+    pub view: vk::ImageView,
+    pub depth_image: vk::Image,
+    pub depth_memory: vk::DeviceMemory,
+    pub depth_view: vk::ImageView,
+    /// one per frame in flight so a pick doesn't overwrite one still being copied
+    pub readbacks: Vec<Readback>,
+    pub draws: Vec<IdDraw>,
+    /// the pixel to copy at the end of the frame
+    pub request: Option<(u32, u32)>,
+    /// the frame the last id was recorded in and the id read back
+    pub result: Option<(usize, u32)>,
+}
+
+impl IdPass {
+    pub fn new(
+        context: &VulkanRenderingContext,
+        vertex_shader: vk::ShaderModule,
+        fragment_shader: vk::ShaderModule,
+        depth_format: vk::Format,
+        frames_in_flight: usize,
+    ) -> Result<Self> {
+        let pipeline_layout = unsafe {
+            context.device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default().push_constant_ranges(&[
+                    vk::PushConstantRange::default()
+                        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+                        .offset(0)
+                        .size(ID_PUSH_CONSTANTS_SIZE),
+                ]),
+                None,
+            )?
+        };
+
+        let pipeline = context.create_id_pipeline(
+            vertex_shader,
+            fragment_shader,
+            vk::Extent2D {
+                width: 1,
+                height: 1,
+            },
+            depth_format,
+            pipeline_layout,
+            Default::default(),
+        )?;
+
+        let readbacks = (0..frames_in_flight)
+            .map(|_| Readback::new(context, size_of::<u32>() as vk::DeviceSize))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            pipeline,
+            pipeline_layout,
+            extent: vk::Extent2D::default(),
+            image: vk::Image::null(),
+            memory: vk::DeviceMemory::null(),
+            view: vk::ImageView::null(),
+            depth_image: vk::Image::null(),
+            depth_memory: vk::DeviceMemory::null(),
+            depth_view: vk::ImageView::null(),
+            readbacks,
+            draws: Vec::new(),
+            request: None,
+            result: None,
+        })
+    }
+
+    /// Recreates the targets if the extent has changed
+    fn resize(
+        &mut self,
+        context: &VulkanRenderingContext,
+        extent: vk::Extent2D,
+        depth_format: vk::Format,
+    ) -> Result<()> {
+        if self.extent == extent && !self.image.is_null() {
+            return Ok(());
+        }
+
+        unsafe {
+            if !self.image.is_null() {
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+                context.device.destroy_image_view(self.depth_view, None);
+                context.device.destroy_image(self.depth_image, None);
+                context.free_memory(self.depth_memory);
+            }
+        }
+
+        let (image, memory) = context.create_image(
+            extent,
+            vk::Format::R32_UINT,
+            vk::ImageTiling::OPTIMAL,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+        let (depth_image, depth_memory) = context.create_image(
+            extent,
+            depth_format,
+            vk::ImageTiling::OPTIMAL,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+
+        self.view =
+            context.create_image_view(image, vk::Format::R32_UINT, vk::ImageAspectFlags::COLOR)?;
+        self.depth_view =
+            context.create_image_view(depth_image, depth_format, vk::ImageAspectFlags::DEPTH)?;
+        self.image = image;
+        self.memory = memory;
+        self.depth_image = depth_image;
+        self.depth_memory = depth_memory;
+        self.extent = extent;
+        Ok(())
+    }
+
+    /// Frees the pipeline, the targets and the readbacks, the device must be idle
+    pub fn destroy(&mut self, context: &VulkanRenderingContext) {
+        unsafe {
+            if !self.image.is_null() {
+                context.device.destroy_image_view(self.view, None);
+                context.device.destroy_image(self.image, None);
+                context.free_memory(self.memory);
+                context.device.destroy_image_view(self.depth_view, None);
+                context.device.destroy_image(self.depth_image, None);
+                context.free_memory(self.depth_memory);
+                self.image = vk::Image::null();
+            }
+            context.device.destroy_pipeline(self.pipeline, None);
+            context
+                .device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+        }
+        for readback in &mut self.readbacks {
+            readback.destroy(context);
+        }
+        self.draws.clear();
+    }
+
+    /// Records the queued draws and the copy of the requested pixel
+    /// Note: must be called outside of any other rendering
+    pub fn record(
+        &mut self,
+        context: &VulkanRenderingContext,
+        command_buffer: vk::CommandBuffer,
+        frame: usize,
+        extent: vk::Extent2D,
+        depth_format: vk::Format,
+        layouts: &ImageLayouts,
+    ) -> Result<()> {
+        let Some((x, y)) = self.request.take() else {
+            self.draws.clear();
+            return Ok(());
+        };
+        if extent.width == 0 || extent.height == 0 {
+            self.draws.clear();
+            return Ok(());
+        }
+        self.resize(context, extent, depth_format)?;
+
+        let transfer_src = ImageLayoutState {
+            layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            access_mask: vk::AccessFlags::TRANSFER_READ,
+            stage_mask: vk::PipelineStageFlags::TRANSFER,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+
+        unsafe {
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                layouts.undefined,
+                layouts.renderable,
+                vk::ImageAspectFlags::COLOR,
+            );
+            context.transition_image_layout(
+                command_buffer,
+                self.depth_image,
+                layouts.undefined,
+                layouts.depth,
+                vk::ImageAspectFlags::DEPTH,
+            );
+
+            context.begin_rendering(
+                command_buffer,
+                self.view,
+                self.depth_view,
+                vk::ClearColorValue { uint32: [0; 4] },
+                vk::Rect2D::default().extent(extent),
+            );
+            context.device.cmd_set_viewport(
+                command_buffer,
+                0,
+                &[vk::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: extent.width as f32,
+                    height: extent.height as f32,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                }],
+            );
+            context.device.cmd_set_scissor(
+                command_buffer,
+                0,
+                &[vk::Rect2D::default().extent(extent)],
+            );
+            context.device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline,
+            );
+
+            for draw in self.draws.drain(..) {
+                context.device.cmd_push_constants(
+                    command_buffer,
+                    self.pipeline_layout,
+                    vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    &draw.data,
+                );
+                context.device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &[draw.mesh.get_vertex_buffer()],
+                    &[0],
+                );
+                context.device.cmd_bind_index_buffer(
+                    command_buffer,
+                    draw.mesh.get_index_buffer(),
+                    0,
+                    vk::IndexType::UINT32,
+                );
+                context.device.cmd_draw_indexed(
+                    command_buffer,
+                    draw.mesh.get_index_count(),
+                    1,
+                    0,
+                    0,
+                    0,
+                );
+            }
+
+            context.device.cmd_end_rendering(command_buffer);
+
+            context.transition_image_layout(
+                command_buffer,
+                self.image,
+                layouts.renderable,
+                transfer_src,
+                vk::ImageAspectFlags::COLOR,
+            );
+        }
+
+        self.readbacks[frame].record_image_copy(
+            context,
+            command_buffer,
+            frame,
+            self.image,
+            vk::BufferImageCopy::default()
+                .image_subresource(
+                    vk::ImageSubresourceLayers::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .layer_count(1),
+                )
+                .image_offset(vk::Offset3D {
+                    x: x.min(extent.width - 1) as i32,
+                    y: y.min(extent.height - 1) as i32,
+                    z: 0,
+                })
+                .image_extent(vk::Extent3D {
+                    width: 1,
+                    height: 1,
+                    depth: 1,
+                }),
+            size_of::<u32>() as u32,
+        )
+    }
+
+    /// Reads the copied id back if it was recorded in `completed_frame`
+    /// Note: must only be called once the fence of `completed_frame` has been waited on
+    pub fn read_result(
+        &mut self,
+        context: &VulkanRenderingContext,
+        completed_frame: usize,
+    ) -> Result<()> {
+        if let Some(bytes) = self.readbacks[completed_frame].try_read(context, completed_frame)? {
+            let id = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            self.result = Some((completed_frame, id));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use ash::vk;
+
+use crate::rendering::vulkan::rendering_context::VulkanRenderingContext;
+
+/// Copies images and buffers into host visible memory and reads them back without stalling
+/// Record a copy in a frame's command buffer, then read it once that frame's fence has been
+/// waited on, frames in flight finish out of step with the frame being recorded
+pub struct Readback {
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: vk::DeviceSize,
+    /// the frame the copy was recorded in, none when nothing is waiting
+    pending_frame: Option<usize>,
+    /// how many bytes the pending copy writes
+    pending_size: vk::DeviceSize,
+}
+
+impl Readback {
+    pub fn new(context: &VulkanRenderingContext, size: vk::DeviceSize) -> Result<Self> {
+        let (buffer, memory) = context.create_buffer(
+            size,
+            vk::BufferUsageFlags::TRANSFER_DST,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+        Ok(Self {
+            buffer,
+            memory,
+            size,
+            pending_frame: None,
+            pending_size: 0,
+        })
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending_frame.is_some()
+    }
+
+    /// Grows the buffer to fit a copy, the pending copy is dropped if the buffer is recreated
+    fn reserve(&mut self, context: &VulkanRenderingContext, size: vk::DeviceSize) -> Result<()> {
+        if size <= self.size {
+            return Ok(());
+        }
+        if self.pending_frame.is_some() {
+            // the old buffer may still be written to
+            unsafe { context.device.device_wait_idle()? };
+            self.pending_frame = None;
+        }
+        let replacement = Self::new(context, size)?;
+        self.destroy(context);
+        *self = replacement;
+        Ok(())
+    }
+
+    /// Records a copy of part of an image, the image must be in TRANSFER_SRC_OPTIMAL
+    /// Note: `bytes_per_pixel` must match the format of the image, the buffer fields of the region
+    /// are ignored
+    pub fn record_image_copy(
+        &mut self,
+        context: &VulkanRenderingContext,
+        command_buffer: vk::CommandBuffer,
+        frame: usize,
+        image: vk::Image,
+        region: vk::BufferImageCopy,
+        bytes_per_pixel: u32,
+    ) -> Result<()> {
+        let extent = region.image_extent;
+        let size = extent.width as vk::DeviceSize
+            * extent.height as vk::DeviceSize
+            * extent.depth as vk::DeviceSize
+            * bytes_per_pixel as vk::DeviceSize;
+        self.reserve(context, size)?;
+
+        unsafe {
+            context.device.cmd_copy_image_to_buffer(
+                command_buffer,
+                image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                self.buffer,
+                &[region
+                    .buffer_offset(0)
+                    .buffer_row_length(0)
+                    .buffer_image_height(0)],
+            );
+        }
+
+        self.pending_frame = Some(frame);
+        self.pending_size = size;
+        Ok(())
+    }
+
+    /// Records a copy of part of a buffer, the buffer needs TRANSFER_SRC usage
+    pub fn record_buffer_copy(
+        &mut self,
+        context: &VulkanRenderingContext,
+        command_buffer: vk::CommandBuffer,
+        frame: usize,
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        size: vk::DeviceSize,
+    ) -> Result<()> {
+        self.reserve(context, size)?;
+
+        unsafe {
+            context.device.cmd_copy_buffer(
+                command_buffer,
+                buffer,
+                self.buffer,
+                &[vk::BufferCopy::default().src_offset(offset).size(size)],
+            );
+        }
+
+        self.pending_frame = Some(frame);
+        self.pending_size = size;
+        Ok(())
+    }
+
+    /// Reads the copy back if it was recorded in `completed_frame`
+    /// Note: must only be called once the fence of `completed_frame` has been waited on
+    pub fn try_read(
+        &mut self,
+        context: &VulkanRenderingContext,
+        completed_frame: usize,
+    ) -> Result<Option<Vec<u8>>> {
+        if self.pending_frame != Some(completed_frame) {
+            return Ok(None);
+        }
+        self.pending_frame = None;
+
+        let mut bytes = vec![0u8; self.pending_size as usize];
+        unsafe {
+            let ptr = context.device.map_memory(
+                self.memory,
+                0,
+                self.pending_size,
+                vk::MemoryMapFlags::empty(),
+            )? as *const u8;
+            std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), bytes.len());
+            context.device.unmap_memory(self.memory);
+        }
+        Ok(Some(bytes))
+    }
+
+    /// Frees the buffer, the pending copy must have finished
+    pub fn destroy(&mut self, context: &VulkanRenderingContext) {
+        unsafe {
+            context.device.destroy_buffer(self.buffer, None);
+            context.free_memory(self.memory);
+        }
+        self.buffer = vk::Buffer::null();
+        self.memory = vk::DeviceMemory::null();
+        self.size = 0;
+        self.pending_frame = None;
+    }
+}
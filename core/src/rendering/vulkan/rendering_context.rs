@@ -1,7 +1,8 @@
 use std::collections::HashSet;
 use std::io;
+use std::sync::{Arc, Mutex};
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use apostasy_macros::Resource;
 use ash::Device;
 use ash::Entry;
@@ -93,6 +94,8 @@ use winit::raw_window_handle::HasDisplayHandle;
 use winit::raw_window_handle::HasWindowHandle;
 use winit::window::Window;
 
+use crate::log;
+use crate::rendering::shared::gpu::{GpuAdapters, pick_adapter};
 use crate::rendering::shared::vertex::Vertex;
 use crate::rendering::shared::vertex::VertexDefinition;
 use crate::rendering::vulkan::device::PhysicalDevice;
@@ -106,6 +109,8 @@ use crate::voxels::meshes::VoxelVertex;
 pub struct RenderingContextAttributes<'window> {
     pub compatability_window: &'window Window,
     pub queue_family_picker: QueueFamilyPicker,
+    /// the adapter to use, its index or part of its name, see `pick_adapter`
+    pub preferred_gpu: Option<String>,
 }
 
 #[derive(Clone, Resource)]
@@ -119,6 +124,10 @@ pub struct VulkanRenderingContext {
     pub instance: Instance,
     pub entry: Entry,
     pub swapchain_extension: swapchain::Device,
+    /// every adapter that can draw to the window and the one picked
+    pub adapters: GpuAdapters,
+    /// the size of every live allocation, shared by clones, see `allocated_bytes`
+    allocations: Arc<Mutex<HashMap<DeviceMemory, DeviceSize>>>,
 }
 
 impl VulkanRenderingContext {
@@ -184,9 +193,37 @@ impl VulkanRenderingContext {
 
             surface_extension.destroy_surface(compatability_surface, None);
 
+            if physical_devices.is_empty() {
+                return Err(anyhow!("No GPU can draw to the window"));
+            }
+            let infos = physical_devices
+                .iter()
+                .map(PhysicalDevice::info)
+                .collect::<Vec<_>>();
+            let handles = physical_devices
+                .iter()
+                .map(|device| device.handle)
+                .collect::<Vec<_>>();
+            // the picker takes the first device it can use, so the chosen one goes first
+            if let Some(index) = pick_adapter(&infos, attributes.preferred_gpu.as_deref()) {
+                let chosen = physical_devices.remove(index);
+                physical_devices.insert(0, chosen);
+            }
+
             let (physical_device, queue_family) =
                 (attributes.queue_family_picker)(physical_devices)?;
 
+            let adapters = GpuAdapters {
+                active: handles
+                    .iter()
+                    .position(|handle| *handle == physical_device.handle)
+                    .unwrap_or_default(),
+                adapters: infos,
+            };
+            if let Some(active) = adapters.active() {
+                log!("Using GPU {}", active);
+            }
+
             let queue_family_indices = HashSet::from([
                 queue_family.graphics,
                 queue_family.present,
@@ -236,10 +273,33 @@ impl VulkanRenderingContext {
                 instance,
                 entry,
                 swapchain_extension,
+                adapters,
+                allocations: Arc::new(Mutex::new(HashMap::new())),
             })
         }
     }
 
+    /// Allocates device memory and keeps track of its size
+    pub fn allocate_memory(&self, info: &MemoryAllocateInfo) -> Result<DeviceMemory> {
+        let memory = unsafe { self.device.allocate_memory(info, None)? };
+        self.allocations
+            .lock()
+            .unwrap()
+            .insert(memory, info.allocation_size);
+        Ok(memory)
+    }
+
+    /// Frees memory from `allocate_memory`
+    pub fn free_memory(&self, memory: DeviceMemory) {
+        unsafe { self.device.free_memory(memory, None) };
+        self.allocations.lock().unwrap().remove(&memory);
+    }
+
+    /// The total size of the memory allocated through `allocate_memory` and not freed yet
+    pub fn allocated_bytes(&self) -> u64 {
+        self.allocations.lock().unwrap().values().sum()
+    }
+
     // unsafe because the window should outlive the surface
     pub fn create_surface(&self, window: &Window) -> Result<Surface> {
         unsafe {
@@ -328,7 +388,7 @@ impl VulkanRenderingContext {
             .allocation_size(mem_reqs.size)
             .memory_type_index(self.find_memory_type(mem_reqs.memory_type_bits, properties)?);
 
-        let memory = unsafe { self.device.allocate_memory(&alloc_info, None).unwrap() };
+        let memory = self.allocate_memory(&alloc_info)?;
         unsafe { self.device.bind_image_memory(image, memory, 0).unwrap() };
         Ok((image, memory))
     }
@@ -687,6 +747,109 @@ impl VulkanRenderingContext {
         }
     }
 
+    pub fn create_particle_pipeline(
+        &self,
+        vertex_shader: ShaderModule,
+        fragment_shader: ShaderModule,
+        image_extent: Extent2D,
+        image_format: Format,
+        depth_format: Format,
+        pipeline_layout: PipelineLayout,
+        _pipeline_chache: PipelineCache,
+    ) -> Result<Pipeline> {
+        let entry_point = std::ffi::CString::new("main").unwrap();
+
+        unsafe {
+            Ok(self
+                .device
+                .create_graphics_pipelines(
+                    PipelineCache::null(),
+                    &[GraphicsPipelineCreateInfo::default()
+                        .stages(&[
+                            PipelineShaderStageCreateInfo::default()
+                                .stage(ShaderStageFlags::VERTEX)
+                                .module(vertex_shader)
+                                .name(&entry_point),
+                            PipelineShaderStageCreateInfo::default()
+                                .stage(ShaderStageFlags::FRAGMENT)
+                                .module(fragment_shader)
+                                .name(&entry_point),
+                        ])
+                        // particles are generated in the vertex shader from their instance index
+                        .vertex_input_state(&PipelineVertexInputStateCreateInfo::default())
+                        .input_assembly_state(
+                            &PipelineInputAssemblyStateCreateInfo::default()
+                                .topology(PrimitiveTopology::TRIANGLE_LIST),
+                        )
+                        .viewport_state(
+                            &PipelineViewportStateCreateInfo::default()
+                                .viewports(&[Viewport {
+                                    x: 0.0,
+                                    y: 0.0,
+                                    width: image_extent.width as f32,
+                                    height: image_extent.height as f32,
+                                    min_depth: 0.0,
+                                    max_depth: 1.0,
+                                }])
+                                .scissors(&[Rect2D {
+                                    offset: Offset2D { x: 0, y: 0 },
+                                    extent: image_extent,
+                                }]),
+                        )
+                        .rasterization_state(
+                            &PipelineRasterizationStateCreateInfo::default()
+                                .depth_clamp_enable(false)
+                                .rasterizer_discard_enable(false)
+                                .polygon_mode(PolygonMode::FILL)
+                                .cull_mode(CullModeFlags::NONE)
+                                .front_face(FrontFace::COUNTER_CLOCKWISE)
+                                .depth_bias_enable(false)
+                                .line_width(1.0),
+                        )
+                        .multisample_state(
+                            &PipelineMultisampleStateCreateInfo::default()
+                                .rasterization_samples(SampleCountFlags::TYPE_1)
+                                .sample_shading_enable(false),
+                        )
+                        .color_blend_state(
+                            &PipelineColorBlendStateCreateInfo::default().attachments(&[
+                                PipelineColorBlendAttachmentState::default()
+                                    .color_write_mask(ColorComponentFlags::RGBA)
+                                    .blend_enable(true)
+                                    .src_color_blend_factor(BlendFactor::SRC_ALPHA)
+                                    .dst_color_blend_factor(BlendFactor::ONE_MINUS_SRC_ALPHA)
+                                    .color_blend_op(BlendOp::ADD)
+                                    .src_alpha_blend_factor(BlendFactor::ONE)
+                                    .dst_alpha_blend_factor(BlendFactor::ZERO)
+                                    .alpha_blend_op(BlendOp::ADD),
+                            ]),
+                        )
+                        .dynamic_state(
+                            &PipelineDynamicStateCreateInfo::default()
+                                .dynamic_states(&[DynamicState::VIEWPORT, DynamicState::SCISSOR]),
+                        )
+                        .depth_stencil_state(
+                            &PipelineDepthStencilStateCreateInfo::default()
+                                .depth_test_enable(true)
+                                .depth_write_enable(false)
+                                .depth_compare_op(CompareOp::LESS),
+                        )
+                        .layout(pipeline_layout)
+                        .render_pass(RenderPass::null())
+                        .push_next(
+                            &mut PipelineRenderingCreateInfo::default()
+                                .color_attachment_formats(&[image_format])
+                                .depth_attachment_format(depth_format),
+                        )],
+                    None,
+                )
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap())
+        }
+    }
+
     pub fn create_wireframe_pipeline(
         &self,
         vertex_shader: ShaderModule,
@@ -789,6 +952,109 @@ impl VulkanRenderingContext {
                 .unwrap())
         }
     }
+    /// Creates a pipeline that writes a single u32 per pixel, used for object picking
+    /// Note: integer targets can't be blended so blending is disabled
+    pub fn create_id_pipeline(
+        &self,
+        vertex_shader: ShaderModule,
+        fragment_shader: ShaderModule,
+        image_extent: Extent2D,
+        depth_format: Format,
+        pipeline_layout: PipelineLayout,
+        _pipeline_chache: PipelineCache,
+    ) -> Result<Pipeline> {
+        let entry_point = std::ffi::CString::new("main").unwrap();
+
+        let bindings = vec![Vertex::get_binding_description()];
+        let attributes = Vertex::get_attribute_descriptions();
+
+        unsafe {
+            Ok(self
+                .device
+                .create_graphics_pipelines(
+                    PipelineCache::null(),
+                    &[GraphicsPipelineCreateInfo::default()
+                        .stages(&[
+                            PipelineShaderStageCreateInfo::default()
+                                .stage(ShaderStageFlags::VERTEX)
+                                .module(vertex_shader)
+                                .name(&entry_point),
+                            PipelineShaderStageCreateInfo::default()
+                                .stage(ShaderStageFlags::FRAGMENT)
+                                .module(fragment_shader)
+                                .name(&entry_point),
+                        ])
+                        .vertex_input_state(
+                            &PipelineVertexInputStateCreateInfo::default()
+                                .vertex_binding_descriptions(&bindings)
+                                .vertex_attribute_descriptions(&attributes),
+                        )
+                        .input_assembly_state(
+                            &PipelineInputAssemblyStateCreateInfo::default()
+                                .topology(PrimitiveTopology::TRIANGLE_LIST),
+                        )
+                        .viewport_state(
+                            &PipelineViewportStateCreateInfo::default()
+                                .viewports(&[Viewport {
+                                    x: 0.0,
+                                    y: 0.0,
+                                    width: image_extent.width as f32,
+                                    height: image_extent.height as f32,
+                                    min_depth: 0.0,
+                                    max_depth: 1.0,
+                                }])
+                                .scissors(&[Rect2D {
+                                    offset: Offset2D { x: 0, y: 0 },
+                                    extent: image_extent,
+                                }]),
+                        )
+                        .rasterization_state(
+                            &PipelineRasterizationStateCreateInfo::default()
+                                .depth_clamp_enable(false)
+                                .rasterizer_discard_enable(false)
+                                .polygon_mode(PolygonMode::FILL)
+                                .cull_mode(CullModeFlags::NONE)
+                                .front_face(FrontFace::CLOCKWISE)
+                                .depth_bias_enable(false)
+                                .line_width(1.0),
+                        )
+                        .multisample_state(
+                            &PipelineMultisampleStateCreateInfo::default()
+                                .rasterization_samples(SampleCountFlags::TYPE_1)
+                                .sample_shading_enable(false),
+                        )
+                        .color_blend_state(
+                            &PipelineColorBlendStateCreateInfo::default().attachments(&[
+                                PipelineColorBlendAttachmentState::default()
+                                    .color_write_mask(ColorComponentFlags::R)
+                                    .blend_enable(false),
+                            ]),
+                        )
+                        .dynamic_state(
+                            &PipelineDynamicStateCreateInfo::default()
+                                .dynamic_states(&[DynamicState::VIEWPORT, DynamicState::SCISSOR]),
+                        )
+                        .depth_stencil_state(
+                            &PipelineDepthStencilStateCreateInfo::default()
+                                .depth_test_enable(true)
+                                .depth_write_enable(true)
+                                .depth_compare_op(CompareOp::LESS),
+                        )
+                        .layout(pipeline_layout)
+                        .render_pass(RenderPass::null())
+                        .push_next(
+                            &mut PipelineRenderingCreateInfo::default()
+                                .color_attachment_formats(&[Format::R32_UINT])
+                                .depth_attachment_format(depth_format),
+                        )],
+                    None,
+                )
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap())
+        }
+    }
     pub fn create_voxel_wireframe_pipeline(
         &self,
         vertex_shader: ShaderModule,
@@ -885,6 +1151,165 @@ impl VulkanRenderingContext {
         }
     }
 
+    /// Creates the pipeline multiplying a fullscreen triangle over the frame, used to darken it
+    /// by the ambient occlusion, depth is neither tested nor written
+    pub fn create_multiply_pipeline(
+        &self,
+        vertex_shader: ShaderModule,
+        fragment_shader: ShaderModule,
+        image_format: Format,
+        depth_format: Format,
+        pipeline_layout: PipelineLayout,
+    ) -> Result<Pipeline> {
+        self.create_fullscreen_pipeline(
+            vertex_shader,
+            fragment_shader,
+            image_format,
+            depth_format,
+            pipeline_layout,
+            PipelineColorBlendAttachmentState::default()
+                .color_write_mask(ColorComponentFlags::RGBA)
+                .blend_enable(true)
+                .src_color_blend_factor(BlendFactor::DST_COLOR)
+                .dst_color_blend_factor(BlendFactor::ZERO)
+                .color_blend_op(BlendOp::ADD)
+                .src_alpha_blend_factor(BlendFactor::ZERO)
+                .dst_alpha_blend_factor(BlendFactor::ONE)
+                .alpha_blend_op(BlendOp::ADD),
+        )
+    }
+
+    /// Creates the pipeline blending a fullscreen triangle over the frame by its alpha, used for
+    /// the selection outline, depth is neither tested nor written
+    pub fn create_overlay_pipeline(
+        &self,
+        vertex_shader: ShaderModule,
+        fragment_shader: ShaderModule,
+        image_format: Format,
+        depth_format: Format,
+        pipeline_layout: PipelineLayout,
+    ) -> Result<Pipeline> {
+        self.create_fullscreen_pipeline(
+            vertex_shader,
+            fragment_shader,
+            image_format,
+            depth_format,
+            pipeline_layout,
+            PipelineColorBlendAttachmentState::default()
+                .color_write_mask(ColorComponentFlags::RGBA)
+                .blend_enable(true)
+                .src_color_blend_factor(BlendFactor::SRC_ALPHA)
+                .dst_color_blend_factor(BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .color_blend_op(BlendOp::ADD)
+                .src_alpha_blend_factor(BlendFactor::ZERO)
+                .dst_alpha_blend_factor(BlendFactor::ONE)
+                .alpha_blend_op(BlendOp::ADD),
+        )
+    }
+
+    /// Creates a pipeline drawing a fullscreen triangle generated in the vertex shader
+    fn create_fullscreen_pipeline(
+        &self,
+        vertex_shader: ShaderModule,
+        fragment_shader: ShaderModule,
+        image_format: Format,
+        depth_format: Format,
+        pipeline_layout: PipelineLayout,
+        blend: PipelineColorBlendAttachmentState,
+    ) -> Result<Pipeline> {
+        let entry_point = std::ffi::CString::new("main").unwrap();
+
+        unsafe {
+            let pipelines = self
+                .device
+                .create_graphics_pipelines(
+                    PipelineCache::null(),
+                    &[GraphicsPipelineCreateInfo::default()
+                        .stages(&[
+                            PipelineShaderStageCreateInfo::default()
+                                .stage(ShaderStageFlags::VERTEX)
+                                .module(vertex_shader)
+                                .name(&entry_point),
+                            PipelineShaderStageCreateInfo::default()
+                                .stage(ShaderStageFlags::FRAGMENT)
+                                .module(fragment_shader)
+                                .name(&entry_point),
+                        ])
+                        // the triangle is generated in the vertex shader
+                        .vertex_input_state(&PipelineVertexInputStateCreateInfo::default())
+                        .input_assembly_state(
+                            &PipelineInputAssemblyStateCreateInfo::default()
+                                .topology(PrimitiveTopology::TRIANGLE_LIST),
+                        )
+                        .viewport_state(
+                            &PipelineViewportStateCreateInfo::default()
+                                .viewport_count(1)
+                                .scissor_count(1),
+                        )
+                        .rasterization_state(
+                            &PipelineRasterizationStateCreateInfo::default()
+                                .polygon_mode(PolygonMode::FILL)
+                                .cull_mode(CullModeFlags::NONE)
+                                .front_face(FrontFace::COUNTER_CLOCKWISE)
+                                .line_width(1.0),
+                        )
+                        .multisample_state(
+                            &PipelineMultisampleStateCreateInfo::default()
+                                .rasterization_samples(SampleCountFlags::TYPE_1),
+                        )
+                        .color_blend_state(
+                            &PipelineColorBlendStateCreateInfo::default().attachments(&[blend]),
+                        )
+                        .dynamic_state(
+                            &PipelineDynamicStateCreateInfo::default()
+                                .dynamic_states(&[DynamicState::VIEWPORT, DynamicState::SCISSOR]),
+                        )
+                        .depth_stencil_state(
+                            &PipelineDepthStencilStateCreateInfo::default()
+                                .depth_test_enable(false)
+                                .depth_write_enable(false),
+                        )
+                        .layout(pipeline_layout)
+                        .render_pass(RenderPass::null())
+                        .push_next(
+                            &mut PipelineRenderingCreateInfo::default()
+                                .color_attachment_formats(&[image_format])
+                                .depth_attachment_format(depth_format),
+                        )],
+                    None,
+                )
+                .map_err(|(_, e)| anyhow!("Failed to create the fullscreen pipeline: {}", e))?;
+            Ok(pipelines[0])
+        }
+    }
+
+    pub fn create_compute_pipeline(
+        &self,
+        shader: ShaderModule,
+        pipeline_layout: PipelineLayout,
+    ) -> Result<Pipeline> {
+        let entry_point = std::ffi::CString::new("main").unwrap();
+
+        unsafe {
+            let pipelines = self
+                .device
+                .create_compute_pipelines(
+                    PipelineCache::null(),
+                    &[vk::ComputePipelineCreateInfo::default()
+                        .stage(
+                            PipelineShaderStageCreateInfo::default()
+                                .stage(ShaderStageFlags::COMPUTE)
+                                .module(shader)
+                                .name(&entry_point),
+                        )
+                        .layout(pipeline_layout)],
+                    None,
+                )
+                .map_err(|(_, e)| anyhow!("Failed to create a compute pipeline: {}", e))?;
+            Ok(pipelines[0])
+        }
+    }
+
     pub fn transition_image_layout(
         &self,
         command_buffer: CommandBuffer,
@@ -958,6 +1383,36 @@ impl VulkanRenderingContext {
         }
     }
 
+    /// Begins rendering to targets that already hold this frame, keeping their contents
+    pub fn resume_rendering(
+        &self,
+        command_buffer: CommandBuffer,
+        view: ImageView,
+        depth_view: ImageView,
+        render_area: Rect2D,
+    ) {
+        unsafe {
+            self.device.cmd_begin_rendering(
+                command_buffer,
+                &RenderingInfo::default()
+                    .layer_count(1)
+                    .color_attachments(&[RenderingAttachmentInfo::default()
+                        .image_view(view)
+                        .image_layout(ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                        .load_op(AttachmentLoadOp::LOAD)
+                        .store_op(AttachmentStoreOp::STORE)])
+                    .depth_attachment(
+                        &RenderingAttachmentInfo::default()
+                            .image_view(depth_view)
+                            .image_layout(ImageLayout::DEPTH_ATTACHMENT_OPTIMAL)
+                            .load_op(AttachmentLoadOp::LOAD)
+                            .store_op(AttachmentStoreOp::STORE),
+                    )
+                    .render_area(render_area),
+            );
+        }
+    }
+
     /// Creates a texture descriptor set
     pub fn create_texture_descriptor_set(
         &self,
@@ -994,6 +1449,183 @@ impl VulkanRenderingContext {
         }
     }
 
+    /// Creates a descriptor set with one combined image sampler per image view, bound in order
+    pub fn create_textures_descriptor_set(
+        &self,
+        descriptor_pool: vk::DescriptorPool,
+        descriptor_set_layout: vk::DescriptorSetLayout,
+        image_views: &[vk::ImageView],
+        sampler: vk::Sampler,
+    ) -> Result<vk::DescriptorSet> {
+        unsafe {
+            let set = self.device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(descriptor_pool)
+                    .set_layouts(&[descriptor_set_layout]),
+            )?[0];
+
+            let image_infos: Vec<[vk::DescriptorImageInfo; 1]> = image_views
+                .iter()
+                .map(|view| {
+                    [vk::DescriptorImageInfo::default()
+                        .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                        .image_view(*view)
+                        .sampler(sampler)]
+                })
+                .collect();
+            let writes: Vec<vk::WriteDescriptorSet> = image_infos
+                .iter()
+                .enumerate()
+                .map(|(binding, info)| {
+                    vk::WriteDescriptorSet::default()
+                        .dst_set(set)
+                        .dst_binding(binding as u32)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .image_info(info)
+                })
+                .collect();
+            self.device.update_descriptor_sets(&writes, &[]);
+
+            Ok(set)
+        }
+    }
+
+    /// Uploads rgba8 pixels to a sampled image in shader read layout
+    pub fn upload_texture(
+        &self,
+        command_pool: CommandPool,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        format: Format,
+    ) -> Result<(Image, DeviceMemory, ImageView)> {
+        let size = pixels.len() as vk::DeviceSize;
+
+        // staging buffer
+        let (staging_buffer, staging_memory) = self.create_buffer(
+            size,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+
+        // copy pixels into staging buffer
+        unsafe {
+            let ptr =
+                self.device
+                    .map_memory(staging_memory, 0, size, vk::MemoryMapFlags::empty())?
+                    as *mut u8;
+            ptr.copy_from_nonoverlapping(pixels.as_ptr(), pixels.len());
+            self.device.unmap_memory(staging_memory);
+        }
+
+        // create GPU image
+        let (vk_image, image_memory) = self.create_image(
+            vk::Extent2D { width, height },
+            format,
+            vk::ImageTiling::OPTIMAL,
+            vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+
+        let cmd = self.begin_single_time_commands(command_pool);
+
+        unsafe {
+            // transition to transfer dst
+            let barrier = vk::ImageMemoryBarrier::default()
+                .old_layout(vk::ImageLayout::UNDEFINED)
+                .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(vk_image)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                })
+                .src_access_mask(vk::AccessFlags::empty())
+                .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE);
+
+            self.device.cmd_pipeline_barrier(
+                cmd,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[barrier],
+            );
+
+            // copy buffer to image
+            let region = vk::BufferImageCopy::default()
+                .buffer_offset(0)
+                .buffer_row_length(0)
+                .buffer_image_height(0)
+                .image_subresource(vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: 0,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                })
+                .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+                .image_extent(vk::Extent3D {
+                    width,
+                    height,
+                    depth: 1,
+                });
+
+            self.device.cmd_copy_buffer_to_image(
+                cmd,
+                staging_buffer,
+                vk_image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region],
+            );
+
+            // transition to shader read
+            let barrier = vk::ImageMemoryBarrier::default()
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(vk_image)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                })
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ);
+
+            self.device.cmd_pipeline_barrier(
+                cmd,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[barrier],
+            );
+        }
+
+        let queue = self.queues[&self.queue_families.transfer];
+        self.end_single_time_commands(cmd, queue, command_pool);
+
+        // cleanup staging
+        unsafe {
+            self.device.destroy_buffer(staging_buffer, None);
+            self.free_memory(staging_memory);
+        }
+
+        // image view
+        let image_view = self.create_image_view(vk_image, format, vk::ImageAspectFlags::COLOR)?;
+
+        Ok((vk_image, image_memory, image_view))
+    }
+
     pub fn begin_single_time_commands(&self, command_pool: CommandPool) -> CommandBuffer {
         let alloc_info = CommandBufferAllocateInfo::default()
             .level(CommandBufferLevel::PRIMARY)
@@ -1064,7 +1696,7 @@ impl VulkanRenderingContext {
                 self.find_memory_type(mem_requirements.memory_type_bits, properties)?,
             );
 
-        let memory = unsafe { self.device.allocate_memory(&alloc_info, None)? };
+        let memory = self.allocate_memory(&alloc_info)?;
 
         unsafe { self.device.bind_buffer_memory(buffer, memory, 0)? };
 
@@ -1110,7 +1742,7 @@ impl VulkanRenderingContext {
                 MemoryPropertyFlags::DEVICE_LOCAL,
             )?);
 
-        let buffer_memory = unsafe { self.device.allocate_memory(&alloc_info, None)? };
+        let buffer_memory = self.allocate_memory(&alloc_info)?;
         unsafe { self.device.bind_buffer_memory(buffer, buffer_memory, 0)? };
 
         // Copy staging -> device local
@@ -1125,7 +1757,7 @@ impl VulkanRenderingContext {
 
         unsafe {
             self.device.destroy_buffer(staging_buffer, None);
-            self.device.free_memory(staging_memory, None);
+            self.free_memory(staging_memory);
         }
 
         Ok((buffer, buffer_memory))
@@ -1171,7 +1803,7 @@ impl VulkanRenderingContext {
                 MemoryPropertyFlags::DEVICE_LOCAL,
             )?);
 
-        let buffer_memory = unsafe { self.device.allocate_memory(&alloc_info, None)? };
+        let buffer_memory = self.allocate_memory(&alloc_info)?;
         unsafe { self.device.bind_buffer_memory(buffer, buffer_memory, 0)? };
 
         // copy
@@ -1186,7 +1818,7 @@ impl VulkanRenderingContext {
 
         unsafe {
             self.device.destroy_buffer(staging_buffer, None);
-            self.device.free_memory(staging_memory, None);
+            self.free_memory(staging_memory);
         }
 
         Ok((buffer, buffer_memory))
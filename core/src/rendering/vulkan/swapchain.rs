@@ -17,6 +17,8 @@ pub struct VulkanSwapchain {
     pub window: Arc<Window>,
     context: Arc<VulkanRenderingContext>,
     pub is_dirty: bool,
+    /// the preferred present mode, FIFO is used if it is unsupported
+    pub present_mode: vk::PresentModeKHR,
     pub depth_format: Format,
     pub depth_image: Image,
     pub depth_image_view: ImageView,
@@ -57,6 +59,7 @@ impl VulkanSwapchain {
             window,
             context,
             is_dirty: true,
+            present_mode: vk::PresentModeKHR::MAILBOX,
             depth_format,
             depth_image: vk::Image::null(),
             depth_image_view: vk::ImageView::null(),
@@ -102,8 +105,8 @@ impl VulkanSwapchain {
                         self.context.physical_device.handle,
                         self.surface.handle,
                     )?;
-                if modes.contains(&vk::PresentModeKHR::MAILBOX) {
-                    vk::PresentModeKHR::MAILBOX
+                if modes.contains(&self.present_mode) {
+                    self.present_mode
                 } else {
                     vk::PresentModeKHR::FIFO
                 }
@@ -159,7 +162,7 @@ impl VulkanSwapchain {
                 self.depth_image = vk::Image::null();
             }
             if !self.depth_memory.is_null() {
-                self.context.device.free_memory(self.depth_memory, None);
+                self.context.free_memory(self.depth_memory);
                 self.depth_memory = vk::DeviceMemory::null();
             }
 
@@ -186,7 +189,8 @@ impl VulkanSwapchain {
                 self.extent,
                 self.depth_format,
                 vk::ImageTiling::OPTIMAL,
-                vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+                // sampled by the ambient occlusion pass
+                vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
                 vk::MemoryPropertyFlags::DEVICE_LOCAL,
             )?;
             self.depth_image = depth_image;
@@ -202,6 +206,43 @@ impl VulkanSwapchain {
         Ok(())
     }
 
+    /// Destroys the swapchain, its depth image and the surface of the window
+    /// Note: the device must be idle, the swapchain can not be used after this
+    pub fn destroy(&mut self) {
+        unsafe {
+            for image_view in self.views.drain(..) {
+                self.context.device.destroy_image_view(image_view, None);
+            }
+            if !self.depth_image_view.is_null() {
+                self.context
+                    .device
+                    .destroy_image_view(self.depth_image_view, None);
+                self.depth_image_view = vk::ImageView::null();
+            }
+            if !self.depth_image.is_null() {
+                self.context.device.destroy_image(self.depth_image, None);
+                self.depth_image = vk::Image::null();
+            }
+            if !self.depth_memory.is_null() {
+                self.context.free_memory(self.depth_memory);
+                self.depth_memory = vk::DeviceMemory::null();
+            }
+            if !self.handle.is_null() {
+                self.context
+                    .swapchain_extension
+                    .destroy_swapchain(self.handle, None);
+                self.handle = vk::SwapchainKHR::null();
+            }
+            self.images.clear();
+            if !self.surface.handle.is_null() {
+                self.context
+                    .surface_extension
+                    .destroy_surface(self.surface.handle, None);
+                self.surface.handle = vk::SurfaceKHR::null();
+            }
+        }
+    }
+
     /// Acquires the next image in the swapchain
     pub fn acquire_next_image(&mut self, image_available_semaphore: vk::Semaphore) -> Result<u32> {
         let (image_index, is_suboptimal) = unsafe {
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use anyhow::Result;
+use apostasy_macros::{Resource, update};
+
+use crate::{log_error, log_warn, objects::world::World, ui::ui_context::EguiContext};
+
+/// Starts in safe mode when passed on the command line
+pub const SAFE_MODE_ARG: &str = "--safe-mode";
+/// Written before the start systems run and removed once they finish
+/// Note: if it still exists on the next run the last run crashed during start up
+pub const STARTUP_MARKER_PATH: &str = "res/.startup";
+/// The packages whose systems keep running in safe mode
+pub const ENGINE_PACKAGES: [&str; 2] = ["apostasy-core", "apostasy-editor"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SafeModeReason {
+    /// started with --safe-mode
+    Requested,
+    /// the last run crashed before its start systems finished
+    PreviousCrash,
+}
+
+impl SafeModeReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SafeModeReason::Requested => "started with --safe-mode",
+            SafeModeReason::PreviousCrash => "the last run crashed during start up",
+        }
+    }
+}
+
+/// A resource that exists while the engine is in safe mode
+/// In safe mode every system outside of the engine packages is disabled so broken game code
+/// can be fixed from the editor
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SafeMode {
+    pub reason: SafeModeReason,
+}
+
+impl SafeMode {
+    /// Checks the command line and the start up marker for a reason to start in safe mode
+    pub fn detect() -> Option<Self> {
+        let reason = if std::env::args().any(|arg| arg == SAFE_MODE_ARG) {
+            SafeModeReason::Requested
+        } else if Path::new(STARTUP_MARKER_PATH).exists() {
+            SafeModeReason::PreviousCrash
+        } else {
+            return None;
+        };
+        Some(Self { reason })
+    }
+}
+
+/// Writes the start up marker, call before running the start systems
+pub fn begin_startup() {
+    let path = Path::new(STARTUP_MARKER_PATH);
+    if let Some(parent) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(parent).and_then(|_| std::fs::write(path, ""))
+    {
+        log_error!("Failed to write {}: {}", STARTUP_MARKER_PATH, e);
+    }
+}
+
+/// Removes the start up marker, call once the start systems have finished
+pub fn end_startup() {
+    if Path::new(STARTUP_MARKER_PATH).exists()
+        && let Err(e) = std::fs::remove_file(STARTUP_MARKER_PATH)
+    {
+        log_error!("Failed to remove {}: {}", STARTUP_MARKER_PATH, e);
+    }
+}
+
+impl World {
+    /// Disables every system outside of the engine packages and inserts the SafeMode resource
+    pub fn enter_safe_mode(&mut self, reason: SafeModeReason) {
+        let user_systems: Vec<String> = self
+            .systems()
+            .iter()
+            .filter(|s| !ENGINE_PACKAGES.contains(&s.package))
+            .map(|s| format!("{}::{}", s.package, s.name))
+            .collect();
+        for system in &user_systems {
+            let _ = self.set_system_enabled(system, false);
+        }
+
+        log_warn!(
+            "Starting in safe mode ({}), disabled {} systems",
+            reason.label(),
+            user_systems.len()
+        );
+        self.insert_resource(SafeMode { reason });
+    }
+
+    pub fn is_safe_mode(&self) -> bool {
+        self.has_resource::<SafeMode>()
+    }
+}
+
+/// Shows a banner while in safe mode
+#[update(while_paused = true)]
+pub fn safe_mode_banner(world: &mut World) -> Result<()> {
+    let Ok(safe_mode) = world.get_resource::<SafeMode>() else {
+        return Ok(());
+    };
+    let reason = safe_mode.reason;
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+
+    egui::Area::new(egui::Id::new("safe_mode_banner"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+        .show(&ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.colored_label(
+                    egui::Color32::from_rgb(230, 180, 60),
+                    format!("Safe mode: {}", reason.label()),
+                );
+                ui.label("Game systems are disabled, restart without --safe-mode to run them");
+            });
+        });
+
+    Ok(())
+}
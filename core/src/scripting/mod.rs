@@ -0,0 +1,224 @@
+use std::fmt;
+
+use anyhow::Result;
+use apostasy_macros::{Component, update};
+
+use crate::{
+    log_error,
+    objects::{
+        resources::input_manager::InputManager, scene::ObjectId, snapshot::SceneLoaded,
+        systems::DeltaTime, world::World,
+    },
+    physics::contacts::{CollisionEvent, CollisionKind, Contact},
+};
+
+/// Behaviour attached to an object through its `Scripts` component
+/// Every hook is optional, e.g. a door only implements `on_action` to open on "interact"
+/// Note: scripting languages implement this once to forward the hooks to their scripts
+pub trait Script: ScriptContainer + Send + Sync + 'static {
+    /// the name shown in errors and the inspector
+    fn name(&self) -> &str;
+
+    /// Called the first frame the script is on an object
+    fn on_start(&mut self, _world: &mut World, _object: ObjectId) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called every frame
+    fn on_update(&mut self, _world: &mut World, _object: ObjectId, _delta: f32) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the object starts touching something, a frame after it happened
+    fn on_collision_enter(
+        &mut self,
+        _world: &mut World,
+        _object: ObjectId,
+        _other: Contact,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the object stops touching something, a frame after it happened
+    fn on_collision_exit(
+        &mut self,
+        _world: &mut World,
+        _object: ObjectId,
+        _other: Contact,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called each frame a keybind or mousebind is active, with the name it was registered with
+    fn on_action(&mut self, _world: &mut World, _object: ObjectId, _action: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after a scene is loaded with `World::load_objects`
+    fn on_scene_loaded(
+        &mut self,
+        _world: &mut World,
+        _object: ObjectId,
+        _path: &str,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub trait ScriptContainer {
+    fn clone_box(&self) -> Box<dyn Script>;
+}
+
+impl<T> ScriptContainer for T
+where
+    T: 'static + Script + Clone,
+{
+    fn clone_box(&self) -> Box<dyn Script> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Script> {
+    fn clone(&self) -> Box<dyn Script> {
+        self.clone_box()
+    }
+}
+
+/// The scripts of an object, their hooks are run by `run_script_hooks`
+/// Note: scripts are not saved with the object, whatever adds them is expected to add them again
+#[derive(Component, Clone, Default)]
+pub struct Scripts {
+    scripts: Vec<Box<dyn Script>>,
+    /// how many scripts have had `on_start` called
+    started: usize,
+}
+
+impl fmt::Debug for Scripts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.scripts.iter().map(|script| script.name()))
+            .finish()
+    }
+}
+
+impl Scripts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, script: impl Script) -> Self {
+        self.scripts.push(Box::new(script));
+        self
+    }
+
+    pub fn push(&mut self, script: impl Script) {
+        self.scripts.push(Box::new(script));
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scripts.iter().map(|script| script.name())
+    }
+
+    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Everything that happened to the scripts' objects since the last frame
+#[derive(Default)]
+struct Hooks {
+    collisions: Vec<CollisionEvent>,
+    actions: Vec<String>,
+    scene_loaded: Vec<String>,
+    delta: f32,
+}
+
+/// Runs the hooks of every script, a failing hook is logged and the other hooks still run
+#[update]
+pub fn run_script_hooks(world: &mut World) -> Result<()> {
+    let ids: Vec<ObjectId> = world
+        .get_objects_with_component_with_ids::<Scripts>()
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let mut hooks = Hooks {
+        delta: world.get_resource::<DeltaTime>()?.0,
+        ..Default::default()
+    };
+    if let Ok(reader) = world.event_reader::<CollisionEvent>() {
+        hooks.collisions = reader.read().copied().collect();
+    }
+    if let Ok(reader) = world.event_reader::<SceneLoaded>() {
+        hooks.scene_loaded = reader.read().map(|event| event.path.clone()).collect();
+    }
+    if let Ok(inputs) = world.get_resource::<InputManager>() {
+        hooks.actions = inputs
+            .keybinds
+            .keys()
+            .filter(|name| inputs.is_keybind_active(name))
+            .chain(
+                inputs
+                    .mouse_keybinds
+                    .keys()
+                    .filter(|name| inputs.is_mousebind_active(name)),
+            )
+            .cloned()
+            .collect();
+    }
+
+    for id in ids {
+        // the scripts are taken out of the object while they run so hooks can change the world
+        let Ok(mut object) = world.object_mut(id) else {
+            continue;
+        };
+        let Ok(scripts) = object.get_component_mut::<Scripts>() else {
+            continue;
+        };
+        let mut running = std::mem::take(&mut scripts.scripts);
+        let started = std::mem::replace(&mut scripts.started, running.len());
+
+        for (i, script) in running.iter_mut().enumerate() {
+            run_hooks(world, id, script.as_mut(), &hooks, i >= started);
+        }
+
+        // the object may be removed by a hook, scripts added by a hook run next frame
+        if let Ok(mut object) = world.object_mut(id)
+            && let Ok(scripts) = object.get_component_mut::<Scripts>()
+        {
+            running.append(&mut scripts.scripts);
+            scripts.scripts = running;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_hooks(world: &mut World, id: ObjectId, script: &mut dyn Script, hooks: &Hooks, start: bool) {
+    let mut results = Vec::new();
+    if start {
+        results.push(script.on_start(world, id));
+    }
+    for path in &hooks.scene_loaded {
+        results.push(script.on_scene_loaded(world, id, path));
+    }
+    for collision in hooks.collisions.iter().filter(|c| c.object == id) {
+        results.push(match collision.kind {
+            CollisionKind::Enter => script.on_collision_enter(world, id, collision.other),
+            CollisionKind::Exit => script.on_collision_exit(world, id, collision.other),
+        });
+    }
+    for action in &hooks.actions {
+        results.push(script.on_action(world, id, action));
+    }
+    results.push(script.on_update(world, id, hooks.delta));
+
+    for result in results {
+        if let Err(e) = result {
+            log_error!("Script {} failed: {}", script.name(), e);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use apostasy_macros::{Component, Resource, update};
+use cgmath::{Vector3, VectorSpace};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    log_error,
+    math::inverse_lerp,
+    objects::{
+        components::transform::Transform, scene::ObjectId, systems::DeltaTime, world::World,
+    },
+    rendering::components::camera::ActiveCamera,
+    utils::yaml,
+};
+
+/// A transform at a point in a sequence
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransformKey {
+    pub time: f32,
+    pub position: [f32; 3],
+    pub euler_angles: [f32; 3],
+}
+
+impl TransformKey {
+    pub fn from_transform(time: f32, transform: &Transform) -> Self {
+        Self {
+            time,
+            position: transform.local_position.into(),
+            euler_angles: transform.local_euler_angles.into(),
+        }
+    }
+}
+
+/// Moves the objects with a name over time
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjectTrack {
+    pub object: String,
+    pub keys: Vec<TransformKey>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CueKind {
+    /// a sound to play
+    Audio,
+    /// a named event for game code to react to
+    Event,
+}
+
+impl CueKind {
+    pub const ALL: [CueKind; 2] = [CueKind::Audio, CueKind::Event];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CueKind::Audio => "Audio",
+            CueKind::Event => "Event",
+        }
+    }
+}
+
+/// Fired once when playback passes its time
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SequenceCue {
+    pub time: f32,
+    pub kind: CueKind,
+    pub name: String,
+}
+
+/// A timeline asset, saved as yaml
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sequence {
+    /// the length of the sequence in seconds
+    pub duration: f32,
+    /// moves the active camera
+    pub camera: Vec<TransformKey>,
+    pub objects: Vec<ObjectTrack>,
+    pub cues: Vec<SequenceCue>,
+}
+
+impl Sequence {
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut sequence: Self = serde_yaml::from_str(&contents)?;
+        sequence.sort();
+        Ok(sequence)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        yaml::save(path, self)
+    }
+
+    /// Sorts every key and cue by time, sampling expects sorted keys
+    pub fn sort(&mut self) {
+        let by_time = |a: &f32, b: &f32| a.total_cmp(b);
+        self.camera.sort_by(|a, b| by_time(&a.time, &b.time));
+        for track in &mut self.objects {
+            track.keys.sort_by(|a, b| by_time(&a.time, &b.time));
+        }
+        self.cues.sort_by(|a, b| by_time(&a.time, &b.time));
+    }
+
+    /// Gets the interpolated (position, euler angles) of sorted keys at a time
+    /// Note: euler angles are interpolated directly, keep neighbouring keys within 180 degrees
+    pub fn sample(keys: &[TransformKey], time: f32) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let first = keys.first()?;
+        let last = keys.last()?;
+        if time <= first.time {
+            return Some((first.position.into(), first.euler_angles.into()));
+        }
+        if time >= last.time {
+            return Some((last.position.into(), last.euler_angles.into()));
+        }
+
+        let next = keys.iter().position(|key| key.time > time)?;
+        let (a, b) = (&keys[next - 1], &keys[next]);
+        let t = inverse_lerp(a.time, b.time, time);
+        Some((
+            Vector3::from(a.position).lerp(b.position.into(), t),
+            Vector3::from(a.euler_angles).lerp(b.euler_angles.into(), t),
+        ))
+    }
+
+    /// Gets the cues with a time in from..to, including to when inclusive
+    pub fn cues_between(&self, from: f32, to: f32, inclusive: bool) -> Vec<SequenceCue> {
+        self.cues
+            .iter()
+            .filter(|cue| cue.time >= from && (cue.time < to || (inclusive && cue.time == to)))
+            .cloned()
+            .collect()
+    }
+
+    /// Gets the cues with a time in to..=from latest first, including to when inclusive
+    /// Used when playing backwards, the mirror of `cues_between`
+    pub fn cues_between_backwards(&self, from: f32, to: f32, inclusive: bool) -> Vec<SequenceCue> {
+        self.cues
+            .iter()
+            .rev()
+            .filter(|cue| cue.time <= from && (cue.time > to || (inclusive && cue.time == to)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Plays a sequence asset
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[component_serde]
+#[serde(default)]
+pub struct SequencePlayer {
+    pub sequence_path: String,
+    /// loaded from sequence_path on the first update, shared with the player system while it
+    /// applies the tracks, edit it with `Arc::make_mut`
+    #[serde(skip)]
+    pub sequence: Option<Arc<Sequence>>,
+    /// the playback position in seconds
+    pub time: f32,
+    /// below 0 plays backwards, stopping at or looping around the start
+    pub speed: f32,
+    pub is_playing: bool,
+    pub is_looping: bool,
+    /// the time the tracks were last applied at, the tracks are applied again when time changes
+    #[serde(skip)]
+    pub applied_time: Option<f32>,
+}
+
+impl Default for SequencePlayer {
+    fn default() -> Self {
+        Self {
+            sequence_path: String::new(),
+            sequence: None,
+            time: 0.0,
+            speed: 1.0,
+            is_playing: false,
+            is_looping: false,
+            applied_time: None,
+        }
+    }
+}
+
+impl SequencePlayer {
+    pub fn new(sequence_path: &str) -> Self {
+        Self {
+            sequence_path: sequence_path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn play(&mut self) {
+        self.is_playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.is_playing = false;
+    }
+
+    /// Stops playback and rewinds to the start
+    pub fn stop(&mut self) {
+        self.is_playing = false;
+        self.time = 0.0;
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.sequence.as_ref().map(|s| s.duration).unwrap_or(0.0)
+    }
+}
+
+/// A cue fired by a sequence player this frame
+#[derive(Clone, Debug)]
+pub struct FiredCue {
+    pub player: ObjectId,
+    pub cue: SequenceCue,
+}
+
+/// The cues fired this frame, cleared at the start of every update
+/// Note: the engine has no audio yet, games play audio cues from here
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SequenceCues {
+    pub fired: Vec<FiredCue>,
+}
+
+/// Moves every object with the track's name
+fn apply_object_track(world: &mut World, track: &ObjectTrack, time: f32) {
+    let Some((position, euler_angles)) = Sequence::sample(&track.keys, time) else {
+        return;
+    };
+    for (_, object) in world.scene.objects.iter_mut() {
+        if object.name != track.object {
+            continue;
+        }
+        if let Ok(transform) = object.get_component_mut::<Transform>() {
+            transform.local_position = position;
+            transform.local_euler_angles = euler_angles;
+        }
+    }
+}
+
+#[update(priority = 10)]
+pub fn sequence_player_system(world: &mut World) -> Result<()> {
+    world.get_resource_mut::<SequenceCues>()?.fired.clear();
+
+    let delta = world.get_resource::<DeltaTime>()?.0;
+    let player_ids: Vec<ObjectId> = world
+        .get_objects_with_component_with_ids::<SequencePlayer>()
+        .iter()
+        .map(|(id, _)| *id)
+        .collect();
+
+    for id in player_ids {
+        let Some(player) = world
+            .get_object_mut(id)
+            .and_then(|object| object.get_component_mut::<SequencePlayer>().ok())
+        else {
+            continue;
+        };
+
+        if player.sequence.is_none() {
+            match Sequence::load(&player.sequence_path) {
+                Ok(sequence) => player.sequence = Some(Arc::new(sequence)),
+                Err(e) => {
+                    log_error!("Failed to load sequence {}: {}", player.sequence_path, e);
+                    // an empty sequence stops the load being retried every frame
+                    player.sequence = Some(Arc::default());
+                    player.is_playing = false;
+                }
+            }
+        }
+        let Some(sequence) = player.sequence.clone() else {
+            continue;
+        };
+
+        let mut fired = Vec::new();
+        if player.is_playing {
+            let previous = player.time;
+            player.time += delta * player.speed;
+
+            if player.speed < 0.0 {
+                if player.time <= 0.0 {
+                    if player.is_looping && sequence.duration > 0.0 {
+                        fired.extend(sequence.cues_between_backwards(previous, 0.0, false));
+                        player.time = player.time.rem_euclid(sequence.duration);
+                        fired.extend(sequence.cues_between_backwards(
+                            sequence.duration,
+                            player.time,
+                            false,
+                        ));
+                    } else {
+                        player.time = 0.0;
+                        player.is_playing = false;
+                        fired.extend(sequence.cues_between_backwards(previous, 0.0, true));
+                    }
+                } else {
+                    fired.extend(sequence.cues_between_backwards(previous, player.time, false));
+                }
+            } else if player.time >= sequence.duration {
+                if player.is_looping && sequence.duration > 0.0 {
+                    fired.extend(sequence.cues_between(previous, sequence.duration, false));
+                    player.time %= sequence.duration;
+                    fired.extend(sequence.cues_between(0.0, player.time, false));
+                } else {
+                    player.time = sequence.duration;
+                    player.is_playing = false;
+                    fired.extend(sequence.cues_between(previous, player.time, true));
+                }
+            } else {
+                fired.extend(sequence.cues_between(previous, player.time, false));
+            }
+        }
+
+        let needs_apply = player.applied_time != Some(player.time);
+        player.applied_time = Some(player.time);
+        let time = player.time;
+
+        world
+            .get_resource_mut::<SequenceCues>()?
+            .fired
+            .extend(fired.into_iter().map(|cue| FiredCue { player: id, cue }));
+
+        if !needs_apply {
+            continue;
+        }
+        if let Some((position, euler_angles)) = Sequence::sample(&sequence.camera, time)
+            && let Ok(camera) = world.get_object_with_tag_mut::<ActiveCamera>()
+            && let Ok(transform) = camera.get_component_mut::<Transform>()
+        {
+            transform.local_position = position;
+            transform.local_euler_angles = euler_angles;
+        }
+        for track in &sequence.objects {
+            apply_object_track(world, track, time);
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,190 @@
+use std::{any::TypeId, fmt::Debug};
+
+use anyhow::Result;
 use apostasy_macros::Resource;
 
+use crate::{
+    log_error,
+    objects::{resource::Resource, world::World},
+};
+
+pub mod pause;
+
+/// Sent to close the engine, `Engine` runs the `#[shutdown]` systems and exits when it sees one
+/// Note: send it with `world.exit()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppExit {
+    /// a system, the console `quit` command or the game asked to exit
+    Requested,
+    /// the window was closed
+    WindowClosed,
+}
+
+/// A type a `State` can hold, usually an enum of the modes of a game
+pub trait States: Clone + PartialEq + Debug + Send + Sync + 'static {}
+
+/// Whether the engine runs as the game or as the editor
+/// Note: systems declare which they run in with `#[update(in_state = GameState::Playing)]`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    Playing,
+    Editor,
+    /// update and fixed update systems are skipped unless they set `while_paused = true`
+    /// Note: enter it with `world.push_state(GameState::Paused)` and leave it with `pop_state`
+    Paused,
+}
+
+impl States for GameState {}
+
+/// The current value of a state machine, added with `World::add_state`
 #[derive(Resource, Clone)]
-pub struct ShouldExit;
+pub struct State<T: States> {
+    current: T,
+    /// applied at the end of the frame, see `set`
+    next: Option<T>,
+    /// true during the first frame of the current state
+    just_changed: bool,
+    /// the states `pop` goes back to, see `push`
+    stack: Vec<T>,
+}
+
+impl<T: States> State<T> {
+    pub fn new(initial: T) -> Self {
+        Self {
+            current: initial,
+            next: None,
+            just_changed: true,
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.current
+    }
+
+    pub fn is(&self, state: &T) -> bool {
+        self.current == *state
+    }
+
+    /// Changes the state at the end of the frame, so every system of a frame sees the same state
+    /// Note: setting it twice in a frame keeps the last one
+    pub fn set(&mut self, next: T) {
+        self.next = Some(next);
+    }
+
+    /// Changes the state at the end of the frame, remembering the state to go back to with `pop`
+    pub fn push(&mut self, next: T) {
+        let previous = self.next.clone().unwrap_or_else(|| self.current.clone());
+        self.stack.push(previous);
+        self.set(next);
+    }
+
+    /// Goes back to the state before the last `push`, returns false if nothing was pushed
+    pub fn pop(&mut self) -> bool {
+        match self.stack.pop() {
+            Some(previous) => {
+                self.set(previous);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Gets how many states `pop` can go back through
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns true during the first frame of the current state
+    pub fn just_changed(&self) -> bool {
+        self.just_changed
+    }
+
+    fn apply(&mut self) {
+        self.just_changed = false;
+        if let Some(next) = self.next.take()
+            && next != self.current
+        {
+            self.current = next;
+            self.just_changed = true;
+        }
+    }
+}
+
+/// Applies the queued change of one state type
+fn apply_state<T: States>(world: &mut World) {
+    if let Ok(state) = world.get_resource_mut::<State<T>>() {
+        state.apply();
+    }
+}
+
+/// A run condition, e.g. `#[update(run_if = resource_exists::<IsInspectorOpen>)]`
+pub fn resource_exists<T: Resource + 'static>(world: &World) -> bool {
+    world.has_resource::<T>()
+}
+
+impl World {
+    /// Asks the engine to exit, it saves and closes before the next frame
+    pub fn exit(&mut self) {
+        if let Err(e) = self.send_event(AppExit::Requested) {
+            log_error!("Failed to send AppExit: {}", e);
+        }
+    }
+
+    /// Returns true once an `AppExit` was sent this frame or the last
+    pub fn is_exiting(&self) -> bool {
+        self.event_reader::<AppExit>()
+            .is_ok_and(|events| events.read().chain(events.read_current()).next().is_some())
+    }
+
+    /// Inserts the State<T> resource and applies its changes at the end of every late update
+    /// Note: adding a state again replaces its current value straight away
+    pub fn add_state<T: States>(&mut self, initial: T) -> &mut Self {
+        self.insert_resource(State::new(initial));
+        self.state_updaters
+            .insert(TypeId::of::<T>(), apply_state::<T>);
+        self
+    }
+
+    /// Gets the current value of a state, none if it was never added
+    pub fn state<T: States>(&self) -> Option<&T> {
+        self.get_resource::<State<T>>().ok().map(State::get)
+    }
+
+    /// Returns true if the state was added and is `state`, used by `in_state = ...`
+    pub fn in_state<T: States>(&self, state: &T) -> bool {
+        self.state::<T>().is_some_and(|current| current == state)
+    }
+
+    /// Changes a state at the end of the frame, errors if the state was never added
+    pub fn set_state<T: States>(&mut self, next: T) -> Result<()> {
+        self.get_resource_mut::<State<T>>()?.set(next);
+        Ok(())
+    }
+
+    /// Changes a state at the end of the frame and remembers the current one, e.g. to pause
+    pub fn push_state<T: States>(&mut self, next: T) -> Result<()> {
+        self.get_resource_mut::<State<T>>()?.push(next);
+        Ok(())
+    }
+
+    /// Goes back to the state before the last `push_state` at the end of the frame
+    /// returns false if nothing was pushed
+    pub fn pop_state<T: States>(&mut self) -> Result<bool> {
+        Ok(self.get_resource_mut::<State<T>>()?.pop())
+    }
+
+    /// Returns true while `GameState::Paused` is the current state
+    pub fn is_paused(&self) -> bool {
+        self.in_state(&GameState::Paused)
+    }
+
+    /// Applies the queued change of every added state
+    pub(crate) fn apply_state_transitions(&mut self) {
+        let updaters: Vec<fn(&mut World)> = self.state_updaters.values().copied().collect();
+        for updater in updaters {
+            updater(self);
+        }
+    }
+}
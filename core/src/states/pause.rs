@@ -0,0 +1,83 @@
+use anyhow::Result;
+use apostasy_macros::{Resource, update};
+
+use crate::{
+    objects::{
+        resources::{
+            cursor_manager::{CursorLockMode, CursorManager},
+            window_manager::WindowManager,
+        },
+        world::World,
+    },
+    states::{GameState, State},
+    ui::ui_context::EguiContext,
+};
+
+/// How the game looks and the cursor behaves while `GameState::Paused` is active
+#[derive(Resource, Clone, Debug)]
+pub struct PauseSettings {
+    /// how much the last frame is darkened behind the pause menu, 0 leaves it as is
+    pub dim: f32,
+    /// frees and shows the cursor while paused, the previous mode is restored on resume
+    pub release_cursor: bool,
+    /// the cursor mode from before the pause
+    resume_cursor: Option<CursorLockMode>,
+}
+
+impl Default for PauseSettings {
+    fn default() -> Self {
+        Self {
+            dim: 0.5,
+            release_cursor: true,
+            resume_cursor: None,
+        }
+    }
+}
+
+/// Frees the cursor when the game is paused and dims the frame behind the pause menu
+/// Note: the dim is drawn on the egui background layer, so windows stay above it
+#[update(while_paused = true)]
+pub fn pause_system(world: &mut World) -> Result<()> {
+    let Ok(state) = world.get_resource::<State<GameState>>() else {
+        return Ok(());
+    };
+    let is_paused = state.is(&GameState::Paused);
+    let just_changed = state.just_changed();
+    let Ok(settings) = world.get_resource::<PauseSettings>() else {
+        return Ok(());
+    };
+    let mut settings = settings.clone();
+
+    if just_changed && settings.release_cursor {
+        let cursor_manager = world.get_resource_mut::<CursorManager>()?;
+        let mut changed = false;
+        if is_paused {
+            settings
+                .resume_cursor
+                .get_or_insert(cursor_manager.cursor_lock_mode);
+            cursor_manager.set_mode(CursorLockMode::NoneVisible);
+            changed = true;
+        } else if let Some(mode) = settings.resume_cursor.take() {
+            cursor_manager.set_mode(mode);
+            changed = true;
+        }
+
+        if changed {
+            let cursor_manager = cursor_manager.clone();
+            cursor_manager.update_cursor(world.get_resource_mut::<WindowManager>()?);
+            world.insert_resource(settings.clone());
+        }
+    }
+
+    if is_paused && settings.dim > 0.0 {
+        let ctx = world.get_resource::<EguiContext>()?.0.clone();
+        let alpha = (settings.dim.clamp(0.0, 1.0) * 255.0) as u8;
+        ctx.layer_painter(egui::LayerId::background()).rect_filled(
+            ctx.viewport_rect(),
+            0.0,
+            egui::Color32::from_black_alpha(alpha),
+        );
+    }
+
+    Ok(())
+}
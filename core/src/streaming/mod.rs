@@ -0,0 +1,211 @@
+use std::{collections::HashSet, sync::Arc};
+
+use anyhow::Result;
+use apostasy_macros::{Component, Resource, update};
+use cgmath::{Vector2, Vector3, Zero};
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+use crate::{
+    log,
+    objects::{Object, components::transform::Transform, scene::ObjectId, world::World},
+    rendering::components::camera::ActiveCamera,
+};
+
+/// Builds the objects belonging to a cell, runs on a worker thread
+/// Note: objects are built at absolute positions and moved relative to the world origin on load
+pub type CellLoaderFn = Arc<dyn Fn(Vector2<i32>) -> Vec<Object> + Send + Sync>;
+
+/// The objects produced by a finished cell load
+pub struct LoadedCell {
+    pub cell: Vector2<i32>,
+    pub objects: Vec<Object>,
+}
+
+/// Marks an object as belonging to a streaming cell, the object is removed when its cell unloads
+#[derive(Component, Clone, Debug)]
+pub struct StreamingCell {
+    pub cell: Vector2<i32>,
+}
+
+impl Default for StreamingCell {
+    fn default() -> Self {
+        Self {
+            cell: Vector2::zero(),
+        }
+    }
+}
+
+impl StreamingCell {
+    pub fn deserialize(&mut self, _value: &serde_yaml::Value) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Gets the cell that contains a world position
+    pub fn from_position(position: Vector3<f32>, cell_size: f32) -> Self {
+        Self {
+            cell: cell_for_position(position, cell_size),
+        }
+    }
+}
+
+/// A resource that enables grid cell streaming of non-voxel objects
+/// Cells within `load_radius` of the active camera are loaded, cells past `unload_radius` are
+/// unloaded, the gap between the two stops cells on a border from reloading every frame
+/// Note: streaming only runs while this resource exists
+#[derive(Resource, Clone)]
+pub struct WorldStreaming {
+    /// the width and depth of a cell in world units
+    pub cell_size: f32,
+    /// the distance (in cells) at which cells are loaded
+    pub load_radius: i32,
+    /// the distance (in cells) at which cells are unloaded, should be above load_radius
+    pub unload_radius: i32,
+    /// the maximum amount of cell loads that can be dispatched per frame
+    pub max_loads_per_frame: usize,
+    pub loader: CellLoaderFn,
+    pub loaded: HashSet<Vector2<i32>>,
+    pub in_flight: HashSet<Vector2<i32>>,
+    pub sender: Sender<LoadedCell>,
+    pub receiver: Receiver<LoadedCell>,
+}
+
+impl WorldStreaming {
+    pub fn new(
+        cell_size: f32,
+        load_radius: i32,
+        unload_radius: i32,
+        loader: impl Fn(Vector2<i32>) -> Vec<Object> + Send + Sync + 'static,
+    ) -> Self {
+        let (sender, receiver) = unbounded::<LoadedCell>();
+        Self {
+            cell_size,
+            load_radius,
+            unload_radius: unload_radius.max(load_radius),
+            max_loads_per_frame: 4,
+            loader: Arc::new(loader),
+            loaded: HashSet::new(),
+            in_flight: HashSet::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Is the cell currently loaded into the world
+    pub fn is_loaded(&self, cell: Vector2<i32>) -> bool {
+        self.loaded.contains(&cell)
+    }
+}
+
+/// Gets the (x, z) cell that contains a world position
+pub fn cell_for_position(position: Vector3<f32>, cell_size: f32) -> Vector2<i32> {
+    Vector2::new(
+        (position.x / cell_size).floor() as i32,
+        (position.z / cell_size).floor() as i32,
+    )
+}
+
+fn cell_distance(a: Vector2<i32>, b: Vector2<i32>) -> i32 {
+    (a.x - b.x).abs().max((a.y - b.y).abs())
+}
+
+#[update]
+pub fn world_streaming_system(world: &mut World) -> Result<()> {
+    if !world.has_resource::<WorldStreaming>() {
+        return Ok(());
+    }
+
+    let Ok(camera) = world.get_object_with_tag::<ActiveCamera>() else {
+        return Ok(());
+    };
+    // cells are laid out in absolute space so they stay put when the origin moves
+    let origin = world.origin_offset().cast::<f32>().unwrap();
+    let camera_position = camera.get_component::<Transform>()?.global_position + origin;
+
+    let streaming = world.get_resource_mut::<WorldStreaming>()?;
+    let camera_cell = cell_for_position(camera_position, streaming.cell_size);
+
+    // dispatch loads for missing cells, closest first
+    let mut wanted: Vec<Vector2<i32>> = Vec::new();
+    let radius = streaming.load_radius;
+    for x in -radius..=radius {
+        for z in -radius..=radius {
+            let cell = camera_cell + Vector2::new(x, z);
+            if !streaming.loaded.contains(&cell) && !streaming.in_flight.contains(&cell) {
+                wanted.push(cell);
+            }
+        }
+    }
+    wanted.sort_by_key(|cell| cell_distance(*cell, camera_cell));
+    wanted.truncate(streaming.max_loads_per_frame);
+
+    for cell in wanted {
+        streaming.in_flight.insert(cell);
+        let loader = streaming.loader.clone();
+        let sender = streaming.sender.clone();
+        rayon::spawn(move || {
+            let objects = loader(cell);
+            let _ = sender.send(LoadedCell { cell, objects });
+        });
+    }
+
+    // add finished cells that are still in range
+    let completed: Vec<LoadedCell> = streaming.receiver.try_iter().collect();
+    let unload_radius = streaming.unload_radius;
+    let mut to_add: Vec<LoadedCell> = Vec::new();
+    for loaded in completed {
+        streaming.in_flight.remove(&loaded.cell);
+        if cell_distance(loaded.cell, camera_cell) > unload_radius {
+            continue;
+        }
+        streaming.loaded.insert(loaded.cell);
+        to_add.push(loaded);
+    }
+
+    // unload cells that have left the unload radius
+    let to_unload: Vec<Vector2<i32>> = streaming
+        .loaded
+        .iter()
+        .copied()
+        .filter(|cell| cell_distance(*cell, camera_cell) > unload_radius)
+        .collect();
+    for cell in &to_unload {
+        streaming.loaded.remove(cell);
+    }
+
+    for loaded in to_add {
+        for mut object in loaded.objects {
+            object.remove_component::<StreamingCell>();
+            object.add_component(StreamingCell { cell: loaded.cell });
+            if object.parent.is_none()
+                && let Ok(transform) = object.get_component_mut::<Transform>()
+            {
+                transform.local_position -= origin;
+                transform.global_position -= origin;
+            }
+            world.add_object(object);
+        }
+    }
+
+    if !to_unload.is_empty() {
+        let ids: Vec<ObjectId> = world
+            .get_objects_with_component_with_ids::<StreamingCell>()
+            .iter()
+            .filter(|(_, object)| {
+                object
+                    .get_component::<StreamingCell>()
+                    .is_ok_and(|c| to_unload.contains(&c.cell))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        log!("Unloading {} streaming cells", to_unload.len());
+        for id in ids {
+            // children are removed along with their parent
+            if world.get_object_mut(id).is_some() {
+                world.remove_object(id);
+            }
+        }
+    }
+
+    Ok(())
+}
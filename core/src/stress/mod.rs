@@ -0,0 +1,245 @@
+use anyhow::Result;
+use apostasy_macros::{Tag, console_command};
+use cgmath::{InnerSpace, Vector3};
+
+use crate::{
+    objects::{
+        components::transform::Transform,
+        pool::{ObjectPool, ObjectPools},
+        scene::ObjectId,
+        world::World,
+    },
+    physics::{collider::Collider, velocity::Velocity},
+    rendering::components::{camera::ActiveCamera, model_renderer::ModelRenderer},
+};
+
+/// The distance between neighbouring objects in a stress grid
+const SPACING: f32 = 2.0;
+
+/// Marks objects spawned by `stress spawn`, removed by `stress clear`
+#[derive(Tag, Clone)]
+pub struct StressObject;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StressKind {
+    /// objects with a model, a collider and a velocity
+    Cube,
+    /// voxels set in the loaded chunks
+    Voxel,
+    Light,
+}
+
+impl StressKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "cube" => Some(StressKind::Cube),
+            "voxel" => Some(StressKind::Voxel),
+            "light" => Some(StressKind::Light),
+            _ => None,
+        }
+    }
+}
+
+/// Gets the positions of a square grid of count points in front of the active camera
+fn grid_positions(world: &World, count: usize) -> Result<Vec<Vector3<f32>>> {
+    let camera = world.get_object_with_tag::<ActiveCamera>()?;
+    let transform = camera.get_component::<Transform>()?;
+    let forward = transform.calculate_global_forward();
+    let flat_forward = Vector3::new(forward.x, 0.0, forward.z);
+    let flat_forward = if flat_forward.magnitude2() > 0.0001 {
+        flat_forward.normalize()
+    } else {
+        Vector3::new(0.0, 0.0, -1.0)
+    };
+
+    let side = (count as f32).sqrt().ceil() as usize;
+    let half = side as f32 * SPACING * 0.5;
+    let center = transform.global_position + flat_forward * (half + 4.0);
+
+    Ok((0..count)
+        .map(|i| {
+            let x = (i % side) as f32 * SPACING - half;
+            let z = (i / side) as f32 * SPACING - half;
+            center + Vector3::new(x, 0.0, z)
+        })
+        .collect())
+}
+
+/// Spawns count objects or voxels of a kind in a grid in front of the active camera
+/// returns how many were spawned, voxels outside of loaded chunks are skipped
+pub fn spawn_stress_grid(world: &mut World, count: usize, kind: StressKind) -> Result<usize> {
+    let positions = grid_positions(world, count)?;
+
+    match kind {
+        StressKind::Cube => {
+            let ids = world.spawn_batch(positions.iter().map(|position| {
+                (
+                    Transform {
+                        local_position: *position,
+                        ..Default::default()
+                    },
+                    ModelRenderer::from_path("model.glb"),
+                    Collider::default(),
+                    Velocity::default(),
+                )
+            }));
+            for id in ids {
+                if let Some(object) = world.get_object_mut(id) {
+                    object.name = "Stress Cube".to_string();
+                    object.tags.push(Box::new(StressObject));
+                }
+            }
+            Ok(positions.len())
+        }
+        StressKind::Voxel => {
+            let origin = world.origin_offset();
+            let spawned = positions
+                .iter()
+                .filter(|position| {
+                    world.set_voxel(
+                        position.x.floor() as i32 + origin.x,
+                        position.y.floor() as i32 + origin.y,
+                        position.z.floor() as i32 + origin.z,
+                        1,
+                    )
+                })
+                .count();
+            Ok(spawned)
+        }
+        StressKind::Light => Err(anyhow::anyhow!("The engine has no lights yet")),
+    }
+}
+
+/// Removes every object spawned by `stress spawn`
+/// Note: voxels are part of the world and stay
+pub fn clear_stress_objects(world: &mut World) -> usize {
+    let ids: Vec<ObjectId> = world
+        .get_objects_with_tag_with_ids::<StressObject>()
+        .iter()
+        .map(|(id, _)| *id)
+        .collect();
+    for id in &ids {
+        world.remove_object(*id);
+    }
+    ids.len()
+}
+
+/// How long a spawn and removal churn took, see `churn_objects`
+#[derive(Clone, Copy, Debug)]
+pub struct ChurnResult {
+    pub rounds: usize,
+    pub spawned: usize,
+    pub total_ms: f32,
+    /// the object capacity after the churn, it stops growing once removed slots are reused
+    pub capacity: usize,
+}
+
+/// Spawns and removes `count` objects `rounds` times, like projectiles or particles would, and
+/// times it
+/// Note: every other object is removed early so removals land in the middle of the storage
+pub fn churn_objects(world: &mut World, count: usize, rounds: usize) -> ChurnResult {
+    let start = std::time::Instant::now();
+    for _ in 0..rounds {
+        let ids =
+            world.spawn_batch((0..count).map(|_| (Transform::default(), Velocity::default())));
+        for id in ids.iter().step_by(2) {
+            world.remove_object(*id);
+        }
+        for _ in world.query::<(&mut Transform, &Velocity)>() {}
+        for id in ids.iter().skip(1).step_by(2) {
+            world.remove_object(*id);
+        }
+    }
+    ChurnResult {
+        rounds,
+        spawned: count * rounds,
+        total_ms: start.elapsed().as_secs_f32() * 1000.0,
+        capacity: world.object_capacity(),
+    }
+}
+
+/// The name of the pool `churn_pooled_objects` uses, removed once it is done
+const STRESS_POOL: &str = "stress";
+
+/// Like `churn_objects` but the objects come from an `ObjectPool` and go back into it, to compare
+/// the two
+pub fn churn_pooled_objects(world: &mut World, count: usize, rounds: usize) -> Result<ChurnResult> {
+    world.add_pool(
+        STRESS_POOL,
+        ObjectPool::from_fn(|world| Ok(world.spawn((Transform::default(), Velocity::default()))))
+            .prewarm(count),
+    )?;
+
+    let start = std::time::Instant::now();
+    for _ in 0..rounds {
+        let ids = (0..count)
+            .map(|_| world.acquire_from_pool(STRESS_POOL))
+            .collect::<Result<Vec<_>>>()?;
+        for id in ids.iter().step_by(2) {
+            world.release_to_pool(*id);
+        }
+        for _ in world.query::<(&mut Transform, &Velocity)>() {}
+        for id in ids.iter().skip(1).step_by(2) {
+            world.release_to_pool(*id);
+        }
+    }
+    let result = ChurnResult {
+        rounds,
+        spawned: count * rounds,
+        total_ms: start.elapsed().as_secs_f32() * 1000.0,
+        capacity: world.object_capacity(),
+    };
+
+    world
+        .get_resource_mut::<ObjectPools>()?
+        .pools
+        .remove(STRESS_POOL);
+    Ok(result)
+}
+
+/// Spawns objects in a grid to measure performance, or clears them
+#[console_command(
+    name = "stress",
+    inputs = "spawn <n> [cube|voxel|light] | churn <n> [rounds] [pooled] | clear"
+)]
+pub fn stress_command(world: &mut World, args: &[&str]) -> Result<()> {
+    match args {
+        ["spawn", count] | ["spawn", count, _] => {
+            let count: usize = count.parse()?;
+            let kind_name = args.get(2).copied().unwrap_or("cube");
+            let kind = StressKind::parse(kind_name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown stress kind {}", kind_name))?;
+            let spawned = spawn_stress_grid(world, count, kind)?;
+            world.console_print(format!("Spawned {} {}s", spawned, kind_name));
+        }
+        ["churn", count] | ["churn", count, _] | ["churn", count, _, "pooled"] => {
+            let count: usize = count.parse()?;
+            let rounds = match args.get(2) {
+                Some(rounds) => rounds.parse()?,
+                None => 100,
+            };
+            let result = match args.get(3) {
+                Some(_) => churn_pooled_objects(world, count, rounds)?,
+                None => churn_objects(world, count, rounds),
+            };
+            world.console_print(format!(
+                "Churned {} objects over {} rounds in {:.2} ms, {:.3} ms a round, room for {}",
+                result.spawned,
+                result.rounds,
+                result.total_ms,
+                result.total_ms / result.rounds.max(1) as f32,
+                result.capacity
+            ));
+        }
+        ["clear"] => {
+            let removed = clear_stress_objects(world);
+            world.console_print(format!("Removed {} stress objects", removed));
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Usage: stress spawn <n> [cube|voxel|light] | churn <n> [rounds] [pooled] | clear"
+            ));
+        }
+    }
+    Ok(())
+}
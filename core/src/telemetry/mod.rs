@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use apostasy_macros::{Resource, start, update};
+use serde::{Deserialize, Serialize};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+use crate::{
+    log_warn,
+    objects::{
+        resources::input_manager::{InputManager, KeyAction, KeyBind},
+        systems::Time,
+        world::World,
+    },
+    rendering::shared::{frustrum::ObjectsDrawing, rendering_config::RenderingStats},
+    ui::ui_context::EguiContext,
+    utils::yaml,
+};
+
+pub const TELEMETRY_SETTINGS_PATH: &str = "res/telemetry.yaml";
+
+/// How many frames the frame time graph shows
+const FRAME_HISTORY: usize = 240;
+/// The frame time at the top of the graph, 30 fps
+const GRAPH_MAX_MS: f32 = 33.3;
+const GRAPH_HEIGHT: f32 = 60.0;
+
+/// Which rows the telemetry overlay shows, saved to `TELEMETRY_SETTINGS_PATH`
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    pub show_fps: bool,
+    pub show_frame_graph: bool,
+    pub show_fixed_steps: bool,
+    pub show_objects: bool,
+    pub show_memory: bool,
+    pub show_gpu: bool,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            show_fps: true,
+            show_frame_graph: true,
+            show_fixed_steps: true,
+            show_objects: true,
+            show_memory: true,
+            show_gpu: true,
+        }
+    }
+}
+
+impl TelemetrySettings {
+    /// Loads the settings from disk, falling back to the defaults if they are missing or invalid
+    pub fn load() -> Self {
+        yaml::load_or_default(TELEMETRY_SETTINGS_PATH)
+    }
+
+    /// Saves the settings to `TELEMETRY_SETTINGS_PATH`
+    pub fn save(&self) -> Result<()> {
+        yaml::save(TELEMETRY_SETTINGS_PATH, self)
+    }
+}
+
+/// The frame times recorded while the overlay is open
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Telemetry {
+    pub is_open: bool,
+    /// the last frame times in milliseconds, oldest first
+    pub frame_times: VecDeque<f32>,
+}
+
+impl Telemetry {
+    pub fn record(&mut self, frame_time_ms: f32) {
+        if self.frame_times.len() == FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time_ms);
+    }
+
+    pub fn average_ms(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32
+    }
+
+    pub fn worst_ms(&self) -> f32 {
+        self.frame_times.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+/// The resident memory of the process, none where it can't be read
+pub fn process_memory_bytes() -> Option<u64> {
+    // the second field of statm is the resident page count
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    format!("{:.1} MB", bytes as f64 / MB)
+}
+
+#[start]
+pub fn telemetry_start(world: &mut World) -> Result<()> {
+    world.insert_resource(Telemetry::default());
+    world.insert_resource(TelemetrySettings::load());
+    world
+        .get_resource_mut::<InputManager>()?
+        .register_keybind(KeyBind::new(
+            PhysicalKey::Code(KeyCode::F3),
+            KeyAction::Press,
+            "ToggleTelemetry",
+        ));
+    Ok(())
+}
+
+/// Draws frame times, object counts and memory estimates in the top right corner
+#[update(while_paused = true)]
+pub fn telemetry_overlay(world: &mut World) -> Result<()> {
+    let toggle = world
+        .get_resource::<InputManager>()?
+        .is_keybind_active("ToggleTelemetry");
+    let frame_time_ms = world.get_resource::<Time>()?.unscaled_delta * 1000.0;
+    let Ok(telemetry) = world.get_resource_mut::<Telemetry>() else {
+        return Ok(());
+    };
+    if toggle {
+        telemetry.is_open = !telemetry.is_open;
+        telemetry.frame_times.clear();
+    }
+    if !telemetry.is_open {
+        return Ok(());
+    }
+    if frame_time_ms > 0.0 {
+        telemetry.record(frame_time_ms);
+    }
+    let telemetry = telemetry.clone();
+
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let mut settings = world.get_resource::<TelemetrySettings>()?.clone();
+    let stats = world
+        .get_resource::<RenderingStats>()
+        .copied()
+        .unwrap_or_default();
+    let objects_drawing = world
+        .get_resource::<ObjectsDrawing>()
+        .map(|d| d.0)
+        .unwrap_or(0);
+    let object_count = world.object_count();
+    let fixed_steps = world.fixed_steps();
+
+    egui::Area::new(egui::Id::new("telemetry_overlay"))
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+        .interactable(true)
+        .show(&ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_min_width(220.0);
+
+                if settings.show_fps {
+                    let average = telemetry.average_ms();
+                    let fps = if average > 0.0 { 1000.0 / average } else { 0.0 };
+                    ui.label(format!("{:.0} fps, {:.2} ms", fps, average));
+                    ui.label(format!("Worst: {:.2} ms", telemetry.worst_ms()));
+                }
+                if settings.show_frame_graph {
+                    frame_graph(ui, &telemetry.frame_times);
+                }
+                if settings.show_fixed_steps {
+                    ui.label(format!("Fixed steps: {}", fixed_steps));
+                }
+                if settings.show_objects {
+                    ui.label(format!("Objects: {}", object_count));
+                    ui.label(format!("Objects drawing: {}", objects_drawing));
+                }
+                if settings.show_gpu {
+                    ui.label(format!("Draw calls: {}", stats.draw_calls));
+                    match stats.gpu_time_ms {
+                        Some(ms) => ui.label(format!("GPU: {:.2} ms", ms)),
+                        None => ui.label("GPU: unsupported"),
+                    };
+                }
+                if settings.show_memory {
+                    match process_memory_bytes() {
+                        Some(bytes) => ui.label(format!("CPU memory: {}", format_bytes(bytes))),
+                        None => ui.label("CPU memory: unknown"),
+                    };
+                    ui.label(format!(
+                        "GPU memory: {}",
+                        format_bytes(stats.gpu_memory_bytes)
+                    ));
+                }
+
+                ui.collapsing("Rows", |ui| {
+                    ui.checkbox(&mut settings.show_fps, "FPS");
+                    ui.checkbox(&mut settings.show_frame_graph, "Frame graph");
+                    ui.checkbox(&mut settings.show_fixed_steps, "Fixed steps");
+                    ui.checkbox(&mut settings.show_objects, "Objects");
+                    ui.checkbox(&mut settings.show_gpu, "GPU");
+                    ui.checkbox(&mut settings.show_memory, "Memory");
+                });
+            });
+        });
+
+    let current = world.get_resource_mut::<TelemetrySettings>()?;
+    if *current != settings {
+        if let Err(e) = settings.save() {
+            log_warn!("Failed to save telemetry settings: {}", e);
+        }
+        *current = settings;
+    }
+
+    Ok(())
+}
+
+/// Draws a bar per frame, the line marks 60 fps
+fn frame_graph(ui: &mut egui::Ui, frame_times: &VecDeque<f32>) {
+    let width = ui.available_width();
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, GRAPH_HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(120));
+
+    let bar_width = width / FRAME_HISTORY as f32;
+    for (i, ms) in frame_times.iter().enumerate() {
+        let height = (ms / GRAPH_MAX_MS).min(1.0) * GRAPH_HEIGHT;
+        let color = if *ms > 1000.0 / 30.0 {
+            egui::Color32::from_rgb(220, 80, 70)
+        } else if *ms > 1000.0 / 60.0 {
+            egui::Color32::from_rgb(220, 180, 70)
+        } else {
+            egui::Color32::from_rgb(90, 190, 100)
+        };
+        let x = rect.left() + i as f32 * bar_width;
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(x, rect.bottom() - height),
+                egui::pos2(x + bar_width.max(1.0), rect.bottom()),
+            ),
+            0.0,
+            color,
+        );
+    }
+
+    let target = rect.bottom() - (1000.0 / 60.0 / GRAPH_MAX_MS) * GRAPH_HEIGHT;
+    painter.hline(
+        rect.x_range(),
+        target,
+        egui::Stroke::new(1.0, egui::Color32::from_gray(160)),
+    );
+}
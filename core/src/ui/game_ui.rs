@@ -0,0 +1,108 @@
+use std::cmp::Reverse;
+
+use anyhow::Result;
+use apostasy_macros::{Resource, update};
+use egui::{FontId, TextStyle};
+
+use crate::{
+    log_error,
+    objects::{registry::registered, resources::input_manager::InputManager, world::World},
+    ui::ui_context::EguiContext,
+};
+
+/// A function drawing part of the game's HUD, registered with `#[game_ui]`
+pub struct GameUiSystem {
+    pub name: &'static str,
+    pub func: fn(&mut World, &mut egui::Ui) -> Result<()>,
+    pub priority: u32,
+    /// the cargo package the function was registered in
+    pub package: &'static str,
+    /// set with `while_paused = true`, e.g. for a pause menu, other HUD is hidden while paused
+    pub while_paused: bool,
+}
+#[cfg(not(target_family = "wasm"))]
+inventory::collect!(GameUiSystem);
+
+/// How the HUD looks, kept apart from the style of the editor and console windows
+#[derive(Resource, Clone)]
+pub struct HudStyle {
+    pub style: egui::Style,
+    /// hides every `#[game_ui]` function, e.g. for screenshots
+    pub is_hidden: bool,
+}
+
+impl Default for HudStyle {
+    fn default() -> Self {
+        let mut style = egui::Style {
+            visuals: egui::Visuals::dark(),
+            ..Default::default()
+        };
+        style.visuals.panel_fill = egui::Color32::TRANSPARENT;
+        style.visuals.window_fill = egui::Color32::from_black_alpha(160);
+        style.text_styles = [
+            (TextStyle::Heading, FontId::proportional(28.0)),
+            (TextStyle::Body, FontId::proportional(18.0)),
+            (TextStyle::Button, FontId::proportional(18.0)),
+            (TextStyle::Small, FontId::proportional(14.0)),
+            (TextStyle::Monospace, FontId::monospace(16.0)),
+        ]
+        .into();
+        Self {
+            style,
+            is_hidden: false,
+        }
+    }
+}
+
+/// Stops keybinds and mousebinds while egui has the keyboard or the pointer, so clicking a HUD
+/// button doesn't also shoot and typing in a field doesn't walk
+/// Note: runs first, egui decides from the layout of the last frame
+#[update(priority = 100, while_paused = true)]
+pub fn capture_ui_input(world: &mut World) -> Result<()> {
+    let Ok(ctx) = world.get_resource::<EguiContext>().map(|ctx| ctx.0.clone()) else {
+        return Ok(());
+    };
+    let inputs = world.get_resource_mut::<InputManager>()?;
+    inputs.keyboard_captured = ctx.wants_keyboard_input();
+    inputs.pointer_captured = ctx.wants_pointer_input();
+    Ok(())
+}
+
+/// Draws every `#[game_ui]` function into one area covering the window, with the `HudStyle`
+/// Note: the HUD is below egui windows, and only takes the pointer where it placed widgets
+#[update(while_paused = true)]
+pub fn game_ui_system(world: &mut World) -> Result<()> {
+    let Ok(ctx) = world.get_resource::<EguiContext>().map(|ctx| ctx.0.clone()) else {
+        return Ok(());
+    };
+    let hud = world.init_resource::<HudStyle>()?;
+    if hud.is_hidden {
+        return Ok(());
+    }
+    let style = hud.style.clone();
+
+    let is_paused = world.is_paused();
+    let mut systems: Vec<&GameUiSystem> = registered::<GameUiSystem>()
+        .filter(|system| !is_paused || system.while_paused)
+        .filter(|system| world.is_system_enabled(system.package, system.name))
+        .collect();
+    if systems.is_empty() {
+        return Ok(());
+    }
+    systems.sort_by_key(|system| Reverse(system.priority));
+
+    egui::Area::new(egui::Id::new("game_ui"))
+        .order(egui::Order::Background)
+        .fixed_pos(egui::Pos2::ZERO)
+        .show(&ctx, |ui| {
+            ui.set_style(style);
+            ui.set_clip_rect(ctx.content_rect());
+            for system in systems {
+                if let Err(e) = (system.func)(world, ui) {
+                    log_error!("HUD {}::{} failed: {}", system.package, system.name, e);
+                }
+            }
+        });
+
+    Ok(())
+}
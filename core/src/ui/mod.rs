@@ -10,6 +10,7 @@ use crate::rendering::vulkan::{
     rendering_context::VulkanRenderingContext, swapchain::VulkanSwapchain,
 };
 
+pub mod game_ui;
 pub mod ui_context;
 
 pub struct UIRenderer {
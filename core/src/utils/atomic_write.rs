@@ -0,0 +1,59 @@
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow};
+
+/// Gets `<path>.<extension>`, e.g. `scene.yaml` and `bak` give `scene.yaml.bak`
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut name: OsString = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// The copy `write_atomic` keeps of the previous version of a file
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_added_extension(path, "bak")
+}
+
+/// Writes a file so a crash leaves either the old or the new contents, never half of each
+/// The contents go to `<path>.tmp` first, which is renamed over the file once it is on disk
+/// Note: the previous version is copied to `<path>.bak` before it is replaced
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let temp_path = with_added_extension(path, "tmp");
+    let written = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents.as_ref())?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(anyhow!("Failed to write {}: {}", temp_path.display(), e));
+    }
+
+    if path.exists() {
+        let backup = backup_path(path);
+        if let Err(e) = std::fs::copy(path, &backup) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(anyhow!(
+                "Failed to back up {} to {}: {}",
+                path.display(),
+                backup.display(),
+                e
+            ));
+        }
+    }
+
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| anyhow!("Failed to replace {}: {}", path.display(), e))
+}
@@ -1,2 +1,5 @@
+pub mod atomic_write;
 pub mod flatten;
 pub mod logging;
+pub mod rng;
+pub mod yaml;
@@ -0,0 +1,142 @@
+use apostasy_macros::Resource;
+use cgmath::{InnerSpace, Vector3};
+use hashbrown::HashMap;
+use rand::{
+    RngExt, SeedableRng,
+    distr::uniform::{SampleRange, SampleUniform},
+    rngs::Xoshiro256PlusPlus,
+};
+
+/// The seed of a world nobody seeded
+pub const DEFAULT_SEED: u64 = 0x5EED;
+
+/// A deterministic random number generator, the same seed gives the same numbers on every platform
+#[derive(Clone, Debug)]
+pub struct RngStream {
+    rng: Xoshiro256PlusPlus,
+}
+
+impl RngStream {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+        }
+    }
+
+    /// Gets a random number in a range, `0..10` excludes 10 and `0..=10` includes it
+    pub fn range<T: SampleUniform, R: SampleRange<T>>(&mut self, range: R) -> T {
+        self.rng.random_range(range)
+    }
+
+    /// Gets a random number from 0 to 1
+    pub fn unit(&mut self) -> f32 {
+        self.rng.random()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.rng.random()
+    }
+
+    /// Returns true with a probability from 0 to 1
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.unit() < probability
+    }
+
+    /// Gets a random direction, every direction is equally likely
+    pub fn unit_vector(&mut self) -> Vector3<f32> {
+        let z = self.range(-1.0..=1.0f32);
+        let angle = self.range(0.0..std::f32::consts::TAU);
+        let radius = (1.0 - z * z).sqrt();
+        Vector3::new(radius * angle.cos(), radius * angle.sin(), z).normalize()
+    }
+
+    /// Picks a random item, None when there are no items
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.range(0..items.len()))
+    }
+
+    /// Picks a random item with a chance proportional to its weight
+    /// returns None when there are no items or no positive weights
+    pub fn weighted_pick<'a, T>(&mut self, items: &'a [(T, f32)]) -> Option<&'a T> {
+        let total: f32 = items.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut target = self.unit() * total;
+        let mut last = None;
+        for (item, weight) in items {
+            if *weight <= 0.0 {
+                continue;
+            }
+            if target < *weight {
+                return Some(item);
+            }
+            target -= weight;
+            last = Some(item);
+        }
+        // float rounding can leave target just above the last weight
+        last
+    }
+}
+
+/// The random numbers of a world, every stream is derived from the world seed
+/// Note: systems should use their own named stream, so adding a system does not change the
+/// numbers every other system gets
+#[derive(Resource, Clone, Debug)]
+pub struct WorldRng {
+    seed: u64,
+    global: RngStream,
+    streams: HashMap<String, RngStream>,
+}
+
+impl Default for WorldRng {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+impl WorldRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            global: RngStream::new(seed),
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts every stream from a new seed
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    /// Gets the stream shared by everything that does not use a named stream
+    pub fn global(&mut self) -> &mut RngStream {
+        &mut self.global
+    }
+
+    /// Gets a named stream, created from the world seed and the name the first time it is used
+    pub fn stream(&mut self, name: &str) -> &mut RngStream {
+        let seed = self.seed;
+        self.streams
+            .entry(name.to_string())
+            .or_insert_with(|| RngStream::new(stream_seed(seed, name)))
+    }
+}
+
+/// Mixes the world seed with a stream name, fnv-1a so the seed never changes between builds
+fn stream_seed(seed: u64, name: &str) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64 ^ seed;
+    for byte in name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::log_warn;
+
+/// Loads a settings file, falling back to the defaults if it is missing or invalid
+pub fn load_or_default<T: DeserializeOwned + Default>(path: &str) -> T {
+    if !Path::new(path).exists() {
+        return T::default();
+    }
+
+    match std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| Ok(serde_yaml::from_str::<T>(&contents)?))
+    {
+        Ok(value) => value,
+        Err(e) => {
+            log_warn!("Failed to load {}: {}", path, e);
+            T::default()
+        }
+    }
+}
+
+/// Saves a settings file, creating its directory if needed
+pub fn save<T: Serialize>(path: &str, value: &T) -> Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let yaml = serde_yaml::to_string(value)?;
+    std::fs::write(path, yaml)?;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use apostasy_macros::{Resource, update};
+use cgmath::Vector3;
+use hashbrown::{HashMap, HashSet};
+
+use crate::{
+    log_warn,
+    objects::world::World,
+    utils::flatten::flatten,
+    voxels::{
+        chunk::Chunk,
+        meshes::{NeedsRemeshing, VoxelBreakRemesh},
+        structure::StructureAsset,
+        voxel::{VoxelId, VoxelRegistry},
+    },
+};
+
+/// Chunks changed by bulk edits that are waiting to be tagged for remeshing
+/// Note: only `chunks_per_frame` chunks are handed to the mesher each frame so a large edit is
+/// spread over several frames instead of queuing every chunk at once
+#[derive(Resource, Clone)]
+pub struct RemeshBacklog {
+    pub pending: VecDeque<(i32, i32, i32)>,
+    queued: HashSet<(i32, i32, i32)>,
+    pub chunks_per_frame: usize,
+}
+
+impl Default for RemeshBacklog {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            queued: HashSet::new(),
+            chunks_per_frame: 8,
+        }
+    }
+}
+
+impl RemeshBacklog {
+    /// Queues a chunk, chunks already waiting keep their place
+    pub fn push(&mut self, chunk: (i32, i32, i32)) {
+        if self.queued.insert(chunk) {
+            self.pending.push_back(chunk);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl World {
+    /// Sets many voxels at once, each chunk is written to once and queued for remeshing once
+    /// returns how many voxels changed
+    /// Note: voxels in chunks that aren't loaded are skipped
+    pub fn set_voxels(
+        &mut self,
+        edits: impl IntoIterator<Item = (Vector3<i32>, VoxelId)>,
+    ) -> usize {
+        let mut by_chunk: HashMap<(i32, i32, i32), Vec<(Vector3<u32>, VoxelId)>> = HashMap::new();
+        for (position, id) in edits {
+            let key = (
+                position.x.div_euclid(32),
+                position.y.div_euclid(32),
+                position.z.div_euclid(32),
+            );
+            if !self.chunk_position_index.contains_key(&key) {
+                continue;
+            }
+            let local = Vector3::new(
+                position.x.rem_euclid(32) as u32,
+                position.y.rem_euclid(32) as u32,
+                position.z.rem_euclid(32) as u32,
+            );
+            by_chunk.entry(key).or_default().push((local, id));
+        }
+
+        let mut changed = 0;
+        let mut dirty: HashSet<(i32, i32, i32)> = HashSet::new();
+        for (key, voxels) in by_chunk {
+            let Some(&oid) = self.chunk_position_index.get(&key) else {
+                continue;
+            };
+            let Some(chunk) = self
+                .scene
+                .objects
+                .get_mut(oid)
+                .and_then(|obj| obj.get_component_mut::<Chunk>().ok())
+            else {
+                continue;
+            };
+            for (local, id) in voxels {
+                let index = flatten(local.x, local.y, local.z, 32);
+                if chunk.voxels[index] == id {
+                    continue;
+                }
+                chunk.voxels[index] = id;
+                changed += 1;
+                dirty.insert(key);
+
+                // voxels on a chunk edge change the faces of the chunk next to them
+                let edges = [
+                    (local.x == 0, (-1, 0, 0)),
+                    (local.x == 31, (1, 0, 0)),
+                    (local.y == 0, (0, -1, 0)),
+                    (local.y == 31, (0, 1, 0)),
+                    (local.z == 0, (0, 0, -1)),
+                    (local.z == 31, (0, 0, 1)),
+                ];
+                for (is_edge, (dx, dy, dz)) in edges {
+                    if is_edge {
+                        dirty.insert((key.0 + dx, key.1 + dy, key.2 + dz));
+                    }
+                }
+            }
+        }
+
+        if changed == 0 {
+            return 0;
+        }
+        let loaded: Vec<(i32, i32, i32)> = dirty
+            .into_iter()
+            .filter(|key| self.chunk_position_index.contains_key(key))
+            .collect();
+        if let Ok(backlog) = self.init_resource::<RemeshBacklog>() {
+            for key in loaded {
+                backlog.push(key);
+            }
+        }
+        changed
+    }
+
+    /// Sets every voxel between `min` and `max` inclusive to `id`, 0 clears them
+    /// returns how many voxels changed
+    pub fn fill_box(&mut self, min: Vector3<i32>, max: Vector3<i32>, id: VoxelId) -> usize {
+        let (lo, hi) = (
+            Vector3::new(min.x.min(max.x), min.y.min(max.y), min.z.min(max.z)),
+            Vector3::new(min.x.max(max.x), min.y.max(max.y), min.z.max(max.z)),
+        );
+        let edits = (lo.z..=hi.z).flat_map(move |z| {
+            (lo.y..=hi.y).flat_map(move |y| (lo.x..=hi.x).map(move |x| (Vector3::new(x, y, z), id)))
+        });
+        self.set_voxels(edits)
+    }
+
+    /// Clears every voxel within `radius` of `center`, e.g. for an explosion
+    /// returns how many voxels were removed
+    pub fn carve_sphere(&mut self, center: Vector3<i32>, radius: f32) -> usize {
+        let r = radius.max(0.0).ceil() as i32;
+        let radius_squared = radius * radius;
+        let mut edits = Vec::new();
+        for z in -r..=r {
+            for y in -r..=r {
+                for x in -r..=r {
+                    if (x * x + y * y + z * z) as f32 <= radius_squared {
+                        edits.push((center + Vector3::new(x, y, z), 0));
+                    }
+                }
+            }
+        }
+        self.set_voxels(edits)
+    }
+
+    /// Places a structure with its origin at `position`
+    /// returns how many voxels changed
+    /// Note: blocks with a voxel name that isn't registered are skipped
+    pub fn paste_structure(
+        &mut self,
+        structure: &StructureAsset,
+        position: Vector3<i32>,
+    ) -> Result<usize> {
+        let registry = self.get_resource::<VoxelRegistry>()?;
+        let origin = Vector3::from(structure.origin);
+        let mut edits = Vec::with_capacity(structure.blocks.len());
+        let mut unknown = 0;
+        for block in &structure.blocks {
+            let Some(&id) = registry.name_to_id.get(&block.voxel) else {
+                unknown += 1;
+                continue;
+            };
+            edits.push((position + Vector3::from(block.position) - origin, id));
+        }
+        if unknown > 0 {
+            log_warn!(
+                "Skipped {} blocks of structure {} with unregistered voxels",
+                unknown,
+                structure.name
+            );
+        }
+        Ok(self.set_voxels(edits))
+    }
+}
+
+/// Hands the next few chunks of the `RemeshBacklog` to the mesher
+#[update(while_paused = true)]
+pub fn stage_bulk_remeshes(world: &mut World) -> Result<()> {
+    let Ok(backlog) = world.get_resource_mut::<RemeshBacklog>() else {
+        return Ok(());
+    };
+    let count = backlog.chunks_per_frame.min(backlog.pending.len());
+    let staged: Vec<(i32, i32, i32)> = backlog.pending.drain(..count).collect();
+    for key in &staged {
+        backlog.queued.remove(key);
+    }
+
+    for key in staged {
+        let Some(&oid) = world.chunk_position_index.get(&key) else {
+            continue;
+        };
+        if let Some(obj) = world.get_object_mut(oid) {
+            obj.add_tag(NeedsRemeshing);
+            obj.add_tag(VoxelBreakRemesh);
+        }
+    }
+    Ok(())
+}
@@ -17,6 +17,8 @@ use crate::{
 };
 
 #[derive(Resource, Clone, Default)]
+#[resource_init]
+#[resource_package(Voxel)]
 pub struct VoxelBreakProgress {
     pub progress: HashMap<(i32, i32, i32), u32>,
 }
@@ -12,6 +12,8 @@ pub struct ChunkLoadBounds {
 }
 
 #[derive(Resource, Clone, Default)]
+#[resource_init]
+#[resource_package(Voxel)]
 pub struct ChunkPositionMap {
     pub position_to_id: HashMap<Vector3<i32>, ObjectId>,
     pub position_to_lod: HashMap<Vector3<i32>, u8>,
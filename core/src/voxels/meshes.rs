@@ -13,6 +13,7 @@ use crate::objects::Object;
 use crate::objects::scene::ObjectId;
 use crate::objects::world::World;
 use crate::rendering::shared::model::GpuMesh;
+use crate::rendering::shared::released_buffers::ReleasedBuffers;
 use crate::rendering::shared::vertex::VertexDefinition;
 use crate::rendering::vulkan::rendering_context::VulkanRenderingContext;
 use crate::utils::flatten::flatten;
@@ -108,6 +109,7 @@ impl VertexDefinition for VoxelVertex {
 }
 
 #[derive(Debug, Component, Clone, Default)]
+#[component_on_remove(release_chunk_mesh)]
 pub struct VoxelChunkMesh {
     pub vertex_buffer: Buffer,
     pub vertex_buffer_memory: DeviceMemory,
@@ -117,6 +119,7 @@ pub struct VoxelChunkMesh {
 }
 
 #[derive(Debug, Component, Clone, Default)]
+#[component_on_remove(release_water_mesh)]
 pub struct WaterMesh {
     pub vertex_buffer: Buffer,
     pub vertex_buffer_memory: DeviceMemory,
@@ -136,6 +139,35 @@ impl WaterMesh {
     }
 }
 
+/// Queues the buffers of a removed mesh for the renderer to destroy
+fn release_mesh_buffers(world: &mut World, buffers: [(Buffer, DeviceMemory); 2]) {
+    if let Ok(released) = world.init_resource::<ReleasedBuffers>() {
+        for (buffer, memory) in buffers {
+            released.release(buffer, memory);
+        }
+    }
+}
+
+fn release_chunk_mesh(world: &mut World, _id: ObjectId, mesh: &mut VoxelChunkMesh) {
+    release_mesh_buffers(
+        world,
+        [
+            (mesh.vertex_buffer, mesh.vertex_buffer_memory),
+            (mesh.index_buffer, mesh.index_buffer_memory),
+        ],
+    );
+}
+
+fn release_water_mesh(world: &mut World, _id: ObjectId, mesh: &mut WaterMesh) {
+    release_mesh_buffers(
+        world,
+        [
+            (mesh.vertex_buffer, mesh.vertex_buffer_memory),
+            (mesh.index_buffer, mesh.index_buffer_memory),
+        ],
+    );
+}
+
 #[derive(Debug, Tag, Clone, Default)]
 pub struct NeedsRemeshing;
 
@@ -425,12 +457,10 @@ fn defer_destroy(
 }
 
 // destroys a buffer memory immediately
-fn destroy_now(device: &ash::Device, buffer: vk::Buffer, memory: vk::DeviceMemory) {
-    unsafe {
-        if buffer != vk::Buffer::null() {
-            device.destroy_buffer(buffer, None);
-            device.free_memory(memory, None);
-        }
+fn destroy_now(ctx: &VulkanRenderingContext, buffer: vk::Buffer, memory: vk::DeviceMemory) {
+    if buffer != vk::Buffer::null() {
+        unsafe { ctx.device.destroy_buffer(buffer, None) };
+        ctx.free_memory(memory);
     }
 }
 
@@ -474,8 +504,8 @@ fn upload_water_mesh(
 ) -> Result<()> {
     // water buffers are destroyed immediately rather than deferred
     if let Ok(old) = object.get_component::<WaterMesh>() {
-        destroy_now(&ctx.device, old.vertex_buffer, old.vertex_buffer_memory);
-        destroy_now(&ctx.device, old.index_buffer, old.index_buffer_memory);
+        destroy_now(ctx, old.vertex_buffer, old.vertex_buffer_memory);
+        destroy_now(ctx, old.index_buffer, old.index_buffer_memory);
     }
 
     let (vb, vbm) = ctx.create_vertex_buffer(vertices, command_pool)?;
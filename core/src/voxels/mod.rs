@@ -2,6 +2,7 @@ use apostasy_macros::Component;
 use cgmath::{Vector3, Zero};
 
 pub mod biome;
+pub mod bulk_edit;
 pub mod chunk;
 pub mod chunk_loader;
 pub mod heightmap;
@@ -13,8 +13,35 @@ use crate::{log_warn, rendering::vulkan::rendering_context::VulkanRenderingConte
 pub struct PendingAtlas {
     pub image: RgbaImage,
     pub tiles: u32,
+    pub tile_size: u32,
+    /// the texture of every tile in order, e.g. `textures/stone.png`
+    pub paths: Vec<String>,
+}
+
+impl PendingAtlas {
+    /// Draws a texture over the tile it has in the atlas, returns false if it has none
+    pub fn replace_tile(&mut self, path: &str, image: &DynamicImage) -> bool {
+        let Some(index) = self.paths.iter().position(|p| p == path) else {
+            return false;
+        };
+        let tx = (index as u32 % self.tiles) * self.tile_size;
+        let ty = (index as u32 / self.tiles) * self.tile_size;
+        let resized = image.resize_exact(
+            self.tile_size,
+            self.tile_size,
+            image::imageops::FilterType::Nearest,
+        );
+        for (px, py, pixel) in resized.pixels() {
+            self.image.put_pixel(tx + px, ty + py, pixel);
+        }
+        true
+    }
 }
 
+/// Inserted after `PendingAtlas` changes, the engine uploads it again and removes this
+#[derive(Resource, Clone, Copy)]
+pub struct AtlasChanged;
+
 #[derive(Resource, Clone, Debug)]
 pub struct VoxelTextureAtlas {
     pub image: vk::Image,
@@ -111,133 +138,12 @@ pub fn upload_atlas(
         return Err(anyhow::anyhow!("Cannot upload empty texture atlas"));
     }
 
-    let pixels = image.as_raw();
-    let size = pixels.len() as vk::DeviceSize;
-
-    // staging buffer
-    let (staging_buffer, staging_memory) = ctx.create_buffer(
-        size,
-        vk::BufferUsageFlags::TRANSFER_SRC,
-        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-    )?;
-
-    // copy pixels into staging buffer
-    unsafe {
-        let ptr = ctx
-            .device
-            .map_memory(staging_memory, 0, size, vk::MemoryMapFlags::empty())?
-            as *mut u8;
-        ptr.copy_from_nonoverlapping(pixels.as_ptr(), pixels.len());
-        ctx.device.unmap_memory(staging_memory);
-    }
-
-    // create GPU image
-    let (vk_image, image_memory) = ctx.create_image(
-        vk::Extent2D { width, height },
-        vk::Format::R8G8B8A8_SRGB,
-        vk::ImageTiling::OPTIMAL,
-        vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
-        vk::MemoryPropertyFlags::DEVICE_LOCAL,
-    )?;
-
-    let cmd = ctx.begin_single_time_commands(command_pool);
-
-    unsafe {
-        // transition to transfer dst
-        let barrier = vk::ImageMemoryBarrier::default()
-            .old_layout(vk::ImageLayout::UNDEFINED)
-            .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
-            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-            .image(vk_image)
-            .subresource_range(vk::ImageSubresourceRange {
-                aspect_mask: vk::ImageAspectFlags::COLOR,
-                base_mip_level: 0,
-                level_count: 1,
-                base_array_layer: 0,
-                layer_count: 1,
-            })
-            .src_access_mask(vk::AccessFlags::empty())
-            .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE);
-
-        ctx.device.cmd_pipeline_barrier(
-            cmd,
-            vk::PipelineStageFlags::TOP_OF_PIPE,
-            vk::PipelineStageFlags::TRANSFER,
-            vk::DependencyFlags::empty(),
-            &[],
-            &[],
-            &[barrier],
-        );
-
-        // copy buffer to image
-        let region = vk::BufferImageCopy::default()
-            .buffer_offset(0)
-            .buffer_row_length(0)
-            .buffer_image_height(0)
-            .image_subresource(vk::ImageSubresourceLayers {
-                aspect_mask: vk::ImageAspectFlags::COLOR,
-                mip_level: 0,
-                base_array_layer: 0,
-                layer_count: 1,
-            })
-            .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
-            .image_extent(vk::Extent3D {
-                width,
-                height,
-                depth: 1,
-            });
-
-        ctx.device.cmd_copy_buffer_to_image(
-            cmd,
-            staging_buffer,
-            vk_image,
-            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-            &[region],
-        );
-
-        // transition to shader read
-        let barrier = vk::ImageMemoryBarrier::default()
-            .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
-            .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
-            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-            .image(vk_image)
-            .subresource_range(vk::ImageSubresourceRange {
-                aspect_mask: vk::ImageAspectFlags::COLOR,
-                base_mip_level: 0,
-                level_count: 1,
-                base_array_layer: 0,
-                layer_count: 1,
-            })
-            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
-            .dst_access_mask(vk::AccessFlags::SHADER_READ);
-
-        ctx.device.cmd_pipeline_barrier(
-            cmd,
-            vk::PipelineStageFlags::TRANSFER,
-            vk::PipelineStageFlags::FRAGMENT_SHADER,
-            vk::DependencyFlags::empty(),
-            &[],
-            &[],
-            &[barrier],
-        );
-    }
-
-    let queue = ctx.queues[&ctx.queue_families.transfer];
-    ctx.end_single_time_commands(cmd, queue, command_pool);
-
-    // cleanup staging
-    unsafe {
-        ctx.device.destroy_buffer(staging_buffer, None);
-        ctx.device.free_memory(staging_memory, None);
-    }
-
-    // image view
-    let image_view = ctx.create_image_view(
-        vk_image,
+    let (vk_image, image_memory, image_view) = ctx.upload_texture(
+        command_pool,
+        width,
+        height,
+        image.as_raw(),
         vk::Format::R8G8B8A8_SRGB,
-        vk::ImageAspectFlags::COLOR,
     )?;
 
     let sampler = unsafe {
@@ -275,3 +181,50 @@ pub fn upload_atlas(
         descriptor_set,
     })
 }
+
+/// Replaces the image of an uploaded atlas, keeping its descriptor set so nothing has to rebind
+/// Note: waits for the device to be idle, so only use it for edits like texture imports
+pub fn reupload_atlas(
+    ctx: &VulkanRenderingContext,
+    command_pool: vk::CommandPool,
+    atlas: &mut VoxelTextureAtlas,
+    image: &RgbaImage,
+) -> Result<()> {
+    if image.width() == 0 || image.height() == 0 {
+        return Err(anyhow::anyhow!("Cannot upload empty texture atlas"));
+    }
+
+    let (vk_image, image_memory, image_view) = ctx.upload_texture(
+        command_pool,
+        image.width(),
+        image.height(),
+        image.as_raw(),
+        vk::Format::R8G8B8A8_SRGB,
+    )?;
+
+    unsafe {
+        ctx.device.device_wait_idle()?;
+
+        let image_info = vk::DescriptorImageInfo::default()
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .image_view(image_view)
+            .sampler(atlas.sampler);
+        ctx.device.update_descriptor_sets(
+            &[vk::WriteDescriptorSet::default()
+                .dst_set(atlas.descriptor_set)
+                .dst_binding(0)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .image_info(&[image_info])],
+            &[],
+        );
+
+        ctx.device.destroy_image_view(atlas.image_view, None);
+        ctx.device.destroy_image(atlas.image, None);
+    }
+    ctx.free_memory(atlas.image_memory);
+
+    atlas.image = vk_image;
+    atlas.image_memory = image_memory;
+    atlas.image_view = image_view;
+    Ok(())
+}
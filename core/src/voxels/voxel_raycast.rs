@@ -1,3 +1,4 @@
+pub use crate::math::Ray;
 use crate::objects::world::World;
 use crate::rendering::components::camera::Camera;
 use crate::voxels::voxel::{VoxelId, VoxelRegistry};
@@ -19,23 +20,6 @@ pub struct RaycastHit {
     pub set_to: Option<VoxelId>,
 }
 
-pub struct Ray {
-    pub origin: Vector3<f32>,
-    pub direction: Vector3<f32>,
-}
-
-impl Ray {
-    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
-        let len =
-            (direction.x * direction.x + direction.y * direction.y + direction.z * direction.z)
-                .sqrt();
-        Self {
-            origin,
-            direction: Vector3::new(direction.x / len, direction.y / len, direction.z / len),
-        }
-    }
-}
-
 #[derive(PartialEq, Eq, Clone)]
 pub enum Direction {
     Forward,
@@ -78,6 +62,7 @@ pub fn raycast_raw(
     chunk_map: &HashMap<(i32, i32, i32), *const [VoxelId; 32 * 32 * 32]>,
     set_to: Option<VoxelId>,
     voxel_registry: &VoxelRegistry,
+    origin: Vector3<i32>,
 ) -> Option<RaycastHit> {
     let mut voxel = Vector3::new(
         ray.origin.x.floor() as i32,
@@ -132,7 +117,9 @@ pub fn raycast_raw(
 
     while distance < max_distance {
         // O(1) voxel sample with no bounds check
-        let id = unsafe { World::get_voxel_raw(chunk_map, voxel.x, voxel.y, voxel.z) };
+        // the ray is relative to the world origin, the chunk map is absolute
+        let absolute = voxel + origin;
+        let id = unsafe { World::get_voxel_raw(chunk_map, absolute.x, absolute.y, absolute.z) };
 
         if id != 0
             && voxel_registry
@@ -141,9 +128,9 @@ pub fn raycast_raw(
                 .has_component::<IsSolid>()
         {
             return Some(RaycastHit {
-                voxel_pos: voxel,
-                chunk_pos: Vector3::new(voxel.x >> 5, voxel.y >> 5, voxel.z >> 5),
-                local_pos: Vector3::new(voxel.x & 31, voxel.y & 31, voxel.z & 31),
+                voxel_pos: absolute,
+                chunk_pos: Vector3::new(absolute.x >> 5, absolute.y >> 5, absolute.z >> 5),
+                local_pos: Vector3::new(absolute.x & 31, absolute.y & 31, absolute.z & 31),
                 face: last_face,
                 distance,
                 set_to,
@@ -185,7 +172,14 @@ pub fn voxel_raycast_system(world: &mut World, set_to: Option<VoxelId>, range: f
 
     let registry = world.get_resource::<VoxelRegistry>()?;
 
-    if let Some(hit) = raycast_raw(&ray, range, &chunk_map, set_to, &registry) {
+    if let Some(hit) = raycast_raw(
+        &ray,
+        range,
+        &chunk_map,
+        set_to,
+        &registry,
+        world.origin_offset(),
+    ) {
         world.insert_resource(hit);
     }
 
@@ -201,7 +195,14 @@ pub fn voxel_raycast(
     let ray = get_camera_ray(transform, direction);
     let chunk_map = world.build_raw_chunk_lookup();
     let registry = world.get_resource::<VoxelRegistry>().unwrap();
-    raycast_raw(&ray, distance, &chunk_map, None, registry)
+    raycast_raw(
+        &ray,
+        distance,
+        &chunk_map,
+        None,
+        registry,
+        world.origin_offset(),
+    )
 }
 
 pub fn voxel_raycast_camera(world: &mut World, range: f32) -> Option<RaycastHit> {
@@ -213,7 +214,14 @@ pub fn voxel_raycast_camera(world: &mut World, range: f32) -> Option<RaycastHit>
     let ray = get_camera_ray(&transform, Direction::Forward);
     let chunk_map = world.build_raw_chunk_lookup();
     let registry = world.get_resource::<VoxelRegistry>().unwrap();
-    raycast_raw(&ray, range, &chunk_map, None, registry)
+    raycast_raw(
+        &ray,
+        range,
+        &chunk_map,
+        None,
+        registry,
+        world.origin_offset(),
+    )
 }
 
 pub fn voxel_raycast_with_map(
@@ -225,5 +233,12 @@ pub fn voxel_raycast_with_map(
 ) -> Option<RaycastHit> {
     let ray = get_camera_ray(transform, direction);
     let registry = world.get_resource::<VoxelRegistry>().unwrap();
-    raycast_raw(&ray, distance, chunk_map, None, registry)
+    raycast_raw(
+        &ray,
+        distance,
+        chunk_map,
+        None,
+        registry,
+        world.origin_offset(),
+    )
 }
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use apostasy_macros::{Resource, update};
+use serde::{Deserialize, Serialize};
+
+use crate::{log, objects::world::World, utils::yaml};
+
+/// Called when the weather preset changes, gets the new preset and its audio bed
+/// Note: the engine has no audio yet, games play the audio bed through this hook
+pub type WeatherChangedFn = Arc<dyn Fn(WeatherPreset, Option<&str>) + Send + Sync>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherPreset {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+    Fog,
+}
+
+impl WeatherPreset {
+    pub const ALL: [WeatherPreset; 4] = [
+        WeatherPreset::Clear,
+        WeatherPreset::Rain,
+        WeatherPreset::Snow,
+        WeatherPreset::Fog,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WeatherPreset::Clear => "Clear",
+            WeatherPreset::Rain => "Rain",
+            WeatherPreset::Snow => "Snow",
+            WeatherPreset::Fog => "Fog",
+        }
+    }
+}
+
+/// Exponential squared distance fog
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Fog {
+    pub color: [f32; 3],
+    /// 0 disables the fog
+    pub density: f32,
+}
+
+/// Particles simulated on the gpu inside a box that follows the active camera
+/// Each particle wraps around the box so the count never changes
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParticleEmitter {
+    pub count: u32,
+    /// the size of the box around the camera
+    pub area: [f32; 3],
+    /// the velocity of every particle in units per second
+    pub velocity: [f32; 3],
+    /// the width of a particle
+    pub size: f32,
+    /// how many seconds of movement a particle is stretched over, 0 for round particles
+    pub streak: f32,
+    pub color: [f32; 4],
+}
+
+/// The weather of the world, change `preset` with `set_preset` at runtime
+/// Saved with the scene's objects by `World::save_objects`
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weather {
+    pub preset: WeatherPreset,
+    /// the clear colour of the frame, should match the fog colour to hide the far plane
+    pub sky_color: [f32; 3],
+    pub fog: Fog,
+    pub particles: Option<ParticleEmitter>,
+    /// the name of the looping ambient sound for this weather
+    pub audio_bed: Option<String>,
+    #[serde(skip)]
+    pub on_changed: Option<WeatherChangedFn>,
+    /// the preset the last `on_changed` call was made for
+    #[serde(skip)]
+    pub applied: Option<WeatherPreset>,
+}
+
+impl std::fmt::Debug for Weather {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Weather")
+            .field("preset", &self.preset)
+            .field("sky_color", &self.sky_color)
+            .field("fog", &self.fog)
+            .field("particles", &self.particles)
+            .field("audio_bed", &self.audio_bed)
+            .finish()
+    }
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self::new(WeatherPreset::Clear)
+    }
+}
+
+impl Weather {
+    pub fn new(preset: WeatherPreset) -> Self {
+        let mut weather = Self {
+            preset,
+            sky_color: [0.0, 0.2, 0.8],
+            fog: Fog {
+                color: [0.0, 0.2, 0.8],
+                density: 0.0,
+            },
+            particles: None,
+            audio_bed: None,
+            on_changed: None,
+            applied: None,
+        };
+        weather.set_preset(preset);
+        weather
+    }
+
+    /// Replaces the sky, fog, particles and audio bed with the preset's
+    pub fn set_preset(&mut self, preset: WeatherPreset) {
+        self.preset = preset;
+        match preset {
+            WeatherPreset::Clear => {
+                self.sky_color = [0.0, 0.2, 0.8];
+                self.fog = Fog {
+                    color: [0.0, 0.2, 0.8],
+                    density: 0.0,
+                };
+                self.particles = None;
+                self.audio_bed = None;
+            }
+            WeatherPreset::Rain => {
+                self.sky_color = [0.25, 0.28, 0.32];
+                self.fog = Fog {
+                    color: [0.25, 0.28, 0.32],
+                    density: 0.006,
+                };
+                self.particles = Some(ParticleEmitter {
+                    count: 6000,
+                    area: [48.0, 32.0, 48.0],
+                    velocity: [1.0, -18.0, 0.5],
+                    size: 0.03,
+                    streak: 0.04,
+                    color: [0.7, 0.75, 0.85, 0.5],
+                });
+                self.audio_bed = Some("rain".to_string());
+            }
+            WeatherPreset::Snow => {
+                self.sky_color = [0.7, 0.72, 0.76];
+                self.fog = Fog {
+                    color: [0.7, 0.72, 0.76],
+                    density: 0.01,
+                };
+                self.particles = Some(ParticleEmitter {
+                    count: 4000,
+                    area: [40.0, 24.0, 40.0],
+                    velocity: [0.4, -1.5, 0.2],
+                    size: 0.08,
+                    streak: 0.0,
+                    color: [1.0, 1.0, 1.0, 0.9],
+                });
+                self.audio_bed = Some("wind".to_string());
+            }
+            WeatherPreset::Fog => {
+                self.sky_color = [0.6, 0.62, 0.64];
+                self.fog = Fog {
+                    color: [0.6, 0.62, 0.64],
+                    density: 0.035,
+                };
+                self.particles = None;
+                self.audio_bed = None;
+            }
+        }
+    }
+
+    /// Loads the weather from a yaml file, falls back to clear weather
+    pub fn load(path: &str) -> Self {
+        yaml::load_or_default(path)
+    }
+
+    /// Saves the weather to a yaml file
+    pub fn save(&self, path: &str) -> Result<()> {
+        yaml::save(path, self)
+    }
+}
+
+/// Calls the weather's `on_changed` hook once per preset change
+#[update]
+pub fn weather_system(world: &mut World) -> Result<()> {
+    let Ok(weather) = world.get_resource_mut::<Weather>() else {
+        return Ok(());
+    };
+    if weather.applied == Some(weather.preset) {
+        return Ok(());
+    }
+    weather.applied = Some(weather.preset);
+
+    log!("Weather changed to {}", weather.preset.label());
+    if let Some(on_changed) = &weather.on_changed {
+        on_changed(weather.preset, weather.audio_bed.as_deref());
+    }
+
+    Ok(())
+}
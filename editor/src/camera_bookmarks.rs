@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use apostasy_core::{
+    anyhow::Result,
+    cgmath::Vector3,
+    log, log_error, log_warn,
+    math::look_euler_angles,
+    objects::{
+        components::{interpolation::InterpolatedTransform, transform::Transform},
+        resources::input_manager::InputManager,
+        snapshot::SceneLoaded,
+        world::World,
+    },
+    rendering::components::camera::{Camera, EditorCamera},
+    serde::{Deserialize, Serialize},
+    start, update,
+    utils::yaml,
+    winit::keyboard::{KeyCode, PhysicalKey},
+};
+use apostasy_macros::Resource;
+
+/// The file the editor camera bookmarks are persisted to
+pub const CAMERA_BOOKMARKS_PATH: &str = "res/editor/camera_bookmarks.yaml";
+
+/// A stored editor camera view
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "apostasy_core::serde")]
+pub struct CameraBookmark {
+    pub position: [f32; 3],
+    pub euler_angles: [f32; 3],
+    pub is_orthographic: bool,
+    pub ortho_size: f32,
+}
+
+/// Numbered editor camera views of each scene, stored with Ctrl+1..9 and recalled with 1..9
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "apostasy_core::serde", default)]
+pub struct CameraBookmarks {
+    /// the bookmarks of each scene by its path, an empty path is a scene that was never loaded
+    pub scenes: BTreeMap<String, [Option<CameraBookmark>; 9]>,
+    /// the path of the open scene, the last `SceneLoaded`
+    #[serde(skip)]
+    pub scene: String,
+}
+
+impl CameraBookmarks {
+    /// Gets a bookmark of the open scene
+    pub fn get(&self, slot: usize) -> Option<CameraBookmark> {
+        self.scenes
+            .get(&self.scene)
+            .and_then(|slots| slots.get(slot).copied().flatten())
+    }
+
+    /// Stores a bookmark of the open scene
+    pub fn set(&mut self, slot: usize, bookmark: CameraBookmark) {
+        self.scenes.entry(self.scene.clone()).or_default()[slot] = Some(bookmark);
+    }
+
+    /// Loads the bookmarks from disk, falling back to none if they are missing or invalid
+    pub fn load() -> Self {
+        yaml::load_or_default(CAMERA_BOOKMARKS_PATH)
+    }
+
+    /// Saves the bookmarks to `CAMERA_BOOKMARKS_PATH`
+    pub fn save(&self) -> Result<()> {
+        yaml::save(CAMERA_BOOKMARKS_PATH, self)
+    }
+}
+
+#[start]
+pub fn camera_bookmarks_start(world: &mut World) -> Result<()> {
+    world.insert_resource(CameraBookmarks::load());
+    Ok(())
+}
+
+/// Stores and recalls bookmarks, and switches between the orthographic views
+#[update]
+pub fn camera_bookmarks(world: &mut World) -> Result<()> {
+    let loaded = world
+        .event_reader::<SceneLoaded>()
+        .ok()
+        .and_then(|reader| reader.read().last().map(|event| event.path.clone()));
+    if let Some(path) = loaded {
+        world.get_resource_mut::<CameraBookmarks>()?.scene = path;
+    }
+
+    let inputs = world.get_resource::<InputManager>()?;
+    let is_ctrl_held = inputs
+        .keys_held
+        .contains(&PhysicalKey::Code(KeyCode::ControlLeft))
+        || inputs
+            .keys_held
+            .contains(&PhysicalKey::Code(KeyCode::ControlRight));
+    let slot = (1..=9).find(|i| inputs.is_keybind_active(&format!("Bookmark{}", i)));
+
+    // ctrl looks from the opposite side, like blender
+    let side = if is_ctrl_held { -1.0 } else { 1.0 };
+    let view = if inputs.is_keybind_active("ViewFront") {
+        Some(Vector3::new(0.0, 0.0, -side))
+    } else if inputs.is_keybind_active("ViewRight") {
+        Some(Vector3::new(-side, 0.0, 0.0))
+    } else if inputs.is_keybind_active("ViewTop") {
+        Some(Vector3::new(0.0, -side, 0.0))
+    } else {
+        None
+    };
+    let toggle_orthographic = inputs.is_keybind_active("ToggleOrthographic");
+    let scroll = inputs.scroll_delta.1;
+    if slot.is_none() && view.is_none() && !toggle_orthographic && scroll == 0.0 {
+        return Ok(());
+    }
+
+    let camera = world.get_object_with_tag_mut::<EditorCamera>()?;
+    let mut transform = camera.get_component::<Transform>()?.clone();
+    let mut lens = camera.get_component::<Camera>()?.clone();
+
+    if let Some(direction) = view {
+        transform.local_euler_angles = look_euler_angles(direction);
+        lens.is_orthographic = true;
+    }
+    if toggle_orthographic {
+        lens.is_orthographic = !lens.is_orthographic;
+    }
+    if lens.is_orthographic && scroll != 0.0 {
+        lens.ortho_size = (lens.ortho_size * (1.0 - scroll * 0.1)).clamp(0.5, 2000.0);
+    }
+
+    let mut stored = None;
+    if let Some(slot) = slot {
+        if is_ctrl_held {
+            stored = Some(CameraBookmark {
+                position: transform.local_position.into(),
+                euler_angles: transform.local_euler_angles.into(),
+                is_orthographic: lens.is_orthographic,
+                ortho_size: lens.ortho_size,
+            });
+        } else {
+            match world.get_resource::<CameraBookmarks>()?.get(slot - 1) {
+                Some(bookmark) => {
+                    // the editor camera has no parent
+                    transform.local_position = bookmark.position.into();
+                    transform.global_position = transform.local_position;
+                    transform.local_euler_angles = bookmark.euler_angles.into();
+                    lens.is_orthographic = bookmark.is_orthographic;
+                    lens.ortho_size = bookmark.ortho_size;
+                }
+                None => {
+                    log_warn!("Camera bookmark {} is empty", slot);
+                }
+            }
+        }
+    }
+
+    let camera = world.get_object_with_tag_mut::<EditorCamera>()?;
+    // a recalled view is jumped to instead of sliding there over a fixed update step
+    if slot.is_some()
+        && !is_ctrl_held
+        && let Ok(interpolated) = camera.get_component_mut::<InterpolatedTransform>()
+    {
+        interpolated.snap(&transform);
+    }
+    *camera.get_component_mut::<Transform>()? = transform;
+    *camera.get_component_mut::<Camera>()? = lens;
+
+    if let (Some(slot), Some(bookmark)) = (slot, stored) {
+        let bookmarks = world.get_resource_mut::<CameraBookmarks>()?;
+        bookmarks.set(slot - 1, bookmark);
+        match bookmarks.save() {
+            Ok(()) => {
+                log!("Stored camera bookmark {}", slot);
+            }
+            Err(e) => {
+                log_error!("Failed to save camera bookmarks: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
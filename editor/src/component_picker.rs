@@ -0,0 +1,183 @@
+use apostasy_core::{
+    egui,
+    objects::{component::ComponentRegistration, registry::registered},
+};
+use apostasy_macros::Resource;
+
+/// The add component popup of the inspector, remembered between frames while it is open
+#[derive(Resource, Clone, Default)]
+pub struct ComponentPicker {
+    pub is_open: bool,
+    pub query: String,
+    /// the index of the highlighted component among the ones that can be added
+    pub highlighted: usize,
+}
+
+/// A registered component that matches the query
+struct PickerEntry {
+    name: &'static str,
+    doc: &'static str,
+    score: i32,
+    /// the object already has it, shown but can't be picked
+    is_present: bool,
+}
+
+/// Scores how well `query` matches `candidate` with its characters in order but not next to
+/// each other, e.g. `rgb` matches `RigidBody`, None if it does not match
+/// Note: characters next to the last match and at the start of a word score higher, the
+/// shortest candidates win ties
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..candidate.len())
+            .find(|i| candidate[*i].to_lowercase().eq(wanted.to_lowercase()))?;
+        let is_word_start = found == 0
+            || matches!(candidate[found - 1], '_' | ':')
+            || (candidate[found].is_uppercase() && candidate[found - 1].is_lowercase());
+        score += 1;
+        if is_word_start {
+            score += 8;
+        }
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        last_match = Some(found);
+        next = found + 1;
+    }
+    Some(score * 100 - candidate.len() as i32)
+}
+
+/// The registered components matching the query grouped by module, the best matches first
+fn matching_groups(query: &str, present: &[&str]) -> Vec<(&'static str, Vec<PickerEntry>)> {
+    let mut groups: Vec<(&'static str, Vec<PickerEntry>)> = Vec::new();
+    for registration in registered::<ComponentRegistration>() {
+        let Some(score) = fuzzy_score(query, registration.type_name) else {
+            continue;
+        };
+        let entry = PickerEntry {
+            name: registration.type_name,
+            doc: registration.doc,
+            score,
+            is_present: present.contains(&registration.type_name),
+        };
+        match groups
+            .iter_mut()
+            .find(|(module, _)| *module == registration.module)
+        {
+            Some((_, entries)) => entries.push(entry),
+            None => groups.push((registration.module, vec![entry])),
+        }
+    }
+
+    for (_, entries) in &mut groups {
+        entries.sort_by(|a, b| b.score.cmp(&a.score).then(a.name.cmp(b.name)));
+    }
+    if query.trim().is_empty() {
+        groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+    } else {
+        let best = |entries: &[PickerEntry]| entries.first().map_or(i32::MIN, |e| e.score);
+        groups.sort_by(|(a_module, a), (b_module, b)| {
+            best(b).cmp(&best(a)).then(a_module.cmp(b_module))
+        });
+    }
+    groups
+}
+
+/// Draws the picker under `anchor`, returns the component that was picked
+/// Note: up and down move the highlight, enter picks it and escape closes the picker
+pub fn component_picker(
+    ctx: &egui::Context,
+    anchor: egui::Pos2,
+    picker: &mut ComponentPicker,
+    present: &[&str],
+) -> Option<&'static str> {
+    if !picker.is_open {
+        return None;
+    }
+    let groups = matching_groups(&picker.query, present);
+    let addable: Vec<&'static str> = groups
+        .iter()
+        .flat_map(|(_, entries)| entries.iter())
+        .filter(|entry| !entry.is_present)
+        .map(|entry| entry.name)
+        .collect();
+
+    let (up, down, enter, escape) = ctx.input(|i| {
+        (
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::Escape),
+        )
+    });
+    let moved = up || down;
+    if down {
+        picker.highlighted += 1;
+    }
+    if up {
+        picker.highlighted = picker.highlighted.saturating_sub(1);
+    }
+    picker.highlighted = picker.highlighted.min(addable.len().saturating_sub(1));
+    let highlighted = addable.get(picker.highlighted).copied();
+
+    let mut picked = None;
+    egui::Window::new("Add Component")
+        .fixed_pos(anchor)
+        .title_bar(false)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut picker.query)
+                    .desired_width(260.0)
+                    .hint_text("search components"),
+            );
+            response.request_focus();
+            if response.changed() {
+                picker.highlighted = 0;
+            }
+            if enter {
+                picked = highlighted;
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(280.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    if groups.is_empty() {
+                        ui.weak("No components match");
+                    }
+                    for (module, entries) in &groups {
+                        ui.weak(*module);
+                        for entry in entries {
+                            let is_highlighted = highlighted == Some(entry.name);
+                            let label = ui
+                                .add_enabled(
+                                    !entry.is_present,
+                                    egui::Button::selectable(is_highlighted, entry.name),
+                                )
+                                .on_disabled_hover_text("Already on this object");
+                            let label = if entry.doc.is_empty() {
+                                label
+                            } else {
+                                label.on_hover_text(entry.doc)
+                            };
+                            if is_highlighted && moved {
+                                label.scroll_to_me(None);
+                            }
+                            if label.clicked() {
+                                picked = Some(entry.name);
+                            }
+                        }
+                    }
+                });
+        });
+
+    if picked.is_some() || escape {
+        *picker = ComponentPicker::default();
+    }
+    picked
+}
@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use apostasy_core::{
+    anyhow::{Result, anyhow},
+    egui, log, log_error,
+    objects::{
+        resources::input_manager::InputManager,
+        scene_search::{
+            SCENE_SEARCH_ROOT, SceneMatch, SceneQuery, find_in_scenes, replace_in_scenes,
+        },
+        world::World,
+    },
+    serde_yaml,
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+/// The query and results of the Find in Scenes window, it is open while this is inserted
+#[derive(Resource, Clone, Default)]
+pub struct FindInScenes {
+    pub component: String,
+    pub field: String,
+    /// yaml, e.g. `player.png` or `[0, 1, 0]`, empty matches any value
+    pub value: String,
+    /// yaml the matched fields are set to
+    pub replacement: String,
+    pub results: Vec<SceneMatch>,
+    /// the result of the last search or replace
+    pub status: String,
+}
+
+impl FindInScenes {
+    fn query(&self) -> Result<SceneQuery> {
+        let text = |text: &str| (!text.trim().is_empty()).then(|| text.trim().to_string());
+        let value = match text(&self.value) {
+            Some(value) => {
+                Some(serde_yaml::from_str(&value).map_err(|e| anyhow!("Invalid value: {}", e))?)
+            }
+            None => None,
+        };
+        if value.is_some() && text(&self.field).is_none() {
+            return Err(anyhow!("Pick the field the value is in"));
+        }
+        Ok(SceneQuery {
+            component: text(&self.component),
+            field: text(&self.field),
+            value,
+        })
+    }
+
+    fn search(&mut self) -> Result<()> {
+        self.results = find_in_scenes(Path::new(SCENE_SEARCH_ROOT), &self.query()?)?;
+        self.status = format!("{} matches", self.results.len());
+        Ok(())
+    }
+
+    fn replace(&mut self) -> Result<()> {
+        let replacement: serde_yaml::Value = serde_yaml::from_str(&self.replacement)
+            .map_err(|e| anyhow!("Invalid replacement: {}", e))?;
+        let replaced =
+            replace_in_scenes(Path::new(SCENE_SEARCH_ROOT), &self.query()?, &replacement)?;
+        let mut scenes: Vec<_> = replaced.iter().map(|found| &found.path).collect();
+        scenes.dedup();
+        log!(
+            "Replaced {} values in {} scenes",
+            replaced.len(),
+            scenes.len()
+        );
+        self.search()?;
+        self.status = format!(
+            "Replaced {} values in {} scenes, {}",
+            replaced.len(),
+            scenes.len(),
+            self.status
+        );
+        Ok(())
+    }
+}
+
+#[update]
+pub fn toggle_find_in_scenes(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleFindInScenes") {
+        if world.has_resource::<FindInScenes>() {
+            world.remove_resource::<FindInScenes>();
+        } else {
+            world.insert_resource(FindInScenes::default());
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the Find in Scenes window, which searches every scene and prefab under `res/` for
+/// objects with a component or a field value and sets the matched fields to a new value
+/// Note: the open scene is not reloaded after a replace
+#[update(in_state = GameState::Editor, run_if = resource_exists::<FindInScenes>)]
+pub fn find_in_scenes_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let mut state = world.get_resource::<FindInScenes>()?.clone();
+    let mut search = false;
+    let mut replace = false;
+
+    let mut is_open = true;
+    egui::Window::new("Find in Scenes")
+        .open(&mut is_open)
+        .default_width(420.0)
+        .show(&ctx, |ui| {
+            egui::Grid::new("find_in_scenes_query")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Component");
+                    ui.text_edit_singleline(&mut state.component)
+                        .on_hover_text("Empty matches every component");
+                    ui.end_row();
+                    ui.label("Field");
+                    ui.text_edit_singleline(&mut state.field);
+                    ui.end_row();
+                    ui.label("Value");
+                    ui.text_edit_singleline(&mut state.value)
+                        .on_hover_text("Yaml, empty matches any value");
+                    ui.end_row();
+                    ui.label("Replace With");
+                    ui.text_edit_singleline(&mut state.replacement);
+                    ui.end_row();
+                });
+            ui.horizontal(|ui| {
+                search = ui.button("Find").clicked();
+                replace = ui
+                    .add_enabled(
+                        !state.field.trim().is_empty() && !state.results.is_empty(),
+                        egui::Button::new("Replace All"),
+                    )
+                    .on_hover_text("Sets the field of every match and saves the scenes")
+                    .clicked();
+            });
+            if !state.status.is_empty() {
+                ui.weak(&state.status);
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("find_in_scenes_results")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for found in &state.results {
+                            ui.label(found.path.display().to_string());
+                            ui.label(format!("{} ({})", found.object_name, found.component));
+                            let value = serde_yaml::to_string(&found.value).unwrap_or_default();
+                            ui.monospace(value.trim_end());
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+    let result = if replace {
+        state.replace()
+    } else if search {
+        state.search()
+    } else {
+        Ok(())
+    };
+    if let Err(e) = result {
+        log_error!("Find in Scenes failed: {}", e);
+        state.status = e.to_string();
+    }
+
+    if is_open {
+        world.insert_resource(state);
+    } else {
+        world.remove_resource::<FindInScenes>();
+    }
+    Ok(())
+}
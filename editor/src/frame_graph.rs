@@ -0,0 +1,149 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui,
+    objects::{
+        resources::input_manager::InputManager,
+        systems::{FrameStep, SystemInfo, SystemKind},
+        world::World,
+    },
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+const ROW_HEIGHT: f32 = 16.0;
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsFrameGraphOpen;
+
+#[update]
+pub fn toggle_frame_graph(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleFrameGraph") {
+        if world.has_resource::<IsFrameGraphOpen>() {
+            world.remove_resource::<IsFrameGraphOpen>();
+        } else {
+            world.insert_resource(IsFrameGraphOpen);
+        }
+    }
+
+    Ok(())
+}
+
+fn kind_color(kind: SystemKind) -> egui::Color32 {
+    match kind {
+        SystemKind::Start => egui::Color32::from_rgb(120, 120, 200),
+        SystemKind::Update => egui::Color32::from_rgb(80, 170, 90),
+        SystemKind::FixedUpdate => egui::Color32::from_rgb(70, 150, 200),
+        SystemKind::LateUpdate => egui::Color32::from_rgb(190, 140, 60),
+        SystemKind::Shutdown => egui::Color32::from_rgb(180, 90, 90),
+    }
+}
+
+/// Draws one step as a node on the line running down the left of the graph
+fn step_row(ui: &mut egui::Ui, color: egui::Color32, is_flush: bool, text: egui::RichText) {
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(16.0, ROW_HEIGHT), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.vline(
+            rect.center().x,
+            rect.y_range(),
+            egui::Stroke::new(1.0, egui::Color32::from_gray(90)),
+        );
+        if is_flush {
+            painter.rect_filled(
+                egui::Rect::from_center_size(rect.center(), egui::vec2(12.0, 4.0)),
+                1.0,
+                color,
+            );
+        } else {
+            painter.circle_filled(rect.center(), 4.0, color);
+        }
+        ui.label(text);
+    });
+}
+
+/// Draws a system as a numbered node, struck through while it is disabled
+fn system_row(ui: &mut egui::Ui, index: usize, system: &SystemInfo) {
+    let mut text = egui::RichText::new(format!("{}. {} ({})", index, system.name, system.priority));
+    if !system.is_enabled {
+        text = text.strikethrough().weak();
+    }
+    step_row(ui, kind_color(system.kind), false, text);
+}
+
+/// Draws the systems of a kind that run once, at startup or shutdown
+fn once_section(ui: &mut egui::Ui, kind: SystemKind, systems: &[SystemInfo]) {
+    ui.add_space(4.0);
+    ui.strong(format!("{} (once)", kind.label()));
+    for (index, system) in systems.iter().filter(|s| s.kind == kind).enumerate() {
+        system_row(ui, index + 1, system);
+    }
+}
+
+/// Draws the order systems and engine flush points run in each frame, with the systems that
+/// run once before and after
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsFrameGraphOpen>)]
+pub fn frame_graph_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let steps = world.frame_graph();
+    let systems = world.systems();
+    let fixed_steps = world.fixed_steps();
+
+    let mut is_open = true;
+    egui::Window::new("Frame Graph")
+        .open(&mut is_open)
+        .default_width(320.0)
+        .show(&ctx, |ui| {
+            ui.label(format!("Fixed update ran {} times last frame", fixed_steps));
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 0.0;
+                once_section(ui, SystemKind::Start, &systems);
+                ui.add_space(4.0);
+                ui.separator();
+                ui.label("Every frame");
+
+                let mut kind = None;
+                let mut index = 0;
+                for step in &steps {
+                    match step {
+                        FrameStep::System(system) => {
+                            if kind != Some(system.kind) {
+                                kind = Some(system.kind);
+                                let mut heading = system.kind.label().to_string();
+                                if system.kind == SystemKind::FixedUpdate {
+                                    heading = format!("{} x{}", heading, fixed_steps);
+                                }
+                                ui.add_space(4.0);
+                                ui.strong(heading);
+                            }
+
+                            index += 1;
+                            system_row(ui, index, system);
+                        }
+                        FrameStep::Flush(label) => {
+                            kind = None;
+                            let text = egui::RichText::new(*label)
+                                .italics()
+                                .color(egui::Color32::GRAY);
+                            step_row(ui, egui::Color32::from_rgb(200, 70, 70), true, text);
+                        }
+                    }
+                }
+
+                ui.add_space(4.0);
+                ui.separator();
+                once_section(ui, SystemKind::Shutdown, &systems);
+            });
+        });
+
+    if !is_open {
+        world.remove_resource::<IsFrameGraphOpen>();
+    }
+
+    Ok(())
+}
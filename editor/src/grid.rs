@@ -0,0 +1,103 @@
+use apostasy_core::{
+    anyhow::Result,
+    cgmath::Vector3,
+    egui,
+    objects::{
+        components::transform::Transform, resources::input_manager::InputManager, world::World,
+    },
+    rendering::{
+        components::camera::{ActiveCamera, get_view_matrix},
+        shared::grid::EditorGrid,
+    },
+    start,
+    ui::ui_context::EguiContext,
+    update,
+};
+
+const GIZMO_SIZE: f32 = 36.0;
+
+#[start]
+pub fn grid_start(world: &mut World) -> Result<()> {
+    world.insert_resource(EditorGrid::default());
+    Ok(())
+}
+
+#[update]
+pub fn toggle_grid(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleGrid") {
+        let grid = world.get_resource_mut::<EditorGrid>()?;
+        grid.is_visible = !grid.is_visible;
+    }
+
+    Ok(())
+}
+
+/// Draws the world axes as seen by the active camera in the bottom left of the viewport
+#[update]
+pub fn axes_gizmo(world: &mut World) -> Result<()> {
+    if !world.get_resource::<EditorGrid>()?.show_axes_gizmo {
+        return Ok(());
+    }
+
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let Ok(camera) = world.get_object_with_tag::<ActiveCamera>() else {
+        return Ok(());
+    };
+    let view = get_view_matrix(camera.get_component::<Transform>()?);
+
+    let axes = [
+        (Vector3::unit_x(), "X", egui::Color32::from_rgb(230, 60, 60)),
+        (Vector3::unit_y(), "Y", egui::Color32::from_rgb(80, 200, 80)),
+        (
+            Vector3::unit_z(),
+            "Z",
+            egui::Color32::from_rgb(70, 110, 230),
+        ),
+    ];
+    // axes pointing away from the camera are drawn first so the nearer ones overlap them
+    let mut projected: Vec<(Vector3<f32>, &str, egui::Color32)> = axes
+        .iter()
+        .map(|(axis, label, color)| ((view * axis.extend(0.0)).truncate(), *label, *color))
+        .collect();
+    projected.sort_by(|a, b| a.0.z.total_cmp(&b.0.z));
+
+    egui::Area::new(egui::Id::new("axes_gizmo"))
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .interactable(false)
+        .show(&ctx, |ui| {
+            let (rect, _) = ui.allocate_exact_size(
+                egui::vec2(GIZMO_SIZE * 2.5, GIZMO_SIZE * 2.5),
+                egui::Sense::hover(),
+            );
+            let painter = ui.painter_at(rect);
+            let center = rect.center();
+            painter.circle_filled(
+                center,
+                GIZMO_SIZE * 1.2,
+                egui::Color32::from_black_alpha(80),
+            );
+
+            for (direction, label, color) in projected {
+                // view space y is up, screen space y is down
+                let offset = egui::vec2(direction.x, -direction.y) * GIZMO_SIZE;
+                // dimmed when pointing into the screen
+                let color = if direction.z < -0.5 {
+                    color.gamma_multiply(0.5)
+                } else {
+                    color
+                };
+                painter.line_segment([center, center + offset], egui::Stroke::new(2.0, color));
+                painter.text(
+                    center + offset * 1.2,
+                    egui::Align2::CENTER_CENTER,
+                    label,
+                    egui::FontId::proportional(12.0),
+                    color,
+                );
+            }
+        });
+
+    Ok(())
+}
@@ -1,10 +1,5 @@
 use apostasy_core::{
-    anyhow::Result,
-    objects::{
-        resources::input_manager::{InputManager, KeyAction, KeyBind, MouseBind},
-        world::World,
-    },
-    start,
+    prelude::*,
     winit::{
         event::MouseButton,
         keyboard::{KeyCode, PhysicalKey},
@@ -67,8 +62,118 @@ pub fn start(world: &mut World) -> Result<()> {
         "LookDown",
     ));
 
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F1),
+        KeyAction::Press,
+        "ToggleFindInScenes",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F2),
+        KeyAction::Press,
+        "ToggleRenderSettings",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F3),
+        KeyAction::Press,
+        "ToggleInputManager",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F4),
+        KeyAction::Press,
+        "ToggleTimeline",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F5),
+        KeyAction::Press,
+        "ToggleSystems",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F6),
+        KeyAction::Press,
+        "ToggleFrameGraph",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F7),
+        KeyAction::Press,
+        "TogglePhysicsSettings",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::Pause),
+        KeyAction::Press,
+        "TogglePhysicsPause",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F8),
+        KeyAction::Press,
+        "ToggleGrid",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F9),
+        KeyAction::Press,
+        "ToggleInspector",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F10),
+        KeyAction::Press,
+        "ToggleProfiler",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F11),
+        KeyAction::Press,
+        "TogglePlayMode",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::F12),
+        KeyAction::Press,
+        "ToggleWorldStats",
+    ));
+
+    let digits = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    for (i, digit) in digits.into_iter().enumerate() {
+        inputs.register_keybind(KeyBind::new(
+            PhysicalKey::Code(digit),
+            KeyAction::Press,
+            &format!("Bookmark{}", i + 1),
+        ));
+    }
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::Numpad1),
+        KeyAction::Press,
+        "ViewFront",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::Numpad3),
+        KeyAction::Press,
+        "ViewRight",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::Numpad7),
+        KeyAction::Press,
+        "ViewTop",
+    ));
+    inputs.register_keybind(KeyBind::new(
+        PhysicalKey::Code(KeyCode::Numpad5),
+        KeyAction::Press,
+        "ToggleOrthographic",
+    ));
+
     inputs.register_mousebind(MouseBind::new(MouseButton::Left, KeyAction::Hold, "Break"));
     inputs.register_mousebind(MouseBind::new(MouseButton::Right, KeyAction::Hold, "Place"));
+    inputs.register_mousebind(MouseBind::new(
+        MouseButton::Middle,
+        KeyAction::Press,
+        "Select",
+    ));
 
     Ok(())
 }
@@ -0,0 +1,282 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui,
+    gilrs::{Axis, Button},
+    objects::{
+        resources::input_manager::{InputManager, KeyAction, KeyBindMode},
+        world::World,
+    },
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+    winit::keyboard::{Key, PhysicalKey},
+};
+use apostasy_macros::Resource;
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsInputManagerOpen;
+
+const AXES: [Axis; 8] = [
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::LeftZ,
+    Axis::RightStickX,
+    Axis::RightStickY,
+    Axis::RightZ,
+    Axis::DPadX,
+    Axis::DPadY,
+];
+
+const BUTTONS: [Button; 19] = [
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::C,
+    Button::Z,
+    Button::LeftTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+#[update]
+pub fn toggle_input_manager(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleInputManager") {
+        if world.has_resource::<IsInputManagerOpen>() {
+            world.remove_resource::<IsInputManagerOpen>();
+        } else {
+            world.insert_resource(IsInputManagerOpen);
+        }
+    }
+    Ok(())
+}
+
+/// Draws the bindings, axes and gamepads of the input manager, the gamepad bindings, axes and
+/// deadzone can be edited, and each binding has a button that captures the next input for it
+/// Note: edits last until the editor closes, register the bindings in a start system to keep them
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsInputManagerOpen>)]
+pub fn input_manager_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let mut inputs = world.get_resource::<InputManager>()?.clone();
+    let capturing = inputs.capturing.clone();
+    let mut capture: Option<String> = None;
+
+    let mut is_open = true;
+    egui::Window::new("Input Manager")
+        .open(&mut is_open)
+        .default_width(420.0)
+        .show(&ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.strong("Gamepads");
+                if inputs.gamepad.connected.is_empty() {
+                    ui.weak("No gamepads connected");
+                }
+                for gamepad in &inputs.gamepad.connected {
+                    ui.label(format!("{} ({})", gamepad.name, gamepad.id));
+                }
+                ui.add(egui::Slider::new(&mut inputs.gamepad.deadzone, 0.0..=0.9).text("Deadzone"))
+                    .on_hover_text("Stick and trigger values closer to 0 than this read as 0");
+
+                ui.separator();
+                ui.strong("Axes");
+                let mut names: Vec<String> = inputs.axis_binds.keys().cloned().collect();
+                names.sort();
+                egui::Grid::new("input_manager_axes")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for name in &names {
+                            let value = inputs.axis(name);
+                            let axis = inputs.axis_binds.get_mut(name).unwrap();
+                            ui.label(name);
+                            egui::ComboBox::from_id_salt(("axis", name))
+                                .selected_text(
+                                    axis.gamepad_axis
+                                        .map(|a| format!("{:?}", a))
+                                        .unwrap_or("None".to_string()),
+                                )
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut axis.gamepad_axis, None, "None");
+                                    for gamepad_axis in AXES {
+                                        ui.selectable_value(
+                                            &mut axis.gamepad_axis,
+                                            Some(gamepad_axis),
+                                            format!("{:?}", gamepad_axis),
+                                        );
+                                    }
+                                });
+                            if capture_button(ui, &capturing, name, "Capture") {
+                                capture = Some(name.clone());
+                            }
+                            ui.label(format!(
+                                "{} / {}",
+                                key_label(axis.positive),
+                                key_label(axis.negative)
+                            ));
+                            ui.checkbox(&mut axis.invert, "Invert");
+                            ui.add(
+                                egui::ProgressBar::new((value + 1.0) / 2.0)
+                                    .desired_width(80.0)
+                                    .text(format!("{:.2}", value)),
+                            );
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+                ui.strong("Gamepad Bindings");
+                let mut names: Vec<String> = inputs.gamepad_binds.keys().cloned().collect();
+                names.sort();
+                egui::Grid::new("input_manager_gamepad_binds")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for name in &names {
+                            let is_active = inputs.is_gamepad_bind_active(name);
+                            let bind = inputs.gamepad_binds.get_mut(name).unwrap();
+                            ui.label(name);
+                            egui::ComboBox::from_id_salt(("gamepad_bind", name))
+                                .selected_text(format!("{:?}", bind.button))
+                                .show_ui(ui, |ui| {
+                                    for button in BUTTONS {
+                                        ui.selectable_value(
+                                            &mut bind.button,
+                                            button,
+                                            format!("{:?}", button),
+                                        );
+                                    }
+                                });
+                            if capture_button(ui, &capturing, name, "Capture") {
+                                capture = Some(name.clone());
+                            }
+                            ui.label(action_label(&bind.action, is_active));
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+                ui.strong("Keybinds");
+                let mut names: Vec<String> = inputs.keybinds.keys().cloned().collect();
+                names.sort();
+                egui::Grid::new("input_manager_keybinds")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for name in &names {
+                            let typed = inputs.key_layout.get(&inputs.keybinds[name].key).cloned();
+                            let bind = inputs.keybinds.get_mut(name).unwrap();
+                            ui.label(name);
+                            let logical_key = bind.logical_key.clone().or(typed);
+                            let label = format!(
+                                "{} / {}",
+                                key_label(Some(bind.key)),
+                                logical_key_label(logical_key.as_ref())
+                            );
+                            if capture_button(ui, &capturing, name, &label) {
+                                capture = Some(name.clone());
+                            }
+                            egui::ComboBox::from_id_salt(("keybind_mode", name))
+                                .selected_text(format!("{:?}", bind.mode))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(
+                                        &mut bind.mode,
+                                        KeyBindMode::Physical,
+                                        "Physical",
+                                    )
+                                    .on_hover_text("The same place on every keyboard layout");
+                                    ui.selectable_value(
+                                        &mut bind.mode,
+                                        KeyBindMode::Logical,
+                                        "Logical",
+                                    )
+                                    .on_hover_text("The key that types the character");
+                                });
+                            if bind.mode == KeyBindMode::Logical && bind.logical_key.is_none() {
+                                bind.logical_key = logical_key;
+                            }
+                            ui.label(format!("{:?}", bind.action));
+                            ui.end_row();
+                        }
+                        let mut mouse_binds: Vec<_> = inputs.mouse_keybinds.values().collect();
+                        mouse_binds.sort_by(|a, b| a.name.cmp(&b.name));
+                        for bind in mouse_binds {
+                            ui.label(&bind.name);
+                            let label = format!("Mouse {:?}", bind.key);
+                            if capture_button(ui, &capturing, &bind.name, &label) {
+                                capture = Some(bind.name.clone());
+                            }
+                            ui.label(format!("{:?}", bind.action));
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+    let current = world.get_resource_mut::<InputManager>()?;
+    current.gamepad.deadzone = inputs.gamepad.deadzone;
+    current.axis_binds = inputs.axis_binds;
+    current.gamepad_binds = inputs.gamepad_binds;
+    current.keybinds = inputs.keybinds;
+    if let Some(name) = capture {
+        if current.is_capturing(&name) {
+            current.cancel_capture();
+        } else {
+            current.start_capture(&name);
+        }
+    }
+    if !is_open {
+        world.remove_resource::<IsInputManagerOpen>();
+    }
+    Ok(())
+}
+
+/// A button showing the current input of a binding, clicked to capture the next input for it
+fn capture_button(ui: &mut egui::Ui, capturing: &Option<String>, name: &str, label: &str) -> bool {
+    if capturing.as_deref() == Some(name) {
+        ui.add(egui::Button::selectable(true, "Press any input..."))
+            .on_hover_text("Escape cancels, click again to stop")
+            .clicked()
+    } else {
+        ui.button(label)
+            .on_hover_text("Click, then press the new input")
+            .clicked()
+    }
+}
+
+fn key_label(key: Option<PhysicalKey>) -> String {
+    match key {
+        Some(PhysicalKey::Code(code)) => format!("{:?}", code),
+        Some(key) => format!("{:?}", key),
+        None => "-".to_string(),
+    }
+}
+
+fn logical_key_label(key: Option<&Key>) -> String {
+    match key {
+        Some(Key::Character(text)) => text.to_uppercase(),
+        Some(Key::Named(named)) => format!("{:?}", named),
+        Some(key) => format!("{:?}", key),
+        None => "?".to_string(),
+    }
+}
+
+fn action_label(action: &KeyAction, is_active: bool) -> String {
+    let action = format!("{:?}", action);
+    if is_active {
+        format!("{} (active)", action)
+    } else {
+        action
+    }
+}
@@ -0,0 +1,671 @@
+use std::ops::RangeInclusive;
+
+use apostasy_core::{
+    anyhow::Result,
+    cgmath::{Deg, Euler, Quaternion, Vector3},
+    egui, log_error,
+    objects::{
+        component::{FieldInfo, InspectOptions},
+        components::transform::Transform,
+        resources::input_manager::InputManager,
+        world::World,
+    },
+    rendering::components::{
+        model_renderer::ModelRenderer,
+        sockets::{ModelSocket, ModelSockets},
+    },
+    serde_yaml,
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+use crate::{
+    component_picker::{ComponentPicker, component_picker},
+    selection::EditorSelection,
+};
+
+/// Scales are kept off zero while dragging, typing can still go past
+const SCALE_SOFT_RANGE: RangeInclusive<f32> = 0.001..=1000.0;
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsInspectorOpen;
+
+/// Remembered between frames while the inspector is open
+#[derive(Resource, Clone, Copy)]
+pub struct InspectorState {
+    /// changing one axis of the scale changes the others by the same ratio
+    pub uniform_scale: bool,
+}
+
+impl Default for InspectorState {
+    fn default() -> Self {
+        Self {
+            uniform_scale: true,
+        }
+    }
+}
+
+/// A component drawn by `reflected_components`, fields are None if they can't be read
+struct ReflectedComponent {
+    name: &'static str,
+    doc: &'static str,
+    fields: Vec<(FieldInfo, Option<serde_yaml::Value>)>,
+}
+
+/// Components with their own section in the inspector
+const CUSTOM_SECTIONS: [&str; 2] = ["Transform", "ModelSockets"];
+
+// ========== ========== Widgets ========== ==========
+
+/// Drags faster the further the value is from zero, holding shift drags ten times slower
+fn drag_speed(ui: &egui::Ui, value: f32, base: f32) -> f64 {
+    let speed = (value.abs() * 0.01).max(base);
+    let fine = if ui.input(|i| i.modifiers.shift) {
+        0.1
+    } else {
+        1.0
+    };
+    (speed * fine) as f64
+}
+
+fn axis_drag(
+    ui: &mut egui::Ui,
+    value: &mut f32,
+    prefix: &str,
+    suffix: &str,
+    base_speed: f32,
+    soft_range: Option<&RangeInclusive<f32>>,
+) -> bool {
+    let speed = drag_speed(ui, *value, base_speed);
+    let mut drag = egui::DragValue::new(value)
+        .prefix(prefix)
+        .suffix(suffix)
+        .speed(speed)
+        .max_decimals(3);
+    if let Some(range) = soft_range {
+        drag = drag.range(range.clone()).clamp_existing_to_range(false);
+    }
+    ui.add(drag).changed()
+}
+
+fn reset_button(ui: &mut egui::Ui, is_default: bool) -> bool {
+    ui.add_enabled(!is_default, egui::Button::new("⟲").small())
+        .on_hover_text("Reset to default")
+        .clicked()
+}
+
+/// Draws an x, y and z drag with a reset button, returns true if the value changed
+pub fn vector3_drag(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut Vector3<f32>,
+    default: Vector3<f32>,
+    suffix: &str,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        changed |= axis_drag(ui, &mut value.x, "x: ", suffix, 0.01, None);
+        changed |= axis_drag(ui, &mut value.y, "y: ", suffix, 0.01, None);
+        changed |= axis_drag(ui, &mut value.z, "z: ", suffix, 0.01, None);
+        if reset_button(ui, *value == default) {
+            *value = default;
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Draws a drag of an angle in degrees, wrapped to -180 to 180
+pub fn angle_drag(ui: &mut egui::Ui, degrees: &mut f32, prefix: &str) -> bool {
+    let changed = ui
+        .add(
+            egui::DragValue::new(degrees)
+                .prefix(prefix)
+                .suffix("°")
+                .speed(if ui.input(|i| i.modifiers.shift) {
+                    0.05
+                } else {
+                    0.5
+                })
+                .max_decimals(2),
+        )
+        .changed();
+    if changed {
+        *degrees = wrap_degrees(*degrees);
+    }
+    changed
+}
+
+/// Draws euler angles in degrees with a reset button, returns true if they changed
+pub fn euler_drag(
+    ui: &mut egui::Ui,
+    label: &str,
+    angles: &mut Vector3<f32>,
+    default: Vector3<f32>,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        changed |= angle_drag(ui, &mut angles.x, "x: ");
+        changed |= angle_drag(ui, &mut angles.y, "y: ");
+        changed |= angle_drag(ui, &mut angles.z, "z: ");
+        if reset_button(ui, *angles == default) {
+            *angles = default;
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Draws a rotation as euler angles in degrees, returns true if it changed
+pub fn quaternion_drag(ui: &mut egui::Ui, label: &str, rotation: &mut Quaternion<f32>) -> bool {
+    let euler = Euler::from(*rotation);
+    let mut angles = Vector3::new(
+        Deg::from(euler.x).0,
+        Deg::from(euler.y).0,
+        Deg::from(euler.z).0,
+    );
+    if !euler_drag(ui, label, &mut angles, Vector3::new(0.0, 0.0, 0.0)) {
+        return false;
+    }
+    *rotation = Quaternion::from(Euler {
+        x: Deg(angles.x),
+        y: Deg(angles.y),
+        z: Deg(angles.z),
+    });
+    true
+}
+
+/// Draws a scale with a toggle to keep its proportions, returns true if it changed
+pub fn scale_drag(
+    ui: &mut egui::Ui,
+    label: &str,
+    scale: &mut Vector3<f32>,
+    uniform: &mut bool,
+) -> bool {
+    let default = Vector3::new(1.0, 1.0, 1.0);
+    let before = *scale;
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        changed |= axis_drag(ui, &mut scale.x, "x: ", "", 0.01, Some(&SCALE_SOFT_RANGE));
+        changed |= axis_drag(ui, &mut scale.y, "y: ", "", 0.01, Some(&SCALE_SOFT_RANGE));
+        changed |= axis_drag(ui, &mut scale.z, "z: ", "", 0.01, Some(&SCALE_SOFT_RANGE));
+        ui.toggle_value(uniform, "🔗")
+            .on_hover_text("Keep proportions");
+        if reset_button(ui, *scale == default) {
+            *scale = default;
+            changed = true;
+        }
+    });
+
+    if changed && *uniform && *scale != default {
+        *scale = keep_proportions(before, *scale);
+    }
+    changed
+}
+
+/// Edits the sockets of a model, returns true if any changed
+fn sockets_editor(ui: &mut egui::Ui, sockets: &mut ModelSockets) -> bool {
+    let mut changed = false;
+    let mut removed = None;
+    for (index, socket) in sockets.sockets.iter_mut().enumerate() {
+        ui.push_id(index, |ui| {
+            ui.horizontal(|ui| {
+                changed |= ui.text_edit_singleline(&mut socket.name).changed();
+                if ui.button("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+            changed |= vector3_drag(
+                ui,
+                "Position",
+                &mut socket.position,
+                Vector3::new(0.0, 0.0, 0.0),
+                " m",
+            );
+            changed |= euler_drag(
+                ui,
+                "Rotation",
+                &mut socket.euler_angles,
+                Vector3::new(0.0, 0.0, 0.0),
+            );
+        });
+    }
+    if let Some(index) = removed {
+        sockets.sockets.remove(index);
+        changed = true;
+    }
+    if ui.button("Add socket").clicked() {
+        sockets.sockets.push(ModelSocket {
+            name: format!("socket_{}", sockets.sockets.len()),
+            ..Default::default()
+        });
+        changed = true;
+    }
+    changed
+}
+
+/// Draws three or four numbers as a color, from a list or the x, y, z and w of a vector
+/// floats are 0 to 1 and integers 0 to 255
+/// returns None if the value isn't a color
+fn color_editor(ui: &mut egui::Ui, value: &mut serde_yaml::Value) -> Option<bool> {
+    let channels: Vec<&mut serde_yaml::Value> = match value {
+        serde_yaml::Value::Sequence(values) => values.iter_mut().collect(),
+        serde_yaml::Value::Mapping(map) => {
+            let mut channels: Vec<(usize, &mut serde_yaml::Value)> = map
+                .iter_mut()
+                .filter_map(|(key, value)| {
+                    let index = ["x", "y", "z", "w"]
+                        .iter()
+                        .position(|axis| key.as_str() == Some(axis))?;
+                    Some((index, value))
+                })
+                .collect();
+            channels.sort_by_key(|(index, _)| *index);
+            channels.into_iter().map(|(_, value)| value).collect()
+        }
+        _ => return None,
+    };
+    if !(3..=4).contains(&channels.len()) || channels.iter().any(|c| !c.is_number()) {
+        return None;
+    }
+
+    let is_float = channels.iter().all(|c| c.is_f64());
+    let scale = if is_float { 1.0 } else { 255.0 };
+    let mut rgba = [1.0; 4];
+    for (channel, value) in rgba.iter_mut().zip(&channels) {
+        *channel = (value.as_f64().unwrap_or_default() / scale) as f32;
+    }
+
+    let changed = if channels.len() == 3 {
+        let mut rgb = [rgba[0], rgba[1], rgba[2]];
+        let changed = ui.color_edit_button_rgb(&mut rgb).changed();
+        rgba[..3].copy_from_slice(&rgb);
+        changed
+    } else {
+        ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed()
+    };
+    if changed {
+        for (value, channel) in channels.into_iter().zip(rgba) {
+            *value = if is_float {
+                (channel as f64).into()
+            } else {
+                ((channel * 255.0).round() as i64).into()
+            };
+        }
+    }
+    Some(changed)
+}
+
+/// Edits a reflected field, numbers, bools and strings get their own widget, anything else is
+/// typed as yaml and applied when the field loses focus
+/// Note: `#[inspect(range = ..)]` numbers get a slider and `#[inspect(color)]` a color picker
+/// returns true if the value changed
+fn field_editor(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    value: &mut serde_yaml::Value,
+    inspect: &InspectOptions,
+) -> bool {
+    if inspect.color
+        && let Some(changed) = color_editor(ui, value)
+    {
+        return changed;
+    }
+    match value {
+        serde_yaml::Value::Bool(b) => ui.checkbox(b, "").changed(),
+        serde_yaml::Value::Number(number) if number.is_f64() && inspect.range.is_some() => {
+            let (min, max) = inspect.range.unwrap_or_default();
+            let mut float = number.as_f64().unwrap_or_default();
+            let changed = ui.add(egui::Slider::new(&mut float, min..=max)).changed();
+            *value = float.into();
+            changed
+        }
+        serde_yaml::Value::Number(number) if inspect.range.is_some() => {
+            let (min, max) = inspect.range.unwrap_or_default();
+            let mut integer = number.as_i64().unwrap_or_default();
+            let changed = ui
+                .add(egui::Slider::new(&mut integer, min as i64..=max as i64))
+                .changed();
+            *value = integer.into();
+            changed
+        }
+        serde_yaml::Value::Number(number) if number.is_f64() => {
+            let mut float = number.as_f64().unwrap_or_default();
+            let speed = drag_speed(ui, float as f32, 0.01);
+            let changed = ui
+                .add(egui::DragValue::new(&mut float).speed(speed))
+                .changed();
+            *value = float.into();
+            changed
+        }
+        serde_yaml::Value::Number(number) => {
+            let mut integer = number.as_i64().unwrap_or_default();
+            let changed = ui.add(egui::DragValue::new(&mut integer)).changed();
+            *value = integer.into();
+            changed
+        }
+        serde_yaml::Value::String(text) => ui.text_edit_singleline(text).changed(),
+        _ => {
+            let current = serde_yaml::to_string(value).unwrap_or_default();
+            let mut text = ui
+                .data_mut(|data| data.get_temp::<String>(id))
+                .unwrap_or_else(|| current.trim_end().to_string());
+            let response = ui.text_edit_singleline(&mut text);
+            if response.changed() {
+                ui.data_mut(|data| data.insert_temp(id, text.clone()));
+            }
+            if !response.lost_focus() {
+                return false;
+            }
+            ui.data_mut(|data| data.remove::<String>(id));
+            match serde_yaml::from_str(&text) {
+                Ok(parsed) => {
+                    *value = parsed;
+                    true
+                }
+                Err(e) => {
+                    log_error!("Invalid value {}: {}", text, e);
+                    false
+                }
+            }
+        }
+    }
+}
+
+/// Draws the fields of the components without their own section, read with the reflection of
+/// the `Component` derive
+/// returns the fields that changed as (component, field, value)
+fn reflected_components(
+    ui: &mut egui::Ui,
+    components: &mut [ReflectedComponent],
+) -> Vec<(&'static str, &'static str, serde_yaml::Value)> {
+    let mut changes = Vec::new();
+    for component in components {
+        let header = egui::CollapsingHeader::new(component.name)
+            .id_salt(component.name)
+            .show(ui, |ui| {
+                if component
+                    .fields
+                    .iter()
+                    .all(|(field, _)| field.inspect.hidden)
+                {
+                    ui.weak("No fields");
+                }
+                for (field, value) in &mut component.fields {
+                    if field.inspect.hidden {
+                        continue;
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(field.inspect.label.unwrap_or(field.name))
+                            .on_hover_text(doc_tooltip(field.doc, field.type_name));
+                        match value {
+                            Some(value) => {
+                                let id = ui.id().with(field.name);
+                                let edited = ui
+                                    .add_enabled_ui(!field.inspect.readonly, |ui| {
+                                        field_editor(ui, id, value, &field.inspect)
+                                    })
+                                    .inner;
+                                if edited {
+                                    changes.push((component.name, field.name, value.clone()));
+                                }
+                            }
+                            None => {
+                                ui.weak(field.type_name).on_hover_text(
+                                    "Only #[component_serde] components can be edited here",
+                                );
+                            }
+                        }
+                    });
+                }
+            });
+        if !component.doc.is_empty() {
+            header.header_response.on_hover_text(component.doc);
+        }
+    }
+    changes
+}
+
+/// The doc comment of a field over its type, or only the type if it has no doc comment
+fn doc_tooltip(doc: &str, type_name: &str) -> String {
+    if doc.is_empty() {
+        type_name.to_string()
+    } else {
+        format!("{}\n\n{}", doc, type_name)
+    }
+}
+
+/// Scales every axis of `before` by the ratio of the axis that changed
+fn keep_proportions(before: Vector3<f32>, after: Vector3<f32>) -> Vector3<f32> {
+    let axes = [
+        (before.x, after.x),
+        (before.y, after.y),
+        (before.z, after.z),
+    ];
+    let Some((old, new)) = axes.into_iter().find(|(old, new)| old != new) else {
+        return after;
+    };
+    if old.abs() < f32::EPSILON {
+        // no ratio from zero, make the scale uniform instead
+        return Vector3::new(new, new, new);
+    }
+    before * (new / old)
+}
+
+fn wrap_degrees(degrees: f32) -> f32 {
+    let wrapped = (degrees + 180.0).rem_euclid(360.0) - 180.0;
+    if wrapped == -180.0 { 180.0 } else { wrapped }
+}
+
+// ========== ========== Panel ========== ==========
+
+#[update]
+pub fn toggle_inspector(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleInspector") {
+        if world.has_resource::<IsInspectorOpen>() {
+            world.remove_resource::<IsInspectorOpen>();
+        } else {
+            world.insert_resource(IsInspectorOpen);
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the name, transform and components of the selected object
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsInspectorOpen>)]
+pub fn inspector_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let selected = world.get_resource::<EditorSelection>()?.selected;
+    let mut state = world
+        .get_resource::<InspectorState>()
+        .copied()
+        .unwrap_or_default();
+    let object = selected.and_then(|id| world.object(id).ok());
+    let mut name = object.as_ref().map(|object| object.name.clone());
+    let mut transform = object
+        .as_ref()
+        .and_then(|object| object.get_component::<Transform>().ok().cloned());
+    let mut sockets = object
+        .as_ref()
+        .and_then(|object| object.get_component::<ModelSockets>().ok().cloned());
+    let model_path = object.and_then(|object| {
+        object
+            .get_component::<ModelRenderer>()
+            .ok()
+            .map(|renderer| renderer.model_path.clone())
+    });
+    let present: Vec<&'static str> = selected
+        .map(|id| {
+            world
+                .component_registrations(id)
+                .into_iter()
+                .map(|r| r.type_name)
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut picker = world
+        .get_resource::<ComponentPicker>()
+        .cloned()
+        .unwrap_or_default();
+    let mut reflected: Vec<ReflectedComponent> = selected
+        .map(|id| {
+            world
+                .component_registrations(id)
+                .into_iter()
+                .filter(|r| !CUSTOM_SECTIONS.contains(&r.type_name))
+                .map(|r| ReflectedComponent {
+                    name: r.type_name,
+                    doc: r.doc,
+                    fields: r
+                        .fields
+                        .iter()
+                        .map(|field| {
+                            let value = world.get_component_field(id, r.type_name, field.name).ok();
+                            (*field, value)
+                        })
+                        .collect(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut field_changes = Vec::new();
+    let mut changed = false;
+    let mut duplicate = false;
+    let mut read_sockets = false;
+    let mut picked = None;
+
+    let mut is_open = true;
+    egui::Window::new("Inspector")
+        .open(&mut is_open)
+        .default_width(360.0)
+        .show(&ctx, |ui| {
+            let Some(name) = name.as_mut() else {
+                ui.label("Nothing selected, middle click an object to select it");
+                return;
+            };
+            ui.horizontal(|ui| {
+                changed |= ui.text_edit_singleline(name).changed();
+                duplicate = ui
+                    .button("Duplicate")
+                    .on_hover_text("Copy the object and its children")
+                    .clicked();
+            });
+
+            let Some(transform) = transform.as_mut() else {
+                return;
+            };
+            ui.separator();
+            ui.strong("Transform");
+            changed |= vector3_drag(
+                ui,
+                "Position",
+                &mut transform.local_position,
+                Vector3::new(0.0, 0.0, 0.0),
+                " m",
+            );
+            changed |= euler_drag(
+                ui,
+                "Rotation",
+                &mut transform.local_euler_angles,
+                Vector3::new(0.0, 0.0, 0.0),
+            );
+            changed |= scale_drag(
+                ui,
+                "Scale",
+                &mut transform.local_scale,
+                &mut state.uniform_scale,
+            );
+
+            if let Some(sockets) = sockets.as_mut() {
+                ui.separator();
+                ui.strong("Sockets");
+                changed |= sockets_editor(ui, sockets);
+            } else if model_path.is_some() {
+                ui.separator();
+                read_sockets = ui
+                    .button("Add sockets")
+                    .on_hover_text("Read the sockets from the empties of the model")
+                    .clicked();
+            }
+
+            if !reflected.is_empty() {
+                ui.separator();
+                ui.strong("Components");
+                field_changes = reflected_components(ui, &mut reflected);
+            }
+
+            ui.separator();
+            let add = ui
+                .button("Add Component")
+                .on_hover_text("Search every registered component");
+            if add.clicked() {
+                picker.is_open = !picker.is_open;
+            }
+            picked = component_picker(ui.ctx(), add.rect.left_bottom(), &mut picker, &present);
+        });
+
+    if !is_open {
+        world.remove_resource::<IsInspectorOpen>();
+    }
+    world.insert_resource(state);
+    if selected.is_none() {
+        picker = ComponentPicker::default();
+    }
+    world.insert_resource(picker);
+
+    if let (true, Some(id)) = (changed, selected) {
+        let mut object = world.object_mut(id)?;
+        if let Some(name) = name {
+            object.name = name;
+        }
+        if let (Some(transform), Ok(current)) = (transform, object.get_component_mut::<Transform>())
+        {
+            *current = transform;
+        }
+        if let (Some(sockets), Ok(current)) = (sockets, object.get_component_mut::<ModelSockets>())
+        {
+            *current = sockets;
+        }
+    }
+
+    if let Some(id) = selected {
+        for (component, field, value) in field_changes {
+            if let Err(e) = world.set_component_field(id, component, field, &value) {
+                log_error!("{}", e);
+            }
+        }
+    }
+
+    if let (Some(component), Some(id)) = (picked, selected)
+        && let Err(e) = world.insert_component_by_name(id, component)
+    {
+        log_error!("Failed to add {}: {}", component, e);
+    }
+
+    if let (true, Some(id), Some(path)) = (read_sockets, selected, model_path) {
+        let sockets = ModelSockets::from_gltf(&path).unwrap_or_else(|e| {
+            log_error!("Failed to read sockets: {}", e);
+            ModelSockets::default()
+        });
+        world.object_mut(id)?.add_component(sockets);
+    }
+
+    if let (true, Some(id)) = (duplicate, selected) {
+        match world.clone_object(id) {
+            Ok(copy) => world.get_resource_mut::<EditorSelection>()?.selected = Some(copy),
+            Err(e) => {
+                log_error!("Failed to duplicate object: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
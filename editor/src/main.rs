@@ -1,12 +1,57 @@
-use apostasy_core::{init_core, packages::Packages, rendering::RenderingBackend};
+use apostasy_core::{
+    anyhow::Result, export::export_from_args, init_core, objects::world::World, packages::Packages,
+    rendering::RenderingBackend, start, states::GameState,
+};
 
+pub mod camera_bookmarks;
+pub mod component_picker;
 pub mod editor_camera;
+pub mod find_in_scenes;
+pub mod frame_graph;
+pub mod grid;
 pub mod input;
+pub mod input_manager_panel;
+pub mod inspector;
+pub mod physics_settings;
+pub mod play_mode;
+pub mod profiler_panel;
+pub mod render_settings;
+pub mod scene_lock;
+pub mod selection;
+pub mod systems_panel;
+pub mod texture_import;
+pub mod timeline;
+pub mod world_stats;
+
+// `register_all`, everything the editor registers, for `registry::install`
+#[cfg(feature = "explicit-registration")]
+apostasy_macros::register_all!();
 
 fn main() {
+    #[cfg(feature = "explicit-registration")]
+    apostasy_core::objects::registry::install(&[apostasy_core::register_all, register_all])
+        .unwrap();
+
+    // `apostasy-editor export --target <platform>` packages the game instead of opening the editor
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(result) = export_from_args(&args) {
+        if let Err(e) = result {
+            eprintln!("Export failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     init_core(
         RenderingBackend::Vulkan,
         vec![Packages::Voxel, Packages::ItemSystem],
     )
     .unwrap();
 }
+
+/// Runs the systems that are only in the editor
+#[start]
+pub fn editor_state(world: &mut World) -> Result<()> {
+    world.add_state(GameState::Editor);
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui, log_error,
+    objects::{resources::input_manager::InputManager, world::World},
+    physics::physics_settings::{PhysicsSettings, ScenePhysicsSettings},
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsPhysicsSettingsOpen;
+
+#[update]
+pub fn toggle_physics_settings(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("TogglePhysicsSettings") {
+        if world.has_resource::<IsPhysicsSettingsOpen>() {
+            world.remove_resource::<IsPhysicsSettingsOpen>();
+        } else {
+            world.insert_resource(IsPhysicsSettingsOpen);
+        }
+    }
+    if world
+        .get_resource::<InputManager>()?
+        .is_keybind_active("TogglePhysicsPause")
+    {
+        let settings = world.get_resource_mut::<PhysicsSettings>()?;
+        settings.paused = !settings.paused;
+    }
+
+    Ok(())
+}
+
+/// Draws the physics settings window, changes are saved to the project straight away
+/// Note: the scene gravity is saved with the scene instead
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsPhysicsSettingsOpen>)]
+pub fn physics_settings_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let mut settings = world.get_resource::<PhysicsSettings>()?.clone();
+    let mut scene = world
+        .get_resource::<ScenePhysicsSettings>()
+        .cloned()
+        .unwrap_or_default();
+
+    let mut is_open = true;
+    egui::Window::new("Physics")
+        .open(&mut is_open)
+        .resizable(false)
+        .show(&ctx, |ui| {
+            ui.checkbox(&mut settings.paused, "Pause Physics")
+                .on_hover_text("Stops gravity and collisions so objects can be arranged (Pause)");
+            ui.separator();
+            ui.add(egui::Slider::new(&mut settings.substeps, 1..=16).text("Substeps"));
+            ui.add(
+                egui::Slider::new(&mut settings.solver_iterations, 1..=16)
+                    .text("Solver Iterations"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut settings.gravity)
+                    .prefix("Gravity: ")
+                    .speed(0.1),
+            );
+            ui.add(
+                egui::DragValue::new(&mut settings.max_fall_speed)
+                    .prefix("Max Fall Speed: ")
+                    .range(0.0..=f32::MAX)
+                    .speed(0.5),
+            );
+
+            ui.separator();
+            ui.strong("Scene");
+            let mut overrides_gravity = scene.gravity.is_some();
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut overrides_gravity, "Override Gravity")
+                    .changed()
+                {
+                    scene.gravity = overrides_gravity.then_some(settings.gravity);
+                }
+                if let Some(gravity) = scene.gravity.as_mut() {
+                    ui.add(egui::DragValue::new(gravity).speed(0.1));
+                }
+            });
+        });
+
+    if !is_open {
+        world.remove_resource::<IsPhysicsSettingsOpen>();
+    }
+
+    world.insert_resource(scene);
+
+    let current = world.get_resource_mut::<PhysicsSettings>()?;
+    if *current != settings {
+        // pausing is not saved, so only save when something else changed
+        let saved_changed = PhysicsSettings {
+            paused: current.paused,
+            ..settings.clone()
+        } != *current;
+        if saved_changed && let Err(e) = settings.save() {
+            log_error!("Failed to save physics settings: {}", e);
+        }
+        *current = settings;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui,
+    objects::{resources::input_manager::InputManager, world::World},
+    ui::ui_context::EguiContext,
+    update,
+};
+
+/// Starts the game in a copy of the edited world and throws the copy away on stop (F11)
+/// Note: runs in every state, so the game can be stopped while it plays
+#[update(while_paused = true)]
+pub fn play_mode_toolbar(world: &mut World) -> Result<()> {
+    let mut toggle = world
+        .get_resource::<InputManager>()?
+        .is_keybind_active("TogglePlayMode");
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let is_playing = world.is_in_play_mode();
+
+    egui::Area::new(egui::Id::new("play_mode_toolbar"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+        .show(&ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let label = if is_playing { "Stop" } else { "Play" };
+                    toggle |= ui.button(label).on_hover_text("F11").clicked();
+                    if is_playing {
+                        ui.label("Changes made while playing are lost on stop");
+                    }
+                });
+            });
+        });
+
+    if toggle {
+        if is_playing {
+            world.stop();
+        } else {
+            world.play();
+        }
+    }
+
+    Ok(())
+}
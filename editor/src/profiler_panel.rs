@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use apostasy_core::{
+    anyhow::Result,
+    egui,
+    objects::{
+        resources::input_manager::InputManager, systems::stats::SystemProfiler, world::World,
+    },
+    profiler,
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsProfilerOpen;
+
+#[update]
+pub fn toggle_profiler(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleProfiler") {
+        if world.has_resource::<IsProfilerOpen>() {
+            world.remove_resource::<IsProfilerOpen>();
+        } else {
+            world.insert_resource(IsProfilerOpen);
+        }
+    }
+
+    Ok(())
+}
+
+/// How many of the slowest systems the panel lists
+const TOP_SYSTEMS: usize = 15;
+
+/// Draws the engine and game profiler scopes, counters and gauges of the last frame, and the
+/// slowest systems
+/// Note: the scope average is over every frame the profiler keeps
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsProfilerOpen>)]
+pub fn profiler_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let history = profiler::history();
+
+    let mut averages: HashMap<&str, f32> = HashMap::new();
+    for frame in &history {
+        for scope in &frame.scopes {
+            *averages.entry(scope.path.as_str()).or_insert(0.0) += scope.total_ms;
+        }
+    }
+    let frame_count = history.len().max(1) as f32;
+    let top_systems = world
+        .get_resource::<SystemProfiler>()
+        .map(|profiler| profiler.top(TOP_SYSTEMS))
+        .unwrap_or_default();
+    let mut reset_systems = false;
+
+    let mut is_open = true;
+    let mut is_enabled = profiler::is_enabled();
+    egui::Window::new("Profiler")
+        .open(&mut is_open)
+        .default_width(360.0)
+        .show(&ctx, |ui| {
+            ui.checkbox(&mut is_enabled, "Recording");
+            let Some(frame) = history.last() else {
+                ui.label("No frames recorded");
+                return;
+            };
+            ui.label(format!("Frame {}", frame.frame));
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("profiler_scopes")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Scope");
+                        ui.strong("Calls");
+                        ui.strong("ms");
+                        ui.strong("Avg ms");
+                        ui.end_row();
+                        for scope in &frame.scopes {
+                            let average = averages.get(scope.path.as_str()).copied().unwrap_or(0.0)
+                                / frame_count;
+                            ui.label(format!("{}{}", "  ".repeat(scope.depth), scope.name));
+                            ui.label(scope.calls.to_string());
+                            ui.label(format!("{:.3}", scope.total_ms));
+                            ui.label(format!("{:.3}", average));
+                            ui.end_row();
+                        }
+                    });
+
+                if !top_systems.is_empty() {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.strong("Slowest systems");
+                        reset_systems = ui.small_button("Reset").clicked();
+                    });
+                    egui::Grid::new("profiler_systems")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("System");
+                            ui.strong("Calls");
+                            ui.strong("Avg ms");
+                            ui.strong("Max ms");
+                            ui.end_row();
+                            for stats in &top_systems {
+                                ui.label(stats.name).on_hover_text(format!(
+                                    "{} ({})",
+                                    stats.package,
+                                    stats.kind.label()
+                                ));
+                                ui.label(stats.calls.to_string());
+                                ui.label(format!("{:.3}", stats.avg_ms));
+                                ui.label(format!("{:.3}", stats.max_ms));
+                                ui.end_row();
+                            }
+                        });
+                }
+
+                if !frame.counters.is_empty() {
+                    ui.separator();
+                    ui.strong("Counters");
+                    for (name, value) in &frame.counters {
+                        ui.label(format!("{}: {}", name, value));
+                    }
+                }
+                if !frame.gauges.is_empty() {
+                    ui.separator();
+                    ui.strong("Gauges");
+                    for (name, value) in &frame.gauges {
+                        ui.label(format!("{}: {:.3}", name, value));
+                    }
+                }
+            });
+        });
+
+    if is_enabled != profiler::is_enabled() {
+        profiler::set_enabled(is_enabled);
+    }
+    if reset_systems && let Ok(profiler) = world.get_resource_mut::<SystemProfiler>() {
+        profiler.reset();
+    }
+    if !is_open {
+        world.remove_resource::<IsProfilerOpen>();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui, log_error,
+    objects::{
+        resources::{
+            input_manager::InputManager,
+            window_settings::{WindowMode, WindowSettings},
+        },
+        world::World,
+    },
+    rendering::shared::{
+        baked_ao::AoBakeSettings,
+        frustrum::ObjectsDrawing,
+        gpu::GpuAdapters,
+        grid::EditorGrid,
+        outline::SelectionOutline,
+        rendering_config::{AmbientOcclusionConfig, PresentMode, RenderingConfig, RenderingStats},
+    },
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsRenderSettingsOpen;
+
+#[update]
+pub fn toggle_render_settings(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleRenderSettings") {
+        if world.has_resource::<IsRenderSettingsOpen>() {
+            world.remove_resource::<IsRenderSettingsOpen>();
+        } else {
+            world.insert_resource(IsRenderSettingsOpen);
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the rendering settings window, changes are saved to the rendering config straight away
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsRenderSettingsOpen>)]
+pub fn render_settings_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let stats = *world.get_resource::<RenderingStats>()?;
+    let objects_drawing = world.get_resource::<ObjectsDrawing>()?.0;
+    let mut config = world.get_resource::<RenderingConfig>()?.clone();
+    let mut window = world.get_resource::<WindowSettings>().ok().cloned();
+    let adapters = world
+        .get_resource::<GpuAdapters>()
+        .cloned()
+        .unwrap_or_default();
+    let mut grid = world.get_resource::<EditorGrid>().ok().cloned();
+    let mut outline = world.get_resource::<SelectionOutline>().ok().cloned();
+    let mut bake_settings = world
+        .get_resource::<AoBakeSettings>()
+        .copied()
+        .unwrap_or_default();
+    let mut bake = false;
+
+    let mut is_open = true;
+    egui::Window::new("Rendering")
+        .open(&mut is_open)
+        .resizable(false)
+        .default_pos([16.0, 16.0])
+        .show(&ctx, |ui| {
+            ui.heading("Settings");
+            egui::ComboBox::from_label("Present Mode")
+                .selected_text(config.present_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in PresentMode::ALL {
+                        ui.selectable_value(&mut config.present_mode, mode, mode.label());
+                    }
+                });
+            ui.checkbox(&mut config.wireframe, "Wireframe");
+            egui::ComboBox::from_label("GPU")
+                .selected_text(config.preferred_gpu.as_deref().unwrap_or("Automatic"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut config.preferred_gpu, None, "Automatic");
+                    for adapter in &adapters.adapters {
+                        ui.selectable_value(
+                            &mut config.preferred_gpu,
+                            Some(adapter.name.clone()),
+                            &adapter.name,
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("Applies on the next start");
+            ui.weak("MSAA, render scale and shadow quality are not supported yet");
+
+            if let Some(window) = &mut window {
+                ui.separator();
+                ui.heading("Window");
+                egui::ComboBox::from_label("Mode")
+                    .selected_text(window.mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in WindowMode::ALL {
+                            ui.selectable_value(&mut window.mode, mode, mode.label());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut window.width)
+                            .prefix("Width: ")
+                            .range(1..=u32::MAX),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut window.height)
+                            .prefix("Height: ")
+                            .range(1..=u32::MAX),
+                    );
+                })
+                .response
+                .on_hover_text("The resolution while fullscreen");
+                ui.checkbox(&mut window.vsync, "VSync")
+                    .on_hover_text("Overrides the present mode");
+                ui.checkbox(&mut window.resizable, "Resizable");
+            }
+
+            ui.separator();
+            ui.heading("Ambient Occlusion");
+            ui.add_enabled_ui(stats.ambient_occlusion_available, |ui| {
+                let ao = &mut config.ambient_occlusion;
+                ui.checkbox(&mut ao.enabled, "Compute Ambient Occlusion")
+                    .on_disabled_hover_text("The compute shader could not be loaded");
+                ui.add_enabled_ui(ao.enabled, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut ao.samples, 1..=AmbientOcclusionConfig::MAX_SAMPLES)
+                            .text("Quality"),
+                    )
+                    .on_hover_text("Samples per pixel");
+                    ui.add(egui::Slider::new(&mut ao.radius, 0.1..=4.0).text("Radius"));
+                    ui.add(egui::Slider::new(&mut ao.intensity, 0.0..=1.0).text("Intensity"));
+                });
+            });
+
+            if let Some(grid) = &mut grid {
+                ui.separator();
+                ui.heading("Grid");
+                ui.checkbox(&mut grid.is_visible, "Show Grid");
+                ui.checkbox(&mut grid.show_axes_gizmo, "Show Axes");
+                ui.add(
+                    egui::DragValue::new(&mut grid.cell_size)
+                        .prefix("Cell Size: ")
+                        .range(0.01..=f32::MAX)
+                        .speed(0.05),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut grid.height)
+                        .prefix("Height: ")
+                        .speed(0.1),
+                );
+                let mut show_snap = grid.snap_size.is_some();
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut show_snap, "Snap Lines");
+                    let mut snap_size = grid.snap_size.unwrap_or(0.25);
+                    ui.add_enabled(
+                        show_snap,
+                        egui::DragValue::new(&mut snap_size)
+                            .range(0.01..=f32::MAX)
+                            .speed(0.01),
+                    );
+                    grid.snap_size = show_snap.then_some(snap_size);
+                });
+            }
+
+            if let Some(outline) = &mut outline {
+                ui.separator();
+                ui.heading("Selection");
+                ui.checkbox(&mut outline.is_visible, "Outline Selection");
+                ui.add_enabled_ui(outline.is_visible, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_rgba_unmultiplied(&mut outline.color);
+                        ui.label("Color");
+                    });
+                    ui.add(
+                        egui::Slider::new(
+                            &mut outline.thickness,
+                            1.0..=SelectionOutline::MAX_THICKNESS,
+                        )
+                        .text("Thickness"),
+                    );
+                });
+            }
+
+            ui.separator();
+            ui.heading("Baking");
+            ui.add(
+                egui::DragValue::new(&mut bake_settings.samples)
+                    .prefix("Samples: ")
+                    .range(1..=1024),
+            );
+            ui.add(
+                egui::DragValue::new(&mut bake_settings.max_distance)
+                    .prefix("Distance: ")
+                    .range(0.01..=f32::MAX)
+                    .speed(0.05),
+            );
+            bake = ui
+                .button("Bake Ambient Occlusion")
+                .on_hover_text("Bakes the models of Static objects, save the scene to keep it")
+                .clicked();
+
+            ui.separator();
+            ui.heading("Stats");
+            if let Some(gpu) = adapters.active() {
+                ui.label(format!("GPU: {} ({})", gpu.name, gpu.kind.label()))
+                    .on_hover_text(format!(
+                        "Driver {}\nVulkan {}\n{} MiB of device memory",
+                        gpu.driver_version,
+                        gpu.api_version,
+                        gpu.memory_bytes / (1024 * 1024)
+                    ));
+            }
+            ui.label(format!("Draw Calls: {}", stats.draw_calls));
+            ui.label(format!("Chunks Drawn: {}", objects_drawing));
+            match stats.gpu_time_ms {
+                Some(gpu_time) => ui.label(format!("GPU Time: {:.2}ms", gpu_time)),
+                None => ui.label("GPU Time: unsupported"),
+            };
+        });
+
+    if !is_open {
+        world.remove_resource::<IsRenderSettingsOpen>();
+    }
+
+    world.insert_resource(bake_settings);
+    if bake && let Err(e) = world.bake_ambient_occlusion(&bake_settings) {
+        log_error!("Failed to bake ambient occlusion: {}", e);
+    }
+
+    if let Some(grid) = grid
+        && let Ok(current) = world.get_resource_mut::<EditorGrid>()
+    {
+        *current = grid;
+    }
+
+    if let Some(outline) = outline
+        && let Ok(current) = world.get_resource_mut::<SelectionOutline>()
+    {
+        // the selected object is kept up to date by outline_selection
+        current.is_visible = outline.is_visible;
+        current.color = outline.color;
+        current.thickness = outline.thickness;
+    }
+
+    if let Some(mut window) = window
+        && let Ok(current) = world.get_resource_mut::<WindowSettings>()
+        && *current != window
+    {
+        window.is_dirty = true;
+        if let Err(e) = window.save() {
+            log_error!("Failed to save window settings: {}", e);
+        }
+        *current = window;
+    }
+
+    let current = world.get_resource_mut::<RenderingConfig>()?;
+    if *current != config {
+        config.is_dirty = true;
+        if let Err(e) = config.save() {
+            log_error!("Failed to save rendering config: {}", e);
+        }
+        *current = config;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui, log_warn,
+    objects::{scene_lock::SceneLock, snapshot::SceneLoaded, world::World},
+    states::GameState,
+    ui::ui_context::EguiContext,
+    update,
+};
+
+/// Locks every scene the editor loads, so a second editor opening it gets it read only
+#[update(in_state = GameState::Editor, while_paused = true)]
+pub fn lock_loaded_scenes(world: &mut World) -> Result<()> {
+    let Ok(reader) = world.event_reader::<SceneLoaded>() else {
+        return Ok(());
+    };
+    let paths: Vec<String> = reader.read().map(|event| event.path.clone()).collect();
+    for path in paths {
+        if let Err(e) = world.lock_scene(&path) {
+            log_warn!("Failed to lock {}: {}", path, e);
+        }
+    }
+    Ok(())
+}
+
+/// Warns that the open scene is open in another instance, with a button to take it over
+#[update(in_state = GameState::Editor, while_paused = true)]
+pub fn scene_lock_banner(world: &mut World) -> Result<()> {
+    let Ok(lock) = world.get_resource::<SceneLock>() else {
+        return Ok(());
+    };
+    let Some(owner) = lock.held_by.clone() else {
+        return Ok(());
+    };
+    let path = lock.path.clone();
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+
+    let mut take_over = false;
+    egui::Area::new(egui::Id::new("scene_lock_banner"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 50.0])
+        .show(&ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("{} is open in {}, it is read only", path, owner),
+                    );
+                    take_over = ui
+                        .button("Take over")
+                        .on_hover_text("Their saves are refused from then on")
+                        .clicked();
+                });
+            });
+        });
+
+    if take_over {
+        world.take_over_scene()?;
+    }
+    Ok(())
+}
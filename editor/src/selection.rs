@@ -0,0 +1,60 @@
+use apostasy_core::{
+    anyhow::Result,
+    log,
+    objects::{resources::input_manager::InputManager, scene::ObjectId, world::World},
+    rendering::shared::{outline::SelectionOutline, picking::ObjectPicking},
+    start,
+    states::GameState,
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+/// The object currently selected in the editor
+#[derive(Resource, Clone, Default)]
+pub struct EditorSelection {
+    pub selected: Option<ObjectId>,
+}
+
+#[start]
+pub fn selection_start(world: &mut World) -> Result<()> {
+    world.insert_resource(EditorSelection::default());
+    world.insert_resource(SelectionOutline::default());
+    Ok(())
+}
+
+/// Outlines the selected object, outside of the editor nothing is outlined
+#[update]
+pub fn outline_selection(world: &mut World) -> Result<()> {
+    let selected = match world.in_state(&GameState::Editor) {
+        true => world.get_resource::<EditorSelection>()?.selected,
+        false => None,
+    };
+    world.get_resource_mut::<SelectionOutline>()?.object = selected;
+    Ok(())
+}
+
+/// Picks the object under the cursor when "Select" is pressed outside of the ui
+#[update]
+pub fn select_object(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let inputs = world.get_resource::<InputManager>()?;
+    let wants_select = inputs.is_mousebind_active("Select") && !ctx.is_pointer_over_area();
+    let cursor = inputs.mouse_position;
+
+    let picking = world.get_resource_mut::<ObjectPicking>()?;
+    if wants_select {
+        picking.pick(cursor.x.max(0.0) as u32, cursor.y.max(0.0) as u32);
+    }
+
+    let Some(result) = picking.result.take() else {
+        return Ok(());
+    };
+
+    world.get_resource_mut::<EditorSelection>()?.selected = result.object;
+    if let Some(object) = result.object.and_then(|id| world.get_object(id)) {
+        log!("Selected {}", object.name);
+    }
+
+    Ok(())
+}
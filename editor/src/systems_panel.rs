@@ -0,0 +1,123 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui, log, log_error,
+    objects::{
+        resources::input_manager::InputManager,
+        systems::{SystemInfo, SystemKind},
+        world::World,
+    },
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+/// The systems that draw this panel, they can't be disabled from it
+const PANEL_SYSTEMS: [&str; 2] = ["toggle_systems", "systems_panel"];
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsSystemsOpen;
+
+#[update]
+pub fn toggle_systems(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleSystems") {
+        if world.has_resource::<IsSystemsOpen>() {
+            world.remove_resource::<IsSystemsOpen>();
+        } else {
+            world.insert_resource(IsSystemsOpen);
+        }
+    }
+
+    Ok(())
+}
+
+/// The package of a system and its ordering constraints
+fn order_text(system: &SystemInfo) -> String {
+    let mut text = system.package.to_string();
+    if let Some(set) = system.order.set {
+        text.push_str(&format!("\nSet: {}", set));
+    }
+    if !system.order.before.is_empty() {
+        text.push_str(&format!("\nBefore: {}", system.order.before.join(", ")));
+    }
+    if !system.order.after.is_empty() {
+        text.push_str(&format!("\nAfter: {}", system.order.after.join(", ")));
+    }
+    text
+}
+
+/// Draws every registered system in run order, unticking a system stops it from running
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsSystemsOpen>)]
+pub fn systems_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let systems = world.systems();
+    let mut changes: Vec<(SystemInfo, bool)> = Vec::new();
+    let mut enable_all = false;
+
+    let mut is_open = true;
+    egui::Window::new("Systems")
+        .open(&mut is_open)
+        .default_width(320.0)
+        .show(&ctx, |ui| {
+            let disabled = systems.iter().filter(|s| !s.is_enabled).count();
+            ui.horizontal(|ui| {
+                ui.label(format!("{} systems, {} disabled", systems.len(), disabled));
+                if ui
+                    .add_enabled(disabled > 0, egui::Button::new("Enable All"))
+                    .clicked()
+                {
+                    enable_all = true;
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for kind in SystemKind::ALL {
+                    egui::CollapsingHeader::new(kind.label())
+                        .default_open(kind == SystemKind::Update)
+                        .show(ui, |ui| {
+                            for system in systems.iter().filter(|s| s.kind == kind) {
+                                let mut is_enabled = system.is_enabled;
+                                let is_locked = PANEL_SYSTEMS.contains(&system.name);
+                                ui.add_enabled(
+                                    !is_locked,
+                                    egui::Checkbox::new(
+                                        &mut is_enabled,
+                                        format!("{} ({})", system.name, system.priority),
+                                    ),
+                                )
+                                .on_hover_text(order_text(system));
+                                if is_enabled != system.is_enabled {
+                                    changes.push((*system, is_enabled));
+                                }
+                            }
+                        });
+                }
+            });
+        });
+
+    if !is_open {
+        world.remove_resource::<IsSystemsOpen>();
+    }
+
+    if enable_all {
+        world.enable_all_systems();
+        log!("Enabled all systems");
+    }
+    for (system, is_enabled) in changes {
+        let name = format!("{}::{}", system.package, system.name);
+        match world.set_system_enabled(&name, is_enabled) {
+            Ok(_) => {
+                let state = if is_enabled { "Enabled" } else { "Disabled" };
+                log!("{} {} system {}", state, system.kind.label(), name);
+            }
+            Err(e) => {
+                log_error!("Failed to toggle {}: {}", name, e);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use apostasy_core::{
+    anyhow::Result,
+    assets::texture_import::{
+        TEXTURE_DIR, TextureCompression, TextureImportSettings, find_new_textures, import_texture,
+    },
+    egui, log, log_error,
+    objects::world::World,
+    states::GameState,
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+/// How often `res/textures` is checked for new images
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An image waiting to be imported and the settings it will be imported with
+#[derive(Clone)]
+pub struct PendingTexture {
+    pub path: PathBuf,
+    pub settings: TextureImportSettings,
+}
+
+/// The images found in `res/textures` that were not imported yet
+#[derive(Resource, Clone, Default)]
+pub struct TextureImports {
+    pub pending: Vec<PendingTexture>,
+    /// images skipped this session, they are offered again after a restart
+    pub skipped: Vec<PathBuf>,
+    last_scan: Option<Instant>,
+}
+
+/// Looks for new or changed images, keeping the settings of ones already offered
+#[update(in_state = GameState::Editor)]
+pub fn scan_textures(world: &mut World) -> Result<()> {
+    let imports = world.init_resource::<TextureImports>()?;
+    if imports
+        .last_scan
+        .is_some_and(|last_scan| last_scan.elapsed() < SCAN_INTERVAL)
+    {
+        return Ok(());
+    }
+    imports.last_scan = Some(Instant::now());
+
+    for path in find_new_textures(Path::new(TEXTURE_DIR)) {
+        if imports.skipped.contains(&path) || imports.pending.iter().any(|p| p.path == path) {
+            continue;
+        }
+        // changed images start from the settings they were last imported with
+        let settings = TextureImportSettings::load(&path).unwrap_or_default();
+        imports.pending.push(PendingTexture { path, settings });
+    }
+
+    Ok(())
+}
+
+/// Converts an image and refreshes its tile in the voxel atlas
+fn import(world: &mut World, texture: &PendingTexture) -> Result<()> {
+    let output = import_texture(&texture.path, &texture.settings)?;
+    let name = output
+        .strip_prefix("res/")
+        .unwrap_or(&output)
+        .to_string_lossy()
+        .replace('\\', "/");
+    if world.refresh_texture(&name)? {
+        log!("Imported {} and refreshed the voxel atlas", name);
+    } else {
+        log!("Imported {}", name);
+    }
+    Ok(())
+}
+
+/// Offers the import settings of every new image
+#[update(in_state = GameState::Editor)]
+pub fn texture_import_panel(world: &mut World) -> Result<()> {
+    let Ok(imports) = world.get_resource::<TextureImports>() else {
+        return Ok(());
+    };
+    if imports.pending.is_empty() {
+        return Ok(());
+    }
+    let mut pending = imports.pending.clone();
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+
+    let mut to_import = Vec::new();
+    let mut to_skip = Vec::new();
+    egui::Window::new("Import Textures")
+        .default_width(320.0)
+        .show(&ctx, |ui| {
+            for (index, texture) in pending.iter_mut().enumerate() {
+                ui.push_id(index, |ui| {
+                    ui.strong(texture.path.display().to_string());
+                    ui.checkbox(&mut texture.settings.srgb, "sRGB colour");
+                    ui.checkbox(&mut texture.settings.mipmaps, "Power of two for mipmaps");
+                    egui::ComboBox::from_label("Compression")
+                        .selected_text(texture.settings.compression.label())
+                        .show_ui(ui, |ui| {
+                            for compression in TextureCompression::ALL {
+                                ui.selectable_value(
+                                    &mut texture.settings.compression,
+                                    compression,
+                                    compression.label(),
+                                );
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        if ui.button("Import").clicked() {
+                            to_import.push(index);
+                        }
+                        if ui.button("Skip").clicked() {
+                            to_skip.push(index);
+                        }
+                    });
+                });
+                ui.separator();
+            }
+            if ui.button("Import all").clicked() {
+                to_import = (0..pending.len()).collect();
+            }
+        });
+
+    for &index in &to_import {
+        if let Err(e) = import(world, &pending[index]) {
+            log_error!("Failed to import {}: {}", pending[index].path.display(), e);
+        }
+    }
+
+    let imports = world.get_resource_mut::<TextureImports>()?;
+    for &index in &to_skip {
+        imports.skipped.push(pending[index].path.clone());
+    }
+    let mut index = 0;
+    pending.retain(|_| {
+        let keep = !to_import.contains(&index) && !to_skip.contains(&index);
+        index += 1;
+        keep
+    });
+    imports.pending = pending;
+
+    Ok(())
+}
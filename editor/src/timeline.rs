@@ -0,0 +1,320 @@
+use std::sync::Arc;
+
+use apostasy_core::{
+    anyhow::Result,
+    egui, log, log_error,
+    objects::{
+        components::transform::Transform, resources::input_manager::InputManager, scene::ObjectId,
+        world::World,
+    },
+    rendering::components::camera::ActiveCamera,
+    sequencer::{CueKind, ObjectTrack, SequenceCue, SequencePlayer, TransformKey},
+    start,
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+use apostasy_macros::Resource;
+
+use crate::selection::EditorSelection;
+
+/// Keys closer than this to the playhead are replaced or deleted
+const KEY_SNAP: f32 = 0.05;
+const TRACK_HEIGHT: f32 = 18.0;
+
+#[derive(Resource, Clone, Copy, Default)]
+pub struct IsTimelineOpen;
+
+/// The state of the timeline panel
+#[derive(Resource, Clone)]
+pub struct TimelineEditor {
+    /// the object whose sequence player is being edited
+    pub player: Option<ObjectId>,
+    pub new_cue_name: String,
+    pub new_cue_kind: CueKind,
+}
+
+impl Default for TimelineEditor {
+    fn default() -> Self {
+        Self {
+            player: None,
+            new_cue_name: String::new(),
+            new_cue_kind: CueKind::Event,
+        }
+    }
+}
+
+#[start]
+pub fn timeline_start(world: &mut World) -> Result<()> {
+    world.insert_resource(TimelineEditor::default());
+    Ok(())
+}
+
+#[update]
+pub fn toggle_timeline(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleTimeline") {
+        if world.has_resource::<IsTimelineOpen>() {
+            world.remove_resource::<IsTimelineOpen>();
+        } else {
+            world.insert_resource(IsTimelineOpen);
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the key at the playhead or adds a new one
+fn set_key(keys: &mut Vec<TransformKey>, key: TransformKey) {
+    keys.retain(|k| (k.time - key.time).abs() > KEY_SNAP);
+    keys.push(key);
+}
+
+/// Draws one row of the timeline, returns the time clicked or dragged to
+fn track_row(
+    ui: &mut egui::Ui,
+    label: &str,
+    times: &[f32],
+    time: f32,
+    duration: f32,
+) -> Option<f32> {
+    ui.horizontal(|ui| {
+        ui.add_sized([80.0, TRACK_HEIGHT], egui::Label::new(label).truncate());
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), TRACK_HEIGHT),
+            egui::Sense::click_and_drag(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, egui::Color32::from_gray(30));
+
+        let to_x =
+            |t: f32| rect.left() + (t / duration.max(f32::EPSILON)).clamp(0.0, 1.0) * rect.width();
+        for key_time in times {
+            painter.circle_filled(
+                egui::pos2(to_x(*key_time), rect.center().y),
+                4.0,
+                egui::Color32::from_rgb(230, 180, 60),
+            );
+        }
+        painter.vline(
+            to_x(time),
+            rect.y_range(),
+            egui::Stroke::new(1.5, egui::Color32::from_rgb(220, 60, 60)),
+        );
+
+        let pointer = response.interact_pointer_pos()?;
+        (response.clicked() || response.dragged())
+            .then(|| ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0) * duration)
+    })
+    .inner
+}
+
+/// Draws the timeline, used to play, scrub and key the sequence of a SequencePlayer
+#[update(in_state = GameState::Editor, run_if = resource_exists::<IsTimelineOpen>)]
+pub fn timeline_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let players: Vec<(ObjectId, String)> = world
+        .get_objects_with_component_with_ids::<SequencePlayer>()
+        .iter()
+        .map(|(id, object)| (*id, object.name.clone()))
+        .collect();
+
+    let mut editor = world.get_resource::<TimelineEditor>()?.clone();
+    if editor
+        .player
+        .is_none_or(|id| !players.iter().any(|(player, _)| *player == id))
+    {
+        editor.player = players.first().map(|(id, _)| *id);
+    }
+
+    let camera = world
+        .get_object_with_tag::<ActiveCamera>()
+        .ok()
+        .and_then(|camera| camera.get_component::<Transform>().ok().cloned());
+    let selected = world
+        .get_resource::<EditorSelection>()?
+        .selected
+        .and_then(|id| world.get_object(id))
+        .and_then(|object| {
+            let transform = object.get_component::<Transform>().ok()?.clone();
+            Some((object.name.clone(), transform))
+        });
+
+    // the combo box can change editor.player, the edits still belong to this player
+    let editing = editor.player;
+    let original = editing
+        .and_then(|id| world.get_object(id))
+        .and_then(|object| object.get_component::<SequencePlayer>().ok().cloned());
+    let mut player = original.clone();
+    let mut save = false;
+
+    let mut is_open = true;
+    egui::Window::new("Timeline")
+        .open(&mut is_open)
+        .default_width(520.0)
+        .show(&ctx, |ui| {
+            let Some(player) = &mut player else {
+                ui.label("No objects with a SequencePlayer");
+                return;
+            };
+
+            let selected_name = players
+                .iter()
+                .find(|(id, _)| Some(*id) == editor.player)
+                .map(|(_, name)| name.clone())
+                .unwrap_or_default();
+            egui::ComboBox::from_label("Player")
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    for (id, name) in &players {
+                        ui.selectable_value(&mut editor.player, Some(*id), name);
+                    }
+                });
+            ui.label(format!("Sequence: {}", player.sequence_path));
+
+            ui.horizontal(|ui| {
+                let label = if player.is_playing { "Pause" } else { "Play" };
+                if ui.button(label).clicked() {
+                    if player.is_playing {
+                        player.pause();
+                    } else {
+                        player.play();
+                    }
+                }
+                if ui.button("Stop").clicked() {
+                    player.stop();
+                }
+                ui.checkbox(&mut player.is_looping, "Loop");
+                ui.add(
+                    egui::DragValue::new(&mut player.speed)
+                        .prefix("Speed: ")
+                        .speed(0.05),
+                );
+            });
+
+            let mut time = player.time;
+            let sequence = Arc::make_mut(player.sequence.get_or_insert_with(Arc::default));
+            ui.add(
+                egui::DragValue::new(&mut sequence.duration)
+                    .prefix("Duration: ")
+                    .suffix("s")
+                    .range(0.0..=f32::MAX)
+                    .speed(0.1),
+            );
+            let duration = sequence.duration;
+            ui.add(egui::Slider::new(&mut time, 0.0..=duration).text("Time"));
+
+            ui.separator();
+            let camera_times: Vec<f32> = sequence.camera.iter().map(|k| k.time).collect();
+            if let Some(t) = track_row(ui, "Camera", &camera_times, time, duration) {
+                time = t;
+            }
+            for track in &sequence.objects {
+                let times: Vec<f32> = track.keys.iter().map(|k| k.time).collect();
+                if let Some(t) = track_row(ui, &track.object, &times, time, duration) {
+                    time = t;
+                }
+            }
+            let cue_times: Vec<f32> = sequence.cues.iter().map(|c| c.time).collect();
+            if let Some(t) = track_row(ui, "Cues", &cue_times, time, duration) {
+                time = t;
+            }
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(camera.is_some(), egui::Button::new("Key Camera"))
+                    .clicked()
+                    && let Some(camera) = &camera
+                {
+                    set_key(
+                        &mut sequence.camera,
+                        TransformKey::from_transform(time, camera),
+                    );
+                }
+                if ui
+                    .add_enabled(selected.is_some(), egui::Button::new("Key Selected"))
+                    .clicked()
+                    && let Some((name, transform)) = &selected
+                {
+                    let key = TransformKey::from_transform(time, transform);
+                    match sequence.objects.iter_mut().find(|t| &t.object == name) {
+                        Some(track) => set_key(&mut track.keys, key),
+                        None => sequence.objects.push(ObjectTrack {
+                            object: name.clone(),
+                            keys: vec![key],
+                        }),
+                    }
+                }
+                if ui.button("Delete Keys At Playhead").clicked() {
+                    let is_far = |t: f32| (t - time).abs() > KEY_SNAP;
+                    sequence.camera.retain(|k| is_far(k.time));
+                    for track in &mut sequence.objects {
+                        track.keys.retain(|k| is_far(k.time));
+                    }
+                    sequence.objects.retain(|t| !t.keys.is_empty());
+                    sequence.cues.retain(|c| is_far(c.time));
+                }
+            });
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("cue_kind")
+                    .selected_text(editor.new_cue_kind.label())
+                    .show_ui(ui, |ui| {
+                        for kind in CueKind::ALL {
+                            ui.selectable_value(&mut editor.new_cue_kind, kind, kind.label());
+                        }
+                    });
+                ui.text_edit_singleline(&mut editor.new_cue_name);
+                if ui
+                    .add_enabled(
+                        !editor.new_cue_name.is_empty(),
+                        egui::Button::new("Add Cue"),
+                    )
+                    .clicked()
+                {
+                    sequence.cues.push(SequenceCue {
+                        time,
+                        kind: editor.new_cue_kind,
+                        name: std::mem::take(&mut editor.new_cue_name),
+                    });
+                }
+            });
+
+            sequence.sort();
+            player.time = time;
+
+            ui.separator();
+            if ui.button("Save").clicked() {
+                save = true;
+            }
+        });
+
+    if !is_open {
+        world.remove_resource::<IsTimelineOpen>();
+    }
+
+    if let (Some(id), Some(player)) = (editing, player) {
+        if save && let Some(sequence) = &player.sequence {
+            match sequence.save(&player.sequence_path) {
+                Ok(()) => {
+                    log!("Saved sequence {}", player.sequence_path);
+                }
+                Err(e) => {
+                    log_error!("Failed to save sequence {}: {}", player.sequence_path, e);
+                }
+            }
+        }
+        if original.as_ref() != Some(&player)
+            && let Some(component) = world
+                .get_object_mut(id)
+                .and_then(|object| object.get_component_mut::<SequencePlayer>().ok())
+        {
+            *component = player;
+        }
+    }
+    *world.get_resource_mut::<TimelineEditor>()? = editor;
+
+    Ok(())
+}
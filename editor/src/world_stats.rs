@@ -0,0 +1,161 @@
+use apostasy_core::{
+    anyhow::Result,
+    egui,
+    objects::{resources::input_manager::InputManager, stats::WorldStats, world::World},
+    states::{GameState, resource_exists},
+    ui::ui_context::EguiContext,
+    update,
+};
+
+/// How many layouts the panel lists, the rest are summed into one row
+const MAX_LAYOUTS: usize = 20;
+
+/// Inserting `WorldStats` makes the engine refresh it each frame, so it doubles as the open flag
+#[update]
+pub fn toggle_world_stats(world: &mut World) -> Result<()> {
+    let inputs = world.get_resource::<InputManager>()?;
+
+    if inputs.is_keybind_active("ToggleWorldStats") {
+        if world.has_resource::<WorldStats>() {
+            world.remove_resource::<WorldStats>();
+        } else {
+            let stats = world.stats();
+            world.insert_resource(stats);
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the object, component, tag and layout counts of the world
+#[update(in_state = GameState::Editor, run_if = resource_exists::<WorldStats>)]
+pub fn world_stats_panel(world: &mut World) -> Result<()> {
+    let ctx = world.get_resource::<EguiContext>()?.0.clone();
+    let stats = world.get_resource::<WorldStats>()?.clone();
+
+    let mut is_open = true;
+    egui::Window::new("World Stats")
+        .open(&mut is_open)
+        .default_width(360.0)
+        .show(&ctx, |ui| {
+            ui.label(format!(
+                "{} objects, room for {}",
+                stats.object_count, stats.object_capacity
+            ));
+            ui.label(format!(
+                "{} chunks, {} resources, {} queued commands",
+                stats.chunk_count, stats.resource_count, stats.queued_commands
+            ));
+            ui.label(format!(
+                "{} component bytes in {} layouts",
+                stats.component_bytes(),
+                stats.layout_count()
+            ));
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::CollapsingHeader::new(format!("Components ({})", stats.components.len()))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::Grid::new("world_stats_components")
+                            .num_columns(4)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Component");
+                                ui.strong("Count");
+                                ui.strong("Size");
+                                ui.strong("Bytes");
+                                ui.end_row();
+                                for component in &stats.components {
+                                    ui.label(&component.name);
+                                    ui.label(component.count.to_string());
+                                    ui.label(component.size.to_string());
+                                    ui.label(component.bytes.to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                egui::CollapsingHeader::new(format!("Tags ({})", stats.tags.len())).show(
+                    ui,
+                    |ui| {
+                        egui::Grid::new("world_stats_tags")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for tag in &stats.tags {
+                                    ui.label(&tag.name);
+                                    ui.label(tag.count.to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    },
+                );
+
+                if !stats.pools.is_empty() {
+                    egui::CollapsingHeader::new(format!("Pools ({})", stats.pools.len()))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            egui::Grid::new("world_stats_pools")
+                                .num_columns(6)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.strong("Pool");
+                                    ui.strong("Active");
+                                    ui.strong("Free");
+                                    ui.strong("Spawned");
+                                    ui.strong("Reused");
+                                    ui.strong("Despawned");
+                                    ui.end_row();
+                                    for pool in &stats.pools {
+                                        ui.label(&pool.name);
+                                        ui.label(pool.active.to_string());
+                                        ui.label(pool.free.to_string());
+                                        ui.label(pool.spawned.to_string());
+                                        ui.label(pool.reused.to_string());
+                                        ui.label(pool.despawned.to_string());
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+
+                egui::CollapsingHeader::new(format!("Layouts ({})", stats.layout_count())).show(
+                    ui,
+                    |ui| {
+                        let total = stats.object_count.max(1) as f32;
+                        for layout in stats.layouts.iter().take(MAX_LAYOUTS) {
+                            let name = if layout.components.is_empty() {
+                                "(no components)".to_string()
+                            } else {
+                                layout.components.join(", ")
+                            };
+                            ui.add(
+                                egui::ProgressBar::new(layout.object_count as f32 / total)
+                                    .text(format!("{} x{}", name, layout.object_count)),
+                            );
+                        }
+                        let rest: usize = stats
+                            .layouts
+                            .iter()
+                            .skip(MAX_LAYOUTS)
+                            .map(|layout| layout.object_count)
+                            .sum();
+                        if rest > 0 {
+                            ui.label(format!(
+                                "{} more layouts with {} objects",
+                                stats.layout_count() - MAX_LAYOUTS,
+                                rest
+                            ));
+                        }
+                    },
+                );
+            });
+        });
+
+    if !is_open {
+        world.remove_resource::<WorldStats>();
+    }
+
+    Ok(())
+}
@@ -2,7 +2,11 @@ use apostasy_core::{
     Component,
     anyhow::Result,
     cgmath::{self, InnerSpace, Vector3, Zero},
-    objects::{Object, components::transform::Transform, world::World},
+    objects::{
+        Object,
+        components::{interpolation::InterpolatedTransform, transform::Transform},
+        world::World,
+    },
     physics::{Gravity, collider::Collider, velocity::Velocity},
     rand::{RngExt, rng},
     serde_yaml::Value,
@@ -112,6 +116,7 @@ pub fn entity_init(world: &mut World) -> Result<()> {
     let sheep = Object::new()
         .add_component(PassiveAI::default())
         .add_component(Transform::default())
+        .add_component(InterpolatedTransform::default())
         .add_component(Gravity::default())
         .add_component(Collider::default())
         .add_component(Velocity::default())
@@ -6,15 +6,20 @@ use apostasy_core::{
     items::{ItemRegistry, container::Container, voxel_component::Voxel},
     log_warn,
     objects::{
-        Object, components::transform::Transform, resources::input_manager::InputManager,
-        tags::Player, world::World,
+        Object,
+        components::{interpolation::InterpolatedTransform, transform::Transform},
+        resources::input_manager::InputManager,
+        tags::Player,
+        world::World,
     },
     physics::{Gravity, collider::Collider, velocity::Velocity},
     rendering::components::{
         camera::{ActiveCamera, Camera, GameCamera},
+        camera_controller::FpsCameraController,
         model_renderer::ModelRenderer,
     },
     serde_yaml, start,
+    states::GameState,
     ui::ui_context::EguiContext,
     update,
     voxels::{
@@ -67,6 +72,13 @@ pub fn player_init(world: &mut World) -> Result<()> {
             ..Default::default()
         })
         .add_component(Camera::default())
+        // the player moves in fixed update steps, the view is drawn between them
+        .add_component(InterpolatedTransform::default())
+        .add_component(FpsCameraController {
+            speed: 3.0,
+            yaw_parent: true,
+            ..Default::default()
+        })
         .add_tag(ActiveCamera)
         .add_tag(GameCamera);
     let player = Object::new()
@@ -98,26 +110,24 @@ pub fn player_init(world: &mut World) -> Result<()> {
     Ok(())
 }
 
-#[update(priority = 1)]
+#[update(priority = 1, in_state = GameState::Playing)]
 pub fn update(world: &mut World) -> Result<()> {
     if world.has_resource::<IsPaused>() && !world.has_resource::<HasInitGeneration>() {
         return Ok(());
     }
-    if world.get_resource::<IsPaused>().is_ok() {
-        let player = world.get_object_with_tag_mut::<Player>()?;
-        let velocity = player.get_component_mut::<Velocity>()?;
-        velocity.process = false;
-        return Ok(());
-    } else {
-        let player = world.get_object_with_tag_mut::<Player>()?;
-        let velocity = player.get_component_mut::<Velocity>()?;
-        velocity.process = true;
-    }
-
-    let player = world.get_object_with_tag::<Player>()?;
+    let is_paused = world.get_resource::<IsPaused>().is_ok();
+    let player = world.get_object_with_tag_mut::<Player>()?;
+    let velocity = player.get_component_mut::<Velocity>()?;
+    velocity.process = !is_paused;
 
     // Block movement if player is still loading
     let has_loading_gate = player.has_tag::<LoadingGate>();
+    let camera = world.get_object_with_tag_mut::<GameCamera>()?;
+    camera.get_component_mut::<FpsCameraController>()?.enabled = !is_paused && !has_loading_gate;
+    if is_paused {
+        return Ok(());
+    }
+
     if has_loading_gate {
         let player = world.get_object_with_tag_mut::<Player>()?;
         let velocity = player.get_component_mut::<Velocity>()?;
@@ -137,31 +147,13 @@ pub fn update(world: &mut World) -> Result<()> {
         return Ok(());
     }
 
-    let inputs = world.get_resource::<InputManager>()?;
-
-    let mouse_delta = inputs.mouse_delta;
-    let direction = inputs.input_vector_2d("Right", "Left", "Backwards", "Forwards");
-    let should_jump = inputs.is_keybind_active("Jump");
-
-    let player = world.get_object_with_tag_mut::<Player>()?;
-    let player_transform = player.get_component_mut::<Transform>()?;
-    player_transform.local_euler_angles.y -= mouse_delta.0 as f32;
-
-    let camera = world.get_object_with_tag_mut::<GameCamera>()?;
-    let cam_transform = camera.get_component_mut::<Transform>()?;
-    cam_transform.local_euler_angles.x -= mouse_delta.1 as f32;
-    cam_transform.local_euler_angles.x = cam_transform.local_euler_angles.x.clamp(-89.0, 89.0);
-
-    let player = world.get_object_with_tag::<Player>()?;
-    let rotation = player.get_component::<Transform>()?.global_rotation;
+    // looking and walking are done by the FpsCameraController on the camera
+    let should_jump = world
+        .get_resource::<InputManager>()?
+        .is_keybind_active("Jump");
 
     let player = world.get_object_with_tag_mut::<Player>()?;
     let velocity = player.get_component_mut::<Velocity>()?;
-
-    let wish_dir = rotation * Vector3::new(direction.x, 0.0, direction.y);
-    velocity.linear_velocity.x = wish_dir.x * 3.0;
-    velocity.linear_velocity.z = wish_dir.z * 3.0;
-
     if should_jump && velocity.is_grounded {
         velocity.linear_velocity.y = 5.0;
     }
@@ -22,7 +22,15 @@ pub mod states;
 pub mod ui;
 pub mod world;
 
+// `register_all`, everything the game registers, for `registry::install`
+#[cfg(feature = "explicit-registration")]
+apostasy_macros::register_all!();
+
 fn main() {
+    #[cfg(feature = "explicit-registration")]
+    apostasy_core::objects::registry::install(&[apostasy_core::register_all, register_all])
+        .unwrap();
+
     init_core(
         RenderingBackend::Vulkan,
         vec![Packages::Voxel, Packages::ItemSystem],
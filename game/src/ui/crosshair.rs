@@ -1,26 +1,16 @@
-use apostasy_core::{
-    anyhow::Result, egui, objects::world::World, ui::ui_context::EguiContext, update,
-};
+use apostasy_core::{anyhow::Result, egui, game_ui, objects::world::World};
 
 use crate::states::HasInitGeneration;
 
-#[update]
-pub fn hud(world: &mut World) -> Result<()> {
-    if !world.get_resource::<HasInitGeneration>().is_ok() {
-        return Ok(());
-    }
-
-    let ctx = world.get_resource::<EguiContext>()?.0.clone();
-
-    egui::Area::new(egui::Id::new("crosshair"))
-        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-        .show(&ctx, |ui| {
-            ui.label(
-                egui::RichText::new("+")
-                    .size(24.0)
-                    .color(egui::Color32::WHITE),
-            );
-        });
-
+#[game_ui(run_if = apostasy_core::states::resource_exists::<HasInitGeneration>)]
+pub fn hud(_world: &mut World, ui: &mut egui::Ui) -> Result<()> {
+    // painted rather than placed, so the crosshair never takes the pointer
+    ui.painter().text(
+        ui.ctx().content_rect().center(),
+        egui::Align2::CENTER_CENTER,
+        "+",
+        egui::FontId::proportional(24.0),
+        egui::Color32::WHITE,
+    );
     Ok(())
 }
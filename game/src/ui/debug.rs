@@ -6,7 +6,7 @@ use apostasy_core::{
     anyhow::Result,
     cgmath::{InnerSpace, Vector3},
     egui,
-    objects::{components::transform::Transform, systems::DeltaTime, tags::Player, world::World},
+    objects::{components::transform::Transform, systems::Time, tags::Player, world::World},
     physics::velocity::Velocity,
     rendering::shared::frustrum::ObjectsDrawing,
     start,
@@ -55,16 +55,13 @@ pub fn hud(world: &mut World) -> Result<()> {
     let linear_velocity = velocity.linear_velocity;
     let is_grounded = velocity.is_grounded;
 
-    let chunk_pos = Vector3::new(
-        (transform.x as i32) / 32,
-        (transform.y as i32) / 32,
-        (transform.z as i32) / 32,
-    );
-    let chunk_pos_display = Vector3::new(
-        (transform.x / 32.0).floor(),
-        (transform.y / 32.0).floor(),
-        (transform.z / 32.0).floor(),
-    );
+    // chunk positions are absolute, the transform is relative to the origin
+    let chunk_pos = (Vector3::new(
+        transform.x.floor() as i32,
+        transform.y.floor() as i32,
+        transform.z.floor() as i32,
+    ) + world.origin_offset())
+    .map(|v| v.div_euclid(32));
 
     let registry = world.get_resource::<BiomeRegistry>()?;
     let seed = world.get_resource::<ChunkLoader>()?.seed;
@@ -84,8 +81,7 @@ pub fn hud(world: &mut World) -> Result<()> {
         .map(|n| n.to_string())
         .unwrap_or_else(|| "None".to_string());
 
-    let dt = world.get_resource::<DeltaTime>()?;
-    let fps = 1.0 / dt.0;
+    let fps = 1.0 / world.get_resource::<Time>()?.unscaled_delta;
     if !(1.0..=5000.0).contains(&fps) || !fps.is_finite() {
         return Ok(());
     }
@@ -134,7 +130,7 @@ pub fn hud(world: &mut World) -> Result<()> {
                 linear_velocity.magnitude()
             ));
             ui.label(format!("Player grounded: {}", is_grounded));
-            ui.label(format!("Player chunk position: {:?}", chunk_pos_display));
+            ui.label(format!("Player chunk position: {:?}", chunk_pos));
         });
 
     Ok(())
@@ -4,7 +4,6 @@ use apostasy_core::{
     egui, log,
     objects::world::World,
     rand::{RngExt, rng},
-    states::ShouldExit,
     ui::ui_context::EguiContext,
     update,
 };
@@ -165,7 +164,7 @@ pub fn hud(world: &mut World) -> Result<()> {
                 ui.add_space(6.0);
                 if ui.button("Quit Game").clicked() {
                     log!("Exiting via main menu");
-                    world.insert_resource(ShouldExit);
+                    world.exit();
                 }
 
                 // version
@@ -88,11 +88,13 @@ pub fn dispatch_chunk_jobs(world: &mut World, _delta: f32) -> Result<()> {
     let player = world.get_object_with_tag::<Player>()?;
     let player_transform = player.get_component::<Transform>()?;
 
-    let player_chunk_pos = Vector3::new(
-        (player_transform.global_position.x / 32.0).floor() as i32,
-        (player_transform.global_position.y / 32.0).floor() as i32,
-        (player_transform.global_position.z / 32.0).floor() as i32,
-    );
+    // chunks are laid out in absolute space so they stay put when the origin moves
+    let player_voxel = Vector3::new(
+        player_transform.global_position.x.floor() as i32,
+        player_transform.global_position.y.floor() as i32,
+        player_transform.global_position.z.floor() as i32,
+    ) + world.origin_offset();
+    let player_chunk_pos = player_voxel.map(|v| v.div_euclid(32));
     let player_forward_chunk = player_transform.global_rotation * FORWARD;
 
     let (last_chunk_pos, load_radius, v_load_radius, lod_distances) = {
@@ -1,11 +1,86 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::parse::{Parse, ParseStream};
 use syn::{DeriveInput, ItemFn, LitInt, parse_macro_input, parse_quote};
 
-#[proc_macro_derive(Component, attributes(component_deserialize))]
+/// Derives Component and registers it
+/// NOTE: `#[component_serde]` serializes the component with serde, otherwise snapshots skip it and
+/// `deserialize(&mut self, value)` is called when it is loaded
+/// NOTE: `#[component_on_remove(func)]` calls `func(&mut World, ObjectId, &mut Self)` when the
+/// component is removed with `World::remove_component` or its object is removed
+/// NOTE: named fields are listed in the registration, `#[component_serde]` components can also
+/// have them read and set by name, see `World::get_component_field`
+/// NOTE: the `///` doc comments of the struct and its fields are kept in the registration, the
+/// editor shows them when hovering the component and its fields
+/// NOTE: `#[inspect(range = 0.0..=1.0)]`, `#[inspect(color)]`, `#[inspect(readonly)]`,
+/// `#[inspect(hidden)]` and `#[inspect(label = "Max Speed")]` on a field change how the editor
+/// inspector draws it, see `InspectOptions`
+/// NOTE: `#[component_version(2)]` is saved with the component, `#[component_migrate(func)]` calls
+/// `func(old_value: serde_yaml::Value, from_version: u32) -> Result<serde_yaml::Value>` to upgrade
+/// values saved at an older version before they are deserialized
+/// NOTE: `#[component_group("render")]` keeps the component next to the others of its group on
+/// every object, so queries over components iterated together find them in a short scan, see
+/// `Object::push_component`
+#[proc_macro_derive(
+    Component,
+    attributes(
+        component_deserialize,
+        component_serde,
+        component_on_remove,
+        component_version,
+        component_migrate,
+        component_group,
+        inspect
+    )
+)]
 pub fn component_derive(input: TokenStream) -> TokenStream {
     let mut ast = parse_macro_input!(input as DeriveInput);
+    let is_serde = ast
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("component_serde"));
+    let on_remove = match ast
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("component_on_remove"))
+        .map(|attr| attr.parse_args::<syn::Path>())
+        .transpose()
+    {
+        Ok(on_remove) => on_remove,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let version = match ast
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("component_version"))
+        .map(|attr| attr.parse_args::<LitInt>()?.base10_parse::<u32>())
+        .transpose()
+    {
+        Ok(version) => version.unwrap_or(0),
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let migrate = match ast
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("component_migrate"))
+        .map(|attr| attr.parse_args::<syn::Path>())
+        .transpose()
+    {
+        Ok(Some(func)) => quote! { Some(#func) },
+        Ok(None) => quote! { None },
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let group = match ast
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("component_group"))
+        .map(|attr| attr.parse_args::<syn::LitStr>())
+        .transpose()
+    {
+        Ok(Some(group)) => quote! { Some(#group) },
+        Ok(None) => quote! { None },
+        Err(e) => return e.to_compile_error().into(),
+    };
     ast.generics
         .make_where_clause()
         .predicates
@@ -15,41 +90,277 @@ pub fn component_derive(input: TokenStream) -> TokenStream {
     let struct_name_str = struct_name.to_string();
     let (impl_generics, type_generics, where_clause) = &ast.generics.split_for_impl();
 
+    let (serialize, deserialize) = if is_serde {
+        (
+            quote! {
+                Some(|component| {
+                    let c = component
+                        .as_any()
+                        .downcast_ref::<#struct_name>()
+                        .ok_or_else(|| apostasy_core::anyhow::anyhow!("Not a {}", #struct_name_str))?;
+                    Ok(apostasy_core::serde_yaml::to_value(c)?)
+                })
+            },
+            quote! {
+                |component, value| {
+                    if let Some(c) = component.as_any_mut().downcast_mut::<#struct_name>() {
+                        *c = apostasy_core::serde_yaml::from_value(value.clone())?;
+                    }
+                    Ok(())
+                }
+            },
+        )
+    } else {
+        (
+            quote! { None },
+            quote! {
+                |component, value| {
+                    if let Some(c) = component.as_any_mut().downcast_mut::<#struct_name>() {
+                        c.deserialize(value)
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+        )
+    };
+
+    // named fields serde doesn't skip, reachable by name with `get_field` and `set_field`
+    let fields: Vec<&syn::Field> = match &ast.data {
+        syn::Data::Struct(data) => data
+            .fields
+            .iter()
+            .filter(|field| field.ident.is_some() && !is_serde_skipped(field))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let field_idents: Vec<&syn::Ident> = fields.iter().filter_map(|f| f.ident.as_ref()).collect();
+    let field_names: Vec<String> = field_idents.iter().map(|ident| ident.to_string()).collect();
+    let field_types: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let ty = &field.ty;
+            quote!(#ty).to_string().replace(' ', "")
+        })
+        .collect();
+    let field_docs: Vec<String> = fields
+        .iter()
+        .map(|field| doc_lines(&field.attrs).join("\n"))
+        .collect();
+    let doc = doc_lines(&ast.attrs).join("\n");
+    let field_inspects = match fields
+        .iter()
+        .map(|field| inspect_options(field))
+        .collect::<syn::Result<Vec<_>>>()
+    {
+        Ok(inspects) => inspects,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let (get_field, set_field) = if is_serde && !fields.is_empty() {
+        (
+            quote! {
+                Some(|component, field| {
+                    let c = component
+                        .as_any()
+                        .downcast_ref::<#struct_name>()
+                        .ok_or_else(|| apostasy_core::anyhow::anyhow!("Not a {}", #struct_name_str))?;
+                    match field {
+                        #(#field_names => Ok(apostasy_core::serde_yaml::to_value(&c.#field_idents)?),)*
+                        _ => Err(apostasy_core::anyhow::anyhow!("{} has no field {}", #struct_name_str, field)),
+                    }
+                })
+            },
+            quote! {
+                Some(|component, field, value| {
+                    let c = component
+                        .as_any_mut()
+                        .downcast_mut::<#struct_name>()
+                        .ok_or_else(|| apostasy_core::anyhow::anyhow!("Not a {}", #struct_name_str))?;
+                    match field {
+                        #(#field_names => c.#field_idents = apostasy_core::serde_yaml::from_value(value.clone())?,)*
+                        _ => return Err(apostasy_core::anyhow::anyhow!("{} has no field {}", #struct_name_str, field)),
+                    }
+                    Ok(())
+                })
+            },
+        )
+    } else {
+        (quote! { None }, quote! { None })
+    };
+
+    let on_remove = match on_remove {
+        Some(func) => quote! {
+            Some(|world, id, component| {
+                if let Some(c) = component.as_any_mut().downcast_mut::<#struct_name>() {
+                    #func(world, id, c);
+                }
+            })
+        },
+        None => quote! { None },
+    };
+
+    let registration = registration(
+        "component",
+        struct_name,
+        quote! { apostasy_core::objects::component::ComponentRegistration },
+        quote! {
+            apostasy_core::objects::component::ComponentRegistration {
+                type_name: #struct_name_str,
+                module: module_path!(),
+                doc: #doc,
+                create: || Box::new(#struct_name::default()),
+                serialize: #serialize,
+                deserialize: #deserialize,
+                on_remove: #on_remove,
+                fields: &[#(apostasy_core::objects::component::FieldInfo {
+                    name: #field_names,
+                    type_name: #field_types,
+                    doc: #field_docs,
+                    inspect: #field_inspects,
+                }),*],
+                get_field: #get_field,
+                set_field: #set_field,
+                version: #version,
+                migrate: #migrate,
+                group: #group,
+            }
+        },
+    );
+
     let output = quote! {
         impl #impl_generics apostasy_core::objects::component::Component for #struct_name #type_generics
         #where_clause
         {
             fn name() -> &'static str where Self: Sized {
-                std::any::type_name::<#struct_name>()
+                std::any::type_name::<Self>()
             }
             fn as_any(&self) -> &dyn std::any::Any { self }
             fn as_any_mut(&mut self) -> &mut dyn std::any::Any { self }
             fn type_name(&self) -> &'static str {
                 std::any::type_name::<Self>()
             }
+            fn group(&self) -> Option<&'static str> {
+                #group
+            }
         }
 
-        inventory::submit! {
-            apostasy_core::objects::component::ComponentRegistration {
-                type_name: #struct_name_str,
-                create: || Box::new(#struct_name::default()),
-                deserialize: |component, value| {
-                    if let Some(c) = component.as_any_mut().downcast_mut::<#struct_name>() {
-                        c.deserialize(value)
-                    } else {
-                        Ok(())
-                    }
-                },
-            }
-        };
+        #registration
     };
 
     output.into()
 }
 
-#[proc_macro_derive(Resource)]
+/// The lines of the `///` doc comment of an item, trimmed
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }),
+                ..
+            }) => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Reads the `#[inspect(...)]` attributes of a field into an `InspectOptions`
+fn inspect_options(field: &syn::Field) -> syn::Result<proc_macro2::TokenStream> {
+    let mut label = quote! { None };
+    let mut range = quote! { None };
+    let mut color = false;
+    let mut readonly = false;
+    let mut hidden = false;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("inspect"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("label") {
+                let text: syn::LitStr = meta.value()?.parse()?;
+                label = quote! { Some(#text) };
+            } else if meta.path.is_ident("range") {
+                let expr: syn::ExprRange = meta.value()?.parse()?;
+                let (Some(start), Some(end), syn::RangeLimits::Closed(_)) =
+                    (&expr.start, &expr.end, &expr.limits)
+                else {
+                    return Err(syn::Error::new_spanned(
+                        &expr,
+                        "expected an inclusive range, e.g. `0.0..=1.0`",
+                    ));
+                };
+                range = quote! { Some(((#start) as f64, (#end) as f64)) };
+            } else if meta.path.is_ident("color") {
+                color = true;
+            } else if meta.path.is_ident("readonly") {
+                readonly = true;
+            } else if meta.path.is_ident("hidden") {
+                hidden = true;
+            } else {
+                return Err(meta.error(
+                    "expected `range`, `color`, `readonly`, `hidden` or `label` in `inspect`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(quote! {
+        apostasy_core::objects::component::InspectOptions {
+            label: #label,
+            range: #range,
+            color: #color,
+            readonly: #readonly,
+            hidden: #hidden,
+        }
+    })
+}
+
+/// Checks for `#[serde(skip)]` and the other skips, the field may not be serde then
+fn is_serde_skipped(field: &syn::Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .any(|attr| {
+            let mut skipped = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip")
+                    || meta.path.is_ident("skip_serializing")
+                    || meta.path.is_ident("skip_deserializing")
+                {
+                    skipped = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::Expr>()?;
+                }
+                Ok(())
+            });
+            skipped
+        })
+}
+
+/// Derives Resource
+/// NOTE: `#[resource_init]` inserts the resource with `FromWorld` when the world is made, and
+/// `#[resource_init(func)]` calls `func(&mut World)` instead
+/// NOTE: `#[resource_package(Voxel)]` only inits it when that package is added
+/// NOTE: `#[resource_shutdown(func)]` calls `func(&mut World)` when the engine exits, then removes
+/// the resource, skipped if it is not in the world
+#[proc_macro_derive(
+    Resource,
+    attributes(resource_init, resource_shutdown, resource_package)
+)]
 pub fn resource_derive(input: TokenStream) -> TokenStream {
     let mut ast = parse_macro_input!(input as DeriveInput);
+    let lifecycle = match resource_lifecycle(&ast) {
+        Ok(lifecycle) => lifecycle,
+        Err(e) => return e.to_compile_error().into(),
+    };
     ast.generics
         .make_where_clause()
         .predicates
@@ -65,7 +376,7 @@ pub fn resource_derive(input: TokenStream) -> TokenStream {
 
         {
             fn name() -> &'static str where Self: Sized {
-                std::any::type_name::<#struct_name>()
+                std::any::type_name::<Self>()
             }
             fn as_any(&self) -> &dyn std::any::Any {
                 self
@@ -77,6 +388,155 @@ pub fn resource_derive(input: TokenStream) -> TokenStream {
                 std::any::type_name::<Self>()
             }
         }
+
+        #lifecycle
+    };
+    output.into()
+}
+
+/// Registers the `#[resource_init]` and `#[resource_shutdown]` hooks of a resource, if it has any
+fn resource_lifecycle(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let find = |name: &str| ast.attrs.iter().find(|attr| attr.path().is_ident(name));
+    let init_attr = find("resource_init");
+    let shutdown_attr = find("resource_shutdown");
+    let package_attr = find("resource_package");
+    if init_attr.is_none() && shutdown_attr.is_none() {
+        if let Some(attr) = package_attr {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`resource_package` needs `resource_init` or `resource_shutdown`",
+            ));
+        }
+        return Ok(empty_registration("resource", &ast.ident));
+    }
+    if !ast.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.generics,
+            "generic resources can't have `resource_init` or `resource_shutdown`",
+        ));
+    }
+
+    let struct_name = &ast.ident;
+    let world = quote! { apostasy_core::objects::world::World };
+
+    let (init, init_fn) = match init_attr {
+        Some(attr) => {
+            let wrapper = format_ident!("__{}_resource_init", struct_name);
+            let body = match &attr.meta {
+                syn::Meta::Path(_) => quote! {
+                    let _ = world.init_resource::<#struct_name>();
+                },
+                _ => {
+                    let func: syn::Path = attr.parse_args()?;
+                    quote! { #func(world); }
+                }
+            };
+            (
+                quote! { Some(#wrapper) },
+                quote! {
+                    #[allow(non_snake_case)]
+                    fn #wrapper(world: &mut #world) {
+                        #body
+                    }
+                },
+            )
+        }
+        None => (quote! { None }, quote! {}),
+    };
+
+    let (shutdown, shutdown_fn) = match shutdown_attr {
+        Some(attr) => {
+            let func: syn::Path = attr.parse_args()?;
+            let wrapper = format_ident!("__{}_resource_shutdown", struct_name);
+            (
+                quote! { Some(#wrapper) },
+                quote! {
+                    #[allow(non_snake_case)]
+                    fn #wrapper(world: &mut #world) {
+                        if !world.has_resource::<#struct_name>() {
+                            return;
+                        }
+                        #func(world);
+                        world.remove_resource::<#struct_name>();
+                    }
+                },
+            )
+        }
+        None => (quote! { None }, quote! {}),
+    };
+
+    let package = match package_attr {
+        Some(attr) => {
+            let package: syn::Ident = attr.parse_args()?;
+            quote! { Some(apostasy_core::packages::Packages::#package) }
+        }
+        None => quote! { None },
+    };
+
+    let registration = registration(
+        "resource",
+        struct_name,
+        quote! { apostasy_core::objects::resource::ResourceLifecycle },
+        quote! {
+            apostasy_core::objects::resource::ResourceLifecycle {
+                type_name: stringify!(#struct_name),
+                package: #package,
+                init: #init,
+                shutdown: #shutdown,
+            }
+        },
+    );
+    Ok(quote! {
+        #init_fn
+        #shutdown_fn
+        #registration
+    })
+}
+
+/// Derives Bundle, every field is a component or another bundle, e.g.
+/// `#[derive(Bundle)] struct PlayerBundle { transform: Transform, physics: PhysicsBundle }`
+/// NOTE: spawn it with `World::spawn` or `spawn_batch` and add it with `World::insert_bundle`
+#[proc_macro_derive(Bundle)]
+pub fn bundle_derive(input: TokenStream) -> TokenStream {
+    let mut ast = parse_macro_input!(input as DeriveInput);
+    let syn::Data::Struct(data) = &ast.data else {
+        return syn::Error::new_spanned(&ast.ident, "only structs can derive Bundle")
+            .to_compile_error()
+            .into();
+    };
+    let types: Vec<&syn::Type> = data.fields.iter().map(|field| &field.ty).collect();
+    let members: Vec<syn::Member> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(index.into()),
+        })
+        .collect();
+
+    ast.generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! { Self: Send + 'static });
+    let struct_name = &ast.ident;
+    let (impl_generics, type_generics, where_clause) = &ast.generics.split_for_impl();
+
+    let output = quote! {
+        impl #impl_generics apostasy_core::objects::bundle::Bundle for #struct_name #type_generics
+            #where_clause
+        {
+            fn component_count() -> usize where Self: Sized {
+                0 #(+ <#types as apostasy_core::objects::bundle::Bundle>::component_count())*
+            }
+
+            fn push_components(
+                self,
+                components: &mut Vec<apostasy_core::objects::component::BoxedComponent>,
+            ) {
+                #(apostasy_core::objects::bundle::Bundle::push_components(self.#members, components);)*
+            }
+        }
     };
     output.into()
 }
@@ -99,7 +559,7 @@ pub fn tag_derive(input: TokenStream) -> TokenStream {
 
         {
             fn name() -> &'static str where Self: Sized {
-                std::any::type_name::<#struct_name>()
+                std::any::type_name::<Self>()
             }
             fn as_any(&self) -> &dyn std::any::Any {
                 self
@@ -116,129 +576,1055 @@ pub fn tag_derive(input: TokenStream) -> TokenStream {
             }
         }
     };
+
+    // only unit tags can be created from their name
+    let is_unit =
+        matches!(&ast.data, syn::Data::Struct(data) if matches!(data.fields, syn::Fields::Unit));
+    let registration = if is_unit && ast.generics.params.is_empty() {
+        let struct_name_str = struct_name.to_string();
+        registration(
+            "tag",
+            struct_name,
+            quote! { apostasy_core::objects::tag::TagRegistration },
+            quote! {
+                apostasy_core::objects::tag::TagRegistration {
+                    type_name: #struct_name_str,
+                    create: || Box::new(#struct_name),
+                }
+            },
+        )
+    } else {
+        empty_registration("tag", struct_name)
+    };
+
+    let output = quote! {
+        #output
+        #registration
+    };
     output.into()
 }
 
 // ========== ========== Systems ========== ==========
 
 struct SystemArgs {
+    /// the stage `#[system]` registers into, the other system attributes set it themselves
+    stage: Option<syn::Path>,
     priority: Option<u32>,
+    /// systems or sets the system runs before, `before` can be given more than once
+    before: Vec<String>,
+    /// systems or sets the system runs after, `after` can be given more than once
+    after: Vec<String>,
+    /// the set the system is in
+    set: Option<String>,
+    /// a `fn(&World) -> bool`, the system is skipped while it returns false
+    run_if: Option<syn::Expr>,
+    /// a state value, the system is skipped unless the state is this value
+    in_state: Option<syn::Expr>,
+    /// only run the system on the main thread, implied by `NonSend` parameters
+    main_thread: bool,
+    /// keep running the update or fixed update system while the game is paused
+    while_paused: bool,
 }
 
 /// Parser for the attribute arguments
 impl Parse for SystemArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if input.is_empty() {
-            return Ok(SystemArgs { priority: None });
+        let mut args = SystemArgs {
+            stage: None,
+            priority: None,
+            before: Vec::new(),
+            after: Vec::new(),
+            set: None,
+            run_if: None,
+            in_state: None,
+            main_thread: false,
+            while_paused: false,
+        };
+
+        while !input.is_empty() {
+            let name: syn::Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+            match name.to_string().as_str() {
+                "stage" => args.stage = Some(input.parse()?),
+                "priority" => {
+                    let priority_lit: LitInt = input.parse()?;
+                    args.priority = Some(priority_lit.base10_parse()?);
+                }
+                "before" => args.before.push(input.parse::<syn::LitStr>()?.value()),
+                "after" => args.after.push(input.parse::<syn::LitStr>()?.value()),
+                "set" => args.set = Some(input.parse::<syn::LitStr>()?.value()),
+                "run_if" => args.run_if = Some(input.parse()?),
+                "in_state" => args.in_state = Some(input.parse()?),
+                "main_thread" => {
+                    let main_thread: syn::LitBool = input.parse()?;
+                    args.main_thread = main_thread.value;
+                }
+                "while_paused" => {
+                    let while_paused: syn::LitBool = input.parse()?;
+                    args.while_paused = while_paused.value;
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        "expected `stage`, `priority`, `before`, `after`, `set`, `run_if`, \
+                         `in_state`, `main_thread` or `while_paused`",
+                    ));
+                }
+            }
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
         }
 
-        let name: syn::Ident = input.parse()?;
-        if name != "priority" {
-            return Err(syn::Error::new_spanned(name, "expected `priority`"));
+        Ok(args)
+    }
+}
+
+impl SystemArgs {
+    /// The `SystemOrder` of the system
+    fn order(&self) -> proc_macro2::TokenStream {
+        let before = &self.before;
+        let after = &self.after;
+        let set = match &self.set {
+            Some(set) => quote! { Some(#set) },
+            None => quote! { None },
+        };
+        quote! {
+            apostasy_core::objects::systems::SystemOrder {
+                before: &[#(#before),*],
+                after: &[#(#after),*],
+                set: #set,
+            }
         }
+    }
+}
 
-        input.parse::<syn::Token![=]>()?;
-        let priority_lit: LitInt = input.parse()?;
-        let priority: u32 = priority_lit.base10_parse()?;
+/// Wraps the function of a system in a check of its `run_if` and `in_state` conditions
+fn system_conditions(
+    args: &SystemArgs,
+    fn_name: &syn::Ident,
+    func: proc_macro2::TokenStream,
+    fixed: bool,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let mut conditions = Vec::new();
+    if let Some(run_if) = &args.run_if {
+        conditions.push(quote! { (#run_if)(&*world) });
+    }
+    if let Some(in_state) = &args.in_state {
+        conditions.push(quote! { world.in_state(&#in_state) });
+    }
+    if conditions.is_empty() {
+        return (func, quote! {});
+    }
 
-        Ok(SystemArgs {
-            priority: Some(priority),
-        })
+    let wrapper = format_ident!("__{}_condition", fn_name);
+    let (delta, pass_delta) = if fixed {
+        (quote! { , delta: f32 }, quote! { , delta })
+    } else {
+        (quote! {}, quote! {})
+    };
+    let wrapper_fn = quote! {
+        #[allow(non_snake_case)]
+        fn #wrapper(
+            world: &mut apostasy_core::objects::world::World #delta
+        ) -> apostasy_core::anyhow::Result<()> {
+            if !(#(#conditions)&&*) {
+                return Ok(());
+            }
+            #func(world #pass_delta)
+        }
+    };
+    (quote! { #wrapper }, wrapper_fn)
+}
+
+/// A parameter of a system function
+enum SystemParam {
+    World,
+    Res,
+    ResMut(Box<syn::Type>),
+    NonSend,
+    NonSendMut(Box<syn::Type>),
+    Query,
+    Timestep,
+}
+
+fn system_param(arg: &syn::FnArg, fixed: bool) -> syn::Result<SystemParam> {
+    let syn::FnArg::Typed(arg) = arg else {
+        return Err(syn::Error::new_spanned(arg, "systems can't take self"));
+    };
+    let unsupported = || {
+        let expected = if fixed {
+            "expected `&mut World`, `Res<T>`, `ResMut<T>`, `NonSend<T>`, `NonSendMut<T>`, \
+             `Query<D, F>` or an `f32` timestep"
+        } else {
+            "expected `&mut World`, `Res<T>`, `ResMut<T>`, `NonSend<T>`, `NonSendMut<T>` or \
+             `Query<D, F>`"
+        };
+        syn::Error::new_spanned(&arg.ty, expected)
+    };
+
+    if let syn::Type::Reference(reference) = arg.ty.as_ref()
+        && let syn::Type::Path(path) = reference.elem.as_ref()
+        && path
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "World")
+    {
+        return Ok(SystemParam::World);
+    }
+
+    let syn::Type::Path(path) = arg.ty.as_ref() else {
+        return Err(unsupported());
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Err(unsupported());
+    };
+    let resource_type = || {
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return Err(syn::Error::new_spanned(segment, "expected a resource type"));
+        };
+        args.args
+            .iter()
+            .find_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(Box::new(ty.clone())),
+                _ => None,
+            })
+            .ok_or_else(|| syn::Error::new_spanned(segment, "expected a resource type"))
+    };
+    match segment.ident.to_string().as_str() {
+        "Res" => Ok(SystemParam::Res),
+        "NonSend" => Ok(SystemParam::NonSend),
+        "Query" => Ok(SystemParam::Query),
+        "f32" if fixed => Ok(SystemParam::Timestep),
+        "ResMut" => Ok(SystemParam::ResMut(resource_type()?)),
+        "NonSendMut" => Ok(SystemParam::NonSendMut(resource_type()?)),
+        _ => Err(unsupported()),
     }
 }
 
-/// Registers a start system, Start systems run once at the start of the game
-/// NOTE: systems with a higher priority run first
-/// NOTE: priority is non negative
-#[proc_macro_attribute]
-pub fn start(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(attr as SystemArgs);
-    let input_fn = parse_macro_input!(item as ItemFn);
+/// The function to register for a system, the wrapper it may need and whether the system uses
+/// non send resources
+type SystemFunc = (proc_macro2::TokenStream, proc_macro2::TokenStream, bool);
+
+/// Gets the function to register for a system
+/// A system taking `&mut World` is registered as is, otherwise a wrapper is generated that takes
+/// its `ResMut` and `NonSendMut` resources out of the world, fetches its `Res`, `NonSend` and
+/// `Query` parameters and calls it
+fn system_func(input_fn: &ItemFn, fixed: bool) -> syn::Result<SystemFunc> {
     let fn_name = &input_fn.sig.ident;
+    let params = input_fn
+        .sig
+        .inputs
+        .iter()
+        .map(|arg| system_param(arg, fixed))
+        .collect::<syn::Result<Vec<_>>>()?;
 
-    let priority = args.priority.unwrap_or(0);
+    if params
+        .iter()
+        .any(|param| matches!(param, SystemParam::World))
+    {
+        if params.len() > 1 + fixed as usize {
+            return Err(syn::Error::new_spanned(
+                &input_fn.sig.inputs,
+                "a system taking `&mut World` can't take other parameters",
+            ));
+        }
+        return Ok((quote! { #fn_name }, quote! {}, false));
+    }
+    if params
+        .iter()
+        .filter(|param| matches!(param, SystemParam::Query))
+        .count()
+        > 1
+    {
+        return Err(syn::Error::new_spanned(
+            &input_fn.sig.inputs,
+            "a system can only have one Query parameter",
+        ));
+    }
 
-    let expanded = quote! {
-        #input_fn
-        inventory::submit! {
-            apostasy_core::objects::systems::StartSystem{
-                name: stringify!(#fn_name),
-                func: #fn_name,
-                priority: #priority,
+    let wrapper = format_ident!("__{}_system", fn_name);
+    let mut takes = Vec::new();
+    let mut unwraps = Vec::new();
+    let mut restores = Vec::new();
+    let mut args = Vec::new();
+    let mut uses_params = false;
+    let mut uses_non_send = false;
+    for (i, param) in params.iter().enumerate() {
+        match param {
+            SystemParam::World => unreachable!(),
+            SystemParam::Res => {
+                uses_params = true;
+                args.push(quote! { params.res()? });
+            }
+            SystemParam::NonSend => {
+                uses_params = true;
+                uses_non_send = true;
+                args.push(quote! { params.non_send()? });
+            }
+            SystemParam::Query => {
+                uses_params = true;
+                args.push(quote! { params.query()? });
+            }
+            SystemParam::Timestep => args.push(quote! { delta }),
+            SystemParam::ResMut(ty) => {
+                let taken = format_ident!("taken_{}", i);
+                takes.push(quote! {
+                    let mut #taken = world.take_system_resource::<#ty>(HELD_BY);
+                });
+                unwraps.push(quote! {
+                    let #taken = #taken.as_mut().map_err(|e| e.clone())?;
+                });
+                restores.push(quote! {
+                    if let Ok(taken) = #taken {
+                        world.restore_system_resource(taken);
+                    }
+                });
+                args.push(quote! {
+                    apostasy_core::objects::systems::params::ResMut::new(#taken.get_mut())
+                });
+            }
+            SystemParam::NonSendMut(ty) => {
+                uses_non_send = true;
+                let taken = format_ident!("taken_{}", i);
+                takes.push(quote! {
+                    let mut #taken = world.take_system_non_send::<#ty>(HELD_BY);
+                });
+                unwraps.push(quote! {
+                    let #taken = #taken.as_mut().map_err(|e| e.clone())?;
+                });
+                restores.push(quote! {
+                    if let Ok(taken) = #taken {
+                        world.restore_system_non_send(taken);
+                    }
+                });
+                args.push(quote! {
+                    apostasy_core::objects::systems::params::NonSendMut::new(#taken.get_mut())
+                });
             }
         }
+    }
+
+    let delta = if fixed {
+        quote! { , delta: f32 }
+    } else {
+        quote! {}
     };
-    TokenStream::from(expanded)
+    let params = if uses_params {
+        quote! { let mut params = world.system_params(); }
+    } else {
+        quote! {}
+    };
+
+    let wrapper_fn = quote! {
+        #[allow(non_snake_case)]
+        fn #wrapper(
+            world: &mut apostasy_core::objects::world::World #delta
+        ) -> apostasy_core::anyhow::Result<()> {
+            const HELD_BY: &str = concat!(env!("CARGO_PKG_NAME"), "::", stringify!(#fn_name));
+            #(#takes)*
+            let result = (|| -> apostasy_core::anyhow::Result<()> {
+                #(#unwraps)*
+                #params
+                #fn_name(#(#args),*)
+            })();
+            #(#restores)*
+            result
+        }
+    };
+    Ok((quote! { #wrapper }, wrapper_fn, uses_non_send))
 }
 
-/// Registers an update system, Update systems run each frame
-/// NOTE: systems with a higher priority run first
-/// NOTE: priority is non negative
-#[proc_macro_attribute]
-pub fn update(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(attr as SystemArgs);
-    let input_fn = parse_macro_input!(item as ItemFn);
+/// A stage systems can be registered into with `#[system(stage = ...)]`
+/// Note: a new stage is an entry in `STAGES` and a registration struct collected by core
+struct Stage {
+    name: &'static str,
+    /// the struct submitted to inventory
+    registration: &'static str,
+    /// the function also gets the `f32` timestep
+    fixed: bool,
+    /// the registration has a `while_paused` field
+    pausable: bool,
+    /// the function takes `(world: &mut World, ui: &mut egui::Ui)` and is ordered by priority only
+    ui: bool,
+}
+
+const STAGES: &[Stage] = &[
+    Stage {
+        name: "Start",
+        registration: "apostasy_core::objects::systems::StartSystem",
+        fixed: false,
+        pausable: false,
+        ui: false,
+    },
+    Stage {
+        name: "Update",
+        registration: "apostasy_core::objects::systems::UpdateSystem",
+        fixed: false,
+        pausable: true,
+        ui: false,
+    },
+    Stage {
+        name: "FixedUpdate",
+        registration: "apostasy_core::objects::systems::FixedUpdateSystem",
+        fixed: true,
+        pausable: true,
+        ui: false,
+    },
+    Stage {
+        name: "LateUpdate",
+        registration: "apostasy_core::objects::systems::LateUpdateSystem",
+        fixed: false,
+        pausable: false,
+        ui: false,
+    },
+    Stage {
+        name: "Shutdown",
+        registration: "apostasy_core::objects::systems::ShutdownSystem",
+        fixed: false,
+        pausable: false,
+        ui: false,
+    },
+    Stage {
+        name: "GameUi",
+        registration: "apostasy_core::ui::game_ui::GameUiSystem",
+        fixed: false,
+        pausable: true,
+        ui: true,
+    },
+];
+
+/// Finds a stage by name, `Update` and `SystemKind::Update` both name the update stage
+fn find_stage(path: &syn::Path) -> syn::Result<&'static Stage> {
+    let name = path
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default();
+    STAGES
+        .iter()
+        .find(|stage| stage.name == name)
+        .ok_or_else(|| {
+            let names = STAGES
+                .iter()
+                .map(|stage| stage.name)
+                .collect::<Vec<_>>()
+                .join(", ");
+            syn::Error::new_spanned(path, format!("unknown stage, expected one of {}", names))
+        })
+}
+
+/// Registers a system into a stage
+fn register_system(
+    stage: &Stage,
+    args: &SystemArgs,
+    input_fn: &ItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if stage.ui {
+        return register_game_ui(stage, args, input_fn);
+    }
     let fn_name = &input_fn.sig.ident;
+    let registration: syn::Path = syn::parse_str(stage.registration)?;
 
     let priority = args.priority.unwrap_or(0);
+    let order = args.order();
+    let (func, wrapper, uses_non_send) = system_func(input_fn, stage.fixed)?;
+    let main_thread = args.main_thread || uses_non_send;
+    let while_paused = if stage.pausable {
+        let while_paused = args.while_paused;
+        quote! { while_paused: #while_paused, }
+    } else {
+        quote! {}
+    };
+    let (func, condition) = system_conditions(args, fn_name, func, stage.fixed);
+    let registration = self::registration(
+        "system",
+        fn_name,
+        quote! { #registration },
+        quote! {
+            #registration {
+                name: stringify!(#fn_name),
+                func: #func,
+                priority: #priority,
+                order: #order,
+                package: env!("CARGO_PKG_NAME"),
+                main_thread: #main_thread,
+                #while_paused
+            }
+        },
+    );
 
-    let expanded = quote! {
+    Ok(quote! {
         #input_fn
-        inventory::submit! {
-            apostasy_core::objects::systems::UpdateSystem{
+        #wrapper
+        #condition
+        #registration
+    })
+}
+
+/// Registers a HUD function, they are drawn in priority order and can not be ordered otherwise
+fn register_game_ui(
+    stage: &Stage,
+    args: &SystemArgs,
+    input_fn: &ItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let fn_name = &input_fn.sig.ident;
+    let registration: syn::Path = syn::parse_str(stage.registration)?;
+
+    if !args.before.is_empty() || !args.after.is_empty() || args.set.is_some() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "game_ui functions are ordered by priority, `before`, `after` and `set` are not supported",
+        ));
+    }
+
+    let priority = args.priority.unwrap_or(0);
+    let while_paused = args.while_paused;
+
+    let mut conditions = Vec::new();
+    if let Some(run_if) = &args.run_if {
+        conditions.push(quote! { (#run_if)(&*world) });
+    }
+    if let Some(in_state) = &args.in_state {
+        conditions.push(quote! { world.in_state(&#in_state) });
+    }
+    let (func, condition) = if conditions.is_empty() {
+        (quote! { #fn_name }, quote! {})
+    } else {
+        let wrapper = format_ident!("__{}_condition", fn_name);
+        let wrapper_fn = quote! {
+            #[allow(non_snake_case)]
+            fn #wrapper(
+                world: &mut apostasy_core::objects::world::World,
+                ui: &mut apostasy_core::egui::Ui,
+            ) -> apostasy_core::anyhow::Result<()> {
+                if !(#(#conditions)&&*) {
+                    return Ok(());
+                }
+                #fn_name(world, ui)
+            }
+        };
+        (quote! { #wrapper }, wrapper_fn)
+    };
+
+    let registration = self::registration(
+        "system",
+        fn_name,
+        quote! { #registration },
+        quote! {
+            #registration {
                 name: stringify!(#fn_name),
-                func: #fn_name,
+                func: #func,
                 priority: #priority,
+                package: env!("CARGO_PKG_NAME"),
+                while_paused: #while_paused,
             }
-        }
-    };
-    TokenStream::from(expanded)
+        },
+    );
+
+    Ok(quote! {
+        #input_fn
+        #condition
+        #registration
+    })
 }
 
-/// Registers a fixed update system, Fixed update systems run x amount of times a second
+/// Registers a system into the stage an alias attribute like `#[update]` stands for
+fn stage_alias(stage: &str, attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as SystemArgs);
+    let input_fn = parse_macro_input!(item as ItemFn);
+    if let Some(path) = &args.stage {
+        return syn::Error::new_spanned(
+            path,
+            "the attribute already sets the stage, use `#[system(stage = ...)]` to pick one",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let stage = STAGES.iter().find(|s| s.name == stage).unwrap();
+    register_system(stage, &args, &input_fn)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Registers a system into a stage, e.g. `#[system(stage = FixedUpdate, priority = 2)]`
+/// Stages: `Start` runs once at the start of the game, `Update` each frame, `FixedUpdate` x
+/// amount of times a second, `LateUpdate` at the end of a frame, `Shutdown` once before the
+/// window closes and `GameUi` draws the HUD
 /// NOTE: systems with a higher priority run first
+/// NOTE: `before = "name"` and `after = "name"` order it against systems or sets of the same stage,
+/// `set = "name"` puts it in a set, they win over priority
+/// NOTE: `run_if = condition` and `in_state = State::Value` skip the system unless they hold
+/// NOTE: `main_thread = true` keeps the system on the main thread, as `NonSend` parameters do
+/// NOTE: `while_paused = true` keeps `Update`, `FixedUpdate` and `GameUi` systems running while
+/// `GameState::Paused` is active
 /// NOTE: priority is non negative
+/// NOTE: systems take either `&mut World` or any of `Res<T>`, `ResMut<T>`, `NonSend<T>`,
+/// `NonSendMut<T>` and one `Query<D, F>`, plus the `f32` timestep in `FixedUpdate`
+/// NOTE: `GameUi` functions take `(world: &mut World, ui: &mut egui::Ui)` and only use priority
 #[proc_macro_attribute]
-pub fn fixed_update(attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn system(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr as SystemArgs);
     let input_fn = parse_macro_input!(item as ItemFn);
+    let Some(path) = &args.stage else {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "expected `stage = ...`, e.g. `#[system(stage = Update)]`",
+        )
+        .to_compile_error()
+        .into();
+    };
+    find_stage(path)
+        .and_then(|stage| register_system(stage, &args, &input_fn))
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Registers a start system, Start systems run once at the start of the game
+/// NOTE: the same as `#[system(stage = Start)]`, see `system` for the arguments
+#[proc_macro_attribute]
+pub fn start(attr: TokenStream, item: TokenStream) -> TokenStream {
+    stage_alias("Start", attr, item)
+}
+
+/// Registers an update system, Update systems run each frame
+/// NOTE: the same as `#[system(stage = Update)]`, see `system` for the arguments
+#[proc_macro_attribute]
+pub fn update(attr: TokenStream, item: TokenStream) -> TokenStream {
+    stage_alias("Update", attr, item)
+}
+
+/// Registers a fixed update system, Fixed update systems run x amount of times a second
+/// NOTE: the same as `#[system(stage = FixedUpdate)]`, see `system` for the arguments
+#[proc_macro_attribute]
+pub fn fixed_update(attr: TokenStream, item: TokenStream) -> TokenStream {
+    stage_alias("FixedUpdate", attr, item)
+}
+
+/// Registers a late update system, Late update systems run at the end of a frame
+/// NOTE: the same as `#[system(stage = LateUpdate)]`, see `system` for the arguments
+#[proc_macro_attribute]
+pub fn late_update(attr: TokenStream, item: TokenStream) -> TokenStream {
+    stage_alias("LateUpdate", attr, item)
+}
+
+/// Registers a shutdown system, Shutdown systems run once when the engine exits, before resources
+/// are shut down and the window closes, for saving
+/// NOTE: the same as `#[system(stage = Shutdown)]`, see `system` for the arguments
+#[proc_macro_attribute]
+pub fn shutdown(attr: TokenStream, item: TokenStream) -> TokenStream {
+    stage_alias("Shutdown", attr, item)
+}
+
+/// Registers a HUD function, drawn each frame with the `HudStyle` in game builds and the editor
+/// NOTE: the same as `#[system(stage = GameUi)]`, see `system` for the arguments
+/// NOTE: functions with a higher priority are drawn first, so lower priorities end up on top
+#[proc_macro_attribute]
+pub fn game_ui(attr: TokenStream, item: TokenStream) -> TokenStream {
+    stage_alias("GameUi", attr, item)
+}
+
+// ========== ========== Console ========== ==========
+
+struct ConsoleCommandArgs {
+    name: Option<syn::LitStr>,
+    inputs: Option<syn::LitStr>,
+}
+
+/// Parser for the attribute arguments
+impl Parse for ConsoleCommandArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = ConsoleCommandArgs {
+            name: None,
+            inputs: None,
+        };
+
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+            let value: syn::LitStr = input.parse()?;
+            match key.to_string().as_str() {
+                "name" => args.name = Some(value),
+                "inputs" => args.inputs = Some(value),
+                _ => return Err(syn::Error::new_spanned(key, "expected `name` or `inputs`")),
+            }
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Checks if a console command takes the typed words itself, as `args: &[&str]`
+fn is_raw_command(input_fn: &ItemFn) -> bool {
+    let inputs: Vec<_> = input_fn.sig.inputs.iter().collect();
+    let [_, syn::FnArg::Typed(arg)] = inputs.as_slice() else {
+        return false;
+    };
+    match &*arg.ty {
+        syn::Type::Reference(reference) => matches!(&*reference.elem, syn::Type::Slice(_)),
+        _ => false,
+    }
+}
+
+/// Builds the wrapper that parses the typed arguments of a console command and the list of them
+fn typed_command(
+    input_fn: &ItemFn,
+    name: &str,
+    inputs: &str,
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
     let fn_name = &input_fn.sig.ident;
+    let wrapper = format_ident!("__{}_command", fn_name);
+    let args_list = format_ident!("__{}_command_args", fn_name);
 
-    let priority = args.priority.unwrap_or(0);
+    let mut infos = Vec::new();
+    let mut parses = Vec::new();
+    let mut values = Vec::new();
+    for (index, arg) in input_fn.sig.inputs.iter().enumerate().skip(1) {
+        let syn::FnArg::Typed(arg) = arg else {
+            return Err(syn::Error::new_spanned(
+                arg,
+                "console commands can not take self",
+            ));
+        };
+        let syn::Pat::Ident(pat) = &*arg.pat else {
+            return Err(syn::Error::new_spanned(
+                &arg.pat,
+                "console command arguments need a plain name, it is shown in the usage text",
+            ));
+        };
+        let ty = &arg.ty;
+        let arg_name = pat.ident.to_string().trim_start_matches('_').to_string();
+        let value = format_ident!("arg_{}", index);
+        infos.push(quote! {
+            apostasy_core::console::args::CommandArgInfo {
+                name: #arg_name,
+                type_name: <#ty as apostasy_core::console::args::CommandArg>::TYPE_NAME,
+                optional: <#ty as apostasy_core::console::args::CommandArg>::OPTIONAL,
+                completions: <#ty as apostasy_core::console::args::CommandArg>::completions,
+            }
+        });
+        let position = index - 1;
+        parses.push(quote! {
+            let #value = <#ty as apostasy_core::console::args::CommandArg>::from_args(args, #position)
+                .map_err(|e| usage(format!("{}: {}", #arg_name, e)))?;
+        });
+        values.push(value);
+    }
+    let count = values.len();
 
-    let expanded = quote! {
-        #input_fn
-        inventory::submit! {
-            apostasy_core::objects::systems::FixedUpdateSystem{
-                name: stringify!(#fn_name),
-                func: #fn_name,
-                priority: #priority,
+    let call = quote! { #fn_name(world, #(#values),*) };
+    let call = match &input_fn.sig.output {
+        syn::ReturnType::Default => quote! { #call; Ok(()) },
+        syn::ReturnType::Type(..) => call,
+    };
+
+    let wrapper_fn = quote! {
+        #[allow(non_upper_case_globals)]
+        const #args_list: &[apostasy_core::console::args::CommandArgInfo] = &[#(#infos),*];
+
+        #[allow(non_snake_case)]
+        fn #wrapper(
+            world: &mut apostasy_core::objects::world::World,
+            args: &[&str],
+        ) -> apostasy_core::anyhow::Result<()> {
+            let usage = |problem: String| {
+                apostasy_core::console::args::usage_error(#name, #inputs, #args_list, problem)
+            };
+            if args.len() > #count {
+                return Err(usage(format!("{} takes at most {} arguments", #name, #count)));
             }
+            #(#parses)*
+            #call
         }
     };
-    TokenStream::from(expanded)
+    Ok((wrapper_fn, quote! { #args_list }))
 }
 
-/// Registers a late update system, Late update systems run at the end of a frame
-/// NOTE: systems with a higher priority run first
-/// NOTE: priority is non negative
+/// Registers a console command, the function gets the world and either the words typed after the
+/// name as `&[&str]` or typed arguments, e.g. `fn teleport(world: &mut World, x: f32, y: f32)`
+/// `name` defaults to the function name, `inputs` describes the arguments in the help text
+/// The doc comment of the function is used as the description of the command
+/// NOTE: typed arguments implement `CommandArg`, `Option` ones can be left out, the usage text is
+/// built from them when `inputs` is not given and parse errors are printed with it
 #[proc_macro_attribute]
-pub fn late_update(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(attr as SystemArgs);
+pub fn console_command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as ConsoleCommandArgs);
     let input_fn = parse_macro_input!(item as ItemFn);
     let fn_name = &input_fn.sig.ident;
 
-    let priority = args.priority.unwrap_or(0);
+    let name = args
+        .name
+        .map(|name| name.value())
+        .unwrap_or_else(|| fn_name.to_string());
+    let inputs = args.inputs.map(|inputs| inputs.value()).unwrap_or_default();
+    let description = doc_lines(&input_fn.attrs).join(" ");
 
-    let expanded = quote! {
-        #input_fn
-        inventory::submit! {
-            apostasy_core::objects::systems::LateUpdateSystem{
-                name: stringify!(#fn_name),
-                func: #fn_name,
-                priority: #priority,
+    let (wrapper_fn, func, command_args) = if is_raw_command(&input_fn) {
+        (quote! {}, quote! { #fn_name }, quote! { &[] })
+    } else {
+        match typed_command(&input_fn, &name, &inputs) {
+            Ok((wrapper_fn, command_args)) => {
+                let wrapper = format_ident!("__{}_command", fn_name);
+                (wrapper_fn, quote! { #wrapper }, command_args)
             }
+            Err(e) => return e.to_compile_error().into(),
         }
     };
+
+    let registration = registration(
+        "command",
+        fn_name,
+        quote! { apostasy_core::console::ConsoleCommand },
+        quote! {
+            apostasy_core::console::ConsoleCommand {
+                name: #name,
+                inputs: #inputs,
+                description: #description,
+                func: #func,
+                args: #command_args,
+                package: env!("CARGO_PKG_NAME"),
+            }
+        },
+    );
+
+    let expanded = quote! {
+        #input_fn
+        #wrapper_fn
+        #registration
+    };
     TokenStream::from(expanded)
 }
+
+// ========== ========== Registration ========== ==========
+
+/// Submits a registration to inventory and emits `__register_<kind>_<name>`, which adds it to a
+/// `Registry` instead, `register_all!` calls these where link sections are not supported
+fn registration(
+    kind: &str,
+    name: &syn::Ident,
+    ty: proc_macro2::TokenStream,
+    value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let register = format_ident!("__register_{}_{}", kind, name);
+    quote! {
+        #[cfg(not(target_family = "wasm"))]
+        inventory::submit! { #value }
+
+        #[doc(hidden)]
+        #[allow(non_snake_case, dead_code)]
+        pub(crate) fn #register(registry: &mut apostasy_core::objects::registry::Registry) {
+            const REGISTRATION: #ty = #value;
+            registry.add(REGISTRATION);
+        }
+    }
+}
+
+/// The `__register_<kind>_<name>` of a derive with nothing to register, so `register_all!` can
+/// call one for every derive it finds
+fn empty_registration(kind: &str, name: &syn::Ident) -> proc_macro2::TokenStream {
+    let register = format_ident!("__register_{}_{}", kind, name);
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_snake_case, dead_code)]
+        pub(crate) fn #register(_registry: &mut apostasy_core::objects::registry::Registry) {}
+    }
+}
+
+/// Generates `pub fn register_all(registry: &mut Registry)`, which registers every component,
+/// tag, resource, system and console command of the crate, found by following its `mod`
+/// declarations from `src/lib.rs` or `src/main.rs`
+/// NOTE: for the `explicit-registration` feature, where inventory is not used, e.g. on wasm, pass
+/// the function to `apostasy_core::objects::registry::install`
+/// NOTE: registered items have to be reachable from the crate root, `#[cfg]` on a module applies
+/// to what is registered in it, on items it is ignored, and `#[cfg(test)]` modules are skipped
+#[proc_macro]
+pub fn register_all(input: TokenStream) -> TokenStream {
+    if !input.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "register_all!() takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let (calls, files) = match collect_registrations() {
+        Ok(found) => found,
+        Err(e) => {
+            return syn::Error::new(proc_macro2::Span::call_site(), e)
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    let output = quote! {
+        // the sources are included so editing any of them expands the macro again
+        const _: &[&str] = &[#(include_str!(#files)),*];
+
+        /// Registers everything this crate registers, see `apostasy_core::objects::registry`
+        pub fn register_all(registry: &mut apostasy_core::objects::registry::Registry) {
+            #(#calls)*
+        }
+    };
+    output.into()
+}
+
+/// Walks the modules of the crate being compiled, returns the `__register_*` functions in them
+/// and the files that were read
+fn collect_registrations() -> Result<(Vec<proc_macro2::TokenStream>, Vec<String>), String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?;
+    let src = std::path::Path::new(&manifest_dir).join("src");
+    let root = ["lib.rs", "main.rs"]
+        .iter()
+        .map(|file| src.join(file))
+        .find(|file| file.exists())
+        .ok_or_else(|| format!("{} has no lib.rs or main.rs", src.display()))?;
+
+    let mut calls = Vec::new();
+    let mut files = Vec::new();
+    scan_file(&root, &src, &[], &[], &mut calls, &mut files)?;
+    Ok((calls, files))
+}
+
+/// Scans a module file, `dir` is where the `mod` declarations in it are looked up
+fn scan_file(
+    file: &std::path::Path,
+    dir: &std::path::Path,
+    module: &[String],
+    cfgs: &[syn::Attribute],
+    calls: &mut Vec<proc_macro2::TokenStream>,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    let source = std::fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let parsed = syn::parse_file(&source)
+        .map_err(|e| format!("Failed to parse {}: {}", file.display(), e))?;
+    files.push(file.display().to_string());
+    scan_items(&parsed.items, dir, module, cfgs, calls, files)
+}
+
+fn scan_items(
+    items: &[syn::Item],
+    dir: &std::path::Path,
+    module: &[String],
+    cfgs: &[syn::Attribute],
+    calls: &mut Vec<proc_macro2::TokenStream>,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    for item in items {
+        match item {
+            syn::Item::Fn(item) => {
+                for attr in &item.attrs {
+                    let kind = match attr_name(attr).as_str() {
+                        "system" | "start" | "update" | "fixed_update" | "late_update"
+                        | "shutdown" | "game_ui" => "system",
+                        "console_command" => "command",
+                        _ => continue,
+                    };
+                    calls.push(register_call(module, cfgs, kind, &item.sig.ident)?);
+                }
+            }
+            syn::Item::Struct(syn::ItemStruct { attrs, ident, .. })
+            | syn::Item::Enum(syn::ItemEnum { attrs, ident, .. }) => {
+                for kind in derived_kinds(attrs) {
+                    calls.push(register_call(module, cfgs, kind, ident)?);
+                }
+            }
+            syn::Item::Mod(item) => {
+                if is_cfg_test(&item.attrs) {
+                    continue;
+                }
+                let name = item.ident.to_string();
+                let inner: Vec<String> = module.iter().cloned().chain([name.clone()]).collect();
+                let inner_dir = dir.join(&name);
+                let inner_cfgs: Vec<syn::Attribute> = cfgs
+                    .iter()
+                    .chain(item.attrs.iter().filter(|attr| attr.path().is_ident("cfg")))
+                    .cloned()
+                    .collect();
+                match &item.content {
+                    Some((_, items)) => {
+                        scan_items(items, &inner_dir, &inner, &inner_cfgs, calls, files)?
+                    }
+                    None => {
+                        let file = [dir.join(format!("{}.rs", name)), inner_dir.join("mod.rs")]
+                            .into_iter()
+                            .find(|file| file.exists())
+                            .ok_or_else(|| {
+                                format!("Module {} has no file in {}", name, dir.display())
+                            })?;
+                        scan_file(&file, &inner_dir, &inner, &inner_cfgs, calls, files)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The last segment of an attribute path, `apostasy_core::update` is `update`
+fn attr_name(attr: &syn::Attribute) -> String {
+    attr.path()
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default()
+}
+
+/// The kinds of registration the `#[derive(...)]` attributes of an item make
+fn derived_kinds(attrs: &[syn::Attribute]) -> Vec<&'static str> {
+    let mut kinds = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let Ok(derives) = attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+        ) else {
+            continue;
+        };
+        for derive in derives {
+            let name = derive
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string());
+            match name.as_deref() {
+                Some("Component") => kinds.push("component"),
+                Some("Tag") => kinds.push("tag"),
+                Some("Resource") => kinds.push("resource"),
+                _ => {}
+            }
+        }
+    }
+    kinds
+}
+
+fn is_cfg_test(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("cfg")
+            && attr
+                .parse_args::<syn::Ident>()
+                .is_ok_and(|ident| ident == "test")
+    })
+}
+
+/// `crate::<module>::__register_<kind>_<name>(registry);`, behind the `#[cfg]`s of its modules
+fn register_call(
+    module: &[String],
+    cfgs: &[syn::Attribute],
+    kind: &str,
+    name: &syn::Ident,
+) -> Result<proc_macro2::TokenStream, String> {
+    let path = std::iter::once("crate".to_string())
+        .chain(module.iter().cloned())
+        .chain([format!("__register_{}_{}", kind, name)])
+        .collect::<Vec<_>>()
+        .join("::");
+    let path: syn::Path =
+        syn::parse_str(&path).map_err(|e| format!("Invalid path {}: {}", path, e))?;
+    Ok(quote! {
+        #(#cfgs)*
+        #path(registry);
+    })
+}